@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use shamir_rust::crypto::ShamirSS;
+use shamir_rust::join::JoinOptions;
+
+// Times `join` with the per-byte Lagrange path and with barycentric weights,
+// for thresholds up to 255. Run with `cargo run --release --example
+// bench-interpolation [secret-bytes]`.
+
+fn main() {
+
+    let len:usize=std::env::args().nth(1).and_then(|v| v.parse().ok()).unwrap_or(1024);
+    let secret:Vec<u8>=(0..len).map(|i| i as u8).collect();
+
+    println!("{:>5} {:>14} {:>14} {:>8}", "k", "lagrange (ms)", "barycentric", "speedup");
+    for k in [2, 3, 5, 8, 16, 32, 64, 128, 255] {
+        let parts:BTreeMap<i32,Vec<u8>>=ShamirSS::split(255, k, secret.clone()).unwrap()
+            .into_iter().take(k as usize).collect();
+
+        let lagrange_only=JoinOptions { barycentric_cutoff: usize::MAX, ..Default::default() };
+        let lagrange=time(|| ShamirSS::join_with_options(parts.clone(), &lagrange_only).unwrap());
+        let barycentric=time(|| ShamirSS::join(parts.clone()).unwrap());
+        assert_eq!(ShamirSS::join(parts.clone()).unwrap(), secret);
+
+        println!("{:>5} {:>14.3} {:>14.3} {:>7.1}x", k, lagrange, barycentric, lagrange/barycentric);
+    }
+}
+
+fn time<F:Fn()->Vec<u8>>(f:F)->f64{
+    let runs=3;
+    let start=Instant::now();
+    for _ in 0..runs {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64()*1000.0/runs as f64
+}
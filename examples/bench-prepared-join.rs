@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use shamir_rust::crypto::ShamirSS;
+use shamir_rust::prepared::PreparedJoin;
+
+// Times what is left to do when the last share of a quorum arrives: a full
+// `join`, a `PreparedJoin::join` with the weights and tables computed ahead,
+// and the final `PendingJoin::add` + `finish` when the other shares were
+// folded in as they came. Run with `cargo run --release --example
+// bench-prepared-join [secret-bytes]`.
+
+const RUNS:u32=200;
+
+fn main() {
+
+    let len:usize=std::env::args().nth(1).and_then(|v| v.parse().ok()).unwrap_or(4096);
+    let secret:Vec<u8>=(0..len).map(|i| (i*7) as u8).collect();
+
+    println!("{:>5} {:>12} {:>12} {:>12}", "k", "join (us)", "prepared", "last share");
+    for k in [2, 3, 5, 16, 64] {
+        let parts:BTreeMap<i32,Vec<u8>>=ShamirSS::split(255, k, secret.clone()).unwrap()
+            .into_iter().take(k as usize).collect();
+        let pairs:Vec<(u8,&[u8])>=parts.iter().map(|(x, y)| (*x as u8, y.as_slice())).collect();
+        let xs:Vec<u8>=pairs.iter().map(|(x, _)| *x).collect();
+        let prepared=PreparedJoin::new(&xs).unwrap();
+        assert_eq!(prepared.join(&pairs).unwrap(), secret);
+
+        let join=time(|| ShamirSS::join(parts.clone()).unwrap());
+        let full=time(|| prepared.join(&pairs).unwrap());
+        let (&(last_x, last), earlier)=pairs.split_last().unwrap();
+        let mut last_share=0.0;
+        for _ in 0..RUNS {
+            let mut pending=prepared.start();
+            for &(x, y) in earlier {
+                pending.add(x, y).unwrap();
+            }
+            let start=Instant::now();
+            pending.add(last_x, last).unwrap();
+            let joined=std::hint::black_box(pending.finish().unwrap());
+            last_share+=start.elapsed().as_secs_f64();
+            assert_eq!(joined, secret);
+        }
+
+        println!("{:>5} {:>12.1} {:>12.1} {:>12.1}", k, join, full, last_share*1e6/RUNS as f64);
+    }
+}
+
+fn time<F:Fn()->Vec<u8>>(f:F)->f64{
+    let start=Instant::now();
+    for _ in 0..RUNS {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64()*1e6/RUNS as f64
+}
@@ -0,0 +1,44 @@
+use std::time::Instant;
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use shamir_rust::context::{Limits, ShamirContext};
+use shamir_rust::crypto::SMALL_SECRET_MAX;
+
+// Times split and join of short secrets with the stack-only path and with
+// the general one, and checks both give the same shares from the same seed.
+// Run with `cargo run --release --example bench-small-secrets`.
+
+const RUNS:u32=20_000;
+
+fn main() {
+
+    println!("{:>5} {:>3} {:>3} {:>12} {:>12} {:>12} {:>12}", "bytes", "k", "n", "split (us)", "fast", "join (us)", "fast");
+    for len in [16, SMALL_SECRET_MAX] {
+        for (k, n) in [(2, 3), (3, 5), (5, 8)] {
+            let secret:Vec<u8>=(0..len).map(|i| i as u8).collect();
+
+            let slow=ShamirContext::new(Limits { small_secret_limit: 0, ..Default::default() }, None);
+            let general=slow.split_with_rng(n, k, secret.clone(), &mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+            let split_general=time(|| slow.split(n, k, secret.clone()).unwrap().len());
+            let join_general=time(|| slow.join(general.clone()).unwrap().len());
+
+            let context=ShamirContext::default();
+            let fast=context.split_with_rng(n, k, secret.clone(), &mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+            assert_eq!(fast, general);
+            assert_eq!(context.join(fast.clone()).unwrap(), secret);
+            let split_fast=time(|| context.split(n, k, secret.clone()).unwrap().len());
+            let join_fast=time(|| context.join(fast.clone()).unwrap().len());
+
+            println!("{:>5} {:>3} {:>3} {:>12.2} {:>12.2} {:>12.2} {:>12.2}", len, k, n, split_general, split_fast, join_general, join_fast);
+        }
+    }
+}
+
+fn time<F:Fn()->usize>(f:F)->f64{
+    let start=Instant::now();
+    for _ in 0..RUNS {
+        std::hint::black_box(f());
+    }
+    start.elapsed().as_secs_f64()*1e6/RUNS as f64
+}
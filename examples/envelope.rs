@@ -0,0 +1,68 @@
+use std::collections::BTreeMap;
+
+use rand::RngCore;
+use shamir_rust::envelope::{KeyWrapper, ShamirKeyWrapper};
+use shamir_rust::hybrid::HybridSS;
+
+// A minimal envelope-encryption store written against `KeyWrapper` only, the
+// way a KMS-backed store would be. Any protector can be plugged in; here it
+// is a Shamir-split key-encryption key held by five custodians, three of
+// whom must come together before stored records can be read again.
+//
+//   cargo run --example envelope
+
+struct Envelope{
+    wrapped_dek:Vec<u8>,
+    ciphertext:Vec<u8>,
+}
+
+struct Store<'a>{
+    protector:&'a dyn KeyWrapper,
+    records:BTreeMap<String,Envelope>,
+}
+
+impl Store<'_>{
+
+    fn put(&mut self,name:&str,data:&[u8])->Result<(),String>{
+        let mut dek=[0u8;32];
+        rand::thread_rng().fill_bytes(&mut dek);
+        let envelope=Envelope {
+            wrapped_dek: self.protector.wrap_key(&dek, name.as_bytes())?,
+            ciphertext: HybridSS::encrypt(&dek, 1, data)?,
+        };
+        dek.fill(0);
+        self.records.insert(name.to_string(), envelope);
+        Ok(())
+    }
+
+    fn get(&self,name:&str)->Result<Vec<u8>,String>{
+        let envelope=self.records.get(name).ok_or(format!("No record {name}"))?;
+        let mut dek=self.protector.unwrap_key(&envelope.wrapped_dek, name.as_bytes())?;
+        let data=HybridSS::decrypt(&dek, &envelope.ciphertext);
+        dek.fill(0);
+        data
+    }
+}
+
+fn main() {
+
+    let (protector, shares)=ShamirKeyWrapper::generate(5, 3).unwrap();
+    let mut store=Store { protector: &protector, records: BTreeMap::new() };
+    store.put("db-password", b"correct horse battery staple").unwrap();
+    store.put("api-token", b"tok_live_0123456789").unwrap();
+    let records=store.records;
+    drop(protector);
+    println!("Stored {} records; key-encryption key split into {} shares", records.len(), shares.len());
+
+    // Later: three custodians rebuild the protector.
+    let quorum:BTreeMap<i32,Vec<u8>>=shares.into_iter().filter(|(x, _)| [1, 3, 5].contains(x)).collect();
+    let protector=ShamirKeyWrapper::from_shares(quorum).unwrap();
+    let store=Store { protector: &protector, records };
+    for name in ["db-password", "api-token"] {
+        println!("{name}: {}", String::from_utf8_lossy(&store.get(name).unwrap()));
+    }
+
+    // A wrapped key moved to another record name does not unwrap.
+    let moved=&store.records["api-token"].wrapped_dek;
+    assert!(protector.unwrap_key(moved, b"db-password").is_err());
+}
@@ -0,0 +1,21 @@
+use std::fs;
+
+use shamir_rust::json::JsonExt;
+use shamir_rust::schema::json_schema;
+use shamir_rust::transcript::Transcript;
+
+// Prints the JSON Schema for the crate's JSON documents, or with a
+// transcript path, that transcript as JSON to validate against it:
+// `cargo run --example json-schema [transcript.txt]`.
+
+fn main() {
+
+    match std::env::args().nth(1) {
+        Some(path) => {
+            let text=fs::read_to_string(&path).unwrap_or_else(|e| panic!("{path}: {e}"));
+            let transcript=Transcript::parse(&text).unwrap_or_else(|e| panic!("{path}: {e}"));
+            println!("{}", transcript.to_json().to_pretty());
+        }
+        None => println!("{}", json_schema().to_pretty()),
+    }
+}
@@ -0,0 +1,130 @@
+// Cluster root credential bootstrap (feature `k8s`).
+//
+// At setup, split the credential and emit one Secret per operator:
+//
+//   cargo run --example k8s-bootstrap --features k8s -- split root.key --threshold 2 \
+//       --operators ops,security,platform --namespace payments > shares.yaml
+//
+// A pod mounts Secrets of its own namespace only, so each operator's Secret
+// lives in the workload namespace but is owned and synced from that
+// operator's own namespace (its own controller, External Secrets, ...), with
+// RBAC that lets no single operator read the others'. The init container
+// joins whichever shares are mounted into a memory-backed emptyDir:
+//
+//   initContainers:
+//   - name: bootstrap
+//     image: registry.example/k8s-bootstrap
+//     args: [join, --mount, /shares/ops, --mount, /shares/security,
+//            --env, PLATFORM_SHARE, --group-read, --out, /run/root/root.key]
+//     env:
+//     - name: PLATFORM_SHARE
+//       valueFrom: {secretKeyRef: {name: root-share-platform, key: share}}
+//     volumeMounts:
+//     - {name: ops, mountPath: /shares/ops, readOnly: true}
+//     - {name: security, mountPath: /shares/security, readOnly: true}
+//     - {name: root, mountPath: /run/root}
+//   securityContext: {runAsUser: 1000, fsGroup: 1000}
+//   volumes:
+//   - {name: ops, secret: {secretName: root-share-ops, defaultMode: 0440}}
+//   - {name: security, secret: {secretName: root-share-security, defaultMode: 0440}}
+//   - {name: root, emptyDir: {medium: Memory}}
+//
+// --group-read accepts the 0440 + fsGroup a non-root pod needs; a pod
+// running as root can use defaultMode 0400 and leave it out.
+
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+use shamir_rust::crypto::{zeroize, ShamirSS};
+use shamir_rust::encoding::encode_share;
+use shamir_rust::k8s::{read_shares, ShareMounts};
+
+fn main(){
+    let args:Vec<String>=env::args().skip(1).collect();
+    let result=match args.first().map(|s| s.as_str()) {
+        Some("split") => split(&args[1..]),
+        Some("join") => join(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(e)=result {
+        eprintln!("{e}");
+        process::exit(1);
+    }
+}
+
+const USAGE:&str="usage: k8s-bootstrap split <credential> --threshold K --operators a,b,c [--namespace NS] [--name PREFIX]
+       k8s-bootstrap join (--mount DIR | --file FILE | --env VAR)... [--group-read] --out FILE";
+
+fn split(args:&[String])->Result<(),String>{
+    let mut input=None;
+    let mut threshold=None;
+    let mut operators=Vec::new();
+    let mut namespace="default".to_string();
+    let mut prefix="root-share".to_string();
+    let mut iter=args.iter();
+    while let Some(arg)=iter.next() {
+        match arg.as_str() {
+            "--threshold" => threshold=iter.next().and_then(|v| v.parse::<i32>().ok()),
+            "--operators" => operators=iter.next().map(|v| v.split(',').map(|s| s.trim().to_string()).collect()).unwrap_or_default(),
+            "--namespace" => namespace=iter.next().cloned().unwrap_or(namespace),
+            "--name" => prefix=iter.next().cloned().unwrap_or(prefix),
+            other => input=Some(other.to_string()),
+        }
+    }
+    let (Some(input), Some(threshold))=(input, threshold) else { return Err(USAGE.to_string()) };
+    if operators.is_empty() {
+        return Err(USAGE.to_string());
+    }
+
+    let secret=fs::read(&input).map_err(|e| format!("{input}: {e}"))?;
+    let parts=ShamirSS::split(operators.len() as i32, threshold, secret).map_err(|e| e.to_string())?;
+    for ((index, data), operator) in parts.iter().zip(&operators) {
+        // Quoted so YAML keeps the share text a string.
+        println!("---");
+        println!("apiVersion: v1");
+        println!("kind: Secret");
+        println!("metadata:");
+        println!("  name: {prefix}-{operator}");
+        println!("  namespace: {namespace}");
+        println!("  labels: {{app.kubernetes.io/part-of: {prefix}, shamir.example/operator: {operator}, shamir.example/index: \"{index}\"}}");
+        println!("type: Opaque");
+        println!("stringData:");
+        println!("  share: \"{}\"", encode_share(*index, data).trim());
+    }
+    eprintln!("{} shares, any {threshold} recover {input}; hand each Secret to its operator only", parts.len());
+    Ok(())
+}
+
+fn join(args:&[String])->Result<(),String>{
+    let mut mounts=ShareMounts::default();
+    let mut out=None;
+    let mut iter=args.iter();
+    while let Some(arg)=iter.next() {
+        let mut value=|| iter.next().cloned().ok_or(USAGE.to_string());
+        match arg.as_str() {
+            "--mount" => mounts.dirs.push(PathBuf::from(value()?)),
+            "--file" => mounts.files.push(PathBuf::from(value()?)),
+            "--env" => mounts.env.push(value()?),
+            "--group-read" => mounts.allow_group_read=true,
+            "--out" => out=Some(PathBuf::from(value()?)),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    let Some(out)=out else { return Err(USAGE.to_string()) };
+
+    let parts=read_shares(&mounts)?;
+    let shares=parts.len();
+    let mut secret=ShamirSS::join(parts).map_err(|e| e.to_string())?;
+    let mut options=OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o400);
+    let written=options.open(&out).and_then(|mut file| file.write_all(&secret));
+    zeroize(&mut secret);
+    written.map_err(|e| format!("{}: {e}", out.display()))?;
+    eprintln!("Joined {shares} shares into {}", out.display());
+    Ok(())
+}
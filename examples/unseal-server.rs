@@ -0,0 +1,174 @@
+// Vault-style unseal flow over plain HTTP (std only, one connection at a time).
+//
+//   cargo run --example unseal-server -- <payload.sealed> --threshold K [--listen ADDR]
+//
+// The sealed payload is any hybrid-mode payload, e.g. `payload.sealed` from a
+// `shamir split-dir` bundle. Custodians submit their key share one at a time:
+//
+//   curl -X PUT --data-binary @custodian-1/key.share http://127.0.0.1:8200/unseal
+//
+// Once the threshold is met the data key is reconstructed, the payload is
+// decrypted into memory and every submitted share is wiped. `GET /status`
+// reports progress; `PUT /seal` discards the unsealed payload again.
+
+use std::env;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process;
+
+use shamir_rust::encoding::decode_share;
+use shamir_rust::hybrid::HybridSS;
+use shamir_rust::session::QuorumSession;
+use shamir_rust::sha256::{to_hex, Sha256};
+
+struct Server{
+    sealed:Vec<u8>,
+    threshold:i32,
+    session:QuorumSession,
+    unsealed:Option<Vec<u8>>,
+}
+
+impl Server{
+
+    fn handle(&mut self,method:&str,path:&str,body:&[u8])->(u16,String){
+        match (method, path) {
+            ("GET", "/status") => (200, self.status()),
+            ("PUT", "/unseal") => self.unseal(body),
+            ("PUT", "/seal") => {
+                self.seal();
+                (200, self.status())
+            }
+            _ => (404, "{\"error\":\"not found\"}".to_string()),
+        }
+    }
+
+    fn unseal(&mut self,body:&[u8])->(u16,String){
+        if self.unsealed.is_some() {
+            return (200, self.status());
+        }
+        let share=String::from_utf8_lossy(body);
+        let (index, data)=match decode_share(&share) {
+            Ok(share) => share,
+            Err(msg) => return (400, error(&msg)),
+        };
+        let progress=match self.session.add(index, data) {
+            Ok(progress) => progress,
+            Err(err) => return (400, error(&err.to_string())),
+        };
+        if progress.is_complete() {
+            let result=self.session.finalize()
+                .map_err(|e| e.to_string())
+                .and_then(|mut key| {
+                    let payload=HybridSS::decrypt(&key, &self.sealed);
+                    key.fill(0);
+                    payload
+                });
+            match result {
+                Ok(payload) => self.unsealed=Some(payload),
+                Err(msg) => {
+                    self.reset();
+                    return (400, error(&format!("{msg}; progress reset")));
+                }
+            }
+        }
+        (200, self.status())
+    }
+
+    fn seal(&mut self){
+        if let Some(mut payload)=self.unsealed.take() {
+            payload.fill(0);
+        }
+        self.reset();
+    }
+
+    fn reset(&mut self){
+        self.session=QuorumSession::new(self.threshold).expect("threshold validated at startup");
+    }
+
+    fn status(&self)->String{
+        let progress=self.session.progress();
+        match &self.unsealed {
+            Some(payload) => format!(
+                "{{\"sealed\":false,\"threshold\":{},\"payload_sha256\":\"{}\"}}",
+                progress.threshold, to_hex(&Sha256::digest(payload))),
+            None => format!(
+                "{{\"sealed\":true,\"threshold\":{},\"progress\":{},\"indices\":{:?}}}",
+                progress.threshold, progress.collected, self.session.indices()),
+        }
+    }
+}
+
+fn error(msg:&str)->String{
+    format!("{{\"error\":\"{}\"}}", msg.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn serve(server:&mut Server,stream:TcpStream)->std::io::Result<()>{
+    let mut reader=BufReader::new(stream.try_clone()?);
+    let mut request_line=String::new();
+    reader.read_line(&mut request_line)?;
+    let mut fields=request_line.split_whitespace();
+    let method=fields.next().unwrap_or("").to_string();
+    let path=fields.next().unwrap_or("").to_string();
+
+    let mut content_length=0usize;
+    loop {
+        let mut header=String::new();
+        if reader.read_line(&mut header)?==0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value))=header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length=value.trim().parse().unwrap_or(0);
+            }
+        }
+    }
+    let mut body=vec![0u8;content_length.min(64*1024)];
+    reader.read_exact(&mut body)?;
+
+    let (status, json)=server.handle(&method, &path, &body);
+    body.fill(0);
+    let reason=match status { 200 => "OK", 400 => "Bad Request", _ => "Not Found" };
+    let mut stream=stream;
+    write!(stream, "HTTP/1.1 {status} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{json}", json.len())
+}
+
+fn main(){
+    let args:Vec<String>=env::args().skip(1).collect();
+    let mut sealed_path=None;
+    let mut threshold=None;
+    let mut listen="127.0.0.1:8200".to_string();
+    let mut iter=args.iter();
+    while let Some(arg)=iter.next() {
+        match arg.as_str() {
+            "--threshold" => threshold=iter.next().and_then(|v| v.parse::<i32>().ok()),
+            "--listen" => listen=iter.next().cloned().unwrap_or(listen),
+            other => sealed_path=Some(other.to_string()),
+        }
+    }
+    let (Some(sealed_path), Some(threshold))=(sealed_path, threshold) else {
+        eprintln!("usage: unseal-server <payload.sealed> --threshold K [--listen ADDR]");
+        process::exit(2);
+    };
+
+    let sealed=fs::read(&sealed_path).unwrap_or_else(|e| {
+        eprintln!("{sealed_path}: {e}");
+        process::exit(1);
+    });
+    let session=QuorumSession::new(threshold).unwrap_or_else(|e| {
+        eprintln!("{e}");
+        process::exit(2);
+    });
+    let mut server=Server { sealed, threshold, session, unsealed: None };
+
+    let listener=TcpListener::bind(&listen).unwrap_or_else(|e| {
+        eprintln!("{listen}: {e}");
+        process::exit(1);
+    });
+    println!("Listening on {listen}, sealed (threshold {threshold})");
+    for stream in listener.incoming().flatten() {
+        if let Err(e)=serve(&mut server, stream) {
+            eprintln!("connection error: {e}");
+        }
+    }
+}
@@ -0,0 +1,3 @@
+// Re-exports the `shamir_*` extern functions so they are linked into the C
+// libraries; see src/ffi.rs in the main crate.
+pub use shamir_rust::ffi::*;
@@ -0,0 +1,45 @@
+/* C interface of shamir-rust, built by the ffi/ crate as libshamir.so and
+ * libshamir.a (`cargo build -p shamir-ffi --release`).
+ *
+ * Every function returns 0 on success or a stable error code (see
+ * ShamirError::code in src/error.rs); the message of the last failure on
+ * the calling thread is available from shamir_last_error_message.
+ *
+ * A share of an L-byte secret is a record of 1 + L bytes: its index
+ * (x-coordinate, 1..255), then its payload. */
+
+#ifndef SHAMIR_H
+#define SHAMIR_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Splits secret[0..secret_len] into n records with threshold k, written
+ * back to back to shares, which must hold exactly n * (1 + secret_len)
+ * bytes. */
+int32_t shamir_split(int32_t n, int32_t k, const uint8_t *secret, size_t secret_len,
+                     uint8_t *shares, size_t shares_len);
+
+/* Joins count records of share_len bytes each into secret, which must hold
+ * share_len - 1 bytes. */
+int32_t shamir_join(const uint8_t *shares, size_t count, size_t share_len,
+                    uint8_t *secret, size_t secret_len);
+
+/* Code of this thread's last error, 0 if none. */
+int32_t shamir_last_error_code(void);
+
+/* NUL-terminated UTF-8 message of this thread's last error, or NULL. Valid
+ * until the next failing call on this thread or shamir_clear_last_error. */
+const char *shamir_last_error_message(void);
+
+void shamir_clear_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
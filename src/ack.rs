@@ -0,0 +1,166 @@
+use rand::RngCore;
+
+use crate::crypto::{constant_time_eq, zeroize};
+use crate::encoding::encode_share;
+use crate::sha256::{from_hex, to_hex, Sha256};
+use crate::transcript::{CustodianRecord, Transcript};
+
+// Delivery acknowledgments. At split time the dealer gives each share a
+// random challenge, written into the share bundle as `ack-challenge <hex>`.
+// The custodian's tool answers with
+//
+//   shamir-ack v1 <index> <challenge> <response>
+//
+// where response = HMAC-SHA256(key, challenge) truncated to 16 bytes and key
+// = HKDF-SHA256(salt = challenge, ikm = the share's text form, info =
+// "shamir-ack v1"). Only someone who can read the share can answer, and the
+// answer says nothing about the share's bytes.
+//
+// The transcript keeps the challenge and a digest of the expected response,
+// not the response itself, so a copy of the transcript cannot be used to
+// forge an acknowledgment. A matching response records the date it was
+// received on the custodian's line.
+
+pub const ACK_CHALLENGE_LEN:usize=16;
+const RESPONSE_LEN:usize=16;
+const DIGEST_LEN:usize=16;
+const LINE_PREFIX:&str="ack-challenge ";
+const RESPONSE_HEADER:&str="shamir-ack v1";
+const KEY_INFO:&[u8]=b"shamir-ack v1";
+
+pub fn new_ack_challenge()->String{
+    let mut challenge=[0u8;ACK_CHALLENGE_LEN];
+    rand::thread_rng().fill_bytes(&mut challenge);
+    to_hex(&challenge)
+}
+
+pub fn challenge_line(challenge:&str)->String{
+    format!("{LINE_PREFIX}{challenge}\n")
+}
+
+// The challenge in a bundle's `ack-challenge` line, if it has one.
+pub fn read_challenge(bundle:&str)->Option<&str>{
+    bundle.lines().find_map(|line| line.trim().strip_prefix(LINE_PREFIX)).map(str::trim)
+}
+
+// The custodian's answer to `challenge` for share (index, data).
+pub fn ack_response(challenge:&str,index:i32,data:&[u8])->Result<String,String>{
+    let challenge=from_hex(challenge).filter(|c| c.len()==ACK_CHALLENGE_LEN).ok_or("Malformed acknowledgment challenge")?;
+    let mut share=encode_share(index, data).into_bytes();
+    let mut key:[u8;32]=Sha256::hkdf_key(&challenge, &share, KEY_INFO);
+    let response=to_hex(&Sha256::hmac(&key, &challenge)[..RESPONSE_LEN]);
+    zeroize(&mut share);
+    zeroize(&mut key);
+    Ok(response)
+}
+
+// What the transcript keeps to check a response against.
+pub fn response_digest(response:&str)->String{
+    to_hex(&Sha256::digest(response.trim().to_ascii_lowercase().as_bytes())[..DIGEST_LEN])
+}
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct Acknowledgment{
+    pub index:i32,
+    pub challenge:String,
+    pub response:String,
+}
+
+impl Acknowledgment{
+
+    pub fn new(challenge:&str,index:i32,data:&[u8])->Result<Acknowledgment,String>{
+        Ok(Acknowledgment { index, challenge: challenge.to_string(), response: ack_response(challenge, index, data)? })
+    }
+
+    pub fn to_text(&self)->String{
+        format!("{RESPONSE_HEADER} {} {} {}\n", self.index, self.challenge, self.response)
+    }
+
+    pub fn parse(text:&str)->Result<Acknowledgment,String>{
+        let line=text.trim();
+        let rest=line.strip_prefix(RESPONSE_HEADER).ok_or("Not a share acknowledgment")?;
+        let fields:Vec<&str>=rest.split_whitespace().collect();
+        let [index, challenge, response]=fields[..] else {
+            return Err("Malformed share acknowledgment".to_string());
+        };
+        Ok(Acknowledgment {
+            index: index.parse().map_err(|_| format!("Malformed share index {index} in acknowledgment"))?,
+            challenge: challenge.to_ascii_lowercase(),
+            response: response.to_ascii_lowercase(),
+        })
+    }
+}
+
+// Checks `ack` against the transcript and stamps the custodian it came from
+// with `date`. A second, matching acknowledgment keeps the first date.
+pub fn record<'a>(transcript:&'a mut Transcript,ack:&Acknowledgment,date:&str)->Result<&'a CustodianRecord,String>{
+    let custodian=transcript.custodians.iter_mut()
+        .find(|c| c.index==ack.index && c.ack_challenge.as_deref()==Some(ack.challenge.as_str()))
+        .ok_or(format!("No custodian with share {} was given challenge {}", ack.index, ack.challenge))?;
+    let expected=custodian.ack_digest.as_deref().ok_or(format!("The transcript has no expected response for share {}", ack.index))?;
+    if !constant_time_eq(response_digest(&ack.response).as_bytes(), expected.as_bytes()) {
+        return Err(format!("The response for share {} does not match: wrong share or altered acknowledgment", ack.index));
+    }
+    if custodian.acknowledged.is_none() {
+        custodian.acknowledged=Some(date.to_string());
+    }
+    Ok(custodian)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::ShamirSS;
+
+    // A transcript as the dealer writes it: one challenge per share and the
+    // digest of the response it expects.
+    fn dealt()->(Transcript,Vec<(i32,Vec<u8>,String)>){
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let mut transcript=Transcript { threshold: 2, shares: 3, ..Transcript::default() };
+        let mut bundles=Vec::new();
+        for (index, data) in parts {
+            let challenge=new_ack_challenge();
+            let bundle=format!("{}{}", encode_share(index, &data), challenge_line(&challenge));
+            transcript.custodians.push(CustodianRecord {
+                index,
+                ack_challenge: Some(challenge.clone()),
+                ack_digest: Some(response_digest(&ack_response(&challenge, index, &data).unwrap())),
+                ..CustodianRecord::default()
+            });
+            bundles.push((index, data, bundle));
+        }
+        (transcript, bundles)
+    }
+
+    #[test]
+    fn response_is_recorded_in_the_transcript() {
+        let (mut transcript, bundles)=dealt();
+        let (index, data, bundle)=&bundles[1];
+        let challenge=read_challenge(bundle).unwrap();
+        let sent=Acknowledgment::new(challenge, *index, data).unwrap();
+        // Hex in either case is accepted.
+        let ack=Acknowledgment::parse(&sent.to_text().replace(&sent.response, &sent.response.to_ascii_uppercase())).unwrap();
+        assert_eq!(ack, sent);
+        assert_eq!(record(&mut transcript, &ack, "2026-10-16").unwrap().index, *index);
+        record(&mut transcript, &ack, "2026-10-17").unwrap();
+        assert_eq!(transcript.custodians[1].acknowledged.as_deref(), Some("2026-10-16"));
+        assert_eq!(transcript.custodians[0].acknowledged, None);
+    }
+
+    #[test]
+    fn response_from_the_wrong_share_is_rejected() {
+        let (mut transcript, bundles)=dealt();
+        let challenge=read_challenge(&bundles[0].2).unwrap().to_string();
+        let (other_index, other_data, _)=&bundles[1];
+
+        // Another custodian's share answering this custodian's challenge.
+        let mut ack=Acknowledgment::new(&challenge, *other_index, other_data).unwrap();
+        assert!(record(&mut transcript, &ack, "2026-10-16").unwrap_err().contains("No custodian"));
+        ack.index=bundles[0].0;
+        assert!(record(&mut transcript, &ack, "2026-10-16").unwrap_err().contains("does not match"));
+        assert!(transcript.custodians.iter().all(|c| c.acknowledged.is_none()));
+
+        assert!(ack_response("not hex", 1, b"share").is_err());
+        assert!(Acknowledgment::parse("shamir-ack v1 1 2").is_err());
+    }
+}
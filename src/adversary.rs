@@ -0,0 +1,304 @@
+use rand::Rng;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{Parts, ShamirSS, Share, GFC256};
+use crate::error::ShamirError;
+
+// Adversarial actors for test suites (feature `test-util`): a dealer that
+// deals inconsistent shares, a shareholder that submits a modified share,
+// and a rushing shareholder that reveals last. Each is seeded, so a failing
+// scenario replays exactly.
+//
+// Plain Shamir sharing has no defence against any of them: `join` returns
+// whatever the points interpolate to. What catches them is redundancy (the
+// `VerifyConsistency` and `ErrorCorrect` policies of `join_with_options`,
+// which need more than k shares) or a commitment made at split time (a
+// `CheckValue` or `SetCommitment`). `assert_resists_cheating` runs every
+// attack against a join and fails if any produces a wrong secret.
+//
+// The crate has no distributed key generation, so the rushing adversary is
+// modelled at reconstruction, the one place where shareholders reveal in
+// turn: the last one to reveal has seen everyone else's share first.
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum DealerAttack{
+    // Shares of a degree-k polynomial through the secret: any k shares join
+    // without error, but different sets of k give different secrets.
+    HighDegree,
+    // Honest dealing, then random bytes for the listed shares.
+    Corrupt(Vec<i32>),
+    // The listed shares lie on a polynomial for `other` instead, so those
+    // custodians hold a different secret than the rest.
+    Equivocate{victims:Vec<i32>,other:Vec<u8>},
+}
+
+pub struct MaliciousDealer{
+    rng:ChaCha20Rng,
+}
+
+impl MaliciousDealer{
+
+    pub fn new(seed:u64)->MaliciousDealer{
+        MaliciousDealer { rng: ChaCha20Rng::seed_from_u64(seed) }
+    }
+
+    // Deals n shares for a k-of-n split of `secret`, cheating as `attack` says.
+    pub fn deal(&mut self,n:i32,k:i32,secret:&[u8],attack:&DealerAttack)->Result<Parts,ShamirError>{
+        match attack {
+            DealerAttack::HighDegree => {
+                if n<=k {
+                    return Err(ShamirError::InsufficientParts { collected: n as usize, threshold: k as usize+1 });
+                }
+                ShamirSS::split_with_rng(n, k+1, secret.to_vec(), &mut self.rng)
+            }
+            DealerAttack::Corrupt(victims) => {
+                let mut parts=ShamirSS::split_with_rng(n, k, secret.to_vec(), &mut self.rng)?;
+                for victim in victims {
+                    if let Some(share)=parts.get_mut(victim) {
+                        self.rng.fill(share.as_mut_slice());
+                    }
+                }
+                Ok(parts)
+            }
+            DealerAttack::Equivocate { victims, other } => {
+                if other.len()!=secret.len() {
+                    return Err(ShamirError::InconsistentLengths);
+                }
+                let mut parts=ShamirSS::split_with_rng(n, k, secret.to_vec(), &mut self.rng)?;
+                let forged=ShamirSS::split_with_rng(n, k, other.clone(), &mut self.rng)?;
+                for victim in victims {
+                    if let (Some(share), Some(replacement))=(parts.get_mut(victim), forged.get(victim)) {
+                        share.clone_from(replacement);
+                    }
+                }
+                Ok(parts)
+            }
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum ShareholderAttack{
+    // Flips this many random bits of the share.
+    FlipBits(usize),
+    // Submits random bytes of the share's length.
+    Randomize,
+    // Submits the real share under another index.
+    Relabel(i32),
+    // Changes the share so that joining it with exactly `coalition` (which
+    // includes the cheater) gives the secret XOR `delta`. Lagrange
+    // interpolation is linear, so this needs no knowledge of the secret.
+    Shift{coalition:Vec<i32>,delta:Vec<u8>},
+}
+
+pub struct MaliciousShareholder{
+    pub index:i32,
+    share:Vec<u8>,
+    rng:ChaCha20Rng,
+}
+
+impl MaliciousShareholder{
+
+    pub fn new(index:i32,share:Vec<u8>,seed:u64)->MaliciousShareholder{
+        MaliciousShareholder { index, share, rng: ChaCha20Rng::seed_from_u64(seed) }
+    }
+
+    // The (index, share) this shareholder hands in.
+    pub fn submit(&mut self,attack:&ShareholderAttack)->Result<Share,ShamirError>{
+        let mut share=self.share.clone();
+        match attack {
+            ShareholderAttack::FlipBits(count) => {
+                for _ in 0..*count {
+                    if share.is_empty() {
+                        break;
+                    }
+                    let bit=self.rng.gen_range(0..share.len()*8);
+                    share[bit/8]^=1<<(bit%8);
+                }
+                Ok((self.index, share))
+            }
+            ShareholderAttack::Randomize => {
+                self.rng.fill(share.as_mut_slice());
+                Ok((self.index, share))
+            }
+            ShareholderAttack::Relabel(index) => {
+                if !(1..=255).contains(index) {
+                    return Err(ShamirError::InvalidIndex(*index));
+                }
+                Ok((*index, share))
+            }
+            ShareholderAttack::Shift { coalition, delta } => {
+                shift(self.index, &mut share, coalition, delta)?;
+                Ok((self.index, share))
+            }
+        }
+    }
+}
+
+// Waits until every other member of the reconstruction has revealed, joins
+// their shares with its own to learn the secret, and only then submits a
+// share that makes the others reconstruct `forged`.
+pub struct RushingShareholder{
+    pub index:i32,
+    share:Vec<u8>,
+}
+
+impl RushingShareholder{
+
+    pub fn new(index:i32,share:Vec<u8>)->RushingShareholder{
+        RushingShareholder { index, share }
+    }
+
+    // Returns the share to submit and the real secret the cheater now knows.
+    pub fn respond(&self,revealed:&Parts,forged:&[u8])->Result<(Share,Vec<u8>),ShamirError>{
+        if revealed.contains_key(&self.index) {
+            return Err(ShamirError::InvalidIndex(self.index));
+        }
+        let mut all=revealed.clone();
+        all.insert(self.index, self.share.clone());
+        let secret=ShamirSS::join(all.clone())?;
+        if forged.len()!=secret.len() {
+            return Err(ShamirError::InconsistentLengths);
+        }
+        let delta:Vec<u8>=secret.iter().zip(forged).map(|(a, b)| a^b).collect();
+        let coalition:Vec<i32>=all.keys().copied().collect();
+        let mut share=self.share.clone();
+        shift(self.index, &mut share, &coalition, &delta)?;
+        Ok(((self.index, share), secret))
+    }
+}
+
+// Adds delta / λ to the share, where λ is its Lagrange weight at 0 within
+// `coalition`: the joined secret then moves by exactly delta.
+fn shift(index:i32,share:&mut [u8],coalition:&[i32],delta:&[u8])->Result<(),ShamirError>{
+    if delta.len()!=share.len() {
+        return Err(ShamirError::InconsistentLengths);
+    }
+    if let Some(bad)=coalition.iter().find(|x| !(1..=255).contains(*x)) {
+        return Err(ShamirError::InvalidIndex(*bad));
+    }
+    let xs:Vec<u8>=coalition.iter().map(|&x| x as u8).collect();
+    let position=xs.iter().position(|&x| x as i32==index).ok_or(ShamirError::InvalidIndex(index))?;
+    let weight=GFC256::lagrange_coefficients(&xs, 0)[position];
+    for (byte, d) in share.iter_mut().zip(delta) {
+        *byte=GFC256::add(*byte, GFC256::div(*d, weight));
+    }
+    Ok(())
+}
+
+// Runs every attack above, one cheater at a time, against `join` given all n
+// shares of a k-of-n split of `secret`, and panics if any makes it return
+// anything but `secret`. Failing with an error is fine: detecting a cheat is
+// enough. Needs n > k, since with exactly k shares nothing can be detected.
+// The honest dealing must join to `secret`.
+//
+// `ShamirSS::join` fails this; `join_with_options` with a threshold and
+// `VerifyConsistency` or `ErrorCorrect` passes.
+pub fn assert_resists_cheating<F:FnMut(Parts)->Result<Vec<u8>,ShamirError>>(n:i32,k:i32,secret:&[u8],seed:u64,mut join:F){
+    assert!(k>1 && n>k && n<255, "assert_resists_cheating needs 1 < k < n < 255");
+    assert!(!secret.is_empty(), "assert_resists_cheating needs a non-empty secret");
+    let mut dealer=MaliciousDealer::new(seed);
+    let honest=dealer.deal(n, k, secret, &DealerAttack::Corrupt(Vec::new())).expect("split failed");
+    let joined=join(honest.clone());
+    assert!(joined.as_deref()==Ok(secret), "honest shares did not join to the secret: {joined:?}");
+
+    let other:Vec<u8>=secret.iter().map(|b| !b).collect();
+    let mut scenarios:Vec<(String,Parts)>=Vec::new();
+    for attack in [
+        DealerAttack::HighDegree,
+        DealerAttack::Corrupt(vec![n]),
+        DealerAttack::Equivocate { victims: vec![n], other: other.clone() },
+    ] {
+        scenarios.push((format!("dealer {attack:?}"), dealer.deal(n, k, secret, &attack).expect("dealing failed")));
+    }
+    // The cheater holds the last share and shifts the set the first k-1
+    // honest shares make with it.
+    let coalition:Vec<i32>=(1..k).chain([n]).collect();
+    let delta:Vec<u8>=vec![1;secret.len()];
+    for attack in [
+        ShareholderAttack::FlipBits(1),
+        ShareholderAttack::FlipBits(8*secret.len()),
+        ShareholderAttack::Randomize,
+        ShareholderAttack::Relabel(n+1),
+        ShareholderAttack::Shift { coalition, delta },
+    ] {
+        let mut parts=honest.clone();
+        let share=parts.remove(&n).unwrap();
+        let mut cheater=MaliciousShareholder::new(n, share, seed);
+        let (index, forged)=cheater.submit(&attack).expect("attack failed");
+        parts.insert(index, forged);
+        scenarios.push((format!("shareholder {n} {attack:?}"), parts));
+    }
+    let mut revealed=honest.clone();
+    let share=revealed.remove(&n).unwrap();
+    let ((index, forged), _)=RushingShareholder::new(n, share).respond(&revealed, &other).expect("rushing attack failed");
+    revealed.insert(index, forged);
+    scenarios.push((format!("rushing shareholder {n}"), revealed));
+
+    for (scenario, parts) in scenarios {
+        if let Ok(joined)=join(parts) {
+            assert!(joined==secret, "{scenario}: join returned a wrong secret instead of failing");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::join::{ExtraShares, JoinOptions};
+
+    fn subset(parts:&Parts,indices:&[i32])->Parts{
+        indices.iter().map(|i| (*i, parts[i].clone())).collect()
+    }
+
+    #[test]
+    fn dealer_attacks_split_the_custodians() {
+        let secret=b"secret".to_vec();
+        let parts=MaliciousDealer::new(1).deal(4, 2, &secret, &DealerAttack::HighDegree).unwrap();
+        assert_ne!(ShamirSS::join(subset(&parts, &[1, 2])).unwrap(), ShamirSS::join(subset(&parts, &[3, 4])).unwrap());
+        assert!(MaliciousDealer::new(1).deal(2, 2, &secret, &DealerAttack::HighDegree).is_err());
+
+        let other=b"forged".to_vec();
+        let attack=DealerAttack::Equivocate { victims: vec![3, 4], other: other.clone() };
+        let parts=MaliciousDealer::new(1).deal(4, 2, &secret, &attack).unwrap();
+        assert_eq!(ShamirSS::join(subset(&parts, &[1, 2])).unwrap(), secret);
+        assert_eq!(ShamirSS::join(subset(&parts, &[3, 4])).unwrap(), other);
+
+        let corrupt=DealerAttack::Corrupt(vec![2]);
+        assert_eq!(MaliciousDealer::new(7).deal(4, 2, &secret, &corrupt).unwrap(), MaliciousDealer::new(7).deal(4, 2, &secret, &corrupt).unwrap());
+    }
+
+    #[test]
+    fn shift_and_rushing_forge_the_secret() {
+        let parts=ShamirSS::split(4, 3, b"secret".to_vec()).unwrap();
+        let delta=vec![0x20;6];
+        let mut cheater=MaliciousShareholder::new(3, parts[&3].clone(), 1);
+        let shifted=cheater.submit(&ShareholderAttack::Shift { coalition: vec![1, 2, 3], delta }).unwrap();
+        let mut forged=subset(&parts, &[1, 2]);
+        forged.insert(shifted.0, shifted.1);
+        assert_eq!(ShamirSS::join(forged).unwrap(), b"SECRET");
+        assert!(cheater.submit(&ShareholderAttack::Relabel(256)).is_err());
+
+        let revealed=subset(&parts, &[1, 2]);
+        let ((index, share), learned)=RushingShareholder::new(3, parts[&3].clone()).respond(&revealed, b"forged").unwrap();
+        assert_eq!(learned, b"secret");
+        let mut forged=revealed.clone();
+        forged.insert(index, share);
+        assert_eq!(ShamirSS::join(forged).unwrap(), b"forged");
+        assert!(RushingShareholder::new(1, parts[&1].clone()).respond(&revealed, b"forged").is_err());
+    }
+
+    #[test]
+    fn redundant_joins_resist_cheating() {
+        for extra_shares in [ExtraShares::VerifyConsistency, ExtraShares::ErrorCorrect] {
+            let options=JoinOptions { threshold: Some(3), extra_shares, ..JoinOptions::default() };
+            assert_resists_cheating(6, 3, b"secret", 5, |parts| ShamirSS::join_with_options(parts, &options));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "wrong secret")]
+    fn plain_join_does_not_resist_cheating() {
+        assert_resists_cheating(4, 2, b"secret", 5, ShamirSS::join);
+    }
+}
@@ -0,0 +1,269 @@
+use age_core::primitives::{aead_decrypt, aead_encrypt, hkdf};
+use base64::{engine::general_purpose, Engine as _};
+use bech32::{Bech32, Hrp};
+use rand::rngs::OsRng;
+use x25519_dalek::{EphemeralSecret, PublicKey as X25519Public, StaticSecret};
+
+use crate::crypto::{constant_time_eq, zeroize, Parts, ShamirSS};
+use crate::encoding::decode_any_share;
+use crate::keypair::{read_public_key, split_keypair, KeyAlgorithm, PublicKey};
+use crate::sha256::Sha256;
+
+// The age plugin `age-plugin-shamir` (src/bin/age-plugin-shamir.rs): files
+// are encrypted to an X25519 key whose private half exists only as k-of-n
+// share bundles, and is reconstructed from k of them at decryption time.
+//
+//   recipient  age1shamir1...          bech32 of the 32-byte public key
+//   identity   AGE-PLUGIN-SHAMIR-1...  bech32 of threshold | public key
+//
+// The identity holds nothing secret: it tells the plugin how many shares to
+// gather and which key they must rebuild, so a wrong or corrupted share is
+// caught before any stanza is tried. Shares come from the files listed in
+// AGE_PLUGIN_SHAMIR_SHARES, then from the user through age's secret prompt.
+//
+// Stanza, the X25519 recipient type of age with its own labels:
+//
+//   -> shamir <tag> <ephemeral share>
+//   <ChaCha20-Poly1305 of the file key>
+//
+// where the key is HKDF-SHA-256(salt = ephemeral share | public key, label
+// WRAP_LABEL) of the X25519 shared secret and the tag is the first 4 bytes of
+// SHA-256 of the public key, so shares are only asked for when a file was
+// encrypted to that key.
+
+pub const PLUGIN_NAME:&str="shamir";
+pub const STANZA_TAG:&str="shamir";
+pub const SHARES_ENV:&str="AGE_PLUGIN_SHAMIR_SHARES";
+const RECIPIENT_HRP:&str="age1shamir";
+const IDENTITY_HRP:&str="age-plugin-shamir-";
+const WRAP_LABEL:&[u8]=b"age-encryption.org/v1/shamir";
+const KEY_LEN:usize=32;
+const TAG_LEN:usize=4;
+const FILE_KEY_LEN:usize=16;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ShamirIdentity{
+    pub threshold:u8,
+    pub public:[u8;KEY_LEN],
+}
+
+impl ShamirIdentity{
+
+    pub fn parse(text:&str)->Result<ShamirIdentity,String>{
+        let (hrp, data)=bech32::decode(text.trim()).map_err(|e| format!("Not a shamir identity: {e}"))?;
+        if !hrp.as_str().eq_ignore_ascii_case(IDENTITY_HRP) {
+            return Err(format!("Not a shamir identity (prefix {hrp})"));
+        }
+        let [threshold, public @ ..]=data.as_slice() else { return Err("Empty shamir identity".to_string()) };
+        let public=public.try_into().map_err(|_| format!("Shamir identity holds {} key bytes, not {KEY_LEN}", public.len()))?;
+        if *threshold<2 {
+            return Err(format!("Shamir identity threshold {threshold} is below 2"));
+        }
+        Ok(ShamirIdentity { threshold: *threshold, public })
+    }
+
+    pub fn encode(&self)->String{
+        let mut data=vec![self.threshold];
+        data.extend_from_slice(&self.public);
+        encode(IDENTITY_HRP, &data).to_uppercase()
+    }
+
+    pub fn recipient(&self)->String{
+        encode(RECIPIENT_HRP, &self.public)
+    }
+
+    // The stanza tag files encrypted to this key carry.
+    pub fn tag(&self)->String{
+        key_tag(&self.public)
+    }
+}
+
+// Both prefixes are valid HRPs and the payloads far below the length limit.
+fn encode(hrp:&str,data:&[u8])->String{
+    let hrp=Hrp::parse(hrp).unwrap_or_else(|_| unreachable!());
+    bech32::encode::<Bech32>(hrp, data).unwrap_or_else(|_| unreachable!())
+}
+
+pub fn parse_recipient(text:&str)->Result<[u8;KEY_LEN],String>{
+    let (hrp, data)=bech32::decode(text.trim()).map_err(|e| format!("Not a shamir recipient: {e}"))?;
+    if hrp.as_str()!=RECIPIENT_HRP {
+        return Err(format!("Not a shamir recipient (prefix {hrp})"));
+    }
+    data.as_slice().try_into().map_err(|_| format!("Shamir recipient holds {} key bytes, not {KEY_LEN}", data.len()))
+}
+
+fn key_tag(public:&[u8;KEY_LEN])->String{
+    general_purpose::STANDARD_NO_PAD.encode(&Sha256::digest(public)[..TAG_LEN])
+}
+
+// A fresh key split k of n: the identity, and one share bundle per index with
+// the public key line appended (see keypair.rs).
+pub fn generate(n:i32,k:i32)->Result<(ShamirIdentity,Vec<(i32,String)>),String>{
+    let threshold=u8::try_from(k).ok().filter(|k| *k>=2).ok_or(format!("Threshold {k} is outside 2..=255"))?;
+    let secret=StaticSecret::random_from_rng(OsRng);
+    let public=X25519Public::from(&secret).to_bytes();
+    let shares=split_keypair(n, k, &PublicKey::new(KeyAlgorithm::X25519, &public)?, secret.as_bytes())?;
+    Ok((ShamirIdentity { threshold, public }, shares))
+}
+
+// Shares handed in so far for one identity, checked one at a time so a bad
+// one can be reported (and asked for again) while gathering.
+pub struct ShareCollector{
+    identity:ShamirIdentity,
+    parts:Parts,
+}
+
+impl ShareCollector{
+
+    pub fn new(identity:ShamirIdentity)->ShareCollector{
+        ShareCollector { identity, parts: Parts::new() }
+    }
+
+    // Takes a single share or a bundle. A bundle naming another public key is
+    // refused outright; a repeated index is ignored.
+    pub fn add(&mut self,text:&str)->Result<(),String>{
+        if let Some(public)=read_public_key(text)? {
+            if public.algorithm!=KeyAlgorithm::X25519 || public.key!=self.identity.public {
+                return Err(format!("Share protects another key ({public})"));
+            }
+        }
+        let (index, mut data)=decode_any_share(text)?;
+        match self.parts.entry(index) {
+            std::collections::btree_map::Entry::Occupied(_) => zeroize(&mut data),
+            std::collections::btree_map::Entry::Vacant(slot) => { slot.insert(data); }
+        }
+        Ok(())
+    }
+
+    pub fn missing(&self)->usize{
+        (self.identity.threshold as usize).saturating_sub(self.parts.len())
+    }
+
+    // Joins the shares and checks that they rebuild the identity's key.
+    pub fn reconstruct(mut self)->Result<StaticSecret,String>{
+        if self.missing()>0 {
+            return Err(format!("{} more share(s) needed", self.missing()));
+        }
+        let parts=std::mem::take(&mut self.parts);
+        let mut joined=ShamirSS::join(parts).map_err(|e| e.to_string())?;
+        let key:Option<[u8;KEY_LEN]>=joined.as_slice().try_into().ok();
+        zeroize(&mut joined);
+        let secret=StaticSecret::from(key.ok_or("Shares do not hold an X25519 key")?);
+        if !constant_time_eq(X25519Public::from(&secret).as_bytes(), &self.identity.public) {
+            return Err("Shares rebuild a different key: one of them is wrong or corrupted".to_string());
+        }
+        Ok(secret)
+    }
+}
+
+impl Drop for ShareCollector{
+    fn drop(&mut self){
+        for data in self.parts.values_mut() {
+            zeroize(data);
+        }
+    }
+}
+
+fn wrap_key(shared:&[u8;KEY_LEN],ephemeral:&[u8;KEY_LEN],public:&[u8;KEY_LEN])->[u8;KEY_LEN]{
+    let mut salt=ephemeral.to_vec();
+    salt.extend_from_slice(public);
+    hkdf(&salt, WRAP_LABEL, shared)
+}
+
+// Stanza arguments (after the tag) and body wrapping `file_key` to `public`.
+pub fn wrap(public:&[u8;KEY_LEN],file_key:&[u8])->(Vec<String>,Vec<u8>){
+    let ephemeral=EphemeralSecret::random_from_rng(OsRng);
+    let share=X25519Public::from(&ephemeral).to_bytes();
+    let shared=ephemeral.diffie_hellman(&X25519Public::from(*public));
+    let mut key=wrap_key(shared.as_bytes(), &share, public);
+    let body=aead_encrypt(&key, file_key);
+    zeroize(&mut key);
+    (vec![key_tag(public), general_purpose::STANDARD_NO_PAD.encode(share)], body)
+}
+
+// None when the stanza is not for this key; Some(Err) when it claims to be
+// but does not open.
+pub fn unwrap(secret:&StaticSecret,args:&[String],body:&[u8])->Option<Result<[u8;FILE_KEY_LEN],String>>{
+    let public=X25519Public::from(secret).to_bytes();
+    let [tag, share]=args else { return None };
+    if *tag!=key_tag(&public) {
+        return None;
+    }
+    Some((|| {
+        let share:[u8;KEY_LEN]=general_purpose::STANDARD_NO_PAD.decode(share).ok()
+            .and_then(|s| s.try_into().ok()).ok_or("Malformed shamir stanza")?;
+        let shared=secret.diffie_hellman(&X25519Public::from(share));
+        if !shared.was_contributory() {
+            return Err("Shamir stanza has a low-order ephemeral share".to_string());
+        }
+        let mut key=wrap_key(shared.as_bytes(), &share, &public);
+        let opened=aead_decrypt(&key, FILE_KEY_LEN, body);
+        zeroize(&mut key);
+        let mut opened=opened.map_err(|_| "Shamir stanza does not open with the reconstructed key")?;
+        let file_key=opened.as_slice().try_into().map_err(|_| "Malformed shamir stanza body".to_string());
+        zeroize(&mut opened);
+        file_key
+    })())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identity_and_recipient_round_trip() {
+        let identity=ShamirIdentity { threshold: 3, public: [7;KEY_LEN] };
+        let encoded=identity.encode();
+        assert!(encoded.starts_with("AGE-PLUGIN-SHAMIR-1"));
+        assert_eq!(ShamirIdentity::parse(&encoded).unwrap(), identity);
+        assert!(identity.recipient().starts_with("age1shamir1"));
+        assert_eq!(parse_recipient(&identity.recipient()).unwrap(), identity.public);
+        assert!(parse_recipient(&encoded).is_err());
+        assert!(ShamirIdentity::parse(&identity.recipient()).is_err());
+    }
+
+    #[test]
+    fn any_threshold_of_shares_unwraps_and_fewer_do_not() {
+        let (identity, shares)=generate(5, 3).unwrap();
+        let (args, body)=wrap(&identity.public, &[9;FILE_KEY_LEN]);
+        for skip in 0..3 {
+            let mut collector=ShareCollector::new(identity);
+            for (_, share) in shares.iter().skip(skip).take(3) {
+                collector.add(share).unwrap();
+            }
+            let secret=collector.reconstruct().unwrap();
+            assert_eq!(unwrap(&secret, &args, &body).unwrap().unwrap(), [9;FILE_KEY_LEN]);
+        }
+        let mut collector=ShareCollector::new(identity);
+        collector.add(&shares[0].1).unwrap();
+        collector.add(&shares[0].1).unwrap();
+        collector.add(&shares[1].1).unwrap();
+        assert_eq!(collector.missing(), 1);
+        assert!(collector.reconstruct().is_err());
+    }
+
+    #[test]
+    fn wrong_shares_and_foreign_stanzas_are_refused() {
+        let (identity, shares)=generate(3, 2).unwrap();
+        let (other, other_shares)=generate(3, 2).unwrap();
+        let mut collector=ShareCollector::new(identity);
+        assert!(collector.add(&other_shares[0].1).is_err());
+
+        // A bare share line of another key passes `add` but not the check
+        // against the identity's public key.
+        let bare=|bundle:&str| bundle.lines().filter(|l| !l.starts_with("public-key")).collect::<Vec<_>>().join("\n");
+        collector.add(&shares[0].1).unwrap();
+        collector.add(&bare(&other_shares[1].1)).unwrap();
+        assert!(collector.reconstruct().is_err());
+
+        let mut collector=ShareCollector::new(identity);
+        collector.add(&shares[0].1).unwrap();
+        collector.add(&shares[2].1).unwrap();
+        let secret=collector.reconstruct().unwrap();
+        let (args, body)=wrap(&other.public, &[1;FILE_KEY_LEN]);
+        assert!(unwrap(&secret, &args, &body).is_none());
+        let (args, mut body)=wrap(&identity.public, &[1;FILE_KEY_LEN]);
+        body[0]^=1;
+        assert!(unwrap(&secret, &args, &body).unwrap().is_err());
+    }
+}
@@ -0,0 +1,85 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+
+use crate::crypto::zeroize;
+
+// The library allocates through the global allocator only, so the caller's
+// allocator is the one it uses: install a locked (mlock) or wiping allocator
+// with #[global_allocator] and every share, secret and coefficient buffer
+// goes through it. Stable Rust has no per-collection allocator parameter.
+//
+// `ZeroizingAllocator` wraps another global allocator (System by default)
+// and wipes every block before handing it back, so freed secret bytes are
+// not left in reused memory. Growing a Vec copies it to a new block and
+// frees the old one, which is wiped too.
+//
+//     #[global_allocator]
+//     static ALLOCATOR:ZeroizingAllocator=ZeroizingAllocator::new(System);
+//
+// `testutil::CountingAllocator` (feature `test-util`) counts allocations
+// instead, to check in tests that a path allocates nothing.
+
+pub struct ZeroizingAllocator<A=System>{
+    inner:A,
+}
+
+impl<A> ZeroizingAllocator<A>{
+    pub const fn new(inner:A)->ZeroizingAllocator<A>{
+        ZeroizingAllocator { inner }
+    }
+}
+
+// SAFETY: allocation is delegated unchanged to `inner`; `dealloc` only writes
+// within the block it is about to free, which the caller still owns.
+unsafe impl<A:GlobalAlloc> GlobalAlloc for ZeroizingAllocator<A>{
+    unsafe fn alloc(&self,layout:Layout)->*mut u8{
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self,layout:Layout)->*mut u8{
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self,ptr:*mut u8,layout:Layout){
+        zeroize(std::slice::from_raw_parts_mut(ptr, layout.size()));
+        self.inner.dealloc(ptr, layout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    // Records whether every block it frees was already wiped.
+    struct Inspecting{
+        wiped:AtomicBool,
+    }
+
+    // SAFETY: delegates to System; `dealloc` only reads the block it frees.
+    unsafe impl GlobalAlloc for Inspecting{
+        unsafe fn alloc(&self,layout:Layout)->*mut u8{
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self,ptr:*mut u8,layout:Layout){
+            let block=std::slice::from_raw_parts(ptr, layout.size());
+            self.wiped.store(block.iter().all(|&b| b==0), Ordering::SeqCst);
+            System.dealloc(ptr, layout);
+        }
+    }
+
+    #[test]
+    fn freed_blocks_are_wiped() {
+        let allocator=ZeroizingAllocator::new(Inspecting { wiped: AtomicBool::new(false) });
+        let layout=Layout::from_size_align(64, 8).unwrap();
+        // SAFETY: the block is allocated with `layout`, written within its
+        // bounds and freed once.
+        unsafe {
+            let ptr=allocator.alloc(layout);
+            assert!(!ptr.is_null());
+            std::ptr::write_bytes(ptr, 0xa5, layout.size());
+            allocator.dealloc(ptr, layout);
+        }
+        assert!(allocator.inner.wiped.load(Ordering::SeqCst));
+    }
+}
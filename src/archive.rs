@@ -0,0 +1,183 @@
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use crate::hardening::write_private;
+use crate::sha256::{to_hex, Sha256};
+
+// Minimal directory archive used by split-dir/join-dir. Each entry is
+// stored as: path length (u32 BE), UTF-8 path with '/' separators,
+// data length (u64 BE), data.
+
+#[derive(Debug,Clone)]
+pub struct Entry{
+    pub path:String,
+    pub data:Vec<u8>,
+}
+
+#[derive(Debug,Clone)]
+pub struct Archive;
+
+impl Archive{
+
+    pub fn walk(root:&Path)->io::Result<Vec<Entry>>{
+        let mut entries=Vec::new();
+        Self::walk_into(root, root, &mut entries)?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+
+    pub fn encode(entries:&[Entry])->Vec<u8>{
+        let mut out=Vec::new();
+        for entry in entries {
+            out.extend_from_slice(&(entry.path.len() as u32).to_be_bytes());
+            out.extend_from_slice(entry.path.as_bytes());
+            out.extend_from_slice(&(entry.data.len() as u64).to_be_bytes());
+            out.extend_from_slice(&entry.data);
+        }
+        out
+    }
+
+    pub fn decode(mut bytes:&[u8])->Result<Vec<Entry>,String>{
+        let mut entries=Vec::new();
+        while !bytes.is_empty() {
+            let path_len=u32::from_be_bytes(Self::take(&mut bytes, 4)?.try_into().unwrap()) as usize;
+            let path=String::from_utf8(Self::take(&mut bytes, path_len)?.to_vec())
+                .map_err(|_| "Archive path is not UTF-8".to_string())?;
+            let data_len=u64::from_be_bytes(Self::take(&mut bytes, 8)?.try_into().unwrap()) as usize;
+            let data=Self::take(&mut bytes, data_len)?.to_vec();
+            entries.push(Entry { path, data });
+        }
+        Ok(entries)
+    }
+
+    // One line per file: "<sha256 hex> <size> <path>".
+    pub fn manifest(entries:&[Entry])->String{
+        let mut out=String::new();
+        for entry in entries {
+            out.push_str(&format!("{} {} {}\n", to_hex(&Sha256::digest(&entry.data)), entry.data.len(), entry.path));
+        }
+        out
+    }
+
+    pub fn verify(entries:&[Entry],manifest:&str)->Result<(),String>{
+        let mut expected:Vec<(&str,&str)>=Vec::new();
+        for line in manifest.lines().filter(|l| !l.trim().is_empty()) {
+            let mut fields=line.splitn(3, ' ');
+            match (fields.next(), fields.next(), fields.next()) {
+                (Some(digest), Some(_), Some(path)) => expected.push((path, digest)),
+                _ => return Err(format!("Malformed manifest line: {line}")),
+            }
+        }
+        if expected.len()!=entries.len() {
+            return Err(format!("Manifest lists {} files, archive holds {}", expected.len(), entries.len()));
+        }
+        for entry in entries {
+            let digest=to_hex(&Sha256::digest(&entry.data));
+            match expected.iter().find(|(path, _)| *path==entry.path) {
+                Some((_, d)) if *d==digest => {}
+                Some(_) => return Err(format!("Digest mismatch for {}", entry.path)),
+                None => return Err(format!("{} is not listed in the manifest", entry.path)),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unpack(root:&Path,entries:&[Entry])->Result<(),String>{
+        for entry in entries {
+            let target=Self::safe_join(root, &entry.path)?;
+            if let Some(parent)=target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("{}: {e}", parent.display()))?;
+            }
+            // Restored files hold the secret payload.
+            write_private(&target, &entry.data).map_err(|e| format!("{}: {e}", target.display()))?;
+        }
+        Ok(())
+    }
+
+    // Symbolic links are refused rather than followed: a link could pull in
+    // files from outside the tree, and a link to an ancestor would recurse
+    // forever.
+    fn walk_into(root:&Path,dir:&Path,entries:&mut Vec<Entry>)->io::Result<()>{
+        for item in fs::read_dir(dir)? {
+            let item=item?;
+            let (path, kind)=(item.path(), item.file_type()?);
+            if kind.is_symlink() {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("{} is a symbolic link; archive its target instead", path.display())));
+            }
+            if kind.is_dir() {
+                Self::walk_into(root, &path, entries)?;
+            } else if kind.is_file() {
+                let relative=path.strip_prefix(root).unwrap_or(&path);
+                let name=relative.components()
+                    .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                entries.push(Entry { path: name, data: fs::read(&path)? });
+            }
+        }
+        Ok(())
+    }
+
+    // Archive paths must stay inside the restore root.
+    fn safe_join(root:&Path,path:&str)->Result<PathBuf,String>{
+        let relative=Path::new(path);
+        if relative.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(format!("Refusing to restore unsafe path {path}"));
+        }
+        Ok(root.join(relative))
+    }
+
+    fn take<'a>(bytes:&mut &'a [u8],len:usize)->Result<&'a [u8],String>{
+        if bytes.len()<len {
+            return Err("Truncated archive".to_string());
+        }
+        let (head, tail)=bytes.split_at(len);
+        *bytes=tail;
+        Ok(head)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name:&str)->PathBuf{
+        let dir=std::env::temp_dir().join(format!("shamir-archive-{name}-{}", std::process::id()));
+        let _=fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn walk_refuses_symlinks() {
+        let dir=scratch("symlink");
+        fs::write(dir.join("a.txt"), b"a").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("loop")).unwrap();
+        let err=Archive::walk(&dir).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unpack_creates_owner_only_files() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir=scratch("unpack");
+        let entries=vec![Entry { path: "sub/secret.txt".to_string(), data: b"payload".to_vec() }];
+        Archive::unpack(&dir, &entries).unwrap();
+        let target=dir.join("sub/secret.txt");
+        assert_eq!(fs::read(&target).unwrap(), b"payload");
+        assert_eq!(fs::metadata(&target).unwrap().permissions().mode() & 0o777, 0o600);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn encode_decode_round_trip() {
+        let entries=vec![Entry { path: "a".to_string(), data: vec![1, 2, 3] }, Entry { path: "b/c".to_string(), data: Vec::new() }];
+        let decoded=Archive::decode(&Archive::encode(&entries)).unwrap();
+        assert_eq!(decoded.len(), 2);
+        assert_eq!((decoded[1].path.as_str(), decoded[0].data.as_slice()), ("b/c", &[1u8, 2, 3][..]));
+    }
+}
@@ -0,0 +1,176 @@
+use std::cell::Cell;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process;
+
+use age_core::format::Stanza;
+use age_core::plugin::Connection;
+use shamir_rust::hardening::write_new_private;
+use shamir_rust::age_plugin::{generate, parse_recipient, unwrap, wrap, ShamirIdentity, ShareCollector, SHARES_ENV, STANZA_TAG};
+
+// age-plugin-shamir: see src/age_plugin.rs for the recipient, identity and
+// stanza formats.
+//
+//   age-plugin-shamir --generate --threshold K --shares N --out DIR
+//       writes N share bundles to DIR and prints the identity (for `age -i`)
+//       with its recipient (for `age -r`)
+//   age-plugin-shamir --age-plugin=recipient-v1|identity-v1
+//       the state machines age runs the plugin with
+
+const USAGE:&str="usage: age-plugin-shamir --generate --threshold K --shares N --out DIR";
+
+fn main() {
+    let args:Vec<String>=std::env::args().skip(1).collect();
+    let result=match args.first().map(String::as_str) {
+        Some("--age-plugin=recipient-v1") => recipient_v1().map_err(|e| e.to_string()),
+        Some("--age-plugin=identity-v1") => identity_v1().map_err(|e| e.to_string()),
+        Some("--generate") => generate_shares(&args[1..]),
+        _ => Err(USAGE.to_string()),
+    };
+    if let Err(e)=result {
+        eprintln!("age-plugin-shamir: {e}");
+        process::exit(1);
+    }
+}
+
+fn generate_shares(args:&[String])->Result<(),String>{
+    let value=|flag:&str| args.iter().position(|a| a==flag).and_then(|i| args.get(i+1)).ok_or(USAGE.to_string());
+    let number=|flag:&str| value(flag)?.parse::<i32>().map_err(|e| format!("{flag}: {e}"));
+    let (k, n, out)=(number("--threshold")?, number("--shares")?, Path::new(value("--out")?));
+    let (identity, shares)=generate(n, k)?;
+    fs::create_dir_all(out).map_err(|e| format!("{}: {e}", out.display()))?;
+    for (index, bundle) in shares {
+        let path=out.join(format!("share-{index}.txt"));
+        write_new_private(&path, bundle.as_bytes()).map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+    println!("# recipient: {}", identity.recipient());
+    println!("# threshold: {k} of {n} shares in {}", out.display());
+    println!("{}", identity.encode());
+    eprintln!("Public key: {}", identity.recipient());
+    Ok(())
+}
+
+// Errors while reading phase 1, as age's `error` command wants them: the
+// kind ("recipient" or "identity"), its position, and a message.
+type Failure=(&'static str,usize,String);
+
+fn counted<'a,T>(kind:&'static str,count:&'a Cell<usize>,parse:impl Fn(&str)->Result<T,String>+'a)->impl Fn(Stanza)->Result<T,Failure>+'a{
+    move |stanza| {
+        let position=count.replace(count.get()+1);
+        let arg=stanza.args.first().map(String::as_str).unwrap_or_default();
+        parse(arg).map_err(|e| (kind, position, e))
+    }
+}
+
+fn send_failures<R:io::Read,W:io::Write>(conn:&mut Connection<R,W>,failures:Vec<Failure>)->io::Result<()>{
+    conn.bidir_send(|mut phase| {
+        for (kind, position, message) in failures {
+            let _=phase.send("error", &[kind, &position.to_string()], message.as_bytes())?;
+        }
+        Ok(())
+    })
+}
+
+// Wraps every file key to every recipient and identity's public key.
+fn recipient_v1()->io::Result<()>{
+    let mut conn=Connection::accept();
+    let (recipients, identities)=(Cell::new(0), Cell::new(0));
+    let (recipients, identities, file_keys, _)=conn.unidir_receive(
+        ("add-recipient", counted("recipient", &recipients, parse_recipient)),
+        ("add-identity", counted("identity", &identities, |text| ShamirIdentity::parse(text).map(|i| i.public))),
+        (Some("wrap-file-key"), |stanza:Stanza| Ok(stanza.body)),
+        (Some("extension-labels"), |_| Ok(())),
+    )?;
+    let file_keys=file_keys.and_then(Result::ok).unwrap_or_default();
+    let keys=match (recipients, identities) {
+        (Ok(recipients), Ok(identities)) => recipients.into_iter().chain(identities).collect::<Vec<_>>(),
+        (recipients, identities) => {
+            let failures=recipients.err().into_iter().chain(identities.err()).flatten().collect();
+            return send_failures(&mut conn, failures);
+        }
+    };
+    conn.bidir_send(|mut phase| {
+        for (file, file_key) in file_keys.iter().enumerate() {
+            for public in &keys {
+                let (args, body)=wrap(public, file_key);
+                let file=file.to_string();
+                let mut metadata=vec![file.as_str(), STANZA_TAG];
+                metadata.extend(args.iter().map(String::as_str));
+                let _=phase.send("recipient-stanza", &metadata, &body)?;
+            }
+        }
+        Ok(())
+    })
+}
+
+// Unwraps the first stanza one of the identities opens, gathering its shares
+// from SHARES_ENV and then from the user.
+fn identity_v1()->io::Result<()>{
+    let mut conn=Connection::accept();
+    let count=Cell::new(0);
+    let (identities, stanzas, _, _)=conn.unidir_receive(
+        ("add-identity", counted("identity", &count, ShamirIdentity::parse)),
+        ("recipient-stanza", |stanza:Stanza| Ok(stanza)),
+        (None, |_| Ok::<(), Failure>(())),
+        (None, |_| Ok::<(), Failure>(())),
+    )?;
+    let identities=match identities {
+        Ok(identities) => identities,
+        Err(failures) => return send_failures(&mut conn, failures),
+    };
+    // recipient-stanza <file> <tag> <args...>; age sends one file, index 0.
+    let stanzas:Vec<Stanza>=stanzas.unwrap_or_default().into_iter()
+        .filter(|s| s.args.first().map(String::as_str)==Some("0") && s.args.get(1).map(String::as_str)==Some(STANZA_TAG))
+        .map(|s| Stanza { tag: STANZA_TAG.to_string(), args: s.args[2..].to_vec(), body: s.body })
+        .collect();
+
+    conn.bidir_send(|mut phase| {
+        for (position, identity) in identities.iter().enumerate() {
+            let mine:Vec<&Stanza>=stanzas.iter().filter(|s| s.args.first()==Some(&identity.tag())).collect();
+            if mine.is_empty() {
+                continue;
+            }
+            let mut collector=ShareCollector::new(*identity);
+            let mut problem=None;
+            for path in std::env::var_os(SHARES_ENV).map(|v| std::env::split_paths(&v).collect::<Vec<_>>()).unwrap_or_default() {
+                if let Err(e)=fs::read_to_string(&path).map_err(|e| e.to_string()).and_then(|text| collector.add(&text)) {
+                    let _=phase.send("msg", &[], format!("{}: {e}", path.display()).as_bytes())?;
+                }
+            }
+            while collector.missing()>0 {
+                let mut prompt=format!("Share for {} ({} more needed):", identity.recipient(), collector.missing());
+                if let Some(e)=problem.take() {
+                    prompt=format!("{e}\n{prompt}");
+                }
+                let Ok(reply)=phase.send("request-secret", &[], prompt.as_bytes())? else {
+                    let _=phase.send("error", &["identity", &position.to_string()], b"Not enough shares")?;
+                    return Ok(());
+                };
+                if let Err(e)=collector.add(&String::from_utf8_lossy(&reply.body)) {
+                    problem=Some(e);
+                }
+            }
+            let secret=match collector.reconstruct() {
+                Ok(secret) => secret,
+                Err(e) => {
+                    let _=phase.send("error", &["identity", &position.to_string()], e.as_bytes())?;
+                    continue;
+                }
+            };
+            for stanza in mine {
+                match unwrap(&secret, &stanza.args, &stanza.body) {
+                    Some(Ok(file_key)) => {
+                        let _=phase.send("file-key", &["0"], &file_key)?;
+                        return Ok(());
+                    }
+                    Some(Err(e)) => {
+                        let _=phase.send("error", &["identity", &position.to_string()], e.as_bytes())?;
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    })
+}
@@ -0,0 +1,149 @@
+use crate::crypto::{constant_time_eq, Parts, ShamirSS};
+use crate::encoding::ParseMode;
+use crate::error::ShamirError;
+use crate::sha256::{to_hex, Sha256};
+
+// Public check value: the dealer evaluates the polynomial at a reserved
+// x-coordinate and publishes a digest of that point. Publishing the point
+// itself would hand out an extra share (k-1 custodians plus the public value
+// would reach the threshold), so only SHA-256(x || y) is released. At join
+// time the reconstructed polynomial is evaluated at the same x and must hash
+// to the published digest, which catches tampered or foreign shares.
+//
+// The digest is also a guessing oracle: k-1 custodians plus a guess of the
+// secret (the point at x = 0) fix the whole polynomial, so they can test
+// guesses offline against the published value, as fast as they can hash.
+// Shamir's own guarantee (k-1 shares say nothing) then only holds as far as
+// the secret cannot be guessed. Only publish a check value for secrets with
+// enough entropy to resist that, such as random keys; the CLI warns below
+// GUESSABLE_SECRET_LEN bytes.
+
+pub const CHECK_X:u8=255;
+
+// Secrets shorter than this (128 bits) may be within reach of an offline
+// search by k-1 custodians holding anything derived from all the shares.
+pub const GUESSABLE_SECRET_LEN:usize=16;
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct CheckValue{
+    pub x:u8,
+    pub digest:[u8;32],
+}
+
+impl CheckValue{
+
+    // Text form for publishing: "<x>:<hex digest>".
+    pub fn to_text(&self)->String{
+        format!("{}:{}\n", self.x, to_hex(&self.digest))
+    }
+
+    pub fn parse(text:&str)->Result<CheckValue,String>{
+        Self::parse_with(text, ParseMode::Lenient)
+    }
+
+    // Strict mode accepts only the exact `to_text` form (lower-case hex, no
+    // leading zeros); lenient mode also takes upper-case hex and "+"/zero-
+    // padded coordinates.
+    pub fn parse_with(text:&str,mode:ParseMode)->Result<CheckValue,String>{
+        let (x, hex)=text.trim().split_once(':').ok_or("Malformed check value")?;
+        let x=x.parse::<u8>().map_err(|_| "Malformed check value coordinate".to_string())?;
+        if hex.len()!=64 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err("Malformed check value digest".to_string());
+        }
+        let mut digest=[0u8;32];
+        for (i, byte) in digest.iter_mut().enumerate() {
+            *byte=u8::from_str_radix(&hex[i*2..i*2+2], 16).map_err(|_| "Malformed check value digest".to_string())?;
+        }
+        let value=CheckValue { x, digest };
+        if mode==ParseMode::Strict && text.strip_suffix('\n').unwrap_or(text)!=value.to_text().trim_end() {
+            return Err("Check value is not in canonical form".to_string());
+        }
+        Ok(value)
+    }
+
+    // Whether the polynomial through `parts` matches this check value.
+    pub fn verify(&self,parts:&Parts)->Result<(),ShamirError>{
+        if parts.contains_key(&(self.x as i32)) {
+            return Err(ShamirError::InvalidIndex(self.x as i32));
+        }
+        let mut point=ShamirSS::evaluate_at(parts, self.x)?;
+        let expected=CheckValue::of(self.x, &point);
+        point.fill(0);
+        if !constant_time_eq(&expected.digest, &self.digest) {
+            return Err(ShamirError::CheckFailed);
+        }
+        Ok(())
+    }
+
+    fn of(x:u8,y:&[u8])->CheckValue{
+        let mut h=Sha256::new();
+        h.update(&[x]);
+        h.update(y);
+        CheckValue { x, digest: h.finalize() }
+    }
+}
+
+impl ShamirSS{
+
+    // Like `split`, with x = CHECK_X reserved for the check value (n <= 254).
+    pub fn split_with_check(n:i32,k:i32,secret:Vec<u8>)->Result<(Parts,CheckValue),ShamirError>{
+        if n > CHECK_X as i32 - 1 {
+            return Err(ShamirError::TooManyShares)
+        }
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        if n < k {
+            return Err(ShamirError::ThresholdAboveShares)
+        }
+        let mut xs:Vec<u8>=(1..=n).map(|x| x as u8).collect();
+        xs.push(CHECK_X);
+        let mut parts=Self::split_at(&xs, k, secret)?;
+
+        let mut point=parts.remove(&(CHECK_X as i32)).unwrap_or_default();
+        let check=CheckValue::of(CHECK_X, &point);
+        point.fill(0);
+        Ok((parts,check))
+    }
+
+    pub fn join_with_check(parts:Parts,check:&CheckValue)->Result<Vec<u8>,ShamirError>{
+        check.verify(&parts)?;
+        Self::join(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_value_catches_tampered_shares() {
+        let (parts, check)=ShamirSS::split_with_check(5, 3, b"random key bytes".to_vec()).unwrap();
+        assert_eq!(check.x, CHECK_X);
+        assert!(!parts.contains_key(&(CHECK_X as i32)));
+        let three:Parts=parts.clone().into_iter().take(3).collect();
+        assert_eq!(ShamirSS::join_with_check(three.clone(), &check).unwrap(), b"random key bytes");
+        assert_eq!(CheckValue::parse(&check.to_text()).unwrap(), check);
+
+        let mut tampered=three.clone();
+        tampered.get_mut(&2).unwrap()[0]^=1;
+        assert_eq!(ShamirSS::join_with_check(tampered, &check), Err(ShamirError::CheckFailed));
+
+        // The reserved point is only reproduced by a polynomial through k shares.
+        let point=ShamirSS::evaluate_at(&three, CHECK_X).unwrap();
+        assert_eq!(CheckValue::of(CHECK_X, &point), check);
+    }
+
+    #[test]
+    fn zero_and_the_reserved_x_are_not_share_indices() {
+        let (parts, check)=ShamirSS::split_with_check(3, 2, b"random key bytes".to_vec()).unwrap();
+        let data=parts[&1].clone();
+        for index in [0, CHECK_X as i32] {
+            let mut forged:Parts=parts.clone().into_iter().take(2).collect();
+            forged.insert(index, data.clone());
+            assert_eq!(check.verify(&forged), Err(ShamirError::InvalidIndex(index)));
+        }
+        assert_eq!(ShamirSS::split_with_check(255, 2, b"key".to_vec()), Err(ShamirError::TooManyShares));
+        assert!(ShamirSS::split_with_check(254, 2, b"key".to_vec()).is_ok());
+    }
+}
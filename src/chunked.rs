@@ -0,0 +1,247 @@
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::error::ShamirError;
+
+// Chunked shares for large, slowly changing secrets. The secret is cut into
+// fixed-size chunks and every chunk is split on its own, with a generation
+// counter per chunk. When the secret changes, `update_chunked` re-splits only
+// the chunks that differ (fresh polynomials, generation + 1) and hands each
+// custodian a delta to apply to the share file they already hold.
+//
+// Unchanged chunks keep their old polynomials. That reveals nothing new:
+// chunks are independent, and the delta only shows which chunks changed.
+// Joining requires every share to agree on each chunk's generation, so
+// shares updated with a delta and stale ones cannot be mixed.
+//
+// Text form (deltas use "shamir-delta v1" and list only changed chunks):
+//   shamir-chunked v1 index=<x> chunk_size=<bytes> chunks=<count>
+//   <chunk> <generation> <base64>
+
+const SHARE_HEADER:&str="shamir-chunked v1";
+const DELTA_HEADER:&str="shamir-delta v1";
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ChunkEntry{
+    pub generation:u32,
+    pub data:Vec<u8>,
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ChunkedShare{
+    pub index:i32,
+    pub chunk_size:usize,
+    pub chunks:Vec<ChunkEntry>,
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ChunkDelta{
+    pub index:i32,
+    pub chunk_size:usize,
+    pub chunk_count:usize,
+    pub updates:Vec<(usize,ChunkEntry)>,
+}
+
+impl ChunkedShare{
+
+    // Replaces the changed chunks and drops chunks past the new end.
+    pub fn apply(&mut self,delta:&ChunkDelta)->Result<(),String>{
+        if delta.index!=self.index || delta.chunk_size!=self.chunk_size {
+            return Err(format!("Delta for share {} does not apply to share {}", delta.index, self.index));
+        }
+        let mut chunks:Vec<Option<ChunkEntry>>=self.chunks.drain(..).map(Some).collect();
+        chunks.resize(delta.chunk_count, None);
+        for (position, entry) in &delta.updates {
+            let slot=chunks.get_mut(*position).ok_or(format!("Delta chunk {position} is past the end"))?;
+            if let Some(old)=slot {
+                if entry.generation<=old.generation {
+                    return Err(format!("Delta chunk {position} is generation {}, not newer than {}", entry.generation, old.generation));
+                }
+            }
+            *slot=Some(entry.clone());
+        }
+        self.chunks=chunks.into_iter().enumerate()
+            .map(|(position, chunk)| chunk.ok_or(format!("Delta leaves chunk {position} without a share")))
+            .collect::<Result<_,_>>()?;
+        Ok(())
+    }
+
+    pub fn to_text(&self)->String{
+        let mut out=format!("{SHARE_HEADER} index={} chunk_size={} chunks={}\n", self.index, self.chunk_size, self.chunks.len());
+        out.push_str(&entries_text(self.chunks.iter().enumerate()));
+        out
+    }
+
+    pub fn parse(text:&str)->Result<ChunkedShare,String>{
+        let (index, chunk_size, count, entries)=parse_text(text, SHARE_HEADER)?;
+        if entries.len()!=count || entries.iter().enumerate().any(|(i, (position, _))| i!=*position) {
+            return Err("Chunked share does not list every chunk in order".to_string());
+        }
+        Ok(ChunkedShare { index, chunk_size, chunks: entries.into_iter().map(|(_, entry)| entry).collect() })
+    }
+}
+
+impl ChunkDelta{
+
+    pub fn to_text(&self)->String{
+        let mut out=format!("{DELTA_HEADER} index={} chunk_size={} chunks={}\n", self.index, self.chunk_size, self.chunk_count);
+        out.push_str(&entries_text(self.updates.iter().map(|(position, entry)| (*position, entry))));
+        out
+    }
+
+    pub fn parse(text:&str)->Result<ChunkDelta,String>{
+        let (index, chunk_size, chunk_count, updates)=parse_text(text, DELTA_HEADER)?;
+        Ok(ChunkDelta { index, chunk_size, chunk_count, updates })
+    }
+}
+
+fn entries_text<'a>(entries:impl Iterator<Item=(usize,&'a ChunkEntry)>)->String{
+    entries.map(|(position, entry)| format!("{position} {} {}\n", entry.generation, general_purpose::STANDARD.encode(&entry.data))).collect()
+}
+
+type ParsedText=(i32,usize,usize,Vec<(usize,ChunkEntry)>);
+
+fn parse_text(text:&str,header:&str)->Result<ParsedText,String>{
+    let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+    let first=lines.next().ok_or("Empty chunked share")?;
+    let fields=first.strip_prefix(header).ok_or(format!("Missing {header} header"))?;
+    let mut values=[None;3];
+    for field in fields.split_whitespace() {
+        let (key, value)=field.split_once('=').ok_or(format!("Malformed header field {field}"))?;
+        let slot=match key {
+            "index" => 0,
+            "chunk_size" => 1,
+            "chunks" => 2,
+            _ => continue,
+        };
+        values[slot]=Some(value.parse::<usize>().map_err(|_| format!("Header field {key} is not a number"))?);
+    }
+    let [Some(index), Some(chunk_size), Some(count)]=values else { return Err("Chunked share header is incomplete".to_string()) };
+
+    let mut entries=Vec::new();
+    for line in lines {
+        let mut parts=line.split_whitespace();
+        let (Some(position), Some(generation), Some(data))=(parts.next(), parts.next(), parts.next()) else {
+            return Err(format!("Malformed chunk line {line}"));
+        };
+        let position=position.parse::<usize>().map_err(|_| format!("Malformed chunk number in {line}"))?;
+        let generation=generation.parse::<u32>().map_err(|_| format!("Malformed generation in {line}"))?;
+        let data=general_purpose::STANDARD.decode(data).map_err(|e| format!("Malformed chunk payload: {e}"))?;
+        entries.push((position, ChunkEntry { generation, data }));
+    }
+    Ok((index as i32, chunk_size, count, entries))
+}
+
+impl ShamirSS{
+
+    pub fn split_chunked(n:i32,k:i32,secret:&[u8],chunk_size:usize)->Result<Vec<ChunkedShare>,ShamirError>{
+        if chunk_size==0 {
+            return Err(ShamirError::InvalidChunkSize)
+        }
+        let mut shares:Vec<ChunkedShare>=(1..=n).map(|index| ChunkedShare { index, chunk_size, chunks: Vec::new() }).collect();
+        for chunk in secret.chunks(chunk_size) {
+            let parts=Self::split(n, k, chunk.to_vec())?;
+            for (share, (_, data)) in shares.iter_mut().zip(parts) {
+                share.chunks.push(ChunkEntry { generation: 1, data });
+            }
+        }
+        Ok(shares)
+    }
+
+    pub fn join_chunked(shares:&[ChunkedShare])->Result<Vec<u8>,ShamirError>{
+        let first=shares.first().ok_or(ShamirError::NoParts)?;
+        if shares.iter().any(|s| s.chunks.len()!=first.chunks.len() || s.chunk_size!=first.chunk_size) {
+            return Err(ShamirError::InconsistentLengths)
+        }
+        let mut secret=Vec::with_capacity(first.chunks.len()*first.chunk_size);
+        for position in 0..first.chunks.len() {
+            let generation=first.chunks[position].generation;
+            let stale:Vec<i32>=shares.iter().filter(|s| s.chunks[position].generation!=generation).map(|s| s.index).collect();
+            if !stale.is_empty() {
+                return Err(ShamirError::InconsistentShares(stale))
+            }
+            let parts:Parts=shares.iter().map(|s| (s.index, s.chunks[position].data.clone())).collect();
+            secret.extend_from_slice(&Self::join(parts)?);
+        }
+        Ok(secret)
+    }
+
+    // Reconstructs the current secret from `shares` (at least k of them),
+    // compares it chunk by chunk with `new_secret` and re-splits only the
+    // chunks that changed or were appended, for custodians 1..=n.
+    pub fn update_chunked(shares:&[ChunkedShare],new_secret:&[u8],n:i32,k:i32)->Result<Vec<ChunkDelta>,ShamirError>{
+        let mut old=Self::join_chunked(shares)?;
+        let first=&shares[0];
+        let chunk_size=first.chunk_size;
+
+        let new_chunks:Vec<&[u8]>=new_secret.chunks(chunk_size).collect();
+        let mut deltas:Vec<ChunkDelta>=(1..=n).map(|index| ChunkDelta { index, chunk_size, chunk_count: new_chunks.len(), updates: Vec::new() }).collect();
+        for (position, chunk) in new_chunks.iter().enumerate() {
+            let previous=old.chunks(chunk_size).nth(position);
+            if previous==Some(*chunk) {
+                continue;
+            }
+            let generation=first.chunks.get(position).map(|c| c.generation+1).unwrap_or(1);
+            let parts=Self::split(n, k, chunk.to_vec())?;
+            for (delta, (_, data)) in deltas.iter_mut().zip(parts) {
+                delta.updates.push((position, ChunkEntry { generation, data }));
+            }
+        }
+        old.fill(0);
+        Ok(deltas)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deltas_update_only_changed_chunks() {
+        let secret=b"aaaabbbbcccc".to_vec();
+        let mut shares=ShamirSS::split_chunked(3, 2, &secret, 4).unwrap();
+        assert_eq!(ShamirSS::join_chunked(&shares[1..]).unwrap(), secret);
+
+        let deltas=ShamirSS::update_chunked(&shares[..2], b"aaaaBBBBccccdd", 3, 2).unwrap();
+        let positions:Vec<usize>=deltas[0].updates.iter().map(|(position, _)| *position).collect();
+        assert_eq!(positions, vec![1, 3]);
+        assert_eq!((deltas[0].updates[0].1.generation, deltas[0].updates[1].1.generation), (2, 1));
+
+        // A custodian who has not applied the delta cannot join with one who has.
+        let stale=shares[2].clone();
+        for (share, delta) in shares.iter_mut().zip(&deltas) {
+            let delta=ChunkDelta::parse(&delta.to_text()).unwrap();
+            share.apply(&delta).unwrap();
+        }
+        assert_eq!(ShamirSS::join_chunked(&shares[..2]).unwrap(), b"aaaaBBBBccccdd");
+        assert!(ShamirSS::join_chunked(&[shares[0].clone(), stale]).is_err());
+        assert!(shares[0].clone().apply(&deltas[0]).is_err());
+        assert!(shares[0].clone().apply(&deltas[1]).is_err());
+    }
+
+    #[test]
+    fn shrinking_the_secret_drops_chunks() {
+        let mut shares=ShamirSS::split_chunked(2, 2, b"aaaabbbbcc", 4).unwrap();
+        let deltas=ShamirSS::update_chunked(&shares, b"aaaa", 2, 2).unwrap();
+        assert!(deltas[0].updates.is_empty());
+        for (share, delta) in shares.iter_mut().zip(&deltas) {
+            share.apply(delta).unwrap();
+        }
+        assert_eq!(ShamirSS::join_chunked(&shares).unwrap(), b"aaaa");
+    }
+
+    #[test]
+    fn text_form_round_trips() {
+        let shares=ShamirSS::split_chunked(3, 2, b"0123456789", 3).unwrap();
+        let text=shares[0].to_text();
+        assert!(text.starts_with("shamir-chunked v1 index=1 chunk_size=3 chunks=4\n"));
+        assert_eq!(ChunkedShare::parse(&text).unwrap(), shares[0]);
+
+        let lines:Vec<&str>=text.lines().collect();
+        assert!(ChunkedShare::parse(&[lines[0], lines[2], lines[1], lines[3], lines[4]].join("\n")).is_err());
+        assert!(ChunkedShare::parse(&lines[..4].join("\n")).is_err());
+        assert!(ChunkedShare::parse("shamir-chunked v1 index=1 chunks=4").is_err());
+        assert!(ChunkDelta::parse(&text).is_err());
+        assert!(ShamirSS::split_chunked(3, 2, b"secret", 0).is_err());
+    }
+}
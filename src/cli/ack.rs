@@ -0,0 +1,103 @@
+use std::path::Path;
+
+use shamir_rust::ack::{read_challenge, record, Acknowledgment};
+use shamir_rust::encoding::decode_any_share;
+use shamir_rust::transcript::Transcript;
+
+use super::{dates, output, read_string, tr, write, Args, CliError};
+
+// Delivery acknowledgments (see `ack.rs`): `split --ack` puts a challenge in
+// every share, the custodian runs `ack respond` on what they received and
+// sends the one-line answer back, and the dealer's `ack record` checks it and
+// dates the custodian's transcript line.
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("respond") => respond(args),
+        Some("record") => record_responses(args),
+        _ => Err(CliError::with_usage(tr!("unknown-ack-command"))),
+    }
+}
+
+fn respond(args:&Args)->Result<(),CliError>{
+    let file=args.positional.get(1).ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let text=read_string(Path::new(file))?;
+    let (index, data)=decode_any_share(&text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let challenge=read_challenge(&text).ok_or_else(|| tr!("no-ack-challenge", file=file))?;
+    let ack=Acknowledgment::new(challenge, index, &data).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    match args.value("out").filter(|v| !v.is_empty()) {
+        Some(path) => {
+            write(Path::new(path), ack.to_text().as_bytes())?;
+            output::result(args, path, &[&"ack", &index, &path]);
+        }
+        None => print!("{}", ack.to_text()),
+    }
+    Ok(())
+}
+
+fn record_responses(args:&Args)->Result<(),CliError>{
+    let files=&args.positional[1..];
+    if files.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-ack-file")));
+    }
+    let path=Path::new(args.required("transcript")?);
+    let mut transcript=Transcript::parse(&read_string(path)?)?;
+    let date=dates::required_today("ack record")?;
+    for file in files {
+        let ack=Acknowledgment::parse(&read_string(Path::new(file))?).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+        let custodian=record(&mut transcript, &ack, &date).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+        let when=custodian.acknowledged.clone().unwrap_or_default();
+        output::result(args, &tr!("acknowledged", index=custodian.index, label=custodian.label, date=when),
+            &[&"acknowledged", &custodian.index, &custodian.label, &when]);
+    }
+    write(path, transcript.to_text().as_bytes())?;
+    let pending=transcript.unacknowledged();
+    if !pending.is_empty() {
+        let labels:Vec<&str>=pending.iter().map(|c| c.label.as_str()).collect();
+        output::info(args, &tr!("ack-pending", count=pending.len(), labels=labels.join(", ")), &[&"pending", &pending.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::cli::error::INTEGRITY;
+    use crate::cli::{encode_share, shares};
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn responses_date_the_transcript() {
+        let root=std::env::temp_dir().join(format!("shamir-ack-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        fs::write(path("secret"), "a secret long enough not to be guessed").unwrap();
+        let transcript=path("transcript.txt");
+        shares::split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("shares"), "--ack", "--transcript", &transcript, "--quiet"])).unwrap();
+        let share=|index:i32| path(&format!("shares/share-{index}-of-3.shs"));
+
+        respond(&args(&["respond", &share(1), "--out", &path("ack-1"), "--quiet"])).unwrap();
+        record_responses(&args(&["record", &path("ack-1"), "--transcript", &transcript, "--quiet"])).unwrap();
+        let dated=Transcript::parse(&fs::read_to_string(&transcript).unwrap()).unwrap();
+        assert_eq!(dated.custodians[0].acknowledged, dates::today());
+        assert_eq!(dated.unacknowledged().len(), 2);
+
+        // An answer computed from other share bytes does not match the digest.
+        let text=fs::read_to_string(share(2)).unwrap();
+        let wrong=Acknowledgment::new(read_challenge(&text).unwrap(), 2, b"not the share").unwrap();
+        fs::write(path("ack-2"), wrong.to_text()).unwrap();
+        let refused=record_responses(&args(&["record", &path("ack-2"), "--transcript", &transcript]));
+        assert_eq!(refused.unwrap_err().code, INTEGRITY);
+
+        fs::write(path("plain"), encode_share(1, b"share")).unwrap();
+        assert!(respond(&args(&["respond", &path("plain")])).is_err());
+        assert!(record_responses(&args(&["record", "--transcript", &transcript])).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+use std::io::{self, Read};
+use std::path::PathBuf;
+
+use shamir_rust::crypto::{zeroize, ShamirSS};
+use shamir_rust::encoding::share_fingerprint;
+use shamir_rust::journal::JournalEvent;
+use shamir_rust::json::{Json, JsonExt};
+
+use super::{encode_share, journal, output, tr, write, Args, CliError};
+
+// `split --stdin-multi`: one split per record on stdin, all with the same
+// k and n. Records are newline-delimited (a trailing \r is dropped), or
+// NUL-delimited with --nul for secrets that may contain newlines; empty
+// records are skipped. Without --out the share sets go to stdout as a JSON
+// array; with --out each record gets its own directory, numbered from 1:
+//
+//   [{"record": 1, "bytes": 12, "shares": [{"index": 1, "fingerprint": "..", "share": "1:.."}, ..]}, ..]
+//   OUT/1/share-1-of-3.shs ...
+pub fn split_batch(args:&Args)->Result<(),CliError>{
+    let mut input=Vec::new();
+    io::stdin().read_to_end(&mut input).map_err(|e| e.to_string())?;
+    let result=split_records(args, &input);
+    zeroize(&mut input);
+    result
+}
+
+fn split_records(args:&Args,input:&[u8])->Result<(),CliError>{
+    let k=args.number("threshold")?;
+    let n=args.number("shares")?;
+    let delimiter=if args.flag("nul") { b'\0' } else { b'\n' };
+    let records:Vec<&[u8]>=input.split(|&b| b==delimiter)
+        .map(|record| if delimiter==b'\n' { record.strip_suffix(b"\r").unwrap_or(record) } else { record })
+        .filter(|record| !record.is_empty())
+        .collect();
+    if records.is_empty() {
+        return Err(CliError::usage(tr!("batch-empty")));
+    }
+
+    let out=args.value("out").filter(|v| !v.is_empty()).map(PathBuf::from);
+    let mut sets=Vec::new();
+    let mut fingerprints=Vec::new();
+    for (number, secret) in (1..).zip(&records) {
+        let parts=ShamirSS::split(n, k, secret.to_vec())?;
+        let mut shares=Vec::new();
+        for (index, data) in &parts {
+            let fingerprint=share_fingerprint(*index, data);
+            match &out {
+                Some(out) => {
+                    let dir=out.join(number.to_string());
+                    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+                    let path=dir.join(format!("share-{index}-of-{n}.shs"));
+                    write(&path, encode_share(*index, data).as_bytes())?;
+                    output::result(args, &path.display().to_string(), &[&"share", index, &fingerprint, &path.display()]);
+                }
+                None => shares.push(Json::object([
+                    ("index", (*index as i64).into()),
+                    ("fingerprint", fingerprint.as_str().into()),
+                    ("share", encode_share(*index, data).trim_end().into()),
+                ])),
+            }
+            fingerprints.push(fingerprint);
+        }
+        if out.is_none() {
+            sets.push(Json::object([
+                ("record", (number as i64).into()),
+                ("bytes", (secret.len() as i64).into()),
+                ("shares", Json::Array(shares)),
+            ]));
+        }
+    }
+
+    match &out {
+        Some(out) => output::info(args, &tr!("batch-done", records=records.len(), dir=out.display()), &[&"batch", &records.len(), &out.display()]),
+        None => println!("{}", Json::Array(sets).to_pretty()),
+    }
+    Ok(journal::record(args, JournalEvent::Split, &fingerprints, &format!("batch of {} k={k} n={n}", records.len()))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::decode_share;
+    use shamir_rust::crypto::Parts;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn every_record_gets_its_own_share_set() {
+        let out=std::env::temp_dir().join(format!("shamir-batch-{}", std::process::id()));
+        let _=std::fs::remove_dir_all(&out);
+        let journal=out.with_extension("journal");
+        let _=std::fs::remove_file(&journal);
+        let (dir, log)=(out.display().to_string(), journal.display().to_string());
+        split_records(&args(&["--threshold", "2", "--shares", "3", "--out", &dir, "--journal", &log, "--quiet"]), b"first\r\n\nsecond\n").unwrap();
+
+        for (record, secret) in [("1", b"first".as_slice()), ("2", b"second")] {
+            let parts:Parts=[1, 3].iter().map(|index| decode_share(&std::fs::read_to_string(out.join(record).join(format!("share-{index}-of-3.shs"))).unwrap()).unwrap()).collect();
+            assert_eq!(ShamirSS::join(parts).unwrap(), secret);
+        }
+        assert!(!out.join("3").exists());
+        assert_eq!(shamir_rust::journal::read_journal(&journal).unwrap()[0].fingerprints.len(), 6);
+        std::fs::remove_dir_all(&out).unwrap();
+        std::fs::remove_file(&journal).unwrap();
+    }
+
+    #[test]
+    fn nul_records_keep_their_newlines() {
+        let out=std::env::temp_dir().join(format!("shamir-batch-nul-{}", std::process::id()));
+        let _=std::fs::remove_dir_all(&out);
+        let dir=out.display().to_string();
+        split_records(&args(&["--threshold", "2", "--shares", "2", "--nul", "--out", &dir, "--quiet"]), b"two\nlines\0").unwrap();
+        let parts:Parts=[1, 2].iter().map(|index| decode_share(&std::fs::read_to_string(out.join("1").join(format!("share-{index}-of-2.shs"))).unwrap()).unwrap()).collect();
+        assert_eq!(ShamirSS::join(parts).unwrap(), b"two\nlines");
+        assert!(split_records(&args(&["--threshold", "2", "--shares", "2", "--out", &dir]), b"\n\r\n\n").is_err());
+        std::fs::remove_dir_all(&out).unwrap();
+    }
+}
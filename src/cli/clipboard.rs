@@ -0,0 +1,49 @@
+use std::thread;
+use std::time::Duration;
+
+use arboard::Clipboard;
+use shamir_rust::crypto::zeroize;
+
+use super::tr;
+
+pub const DEFAULT_CLEAR_SECS:u64=30;
+
+// Copies, waits, then clears the clipboard unless the user has since copied
+// something else. Blocks for `clear_after`. The clipboard is the platform's
+// own (via arboard: X11 and Wayland, macOS, Windows), held open while waiting
+// because on X11 the copying process serves the contents itself.
+pub fn copy_with_clear(data:&[u8],clear_after:u64)->Result<(),String>{
+    let text=std::str::from_utf8(data).map_err(|_| tr!("clipboard-not-text"))?;
+    let unavailable=|e:arboard::Error| tr!("clipboard-unavailable", error=e);
+    let mut clipboard=Clipboard::new().map_err(unavailable)?;
+    clipboard.set_text(text).map_err(unavailable)?;
+    eprintln!("{}", tr!("clipboard-copied", secs=clear_after));
+    thread::sleep(Duration::from_secs(clear_after));
+
+    match clipboard.get_text() {
+        Ok(current) if current.trim_end()!=text.trim_end() => {
+            zeroize(&mut current.into_bytes());
+            eprintln!("{}", tr!("clipboard-changed"));
+            Ok(())
+        }
+        current => {
+            if let Ok(current)=current {
+                zeroize(&mut current.into_bytes());
+            }
+            clipboard.clear().map_err(unavailable)?;
+            eprintln!("{}", tr!("clipboard-cleared"));
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn binary_secret_never_reaches_the_clipboard() {
+        // Refused before the clipboard is opened, so this needs no display.
+        assert_eq!(copy_with_clear(&[0xff, 0xfe, 0x00], 0).unwrap_err(), tr!("clipboard-not-text"));
+    }
+}
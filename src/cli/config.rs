@@ -0,0 +1,324 @@
+use std::collections::HashSet;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use super::{i18n, tr, Args, CliError};
+
+// Named profiles of default options, so a recurring ceremony does not retype
+// a dozen flags. The file is `--config FILE`, else
+// $XDG_CONFIG_HOME/shamir/config.toml or ~/.config/shamir/config.toml, in the
+// subset of TOML this reads: `[profile.NAME]` and `[profile.NAME.COMMAND]`
+// tables of `key = value`, where the key is an option name without the
+// dashes and the value a string, an integer, a boolean (true sets a switch,
+// false keeps a broader table from setting it) or an array of strings and
+// integers (a comma-separated option such as `labels`).
+//
+// `--profile NAME` picks a profile, otherwise `default` applies when the
+// file has one. Options on the command line win over the profile's
+// COMMAND table, which wins over its general table.
+
+const DEFAULT_PROFILE:&str="default";
+// Options that choose the profile cannot come from one.
+const RESERVED:&[&str]=&["config", "profile"];
+
+enum Setting{
+    Text(String),
+    Switch(bool),
+}
+
+struct Entry{
+    line:usize,
+    table:String,
+    key:String,
+    setting:Setting,
+}
+
+// Resolved after the language is chosen, which a profile can set.
+#[derive(Debug)]
+enum ConfigError{
+    NoHome,
+    Missing(String),
+    Read(String,String),
+    Malformed(String,usize),
+    Reserved(String,String),
+    UnknownProfile(String,String,Vec<String>),
+}
+
+pub fn apply_profile(command:&str,args:&mut Args)->Result<(),CliError>{
+    load(command, args).map_err(|err| {
+        let _=i18n::select(args);
+        match err {
+            ConfigError::NoHome => CliError::usage(tr!("config-no-home")),
+            ConfigError::Missing(path) => CliError::usage(tr!("config-missing", path=path)),
+            ConfigError::Read(path, error) => CliError::from(format!("{path}: {error}")),
+            ConfigError::Malformed(path, line) => CliError::usage(tr!("config-malformed", path=path, line=line)),
+            ConfigError::Reserved(path, key) => CliError::usage(tr!("config-reserved-key", path=path, key=key)),
+            ConfigError::UnknownProfile(path, profile, available) =>
+                CliError::usage(tr!("config-unknown-profile", profile=profile, path=path, available=available.join(", "))),
+        }
+    })
+}
+
+fn load(command:&str,args:&mut Args)->Result<(),ConfigError>{
+    let explicit_file=args.value("config").filter(|v| !v.is_empty()).map(PathBuf::from);
+    let explicit_profile=args.value("profile").filter(|v| !v.is_empty()).map(|v| v.to_string());
+    let explicit=explicit_file.is_some() || explicit_profile.is_some();
+    let path=match explicit_file.or_else(default_path) {
+        Some(path) => path,
+        None if explicit => return Err(ConfigError::NoHome),
+        None => return Ok(()),
+    };
+    let shown=path.display().to_string();
+    let source=match fs::read_to_string(&path) {
+        Ok(source) => source,
+        Err(e) if e.kind()==ErrorKind::NotFound && !explicit => return Ok(()),
+        Err(e) if e.kind()==ErrorKind::NotFound => return Err(ConfigError::Missing(shown)),
+        Err(e) => return Err(ConfigError::Read(shown, e.to_string())),
+    };
+    let entries=parse(&source).map_err(|line| ConfigError::Malformed(shown.clone(), line))?;
+    let mut profiles=Vec::new();
+    for entry in &entries {
+        let parts:Vec<&str>=entry.table.split('.').collect();
+        if parts.len()<2 || parts.len()>3 || parts[0]!="profile" {
+            return Err(ConfigError::Malformed(shown, entry.line));
+        }
+        if RESERVED.contains(&entry.key.as_str()) {
+            return Err(ConfigError::Reserved(shown, entry.key.clone()));
+        }
+        if !profiles.contains(&parts[1]) {
+            profiles.push(parts[1]);
+        }
+    }
+    let named=explicit_profile.is_some();
+    let profile=explicit_profile.unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    if !profiles.contains(&profile.as_str()) {
+        if named {
+            return Err(ConfigError::UnknownProfile(shown, profile, profiles.iter().map(|p| p.to_string()).collect()));
+        }
+        return Ok(());
+    }
+    let specific=format!("profile.{profile}.{command}");
+    let general=format!("profile.{profile}");
+    let mut decided=HashSet::new();
+    for table in [&specific, &general] {
+        for entry in entries.iter().filter(|e| &e.table==table) {
+            if args.options.contains_key(&entry.key) || !decided.insert(entry.key.as_str()) {
+                continue;
+            }
+            match &entry.setting {
+                Setting::Text(value) => { args.options.insert(entry.key.clone(), value.clone()); }
+                Setting::Switch(true) => { args.options.insert(entry.key.clone(), String::new()); }
+                Setting::Switch(false) => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn default_path()->Option<PathBuf>{
+    let base=match std::env::var_os("XDG_CONFIG_HOME").filter(|v| !v.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME").filter(|v| !v.is_empty())?).join(".config"),
+    };
+    Some(base.join("shamir").join("config.toml"))
+}
+
+// The settings in file order; Err is the line number of the first line that
+// is not one.
+fn parse(source:&str)->Result<Vec<Entry>,usize>{
+    let mut entries=Vec::new();
+    let mut table=String::new();
+    let mut lines=source.lines().enumerate();
+    while let Some((number, line))=lines.next() {
+        let number=number+1;
+        let line=strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(header)=line.strip_prefix('[') {
+            let name=header.strip_suffix(']').ok_or(number)?.trim();
+            if !name.split('.').all(bare_key) {
+                return Err(number);
+            }
+            table=name.to_string();
+            continue;
+        }
+        let (key, value)=line.split_once('=').ok_or(number)?;
+        let key=key.trim();
+        if !bare_key(key) {
+            return Err(number);
+        }
+        // Arrays may run over several lines.
+        let mut text=value.trim().to_string();
+        while text.starts_with('[') && setting(&text).is_none() {
+            let Some((_, next))=lines.next() else { break };
+            text.push(' ');
+            text.push_str(strip_comment(next).trim());
+        }
+        let setting=setting(&text).ok_or(number)?;
+        entries.push(Entry { line: number, table: table.clone(), key: key.to_string(), setting });
+    }
+    Ok(entries)
+}
+
+fn bare_key(key:&str)->bool{
+    !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c=='-' || c=='_')
+}
+
+// The line up to a `#` outside a string.
+fn strip_comment(line:&str)->&str{
+    let mut quote=None;
+    let mut escaped=false;
+    for (pos, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped=false,
+            Some('"') if c=='\\' => escaped=true,
+            Some(q) if c==q => quote=None,
+            Some(_) => {}
+            None if c=='"' || c=='\'' => quote=Some(c),
+            None if c=='#' => return &line[..pos],
+            None => {}
+        }
+    }
+    line
+}
+
+fn setting(text:&str)->Option<Setting>{
+    match text {
+        "true" => return Some(Setting::Switch(true)),
+        "false" => return Some(Setting::Switch(false)),
+        _ => {}
+    }
+    let Some(mut rest)=text.strip_prefix('[') else {
+        let (value, rest)=scalar(text)?;
+        return rest.trim().is_empty().then_some(Setting::Text(value));
+    };
+    let mut items=Vec::new();
+    loop {
+        rest=rest.trim_start();
+        if let Some(after)=rest.strip_prefix(']') {
+            rest=after;
+            break;
+        }
+        let (item, after)=scalar(rest)?;
+        // Items become one comma-separated option value.
+        if item.contains(',') {
+            return None;
+        }
+        items.push(item);
+        rest=after.trim_start();
+        match rest.strip_prefix(',') {
+            Some(after) => rest=after,
+            None if rest.starts_with(']') => {}
+            None => return None,
+        }
+    }
+    rest.trim().is_empty().then(|| Setting::Text(items.join(",")))
+}
+
+// A string or an integer, and the text after it.
+fn scalar(text:&str)->Option<(String,&str)>{
+    if let Some(body)=text.strip_prefix('\'') {
+        let end=body.find('\'')?;
+        return Some((body[..end].to_string(), &body[end+1..]));
+    }
+    if let Some(body)=text.strip_prefix('"') {
+        let mut value=String::new();
+        let mut chars=body.char_indices();
+        while let Some((pos, c))=chars.next() {
+            match c {
+                '"' => return Some((value, &body[pos+1..])),
+                '\\' => value.push(match chars.next()?.1 {
+                    '"' => '"',
+                    '\\' => '\\',
+                    'n' => '\n',
+                    't' => '\t',
+                    _ => return None,
+                }),
+                _ => value.push(c),
+            }
+        }
+        return None;
+    }
+    let end=text.find(|c:char| !(c.is_ascii_digit() || c=='_' || c=='+' || c=='-')).unwrap_or(text.len());
+    let digits:String=text[..end].chars().filter(|&c| c!='_' && c!='+').collect();
+    digits.parse::<i64>().ok().map(|n| (n.to_string(), &text[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CONFIG:&str=r##"
+# Quarterly key ceremony.
+[profile.default]
+threshold = 3
+shares = 5
+
+[profile.vault]
+threshold = 2
+labels = [
+  "alice", "bob",  # primary
+  'carol',
+]
+quiet = true
+name-template = "vault-{index}.shs" # not a comment: "#"
+
+[profile.vault.join]
+quiet = false
+strict = true
+"##;
+
+    fn load_with(config:&str,words:&[&str])->Result<Args,ConfigError>{
+        static FILES:AtomicUsize=AtomicUsize::new(0);
+        let path=std::env::temp_dir().join(format!("shamir-config-{}-{}.toml", std::process::id(), FILES.fetch_add(1, Ordering::Relaxed)));
+        fs::write(&path, config).unwrap();
+        let mut raw:Vec<String>=vec!["--config".to_string(), path.display().to_string()];
+        raw.extend(words.iter().map(|w| w.to_string()));
+        let command=words.first().copied().unwrap_or("split");
+        let mut args=Args::parse(&raw);
+        let result=load(command, &mut args);
+        fs::remove_file(&path).unwrap();
+        result.map(|()| args)
+    }
+
+    #[test]
+    fn profiles_fill_in_unset_options() {
+        let args=load_with(CONFIG, &["split", "--shares", "7"]).unwrap();
+        assert_eq!((args.value("threshold"), args.value("shares")), (Some("3"), Some("7")));
+
+        let args=load_with(CONFIG, &["split", "--profile", "vault"]).unwrap();
+        assert_eq!(args.value("labels"), Some("alice,bob,carol"));
+        assert_eq!(args.value("name-template"), Some("vault-{index}.shs"));
+        assert!(args.flag("quiet"));
+        assert_eq!(args.value("shares"), None);
+
+        // The command table wins over the general one, and `false` keeps it
+        // from setting the switch.
+        let args=load_with(CONFIG, &["join", "--profile", "vault"]).unwrap();
+        assert!(!args.flag("quiet"));
+        assert!(args.flag("strict"));
+    }
+
+    #[test]
+    fn malformed_files_name_the_line() {
+        assert!(matches!(load_with("[profile.a]\nthreshold 3\n", &[]), Err(ConfigError::Malformed(_, 2))));
+        assert!(matches!(load_with("[profile.a]\nlabels = [\"a,b\"]\n", &[]), Err(ConfigError::Malformed(_, 2))));
+        assert!(matches!(load_with("[other]\nquiet = true\n", &[]), Err(ConfigError::Malformed(_, 2))));
+        assert!(matches!(load_with("[profile.a]\nprofile = \"b\"\n", &[]), Err(ConfigError::Reserved(_, _))));
+        assert!(matches!(load_with(CONFIG, &["split", "--profile", "missing"]), Err(ConfigError::UnknownProfile(_, _, _))));
+        assert!(load_with("[profile.a]\nquiet = true\n", &[]).is_ok());
+    }
+
+    #[test]
+    fn values_parse_as_toml_scalars() {
+        assert!(matches!(setting("\"tab\\there\""), Some(Setting::Text(v)) if v=="tab\there"));
+        assert!(matches!(setting("1_000"), Some(Setting::Text(v)) if v=="1000"));
+        assert!(matches!(setting("[1, 'two']"), Some(Setting::Text(v)) if v=="1,two"));
+        assert!(setting("\"open").is_none());
+        assert!(setting("yes").is_none());
+        assert_eq!(strip_comment("key = 'a#b' # note"), "key = 'a#b' ");
+    }
+}
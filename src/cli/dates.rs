@@ -0,0 +1,140 @@
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{tr, Args, CliError};
+
+// The clock behind every date and timestamp the CLI writes (transcripts,
+// {date} in file names, drill schedules, revocation lists, journals), so a
+// reproducible run can pin it. `--logical-time YYYY-MM-DD` or
+// `--logical-time @SECONDS` fixes it, `--logical-time omit` leaves dates out
+// (commands that must record one then refuse to run), and without the option
+// SOURCE_DATE_EPOCH, the reproducible-builds convention, is honoured. Dates
+// are UTC and never formatted through the locale. Authorization (token
+// expiry) never uses this clock, only the system one.
+enum Clock{
+    System,
+    Fixed(u64),
+    Omit,
+}
+
+static CLOCK:OnceLock<Clock>=OnceLock::new();
+
+pub fn select(args:&Args)->Result<(),CliError>{
+    let clock=match args.value("logical-time") {
+        Some("omit") => Clock::Omit,
+        Some(value) if !value.is_empty() => Clock::Fixed(parse_time(value).ok_or_else(|| CliError::usage(tr!("logical-time-malformed", value=value)))?),
+        _ => match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(secs) if !secs.trim().is_empty() => Clock::Fixed(secs.trim().parse().map_err(|_| CliError::usage(tr!("source-date-epoch-malformed", value=secs)))?),
+            _ => Clock::System,
+        },
+    };
+    let _=CLOCK.set(clock);
+    Ok(())
+}
+
+// Seconds since the epoch; None with --logical-time omit.
+pub fn now()->Option<u64>{
+    match CLOCK.get().unwrap_or(&Clock::System) {
+        Clock::System => Some(SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)),
+        Clock::Fixed(secs) => Some(*secs),
+        Clock::Omit => None,
+    }
+}
+
+// Current UTC date as YYYY-MM-DD.
+pub fn today()->Option<String>{
+    days_from_now(0)
+}
+
+pub fn days_from_now(days:i64)->Option<String>{
+    now().map(|secs| civil_from_days((secs/86_400) as i64+days))
+}
+
+// For commands that cannot do without a date; `what` names the option or
+// command that needs it.
+pub fn required_today(what:&str)->Result<String,CliError>{
+    today().ok_or_else(|| CliError::usage(tr!("logical-time-omitted", what=what)))
+}
+
+// Days since 1970-01-01 to YYYY-MM-DD (proleptic Gregorian).
+fn civil_from_days(days:i64)->String{
+    let days=days+719_468;
+    let era=days.div_euclid(146_097);
+    let doe=days.rem_euclid(146_097);
+    let yoe=(doe-doe/1_460+doe/36_524-doe/146_096)/365;
+    let doy=doe-(365*yoe+yoe/4-yoe/100);
+    let mp=(5*doy+2)/153;
+    let day=doy-(153*mp+2)/5+1;
+    let month=if mp<10 { mp+3 } else { mp-9 };
+    let year=yoe+era*400+i64::from(month<=2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+// The inverse of `civil_from_days`.
+fn days_from_civil(year:i64,month:i64,day:i64)->i64{
+    let year=if month<=2 { year-1 } else { year };
+    let era=year.div_euclid(400);
+    let yoe=year.rem_euclid(400);
+    let doy=(153*(if month>2 { month-3 } else { month+9 })+2)/5+day-1;
+    let doe=yoe*365+yoe/4-yoe/100+doy;
+    era*146_097+doe-719_468
+}
+
+// YYYY-MM-DD (midnight UTC) or @SECONDS.
+pub fn parse_time(value:&str)->Option<u64>{
+    if let Some(secs)=value.strip_prefix('@') {
+        return secs.parse().ok();
+    }
+    if !is_date(value) {
+        return None;
+    }
+    let field=|range:std::ops::Range<usize>| value[range].parse::<i64>().ok();
+    let (year, month, day)=(field(0..4)?, field(5..7)?, field(8..10)?);
+    let days=days_from_civil(year, month, day);
+    // Rejects 2026-02-30 and the like.
+    if civil_from_days(days)!=value {
+        return None;
+    }
+    u64::try_from(days*86_400).ok()
+}
+
+// The UTC date of `secs`, with the exact time as @SECONDS.
+pub fn format_time(secs:u64)->String{
+    format!("{} (@{secs})", civil_from_days((secs/86_400) as i64))
+}
+
+pub fn is_date(value:&str)->bool{
+    let bytes=value.as_bytes();
+    bytes.len()==10 && bytes[4]==b'-' && bytes[7]==b'-'
+        && bytes.iter().enumerate().all(|(i, b)| i==4 || i==7 || b.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates_round_trip() {
+        assert_eq!(civil_from_days(0), "1970-01-01");
+        assert_eq!(civil_from_days(-1), "1969-12-31");
+        assert_eq!(civil_from_days(11_016), "2000-02-29");
+        for days in (-800_000..800_000).step_by(997) {
+            let date=civil_from_days(days);
+            let field=|range:std::ops::Range<usize>| date[range].parse::<i64>().unwrap();
+            assert_eq!(days_from_civil(field(0..4), field(5..7), field(8..10)), days, "{date}");
+        }
+    }
+
+    #[test]
+    fn times_parse_as_dates_or_seconds() {
+        assert_eq!(parse_time("1970-01-02"), Some(86_400));
+        assert_eq!(parse_time("@1700000000"), Some(1_700_000_000));
+        assert_eq!(parse_time("2024-02-29"), Some(19_782*86_400));
+        for bad in ["2023-02-29", "2026-13-01", "2026-1-01", "26-01-01xx", "@soon", "1969-12-31"] {
+            assert_eq!(parse_time(bad), None, "{bad}");
+        }
+        assert_eq!(format_time(86_400+5), "1970-01-02 (@86405)");
+        assert!(is_date("2026-10-16"));
+        assert!(!is_date("2026/10/16"));
+    }
+}
@@ -0,0 +1,167 @@
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use shamir_rust::dealer::{DealerIdentity, DealerKey};
+
+use super::{dates, output, read_string, tr, write, Args, CliError};
+
+// The dealer's signing key for transcripts, hints, recovery instructions and
+// revocation lists, kept in a directory of its own (mode 0700) holding the
+// `identity` file of `shamir_rust::dealer`: the Ed25519 key chain and the
+// current secret key, encrypted under the passphrase.
+//
+// `export` writes the public key chain custodians pin and pass as
+// --dealer-key. `rotate` makes a new key certified by the current one, and
+// `check` tells a custodian who pinned the old export whether a new one
+// descends from it. `--sign-with DIR` signs with the directory's current key.
+
+const IDENTITY_FILE:&str="identity";
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("init") => init(args),
+        Some("export") => export(args),
+        Some("rotate") => rotate(args),
+        Some("show") => show(args),
+        Some("check") => check(args),
+        _ => Err(CliError::with_usage(tr!("unknown-dealer-command"))),
+    }
+}
+
+// True when `path` is a directory made by `dealer init`.
+pub fn is_home(path:&Path)->bool{
+    path.join(IDENTITY_FILE).is_file()
+}
+
+// Signature by the current key of the dealer directory `home`.
+pub fn sign(home:&Path,passphrase:Option<&Path>,data:&[u8])->Result<Vec<u8>,String>{
+    let passphrase=passphrase.ok_or_else(|| tr!("dealer-needs-passphrase", home=home.display()))?;
+    load(home)?.sign(data, &read_passphrase(passphrase)?)
+}
+
+// Checks a dealer signature against the exported key chain in `keyring`.
+pub fn verify(keyring:&str,data:&[u8],signature:&[u8])->Result<String,String>{
+    let key=DealerKey::parse(&read_string(Path::new(keyring))?).map_err(|e| format!("{keyring}: {e}"))?;
+    key.verify(data, signature).map_err(|_| tr!("signature-invalid", keyring=keyring))
+}
+
+fn load(home:&Path)->Result<DealerIdentity,String>{
+    DealerIdentity::load(&home.join(IDENTITY_FILE))
+}
+
+// The first line of the passphrase file, as gpg's --passphrase-file reads it.
+fn read_passphrase(path:&Path)->Result<String,String>{
+    match read_string(path)?.lines().next() {
+        Some(line) if !line.is_empty() => Ok(line.to_string()),
+        _ => Err(tr!("dealer-empty-passphrase", path=path.display())),
+    }
+}
+
+fn home(args:&Args)->Result<&Path,CliError>{
+    Ok(Path::new(args.required("dealer-home")?))
+}
+
+fn init(args:&Args)->Result<(),CliError>{
+    let home=home(args)?;
+    if home.exists() {
+        return Err(tr!("dealer-exists", home=home.display()).into());
+    }
+    let passphrase=read_passphrase(Path::new(args.required("passphrase-file")?))?;
+    let identity=DealerIdentity::generate(args.required("uid")?, &passphrase)?;
+    let mut builder=fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+    builder.create(home).map_err(|e| format!("{}: {e}", home.display()))?;
+    identity.save(&home.join(IDENTITY_FILE))?;
+    let fingerprint=identity.fingerprint();
+    output::info(args, &tr!("dealer-created", fingerprint=fingerprint, home=home.display()), &[&"dealer", &fingerprint, &home.display()]);
+    Ok(())
+}
+
+fn export(args:&Args)->Result<(),CliError>{
+    let identity=load(home(args)?)?;
+    let key=identity.public_key().to_text();
+    let fingerprint=identity.fingerprint();
+    match args.value("out").filter(|v| !v.is_empty()) {
+        Some(out) => {
+            write(Path::new(out), key.as_bytes())?;
+            output::info(args, &tr!("dealer-exported", fingerprint=fingerprint, path=out), &[&"dealer", &fingerprint, &out]);
+        }
+        None => std::io::stdout().write_all(key.as_bytes()).map_err(|e| e.to_string())?,
+    }
+    Ok(())
+}
+
+fn rotate(args:&Args)->Result<(),CliError>{
+    let home=home(args)?;
+    let mut identity=load(home)?;
+    let passphrase=read_passphrase(Path::new(args.required("passphrase-file")?))?;
+    let previous=identity.fingerprint();
+    identity.rotate(args.value("uid").filter(|v| !v.is_empty()), &passphrase, &dates::today().unwrap_or_default())?;
+    identity.save(&home.join(IDENTITY_FILE))?;
+    let fingerprint=identity.fingerprint();
+    output::info(args, &tr!("dealer-rotated", fingerprint=fingerprint, previous=previous), &[&"dealer", &fingerprint, &previous]);
+    Ok(())
+}
+
+fn show(args:&Args)->Result<(),CliError>{
+    let identity=load(home(args)?)?;
+    let fingerprint=identity.fingerprint();
+    output::result(args, &tr!("dealer-current", fingerprint=fingerprint), &[&"current", &fingerprint]);
+    for (fingerprint, date) in &identity.retired {
+        output::result(args, &tr!("dealer-retired", fingerprint=fingerprint, date=date), &[&"retired", fingerprint, date]);
+    }
+    Ok(())
+}
+
+// Whether the export in --dealer-key was certified, through rotations, by
+// the key in the --pinned export.
+fn check(args:&Args)->Result<(),CliError>{
+    let parse=|option:&str| -> Result<DealerKey,CliError> {
+        let path=args.required(option)?;
+        DealerKey::parse(&read_string(Path::new(path))?).map_err(|e| CliError::integrity(format!("{path}: {e}")))
+    };
+    let key=parse("dealer-key")?;
+    let pinned=parse("pinned")?;
+    let (fingerprint, previous)=(key.current().fingerprint(), pinned.current().fingerprint());
+    if !key.follows(&pinned) {
+        return Err(CliError::integrity(tr!("dealer-not-certified", fingerprint=fingerprint, previous=previous)));
+    }
+    output::result(args, &tr!("dealer-certified", fingerprint=fingerprint, previous=previous), &[&"certified", &fingerprint, &previous]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::keyring;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn directory_signs_without_gpg() {
+        let root=std::env::temp_dir().join(format!("shamir-dealer-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        fs::write(path("passphrase"), "passphrase\n").unwrap();
+        let (home, passphrase, pinned, current)=(path("home"), path("passphrase"), path("pinned.pub"), path("current.pub"));
+        init(&args(&["init", "--dealer-home", &home, "--uid", "Dealer <dealer@example.org>", "--passphrase-file", &passphrase, "--quiet"])).unwrap();
+        export(&args(&["export", "--dealer-home", &home, "--out", &pinned, "--quiet"])).unwrap();
+
+        assert!(keyring::sign(&home, None, b"hint").is_err());
+        let signature=keyring::sign(&home, Some(&passphrase), b"hint").unwrap();
+        assert!(keyring::verify(&pinned, b"hint", &signature).is_ok());
+        assert!(keyring::verify(&pinned, b"edited", &signature).is_err());
+
+        rotate(&args(&["rotate", "--dealer-home", &home, "--passphrase-file", &passphrase, "--quiet"])).unwrap();
+        export(&args(&["export", "--dealer-home", &home, "--out", &current, "--quiet"])).unwrap();
+        check(&args(&["check", "--dealer-key", &current, "--pinned", &pinned, "--quiet"])).unwrap();
+        assert!(check(&args(&["check", "--dealer-key", &pinned, "--pinned", &current, "--quiet"])).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
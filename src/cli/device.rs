@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use shamir_rust::device::DeviceFactor;
+use shamir_rust::encoding::{decode_any_share, share_fingerprint};
+use shamir_rust::hardening::write_new_private;
+use shamir_rust::sha256::{from_hex, to_hex};
+
+use super::{encode_share, output, read_string, tr, Args, CliError};
+
+// Device factors for coupled splits (`split`/`join` with --device or
+// --factor-file). A named factor lives in the OS keychain: macOS `security`,
+// elsewhere `secret-tool` from libsecret. The factor never appears on a
+// command line; both tools receive it on stdin.
+const SERVICE:&str="shamir-device-factor";
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("init") => init(args),
+        Some("export") => export(args),
+        Some("escrow") => escrow(args),
+        Some("import") => import(args),
+        _ => Err(CliError::with_usage(tr!("unknown-device-command"))),
+    }
+}
+
+// Where a factor is kept, from --device NAME or --factor-file FILE.
+enum Store<'a>{
+    Keychain(&'a str),
+    File(&'a Path),
+}
+
+fn store<'a>(device:Option<&'a str>,file:Option<&'a str>)->Result<Option<Store<'a>>,CliError>{
+    match (device.filter(|v| !v.is_empty()), file.filter(|v| !v.is_empty())) {
+        (Some(_), Some(_)) => Err(CliError::usage(tr!("device-and-factor-file"))),
+        (Some(name), None) => Ok(Some(Store::Keychain(name))),
+        (None, Some(path)) => Ok(Some(Store::File(Path::new(path)))),
+        (None, None) => Ok(None),
+    }
+}
+
+fn required_store(args:&Args)->Result<Store<'_>,CliError>{
+    store(args.value("device"), args.value("factor-file"))?.ok_or_else(|| CliError::with_usage(tr!("missing-device")))
+}
+
+// The factor a split or join is coupled to, if any.
+pub fn load(device:Option<&str>,file:Option<&str>)->Result<Option<DeviceFactor>,CliError>{
+    match store(device, file)? {
+        Some(store) => Ok(Some(read_factor(&store)?)),
+        None => Ok(None),
+    }
+}
+
+fn read_factor(store:&Store)->Result<DeviceFactor,CliError>{
+    match store {
+        Store::File(path) => Ok(DeviceFactor::parse(&read_string(path)?).map_err(|e| CliError::integrity(format!("{}: {e}", path.display())))?),
+        Store::Keychain(name) => {
+            let hex=keychain_lookup(name)?;
+            let bytes=from_hex(hex.trim()).ok_or_else(|| CliError::integrity(tr!("keychain-malformed", name=name)))?;
+            Ok(DeviceFactor::from_bytes(&bytes).map_err(CliError::integrity)?)
+        }
+    }
+}
+
+fn save_factor(store:&Store,factor:&DeviceFactor)->Result<(),CliError>{
+    match store {
+        Store::File(path) => {
+            if path.exists() {
+                return Err(tr!("factor-exists", location=path.display()).into());
+            }
+            write_private(path, factor.to_text().as_bytes())?;
+        }
+        Store::Keychain(name) => {
+            if keychain_lookup(name).is_ok() {
+                return Err(tr!("factor-exists", location=name).into());
+            }
+            keychain_store(name, &to_hex(factor.as_bytes()))?;
+        }
+    }
+    Ok(())
+}
+
+fn location(store:&Store)->String{
+    match store {
+        Store::File(path) => path.display().to_string(),
+        Store::Keychain(name) => format!("keychain:{name}"),
+    }
+}
+
+// Generates a factor and stores it; refuses to replace an existing one.
+fn init(args:&Args)->Result<(),CliError>{
+    let store=required_store(args)?;
+    let factor=DeviceFactor::generate();
+    save_factor(&store, &factor)?;
+    output::result(args, &tr!("factor-created", fingerprint=factor.fingerprint(), location=location(&store)),
+        &[&"factor", &factor.fingerprint(), &location(&store)]);
+    Ok(())
+}
+
+// Prints (or writes with --out) the factor's text form, for an offline backup.
+fn export(args:&Args)->Result<(),CliError>{
+    let factor=read_factor(&required_store(args)?)?;
+    match args.value("out").filter(|v| !v.is_empty()) {
+        Some(out) => Ok(write_private(Path::new(out), factor.to_text().as_bytes())?),
+        None => {
+            print!("{}", factor.to_text());
+            Ok(())
+        }
+    }
+}
+
+// Splits the factor among an escrow group, one share file per member.
+fn escrow(args:&Args)->Result<(),CliError>{
+    let factor=read_factor(&required_store(args)?)?;
+    let k=args.number("threshold")?;
+    let n=args.number("shares")?;
+    let out=PathBuf::from(args.value("out").filter(|v| !v.is_empty()).unwrap_or("."));
+    let parts=factor.escrow(n, k)?;
+    std::fs::create_dir_all(&out).map_err(|e| format!("{}: {e}", out.display()))?;
+    for (index, data) in &parts {
+        let path=out.join(format!("factor-{}-{index}-of-{n}.shs", factor.fingerprint()));
+        write_private(&path, encode_share(*index, data).as_bytes())?;
+        output::result(args, &path.display().to_string(), &[&"share", index, &share_fingerprint(*index, data), &path.display()]);
+    }
+    Ok(())
+}
+
+// Restores a factor from an exported copy or from escrow shares.
+fn import(args:&Args)->Result<(),CliError>{
+    let files=&args.positional[1..];
+    let store=required_store(args)?;
+    if files.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-files")));
+    }
+    let texts=files.iter().map(|file| read_string(Path::new(file))).collect::<Result<Vec<_>,_>>()?;
+    let factor=match texts.as_slice() {
+        [text] if text.trim_start().starts_with("shamir-device-factor") => {
+            DeviceFactor::parse(text).map_err(|e| CliError::integrity(format!("{}: {e}", files[0])))?
+        }
+        _ => {
+            let mut parts=BTreeMap::new();
+            for (file, text) in files.iter().zip(&texts) {
+                let (index, data)=decode_any_share(text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+                if parts.insert(index, data).is_some() {
+                    return Err(CliError::integrity(tr!("share-given-twice", file=file, index=index)));
+                }
+            }
+            DeviceFactor::from_escrow(parts).map_err(CliError::integrity)?
+        }
+    };
+    save_factor(&store, &factor)?;
+    output::result(args, &tr!("factor-imported", fingerprint=factor.fingerprint(), location=location(&store)),
+        &[&"factor", &factor.fingerprint(), &location(&store)]);
+    Ok(())
+}
+
+// Factors and shares are never written over an existing file.
+fn write_private(path:&Path,data:&[u8])->Result<(),String>{
+    write_new_private(path, data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_store(name:&str,hex:&str)->Result<(),String>{
+    // `security -i` reads commands from stdin, keeping the value off argv.
+    let command=format!("add-generic-password -s {SERVICE} -a {name} -w {hex}\n");
+    run_tool(Command::new("security").arg("-i"), command.as_bytes()).map(|_| ())
+}
+
+#[cfg(target_os = "macos")]
+fn keychain_lookup(name:&str)->Result<String,String>{
+    run_tool(Command::new("security").args(["find-generic-password", "-s", SERVICE, "-a", name, "-w"]), b"")
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keychain_store(name:&str,hex:&str)->Result<(),String>{
+    let label=format!("--label=shamir device factor {name}");
+    run_tool(Command::new("secret-tool").args(["store", &label, "service", SERVICE, "account", name]), hex.as_bytes()).map(|_| ())
+}
+
+#[cfg(not(target_os = "macos"))]
+fn keychain_lookup(name:&str)->Result<String,String>{
+    run_tool(Command::new("secret-tool").args(["lookup", "service", SERVICE, "account", name]), b"")
+}
+
+fn run_tool(command:&mut Command,input:&[u8])->Result<String,String>{
+    let mut child=command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| tr!("keychain-unavailable", error=e))?;
+    child.stdin.take().ok_or_else(|| tr!("keychain-unavailable", error="stdin"))?.write_all(input).map_err(|e| e.to_string())?;
+    let out=child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() || (out.stdout.is_empty() && input.is_empty()) {
+        return Err(tr!("keychain-failed"));
+    }
+    String::from_utf8(out.stdout).map_err(|_| tr!("keychain-failed"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::cli::error::BAD_ARGUMENTS;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn factor_file_survives_export_and_escrow() {
+        let root=std::env::temp_dir().join(format!("shamir-device-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let fingerprint=|file:&str| load(None, Some(file)).unwrap().unwrap().fingerprint();
+
+        init(&args(&["init", "--factor-file", &path("factor"), "--quiet"])).unwrap();
+        assert!(init(&args(&["init", "--factor-file", &path("factor"), "--quiet"])).is_err());
+        export(&args(&["export", "--factor-file", &path("factor"), "--out", &path("backup")])).unwrap();
+        import(&args(&["import", &path("backup"), "--factor-file", &path("restored"), "--quiet"])).unwrap();
+        assert_eq!(fingerprint(&path("restored")), fingerprint(&path("factor")));
+
+        escrow(&args(&["escrow", "--factor-file", &path("factor"), "--threshold", "2", "--shares", "3", "--out", &path("escrow"), "--quiet"])).unwrap();
+        let mut shares:Vec<String>=fs::read_dir(path("escrow")).unwrap().map(|e| e.unwrap().path().display().to_string()).collect();
+        shares.sort();
+        assert_eq!(shares.len(), 3);
+        import(&args(&["import", &shares[0], &shares[2], "--factor-file", &path("rebuilt"), "--quiet"])).unwrap();
+        assert_eq!(fingerprint(&path("rebuilt")), fingerprint(&path("factor")));
+        assert!(import(&args(&["import", &shares[1], &shares[1], "--factor-file", &path("twice")])).is_err());
+
+        assert_eq!(load(Some("laptop"), Some(&path("factor"))).err().map(|e| e.code), Some(BAD_ARGUMENTS));
+        assert!(load(None, Some("")).unwrap().is_none());
+        assert_eq!(export(&args(&["export"])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
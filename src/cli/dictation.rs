@@ -0,0 +1,163 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::Path;
+
+use shamir_rust::encoding::{decode_any_share, decode_bech32, encode_bech32};
+use shamir_rust::sha256::{to_hex, Sha256};
+
+use super::{read_string, tr, Args, CliError};
+
+// Phone-based recovery: a custodian reads their share as bech32 groups of
+// four characters and the operator types them in. Every few groups both
+// sides see a short checkpoint code over everything entered so far, so a
+// mistyped group is caught at the next checkpoint instead of after the whole
+// share fails its checksum. Only the data part after "shamir1" is dictated.
+
+const GROUP_LEN:usize=4;
+const CHECKPOINT_EVERY:usize=4;
+const BECH32_PREFIX:&str="shamir1";
+const BECH32_CHARS:&str="qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+fn checkpoint(prefix:&str)->String{
+    to_hex(&Sha256::digest(prefix.as_bytes())[..2])
+}
+
+// Custodian side: prints the share in groups with the checkpoint codes the
+// operator will read back.
+pub fn dictate(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let (index, data)=decode_any_share(&read_string(Path::new(file))?)?;
+    let encoded=encode_bech32(index, &data);
+    let text=&encoded[BECH32_PREFIX.len()..];
+    let groups:Vec<&str>=text.as_bytes().chunks(GROUP_LEN).map(|g| std::str::from_utf8(g).unwrap()).collect();
+    for (line, chunk) in groups.chunks(CHECKPOINT_EVERY).enumerate() {
+        let end=((line+1)*CHECKPOINT_EVERY*GROUP_LEN).min(text.len());
+        println!("{:<20} {}", chunk.join(" "), tr!("dictate-check", code=checkpoint(&text[..end])));
+    }
+    Ok(())
+}
+
+// Photography-resistant display for in-person ceremonies: the share is shown
+// a segment at a time (`--groups` groups, by default one dictation line with
+// its check code), the operator confirms each one and the screen and its
+// scrollback are cleared in between, so no single photograph captures the
+// whole share. Refuses to run unless stdout is a terminal, where a redirect
+// would capture everything at once.
+pub fn reveal(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let per_segment=match args.value("groups") {
+        Some(value) if !value.is_empty() => value.parse::<usize>().ok().filter(|&n| n>0)
+            .ok_or_else(|| CliError::usage(tr!("option-not-number", name="groups")))?,
+        _ => CHECKPOINT_EVERY,
+    };
+    if !io::stdout().is_terminal() {
+        return Err(CliError::usage(tr!("reveal-not-terminal")));
+    }
+    let (index, data)=decode_any_share(&read_string(Path::new(file))?)?;
+    let encoded=encode_bech32(index, &data);
+    let text=&encoded[BECH32_PREFIX.len()..];
+    let segment_len=per_segment*GROUP_LEN;
+    let segments=text.len().div_ceil(segment_len);
+    let stdin=io::stdin();
+    let mut lines=stdin.lock().lines();
+    let mut out=io::stdout();
+    let mut current=0;
+    while current<segments {
+        let start=current*segment_len;
+        let end=(start+segment_len).min(text.len());
+        let groups:Vec<&str>=text.as_bytes()[start..end].chunks(GROUP_LEN).map(|g| std::str::from_utf8(g).unwrap()).collect();
+        clear_screen(&mut out)?;
+        println!("{}", tr!("reveal-segment", index=index, number=current+1, segments=segments, first=start/GROUP_LEN+1, last=end.div_ceil(GROUP_LEN)));
+        println!();
+        println!("    {}    {}", groups.join(" "), tr!("dictate-check", code=checkpoint(&text[..end])));
+        println!();
+        print!("{} ", tr!("reveal-prompt"));
+        out.flush().map_err(|e| e.to_string())?;
+        let answer=match lines.next() {
+            Some(line) => line.map_err(|e| e.to_string())?,
+            None => break,
+        };
+        match answer.trim() {
+            "q" => break,
+            "b" => current=current.saturating_sub(1),
+            _ => current+=1,
+        }
+    }
+    clear_screen(&mut out)?;
+    println!("{}", tr!("reveal-done", index=index));
+    Ok(())
+}
+
+// Clears the screen and the terminal's scrollback.
+fn clear_screen(out:&mut io::Stdout)->Result<(),String>{
+    out.write_all(b"\x1b[2J\x1b[3J\x1b[H").and_then(|()| out.flush()).map_err(|e| e.to_string())
+}
+
+// Operator side of `join --dictation`: reads shares group by group until an
+// empty share is entered.
+pub fn read_dictated_shares()->Result<Vec<(i32,Vec<u8>)>,String>{
+    let stdin=io::stdin();
+    let mut lines=stdin.lock().lines();
+    let mut shares=Vec::new();
+    loop {
+        eprintln!("{}", tr!("dictation-share", number=shares.len()+1, group_len=GROUP_LEN));
+        let mut text=String::new();
+        let mut groups=0;
+        loop {
+            eprint!("  {} ", tr!("dictation-group", number=groups+1));
+            io::stderr().flush().map_err(|e| e.to_string())?;
+            let line=match lines.next() {
+                Some(line) => line.map_err(|e| e.to_string())?,
+                None => break,
+            };
+            let entered:String=line.split_whitespace().collect::<String>().to_ascii_lowercase();
+            if entered.is_empty() {
+                break;
+            }
+            if let Some(bad)=entered.chars().find(|c| !BECH32_CHARS.contains(*c)) {
+                eprintln!("  {}", tr!("dictation-bad-character", character=bad));
+                continue;
+            }
+            text.push_str(&entered);
+            groups+=entered.len().div_ceil(GROUP_LEN);
+            if groups%CHECKPOINT_EVERY==0 {
+                eprintln!("  {}", tr!("dictation-checkpoint", code=checkpoint(&text)));
+            }
+        }
+        if text.is_empty() {
+            return Ok(shares);
+        }
+        eprintln!("  {}", tr!("dictation-final-check", code=checkpoint(&text)));
+        match decode_bech32(&format!("{BECH32_PREFIX}{text}")) {
+            Ok(share) => {
+                eprintln!("  {}", tr!("dictation-accepted", index=share.0));
+                shares.push(share);
+            }
+            Err(e) => eprintln!("  {}", tr!("dictation-retry", error=e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dictated_groups_rebuild_the_share() {
+        let encoded=encode_bech32(3, b"dictated share");
+        let text=&encoded[BECH32_PREFIX.len()..];
+        assert!(text.chars().all(|c| BECH32_CHARS.contains(c)));
+        let groups:Vec<&str>=text.as_bytes().chunks(GROUP_LEN).map(|g| std::str::from_utf8(g).unwrap()).collect();
+        let entered:String=groups.join(" ").split_whitespace().collect();
+        assert_eq!(decode_bech32(&format!("{BECH32_PREFIX}{entered}")).unwrap(), (3, b"dictated share".to_vec()));
+    }
+
+    #[test]
+    fn checkpoints_catch_a_mistyped_group() {
+        let encoded=encode_bech32(3, b"dictated share");
+        let text=&encoded[BECH32_PREFIX.len()..];
+        let prefix=&text[..CHECKPOINT_EVERY*GROUP_LEN];
+        let swapped=if prefix.starts_with('q') { prefix.replacen('q', "p", 1) } else { format!("q{}", &prefix[1..]) };
+        assert_ne!(checkpoint(prefix), checkpoint(&swapped));
+        assert_eq!(checkpoint(prefix).len(), 4);
+    }
+}
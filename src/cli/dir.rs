@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use shamir_rust::archive::Archive;
+use shamir_rust::encoding::share_fingerprint;
+use shamir_rust::hybrid::HybridSS;
+use shamir_rust::journal::JournalEvent;
+
+use super::{decode_share, encode_share, journal, output, read, read_string, revocation, tr, write, Args, CliError};
+
+// Contents of k custodian bundles, checked to describe the same payload.
+// The manifest is the associated data of the sealed payload, so a bundle
+// whose manifest.txt was edited does not open.
+struct Bundles{
+    parts:BTreeMap<i32,Vec<u8>>,
+    sealed:Vec<u8>,
+    manifest:String,
+}
+
+pub fn split_dir(args:&Args)->Result<(),CliError>{
+    let dir=PathBuf::from(args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-directory")))?);
+    let k=args.number("threshold")?;
+    let n=args.number("shares")?;
+    let out=match args.value("out") {
+        Some(out) if !out.is_empty() => PathBuf::from(out),
+        _ => PathBuf::from(format!("{}.shamir", dir.display())),
+    };
+
+    let entries=Archive::walk(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    let manifest=Archive::manifest(&entries);
+    let (sealed, parts)=HybridSS::seal_with_aad(n, k, &Archive::encode(&entries), manifest.as_bytes())?;
+    write_bundles(&out, &parts, &sealed, &manifest)?;
+    journal::record(args, JournalEvent::Split, &fingerprints(&parts), &format!("split-dir k={k} n={n}"))?;
+
+    output::info(args, &tr!("split-dir-done", files=entries.len(), bundles=parts.len(), dir=out.display(), threshold=k),
+        &[&"bundles", &parts.len(), &out.display(), &HybridSS::generation(&sealed)?]);
+    Ok(())
+}
+
+pub fn join_dir(args:&Args)->Result<(),CliError>{
+    let bundles=read_bundles(&args.positional)?;
+    let out=PathBuf::from(args.required("out")?);
+
+    let generation=HybridSS::generation(&bundles.sealed)?;
+    if let Some(min)=args.value("min-generation") {
+        let min=min.parse::<u32>().map_err(|_| tr!("option-not-number", name="min-generation"))?;
+        if generation<min {
+            return Err(tr!("generation-too-old", generation=generation, min=min).into());
+        }
+    }
+
+    revocation::enforce(args, revocation::load(args)?.as_ref(), &bundles.parts, Some(generation))?;
+    let used=fingerprints(&bundles.parts);
+    let archive=HybridSS::open_with_aad(bundles.parts, &bundles.sealed, bundles.manifest.as_bytes())?;
+    let entries=Archive::decode(&archive)?;
+    Archive::verify(&entries, &bundles.manifest)?;
+    Archive::unpack(&out, &entries)?;
+    journal::record(args, JournalEvent::Recovery, &used, &format!("join-dir generation {generation}"))?;
+
+    output::info(args, &tr!("join-dir-done", files=entries.len(), generation=generation, dir=out.display()),
+        &[&"restored", &entries.len(), &out.display(), &generation]);
+    Ok(())
+}
+
+pub fn rotate(args:&Args)->Result<(),CliError>{
+    let bundles=read_bundles(&args.positional)?;
+    let k=args.number("threshold")?;
+    let n=args.number("shares")?;
+    let out=PathBuf::from(args.required("out")?);
+
+    let (sealed, parts)=HybridSS::rotate_with_aad(bundles.parts, &bundles.sealed, n, k, bundles.manifest.as_bytes())?;
+    write_bundles(&out, &parts, &sealed, &bundles.manifest)?;
+    journal::record(args, JournalEvent::Rotate, &fingerprints(&parts), &format!("generation {} k={k} n={n}", HybridSS::generation(&sealed)?))?;
+
+    let generation=HybridSS::generation(&sealed)?;
+    output::info(args, &tr!("rotate-done", generation=generation, bundles=parts.len(), dir=out.display(), threshold=k),
+        &[&"bundles", &parts.len(), &out.display(), &generation]);
+    Ok(())
+}
+
+fn fingerprints(parts:&BTreeMap<i32,Vec<u8>>)->Vec<String>{
+    parts.iter().map(|(index, data)| share_fingerprint(*index, data)).collect()
+}
+
+fn write_bundles(out:&Path,parts:&BTreeMap<i32,Vec<u8>>,sealed:&[u8],manifest:&str)->Result<(),String>{
+    for (index, data) in parts {
+        let bundle=out.join(format!("custodian-{index}"));
+        fs::create_dir_all(&bundle).map_err(|e| format!("{}: {e}", bundle.display()))?;
+        write(&bundle.join("key.share"), encode_share(*index, data).as_bytes())?;
+        write(&bundle.join("payload.sealed"), sealed)?;
+        write(&bundle.join("manifest.txt"), manifest.as_bytes())?;
+    }
+    write(&out.join("manifest.txt"), manifest.as_bytes())
+}
+
+fn read_bundles(paths:&[String])->Result<Bundles,CliError>{
+    if paths.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-bundles")));
+    }
+
+    let mut parts:BTreeMap<i32,Vec<u8>>=BTreeMap::new();
+    let mut sealed:Option<Vec<u8>>=None;
+    let mut manifest:Option<String>=None;
+    for bundle in paths {
+        let bundle=Path::new(bundle);
+        let (index, data)=decode_share(&read_string(&bundle.join("key.share"))?)?;
+        if parts.insert(index, data).is_some() {
+            return Err(CliError::integrity(tr!("share-given-twice", file=bundle.display(), index=index)));
+        }
+
+        let payload=read(&bundle.join("payload.sealed"))?;
+        match &sealed {
+            Some(existing) if *existing!=payload => {
+                return Err(CliError::integrity(tr!("bundle-different-payload", bundle=bundle.display())));
+            }
+            Some(_) => {}
+            None => sealed=Some(payload),
+        }
+        let listed=read_string(&bundle.join("manifest.txt"))?;
+        match &manifest {
+            Some(existing) if *existing!=listed => {
+                return Err(CliError::integrity(tr!("bundle-different-manifest", bundle=bundle.display())));
+            }
+            Some(_) => {}
+            None => manifest=Some(listed),
+        }
+    }
+
+    Ok(Bundles { parts, sealed: sealed.unwrap_or_default(), manifest: manifest.unwrap_or_default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn edited_manifests_do_not_open() {
+        let root=std::env::temp_dir().join(format!("shamir-dir-manifest-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        let (src, out, restored)=(root.join("src"), root.join("bundles"), root.join("restored"));
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("a.txt"), b"secret").unwrap();
+        let (src, out, restored)=(src.display().to_string(), out.display().to_string(), restored.display().to_string());
+        split_dir(&args(&[&src, "--threshold", "2", "--shares", "3", "--out", &out, "--quiet"])).unwrap();
+
+        let bundles=[format!("{out}/custodian-1"), format!("{out}/custodian-2")];
+        let join=|restored:&str| join_dir(&args(&[&bundles[0], &bundles[1], "--out", restored, "--quiet"]));
+        let forged=format!("{} 6 a.txt\n", "0".repeat(64));
+        fs::write(Path::new(&bundles[0]).join("manifest.txt"), &forged).unwrap();
+        assert!(join(&restored).is_err());
+        fs::write(Path::new(&bundles[1]).join("manifest.txt"), &forged).unwrap();
+        assert!(join(&restored).is_err());
+        assert!(!Path::new(&restored).exists());
+
+        let original=fs::read(Path::new(&out).join("manifest.txt")).unwrap();
+        for bundle in &bundles {
+            fs::write(Path::new(bundle).join("manifest.txt"), &original).unwrap();
+        }
+        join(&restored).unwrap();
+        assert_eq!(fs::read(Path::new(&restored).join("a.txt")).unwrap(), b"secret");
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use shamir_rust::crypto::ShamirSS;
+use shamir_rust::{hardening, stats};
+
+use super::{output, tr, Args, CliError};
+
+// `doctor` looks over the machine before a ceremony: each check gives a
+// pass, warn or fail finding with a hint on how to fix it. Any failure (and
+// with --strict any warning) makes the command fail. Checks that cannot run
+// on this platform warn rather than pass, so a clean report means something.
+
+// Free space under which writing shares, transcripts and journals is likely
+// to fail part way (fail) or worth a second look (warn).
+const DISK_FAIL_BYTES:u64=1<<20;
+const DISK_WARN_BYTES:u64=100<<20;
+// Share bytes drawn by the RNG check: enough for the chi-squared test.
+const RNG_SAMPLE_BYTES:usize=1536;
+
+// Process names of clipboard managers that keep a history of copied text.
+const CLIPBOARD_MANAGERS:&[&str]=&[
+    "clipit", "parcellite", "copyq", "klipper", "gpaste-daemon", "diodon", "clipman", "xfce4-clipman",
+    "cliphist", "greenclip", "clipmenud", "clipcat", "ditto", "maccy", "flycut", "pasty",
+];
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+enum Status{
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl Status{
+    fn name(self)->&'static str{
+        match self {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+        }
+    }
+}
+
+struct Finding{
+    check:&'static str,
+    status:Status,
+    message:String,
+    hint:Option<String>,
+}
+
+impl Finding{
+    fn pass(check:&'static str,message:String)->Finding{
+        Finding { check, status: Status::Pass, message, hint: None }
+    }
+
+    fn warn(check:&'static str,message:String,hint:String)->Finding{
+        Finding { check, status: Status::Warn, message, hint: Some(hint) }
+    }
+
+    fn fail(check:&'static str,message:String,hint:String)->Finding{
+        Finding { check, status: Status::Fail, message, hint: Some(hint) }
+    }
+}
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    let out=args.value("out").filter(|v| !v.is_empty()).unwrap_or(".");
+    let findings=[rng(), core_dumps(), swap(), clipboard_managers(), scrollback(), disk_space(Path::new(out))];
+    for finding in &findings {
+        let mut human=format!("{:<5} {:<10} {}", finding.status.name(), finding.check, finding.message);
+        if let Some(hint)=&finding.hint {
+            human.push_str(&format!("\n{:<16} {}", "", tr!("doctor-hint", hint=hint)));
+        }
+        output::result(args, &human, &[&"finding", &finding.status.name(), &finding.check, &finding.message, &finding.hint.as_deref().unwrap_or("")]);
+    }
+    let failed=findings.iter().filter(|f| f.status==Status::Fail || (f.status==Status::Warn && args.flag("strict"))).count();
+    if failed>0 {
+        return Err(tr!("doctor-failed", count=failed).into());
+    }
+    Ok(())
+}
+
+// The OS source answers, and a test split through the same RNG path as a
+// ceremony gives shares that look uniform.
+fn rng()->Finding{
+    let mut probe=[[0u8;32];2];
+    for block in &mut probe {
+        if OsRng.try_fill_bytes(block).is_err() {
+            return Finding::fail("rng", tr!("doctor-rng-unavailable"), tr!("doctor-rng-unavailable-hint"));
+        }
+    }
+    if probe[0]==probe[1] || probe[0].iter().all(|&b| b==0) {
+        return Finding::fail("rng", tr!("doctor-rng-stuck"), tr!("doctor-rng-unavailable-hint"));
+    }
+    let report=match ShamirSS::split(3, 2, vec![0u8;RNG_SAMPLE_BYTES/3]) {
+        Ok(parts) => stats::assess_shares(&parts),
+        Err(e) => return Finding::fail("rng", e.to_string(), tr!("doctor-rng-unavailable-hint")),
+    };
+    if !report.passed() {
+        return Finding::fail("rng", tr!("doctor-rng-biased", bytes=report.bytes), tr!("doctor-rng-biased-hint"));
+    }
+    Finding::pass("rng", tr!("doctor-rng-ok", bytes=report.bytes))
+}
+
+fn core_dumps()->Finding{
+    match hardening::core_dumps_enabled() {
+        Some(false) => Finding::pass("core-dumps", tr!("doctor-core-off")),
+        Some(true) => {
+            // A pipe in core_pattern hands dumps to a collector such as
+            // systemd-coredump, which keeps them on disk.
+            let handler=fs::read_to_string("/proc/sys/kernel/core_pattern").ok()
+                .and_then(|pattern| pattern.trim().strip_prefix('|').map(|cmd| cmd.split_whitespace().next().unwrap_or("").to_string()));
+            let message=match handler {
+                Some(handler) => tr!("doctor-core-on-handler", handler=handler),
+                None => tr!("doctor-core-on"),
+            };
+            Finding::warn("core-dumps", message, tr!("doctor-core-hint"))
+        }
+        None => Finding::warn("core-dumps", tr!("doctor-unchecked"), tr!("doctor-core-hint")),
+    }
+}
+
+// Active swap that is not zram or dm-crypt may write secret pages to disk.
+fn swap()->Finding{
+    if cfg!(target_os = "macos") {
+        return Finding::pass("swap", tr!("doctor-swap-encrypted-os"));
+    }
+    let Ok(table)=fs::read_to_string("/proc/swaps") else {
+        return Finding::warn("swap", tr!("doctor-unchecked"), tr!("doctor-swap-hint"));
+    };
+    let exposed:Vec<&str>=table.lines().skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|device| !encrypted_swap(device))
+        .collect();
+    if exposed.is_empty() {
+        Finding::pass("swap", tr!("doctor-swap-ok"))
+    } else {
+        Finding::warn("swap", tr!("doctor-swap-exposed", devices=exposed.join(", ")), tr!("doctor-swap-hint"))
+    }
+}
+
+fn encrypted_swap(device:&str)->bool{
+    let Some(name)=device.strip_prefix("/dev/") else { return false };
+    if name.starts_with("zram") {
+        return true;
+    }
+    // Device-mapper targets set up by cryptsetup have a CRYPT- uuid; swap
+    // through /dev/mapper/NAME is resolved to its dm-N node first.
+    let node=fs::canonicalize(device).ok().and_then(|path| path.file_name().map(|n| n.to_string_lossy().into_owned())).unwrap_or(name.to_string());
+    fs::read_to_string(format!("/sys/block/{node}/dm/uuid")).is_ok_and(|uuid| uuid.starts_with("CRYPT-"))
+}
+
+fn clipboard_managers()->Finding{
+    let Ok(out)=Command::new("ps").args(["-A", "-o", "comm="]).stderr(Stdio::null()).output() else {
+        return Finding::warn("clipboard", tr!("doctor-unchecked"), tr!("doctor-clipboard-hint"));
+    };
+    let mut running:Vec<String>=String::from_utf8_lossy(&out.stdout).lines()
+        .filter_map(|line| {
+            let name=Path::new(line.trim()).file_name()?.to_string_lossy().to_lowercase();
+            CLIPBOARD_MANAGERS.iter().any(|manager| name.starts_with(manager)).then_some(name)
+        })
+        .collect();
+    running.sort();
+    running.dedup();
+    if running.is_empty() {
+        Finding::pass("clipboard", tr!("doctor-clipboard-ok"))
+    } else {
+        Finding::warn("clipboard", tr!("doctor-clipboard-running", managers=running.join(", ")), tr!("doctor-clipboard-hint"))
+    }
+}
+
+// A terminal multiplexer keeps scrollback in its server, beyond the reach of
+// `reveal`'s clear and after the window is closed.
+fn scrollback()->Finding{
+    if !io::stdout().is_terminal() {
+        return Finding::pass("scrollback", tr!("doctor-scrollback-no-terminal"));
+    }
+    let multiplexer=if std::env::var_os("TMUX").is_some() {
+        Some("tmux")
+    } else if std::env::var_os("STY").is_some() {
+        Some("screen")
+    } else {
+        None
+    };
+    match multiplexer {
+        Some(name) => Finding::warn("scrollback", tr!("doctor-scrollback-multiplexer", multiplexer=name), tr!("doctor-scrollback-hint")),
+        None => Finding::pass("scrollback", tr!("doctor-scrollback-ok")),
+    }
+}
+
+fn disk_space(out:&Path)->Finding{
+    // The output directory may not exist yet; its nearest existing ancestor
+    // is on the same file system.
+    let dir=out.ancestors().find(|dir| dir.is_dir()).unwrap_or(Path::new("."));
+    let available=Command::new("df").args(["-Pk"]).arg(dir).stderr(Stdio::null()).output().ok()
+        .and_then(|out| {
+            // POSIX format: a header, then "fs blocks used available capacity mount".
+            let text=String::from_utf8_lossy(&out.stdout).into_owned();
+            text.lines().nth(1)?.split_whitespace().nth(3)?.parse::<u64>().ok()
+        })
+        .map(|kib| kib*1024);
+    let path=out.display();
+    match available {
+        None => Finding::warn("disk", tr!("doctor-unchecked"), tr!("doctor-disk-hint")),
+        Some(bytes) if bytes<DISK_FAIL_BYTES => Finding::fail("disk", tr!("doctor-disk-low", path=path, mib=bytes>>20), tr!("doctor-disk-hint")),
+        Some(bytes) if bytes<DISK_WARN_BYTES => Finding::warn("disk", tr!("doctor-disk-low", path=path, mib=bytes>>20), tr!("doctor-disk-hint")),
+        Some(bytes) => Finding::pass("disk", tr!("doctor-disk-ok", path=path, mib=bytes>>20)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rng_check_passes_on_the_os_source() {
+        let finding=rng();
+        assert_eq!(finding.status, Status::Pass, "{}", finding.message);
+        assert!(finding.hint.is_none());
+    }
+
+    #[test]
+    fn only_zram_and_dm_crypt_swap_count_as_encrypted() {
+        assert!(encrypted_swap("/dev/zram0"));
+        assert!(!encrypted_swap("/swapfile"));
+        assert!(!encrypted_swap("/dev/shamir-no-such-device"));
+    }
+
+    #[test]
+    fn every_finding_short_of_pass_has_a_hint() {
+        // A directory that does not exist yet is measured on its parent.
+        let out=std::env::temp_dir().join(format!("shamir-doctor-{}", std::process::id())).join("shares");
+        for finding in [core_dumps(), swap(), clipboard_managers(), scrollback(), disk_space(&out)] {
+            assert_eq!(finding.hint.is_some(), finding.status!=Status::Pass, "{}", finding.check);
+        }
+        assert!(!out.exists());
+    }
+}
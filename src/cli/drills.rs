@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use shamir_rust::transcript::Transcript;
+
+use super::{dates, read_string, tr, Args, CliError};
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("status") => status(args),
+        _ => Err(CliError::with_usage(tr!("unknown-drills-command"))),
+    }
+}
+
+fn status(args:&Args)->Result<(),CliError>{
+    let path=args.positional.get(1).ok_or_else(|| CliError::with_usage(tr!("missing-transcript")))?;
+    let transcript=Transcript::parse(&read_string(Path::new(path))?)?;
+    let today=match args.value("today") {
+        Some(day) if !day.is_empty() => {
+            if !dates::is_date(day) {
+                return Err(CliError::usage(tr!("today-not-date")));
+            }
+            day.to_string()
+        }
+        _ => dates::required_today("drills status")?,
+    };
+
+    let overdue=transcript.overdue(&today);
+    for c in &transcript.custodians {
+        let state=match &c.next_drill_due {
+            None => tr!("drill-unscheduled"),
+            Some(due) if due.as_str()<today.as_str() => tr!("drill-overdue", due=due),
+            Some(due) => tr!("drill-due", due=due),
+        };
+        let contact=c.contact.as_deref().unwrap_or("-");
+        println!("{} {:>3}  {:<16} {:<10} {:<28} {state}", tr!("drills-share"), c.index, c.label, c.fingerprint, contact);
+    }
+    println!("{}", tr!("drills-summary", overdue=overdue.len(), total=transcript.custodians.len(), today=today));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use shamir_rust::transcript::CustodianRecord;
+
+    use crate::cli::error::BAD_ARGUMENTS;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn status_needs_a_date_and_a_transcript() {
+        let path=std::env::temp_dir().join(format!("shamir-drills-{}.txt", std::process::id()));
+        let custodian=|index:i32,due:Option<&str>| CustodianRecord {
+            index,
+            label: format!("custodian {index}"),
+            fingerprint: "abcd".to_string(),
+            next_drill_due: due.map(str::to_string),
+            ..CustodianRecord::default()
+        };
+        let transcript=Transcript { threshold: 2, shares: 3, created: "2024-01-01".to_string(),
+            custodians: vec![custodian(1, Some("2024-06-01")), custodian(2, Some("2025-06-01")), custodian(3, None)], ..Transcript::default() };
+        assert_eq!(transcript.overdue("2025-01-01").len(), 1);
+        fs::write(&path, transcript.to_text()).unwrap();
+        let file=path.display().to_string();
+
+        status(&args(&["status", &file, "--today", "2025-01-01"])).unwrap();
+        assert_eq!(status(&args(&["status", &file, "--today", "01/01/2025"])).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(status(&args(&["status"])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::write(&path, "not a transcript\n").unwrap();
+        assert!(status(&args(&["status", &file, "--today", "2025-01-01"])).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+}
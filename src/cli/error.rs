@@ -0,0 +1,148 @@
+use std::fmt;
+
+use shamir_rust::error::ShamirError;
+
+use super::i18n::tr;
+
+// Process exit codes. These are part of the CLI contract: codes are never
+// renumbered and new ones take the next free number. Success is 0.
+pub const FAILURE:i32=1;
+pub const BAD_ARGUMENTS:i32=2;
+pub const INSUFFICIENT_SHARES:i32=3;
+pub const INTEGRITY:i32=4;
+
+// A failed command: the message for the user and the exit code. Plain String
+// errors from helpers become FAILURE; core errors are classified by `code_for`.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct CliError{
+    pub code:i32,
+    pub message:String,
+}
+
+impl CliError{
+
+    pub fn usage(message:impl Into<String>)->CliError{
+        CliError { code: BAD_ARGUMENTS, message: message.into() }
+    }
+
+    // A usage error followed by the command summary.
+    pub fn with_usage(message:impl Into<String>)->CliError{
+        CliError::usage(format!("{}\n{}", message.into(), tr!("usage")))
+    }
+
+    pub fn integrity(message:impl Into<String>)->CliError{
+        CliError { code: INTEGRITY, message: message.into() }
+    }
+}
+
+// No wildcard arm, so a new core error does not compile until it is placed.
+fn code_for(err:&ShamirError)->i32{
+    match err {
+        ShamirError::ThresholdTooSmall
+        | ShamirError::ThresholdAboveShares
+        | ShamirError::TooManyShares
+        | ShamirError::RangeOutOfBounds { .. }
+        | ShamirError::BufferSize { .. }
+        | ShamirError::UnknownScheme(_)
+        | ShamirError::SecretTooLarge { .. }
+        | ShamirError::InvalidChunkSize
+        | ShamirError::KdfOutputTooLong { .. }
+        | ShamirError::EmptySecret => BAD_ARGUMENTS,
+        ShamirError::NoParts
+        | ShamirError::InsufficientParts { .. } => INSUFFICIENT_SHARES,
+        ShamirError::InconsistentLengths
+        | ShamirError::InvalidIndex(_)
+        | ShamirError::DuplicateIndex(_)
+        | ShamirError::InconsistentShares(_)
+        | ShamirError::MislabeledShares(_)
+        | ShamirError::ForeignShares(_)
+        | ShamirError::InvalidShares(_)
+        | ShamirError::CheckFailed => INTEGRITY,
+        ShamirError::JoinDenied(_)
+        | ShamirError::NotInvertible => FAILURE,
+    }
+}
+
+// The core's messages are English; the CLI shows them in the user's language.
+fn message_for(err:&ShamirError)->String{
+    match err {
+        ShamirError::ThresholdTooSmall => tr!("error-threshold-too-small"),
+        ShamirError::ThresholdAboveShares => tr!("error-threshold-above-shares"),
+        ShamirError::TooManyShares => tr!("error-too-many-shares"),
+        ShamirError::NoParts => tr!("error-no-parts"),
+        ShamirError::InconsistentLengths => tr!("error-inconsistent-lengths"),
+        ShamirError::InvalidIndex(index) => tr!("error-invalid-index", index=index),
+        ShamirError::DuplicateIndex(index) => tr!("error-duplicate-index", index=index),
+        ShamirError::InsufficientParts { collected, threshold } => tr!("error-insufficient-parts", collected=collected, threshold=threshold),
+        ShamirError::InconsistentShares(indices) => tr!("error-inconsistent-shares", indices=format!("{indices:?}")),
+        ShamirError::RangeOutOfBounds { start, end, len } => tr!("error-range-out-of-bounds", start=start, end=end, len=len),
+        ShamirError::CheckFailed => tr!("error-check-failed"),
+        ShamirError::BufferSize { expected, actual } => tr!("error-buffer-size", expected=expected, actual=actual),
+        ShamirError::UnknownScheme(id) => tr!("error-unknown-scheme", id=id),
+        ShamirError::SecretTooLarge { len, max } => tr!("error-secret-too-large", len=len, max=max),
+        ShamirError::InvalidChunkSize => tr!("error-invalid-chunk-size"),
+        ShamirError::JoinDenied(reason) => tr!("error-join-denied", reason=reason),
+        ShamirError::KdfOutputTooLong { requested, max } => tr!("error-kdf-output-too-long", requested=requested, max=max),
+        ShamirError::EmptySecret => tr!("error-empty-secret"),
+        ShamirError::MislabeledShares(fixes) => tr!("error-mislabeled-shares", fixes=fixes.iter().map(|(labeled, likely)| format!("{labeled} -> {likely}")).collect::<Vec<_>>().join(", ")),
+        ShamirError::NotInvertible => tr!("error-not-invertible"),
+        ShamirError::ForeignShares(indices) => tr!("error-foreign-shares", indices=format!("{indices:?}")),
+        ShamirError::InvalidShares(indices) => tr!("error-invalid-shares", indices=format!("{indices:?}")),
+    }
+}
+
+impl fmt::Display for CliError{
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str(&self.message)
+    }
+}
+
+impl From<ShamirError> for CliError{
+    fn from(err:ShamirError)->CliError{
+        CliError { code: code_for(&err), message: message_for(&err) }
+    }
+}
+
+impl From<String> for CliError{
+    fn from(message:String)->CliError{
+        CliError { code: FAILURE, message }
+    }
+}
+
+impl From<&str> for CliError{
+    fn from(message:&str)->CliError{
+        CliError { code: FAILURE, message: message.to_string() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn core_errors_get_their_exit_codes() {
+        let code=|err:ShamirError| CliError::from(err).code;
+        assert_eq!(code(ShamirError::ThresholdTooSmall), BAD_ARGUMENTS);
+        assert_eq!(code(ShamirError::InsufficientParts { collected: 2, threshold: 3 }), INSUFFICIENT_SHARES);
+        assert_eq!(code(ShamirError::NoParts), INSUFFICIENT_SHARES);
+        assert_eq!(code(ShamirError::DuplicateIndex(4)), INTEGRITY);
+        assert_eq!(code(ShamirError::CheckFailed), INTEGRITY);
+        assert_eq!(code(ShamirError::JoinDenied("closed".to_string())), FAILURE);
+        assert_eq!(CliError::from("helper failed").code, FAILURE);
+        assert_eq!(CliError::integrity("tampered").code, INTEGRITY);
+    }
+
+    #[test]
+    fn messages_carry_their_values() {
+        let message=CliError::from(ShamirError::InsufficientParts { collected: 2, threshold: 3 }).message;
+        assert!(message.contains('2') && message.contains('3'), "{message}");
+        let message=CliError::from(ShamirError::MislabeledShares(vec![(4, 5)])).message;
+        assert!(message.contains("4 -> 5"), "{message}");
+        assert!(!CliError::from(ShamirError::NotInvertible).message.starts_with("error-"));
+
+        let usage=CliError::with_usage("missing file");
+        assert_eq!(usage.code, BAD_ARGUMENTS);
+        assert!(usage.message.starts_with("missing file\n") && usage.message.lines().count()>1);
+        assert_eq!(usage.to_string(), usage.message);
+    }
+}
@@ -0,0 +1,167 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+use shamir_rust::encoding::{decode_any_share, share_fingerprint};
+use shamir_rust::fountain::{FountainDecoder, FountainEncoder, PartStatus, DEFAULT_FRAGMENT_LEN};
+use shamir_rust::qr::{EccLevel, QrCode};
+
+use super::image::{self, Bitmap};
+use super::{encode_share, output, read_string, tr, write, Args, CliError};
+
+// Air-gap transfer of shares too large for one QR code, as fountain-coded
+// animated QR sequences (see `fountain.rs`). `frames export` turns a share
+// into parts: an animated GIF with --out FILE.gif, one PNG per frame with
+// --out DIR, otherwise one part per line on stdout. `frames import` reads
+// scanned parts, one per line, from files or stdin in any order, skipping
+// damaged ones, until the share is complete.
+
+const DEFAULT_SCALE:usize=4;
+const MAX_SCALE:usize=32;
+const DEFAULT_DELAY_MS:usize=250;
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("export") => export(args),
+        Some("import") => import(args),
+        _ => Err(CliError::with_usage(tr!("unknown-frames-command"))),
+    }
+}
+
+fn export(args:&Args)->Result<(),CliError>{
+    let file=args.positional.get(1).ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let (index, data)=decode_any_share(&read_string(Path::new(file))?).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let encoder=FountainEncoder::new(index, &data, optional_count(args, "fragment-len", DEFAULT_FRAGMENT_LEN)?).map_err(CliError::usage)?;
+    let fragments=encoder.fragment_count();
+    // Every fragment once, then as many mixed parts, so a scanner that
+    // keeps missing one frame of the loop still completes.
+    let default_frames=if fragments==1 { 1 } else { fragments*2 };
+    let parts=encoder.parts(optional_count(args, "frames", default_frames)?);
+    let Some(out)=args.value("out").filter(|v| !v.is_empty()) else {
+        for part in &parts {
+            println!("{part}");
+        }
+        return Ok(());
+    };
+    let scale=optional_count(args, "scale", DEFAULT_SCALE)?;
+    if scale>MAX_SCALE {
+        return Err(CliError::usage(tr!("frames-scale-too-large", max=MAX_SCALE)));
+    }
+    let symbols=parts.iter().map(|part| QrCode::encode_text(part, EccLevel::Medium)).collect::<Result<Vec<_>,_>>().map_err(CliError::usage)?;
+    // Longer sequence numbers can take a part to the next QR version; every
+    // frame gets the size of the largest so the animation does not jump.
+    let modules=symbols.iter().map(QrCode::size).max().unwrap_or(0);
+    let bitmaps:Vec<Bitmap>=symbols.iter().map(|qr| Bitmap::from_qr(qr, modules, scale)).collect();
+    if out.ends_with(".gif") {
+        let delay=optional_count(args, "delay", DEFAULT_DELAY_MS)?;
+        write(Path::new(out), &image::gif(&bitmaps, delay as u32)?)?;
+    } else {
+        std::fs::create_dir_all(out).map_err(|e| format!("{out}: {e}"))?;
+        for (number, bitmap) in bitmaps.iter().enumerate() {
+            write(&Path::new(out).join(format!("frame-{:04}.png", number+1)), &image::png(bitmap)?)?;
+        }
+    }
+    output::result(args, &tr!("frames-written", frames=parts.len(), fragments=fragments, path=out),
+        &[&"frames", &index, &fragments, &parts.len(), &out]);
+    Ok(())
+}
+
+fn import(args:&Args)->Result<(),CliError>{
+    let files=&args.positional[1..];
+    let mut decoder=FountainDecoder::new();
+    if files.is_empty() {
+        receive(args, &mut decoder, "-", io::stdin().lock())?;
+    }
+    for file in files {
+        if decoder.is_complete() {
+            break;
+        }
+        let reader=File::open(file).map_err(|e| format!("{file}: {e}"))?;
+        receive(args, &mut decoder, file, BufReader::new(reader))?;
+    }
+    let Some((index, data))=decoder.share() else {
+        return Err(match decoder.progress() {
+            (_, 0) => tr!("frames-none").into(),
+            (solved, fragments) => tr!("frames-incomplete", solved=solved, fragments=fragments).into(),
+        });
+    };
+    let text=encode_share(index, &data);
+    match args.value("out").filter(|v| !v.is_empty()) {
+        Some(out) => {
+            write(Path::new(out), text.as_bytes())?;
+            output::result(args, out, &[&"share", &index, &share_fingerprint(index, &data), &out]);
+        }
+        None => print!("{text}"),
+    }
+    Ok(())
+}
+
+// Feeds parts to the decoder until the share is complete; a damaged part or
+// one from another share is a warning.
+fn receive(args:&Args,decoder:&mut FountainDecoder,source:&str,reader:impl BufRead)->Result<(),CliError>{
+    for line in reader.lines() {
+        let line=line.map_err(|e| format!("{source}: {e}"))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match decoder.receive(&line) {
+            Ok(PartStatus::Complete { .. }) => return Ok(()),
+            Ok(_) => {}
+            Err(e) => output::warning(args, source, &e),
+        }
+    }
+    Ok(())
+}
+
+fn optional_count(args:&Args,name:&str,default:usize)->Result<usize,CliError>{
+    match args.value(name) {
+        Some(value) if !value.is_empty() => match value.parse::<usize>() {
+            Ok(count) if count>0 => Ok(count),
+            _ => Err(CliError::usage(tr!("option-not-number", name=name))),
+        },
+        _ => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::cli::error::BAD_ARGUMENTS;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn scanned_parts_rebuild_the_share() {
+        let root=std::env::temp_dir().join(format!("shamir-frames-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let data:Vec<u8>=(0..200u8).collect();
+        let share=encode_share(4, &data);
+        fs::write(path("share"), &share).unwrap();
+
+        // Out of order, with a damaged scan among them.
+        let mut parts=FountainEncoder::new(4, &data, 32).unwrap().parts(14);
+        parts.reverse();
+        parts.insert(1, "not a part".to_string());
+        fs::write(path("parts"), parts.join("\n")).unwrap();
+        import(&args(&["import", &path("parts"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read_to_string(path("joined")).unwrap(), share);
+
+        fs::write(path("few"), parts[..3].join("\n")).unwrap();
+        assert!(import(&args(&["import", &path("few"), "--quiet"])).is_err());
+
+        export(&args(&["export", &path("share"), "--fragment-len", "100", "--out", &path("pngs"), "--quiet"])).unwrap();
+        assert_eq!(fs::read_dir(path("pngs")).unwrap().count(), 6);
+        export(&args(&["export", &path("share"), "--out", &path("share.gif"), "--quiet"])).unwrap();
+        assert!(fs::read(path("share.gif")).unwrap().starts_with(b"GIF"));
+        let scale=export(&args(&["export", &path("share"), "--out", &path("big"), "--scale", "33"]));
+        assert_eq!(scale.unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(export(&args(&["export", &path("share"), "--frames", "0"])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+#[cfg(feature = "i18n")]
+use fluent_bundle::concurrent::FluentBundle;
+#[cfg(feature = "i18n")]
+use fluent_bundle::{FluentArgs, FluentResource};
+#[cfg(feature = "i18n")]
+use unic_langid::LanguageIdentifier;
+
+use super::output::{self, Mode};
+use super::{Args, CliError};
+
+// Translated CLI text. Catalogs are Fluent files under src/cli/locales,
+// compiled in and resolved with fluent-bundle. A message missing from a
+// catalog falls back to English. Placeables are not wrapped in Unicode
+// isolation marks, which would end up in share files and porcelain output.
+//
+// The language is `--lang`, else the locale (LC_ALL, LC_MESSAGES, LANG).
+// --porcelain ignores the locale so scripts always see the same text.
+// Without the `i18n` feature only English is built in, and it is formatted
+// by `plain` below instead of fluent-bundle.
+const CATALOGS:&[(&str,&str)]=&[
+    ("en", include_str!("locales/en.ftl")),
+    #[cfg(feature = "i18n")]
+    ("es", include_str!("locales/es.ftl")),
+];
+
+const FALLBACK:&str="en";
+
+static ACTIVE:OnceLock<&'static str>=OnceLock::new();
+
+#[cfg(feature = "i18n")]
+type Catalog=FluentBundle<FluentResource>;
+#[cfg(not(feature = "i18n"))]
+type Catalog=HashMap<&'static str,String>;
+
+fn catalogs()->&'static HashMap<&'static str,Catalog>{
+    static PARSED:OnceLock<HashMap<&'static str,Catalog>>=OnceLock::new();
+    PARSED.get_or_init(|| CATALOGS.iter().map(|(lang, source)| (*lang, bundle(lang, source))).collect())
+}
+
+// The catalogs are part of the binary and checked by the tests below, so a
+// syntax error is a build defect: the entries that did parse are kept.
+#[cfg(feature = "i18n")]
+fn bundle(lang:&str,source:&'static str)->Catalog{
+    let resource=FluentResource::try_new(source.to_string()).unwrap_or_else(|(resource, _)| resource);
+    let id:LanguageIdentifier=lang.parse().unwrap_or_default();
+    let mut bundle=FluentBundle::new_concurrent(vec![id]);
+    bundle.set_use_isolating(false);
+    let _=bundle.add_resource(resource);
+    bundle
+}
+
+#[cfg(not(feature = "i18n"))]
+fn bundle(_lang:&str,source:&'static str)->Catalog{
+    plain::messages(source)
+}
+
+// Picks the language for the rest of the run; only the first call counts.
+pub fn select(args:&Args)->Result<(),CliError>{
+    let requested=match args.value("lang").filter(|v| !v.is_empty()) {
+        Some(lang) => {
+            let lang=language(lang);
+            if !catalogs().contains_key(lang.as_str()) {
+                let available:Vec<&str>=CATALOGS.iter().map(|(lang, _)| *lang).collect();
+                return Err(CliError::usage(tr!("unknown-language", lang=lang, available=available.join(", "))));
+            }
+            Some(lang)
+        }
+        None if output::mode(args)==Mode::Porcelain => None,
+        None => locale(),
+    };
+    let lang=requested.and_then(|lang| CATALOGS.iter().map(|(l, _)| *l).find(|l| *l==lang)).unwrap_or(FALLBACK);
+    let _=ACTIVE.set(lang);
+    Ok(())
+}
+
+// "es_ES.UTF-8" and "es-ES" -> "es".
+fn language(tag:&str)->String{
+    tag.split(['_', '-', '.', '@']).next().unwrap_or("").to_ascii_lowercase()
+}
+
+fn locale()->Option<String>{
+    if !cfg!(feature = "i18n") {
+        return None;
+    }
+    ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+        .find_map(|name| std::env::var(name).ok().filter(|v| !v.is_empty()))
+        .map(|tag| language(&tag))
+}
+
+pub fn message(id:&str,args:&[(&str,&dyn Display)])->String{
+    let catalogs=catalogs();
+    let lang=ACTIVE.get().copied().unwrap_or(FALLBACK);
+    [lang, FALLBACK].iter().filter_map(|lang| catalogs.get(lang))
+        .find_map(|bundle| format(bundle, id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+#[cfg(feature = "i18n")]
+fn format(bundle:&Catalog,id:&str,args:&[(&str,&dyn Display)])->Option<String>{
+    let pattern=bundle.get_message(id)?.value()?;
+    let mut fluent=FluentArgs::new();
+    for (name, value) in args {
+        fluent.set(*name, value.to_string());
+    }
+    let mut errors=Vec::new();
+    Some(bundle.format_pattern(pattern, Some(&fluent), &mut errors).into_owned())
+}
+
+#[cfg(not(feature = "i18n"))]
+fn format(bundle:&Catalog,id:&str,args:&[(&str,&dyn Display)])->Option<String>{
+    bundle.get(id).map(|pattern| plain::format(pattern, args))
+}
+
+// The subset of Fluent that en.ftl uses, so an English-only build needs no
+// fluent-bundle: messages, values over several indented lines (dedented as
+// Fluent does), `{ $name }` variables and `{"..."}` string literals. The
+// tests format every English message both ways and compare.
+#[cfg(any(not(feature = "i18n"), test))]
+mod plain {
+    use std::collections::HashMap;
+    use std::fmt::Display;
+
+    pub fn messages(source:&str)->HashMap<&str,String>{
+        let mut messages=HashMap::new();
+        let mut lines=source.lines().peekable();
+        while let Some(line)=lines.next() {
+            if line.starts_with([' ', '#']) {
+                continue;
+            }
+            let Some((id, inline))=line.split_once('=') else { continue };
+            let mut block=Vec::new();
+            while let Some(next)=lines.next_if(|next| next.starts_with(' ') || next.trim().is_empty()) {
+                block.push(next);
+            }
+            let indent=block.iter().filter(|l| !l.trim().is_empty()).map(|l| l.len()-l.trim_start().len()).min().unwrap_or(0);
+            let inline=inline.trim();
+            let text:Vec<&str>=(!inline.is_empty()).then_some(inline).into_iter()
+                .chain(block.iter().map(|l| l.get(indent..).unwrap_or("")))
+                .collect();
+            messages.insert(id.trim(), text.join("\n").trim_end().to_string());
+        }
+        messages
+    }
+
+    pub fn format(pattern:&str,args:&[(&str,&dyn Display)])->String{
+        let mut out=String::new();
+        let mut rest=pattern;
+        while let Some((before, after))=rest.split_once('{') {
+            out.push_str(before);
+            let Some((placeable, tail))=after.split_once('}') else {
+                rest=after;
+                break;
+            };
+            let placeable=placeable.trim();
+            if let Some(name)=placeable.strip_prefix('$') {
+                match args.iter().find(|(n, _)| *n==name) {
+                    Some((_, value)) => out.push_str(&value.to_string()),
+                    None => out.push_str(&format!("{{${name}}}")),
+                }
+            } else if let Some(literal)=placeable.strip_prefix('"').and_then(|p| p.strip_suffix('"')) {
+                out.push_str(&literal.replace("\\\"", "\"").replace("\\\\", "\\"));
+            }
+            rest=tail;
+        }
+        out.push_str(rest);
+        out
+    }
+}
+
+// tr!("id") or tr!("id", name=value, ...) for `{ $name }` placeables.
+macro_rules! tr {
+    ($id:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::cli::i18n::message($id, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*])
+    };
+}
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "i18n")]
+    #[test]
+    fn catalogs_parse_and_translate_only_known_messages() {
+        let english=FluentResource::try_new(CATALOGS[0].1.to_string()).expect("en.ftl parses");
+        let ids=|resource:&FluentResource| resource.entries().filter_map(|entry| match entry {
+            fluent_syntax::ast::Entry::Message(message) => Some(message.id.name.to_string()),
+            _ => None,
+        }).collect::<Vec<_>>();
+        let known=ids(&english);
+        for (lang, source) in CATALOGS {
+            let resource=FluentResource::try_new(source.to_string()).unwrap_or_else(|(_, errors)| panic!("{lang}.ftl: {errors:?}"));
+            for id in ids(&resource) {
+                assert!(known.contains(&id), "{lang}.ftl: {id} is not in en.ftl");
+            }
+        }
+    }
+
+    #[test]
+    fn placeables_are_filled_without_isolation_marks() {
+        let text=message("unknown-language", &[("lang", &"xx"), ("available", &"en, es")]);
+        assert!(text.contains("xx") && text.contains("en, es"), "{text}");
+        assert!(!text.contains('\u{2068}'));
+        assert_eq!(message("no-such-message", &[]), "no-such-message");
+    }
+
+    // Every English message reads the same with and without `i18n`.
+    #[cfg(feature = "i18n")]
+    #[test]
+    fn plain_english_matches_fluent() {
+        let fluent=bundle("en", CATALOGS[0].1);
+        let plain=plain::messages(CATALOGS[0].1);
+        let resource=FluentResource::try_new(CATALOGS[0].1.to_string()).expect("en.ftl parses");
+        assert_eq!(plain.len(), resource.entries().filter(|e| matches!(e, fluent_syntax::ast::Entry::Message(_))).count());
+        for (id, pattern) in &plain {
+            let names:Vec<&str>=pattern.split('{').skip(1)
+                .filter_map(|p| p.split_once('}')?.0.trim().strip_prefix('$'))
+                .collect();
+            let values:Vec<(&str,&dyn Display)>=names.iter().map(|name| (*name, name as &dyn Display)).collect();
+            assert_eq!(Some(plain::format(pattern, &values)), format(&fluent, id, &values), "{id}");
+        }
+    }
+}
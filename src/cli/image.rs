@@ -0,0 +1,118 @@
+use shamir_rust::qr::QrCode;
+
+// Two-colour images for `frames export`, written with the `png` and `gif`
+// crates.
+
+// Modules of light margin around a symbol, as scanners expect.
+const QUIET_ZONE:usize=4;
+
+pub struct Bitmap{
+    width:usize,
+    height:usize,
+    // Row-major; true is dark.
+    pixels:Vec<bool>,
+}
+
+impl Bitmap{
+
+    // A QR symbol centred in a square of `modules` modules (at least the
+    // symbol's size) plus the quiet zone, `scale` pixels per module.
+    pub fn from_qr(qr:&QrCode,modules:usize,scale:usize)->Bitmap{
+        let modules=modules.max(qr.size());
+        let side=(modules+2*QUIET_ZONE)*scale;
+        let offset=QUIET_ZONE+(modules-qr.size())/2;
+        let mut pixels=vec![false; side*side];
+        for y in 0..qr.size() {
+            for x in 0..qr.size() {
+                if !qr.module(x, y) {
+                    continue;
+                }
+                for dy in 0..scale {
+                    let row=((y+offset)*scale+dy)*side;
+                    let start=row+(x+offset)*scale;
+                    pixels[start..start+scale].fill(true);
+                }
+            }
+        }
+        Bitmap { width: side, height: side, pixels }
+    }
+}
+
+// A 1-bit greyscale PNG.
+pub fn png(bitmap:&Bitmap)->Result<Vec<u8>,String>{
+    let mut raw=Vec::with_capacity(bitmap.width.div_ceil(8)*bitmap.height);
+    for row in bitmap.pixels.chunks(bitmap.width) {
+        // Packed MSB first; 1 is white.
+        raw.extend(row.chunks(8).map(|bits| bits.iter().enumerate().fold(0u8, |acc, (i, &dark)| acc|(u8::from(!dark)<<(7-i)))));
+    }
+    let mut out=Vec::new();
+    let mut encoder=png::Encoder::new(&mut out, bitmap.width as u32, bitmap.height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::One);
+    let mut writer=encoder.write_header().map_err(|e| e.to_string())?;
+    writer.write_image_data(&raw).map_err(|e| e.to_string())?;
+    writer.finish().map_err(|e| e.to_string())?;
+    Ok(out)
+}
+
+// An endlessly looping GIF showing each frame for `delay_ms`. Every frame
+// must be the size of the first.
+pub fn gif(frames:&[Bitmap],delay_ms:u32)->Result<Vec<u8>,String>{
+    let (width, height)=frames.first().map(|f| (f.width as u16, f.height as u16)).unwrap_or((0, 0));
+    let mut out=Vec::new();
+    {
+        // Global colour table of two entries: 0 white, 1 black.
+        let mut encoder=gif::Encoder::new(&mut out, width, height, &[0xFF, 0xFF, 0xFF, 0, 0, 0]).map_err(|e| e.to_string())?;
+        encoder.set_repeat(gif::Repeat::Infinite).map_err(|e| e.to_string())?;
+        for bitmap in frames {
+            let indices:Vec<u8>=bitmap.pixels.iter().map(|&dark| u8::from(dark)).collect();
+            let mut frame=gif::Frame::from_indexed_pixels(width, height, indices, None);
+            frame.delay=(delay_ms/10).min(u16::MAX as u32) as u16;
+            encoder.write_frame(&frame).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shamir_rust::qr::EccLevel;
+
+    fn bitmap()->Bitmap{
+        Bitmap::from_qr(&QrCode::encode_text("UR:SHAMIR/1-1/ABC", EccLevel::Medium).unwrap(), 0, 2)
+    }
+
+    #[test]
+    fn png_decodes_to_the_bitmap() {
+        let bitmap=bitmap();
+        let decoder=png::Decoder::new(std::io::Cursor::new(png(&bitmap).unwrap()));
+        let mut reader=decoder.read_info().unwrap();
+        let mut buf=vec![0; reader.output_buffer_size().unwrap()];
+        let info=reader.next_frame(&mut buf).unwrap();
+        assert_eq!((info.width as usize, info.height as usize), (bitmap.width, bitmap.height));
+        let stride=info.line_size;
+        for (y, row) in bitmap.pixels.chunks(bitmap.width).enumerate() {
+            for (x, &dark) in row.iter().enumerate() {
+                let white=buf[y*stride+x/8]>>(7-x%8)&1==1;
+                assert_eq!(dark, !white, "pixel {x},{y}");
+            }
+        }
+    }
+
+    #[test]
+    fn gif_holds_every_frame() {
+        let frames=[bitmap(), bitmap(), bitmap()];
+        let mut options=gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let gif=gif(&frames, 250).unwrap();
+        let mut decoder=options.read_info(gif.as_slice()).unwrap();
+        let mut count=0;
+        while let Some(frame)=decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 25);
+            assert_eq!(frame.buffer.iter().map(|&i| i==1).collect::<Vec<_>>(), frames[count].pixels);
+            count+=1;
+        }
+        assert_eq!(count, 3);
+    }
+}
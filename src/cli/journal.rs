@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use shamir_rust::journal::{self, JournalEvent};
+
+use super::{dates, output, tr, Args, CliError};
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    let path=args.positional.get(1).ok_or_else(|| CliError::with_usage(tr!("missing-journal")))?;
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("verify") => {
+            let count=journal::verify_journal(Path::new(path))?;
+            output::info(args, &tr!("journal-intact", count=count), &[&"intact", &count]);
+            Ok(())
+        }
+        Some("show") => {
+            for entry in journal::read_journal(Path::new(path))? {
+                println!("{:>4} {} {:<9} [{}] {}", entry.seq, entry.timestamp, entry.event, entry.fingerprints.join(" "), entry.note);
+            }
+            Ok(())
+        }
+        _ => Err(CliError::with_usage(tr!("unknown-journal-command"))),
+    }
+}
+
+// Appends to the journal named by --journal, if any.
+pub fn record(args:&Args,event:JournalEvent,fingerprints:&[String],note:&str)->Result<(),String>{
+    record_to(args.value("journal").filter(|v| !v.is_empty()), event, fingerprints, note)
+}
+
+pub fn record_to(path:Option<&str>,event:JournalEvent,fingerprints:&[String],note:&str)->Result<(),String>{
+    match path {
+        Some(path) => journal::append_entry_at(Path::new(path), event, fingerprints, note, dates::now().unwrap_or(0)).map(|_| ()),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::cli::error::BAD_ARGUMENTS;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn events_go_to_the_journal_option() {
+        let path=std::env::temp_dir().join(format!("shamir-cli-journal-{}.log", std::process::id()));
+        let _=fs::remove_file(&path);
+        let file=path.display().to_string();
+        record(&args(&[]), JournalEvent::Split, &["abcd".to_string()], "k=2 n=3").unwrap();
+        assert!(!path.exists());
+
+        record(&args(&["--journal", &file]), JournalEvent::Split, &["abcd".to_string()], "k=2 n=3").unwrap();
+        record_to(Some(&file), JournalEvent::Recovery, &["abcd".to_string()], "join").unwrap();
+        assert_eq!(journal::read_journal(&path).unwrap().len(), 2);
+        run(&args(&["verify", &file, "--quiet"])).unwrap();
+        run(&args(&["show", &file])).unwrap();
+
+        let edited=fs::read_to_string(&path).unwrap().replace("k=2 n=3", "k=1 n=3");
+        fs::write(&path, edited).unwrap();
+        assert!(run(&args(&["verify", &file, "--quiet"])).is_err());
+        assert_eq!(run(&args(&["verify"])).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(run(&args(&["replay", &file])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,153 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use rand::RngCore;
+use shamir_rust::dealer::is_dealer_signature;
+use shamir_rust::sha256::to_hex;
+
+use super::{dealer, tr};
+
+// OpenPGP recipients through the local `gpg` binary: one share per
+// encryption-capable public key in a keyring file, each encrypted (ASCII
+// armored) to its key. Keys are addressed by fingerprint; trust is taken as
+// given, since the keyring file itself is the custodian list.
+
+pub struct Recipient{
+    pub fingerprint:String,
+    pub uid:String,
+}
+
+fn gpg(keyring:&str)->Result<Command,String>{
+    // gpg resolves bare keyring names against its home directory.
+    let path=Path::new(keyring).canonicalize().map_err(|e| format!("{keyring}: {e}"))?;
+    let mut command=Command::new("gpg");
+    command.args(["--batch", "--quiet", "--no-default-keyring", "--keyring"]).arg(path);
+    Ok(command)
+}
+
+pub fn recipients(keyring:&str)->Result<Vec<Recipient>,String>{
+    let out=gpg(keyring)?.args(["--with-colons", "--list-keys"])
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| tr!("gpg-unavailable", error=e))?;
+    if !out.status.success() {
+        return Err(tr!("gpg-list-failed", keyring=keyring));
+    }
+
+    // Colon records: a `pub` line opens a key (field 2 validity, field 12
+    // capabilities), followed by its `fpr` and `uid` lines (value in field 10).
+    let mut found:Vec<Recipient>=Vec::new();
+    let mut usable=false;
+    let mut pending:Option<Recipient>=None;
+    for line in String::from_utf8_lossy(&out.stdout).lines() {
+        let fields:Vec<&str>=line.split(':').collect();
+        match fields.first().copied() {
+            Some("pub") => {
+                found.extend(pending.take().filter(|_| usable));
+                let validity=fields.get(1).copied().unwrap_or("");
+                usable=!matches!(validity, "r" | "e" | "d" | "i") && fields.get(11).is_some_and(|caps| caps.contains('E'));
+                pending=Some(Recipient { fingerprint: String::new(), uid: String::new() });
+            }
+            Some("fpr") => if let Some(r)=pending.as_mut().filter(|r| r.fingerprint.is_empty()) {
+                r.fingerprint=fields.get(9).copied().unwrap_or("").to_string();
+            },
+            Some("uid") => if let Some(r)=pending.as_mut().filter(|r| r.uid.is_empty()) {
+                r.uid=fields.get(9).copied().unwrap_or("").to_string();
+            },
+            _ => {}
+        }
+    }
+    found.extend(pending.take().filter(|_| usable));
+    if found.is_empty() {
+        return Err(tr!("keyring-no-keys", keyring=keyring));
+    }
+    Ok(found)
+}
+
+pub fn encrypt(keyring:&str,fingerprint:&str,data:&[u8])->Result<Vec<u8>,String>{
+    let mut child=gpg(keyring)?.args(["--trust-model", "always", "--armor", "--encrypt", "--recipient", fingerprint])
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| tr!("gpg-unavailable", error=e))?;
+    child.stdin.take().ok_or_else(|| tr!("gpg-stdin-unavailable"))?.write_all(data).map_err(|e| e.to_string())?;
+    let out=child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(tr!("gpg-encrypt-failed", fingerprint=fingerprint));
+    }
+    Ok(out.stdout)
+}
+
+// Detached, ASCII-armored signature by a secret key in the default gpg home,
+// or by the current key of a dealer directory (see `dealer.rs`).
+pub fn sign(key:&str,passphrase:Option<&str>,data:&[u8])->Result<Vec<u8>,String>{
+    if dealer::is_home(Path::new(key)) {
+        return dealer::sign(Path::new(key), passphrase.map(Path::new), data);
+    }
+    let mut child=Command::new("gpg").args(["--batch", "--quiet", "--armor", "--detach-sign", "--local-user", key])
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| tr!("gpg-unavailable", error=e))?;
+    child.stdin.take().ok_or_else(|| tr!("gpg-stdin-unavailable"))?.write_all(data).map_err(|e| e.to_string())?;
+    let out=child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(tr!("gpg-sign-failed", key=key));
+    }
+    Ok(out.stdout)
+}
+
+// Checks a detached signature against the keys in `keyring` and returns the
+// signing key's fingerprint. A dealer directory's signature is checked
+// against its exported key chain instead.
+pub fn verify(keyring:&str,data:&[u8],signature:&[u8])->Result<String,String>{
+    if is_dealer_signature(signature) {
+        return dealer::verify(keyring, data, signature);
+    }
+    let mut nonce=[0u8;8];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let sig_path=std::env::temp_dir().join(format!("shamir-{}.sig", to_hex(&nonce)));
+    std::fs::write(&sig_path, signature).map_err(|e| format!("{}: {e}", sig_path.display()))?;
+    let result=gpg(keyring).and_then(|mut command| {
+        let mut child=command.args(["--trust-model", "always", "--status-fd", "1", "--verify"]).arg(&sig_path).arg("-")
+            .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| tr!("gpg-unavailable", error=e))?;
+        child.stdin.take().ok_or_else(|| tr!("gpg-stdin-unavailable"))?.write_all(data).map_err(|e| e.to_string())?;
+        child.wait_with_output().map_err(|e| e.to_string())
+    });
+    let _=std::fs::remove_file(&sig_path);
+    // Status lines: "[GNUPG:] VALIDSIG <fingerprint> ..." only for a good signature.
+    String::from_utf8_lossy(&result?.stdout).lines()
+        .find_map(|line| line.strip_prefix("[GNUPG:] VALIDSIG "))
+        .and_then(|rest| rest.split_whitespace().next())
+        .map(|fingerprint| fingerprint.to_string())
+        .ok_or_else(|| tr!("signature-invalid", keyring=keyring))
+}
+
+// File-name-safe form of a user ID: "Alice <a@x.org>" -> "Alice_a@x.org".
+pub fn uid_label(uid:&str)->String{
+    let cleaned:String=uid.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || "@.-+".contains(c) { c } else { '_' })
+        .collect();
+    cleaned.split('_').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn uid_labels_are_file_name_safe() {
+        assert_eq!(uid_label("Alice <alice@example.org>"), "Alice_alice@example.org");
+        assert_eq!(uid_label("Bob O'Neil (work) <bob+vault@x.org>"), "Bob_O_Neil_work_bob+vault@x.org");
+        assert_eq!(uid_label("../../etc"), ".._.._etc");
+        assert_eq!(uid_label(""), "");
+    }
+
+    #[test]
+    fn missing_keyring_fails_before_running_gpg() {
+        let keyring=std::env::temp_dir().join(format!("shamir-no-keyring-{}.gpg", std::process::id())).display().to_string();
+        assert!(matches!(recipients(&keyring), Err(e) if e.starts_with(&keyring)));
+        assert!(encrypt(&keyring, "ABCD", b"share").unwrap_err().starts_with(&keyring));
+    }
+}
@@ -0,0 +1,390 @@
+# English CLI messages; the fallback for every other catalog.
+# Message ids are stable; placeables are named after the value they show.
+
+usage =
+    usage:
+      shamir                                    run the split/join demo
+      shamir split <file|-> --threshold K (--shares N | --indices x,y,... | --keyring FILE)
+    {"        "}[--out DIR] [--name-template T] [--labels a,b,...]
+    {"        "}[--transcript FILE] [--contacts c1,c2,...] [--drill-every DAYS]
+    {"        "}[--check FILE] [--watermark] [--ack] [--set-commitment] [--entropy-report] [--spares N] [--compact [--sparse] [--transform NAMES]]
+    {"        "}[--instructions FILE] [--hints FILE] [--sign-with KEYID] [--device NAME | --factor-file FILE] [--plan]
+    {"        "}[--encoding text|hex|base32|bech32] [--receipts DIR]
+      shamir split --from-plan PLAN.json
+      shamir split --stdin-multi --threshold K --shares N [--nul] [--out DIR]
+                                                one split per stdin line (NUL with --nul);
+                                                JSON share sets on stdout, or DIR/1, DIR/2, ...
+      shamir join <share>... [--out FILE] [--range START..END] [--check FILE]
+    {"        "}[--copy [--clear-after SECS]] [--shred-inputs] [--dictation] [--strict]
+    {"        "}[--device NAME | --factor-file FILE] [--token FILE --token-key KEYRING] [--require-token]
+      shamir activate-spare <spare> --transcript FILE --label NAME [--contact C] [--out DIR]
+      shamir copy <share> [--clear-after SECS]
+      shamir verify <share>... [--shares N]     lint shares before distributing them
+      shamir info <share> [--dealer-key KEYRING] [--procedure FILE]
+      shamir trace <share> --transcript FILE    name the custodian a watermarked share was issued to
+      shamir receipt verify <share> --receipt FILE --dealer-key KEYRING
+                                                check a share against the custodian's signed split receipt
+      shamir ack respond <share> [--out FILE]   answer the delivery challenge in a share from split --ack
+      shamir ack record <response>... --transcript FILE
+                                                check custodians' answers and date them in the transcript
+      shamir bundle <share>                     print the share as text, bech32 and fingerprint
+      shamir plate <share> [--width W]          print a stamping grid for a steel backup plate
+      shamir dictate <share>                    print bech32 groups and checks for join --dictation
+      shamir reveal <share> [--groups N]        page through bech32 groups on screen, never the whole share
+      shamir frames export <share> [--out FILE.gif | --out DIR] [--fragment-len BYTES]
+    {"        "}[--frames N] [--scale PX] [--delay MS]
+                                                animated QR parts for shares too large for one QR code
+      shamir frames import [<file>...] [--out FILE]
+                                                read scanned parts, one per line, in any order
+      shamir shred <file>...
+      shamir revoke (<share>... | --fingerprint F,... | --generation G) --list FILE
+            --sign-with KEYID [--reason TEXT] [--revocation-key KEYRING]
+      shamir split-dir <dir> --threshold K --shares N [--out DIR]
+      shamir join-dir <bundle>... --out DIR [--min-generation G]
+      shamir rotate <bundle>... --threshold K --shares N --out DIR
+      shamir split-stream [<file|->] --threshold K --shares N --out DIR [--chunk-size BYTES]
+    {"        "}[--high-watermark BYTES] [--low-watermark BYTES] [--mmap]
+                                                split a pipe (tar | shamir split-stream) in bounded memory
+      shamir split-stream <file> --threshold K --shares N --out DIR --resumable [--chunk-size BYTES]
+      shamir split-stream <file> --out DIR --resume
+                                                deal a file with a journal, continue after a crash
+      shamir join-stream <share stream>... [--out FILE]
+      shamir selftest-statistics [--trials T] [--seed S] [--threshold K] [--shares N]
+      shamir doctor [--out DIR] [--strict]      check this machine before a ceremony
+      shamir selftest-spec                      (built with --features spec)
+      shamir drills status <transcript> [--today YYYY-MM-DD]
+      shamir journal (verify|show) <journal>
+      shamir device init (--device NAME | --factor-file FILE)
+      shamir device export (--device NAME | --factor-file FILE) [--out FILE]
+      shamir device escrow (--device NAME | --factor-file FILE) --threshold K --shares N [--out DIR]
+      shamir device import <factor|share>... (--device NAME | --factor-file FILE)
+      shamir dealer init --dealer-home DIR --uid "NAME <EMAIL>" --passphrase-file FILE
+      shamir dealer export --dealer-home DIR [--out FILE]
+      shamir dealer rotate --dealer-home DIR --passphrase-file FILE [--uid "NAME <EMAIL>"]
+      shamir dealer show --dealer-home DIR
+      shamir dealer check --dealer-key FILE --pinned FILE
+      shamir token issue [<share>...] [--fingerprint F,...] [--transcript FILE] --purpose TEXT
+            --expires YYYY-MM-DD|@SECS [--outputs file,stdout,clipboard] --sign-with KEYID --out FILE
+                                                a dealer-signed recovery token for join --token
+      shamir token show <token> [--token-key KEYRING]
+      shamir seal --systemd <share> --name NAME [--with-key KEY] [--out FILE]
+      shamir unseal --systemd [<credential>...] [--credential NAME,...] [--fetch CMD]
+    {"        "}[--threshold K] --exec CMD     (built with --features systemd)
+
+      split, join, split-dir, join-dir and rotate accept --journal FILE to append
+      a hash-chained entry for the operation.
+
+      join, join-dir and verify accept --revocations FILE [--revocation-key KEYRING]
+      to refuse revoked shares, or only warn about them with --on-revoked warn;
+      --revocations-pin FILE keeps the last verified list and refuses one that
+      drops its entries.
+
+      split and join with --device (OS keychain) or --factor-file couple the
+      shares to a device factor: the shares alone no longer recover the secret.
+
+      join --token FILE --token-key KEYRING only reconstructs shares the token
+      covers, before it expires and into an output form it allows; --require-token
+      (best set in a config profile) refuses any join without one.
+
+      --sign-with takes a gpg key ID or a dealer directory (shamir dealer init);
+      --passphrase-file FILE unlocks the key without a prompt.
+
+      Every command accepts --logical-time (YYYY-MM-DD | @SECONDS | omit) to
+      pin or leave out the dates it writes, for byte-identical reruns; without
+      it, SOURCE_DATE_EPOCH is honoured.
+
+      Every command accepts --profile NAME to take default options from that
+      profile of ~/.config/shamir/config.toml (or --config FILE); the profile
+      named default applies without it.
+
+      Every command accepts --no-coredump to disable core dumps and scrub the
+      library's RNG state before exiting.
+
+      Every command accepts --quiet (essential output only) or --porcelain
+      (stable tab-separated records for scripts), and --lang LANG (en, es) to
+      choose the language of messages; the default follows the locale.
+
+    exit codes: 0 success, 1 other failure, 2 bad arguments,
+      3 insufficient shares, 4 integrity failure (malformed, inconsistent or
+      mismatching shares)
+
+## Arguments
+
+missing-option = Missing --{ $name }
+option-not-number = --{ $name } must be a number
+quiet-porcelain-conflict = --quiet and --porcelain cannot be combined
+logical-time-malformed = --logical-time { $value } is not YYYY-MM-DD, @SECONDS or omit
+source-date-epoch-malformed = SOURCE_DATE_EPOCH { $value } is not a number of seconds
+logical-time-omitted = { $what } needs a date; give --logical-time a date instead of omit
+config-no-home = --profile needs HOME or XDG_CONFIG_HOME to find the config file; give --config FILE
+config-missing = Config file { $path } not found
+config-malformed = { $path } line { $line }: expected [profile.NAME], [profile.NAME.COMMAND] or key = value
+config-reserved-key = { $path }: --{ $key } cannot be set in a profile
+config-unknown-profile = No profile { $profile } in { $path } (available: { $available })
+unknown-command = Unknown command { $command }
+unknown-language = Unknown language { $lang } (available: { $available })
+unknown-drills-command = Unknown drills command
+unknown-journal-command = Unknown journal command
+unknown-frames-command = Unknown frames command
+missing-secret-file = Missing secret file
+missing-share-file = Missing share file
+missing-spare-file = Missing spare share file
+missing-shares = Missing shares
+missing-files = Missing files
+missing-directory = Missing directory
+missing-bundles = Missing bundles
+missing-transcript = Missing transcript
+missing-ack-file = Missing acknowledgment file
+missing-journal = Missing journal file
+index-out-of-range = --indices entry { $index } is not in 0..=255
+keyring-with-indices = --keyring cannot be combined with --indices
+keyring-count-mismatch = --keyring holds { $count } keys but --shares asks for { $shares }
+keyring-without-recipients = Keyring and recipients must be given together
+drill-every-not-number = --drill-every must be a number of days
+custodian-count-mismatch = --{ $name } lists { $count } custodians, expected { $expected }
+check-with-indices = --check cannot be combined with --indices
+secret-guessable = The secret is only { $bytes } bytes: with { $what }, any { $custodians } custodians together can test guesses of it offline; use it only for a random secret of 16 bytes or more
+instructions-without-signer = --instructions or --hints and --sign-with must be given together
+receipts-without-signer = --receipts needs --sign-with: receipts are signed by the dealer
+hint-invalid = Hint "{ $hint }": { $error }
+spares-without-transcript = --spares needs --transcript to record the sealed spares
+watermark-without-transcript = --watermark needs --transcript to keep the custodians' nonces
+ack-without-transcript = --ack needs --transcript to keep the challenges and expected answers
+entropy-without-transcript = --entropy-report needs --transcript to record the report
+entropy-passed = Share bytes pass the entropy checks ({ $bytes } bytes, chi-squared { $chi }, serial correlation { $serial })
+entropy-failed = Share bytes fail the entropy checks ({ $bytes } bytes, chi-squared { $chi }, serial correlation { $serial } over limit { $limit }, OS random source { $os }); the report is in the transcript
+batch-empty = No secrets on stdin
+batch-done = Split { $records } secrets into { $dir }
+transform-without-compact = --sparse and --transform need --compact, whose header records the transforms
+range-with-transform = --range cannot be used with shares of a secret transformed before splitting (--sparse, --transform)
+transform-mismatch = { $file } was split with a different transform than the other shares
+compact-with-metadata = --compact cannot be combined with --watermark, --ack, --set-commitment, --instructions or --hints
+encoding-unknown = Unknown share encoding { $name }; known: { $known }
+encoding-with-format-options = --encoding cannot be combined with --compact, --watermark, --ack, --set-commitment, --instructions or --hints
+range-malformed = --range must be START..END
+range-start-not-number = --range start must be a number
+range-end-not-number = --range end must be a number
+clear-after-not-number = --clear-after must be a number of seconds
+today-not-date = --today must be YYYY-MM-DD
+
+missing-revocation-target = Missing shares, --fingerprint or --generation to revoke
+on-revoked-invalid = --on-revoked must be deny or warn, not { $value }
+
+## Split plans and file names
+
+plan-wrong-version = Plan is not a version { $version } split plan
+plan-missing = Plan is missing { $key }
+plan-index-out-of-range = Plan index is not in 0..=255
+plan-custodian-count = Plan lists { $count } custodians, expected { $expected }
+plan-secret-size = { $input } is { $bytes } bytes but the plan was approved for { $expected }
+template-duplicate-name = Template { $template } gives several shares the name { $name }
+template-unclosed = Unclosed placeholder in { $template }
+template-unknown-placeholder = Unknown placeholder { $placeholder } in { $template }
+template-invalid-name = Template { $template } produced an invalid file name { $name }
+
+## Commands
+
+secret-written = Wrote { $bytes } bytes to { $path }
+share-given-twice = { $file }: share { $index } given twice
+verify-failed = { $warnings } warning(s) in { $shares } shares
+verify-ok = { $shares } shares OK
+spare-unknown = Share { $index } is not a spare in { $transcript }
+spare-mismatch = { $file } does not match spare { $index } in the transcript
+spare-already-activated = Spare { $index } was already activated on { $date }
+info-share = share { $index }, { $bytes } bytes, fingerprint { $fingerprint }
+info-hint = hint: { $hint }
+info-hint-unchecked = hint: { $hint } (signature not checked)
+info-public-key = protects the private half of { $algorithm } key { $fingerprint }
+info-no-instructions = no recovery instructions
+procedure-mismatch = { $path } is not the procedure the instructions refer to
+procedure-matches = procedure { $path } matches
+signature-good = signature good, dealer key { $fingerprint }
+signature-unchecked = signature not checked (pass --dealer-key)
+no-watermark = { $file } carries no watermark
+watermark-unmatched = Watermark on { $file } matches no custodian in the transcript
+traced = share { $index } issued to { $label } ({ $contact })
+no-contact = no contact
+split-dir-done = Split { $files } files into { $bundles } bundles under { $dir } (threshold { $threshold })
+join-dir-done = Restored { $files } files (generation { $generation }) into { $dir }
+split-stream-dealt = Dealt { $bytes } bytes into { $shares } share streams (threshold { $threshold }); { $resumed } of { $chunks } chunks were already on disk
+resumable-needs-file = --resumable and --resume need an input file, not standard input
+resumable-with-mmap = --resumable and --resume read the input chunk by chunk; drop --mmap
+dealing-journal-exists = { $path } is left from an interrupted dealing; continue it with --resume or remove it
+split-stream-done = Split { $bytes } bytes into { $shares } share streams (threshold { $threshold }); reading paused { $stalls } times for slow writers
+rotate-done = Rotated to generation { $generation } with { $bundles } bundles under { $dir } (threshold { $threshold })
+generation-too-old = Payload is generation { $generation }, older than required { $min }
+bundle-different-payload = { $bundle } holds a different payload
+bundle-different-manifest = { $bundle } holds a different manifest.txt
+journal-intact = Journal intact: { $count } entries
+drill-unscheduled = no drill scheduled
+drill-overdue = OVERDUE since { $due }
+drill-due = due { $due }
+drills-share = share
+drills-summary = { $overdue } of { $total } custodians overdue as of { $today }
+shred-warning = overwriting is best effort; SSD wear levelling, copy-on-write filesystems (btrfs, ZFS, APFS), snapshots and backups may keep old copies of the data
+shred-not-regular = { $file } is not a regular file (symbolic links are not followed); nothing was overwritten
+shredded = shredded { $file }
+selftest-header = { $samples } samples per share, seed { $seed }, k={ $k }, n={ $n }
+selftest-share = share { $index }: chi2 { $chi2 } (limit { $limit }), entropy { $entropy } bits  { $verdict }
+selftest-ok = ok
+selftest-fail = FAIL
+selftest-repeat-rate = neighbour repeat rate { $rate } (limit { $limit })
+selftest-failed = Statistical self-test failed
+selftest-passed = Statistical self-test passed
+doctor-hint = hint: { $hint }
+doctor-failed = Checks not passed: { $count }
+doctor-unchecked = cannot be checked on this platform
+doctor-rng-ok = OS random source answers; { $bytes } share bytes from a test split look uniform
+doctor-rng-unavailable = the OS random source does not answer
+doctor-rng-unavailable-hint = do not run a ceremony on this machine until getrandom or /dev/urandom works
+doctor-rng-stuck = the OS random source returns repeated or zero bytes
+doctor-rng-biased = { $bytes } share bytes from a test split do not look uniform
+doctor-rng-biased-hint = rerun doctor; if it fails again, run selftest-statistics and use another machine
+doctor-core-off = core dumps are disabled
+doctor-core-on = core dumps are enabled; a crash would write secret bytes to disk
+doctor-core-on-handler = core dumps are enabled and collected by { $handler }; a crash would write secret bytes to disk
+doctor-core-hint = pass --no-coredump to every command, or run `ulimit -c 0` in this shell
+doctor-swap-ok = no unencrypted swap is active
+doctor-swap-encrypted-os = swap is encrypted by the OS
+doctor-swap-exposed = unencrypted swap is active on { $devices }
+doctor-swap-hint = run `swapoff -a` for the ceremony, or use encrypted swap or zram
+doctor-clipboard-ok = no clipboard manager is running
+doctor-clipboard-running = clipboard manager running: { $managers }; it keeps copied shares in its history
+doctor-clipboard-hint = quit it for the ceremony, or do not use --copy
+doctor-scrollback-no-terminal = output is not a terminal
+doctor-scrollback-ok = no terminal multiplexer; reveal clears the scrollback it writes
+doctor-scrollback-multiplexer = running inside { $multiplexer }, which keeps scrollback after the window closes
+doctor-scrollback-hint = run the ceremony outside tmux or screen, or clear their history afterwards
+doctor-disk-ok = { $mib } MiB free for { $path }
+doctor-disk-low = only { $mib } MiB free for { $path }
+doctor-disk-hint = free some space or pass --out on another file system
+spec-conforms = { $package } conforms to { $spec } ({ $vectors } vectors)
+
+revoked = revoked { $kind } { $value } (list serial { $serial })
+share-revoked = share { $index } was revoked on { $revocation }
+generation-revoked = generation { $generation } was revoked on { $revocation }
+revocations-unsigned = { $path } is not signed
+revocations-unchecked = revocation list signature not checked (pass --revocation-key)
+revoke-needs-key = { $path } exists: pass --revocation-key to check its signature before extending it
+revocations-pin-without-key = --revocations-pin needs --revocation-key: only a verified list is pinned
+revocations-rollback = { $path } does not extend the pinned list: { $error }
+unknown-device-command = Unknown device command
+missing-device = Missing --device or --factor-file
+device-and-factor-file = --device and --factor-file cannot be combined
+factor-exists = A device factor already exists at { $location }
+factor-created = Created device factor { $fingerprint } at { $location }
+factor-imported = Imported device factor { $fingerprint } into { $location }
+split-coupled = Shares are coupled to device factor { $fingerprint }; keep a backup of it apart from the shares
+keychain-unavailable = Cannot reach the OS keychain: { $error }
+keychain-failed = The OS keychain has no such device factor or refused the request
+keychain-malformed = Keychain entry { $name } is not a device factor
+unknown-dealer-command = Unknown dealer command
+dealer-exists = { $home } already exists; dealer init needs a new directory
+dealer-empty-passphrase = { $path } holds an empty passphrase; the dealer key must be encrypted at rest
+dealer-needs-passphrase = Signing with the dealer directory { $home } needs --passphrase-file
+dealer-created = Created dealer key { $fingerprint } in { $home }; export it for the custodians to pin
+dealer-exported = Exported dealer key { $fingerprint } to { $path }
+dealer-rotated = Rotated to dealer key { $fingerprint }, certified by { $previous }
+dealer-current = current { $fingerprint }
+dealer-retired = retired { $fingerprint } { $date }
+dealer-certified = Dealer key { $fingerprint } is certified by the pinned key { $previous }
+dealer-not-certified = Dealer key { $fingerprint } is not certified by the pinned key { $previous }
+unknown-receipt-command = Unknown receipt command
+receipt-written = Wrote the receipt for share { $index } to { $path }
+receipt-unsigned = { $path } is not signed
+unknown-ack-command = Unknown ack command
+no-ack-challenge = { $file } carries no acknowledgment challenge
+acknowledged = share { $index } acknowledged by { $label } on { $date }
+ack-pending = { $count } custodians have not acknowledged yet: { $labels }
+receipt-matches = Share { $index } ({ $fingerprint }) matches its receipt: ceremony { $ceremony }, { $threshold } of { $shares }, created { $created }, dealer key { $signer }
+unknown-token-command = Unknown token command
+missing-token-file = Missing token file
+missing-token-shares = Missing shares, --fingerprint or --transcript for the token to cover
+token-no-purpose = --purpose must not be empty
+token-expires-malformed = --expires must be YYYY-MM-DD or @SECONDS, not { $value }
+token-expires-past = --expires must be later than now
+token-output-unknown = Unknown output form { $form }; use file, stdout or clipboard
+token-issued = Issued recovery token { $id } for { $shares } shares to { $path }
+token-unsigned = { $path } is not signed
+token-without-key = --token needs --token-key to check the dealer's signature
+token-required = a recovery token is required (--token FILE)
+token-output-denied = the recovery token does not allow output to { $form } (allowed: { $allowed })
+token-accepted = Recovery token { $id } accepted: { $purpose } (dealer key { $signer })
+token-summary = token { $id }: { $purpose }
+token-window = valid from { $issued } until { $expires }
+token-outputs = outputs { $outputs }
+token-shares = shares { $shares }
+token-signature-unchecked = signature not checked (pass --token-key)
+missing-systemd = seal and unseal need --systemd
+credential-exists = A credential already exists at { $path }
+credential-sealed = Sealed share { $index } as credential { $name } in { $path }
+credentials-directory-missing = --credential needs $CREDENTIALS_DIRECTORY, set only inside a systemd service
+credential-encrypt-failed = systemd-creds could not encrypt credential { $name }: { $error }
+credential-decrypt-failed = systemd-creds could not decrypt credential { $name }: { $error }
+fetch-failed = { $command } failed: { $error }
+memfd-failed = Cannot create a sealed memfd: { $error }
+memfd-unsupported = Unsealing into a memfd needs Linux
+unsealed = Reconstructed the secret from { $shares } shares into sealed memfd { $fd }
+exec-failed = Cannot start { $command }: { $error }
+
+## Clipboard, dictation and QR frames
+
+clipboard-unavailable = Cannot use the clipboard: { $error }
+clipboard-not-text = Only text can go on the clipboard; use --out for binary secrets
+clipboard-copied = Copied to clipboard; clearing in { $secs } s (Ctrl-C leaves it in place)
+clipboard-changed = Clipboard changed since copy; leaving it alone
+clipboard-cleared = Clipboard cleared
+dictate-check = check { $code }
+dictation-share = Share { $number }: type { $group_len }-character groups, an empty line ends the share (empty first line: done)
+dictation-group = group { $number }:
+dictation-bad-character = '{ $character }' is not a bech32 character, re-enter the group
+dictation-checkpoint = check { $code } (ask the custodian to confirm)
+dictation-final-check = final check { $code }
+dictation-accepted = share { $index } accepted
+dictation-retry = { $error }; dictate this share again
+reveal-not-terminal = reveal only writes to a terminal; use dictate or bundle to print the whole share
+reveal-segment = Share { $index }, segment { $number } of { $segments } (groups { $first }-{ $last })
+reveal-prompt = Enter: next, b: back, q: quit
+reveal-done = Share { $index }: screen cleared
+frames-written = Wrote { $frames } QR frames ({ $fragments } fragments) to { $path }
+frames-scale-too-large = --scale is at most { $max } pixels per module
+frames-none = No valid parts read
+frames-incomplete = Only { $solved } of { $fragments } fragments recovered; scan more frames
+
+## OpenPGP
+
+no-coredump-failed = Cannot disable core dumps: { $error }
+gpg-unavailable = Cannot run gpg: { $error }
+gpg-list-failed = gpg could not list { $keyring }
+gpg-stdin-unavailable = gpg stdin unavailable
+gpg-encrypt-failed = gpg could not encrypt to { $fingerprint }
+gpg-sign-failed = gpg could not sign with { $key }
+keyring-no-keys = { $keyring } holds no usable encryption keys
+signature-invalid = Signature does not verify against { $keyring }
+
+## Warnings and core errors
+
+warning = warning: { $message }
+warning-from = warning: { $source }: { $message }
+error-threshold-too-small = Not k > 1
+error-threshold-above-shares = Not n >= k
+error-too-many-shares = Not n <= 255
+error-no-parts = No parts provided
+error-inconsistent-lengths = Varying lengths of part values
+error-invalid-index = Invalid share index { $index } (must be 1..=255)
+error-duplicate-index = Duplicate share index { $index }
+error-insufficient-parts = Only { $collected } of { $threshold } required parts provided
+error-inconsistent-shares = Shares { $indices } are inconsistent with the others
+error-range-out-of-bounds = Byte range { $start }..{ $end } is outside the { $len }-byte secret
+error-check-failed = Shares do not match the published check value
+error-buffer-size = Buffer holds { $actual } entries, { $expected } required
+error-unknown-scheme = Unknown secret sharing scheme id { $id }
+error-secret-too-large = Secret of { $len } bytes exceeds the { $max }-byte limit
+error-invalid-chunk-size = Chunk size must be at least 1 byte
+error-join-denied = Join not authorized: { $reason }
+error-kdf-output-too-long = Requested { $requested } bytes of key material, HKDF gives at most { $max }
+error-empty-secret = Secret and shares must be at least 1 byte
+error-mislabeled-shares = Shares appear to carry the wrong index; likely correct indices: { $fixes }
+error-not-invertible = A non-zero field element has no inverse; the field fails conformance
+error-foreign-shares = Shares { $indices } belong to a different split
+error-invalid-shares = Shares { $indices } fail their integrity check
@@ -0,0 +1,389 @@
+# Mensajes de la CLI en español. Los ids que falten aquí se muestran en inglés.
+
+usage =
+    uso:
+      shamir                                    ejecuta la demostración de split/join
+      shamir split <fichero|-> --threshold K (--shares N | --indices x,y,... | --keyring FICHERO)
+    {"        "}[--out DIR] [--name-template T] [--labels a,b,...]
+    {"        "}[--transcript FICHERO] [--contacts c1,c2,...] [--drill-every DÍAS]
+    {"        "}[--check FICHERO] [--watermark] [--ack] [--set-commitment] [--entropy-report] [--spares N] [--compact [--sparse] [--transform NAMES]]
+    {"        "}[--instructions FICHERO] [--hints FICHERO] [--sign-with IDCLAVE] [--device NOMBRE | --factor-file FICHERO] [--plan]
+    {"        "}[--encoding text|hex|base32|bech32] [--receipts DIR]
+      shamir split --from-plan PLAN.json
+      shamir split --stdin-multi --threshold K --shares N [--nul] [--out DIR]
+                                                un reparto por línea de stdin (NUL con --nul);
+                                                conjuntos de partes en JSON por stdout, o DIR/1, DIR/2, ...
+      shamir join <parte>... [--out FICHERO] [--range INICIO..FIN] [--check FICHERO]
+    {"        "}[--copy [--clear-after SEG]] [--shred-inputs] [--dictation] [--strict]
+    {"        "}[--device NOMBRE | --factor-file FICHERO] [--token FICHERO --token-key ANILLO] [--require-token]
+      shamir activate-spare <reserva> --transcript FICHERO --label NOMBRE [--contact C] [--out DIR]
+      shamir copy <parte> [--clear-after SEG]
+      shamir verify <parte>... [--shares N]     revisa las partes antes de repartirlas
+      shamir info <parte> [--dealer-key ANILLO] [--procedure FICHERO]
+      shamir trace <parte> --transcript FICHERO identifica al custodio que recibió una parte marcada
+      shamir receipt verify <parte> --receipt FICHERO --dealer-key ANILLO
+                                                comprueba una parte con el resguardo firmado del custodio
+      shamir ack respond <parte> [--out FICHERO] responde al desafío de entrega de una parte de split --ack
+      shamir ack record <respuesta>... --transcript FICHERO
+                                                comprueba las respuestas de los custodios y las fecha en el acta
+      shamir bundle <parte>                     muestra la parte como texto, bech32 y huella
+      shamir plate <parte> [--width W]          muestra una cuadrícula para grabar en una placa de acero
+      shamir dictate <parte>                    muestra grupos bech32 y controles para join --dictation
+      shamir reveal <parte> [--groups N]        pasa por pantalla los grupos bech32, nunca la parte entera
+      shamir frames export <parte> [--out FICHERO.gif | --out DIR] [--fragment-len BYTES]
+    {"        "}[--frames N] [--scale PX] [--delay MS]
+                                                fotogramas QR animados para partes que no caben en un solo QR
+      shamir frames import [<fichero>...] [--out FICHERO]
+                                                lee partes escaneadas, una por línea, en cualquier orden
+      shamir shred <fichero>...
+      shamir revoke (<parte>... | --fingerprint H,... | --generation G) --list FICHERO
+            --sign-with IDCLAVE [--reason TEXTO] [--revocation-key ANILLO]
+      shamir split-dir <dir> --threshold K --shares N [--out DIR]
+      shamir join-dir <paquete>... --out DIR [--min-generation G]
+      shamir rotate <paquete>... --threshold K --shares N --out DIR
+      shamir split-stream [<fichero|->] --threshold K --shares N --out DIR [--chunk-size BYTES]
+    {"        "}[--high-watermark BYTES] [--low-watermark BYTES] [--mmap]
+                                                reparte una tubería (tar | shamir split-stream) con memoria acotada
+      shamir split-stream <fichero> --threshold K --shares N --out DIR --resumable [--chunk-size BYTES]
+      shamir split-stream <fichero> --out DIR --resume
+                                                reparte un fichero con diario, sigue tras una caída
+      shamir join-stream <flujo de parte>... [--out FICHERO]
+      shamir selftest-statistics [--trials T] [--seed S] [--threshold K] [--shares N]
+      shamir doctor [--out DIR] [--strict]      revisa esta máquina antes de una ceremonia
+      shamir selftest-spec                      (compilado con --features spec)
+      shamir drills status <acta> [--today AAAA-MM-DD]
+      shamir journal (verify|show) <diario>
+      shamir device init (--device NOMBRE | --factor-file FICHERO)
+      shamir device export (--device NOMBRE | --factor-file FICHERO) [--out FICHERO]
+      shamir device escrow (--device NOMBRE | --factor-file FICHERO) --threshold K --shares N [--out DIR]
+      shamir device import <factor|parte>... (--device NOMBRE | --factor-file FICHERO)
+      shamir dealer init --dealer-home DIR --uid "NOMBRE <CORREO>" --passphrase-file FICHERO
+      shamir dealer export --dealer-home DIR [--out FICHERO]
+      shamir dealer rotate --dealer-home DIR --passphrase-file FICHERO [--uid "NOMBRE <CORREO>"]
+      shamir dealer show --dealer-home DIR
+      shamir dealer check --dealer-key FICHERO --pinned FICHERO
+      shamir token issue [<parte>...] [--fingerprint H,...] [--transcript FICHERO] --purpose TEXTO
+            --expires AAAA-MM-DD|@SEG [--outputs file,stdout,clipboard] --sign-with IDCLAVE --out FICHERO
+                                                un token de recuperación firmado por el repartidor para join --token
+      shamir token show <token> [--token-key ANILLO]
+      shamir seal --systemd <parte> --name NOMBRE [--with-key CLAVE] [--out FICHERO]
+      shamir unseal --systemd [<credencial>...] [--credential NOMBRE,...] [--fetch ORDEN]
+    {"        "}[--threshold K] --exec ORDEN   (compilado con --features systemd)
+
+      split, join, split-dir, join-dir y rotate aceptan --journal FICHERO para
+      añadir una entrada encadenada por hash con la operación.
+
+      join, join-dir y verify aceptan --revocations FICHERO [--revocation-key ANILLO]
+      para rechazar las partes revocadas, o solo avisar con --on-revoked warn;
+      --revocations-pin FICHERO guarda la última lista verificada y rechaza
+      una que elimine sus entradas.
+
+      split y join con --device (llavero del sistema) o --factor-file acoplan las
+      partes a un factor de dispositivo: las partes solas ya no recuperan el secreto.
+
+      join --token FICHERO --token-key ANILLO solo reconstruye las partes que cubre
+      el token, antes de que caduque y en una forma de salida que permita;
+      --require-token (mejor en un perfil de configuración) rechaza todo join sin él.
+
+      --sign-with acepta un id de clave gpg o un directorio de repartidor
+      (shamir dealer init); --passphrase-file FICHERO desbloquea la clave sin preguntar.
+
+      Todas las órdenes aceptan --logical-time (AAAA-MM-DD | @SEGUNDOS | omit)
+      para fijar u omitir las fechas que escriben, de modo que repetirlas dé
+      ficheros idénticos; sin la opción, se respeta SOURCE_DATE_EPOCH.
+
+      Todas las órdenes aceptan --profile NOMBRE para tomar opciones por defecto
+      de ese perfil de ~/.config/shamir/config.toml (o --config FICHERO); sin la
+      opción se aplica el perfil llamado default.
+
+      Todas las órdenes aceptan --no-coredump para desactivar los volcados de
+      memoria y borrar el estado del RNG de la biblioteca antes de salir.
+
+      Todas las órdenes aceptan --quiet (solo la salida esencial) o --porcelain
+      (registros separados por tabuladores, estables para scripts), y --lang IDIOMA
+      (en, es) para elegir el idioma de los mensajes; por defecto se sigue el locale.
+
+    códigos de salida: 0 éxito, 1 otro error, 2 argumentos incorrectos,
+      3 partes insuficientes, 4 error de integridad (partes malformadas,
+      inconsistentes o que no coinciden)
+
+## Argumentos
+
+missing-option = Falta --{ $name }
+option-not-number = --{ $name } debe ser un número
+quiet-porcelain-conflict = --quiet y --porcelain no se pueden combinar
+logical-time-malformed = --logical-time { $value } no es AAAA-MM-DD, @SEGUNDOS ni omit
+source-date-epoch-malformed = SOURCE_DATE_EPOCH { $value } no es un número de segundos
+logical-time-omitted = { $what } necesita una fecha; da a --logical-time una fecha en lugar de omit
+unknown-command = Orden desconocida { $command }
+config-no-home = --profile necesita HOME o XDG_CONFIG_HOME para encontrar el fichero de configuración; da --config FICHERO
+config-missing = No se encuentra el fichero de configuración { $path }
+config-malformed = { $path } línea { $line }: se esperaba [profile.NOMBRE], [profile.NOMBRE.ORDEN] o clave = valor
+config-reserved-key = { $path }: --{ $key } no se puede fijar en un perfil
+config-unknown-profile = No hay ningún perfil { $profile } en { $path } (disponibles: { $available })
+unknown-language = Idioma desconocido { $lang } (disponibles: { $available })
+unknown-drills-command = Orden de simulacros desconocida
+unknown-journal-command = Orden de diario desconocida
+unknown-frames-command = Orden de fotogramas desconocida
+missing-secret-file = Falta el fichero del secreto
+missing-share-file = Falta el fichero de la parte
+missing-spare-file = Falta el fichero de la parte de reserva
+missing-shares = Faltan las partes
+missing-files = Faltan los ficheros
+missing-directory = Falta el directorio
+missing-bundles = Faltan los paquetes
+missing-transcript = Falta el acta
+missing-ack-file = Falta el fichero de acuse
+missing-journal = Falta el fichero del diario
+index-out-of-range = La entrada { $index } de --indices no está en 0..=255
+keyring-with-indices = --keyring no se puede combinar con --indices
+keyring-count-mismatch = --keyring contiene { $count } claves pero --shares pide { $shares }
+keyring-without-recipients = El anillo de claves y los destinatarios deben darse juntos
+drill-every-not-number = --drill-every debe ser un número de días
+custodian-count-mismatch = --{ $name } enumera { $count } custodios, se esperaban { $expected }
+check-with-indices = --check no se puede combinar con --indices
+secret-guessable = El secreto solo tiene { $bytes } bytes: con { $what }, { $custodians } custodios cualesquiera pueden probar suposiciones sin conexión; úselo solo con un secreto aleatorio de 16 bytes o más
+instructions-without-signer = --instructions o --hints y --sign-with deben darse juntos
+receipts-without-signer = --receipts necesita --sign-with: el repartidor firma los resguardos
+hint-invalid = Pista "{ $hint }": { $error }
+spares-without-transcript = --spares necesita --transcript para registrar las partes de reserva selladas
+watermark-without-transcript = --watermark necesita --transcript para guardar los nonces de los custodios
+ack-without-transcript = --ack necesita --transcript para guardar los desafíos y las respuestas esperadas
+entropy-without-transcript = --entropy-report necesita --transcript para guardar el informe
+entropy-passed = Los bytes de las partes superan las pruebas de entropía ({ $bytes } bytes, chi cuadrado { $chi }, correlación serial { $serial })
+entropy-failed = Los bytes de las partes no superan las pruebas de entropía ({ $bytes } bytes, chi cuadrado { $chi }, correlación serial { $serial } con límite { $limit }, fuente aleatoria del sistema { $os }); el informe está en el acta
+batch-empty = No hay secretos en stdin
+batch-done = { $records } secretos repartidos en { $dir }
+transform-without-compact = --sparse y --transform necesitan --compact, cuya cabecera registra las transformaciones
+range-with-transform = --range no se puede usar con partes de un secreto transformado antes del reparto (--sparse, --transform)
+transform-mismatch = { $file } se repartió con una transformación distinta de la de las otras partes
+compact-with-metadata = --compact no se puede combinar con --watermark, --ack, --set-commitment, --instructions ni --hints
+encoding-unknown = Codificación de parte desconocida { $name }; conocidas: { $known }
+encoding-with-format-options = --encoding no se puede combinar con --compact, --watermark, --ack, --set-commitment, --instructions ni --hints
+range-malformed = --range debe ser INICIO..FIN
+range-start-not-number = El inicio de --range debe ser un número
+range-end-not-number = El final de --range debe ser un número
+clear-after-not-number = --clear-after debe ser un número de segundos
+today-not-date = --today debe ser AAAA-MM-DD
+
+missing-revocation-target = Faltan las partes, --fingerprint o --generation que revocar
+on-revoked-invalid = --on-revoked debe ser deny o warn, no { $value }
+
+## Planes de reparto y nombres de fichero
+
+plan-wrong-version = El plan no es un plan de reparto de la versión { $version }
+plan-missing = Al plan le falta { $key }
+plan-index-out-of-range = Un índice del plan no está en 0..=255
+plan-custodian-count = El plan enumera { $count } custodios, se esperaban { $expected }
+plan-secret-size = { $input } tiene { $bytes } bytes pero el plan se aprobó para { $expected }
+template-duplicate-name = La plantilla { $template } da a varias partes el nombre { $name }
+template-unclosed = Marcador sin cerrar en { $template }
+template-unknown-placeholder = Marcador desconocido { $placeholder } en { $template }
+template-invalid-name = La plantilla { $template } produjo un nombre de fichero no válido { $name }
+
+## Órdenes
+
+secret-written = Escritos { $bytes } bytes en { $path }
+share-given-twice = { $file }: la parte { $index } se ha dado dos veces
+verify-failed = { $warnings } aviso(s) en { $shares } partes
+verify-ok = { $shares } partes correctas
+spare-unknown = La parte { $index } no es una reserva en { $transcript }
+spare-mismatch = { $file } no coincide con la reserva { $index } del acta
+spare-already-activated = La reserva { $index } ya se activó el { $date }
+info-share = parte { $index }, { $bytes } bytes, huella { $fingerprint }
+info-hint = pista: { $hint }
+info-hint-unchecked = pista: { $hint } (firma no comprobada)
+info-public-key = protege la mitad privada de la clave { $algorithm } { $fingerprint }
+info-no-instructions = sin instrucciones de recuperación
+procedure-mismatch = { $path } no es el procedimiento al que se refieren las instrucciones
+procedure-matches = el procedimiento { $path } coincide
+signature-good = firma correcta, clave del repartidor { $fingerprint }
+signature-unchecked = firma no comprobada (indique --dealer-key)
+no-watermark = { $file } no lleva marca de agua
+watermark-unmatched = La marca de agua de { $file } no coincide con ningún custodio del acta
+traced = parte { $index } entregada a { $label } ({ $contact })
+no-contact = sin contacto
+split-dir-done = Repartidos { $files } ficheros en { $bundles } paquetes bajo { $dir } (umbral { $threshold })
+join-dir-done = Restaurados { $files } ficheros (generación { $generation }) en { $dir }
+split-stream-dealt = Repartidos { $bytes } bytes en { $shares } flujos de partes (umbral { $threshold }); { $resumed } de { $chunks } bloques ya estaban en disco
+resumable-needs-file = --resumable y --resume necesitan un fichero de entrada, no la entrada estándar
+resumable-with-mmap = --resumable y --resume leen la entrada bloque a bloque; quita --mmap
+dealing-journal-exists = { $path } queda de un reparto interrumpido; continúalo con --resume o bórralo
+split-stream-done = Repartidos { $bytes } bytes en { $shares } flujos de partes (umbral { $threshold }); la lectura se ha detenido { $stalls } veces por escritores lentos
+rotate-done = Rotado a la generación { $generation } con { $bundles } paquetes bajo { $dir } (umbral { $threshold })
+generation-too-old = El contenido es de la generación { $generation }, anterior a la requerida { $min }
+bundle-different-payload = { $bundle } contiene un contenido distinto
+bundle-different-manifest = { $bundle } contiene un manifest.txt distinto
+journal-intact = Diario íntegro: { $count } entradas
+drill-unscheduled = sin simulacro programado
+drill-overdue = ATRASADO desde { $due }
+drill-due = previsto { $due }
+drills-share = parte
+drills-summary = { $overdue } de { $total } custodios atrasados a { $today }
+shred-warning = la sobrescritura es de mejor esfuerzo; la nivelación de desgaste de los SSD, los sistemas de ficheros copy-on-write (btrfs, ZFS, APFS), las instantáneas y las copias de seguridad pueden conservar copias antiguas de los datos
+shred-not-regular = { $file } no es un fichero normal (no se siguen los enlaces simbólicos); no se ha sobrescrito nada
+shredded = destruido { $file }
+selftest-header = { $samples } muestras por parte, semilla { $seed }, k={ $k }, n={ $n }
+selftest-share = parte { $index }: chi2 { $chi2 } (límite { $limit }), entropía { $entropy } bits  { $verdict }
+selftest-ok = ok
+selftest-fail = FALLO
+selftest-repeat-rate = tasa de repetición entre vecinos { $rate } (límite { $limit })
+selftest-failed = La autoprueba estadística ha fallado
+selftest-passed = La autoprueba estadística ha pasado
+doctor-hint = consejo: { $hint }
+doctor-failed = Comprobaciones que no han pasado: { $count }
+doctor-unchecked = no se puede comprobar en esta plataforma
+doctor-rng-ok = la fuente aleatoria del sistema responde; { $bytes } bytes de partes de un reparto de prueba parecen uniformes
+doctor-rng-unavailable = la fuente aleatoria del sistema no responde
+doctor-rng-unavailable-hint = no haga ninguna ceremonia en esta máquina hasta que getrandom o /dev/urandom funcionen
+doctor-rng-stuck = la fuente aleatoria del sistema devuelve bytes repetidos o nulos
+doctor-rng-biased = { $bytes } bytes de partes de un reparto de prueba no parecen uniformes
+doctor-rng-biased-hint = repita doctor; si vuelve a fallar, ejecute selftest-statistics y use otra máquina
+doctor-core-off = los volcados de memoria están desactivados
+doctor-core-on = los volcados de memoria están activados; un fallo escribiría bytes secretos en disco
+doctor-core-on-handler = los volcados de memoria están activados y los recoge { $handler }; un fallo escribiría bytes secretos en disco
+doctor-core-hint = pase --no-coredump a todas las órdenes o ejecute `ulimit -c 0` en esta shell
+doctor-swap-ok = no hay swap sin cifrar activa
+doctor-swap-encrypted-os = el sistema cifra la swap
+doctor-swap-exposed = hay swap sin cifrar activa en { $devices }
+doctor-swap-hint = ejecute `swapoff -a` durante la ceremonia o use swap cifrada o zram
+doctor-clipboard-ok = no hay ningún gestor del portapapeles en marcha
+doctor-clipboard-running = gestor del portapapeles en marcha: { $managers }; guarda las partes copiadas en su historial
+doctor-clipboard-hint = ciérrelo durante la ceremonia o no use --copy
+doctor-scrollback-no-terminal = la salida no es un terminal
+doctor-scrollback-ok = sin multiplexor de terminal; reveal borra el historial que escribe
+doctor-scrollback-multiplexer = se ejecuta dentro de { $multiplexer }, que guarda el historial aunque se cierre la ventana
+doctor-scrollback-hint = haga la ceremonia fuera de tmux o screen, o borre su historial después
+doctor-disk-ok = { $mib } MiB libres para { $path }
+doctor-disk-low = solo { $mib } MiB libres para { $path }
+doctor-disk-hint = libere espacio o pase --out en otro sistema de ficheros
+spec-conforms = { $package } cumple { $spec } ({ $vectors } vectores)
+
+revoked = revocado { $kind } { $value } (número de serie de la lista { $serial })
+share-revoked = la parte { $index } se revocó el { $revocation }
+generation-revoked = la generación { $generation } se revocó el { $revocation }
+revocations-unsigned = { $path } no está firmada
+revocations-unchecked = firma de la lista de revocación no comprobada (indique --revocation-key)
+revoke-needs-key = { $path } ya existe: indique --revocation-key para comprobar su firma antes de ampliarla
+revocations-pin-without-key = --revocations-pin necesita --revocation-key: solo se fija una lista verificada
+revocations-rollback = { $path } no amplía la lista fijada: { $error }
+unknown-device-command = Orden de dispositivo desconocida
+missing-device = Falta --device o --factor-file
+device-and-factor-file = --device y --factor-file no se pueden combinar
+factor-exists = Ya existe un factor de dispositivo en { $location }
+factor-created = Creado el factor de dispositivo { $fingerprint } en { $location }
+factor-imported = Importado el factor de dispositivo { $fingerprint } en { $location }
+split-coupled = Las partes están acopladas al factor de dispositivo { $fingerprint }; guarde una copia aparte de las partes
+keychain-unavailable = No se puede acceder al llavero del sistema: { $error }
+keychain-failed = El llavero del sistema no tiene ese factor de dispositivo o ha rechazado la petición
+keychain-malformed = La entrada { $name } del llavero no es un factor de dispositivo
+unknown-dealer-command = Orden del repartidor desconocida
+dealer-exists = { $home } ya existe; dealer init necesita un directorio nuevo
+dealer-empty-passphrase = { $path } contiene una frase de paso vacía; la clave del repartidor debe guardarse cifrada
+dealer-needs-passphrase = Firmar con el directorio del repartidor { $home } necesita --passphrase-file
+dealer-created = Creada la clave del repartidor { $fingerprint } en { $home }; expórtela para que los custodios la fijen
+dealer-exported = Exportada la clave del repartidor { $fingerprint } a { $path }
+dealer-rotated = Nueva clave del repartidor { $fingerprint }, certificada por { $previous }
+dealer-current = actual { $fingerprint }
+dealer-retired = retirada { $fingerprint } { $date }
+dealer-certified = La clave del repartidor { $fingerprint } está certificada por la clave fijada { $previous }
+dealer-not-certified = La clave del repartidor { $fingerprint } no está certificada por la clave fijada { $previous }
+unknown-receipt-command = Orden de resguardo desconocida
+receipt-written = Escrito el resguardo de la parte { $index } en { $path }
+receipt-unsigned = { $path } no está firmado
+unknown-ack-command = Orden de acuse desconocida
+no-ack-challenge = { $file } no lleva desafío de acuse
+acknowledged = parte { $index } recibida por { $label } el { $date }
+ack-pending = { $count } custodios aún no han acusado recibo: { $labels }
+receipt-matches = La parte { $index } ({ $fingerprint }) coincide con su resguardo: ceremonia { $ceremony }, { $threshold } de { $shares }, creada { $created }, clave del repartidor { $signer }
+unknown-token-command = Orden de token desconocida
+missing-token-file = Falta el fichero del token
+missing-token-shares = Faltan partes, --fingerprint o --transcript que cubra el token
+token-no-purpose = --purpose no puede estar vacío
+token-expires-malformed = --expires debe ser AAAA-MM-DD o @SEGUNDOS, no { $value }
+token-expires-past = --expires debe ser posterior a ahora
+token-output-unknown = Forma de salida desconocida { $form }; use file, stdout o clipboard
+token-issued = Emitido el token de recuperación { $id } para { $shares } partes en { $path }
+token-unsigned = { $path } no está firmado
+token-without-key = --token necesita --token-key para comprobar la firma del repartidor
+token-required = hace falta un token de recuperación (--token FICHERO)
+token-output-denied = el token de recuperación no permite la salida a { $form } (permitidas: { $allowed })
+token-accepted = Token de recuperación { $id } aceptado: { $purpose } (clave del repartidor { $signer })
+token-summary = token { $id }: { $purpose }
+token-window = válido desde { $issued } hasta { $expires }
+token-outputs = salidas { $outputs }
+token-shares = partes { $shares }
+token-signature-unchecked = firma no comprobada (indique --token-key)
+missing-systemd = seal y unseal necesitan --systemd
+credential-exists = Ya existe una credencial en { $path }
+credential-sealed = Parte { $index } sellada como credencial { $name } en { $path }
+credentials-directory-missing = --credential necesita $CREDENTIALS_DIRECTORY, que solo existe dentro de un servicio systemd
+credential-encrypt-failed = systemd-creds no pudo cifrar la credencial { $name }: { $error }
+credential-decrypt-failed = systemd-creds no pudo descifrar la credencial { $name }: { $error }
+fetch-failed = { $command } ha fallado: { $error }
+memfd-failed = No se puede crear un memfd sellado: { $error }
+memfd-unsupported = Desbloquear en un memfd requiere Linux
+unsealed = Secreto reconstruido a partir de { $shares } partes en el memfd sellado { $fd }
+exec-failed = No se puede iniciar { $command }: { $error }
+
+## Portapapeles, dictado y fotogramas QR
+
+clipboard-unavailable = No se puede usar el portapapeles: { $error }
+clipboard-not-text = Solo se puede copiar texto al portapapeles; use --out para secretos binarios
+clipboard-copied = Copiado al portapapeles; se borrará en { $secs } s (Ctrl-C lo deja como está)
+clipboard-changed = El portapapeles ha cambiado desde la copia; no se toca
+clipboard-cleared = Portapapeles borrado
+dictate-check = control { $code }
+dictation-share = Parte { $number }: escriba grupos de { $group_len } caracteres; una línea vacía termina la parte (primera línea vacía: fin)
+dictation-group = grupo { $number }:
+dictation-bad-character = '{ $character }' no es un carácter bech32; vuelva a escribir el grupo
+dictation-checkpoint = control { $code } (pida al custodio que lo confirme)
+dictation-final-check = control final { $code }
+dictation-accepted = parte { $index } aceptada
+dictation-retry = { $error }; dicte esta parte de nuevo
+reveal-not-terminal = reveal solo escribe en un terminal; usa dictate o bundle para imprimir la parte entera
+reveal-segment = Parte { $index }, segmento { $number } de { $segments } (grupos { $first }-{ $last })
+reveal-prompt = Intro: siguiente, b: atrás, q: salir
+reveal-done = Parte { $index }: pantalla borrada
+frames-written = { $frames } fotogramas QR ({ $fragments } fragmentos) escritos en { $path }
+frames-scale-too-large = --scale admite como máximo { $max } píxeles por módulo
+frames-none = No se ha leído ninguna parte válida
+frames-incomplete = Solo se han recuperado { $solved } de { $fragments } fragmentos; escanee más fotogramas
+
+## OpenPGP
+
+no-coredump-failed = No se pueden desactivar los volcados de memoria: { $error }
+gpg-unavailable = No se puede ejecutar gpg: { $error }
+gpg-list-failed = gpg no ha podido listar { $keyring }
+gpg-stdin-unavailable = la entrada estándar de gpg no está disponible
+gpg-encrypt-failed = gpg no ha podido cifrar para { $fingerprint }
+gpg-sign-failed = gpg no ha podido firmar con { $key }
+keyring-no-keys = { $keyring } no contiene claves de cifrado utilizables
+signature-invalid = La firma no se verifica con { $keyring }
+
+## Avisos y errores del núcleo
+
+warning = aviso: { $message }
+warning-from = aviso: { $source }: { $message }
+error-threshold-too-small = El umbral k debe ser mayor que 1
+error-threshold-above-shares = El número de partes n debe ser al menos k
+error-too-many-shares = El número de partes n debe ser como máximo 255
+error-no-parts = No se ha dado ninguna parte
+error-inconsistent-lengths = Las partes tienen longitudes distintas
+error-invalid-index = Índice de parte no válido { $index } (debe estar en 1..=255)
+error-duplicate-index = Índice de parte duplicado { $index }
+error-insufficient-parts = Solo se han dado { $collected } de las { $threshold } partes necesarias
+error-inconsistent-shares = Las partes { $indices } no son coherentes con las demás
+error-range-out-of-bounds = El rango de bytes { $start }..{ $end } queda fuera del secreto de { $len } bytes
+error-check-failed = Las partes no coinciden con el valor de control publicado
+error-buffer-size = El búfer tiene { $actual } entradas, se necesitan { $expected }
+error-unknown-scheme = Identificador de esquema de compartición desconocido { $id }
+error-secret-too-large = El secreto de { $len } bytes supera el límite de { $max } bytes
+error-invalid-chunk-size = El tamaño de bloque debe ser al menos 1 byte
+error-join-denied = Reconstrucción no autorizada: { $reason }
+error-kdf-output-too-long = Se han pedido { $requested } bytes de material de clave; HKDF da como máximo { $max }
+error-empty-secret = El secreto y las partes deben tener al menos 1 byte
+error-mislabeled-shares = Parece que algunas partes llevan un índice equivocado; índices probablemente correctos: { $fixes }
+error-not-invertible = Un elemento no nulo del cuerpo no tiene inverso; el cuerpo no supera la conformidad
+error-foreign-shares = Las partes { $indices } pertenecen a otro reparto
+error-invalid-shares = Las partes { $indices } no superan la comprobación de integridad
@@ -0,0 +1,193 @@
+mod ack;
+mod batch;
+mod clipboard;
+mod config;
+mod dates;
+mod dealer;
+mod device;
+mod dictation;
+mod doctor;
+mod dir;
+mod drills;
+mod error;
+mod frames;
+mod i18n;
+mod image;
+mod journal;
+mod keyring;
+mod naming;
+pub mod output;
+mod receipt;
+mod revocation;
+mod selftest;
+mod shares;
+mod shred;
+mod stream;
+#[cfg(feature = "systemd")]
+mod systemd;
+mod token;
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use shamir_rust::hardening;
+
+pub(crate) use shamir_rust::encoding::{decode_share, encode_share};
+pub use config::apply_profile;
+pub use error::CliError;
+use i18n::tr;
+
+// Flags that never take a value, so `--quiet share.txt` keeps the file
+// positional.
+const SWITCHES:&[&str]=&["ack", "compact", "copy", "dictation", "entropy-report", "mmap", "no-coredump", "nul", "plan", "porcelain", "quiet", "require-token", "resumable", "resume", "set-commitment", "shred-inputs", "sparse", "stdin-multi", "strict", "systemd", "watermark"];
+
+// Positional arguments plus `--name value` options and bare `--flag`s.
+pub struct Args{
+    pub positional:Vec<String>,
+    options:HashMap<String,String>,
+}
+
+impl Args{
+
+    pub fn parse(raw:&[String])->Args{
+        let mut positional=Vec::new();
+        let mut options=HashMap::new();
+        let mut iter=raw.iter().peekable();
+        while let Some(arg)=iter.next() {
+            if let Some(name)=arg.strip_prefix("--") {
+                let value=match iter.peek() {
+                    Some(next) if !next.starts_with("--") && !SWITCHES.contains(&name) => iter.next().unwrap().clone(),
+                    _ => String::new(),
+                };
+                options.insert(name.to_string(), value);
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Args { positional, options }
+    }
+
+    pub fn value(&self,name:&str)->Option<&str>{
+        self.options.get(name).map(|v| v.as_str())
+    }
+
+    pub fn flag(&self,name:&str)->bool{
+        self.options.contains_key(name)
+    }
+
+    pub fn required(&self,name:&str)->Result<&str,CliError>{
+        match self.value(name) {
+            Some(v) if !v.is_empty() => Ok(v),
+            _ => Err(CliError::with_usage(tr!("missing-option", name=name))),
+        }
+    }
+
+    // Comma-separated values; empty when the option is absent.
+    pub fn list(&self,name:&str)->Vec<&str>{
+        match self.value(name) {
+            Some(v) if !v.is_empty() => v.split(',').map(|item| item.trim()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn number(&self,name:&str)->Result<i32,CliError>{
+        self.required(name)?.parse::<i32>().map_err(|_| CliError::usage(tr!("option-not-number", name=name)))
+    }
+}
+
+pub fn run(command:&str,args:&Args)->Result<(),CliError>{
+    i18n::select(args)?;
+    dates::select(args)?;
+    if args.flag("quiet") && args.flag("porcelain") {
+        return Err(CliError::usage(tr!("quiet-porcelain-conflict")));
+    }
+    // --no-coredump: no core file for any crash from here on, and the
+    // library's RNG and error state is scrubbed before exiting.
+    let hardened=args.flag("no-coredump");
+    if hardened {
+        hardening::disable_core_dumps().map_err(|e| tr!("no-coredump-failed", error=e))?;
+    }
+    let result=match command {
+        "split" => shares::split(args),
+        "join" => shares::join(args),
+        "activate-spare" => shares::activate_spare(args),
+        "copy" => shares::copy(args),
+        "verify" => shares::verify(args),
+        "bundle" => shares::bundle(args),
+        "plate" => shares::plate(args),
+        "info" => shares::info(args),
+        "revoke" => revocation::revoke(args),
+        "receipt" => receipt::run(args),
+        "trace" => shares::trace_leak(args),
+        "ack" => ack::run(args),
+        "dictate" => dictation::dictate(args),
+        "reveal" => dictation::reveal(args),
+        "device" => device::run(args),
+        "dealer" => dealer::run(args),
+        "shred" => shred::run(args),
+        "split-dir" => dir::split_dir(args),
+        "join-dir" => dir::join_dir(args),
+        "split-stream" => stream::split_stream(args),
+        "join-stream" => stream::join_stream(args),
+        "rotate" => dir::rotate(args),
+        "selftest-statistics" => selftest::statistics(args),
+        "doctor" => doctor::run(args),
+        #[cfg(feature = "spec")]
+        "selftest-spec" => selftest::spec(args),
+        "drills" => drills::run(args),
+        "journal" => journal::run(args),
+        "frames" => frames::run(args),
+        "token" => token::run(args),
+        #[cfg(feature = "systemd")]
+        "seal" => systemd::seal(args),
+        #[cfg(feature = "systemd")]
+        "unseal" => systemd::unseal(args),
+        _ => Err(CliError::with_usage(tr!("unknown-command", command=command))),
+    };
+    if hardened {
+        shamir_rust::scrub();
+    }
+    result
+}
+
+pub(crate) fn write(path:&Path,data:&[u8])->Result<(),String>{
+    create_private(path)?.write_all(data).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// Most of what the CLI writes is a share or a secret, so every file is
+// created owner-only; see `hardening::create_private`.
+pub(crate) fn create_private(path:&Path)->Result<fs::File,String>{
+    hardening::create_private(path).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub(crate) fn read(path:&Path)->Result<Vec<u8>,String>{
+    fs::read(path).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+pub(crate) fn read_string(path:&Path)->Result<String,String>{
+    fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn switches_never_take_a_value() {
+        let args=args(&["--quiet", "share.txt", "--out", "secret.bin", "--threshold", "--shares", "5", "extra"]);
+        assert_eq!(args.positional, vec!["share.txt", "extra"]);
+        assert!(args.flag("quiet"));
+        assert_eq!(args.value("out"), Some("secret.bin"));
+        assert_eq!(args.value("threshold"), Some(""));
+        assert!(args.required("threshold").is_err());
+        assert_eq!(args.number("shares").unwrap(), 5);
+        assert!(args.number("out").is_err());
+        assert!(!args.flag("porcelain"));
+    }
+}
@@ -0,0 +1,66 @@
+use super::tr;
+
+pub const DEFAULT_TEMPLATE:&str="share-{index}-of-{total}.shs";
+
+// Values available to `--name-template` placeholders.
+pub struct NameContext<'a>{
+    pub index:i32,
+    pub total:i32,
+    pub threshold:i32,
+    pub date:&'a str,
+    pub fingerprint:&'a str,
+    pub label:&'a str,
+}
+
+pub fn render(template:&str,ctx:&NameContext)->Result<String,String>{
+    let mut out=String::new();
+    let mut rest=template;
+    while let Some(open)=rest.find('{') {
+        out.push_str(&rest[..open]);
+        let close=rest[open..].find('}').ok_or_else(|| tr!("template-unclosed", template=template))?+open;
+        let value=match &rest[open+1..close] {
+            "index" => ctx.index.to_string(),
+            "total" => ctx.total.to_string(),
+            "threshold" => ctx.threshold.to_string(),
+            "date" => ctx.date.to_string(),
+            "fingerprint" => ctx.fingerprint.to_string(),
+            "label" => ctx.label.to_string(),
+            other => return Err(tr!("template-unknown-placeholder", placeholder=format!("{{{other}}}"), template=template)),
+        };
+        out.push_str(&value);
+        rest=&rest[close+1..];
+    }
+    out.push_str(rest);
+
+    if out.is_empty() || out.contains('/') || out.contains('\\') || out=="." || out==".." {
+        return Err(tr!("template-invalid-name", template=template, name=format!("{out:?}")));
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context()->NameContext<'static>{
+        NameContext { index: 2, total: 5, threshold: 3, date: "2026-10-16", fingerprint: "1a2b3c4d", label: "alice" }
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        assert_eq!(render(DEFAULT_TEMPLATE, &context()).unwrap(), "share-2-of-5.shs");
+        assert_eq!(render("{label}-{threshold}of{total}-{date}-{fingerprint}.txt", &context()).unwrap(), "alice-3of5-2026-10-16-1a2b3c4d.txt");
+        assert_eq!(render("plain", &context()).unwrap(), "plain");
+    }
+
+    #[test]
+    fn bad_templates_and_names_are_refused() {
+        for template in ["share-{index", "share-{owner}", "", "{label}/{index}", "..", "a\\{index}"] {
+            assert!(render(template, &context()).is_err(), "{template}");
+        }
+        let empty=NameContext { label: "", ..context() };
+        assert!(render("{label}", &empty).is_err());
+        let dotted=NameContext { label: "..", ..context() };
+        assert!(render("{label}", &dotted).is_err());
+    }
+}
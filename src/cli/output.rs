@@ -0,0 +1,134 @@
+use std::fmt::Display;
+
+use super::{tr, Args, CliError};
+
+// Output modes shared by every command.
+//
+// --quiet prints only what a command exists to produce (written share paths,
+// the secret, reports asked for by name) plus errors; progress lines,
+// summaries and warnings are dropped.
+//
+// --porcelain replaces human-readable lines with tab-separated records for
+// scripts. The first field names the record; fields are only ever appended,
+// never reordered. Tabs and newlines inside fields become spaces.
+//   share     <index> <fingerprint> <path>     split, activate-spare, frames import --out; one per share written
+//   spare     <index> <fingerprint> <path>     split --spares
+//   receipt   <index> <path>                   split --receipts; one per custodian
+//   matches   <ceremony> <index> <fingerprint> <signer>   receipt verify
+//   secret    <path> <bytes>                   join --out, join-stream --out
+//   verified  <shares>                         verify
+//   traced    <index> <label> <contact>        trace
+//   ack       <index> <path>                   ack respond --out
+//   acknowledged <index> <label> <date>        ack record; one per response
+//   pending   <custodians>                     ack record, when some have not answered
+//   info      <index> <bytes> <fingerprint>    info, followed by
+//   instructions <field> <value>                 one per field, or `none`
+//   procedure <path> matches                   info --procedure
+//   hint      good <fingerprint> <text> | unchecked - <text>   info
+//   public-key <algorithm> <key fingerprint>   info, for a share of a keypair's private half
+//   signature good <fingerprint> | unchecked   info
+//   entropy   <bytes> pass                     split --entropy-report; a failure is a warning
+//   batch     <records> <dir>                  split --stdin-multi --out
+//   bundles   <count> <dir> <generation>       split-dir, rotate
+//   restored  <files> <dir> <generation>       join-dir
+//   stream    <index> <path>                   split-stream; one per share stream
+//   streamed  <bytes> <stalls> <peak buffered bytes>   split-stream
+//   dealt     <bytes> <resumed chunks> <chunks>   split-stream --resumable, --resume
+//   shredded  <file>                           shred, join --shred-inputs
+//   revoked   <kind> <value> <serial>          revoke; kind is fingerprint or generation
+//   token     <id> <path>                      token issue
+//   token     <id> <purpose>                   token show, followed by
+//   window    <issued> <expires>                 Unix seconds
+//   outputs   <form,...>                         allowed output forms
+//   shares    <fingerprint,...>                  covered shares
+//   signature good <fingerprint> | unchecked
+//   authorized <id> <purpose> <signer>         join --token with --out or --copy
+//   coupled   <factor fingerprint>             split --device, --factor-file
+//   factor    <fingerprint> <location>         device init, device import
+//   dealer    <fingerprint> <home | path | previous>   dealer init, export --out, rotate
+//   current   <fingerprint>                    dealer show, followed by
+//   retired   <fingerprint> <date>               one per retired key
+//   intact    <entries>                        journal verify
+//   finding   <pass|warn|fail> <check> <message> <hint>   doctor; one per check
+//   sealed    <index> <name> <path>            seal --systemd
+//   frames    <index> <fragments> <frames> <path>   frames export --out
+//   unsealed  <shares> <fd>                    unseal --systemd, before exec
+//   warning   <source> <message>               stderr
+//   error     <exit code> <message>            stderr, first line only
+// Output that is already data (a secret on stdout, bundle, dictate, plan
+// JSON, journal show, drills status, self-tests) is the same in every mode.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Mode{
+    Normal,
+    Quiet,
+    Porcelain,
+}
+
+pub fn mode(args:&Args)->Mode{
+    if args.flag("porcelain") {
+        Mode::Porcelain
+    } else if args.flag("quiet") {
+        Mode::Quiet
+    } else {
+        Mode::Normal
+    }
+}
+
+fn record(fields:&[&dyn Display])->String{
+    fields.iter().map(|field| field.to_string().replace(['\t', '\n', '\r'], " ")).collect::<Vec<_>>().join("\t")
+}
+
+// A summary line: printed normally, dropped by --quiet.
+pub fn info(args:&Args,human:&str,fields:&[&dyn Display]){
+    match mode(args) {
+        Mode::Normal => println!("{human}"),
+        Mode::Quiet => {}
+        Mode::Porcelain => println!("{}", record(fields)),
+    }
+}
+
+// The command's actual result: printed by --quiet too.
+pub fn result(args:&Args,human:&str,fields:&[&dyn Display]){
+    match mode(args) {
+        Mode::Normal | Mode::Quiet => println!("{human}"),
+        Mode::Porcelain => println!("{}", record(fields)),
+    }
+}
+
+pub fn warning(args:&Args,source:&str,message:&str){
+    match mode(args) {
+        Mode::Normal if source.is_empty() => eprintln!("{}", tr!("warning", message=message)),
+        Mode::Normal => eprintln!("{}", tr!("warning-from", source=source, message=message)),
+        Mode::Quiet => {}
+        Mode::Porcelain => eprintln!("{}", record(&[&"warning", &source, &message])),
+    }
+}
+
+pub fn error(args:&Args,err:&CliError){
+    match mode(args) {
+        Mode::Normal | Mode::Quiet => eprintln!("{err}"),
+        Mode::Porcelain => eprintln!("{}", record(&[&"error", &err.code, &err.message.lines().next().unwrap_or("")])),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn porcelain_wins_over_quiet() {
+        assert_eq!(mode(&args(&[])), Mode::Normal);
+        assert_eq!(mode(&args(&["--quiet", "share.txt"])), Mode::Quiet);
+        assert_eq!(mode(&args(&["--quiet", "--porcelain"])), Mode::Porcelain);
+    }
+
+    #[test]
+    fn records_are_one_tab_separated_line() {
+        assert_eq!(record(&[&"share", &3, &"a\tb", &"two\r\nlines"]), "share\t3\ta b\ttwo  lines");
+        assert_eq!(record(&[]), "");
+    }
+}
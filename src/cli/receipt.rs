@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use shamir_rust::encoding::decode_any_share;
+use shamir_rust::receipt::Receipt;
+use shamir_rust::revocation::read_signed;
+
+use super::{keyring, output, read_string, tr, Args, CliError};
+
+// Split receipts (see `receipt.rs`): `split --receipts DIR --sign-with KEY`
+// writes one per custodian, and `receipt verify` checks a share a custodian
+// presents against theirs. The signature is always checked: a receipt is
+// stored without protection, so an unsigned one proves nothing.
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("verify") => verify(args),
+        _ => Err(CliError::with_usage(tr!("unknown-receipt-command"))),
+    }
+}
+
+fn verify(args:&Args)->Result<(),CliError>{
+    let file=args.positional.get(1).ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let path=args.required("receipt")?;
+    let dealer_key=args.required("dealer-key")?;
+    let (text, signature)=read_signed(&read_string(Path::new(path))?).map_err(CliError::integrity)?;
+    let signature=signature.ok_or_else(|| CliError::integrity(tr!("receipt-unsigned", path=path)))?;
+    let signer=keyring::verify(dealer_key, text.as_bytes(), &signature).map_err(CliError::integrity)?;
+    let receipt=Receipt::parse(&text).map_err(|e| CliError::integrity(format!("{path}: {e}")))?;
+    let (index, data)=decode_any_share(&read_string(Path::new(file))?).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    receipt.check(index, &data).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let created=if receipt.created.is_empty() { "-" } else { receipt.created.as_str() };
+    output::result(args, &tr!("receipt-matches", index=index, fingerprint=receipt.fingerprint, ceremony=receipt.ceremony,
+        threshold=receipt.threshold, shares=receipt.shares, created=created, signer=signer),
+        &[&"matches", &receipt.ceremony, &index, &receipt.fingerprint, &signer]);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use shamir_rust::dealer::DealerIdentity;
+
+    use crate::cli::error::INTEGRITY;
+    use crate::cli::shares;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn receipt_matches_only_its_own_share() {
+        let root=std::env::temp_dir().join(format!("shamir-receipt-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("home")).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let identity=DealerIdentity::generate("Dealer <dealer@example.org>", "passphrase").unwrap();
+        identity.save(&root.join("home").join("identity")).unwrap();
+        fs::write(path("dealer.pub"), identity.public_key().to_text()).unwrap();
+        fs::write(path("passphrase"), "passphrase\n").unwrap();
+        fs::write(path("secret"), "a secret long enough not to be guessed").unwrap();
+        shares::split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("shares"), "--receipts", &path("receipts"),
+            "--sign-with", &path("home"), "--passphrase-file", &path("passphrase"), "--quiet"])).unwrap();
+        let (share, receipt, key)=(path("shares/share-1-of-3.shs"), path("receipts/receipt-1.txt"), path("dealer.pub"));
+
+        verify(&args(&["verify", &share, "--receipt", &receipt, "--dealer-key", &key, "--quiet"])).unwrap();
+        let other=verify(&args(&["verify", &path("shares/share-2-of-3.shs"), "--receipt", &receipt, "--dealer-key", &key]));
+        assert_eq!(other.unwrap_err().code, INTEGRITY);
+
+        // Without its signature, or signed by someone else, a receipt proves nothing.
+        let (text, _)=read_signed(&fs::read_to_string(&receipt).unwrap()).unwrap();
+        fs::write(path("unsigned.txt"), &text).unwrap();
+        let unsigned=verify(&args(&["verify", &share, "--receipt", &path("unsigned.txt"), "--dealer-key", &key]));
+        assert_eq!(unsigned.unwrap_err().code, INTEGRITY);
+        let stranger=DealerIdentity::generate("Stranger <stranger@example.org>", "passphrase").unwrap();
+        fs::write(path("stranger.pub"), stranger.public_key().to_text()).unwrap();
+        let foreign=verify(&args(&["verify", &share, "--receipt", &receipt, "--dealer-key", &path("stranger.pub")]));
+        assert_eq!(foreign.unwrap_err().code, INTEGRITY);
+        assert!(verify(&args(&["verify", &share, "--dealer-key", &key])).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+use std::path::Path;
+
+use shamir_rust::crypto::Parts;
+use shamir_rust::encoding::{decode_any_share, share_fingerprint};
+use shamir_rust::revocation::{read_signed, signed_text, Revocation, Revoked, RevocationList};
+
+use super::{dates, keyring, output, read_string, tr, write, Args, CliError};
+
+// Appends shares, fingerprints or a generation to the dealer's revocation
+// list and re-signs it. The list is created on first use; after that its
+// signature is checked against --revocation-key before it is extended, so a
+// list edited on disk is never re-signed, and the new list must extend it.
+pub fn revoke(args:&Args)->Result<(),CliError>{
+    let path=Path::new(args.required("list")?);
+    let key=args.required("sign-with")?;
+    let mut list=if path.exists() {
+        let keyring=args.value("revocation-key").filter(|v| !v.is_empty())
+            .ok_or_else(|| CliError::usage(tr!("revoke-needs-key", path=path.display().to_string())))?;
+        verified(path, keyring)?
+    } else {
+        RevocationList::default()
+    };
+    let previous=list.clone();
+
+    let mut targets=Vec::new();
+    for file in &args.positional {
+        let (index, data)=decode_any_share(&read_string(Path::new(file))?).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+        targets.push(Revoked::Fingerprint(share_fingerprint(index, &data)));
+    }
+    targets.extend(args.list("fingerprint").into_iter().map(|fingerprint| Revoked::Fingerprint(fingerprint.to_string())));
+    if let Some(generation)=args.value("generation").filter(|v| !v.is_empty()) {
+        let generation=generation.parse::<u32>().map_err(|_| CliError::usage(tr!("option-not-number", name="generation")))?;
+        targets.push(Revoked::Generation(generation));
+    }
+    if targets.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-revocation-target")));
+    }
+
+    let date=dates::required_today("revoke")?;
+    for target in targets {
+        list.revoke(target.clone(), &date, args.value("reason").unwrap_or("")).map_err(CliError::usage)?;
+        let (kind, value)=match &target {
+            Revoked::Fingerprint(fingerprint) => ("fingerprint", fingerprint.clone()),
+            Revoked::Generation(generation) => ("generation", generation.to_string()),
+        };
+        output::result(args, &tr!("revoked", kind=kind, value=value, serial=list.serial), &[&"revoked", &kind, &value, &list.serial]);
+    }
+    list.extends(&previous).map_err(CliError::integrity)?;
+    let text=list.to_text();
+    let signature=keyring::sign(key, args.value("passphrase-file").filter(|v| !v.is_empty()), text.as_bytes())?;
+    Ok(write(path, signed_text(&text, &signature).as_bytes())?)
+}
+
+// The signed list at `path`, whose signature must verify against `keyring`.
+fn verified(path:&Path,keyring:&str)->Result<RevocationList,CliError>{
+    let (text, signature)=read_signed(&read_string(path)?).map_err(CliError::integrity)?;
+    let signature=signature.ok_or_else(|| CliError::integrity(tr!("revocations-unsigned", path=path.display().to_string())))?;
+    keyring::verify(keyring, text.as_bytes(), &signature).map_err(CliError::integrity)?;
+    RevocationList::parse(&text).map_err(CliError::integrity)
+}
+
+// The list named by --revocations, if any. With --revocation-key its
+// signature must verify against that keyring; without, it is used unchecked,
+// which can only make the tools refuse more. With --revocations-pin the
+// list must also extend the one pinned there by an earlier run, which then
+// becomes the pin, so an older list with entries dropped is refused.
+pub fn load(args:&Args)->Result<Option<RevocationList>,CliError>{
+    let Some(path)=args.value("revocations").filter(|v| !v.is_empty()) else { return Ok(None) };
+    let path=Path::new(path);
+    let pin=args.value("revocations-pin").filter(|v| !v.is_empty()).map(Path::new);
+    let Some(keyring)=args.value("revocation-key").filter(|v| !v.is_empty()) else {
+        if pin.is_some() {
+            return Err(CliError::usage(tr!("revocations-pin-without-key")));
+        }
+        output::warning(args, &path.display().to_string(), &tr!("revocations-unchecked"));
+        return Ok(Some(RevocationList::parse(&read_signed(&read_string(path)?).map_err(CliError::integrity)?.0).map_err(CliError::integrity)?));
+    };
+    let list=verified(path, keyring)?;
+    if let Some(pin)=pin {
+        if pin.exists() {
+            let pinned=verified(pin, keyring)?;
+            list.extends(&pinned).map_err(|e| CliError::integrity(tr!("revocations-rollback", path=path.display().to_string(), error=e)))?;
+        }
+        write(pin, read_string(path)?.as_bytes())?;
+    }
+    Ok(Some(list))
+}
+
+// Applies --on-revoked to the revoked shares in `parts` and, for sealed
+// bundles, the payload generation: `deny` (the default) fails with an
+// integrity error, `warn` reports them and carries on.
+pub fn enforce(args:&Args,list:Option<&RevocationList>,parts:&Parts,generation:Option<u32>)->Result<(),CliError>{
+    let warn=match args.value("on-revoked").filter(|v| !v.is_empty()) {
+        None | Some("deny") => false,
+        Some("warn") => true,
+        Some(other) => return Err(CliError::usage(tr!("on-revoked-invalid", value=other))),
+    };
+    let Some(list)=list else { return Ok(()) };
+    let describe=|entry:&Revocation| if entry.reason.is_empty() { entry.date.clone() } else { format!("{} ({})", entry.date, entry.reason) };
+    let mut revoked:Vec<String>=parts.iter()
+        .filter_map(|(index, data)| list.fingerprint_revocation(&share_fingerprint(*index, data)).map(|entry| tr!("share-revoked", index=index, revocation=describe(entry))))
+        .collect();
+    if let Some(entry)=generation.and_then(|generation| list.generation_revocation(generation)) {
+        revoked.push(tr!("generation-revoked", generation=generation.unwrap_or(0), revocation=describe(entry)));
+    }
+    if revoked.is_empty() {
+        return Ok(());
+    }
+    if !warn {
+        return Err(CliError::integrity(revoked.join("\n")));
+    }
+    for message in &revoked {
+        output::warning(args, "", message);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use shamir_rust::crypto::ShamirSS;
+    use shamir_rust::dealer::DealerIdentity;
+
+    use crate::cli::encode_share;
+    use crate::cli::error::{BAD_ARGUMENTS, INTEGRITY};
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn signed_list_grows_and_is_enforced() {
+        let root=std::env::temp_dir().join(format!("shamir-revocation-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("home")).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let identity=DealerIdentity::generate("Dealer <dealer@example.org>", "passphrase").unwrap();
+        identity.save(&root.join("home").join("identity")).unwrap();
+        fs::write(path("dealer.pub"), identity.public_key().to_text()).unwrap();
+        fs::write(path("passphrase"), "passphrase\n").unwrap();
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        fs::write(path("share-1"), encode_share(1, &parts[&1])).unwrap();
+        let (list, home, passphrase, key, pin)=(path("revoked.txt"), path("home"), path("passphrase"), path("dealer.pub"), path("pin.txt"));
+
+        revoke(&args(&["--list", &list, "--sign-with", &home, "--passphrase-file", &passphrase, "--reason", "lost", "--quiet", &path("share-1")])).unwrap();
+        let first=fs::read_to_string(&list).unwrap();
+        assert_eq!(revoke(&args(&["--list", &list, "--sign-with", &home, "--generation", "2"])).unwrap_err().code, BAD_ARGUMENTS);
+        revoke(&args(&["--list", &list, "--sign-with", &home, "--passphrase-file", &passphrase, "--revocation-key", &key, "--generation", "2", "--quiet"])).unwrap();
+
+        let checked=args(&["--revocations", &list, "--revocation-key", &key, "--revocations-pin", &pin]);
+        let loaded=load(&checked).unwrap().unwrap();
+        assert_eq!(loaded.entries.len(), 2);
+        assert!(Path::new(&pin).exists());
+        assert!(enforce(&checked, Some(&loaded), &parts, None).is_err());
+        assert!(enforce(&args(&["--on-revoked", "warn", "--quiet"]), Some(&loaded), &parts, None).is_ok());
+        let unrevoked:Parts=parts.iter().skip(1).map(|(i, d)| (*i, d.clone())).collect();
+        assert!(enforce(&checked, Some(&loaded), &unrevoked, Some(1)).is_ok());
+        assert!(enforce(&checked, Some(&loaded), &unrevoked, Some(2)).is_err());
+        assert!(enforce(&args(&["--on-revoked", "ignore"]), None, &parts, None).is_err());
+
+        // An older list, validly signed but with entries dropped, is refused
+        // once a newer one is pinned; so is a list edited on disk.
+        fs::write(&list, &first).unwrap();
+        assert_eq!(load(&checked).unwrap_err().code, INTEGRITY);
+        fs::write(&list, first.replace("lost", "found")).unwrap();
+        assert_eq!(load(&args(&["--revocations", &list, "--revocation-key", &key])).unwrap_err().code, INTEGRITY);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+use shamir_rust::stats::{self, CHI_SQUARED_CRITICAL};
+
+use super::{tr, Args, CliError};
+
+const FIXED_SECRET:&[u8]=b"\x00\x00\x00\x00\xffselftest\xff\x00\x00\x00\x00";
+
+pub fn statistics(args:&Args)->Result<(),CliError>{
+    let trials=optional(args, "trials", 2000)?;
+    let seed=optional(args, "seed", 0x5eed)?;
+    let k=optional(args, "threshold", 3)? as i32;
+    let n=optional(args, "shares", 5)? as i32;
+
+    let report=stats::share_statistics(n, k, FIXED_SECRET, trials as usize, seed)?;
+
+    println!("{}", tr!("selftest-header", samples=report.samples_per_share, seed=seed, k=k, n=n));
+    for share in &report.shares {
+        let verdict=if share.chi_squared<CHI_SQUARED_CRITICAL { tr!("selftest-ok") } else { tr!("selftest-fail") };
+        println!("{}", tr!("selftest-share", index=format!("{:>3}", share.index), chi2=format!("{:>8.2}", share.chi_squared),
+            limit=CHI_SQUARED_CRITICAL, entropy=format!("{:.4}", share.entropy_bits), verdict=verdict));
+    }
+    println!("{}", tr!("selftest-repeat-rate", rate=format!("{:.5}", report.repeat_rate), limit=format!("{:.5}", report.repeat_limit)));
+
+    if !report.passed() {
+        return Err(tr!("selftest-failed").into());
+    }
+    println!("{}", tr!("selftest-passed"));
+    Ok(())
+}
+
+fn optional(args:&Args,name:&str,default:u64)->Result<u64,String>{
+    match args.value(name) {
+        Some(v) if !v.is_empty() => v.parse::<u64>().map_err(|_| tr!("option-not-number", name=name)),
+        _ => Ok(default),
+    }
+}
+
+#[cfg(feature = "spec")]
+pub fn spec(_args:&Args)->Result<(),CliError>{
+    let vectors=shamir_rust::spec::check_conformance()?;
+    println!("{}", tr!("spec-conforms", package=env!("CARGO_PKG_NAME"), spec=shamir_rust::spec::SPEC_VERSION, vectors=vectors));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn seeded_run_passes_and_options_are_numbers() {
+        statistics(&args(&[])).unwrap();
+        assert_eq!(optional(&args(&["--trials", ""]), "trials", 7).unwrap(), 7);
+        assert_eq!(optional(&args(&["--trials", "12"]), "trials", 7).unwrap(), 12);
+        assert!(statistics(&args(&["--trials", "many"])).is_err());
+        assert!(statistics(&args(&["--threshold", "6", "--shares", "5"])).is_err());
+    }
+
+    #[cfg(feature = "spec")]
+    #[test]
+    fn build_conforms_to_the_spec() {
+        spec(&args(&[])).unwrap();
+    }
+}
@@ -0,0 +1,1099 @@
+use std::collections::{BTreeMap, HashSet};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use shamir_rust::ack::{ack_response, challenge_line, new_ack_challenge, response_digest};
+use shamir_rust::check::{CheckValue, GUESSABLE_SECRET_LEN};
+use shamir_rust::commitment::{check_membership, read_set, SetCommitment};
+use shamir_rust::crypto::{zeroize, Parts, ShamirSS};
+use shamir_rust::encoding::{compact_text_len, decode_any_share, decode_any_share_with, encode_base32, encode_bech32, encode_bundle, encode_compact_text,
+    encode_hex, share_fingerprint, ParseMode};
+use shamir_rust::error::ShamirError;
+use shamir_rust::journal::JournalEvent;
+use shamir_rust::hint::{check_hint, hint_lines, hint_statement, read_hint};
+use shamir_rust::keypair::read_public_key;
+use shamir_rust::instructions::{instructions_lines, read_instructions, RecoveryInstructions};
+use shamir_rust::json::{Json, JsonExt};
+use shamir_rust::stats;
+use shamir_rust::token::OutputForm;
+use shamir_rust::plate::{encode_plate, DEFAULT_PLATE_WIDTH};
+use shamir_rust::receipt::{new_ceremony_id, Receipt};
+use shamir_rust::revocation::signed_text;
+use shamir_rust::lint::lint_shares;
+use shamir_rust::transcript::{CustodianRecord, EntropyRecord, SpareRecord, Transcript};
+use shamir_rust::transform::{Pipeline, Transforms};
+use shamir_rust::watermark::{new_watermark_nonce, read_watermark, trace, watermark_line, watermark_tag};
+
+use super::{batch, clipboard, device, dictation, journal, keyring, revocation, shred, token};
+use super::naming::{self, NameContext, DEFAULT_TEMPLATE};
+use super::{dates, decode_share, encode_share, output, read, read_string, tr, write, Args, CliError};
+
+const PLAN_VERSION:i64=1;
+const SHARE_FORMAT:&str="text: <index>:<base64 share bytes>, one share per file";
+const ENCODED_FORMAT:&str="{encoding}: index byte and share bytes in that encoding, one share per file";
+// --encoding names; text is the default form. "mnemonic" takes a wordlist
+// suffix, e.g. "mnemonic-es" (see `mnemonic::LANGUAGES`); alone it is English.
+#[cfg(not(feature = "mnemonic"))]
+const ENCODINGS:&[&str]=&["text", "hex", "base32", "bech32"];
+#[cfg(feature = "mnemonic")]
+const ENCODINGS:&[&str]=&["text", "hex", "base32", "bech32", "mnemonic"];
+const COMPACT_FORMAT:&str="compact: base64 of a fixed 16-byte header, the share bytes and a 4-byte checksum; every file the same size";
+const KEYRING_FORMAT:&str="OpenPGP ASCII-armored message to the custodian's key, containing the text form";
+const BUNDLE_FORMAT:&str="share bundle (text, bech32, fingerprint) plus watermark, acknowledgment challenge, set commitment and/or signed recovery instruction lines";
+const SPARE_DIR:&str="spares";
+const KEYRING_TEMPLATE:&str="share-{index}-{label}.asc";
+const ENTROPY_POLICY:&str="rand::thread_rng (ChaCha12 seeded and periodically reseeded from the OS), one fresh polynomial per secret byte";
+
+// Everything `split` will do, fixed before any randomness is drawn. `--plan`
+// prints it as JSON for review; `--from-plan` executes an approved one.
+struct SplitPlan{
+    input:String,
+    threshold:i32,
+    shares:i32,
+    indices:Vec<u8>,
+    out:String,
+    template:String,
+    labels:Vec<String>,
+    contacts:Vec<String>,
+    hints:Vec<String>,
+    drill_every:Option<i64>,
+    transcript:Option<String>,
+    check:Option<String>,
+    journal:Option<String>,
+    keyring:Option<String>,
+    recipients:Vec<String>,
+    watermark:bool,
+    // A delivery challenge per share; see `ack.rs`.
+    ack:bool,
+    set_commitment:bool,
+    entropy_report:bool,
+    compact:bool,
+    // Pre-split transform names in application order; --sparse is trim-zeros.
+    transforms:Vec<String>,
+    // One of ENCODINGS for the share files; None is the text form.
+    encoding:Option<String>,
+    spares:usize,
+    instructions:Option<String>,
+    sign_with:Option<String>,
+    // Directory for the signed custodian receipts; see `receipt.rs`.
+    receipts:Option<String>,
+    device:Option<String>,
+    factor_file:Option<String>,
+}
+
+impl SplitPlan{
+
+    fn from_args(args:&Args)->Result<SplitPlan,CliError>{
+        let input=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-secret-file")))?.clone();
+        let threshold=args.number("threshold")?;
+        let indices:Vec<u8>=match args.value("indices") {
+            Some(list) if !list.is_empty() => list.split(',')
+                .map(|x| x.trim().parse::<u8>().map_err(|_| tr!("index-out-of-range", index=x)))
+                .collect::<Result<_,_>>()?,
+            _ => Vec::new(),
+        };
+        let option=|name:&str| args.value(name).filter(|v| !v.is_empty()).map(|v| v.to_string());
+        let keyring=option("keyring");
+        let keys=match &keyring {
+            Some(_) if !indices.is_empty() => return Err(CliError::usage(tr!("keyring-with-indices"))),
+            Some(path) => keyring::recipients(path)?,
+            None => Vec::new(),
+        };
+        let shares=match (keys.len(), indices.len()) {
+            (0, 0) => args.number("shares")?,
+            (0, count) => count as i32,
+            (count, _) => {
+                if args.value("shares").is_some_and(|v| v!=count.to_string()) {
+                    return Err(CliError::usage(tr!("keyring-count-mismatch", count=count, shares=args.value("shares").unwrap_or(""))));
+                }
+                count as i32
+            }
+        };
+        let mut labels:Vec<String>=args.list("labels").iter().map(|l| l.to_string()).collect();
+        if labels.is_empty() {
+            labels=keys.iter().map(|key| keyring::uid_label(&key.uid)).collect();
+        }
+        let hints=match args.value("hints").filter(|v| !v.is_empty()) {
+            Some(path) => read_string(Path::new(path))?.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect(),
+            None => Vec::new(),
+        };
+        let default_template=if keyring.is_some() { KEYRING_TEMPLATE } else { DEFAULT_TEMPLATE };
+        let drill_every=match args.value("drill-every") {
+            Some(days) if !days.is_empty() => Some(days.parse::<i64>().map_err(|_| tr!("drill-every-not-number"))?),
+            _ => None,
+        };
+        let mut transforms=Vec::new();
+        if args.flag("sparse") {
+            transforms.push("trim-zeros".to_string());
+        }
+        transforms.extend(args.list("transform").iter().map(|t| t.to_string()));
+        let spares=match args.value("spares") {
+            Some(count) if !count.is_empty() => count.parse::<usize>().map_err(|_| CliError::usage(tr!("option-not-number", name="spares")))?,
+            _ => 0,
+        };
+        let plan=SplitPlan {
+            input,
+            threshold,
+            shares,
+            indices,
+            out: option("out").unwrap_or(".".to_string()),
+            template: option("name-template").unwrap_or(default_template.to_string()),
+            labels,
+            contacts: args.list("contacts").iter().map(|c| c.to_string()).collect(),
+            hints,
+            drill_every,
+            transcript: option("transcript"),
+            check: option("check"),
+            journal: option("journal"),
+            keyring,
+            recipients: keys.into_iter().map(|key| key.fingerprint).collect(),
+            watermark: args.flag("watermark"),
+            ack: args.flag("ack"),
+            set_commitment: args.flag("set-commitment"),
+            entropy_report: args.flag("entropy-report"),
+            compact: args.flag("compact"),
+            transforms,
+            encoding: option("encoding"),
+            spares,
+            instructions: option("instructions"),
+            sign_with: option("sign-with"),
+            receipts: option("receipts"),
+            device: option("device"),
+            factor_file: option("factor-file"),
+        };
+        plan.validate()?;
+        Ok(plan)
+    }
+
+    fn validate(&self)->Result<(),CliError>{
+        for (name, list) in [("labels", &self.labels), ("contacts", &self.contacts), ("hints", &self.hints), ("keyring", &self.recipients)] {
+            if !list.is_empty() && list.len()!=self.shares as usize {
+                return Err(CliError::usage(tr!("custodian-count-mismatch", name=name, count=list.len(), expected=self.shares)));
+            }
+        }
+        if self.check.is_some() && !self.indices.is_empty() {
+            return Err(CliError::usage(tr!("check-with-indices")));
+        }
+        for hint in &self.hints {
+            check_hint(hint).map_err(|e| CliError::usage(tr!("hint-invalid", hint=hint, error=e)))?;
+        }
+        if (self.instructions.is_some() || !self.hints.is_empty())!=self.sign_with.is_some() && self.receipts.is_none() {
+            return Err(CliError::usage(tr!("instructions-without-signer")));
+        }
+        if self.receipts.is_some() && self.sign_with.is_none() {
+            return Err(CliError::usage(tr!("receipts-without-signer")));
+        }
+        if self.spares>0 && self.transcript.is_none() {
+            return Err(CliError::usage(tr!("spares-without-transcript")));
+        }
+        if self.watermark && self.transcript.is_none() {
+            return Err(CliError::usage(tr!("watermark-without-transcript")));
+        }
+        if self.ack && self.transcript.is_none() {
+            return Err(CliError::usage(tr!("ack-without-transcript")));
+        }
+        if self.entropy_report && self.transcript.is_none() {
+            return Err(CliError::usage(tr!("entropy-without-transcript")));
+        }
+        if !self.transforms.is_empty() && !self.compact {
+            return Err(CliError::usage(tr!("transform-without-compact")));
+        }
+        self.pipeline()?;
+        if self.compact && (self.bundled()) {
+            return Err(CliError::usage(tr!("compact-with-metadata")));
+        }
+        if let Some(name)=&self.encoding {
+            if !ENCODINGS.contains(&name.as_str()) && mnemonic_language(name).is_none() {
+                return Err(CliError::usage(tr!("encoding-unknown", name=name, known=ENCODINGS.join(", "))));
+            }
+            if self.compact || self.bundled() {
+                return Err(CliError::usage(tr!("encoding-with-format-options")));
+            }
+        }
+        if self.device.is_some() && self.factor_file.is_some() {
+            return Err(CliError::usage(tr!("device-and-factor-file")));
+        }
+        if self.keyring.is_some()==self.recipients.is_empty() {
+            return Err(CliError::usage(tr!("keyring-without-recipients")));
+        }
+        Ok(())
+    }
+
+    // Whether share files carry metadata lines, and so are written as bundles.
+    fn bundled(&self)->bool{
+        self.watermark || self.ack || self.set_commitment || self.instructions.is_some() || !self.hints.is_empty()
+    }
+
+    fn pipeline(&self)->Result<Pipeline,CliError>{
+        let names:Vec<&str>=self.transforms.iter().map(|t| t.as_str()).collect();
+        Transforms::builtin().pipeline(&names).map_err(CliError::usage)
+    }
+
+    // A share in --encoding, or the text form.
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{
+        Ok(match self.encoding.as_deref() {
+            Some("hex") => encode_hex(index, data)+"\n",
+            Some("base32") => encode_base32(index, data)+"\n",
+            Some("bech32") => encode_bech32(index, data)+"\n",
+            #[cfg(feature = "mnemonic")]
+            Some(name) if name.starts_with("mnemonic") => {
+                let language=mnemonic_language(name).unwrap_or(bip39::Language::English);
+                shamir_rust::mnemonic::encode_mnemonic(index, data, language)?+"\n"
+            }
+            _ => encode_share(index, data),
+        })
+    }
+
+    fn order(&self)->Vec<i32>{
+        if self.indices.is_empty() { (1..=self.shares).collect() } else { self.indices.iter().map(|&x| x as i32).collect() }
+    }
+
+    fn label(&self,position:usize,index:i32)->String{
+        self.labels.get(position).cloned().unwrap_or(index.to_string())
+    }
+
+    // {fingerprint} and {date} are only known once the shares exist, so the
+    // listed destinations keep them as placeholders.
+    fn to_json(&self,secret_len:Option<u64>)->Result<Json,String>{
+        let mut custodians=Vec::new();
+        for (position, index) in self.order().into_iter().enumerate() {
+            let label=self.label(position, index);
+            let ctx=NameContext { index, total: self.shares, threshold: self.threshold, date: "{date}", fingerprint: "{fingerprint}", label: &label };
+            let file=Path::new(&self.out).join(naming::render(&self.template, &ctx)?);
+            custodians.push(Json::object([
+                ("index", (index as i64).into()),
+                ("label", self.labels.get(position).map(|l| l.as_str()).into()),
+                ("contact", self.contacts.get(position).map(|c| c.as_str()).into()),
+                ("hint", self.hints.get(position).map(|h| h.as_str()).into()),
+                ("recipient", self.recipients.get(position).map(|r| r.as_str()).into()),
+                ("file", file.display().to_string().into()),
+            ]));
+        }
+        let secret_len=secret_len.map(|len| len as i64);
+        Ok(Json::object([
+            ("version", PLAN_VERSION.into()),
+            ("input", self.input.as_str().into()),
+            ("threshold", (self.threshold as i64).into()),
+            ("shares", (self.shares as i64).into()),
+            ("indices", if self.indices.is_empty() { Json::Null } else { Json::Array(self.indices.iter().map(|&x| (x as i64).into()).collect()) }),
+            ("secret_bytes", secret_len.into()),
+            ("share_bytes", if self.transforms.is_empty() { secret_len } else { None }.into()),
+            ("file_bytes", if self.compact && self.transforms.is_empty() { secret_len.map(|len| compact_text_len(len as usize) as i64) } else { None }.into()),
+            ("format", match (self.keyring.is_some(), self.bundled(), self.compact) {
+                (true, _, _) => KEYRING_FORMAT.to_string(),
+                (false, true, _) => BUNDLE_FORMAT.to_string(),
+                (false, false, true) => COMPACT_FORMAT.to_string(),
+                (false, false, false) => match &self.encoding {
+                    Some(name) if name!="text" => ENCODED_FORMAT.replace("{encoding}", name),
+                    _ => SHARE_FORMAT.to_string(),
+                },
+            }.into()),
+            ("keyring", self.keyring.clone().into()),
+            ("out", self.out.as_str().into()),
+            ("name_template", self.template.as_str().into()),
+            ("custodians", Json::Array(custodians)),
+            ("drill_every_days", self.drill_every.into()),
+            ("spares", (self.spares as i64).into()),
+            ("spare_dir", (self.spares>0).then(|| Path::new(&self.out).join(SPARE_DIR).display().to_string()).into()),
+            ("metadata", Json::object([
+                ("share_files", match (self.watermark, self.ack) {
+                    (true, true) => "index, share bytes, watermark tag and acknowledgment challenge",
+                    (true, false) => "index, share bytes and watermark tag",
+                    (false, true) => "index, share bytes and acknowledgment challenge",
+                    (false, false) => "index and share bytes only",
+                }.into()),
+                ("watermark", self.watermark.into()),
+                ("ack", self.ack.into()),
+                ("set_commitment", self.set_commitment.into()),
+                ("entropy_report", self.entropy_report.into()),
+                ("compact", self.compact.into()),
+                ("transforms", Json::Array(self.transforms.iter().map(|t| t.as_str().into()).collect())),
+                ("encoding", self.encoding.clone().into()),
+                ("instructions", self.instructions.clone().into()),
+                ("sign_with", self.sign_with.clone().into()),
+                ("receipts", self.receipts.clone().into()),
+                ("device", self.device.clone().into()),
+                ("factor_file", self.factor_file.clone().into()),
+                ("transcript", self.transcript.clone().into()),
+                ("check", self.check.clone().into()),
+                ("journal", self.journal.clone().into()),
+            ])),
+            ("entropy", ENTROPY_POLICY.into()),
+        ]))
+    }
+
+    fn from_json(json:&Json)->Result<(SplitPlan,Option<i64>),CliError>{
+        if json.get("version").and_then(Json::as_i64)!=Some(PLAN_VERSION) {
+            return Err(tr!("plan-wrong-version", version=PLAN_VERSION).into());
+        }
+        let text=|key:&str| json.get(key).and_then(Json::as_str).map(|v| v.to_string()).ok_or_else(|| tr!("plan-missing", key=key));
+        let number=|key:&str| json.get(key).and_then(Json::as_i64).ok_or_else(|| tr!("plan-missing", key=key));
+        let optional=|value:Option<&Json>| value.and_then(Json::as_str).map(|v| v.to_string());
+        let metadata=json.get("metadata").ok_or_else(|| tr!("plan-missing", key="metadata"))?;
+
+        let indices=match json.get("indices") {
+            Some(Json::Array(items)) => items.iter()
+                .map(|x| x.as_i64().and_then(|x| u8::try_from(x).ok()).ok_or_else(|| tr!("plan-index-out-of-range")))
+                .collect::<Result<_,_>>()?,
+            _ => Vec::new(),
+        };
+        // Plans from before transform pipelines carry "sparse" instead.
+        let transforms=match metadata.get("transforms").and_then(Json::as_array) {
+            Some(items) => items.iter().map(|t| t.as_str().map(|t| t.to_string()).ok_or_else(|| tr!("plan-missing", key="transforms"))).collect::<Result<_,_>>()?,
+            None if metadata.get("sparse").and_then(Json::as_bool)==Some(true) => vec!["trim-zeros".to_string()],
+            None => Vec::new(),
+        };
+        let custodians=json.get("custodians").and_then(Json::as_array).ok_or_else(|| tr!("plan-missing", key="custodians"))?;
+        let column=|key:&str| -> Vec<String> {
+            let values:Vec<String>=custodians.iter().filter_map(|c| optional(c.get(key))).collect();
+            if values.len()==custodians.len() { values } else { Vec::new() }
+        };
+
+        let plan=SplitPlan {
+            input: text("input")?,
+            threshold: number("threshold")? as i32,
+            shares: number("shares")? as i32,
+            indices,
+            out: text("out")?,
+            template: text("name_template")?,
+            labels: column("label"),
+            contacts: column("contact"),
+            hints: column("hint"),
+            drill_every: json.get("drill_every_days").and_then(Json::as_i64),
+            transcript: optional(metadata.get("transcript")),
+            check: optional(metadata.get("check")),
+            journal: optional(metadata.get("journal")),
+            keyring: optional(json.get("keyring")),
+            recipients: column("recipient"),
+            watermark: metadata.get("watermark").and_then(Json::as_bool).unwrap_or(false),
+            ack: metadata.get("ack").and_then(Json::as_bool).unwrap_or(false),
+            set_commitment: metadata.get("set_commitment").and_then(Json::as_bool).unwrap_or(false),
+            entropy_report: metadata.get("entropy_report").and_then(Json::as_bool).unwrap_or(false),
+            compact: metadata.get("compact").and_then(Json::as_bool).unwrap_or(false),
+            transforms,
+            encoding: optional(metadata.get("encoding")),
+            spares: json.get("spares").and_then(Json::as_i64).unwrap_or(0).max(0) as usize,
+            instructions: optional(metadata.get("instructions")),
+            sign_with: optional(metadata.get("sign_with")),
+            receipts: optional(metadata.get("receipts")),
+            device: optional(metadata.get("device")),
+            factor_file: optional(metadata.get("factor_file")),
+        };
+        plan.validate()?;
+        if custodians.len()!=plan.shares as usize {
+            return Err(tr!("plan-custodian-count", count=custodians.len(), expected=plan.shares).into());
+        }
+        Ok((plan, json.get("secret_bytes").and_then(Json::as_i64)))
+    }
+}
+
+pub fn split(args:&Args)->Result<(),CliError>{
+    if args.flag("stdin-multi") {
+        return batch::split_batch(args);
+    }
+    if let Some(path)=args.value("from-plan").filter(|v| !v.is_empty()) {
+        let (plan, secret_bytes)=SplitPlan::from_json(&Json::parse(&read_string(Path::new(path))?)?)?;
+        let secret=read_secret(&plan.input)?;
+        if let Some(expected)=secret_bytes {
+            if secret.len() as i64!=expected {
+                return Err(tr!("plan-secret-size", input=plan.input, bytes=secret.len(), expected=expected).into());
+            }
+        }
+        return execute(args, &plan, secret);
+    }
+
+    let plan=SplitPlan::from_args(args)?;
+    if args.flag("plan") {
+        let secret_len=match plan.input.as_str() {
+            "-" => None,
+            input => Some(std::fs::metadata(input).map_err(|e| format!("{input}: {e}"))?.len()),
+        };
+        println!("{}", plan.to_json(secret_len)?.to_pretty());
+        return Ok(());
+    }
+    let secret=read_secret(&plan.input)?;
+    execute(args, &plan, secret)
+}
+
+// `split` after the plan is fixed. Each step is its own function below, so a
+// feature that changes how shares are made or written extends one step
+// instead of this sequence.
+fn execute(args:&Args,plan:&SplitPlan,secret:Vec<u8>)->Result<(),CliError>{
+    let (k, n)=(plan.threshold, plan.shares);
+    warn_guessable(args, plan, secret.len());
+    let transform=plan.pipeline()?;
+    let secret=prepare_secret(args, plan, &transform, secret)?;
+    let next_drill_due=match plan.drill_every {
+        Some(days) => Some(dates::days_from_now(days).ok_or_else(|| CliError::usage(tr!("logical-time-omitted", what="--drill-every")))?),
+        None => None,
+    };
+    let (parts, check)=deal(plan, secret)?;
+    let spares=ShamirSS::spare_shares(&parts, plan.spares)?;
+    let shared=SharedLines::new(args, plan, &parts, &spares)?;
+    let date=match dates::today() {
+        Some(date) => date,
+        None if plan.template.contains("{date}") => return Err(CliError::usage(tr!("logical-time-omitted", what="{date}"))),
+        None => String::new(),
+    };
+
+    let mut transcript=Transcript { threshold: k, shares: n, created: date.clone(), ceremony: new_ceremony_id(), ..Transcript::default() };
+    if plan.entropy_report {
+        transcript.entropy=Some(entropy_report(args, &parts));
+    }
+    let order=plan.order();
+    let mut names=HashSet::new();
+    let mut files=Vec::new();
+    let mut receipts=Vec::new();
+    for (index, data) in &parts {
+        let position=order.iter().position(|x| x==index).unwrap_or(0);
+        let issued=issue_share(args, plan, &shared, position, *index, data, transform)?;
+        let custodian=CustodianRecord { next_drill_due: next_drill_due.clone(), ..issued.custodian };
+        if let Some(receipt)=receipt(args, plan, &transcript, &custodian, data)? {
+            receipts.push(receipt);
+        }
+        let ctx=NameContext { index: *index, total: n, threshold: k, date: &date, fingerprint: &custodian.fingerprint, label: &custodian.label };
+        let name=naming::render(&plan.template, &ctx)?;
+        if !names.insert(name.clone()) {
+            return Err(CliError::usage(tr!("template-duplicate-name", template=plan.template, name=name)));
+        }
+        files.push((Path::new(&plan.out).join(name), issued.file));
+        transcript.custodians.push(custodian);
+    }
+
+    write_shares(args, plan, &files, &transcript)?;
+    write_receipts(args, plan, &receipts)?;
+    write_spares(args, plan, &spares, transform, &mut transcript)?;
+    if let (Some(path), Some(check))=(&plan.check, check) {
+        write(Path::new(path), check.to_text().as_bytes())?;
+    }
+    if let Some(path)=&plan.transcript {
+        write(Path::new(path), transcript.to_text().as_bytes())?;
+    }
+    let fingerprints:Vec<String>=transcript.custodians.iter().map(|c| c.fingerprint.clone()).collect();
+    Ok(journal::record_to(plan.journal.as_deref(), JournalEvent::Split, &fingerprints, &format!("k={k} n={n}"))?)
+}
+
+// "mnemonic" or "mnemonic-<code>" to its wordlist.
+#[cfg(feature = "mnemonic")]
+fn mnemonic_language(name:&str)->Option<bip39::Language>{
+    match name.strip_prefix("mnemonic")? {
+        "" => Some(bip39::Language::English),
+        suffix => shamir_rust::mnemonic::language(suffix.strip_prefix('-')?),
+    }
+}
+
+#[cfg(not(feature = "mnemonic"))]
+fn mnemonic_language(_name:&str)->Option<()>{
+    None
+}
+
+// A check value lets k-1 custodians test guesses of the secret offline (see
+// `check.rs`), which only matters when the secret is short enough to guess.
+// Every option that publishes something derived from all the shares: with
+// any of them, k-1 custodians can confirm a guess of a short secret.
+fn warn_guessable(args:&Args,plan:&SplitPlan,len:usize){
+    let oracles:Vec<&str>=[(plan.check.is_some(), "--check"), (plan.set_commitment, "--set-commitment"), (plan.receipts.is_some(), "--receipts")].into_iter().filter_map(|(on, flag)| on.then_some(flag)).collect();
+    if len<GUESSABLE_SECRET_LEN && !oracles.is_empty() {
+        output::warning(args, "", &tr!("secret-guessable", bytes=len, what=oracles.join(", "), custodians=plan.threshold-1));
+    }
+}
+
+// The bytes that are actually split: the secret through the --transform
+// pipeline, then masked with the device factor when the split is coupled.
+fn prepare_secret(args:&Args,plan:&SplitPlan,transform:&Pipeline,mut secret:Vec<u8>)->Result<Vec<u8>,CliError>{
+    if !transform.is_empty() {
+        let transformed=Transforms::builtin().apply(transform, &secret)?;
+        zeroize(&mut secret);
+        secret=transformed;
+    }
+    if let Some(factor)=device::load(plan.device.as_deref(), plan.factor_file.as_deref())? {
+        factor.apply_at(0, &mut secret);
+        output::info(args, &tr!("split-coupled", fingerprint=factor.fingerprint()), &[&"coupled", &factor.fingerprint()]);
+    }
+    Ok(secret)
+}
+
+fn deal(plan:&SplitPlan,secret:Vec<u8>)->Result<(Parts,Option<CheckValue>),ShamirError>{
+    let (k, n)=(plan.threshold, plan.shares);
+    Ok(match (&plan.check, plan.indices.is_empty()) {
+        (Some(_), _) => {
+            let (parts, check)=ShamirSS::split_with_check(n, k, secret)?;
+            (parts, Some(check))
+        }
+        (None, true) => (ShamirSS::split(n, k, secret)?, None),
+        (None, false) => (ShamirSS::split_at(&plan.indices, k, secret)?, None),
+    })
+}
+
+fn entropy_report(args:&Args,parts:&Parts)->EntropyRecord{
+    let record=EntropyRecord::new(&stats::assess_shares(parts), ENTROPY_POLICY);
+    if record.passed {
+        output::info(args, &tr!("entropy-passed", bytes=record.bytes, chi=record.chi_squared, serial=record.serial_correlation),
+            &[&"entropy", &record.bytes, &"pass"]);
+    } else {
+        output::warning(args, "", &tr!("entropy-failed", bytes=record.bytes, chi=record.chi_squared, serial=record.serial_correlation,
+            limit=record.serial_limit, os=if record.os_random { "ok" } else { "failed" }));
+    }
+    record
+}
+
+// Metadata lines every share file of this split carries.
+struct SharedLines{
+    set_commitment:Option<String>,
+    instructions:Option<String>,
+}
+
+impl SharedLines{
+
+    fn new(args:&Args,plan:&SplitPlan,parts:&Parts,spares:&Parts)->Result<SharedLines,CliError>{
+        let set_commitment=plan.set_commitment.then(|| SetCommitment::new(parts.iter().chain(spares)).line());
+        let instructions=match (&plan.instructions, &plan.sign_with) {
+            (Some(path), Some(key)) => {
+                let text=read_string(Path::new(path))?;
+                RecoveryInstructions::parse(&text).map_err(|e| CliError::usage(format!("{path}: {e}")))?;
+                Some(instructions_lines(&text, &sign(args, key, text.as_bytes())?))
+            }
+            _ => None,
+        };
+        Ok(SharedLines { set_commitment, instructions })
+    }
+}
+
+// One share's file contents and its transcript line (without the drill
+// date, which is the same for everyone).
+struct IssuedShare{
+    file:Vec<u8>,
+    custodian:CustodianRecord,
+}
+
+fn issue_share(args:&Args,plan:&SplitPlan,shared:&SharedLines,position:usize,index:i32,data:&[u8],transform:Pipeline)->Result<IssuedShare,CliError>{
+    let fingerprint=share_fingerprint(index, data);
+    let mut metadata=String::new();
+    let watermark=plan.watermark.then(new_watermark_nonce);
+    if let Some(nonce)=&watermark {
+        metadata.push_str(&watermark_line(&watermark_tag(nonce, index, data)?));
+    }
+    let (ack_challenge, ack_digest)=ack_lines(plan, index, data, &mut metadata)?;
+    for line in [&shared.set_commitment, &shared.instructions].into_iter().flatten() {
+        metadata.push_str(line);
+    }
+    if let (Some(hint), Some(key))=(plan.hints.get(position), &plan.sign_with) {
+        metadata.push_str(&hint_lines(hint, &sign(args, key, hint_statement(index, &fingerprint, hint).as_bytes())?));
+    }
+    let share_text=match (metadata.is_empty(), plan.compact) {
+        (false, _) => encode_bundle(index, data)+&metadata,
+        (true, true) => encode_compact_text(index, data, transform),
+        (true, false) => plan.encode(index, data)?,
+    };
+    let file=match &plan.keyring {
+        Some(path) => keyring::encrypt(path, &plan.recipients[position], share_text.as_bytes())?,
+        None => share_text.into_bytes(),
+    };
+    Ok(IssuedShare {
+        file,
+        custodian: CustodianRecord {
+            index,
+            label: plan.label(position, index),
+            fingerprint,
+            contact: plan.contacts.get(position).cloned(),
+            watermark,
+            ack_challenge,
+            ack_digest,
+            ..CustodianRecord::default()
+        },
+    })
+}
+
+// With --ack: a fresh challenge line in the share's metadata, and the
+// challenge and expected-response digest for the transcript.
+fn ack_lines(plan:&SplitPlan,index:i32,data:&[u8],metadata:&mut String)->Result<(Option<String>,Option<String>),String>{
+    if !plan.ack {
+        return Ok((None, None));
+    }
+    let challenge=new_ack_challenge();
+    metadata.push_str(&challenge_line(&challenge));
+    let digest=response_digest(&ack_response(&challenge, index, data)?);
+    Ok((Some(challenge), Some(digest)))
+}
+
+// With --receipts: the custodian's signed receipt, to be written once every
+// share file is ready.
+fn receipt(args:&Args,plan:&SplitPlan,transcript:&Transcript,custodian:&CustodianRecord,data:&[u8])->Result<Option<(i32,PathBuf,String)>,CliError>{
+    let (Some(dir), Some(key))=(&plan.receipts, &plan.sign_with) else { return Ok(None) };
+    let text=Receipt::new(transcript, custodian, data).to_text();
+    let signature=sign(args, key, text.as_bytes())?;
+    Ok(Some((custodian.index, Path::new(dir).join(format!("receipt-{}.txt", custodian.index)), signed_text(&text, &signature))))
+}
+
+fn sign(args:&Args,key:&str,data:&[u8])->Result<Vec<u8>,String>{
+    keyring::sign(key, args.value("passphrase-file").filter(|v| !v.is_empty()), data)
+}
+
+fn write_shares(args:&Args,plan:&SplitPlan,files:&[(PathBuf,Vec<u8>)],transcript:&Transcript)->Result<(),CliError>{
+    std::fs::create_dir_all(&plan.out).map_err(|e| format!("{}: {e}", plan.out))?;
+    for ((path, text), custodian) in files.iter().zip(&transcript.custodians) {
+        write(path, text)?;
+        output::result(args, &path.display().to_string(), &[&"share", &custodian.index, &custodian.fingerprint, &path.display()]);
+    }
+    Ok(())
+}
+
+fn write_receipts(args:&Args,plan:&SplitPlan,receipts:&[(i32,PathBuf,String)])->Result<(),CliError>{
+    let Some(dir)=&plan.receipts else { return Ok(()) };
+    std::fs::create_dir_all(dir).map_err(|e| format!("{dir}: {e}"))?;
+    for (index, path, text) in receipts {
+        write(path, text.as_bytes())?;
+        output::info(args, &tr!("receipt-written", index=index, path=path.display()), &[&"receipt", index, &path.display()]);
+    }
+    Ok(())
+}
+
+// Sealed spares go to SPARE_DIR under --out and are listed in the transcript.
+fn write_spares(args:&Args,plan:&SplitPlan,spares:&Parts,transform:Pipeline,transcript:&mut Transcript)->Result<(),CliError>{
+    if spares.is_empty() {
+        return Ok(());
+    }
+    let dir=Path::new(&plan.out).join(SPARE_DIR);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    for (index, data) in spares {
+        let path=dir.join(format!("spare-{index}.shs"));
+        let fingerprint=share_fingerprint(*index, data);
+        let text=if plan.compact { encode_compact_text(*index, data, transform) } else { plan.encode(*index, data)? };
+        write(&path, text.as_bytes())?;
+        output::result(args, &path.display().to_string(), &[&"spare", index, &fingerprint, &path.display()]);
+        transcript.spares.push(SpareRecord { index: *index, fingerprint, activated: None });
+    }
+    Ok(())
+}
+
+pub fn join(args:&Args)->Result<(),CliError>{
+    if args.positional.is_empty() && !args.flag("dictation") {
+        return Err(CliError::with_usage(tr!("missing-shares")));
+    }
+    let mode=if args.flag("strict") { ParseMode::Strict } else { ParseMode::Lenient };
+    let mut parts:BTreeMap<i32,Vec<u8>>=BTreeMap::new();
+    let mut sets=BTreeMap::new();
+    let mut transform=None;
+    for file in &args.positional {
+        let text=read_string(Path::new(file))?;
+        let decoded=decode_any_share_with(&text, mode).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+        for warning in &decoded.warnings {
+            output::warning(args, file, warning);
+        }
+        if transform.is_some_and(|t| t!=decoded.transform) {
+            return Err(CliError::integrity(tr!("transform-mismatch", file=file)));
+        }
+        transform=Some(decoded.transform);
+        if let Some(set)=read_set(&text) {
+            sets.insert(decoded.index, set.map_err(|e| CliError::integrity(format!("{file}: {e}")))?);
+        }
+        // Two copies of one index would silently replace each other and
+        // join into a wrong secret.
+        if parts.insert(decoded.index, decoded.data).is_some() {
+            return Err(CliError::integrity(tr!("share-given-twice", file=file, index=decoded.index)));
+        }
+    }
+    let transform=transform.unwrap_or_default();
+    if args.flag("dictation") {
+        for (index, data) in dictation::read_dictated_shares()? {
+            if parts.insert(index, data).is_some() {
+                return Err(CliError::integrity(tr!("share-given-twice", file="--dictation", index=index)));
+            }
+        }
+    }
+    check_set(&parts, &sets)?;
+    revocation::enforce(args, revocation::load(args)?.as_ref(), &parts, None)?;
+    let fingerprints:Vec<String>=parts.iter().map(|(index, data)| share_fingerprint(*index, data)).collect();
+    let form=match args.value("out") {
+        _ if args.flag("copy") => OutputForm::Clipboard,
+        Some(out) if !out.is_empty() => OutputForm::File,
+        _ => OutputForm::Stdout,
+    };
+    let authorization=token::enforce(args, &fingerprints, form)?;
+    let factor=device::load(args.value("device"), args.value("factor-file"))?;
+    let mut offset=0;
+    let mut secret=match args.value("range") {
+        Some(range) if !range.is_empty() => {
+            if !transform.is_empty() {
+                return Err(CliError::usage(tr!("range-with-transform")));
+            }
+            let (start, end)=range.split_once("..").ok_or_else(|| tr!("range-malformed"))?;
+            let start=start.parse::<usize>().map_err(|_| tr!("range-start-not-number"))?;
+            let end=end.parse::<usize>().map_err(|_| tr!("range-end-not-number"))?;
+            offset=start;
+            ShamirSS::join_range(&parts, start..end)?
+        }
+        _ => match args.value("check").filter(|v| !v.is_empty()) {
+            Some(path) => ShamirSS::join_with_check(parts, &CheckValue::parse_with(&read_string(Path::new(path))?, mode)?)?,
+            None => ShamirSS::join(parts)?,
+        },
+    };
+    if let Some(factor)=&factor {
+        factor.apply_at(offset, &mut secret);
+    }
+    let secret=Transforms::builtin().restore(&transform, secret).map_err(CliError::integrity)?;
+
+    if args.flag("copy") {
+        clipboard::copy_with_clear(&secret, clear_after(args)?)?;
+    } else {
+        match args.value("out") {
+            Some(out) if !out.is_empty() => {
+                write(Path::new(out), &secret)?;
+                output::info(args, &tr!("secret-written", bytes=secret.len(), path=out), &[&"secret", &out, &secret.len()]);
+            }
+            _ => io::stdout().write_all(&secret).map_err(|e| e.to_string())?,
+        }
+    }
+    let note=match &authorization {
+        Some(token) => format!("join token {}: {}", token.id, token.purpose),
+        None => "join".to_string(),
+    };
+    journal::record(args, JournalEvent::Recovery, &fingerprints, &note)?;
+    if args.flag("shred-inputs") {
+        shred::shred_all(args, &args.positional)?;
+    }
+    Ok(())
+}
+
+// Puts a share file's text on the clipboard, cleared after a timeout.
+pub fn copy(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let text=read_string(Path::new(file))?;
+    decode_share(&text)?;
+    Ok(clipboard::copy_with_clear(text.trim().as_bytes(), clear_after(args)?)?)
+}
+
+// Lints a set of shares before they are handed out; any warning fails.
+pub fn verify(args:&Args)->Result<(),CliError>{
+    if args.positional.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-shares")));
+    }
+    let mut parts:BTreeMap<i32,Vec<u8>>=BTreeMap::new();
+    let mut sets=BTreeMap::new();
+    for file in &args.positional {
+        let text=read_string(Path::new(file))?;
+        let (index, data)=decode_any_share(&text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+        if parts.insert(index, data).is_some() {
+            return Err(CliError::integrity(tr!("share-given-twice", file=file, index=index)));
+        }
+        if let Some(set)=read_set(&text) {
+            sets.insert(index, set.map_err(|e| CliError::integrity(format!("{file}: {e}")))?);
+        }
+    }
+    check_set(&parts, &sets)?;
+    let expected=match args.value("shares") {
+        Some(n) if !n.is_empty() => Some(1..=n.parse::<i32>().map_err(|_| CliError::usage(tr!("option-not-number", name="shares")))?),
+        _ => None,
+    };
+    revocation::enforce(args, revocation::load(args)?.as_ref(), &parts, None)?;
+    let warnings=lint_shares(&parts, expected);
+    for warning in &warnings {
+        output::warning(args, "", &warning.to_string());
+    }
+    if !warnings.is_empty() {
+        return Err(CliError::integrity(tr!("verify-failed", warnings=warnings.len(), shares=parts.len())));
+    }
+    output::info(args, &tr!("verify-ok", shares=parts.len()), &[&"verified", &parts.len()]);
+    Ok(())
+}
+
+// Prints every physical encoding of a share with cross-checks, for custodians
+// keeping copies on different media.
+pub fn bundle(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let (index, data)=decode_any_share(&read_string(Path::new(file))?)?;
+    print!("{}", encode_bundle(index, &data));
+    Ok(())
+}
+
+// Prints the share as a stamping grid for a steel plate; see `plate.rs`.
+pub fn plate(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let (index, data)=decode_any_share(&read_string(Path::new(file))?)?;
+    let width=match args.value("width") {
+        Some(width) if !width.is_empty() => width.parse::<usize>().map_err(|_| CliError::usage(tr!("option-not-number", name="width")))?,
+        _ => DEFAULT_PLATE_WIDTH,
+    };
+    print!("{}", encode_plate(index, &data, width).map_err(CliError::usage)?);
+    Ok(())
+}
+
+// Issues a sealed spare share to a new custodian: the spare must be listed
+// in the transcript and not yet activated. The transcript gains the new
+// custodian and the activation date. The lost share it replaces still counts
+// towards the threshold; run a refresh if it may have been compromised.
+pub fn activate_spare(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-spare-file")))?;
+    let (index, data)=decode_share(&read_string(Path::new(file))?).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let transcript_path=Path::new(args.required("transcript")?);
+    let label=args.required("label")?;
+    let mut transcript=Transcript::parse(&read_string(transcript_path)?)?;
+
+    let fingerprint=share_fingerprint(index, &data);
+    let date=dates::required_today("activate-spare")?;
+    let spare=transcript.spares.iter_mut().find(|s| s.index==index)
+        .ok_or_else(|| CliError::integrity(tr!("spare-unknown", index=index, transcript=transcript_path.display())))?;
+    if spare.fingerprint!=fingerprint {
+        return Err(CliError::integrity(tr!("spare-mismatch", file=file, index=index)));
+    }
+    if let Some(activated)=&spare.activated {
+        return Err(tr!("spare-already-activated", index=index, date=activated).into());
+    }
+    spare.activated=Some(date.clone());
+    transcript.custodians.push(CustodianRecord {
+        index,
+        label: label.to_string(),
+        fingerprint: fingerprint.clone(),
+        contact: args.value("contact").filter(|v| !v.is_empty()).map(|v| v.to_string()),
+        ..Default::default()
+    });
+
+    let ctx=NameContext { index, total: transcript.shares, threshold: transcript.threshold, date: &date, fingerprint: &fingerprint, label };
+    let out=PathBuf::from(args.value("out").filter(|v| !v.is_empty()).unwrap_or("."));
+    let path=out.join(naming::render(args.value("name-template").filter(|v| !v.is_empty()).unwrap_or(DEFAULT_TEMPLATE), &ctx)?);
+    std::fs::create_dir_all(&out).map_err(|e| format!("{}: {e}", out.display()))?;
+    write(&path, encode_share(index, &data).as_bytes())?;
+    write(transcript_path, transcript.to_text().as_bytes())?;
+    journal::record(args, JournalEvent::Split, std::slice::from_ref(&fingerprint), &format!("activated spare {index} for {label}"))?;
+    output::result(args, &path.display().to_string(), &[&"share", &index, &fingerprint, &path.display()]);
+    Ok(())
+}
+
+// Shows what a share is and the hint and recovery instructions it carries,
+// checking their signatures when the dealer's public key is given.
+pub fn info(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let text=read_string(Path::new(file))?;
+    let (index, data)=decode_any_share(&text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let fingerprint=share_fingerprint(index, &data);
+    output::result(args, &tr!("info-share", index=index, bytes=data.len(), fingerprint=fingerprint), &[&"info", &index, &data.len(), &fingerprint]);
+    let dealer_key=args.value("dealer-key").filter(|v| !v.is_empty());
+
+    // With --dealer-key, a hint whose signature fails is an error.
+    if let Some((hint, signature))=read_hint(&text).map_err(CliError::integrity)? {
+        let statement=hint_statement(index, &fingerprint, &hint);
+        match dealer_key {
+            Some(keyring) => {
+                let signer=keyring::verify(keyring, statement.as_bytes(), &signature).map_err(CliError::integrity)?;
+                output::result(args, &tr!("info-hint", hint=hint), &[&"hint", &"good", &signer, &hint]);
+            }
+            None => output::result(args, &tr!("info-hint-unchecked", hint=hint), &[&"hint", &"unchecked", &"-", &hint]),
+        }
+    }
+    // The public half of a keypair whose private half this share protects.
+    if let Some(public)=read_public_key(&text).map_err(CliError::integrity)? {
+        output::result(args, &tr!("info-public-key", algorithm=public.algorithm.name(), fingerprint=public.fingerprint()),
+            &[&"public-key", &public.algorithm.name(), &public.fingerprint()]);
+    }
+
+    let Some((instructions, signature))=read_instructions(&text)? else {
+        output::result(args, &tr!("info-no-instructions"), &[&"instructions", &"none"]);
+        return Ok(());
+    };
+    let parsed=RecoveryInstructions::parse(&instructions).map_err(CliError::integrity)?;
+    let signer=match dealer_key {
+        Some(keyring) => Some(keyring::verify(keyring, instructions.as_bytes(), &signature).map_err(CliError::integrity)?),
+        None => None,
+    };
+    for line in instructions.lines().skip(1).filter(|line| !line.trim().is_empty()) {
+        let (key, value)=line.split_once(' ').unwrap_or((line, ""));
+        output::result(args, &format!("  {line}"), &[&"instructions", &key, &value]);
+    }
+    if let Some(path)=args.value("procedure").filter(|v| !v.is_empty()) {
+        if !parsed.matches_procedure(&read(Path::new(path))?) {
+            return Err(CliError::integrity(tr!("procedure-mismatch", path=path)));
+        }
+        output::result(args, &tr!("procedure-matches", path=path), &[&"procedure", &path, &"matches"]);
+    }
+    match signer {
+        Some(fingerprint) => output::result(args, &tr!("signature-good", fingerprint=fingerprint), &[&"signature", &"good", &fingerprint]),
+        None => output::result(args, &tr!("signature-unchecked"), &[&"signature", &"unchecked"]),
+    }
+    Ok(())
+}
+
+// Names the custodian a leaked, watermarked share was issued to.
+pub fn trace_leak(args:&Args)->Result<(),CliError>{
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let text=read_string(Path::new(file))?;
+    let (index, data)=decode_any_share(&text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let transcript=Transcript::parse(&read_string(Path::new(args.required("transcript")?))?)?;
+    let tag=read_watermark(&text).ok_or_else(|| tr!("no-watermark", file=file))?;
+    let custodian=trace(&transcript, index, &data, tag).ok_or_else(|| CliError::integrity(tr!("watermark-unmatched", file=file)))?;
+    output::result(args, &tr!("traced", index=custodian.index, label=custodian.label, contact=custodian.contact.clone().unwrap_or(tr!("no-contact"))),
+        &[&"traced", &custodian.index, &custodian.label, &custodian.contact.as_deref().unwrap_or("")]);
+    Ok(())
+}
+
+// Shares from `split --set-commitment` name the split they belong to; a share
+// mixed in from another split with the same parameters fails here instead of
+// joining into garbage.
+fn check_set(parts:&BTreeMap<i32,Vec<u8>>,sets:&BTreeMap<i32,SetCommitment>)->Result<(),ShamirError>{
+    let shares:Vec<(i32,&[u8],Option<&SetCommitment>)>=parts.iter().map(|(index, data)| (*index, data.as_slice(), sets.get(index))).collect();
+    check_membership(&shares)
+}
+
+fn clear_after(args:&Args)->Result<u64,String>{
+    match args.value("clear-after") {
+        Some(secs) if !secs.is_empty() => secs.parse::<u64>().map_err(|_| tr!("clear-after-not-number")),
+        _ => Ok(clipboard::DEFAULT_CLEAR_SECS),
+    }
+}
+
+fn read_secret(input:&str)->Result<Vec<u8>,String>{
+    if input=="-" {
+        let mut secret=Vec::new();
+        io::stdin().read_to_end(&mut secret).map_err(|e| e.to_string())?;
+        return Ok(secret);
+    }
+    read(Path::new(input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use crate::cli::error::{BAD_ARGUMENTS, INTEGRITY};
+
+    const SECRET:&str="a secret long enough not to be guessed";
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    // A fresh directory holding the secret file.
+    fn scratch(name:&str)->PathBuf{
+        let root=std::env::temp_dir().join(format!("shamir-shares-{name}-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("secret"), SECRET).unwrap();
+        root
+    }
+
+    #[test]
+    fn named_shares_join_back() {
+        let root=scratch("join");
+        let path=|name:&str| root.join(name).display().to_string();
+        split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("out"), "--name-template", "{label}-{index}.shs",
+            "--labels", "alice,bob,carol", "--quiet"])).unwrap();
+        let mut names:Vec<String>=fs::read_dir(path("out")).unwrap().map(|e| e.unwrap().file_name().to_string_lossy().into_owned()).collect();
+        names.sort();
+        assert_eq!(names, ["alice-1.shs", "bob-2.shs", "carol-3.shs"]);
+
+        join(&args(&[&path("out/carol-3.shs"), &path("out/alice-1.shs"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read_to_string(path("joined")).unwrap(), SECRET);
+
+        let twice=join(&args(&[&path("out/bob-2.shs"), &path("out/bob-2.shs"), "--out", &path("twice")]));
+        assert_eq!(twice.unwrap_err().code, INTEGRITY);
+        let same=split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("same"), "--name-template", "share.shs"]));
+        assert_eq!(same.unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(split(&args(&["--threshold", "2", "--shares", "3"])).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(join(&args(&[])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn plan_round_trips_and_splits_later() {
+        let root=scratch("plan");
+        let path=|name:&str| root.join(name).display().to_string();
+        let words=args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("out"), "--labels", "alice,bob,carol", "--transcript", &path("transcript.txt")]);
+        let json=SplitPlan::from_args(&words).unwrap().to_json(Some(SECRET.len() as u64)).unwrap();
+        let (plan, secret_bytes)=SplitPlan::from_json(&Json::parse(&json.to_pretty()).unwrap()).unwrap();
+        assert_eq!(secret_bytes, Some(SECRET.len() as i64));
+        assert_eq!(plan.to_json(secret_bytes.map(|len| len as u64)).unwrap().to_pretty(), json.to_pretty());
+
+        fs::write(path("plan.json"), json.to_pretty()).unwrap();
+        split(&args(&["--from-plan", &path("plan.json"), "--quiet"])).unwrap();
+        assert!(Path::new(&path("transcript.txt")).exists());
+        join(&args(&[&path("out/share-1-of-3.shs"), &path("out/share-3-of-3.shs"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read_to_string(path("joined")).unwrap(), SECRET);
+
+        // The secret changed size since the plan was made.
+        fs::write(path("secret"), "shorter").unwrap();
+        assert!(split(&args(&["--from-plan", &path("plan.json"), "--quiet"])).is_err());
+        fs::write(path("plan.json"), json.to_pretty().replace("\"version\": 1", "\"version\": 9")).unwrap();
+        assert!(split(&args(&["--from-plan", &path("plan.json"), "--quiet"])).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn verify_fails_on_any_lint_warning() {
+        let root=scratch("verify");
+        let path=|name:&str| root.join(name).display().to_string();
+        split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("out"), "--quiet"])).unwrap();
+        let shares:Vec<String>=(1..=3).map(|index| path(&format!("out/share-{index}-of-3.shs"))).collect();
+
+        verify(&args(&[&shares[0], &shares[1], &shares[2], "--shares", "3", "--quiet"])).unwrap();
+        let outside=verify(&args(&[&shares[0], &shares[2], "--shares", "2", "--quiet"]));
+        assert_eq!(outside.unwrap_err().code, INTEGRITY);
+        fs::write(path("copy"), encode_share(4, &decode_share(&fs::read_to_string(&shares[0]).unwrap()).unwrap().1)).unwrap();
+        assert_eq!(verify(&args(&[&shares[0], &path("copy"), "--quiet"])).unwrap_err().code, INTEGRITY);
+        assert_eq!(verify(&args(&[&shares[0], "--shares", "three"])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn leaked_share_is_traced_to_its_custodian() {
+        let root=scratch("trace");
+        let path=|name:&str| root.join(name).display().to_string();
+        let transcript=path("transcript.txt");
+        split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--out", &path("out"), "--watermark", "--transcript", &transcript,
+            "--labels", "alice,bob,carol", "--quiet"])).unwrap();
+        trace_leak(&args(&[&path("out/share-2-of-3.shs"), "--transcript", &transcript, "--quiet"])).unwrap();
+
+        // A tag moved onto another custodian's share matches nobody.
+        let bob=fs::read_to_string(path("out/share-2-of-3.shs")).unwrap();
+        let carol=fs::read_to_string(path("out/share-3-of-3.shs")).unwrap();
+        let tag=bob.lines().find(|line| read_watermark(line).is_some()).unwrap();
+        let forged:String=carol.lines().map(|line| if read_watermark(line).is_some() { tag } else { line }).map(|line| format!("{line}\n")).collect();
+        assert!(decode_any_share(&forged).is_ok() && forged!=carol);
+        fs::write(path("forged"), forged).unwrap();
+        assert_eq!(trace_leak(&args(&[&path("forged"), "--transcript", &transcript])).unwrap_err().code, INTEGRITY);
+        fs::write(path("plain"), encode_share(1, b"share")).unwrap();
+        assert!(trace_leak(&args(&[&path("plain"), "--transcript", &transcript])).is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn spare_is_activated_once() {
+        let root=scratch("spare");
+        let path=|name:&str| root.join(name).display().to_string();
+        let transcript=path("transcript.txt");
+        // Spares take the highest free indices.
+        split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--spares", "2", "--out", &path("out"), "--transcript", &transcript, "--quiet"])).unwrap();
+        let activate=|spare:&str| activate_spare(&args(&[spare, "--transcript", &transcript, "--label", "dave", "--out", &path("issued"), "--quiet"]));
+
+        activate(&path("out/spares/spare-254.shs")).unwrap();
+        let updated=Transcript::parse(&fs::read_to_string(&transcript).unwrap()).unwrap();
+        assert_eq!(updated.custodians.last().unwrap().label, "dave");
+        assert!(updated.spares.iter().find(|s| s.index==254).unwrap().activated.is_some());
+        assert!(updated.spares.iter().find(|s| s.index==253).unwrap().activated.is_none());
+        join(&args(&[&path("issued/share-254-of-3.shs"), &path("out/share-2-of-3.shs"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read_to_string(path("joined")).unwrap(), SECRET);
+
+        assert!(activate(&path("out/spares/spare-254.shs")).is_err());
+        assert_eq!(activate(&path("out/share-1-of-3.shs")).unwrap_err().code, INTEGRITY);
+        let no_transcript=split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--spares", "1", "--out", &path("other")]));
+        assert_eq!(no_transcript.unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn share_from_another_split_is_refused() {
+        let root=scratch("set");
+        let path=|name:&str| root.join(name).display().to_string();
+        for out in ["first", "second"] {
+            split(&args(&[&path("secret"), "--threshold", "2", "--shares", "3", "--set-commitment", "--out", &path(out), "--quiet"])).unwrap();
+        }
+        join(&args(&[&path("first/share-1-of-3.shs"), &path("first/share-2-of-3.shs"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read_to_string(path("joined")).unwrap(), SECRET);
+
+        let mixed=join(&args(&[&path("first/share-1-of-3.shs"), &path("second/share-2-of-3.shs"), "--out", &path("mixed")]));
+        assert_eq!(mixed.unwrap_err().code, INTEGRITY);
+        assert!(!Path::new(&path("mixed")).exists());
+        let linted=verify(&args(&[&path("second/share-3-of-3.shs"), &path("first/share-2-of-3.shs")]));
+        assert_eq!(linted.unwrap_err().code, INTEGRITY);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,102 @@
+use std::fs::{self, OpenOptions};
+use std::io::{Seek, SeekFrom, Write};
+use std::path::Path;
+
+use rand::RngCore;
+
+use super::{output, tr, Args, CliError};
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    if args.positional.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-files")));
+    }
+    Ok(shred_all(args, &args.positional)?)
+}
+
+pub fn shred_all(args:&Args,files:&[String])->Result<(),String>{
+    output::warning(args, "", &tr!("shred-warning"));
+    for file in files {
+        shred(Path::new(file))?;
+        output::info(args, &tr!("shredded", file=file), &[&"shredded", file]);
+    }
+    Ok(())
+}
+
+// Size of each overwrite write, so shredding a large file needs no buffer of
+// its size.
+const CHUNK:usize=64*1024;
+
+// One pass of random data then one of zeros, each flushed to disk, before the
+// file is truncated and removed. Only regular files are shredded: a symbolic
+// link is refused rather than followed, so a link planted among the inputs
+// cannot point the overwrite at some other file.
+fn shred(path:&Path)->Result<(),String>{
+    let err=|e:std::io::Error| format!("{}: {e}", path.display());
+    let metadata=fs::symlink_metadata(path).map_err(err)?;
+    if !metadata.file_type().is_file() {
+        return Err(tr!("shred-not-regular", file=path.display()));
+    }
+    let mut file=OpenOptions::new().write(true).open(path).map_err(err)?;
+    // The path could have been swapped for a link between the check and the
+    // open; make sure this is still the file that was checked.
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        let opened=file.metadata().map_err(err)?;
+        if (opened.dev(), opened.ino())!=(metadata.dev(), metadata.ino()) {
+            return Err(tr!("shred-not-regular", file=path.display()));
+        }
+    }
+
+    let len=metadata.len();
+    let mut chunk=vec![0u8;CHUNK];
+    for random in [true, false] {
+        file.seek(SeekFrom::Start(0)).map_err(err)?;
+        let mut left=len;
+        while left>0 {
+            let size=left.min(CHUNK as u64) as usize;
+            let block=&mut chunk[..size];
+            if random {
+                rand::thread_rng().fill_bytes(block);
+            } else {
+                block.fill(0);
+            }
+            file.write_all(block).map_err(err)?;
+            left-=size as u64;
+        }
+        file.sync_all().map_err(err)?;
+    }
+    file.set_len(0).map_err(err)?;
+    file.sync_all().map_err(err)?;
+    drop(file);
+    fs::remove_file(path).map_err(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn files_are_removed_but_links_and_directories_refused() {
+        let root=std::env::temp_dir().join(format!("shamir-shred-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        // Larger than one chunk, so the overwrite loops.
+        let share=root.join("share");
+        fs::write(&share, vec![7u8;CHUNK*2+3]).unwrap();
+        shred(&share).unwrap();
+        assert!(!share.exists());
+        assert!(shred(&share).is_err());
+        assert!(shred(&root).is_err());
+
+        #[cfg(unix)]
+        {
+            let target=root.join("target");
+            fs::write(&target, b"keep me").unwrap();
+            std::os::unix::fs::symlink(&target, root.join("link")).unwrap();
+            assert!(shred(&root.join("link")).is_err());
+            assert_eq!(fs::read(&target).unwrap(), b"keep me");
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
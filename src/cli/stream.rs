@@ -0,0 +1,198 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use shamir_rust::dealing::{self, DealingJournal, JOURNAL_FILE};
+use shamir_rust::error::ShamirError;
+use shamir_rust::stream::{join_streams, StreamOptions, StreamSplitter};
+
+use super::{create_private, output, read_string, tr, Args, CliError};
+
+// `split-stream` splits stdin (or a file) into share streams under --out
+// without holding the input in memory, e.g. `tar c dir | shamir split-stream`;
+// `join-stream` joins them back to stdout or --out. Buffering per share is
+// bounded by --high-watermark: past it, reading pauses until the slowest
+// share file drains to --low-watermark (see `stream.rs`). With --mmap
+// (feature `mmap`) a file input is memory-mapped instead of read (see
+// `mmap.rs`). With --resumable a file input is dealt chunk by chunk with a
+// journal under --out, and after an interruption `split-stream FILE --out
+// DIR --resume` continues it (see `dealing.rs`).
+
+pub fn split_stream(args:&Args)->Result<(),CliError>{
+    if args.flag("resumable") || args.flag("resume") {
+        return split_resumable(args);
+    }
+    let k=args.number("threshold")?;
+    let n=args.number("shares")?;
+    let out=PathBuf::from(args.required("out")?);
+    let defaults=StreamOptions::default();
+    let options=StreamOptions {
+        chunk_size: bytes_option(args, "chunk-size", defaults.chunk_size)?,
+        high_watermark: bytes_option(args, "high-watermark", defaults.high_watermark)?,
+        low_watermark: bytes_option(args, "low-watermark", defaults.low_watermark)?,
+    };
+    if n > 255 {
+        return Err(ShamirError::TooManyShares.into());
+    }
+    let xs:Vec<u8>=(1..=n.max(0)).map(|x| x as u8).collect();
+    let splitter=StreamSplitter::new(k, &xs, options)?;
+
+    fs::create_dir_all(&out).map_err(|e| format!("{}: {e}", out.display()))?;
+    let paths=share_paths(&out, &xs);
+    let writers=paths.iter().map(|path| create_private(path).map(BufWriter::new))
+        .collect::<Result<Vec<_>,_>>()?;
+    let stats=match args.positional.first().filter(|f| f.as_str()!="-") {
+        #[cfg(feature = "mmap")]
+        Some(file) if args.flag("mmap") => splitter.split_mmap(std::path::Path::new(file), writers)?,
+        Some(file) => splitter.split(File::open(file).map_err(|e| format!("{file}: {e}"))?, writers)?,
+        None => splitter.split(io::stdin().lock(), writers)?,
+    };
+
+    for (x, path) in xs.iter().zip(&paths) {
+        output::result(args, &path.display().to_string(), &[&"stream", x, &path.display()]);
+    }
+    output::info(args, &tr!("split-stream-done", bytes=stats.bytes, shares=n, threshold=k, stalls=stats.stalls),
+        &[&"streamed", &stats.bytes, &stats.stalls, &stats.peak_buffered]);
+    Ok(())
+}
+
+fn share_paths(out:&Path,xs:&[u8])->Vec<PathBuf>{
+    xs.iter().map(|x| out.join(format!("share-{x}-of-{}.stream", xs.len()))).collect()
+}
+
+fn split_resumable(args:&Args)->Result<(),CliError>{
+    let input=args.positional.first().filter(|f| f.as_str()!="-").ok_or_else(|| CliError::usage(tr!("resumable-needs-file")))?;
+    if args.flag("mmap") {
+        return Err(CliError::usage(tr!("resumable-with-mmap")));
+    }
+    let input=Path::new(input);
+    let out=PathBuf::from(args.required("out")?);
+    let journal=out.join(JOURNAL_FILE);
+    let (xs, threshold, stats)=if args.flag("resume") {
+        let state=DealingJournal::parse(&read_string(&journal)?).map_err(|e| CliError::integrity(format!("{}: {e}", journal.display())))?;
+        let stats=dealing::resume(input, &share_paths(&out, &state.xs), &journal)?;
+        (state.xs, state.threshold, stats)
+    } else {
+        let k=args.number("threshold")?;
+        let n=args.number("shares")?;
+        if n > 255 {
+            return Err(ShamirError::TooManyShares.into());
+        }
+        if journal.exists() {
+            return Err(CliError::usage(tr!("dealing-journal-exists", path=journal.display())));
+        }
+        let xs:Vec<u8>=(1..=n.max(0)).map(|x| x as u8).collect();
+        let chunk_size=bytes_option(args, "chunk-size", StreamOptions::default().chunk_size)?;
+        fs::create_dir_all(&out).map_err(|e| format!("{}: {e}", out.display()))?;
+        let stats=dealing::split_file(k, &xs, chunk_size, input, &share_paths(&out, &xs), &journal)?;
+        (xs, k as usize, stats)
+    };
+
+    for (x, path) in xs.iter().zip(share_paths(&out, &xs)) {
+        output::result(args, &path.display().to_string(), &[&"stream", x, &path.display()]);
+    }
+    output::info(args, &tr!("split-stream-dealt", bytes=stats.bytes, shares=xs.len(), threshold=threshold, resumed=stats.resumed_chunks, chunks=stats.total_chunks),
+        &[&"dealt", &stats.bytes, &stats.resumed_chunks, &stats.total_chunks]);
+    Ok(())
+}
+
+pub fn join_stream(args:&Args)->Result<(),CliError>{
+    if args.positional.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-shares")));
+    }
+    let readers=args.positional.iter().map(|file| File::open(file).map(BufReader::new).map_err(|e| format!("{file}: {e}")))
+        .collect::<Result<Vec<_>,_>>()?;
+    let chunk_size=bytes_option(args, "chunk-size", StreamOptions::default().chunk_size)?;
+    match args.value("out").filter(|v| !v.is_empty()) {
+        Some(out) => {
+            let file=create_private(Path::new(out))?;
+            let bytes=join_streams(readers, BufWriter::new(file), chunk_size)?;
+            output::info(args, &tr!("secret-written", bytes=bytes, path=out), &[&"secret", &out, &bytes]);
+        }
+        None => {
+            join_streams(readers, io::stdout().lock(), chunk_size)?;
+        }
+    }
+    Ok(())
+}
+
+fn bytes_option(args:&Args,name:&str,default:usize)->Result<usize,CliError>{
+    match args.value(name) {
+        Some(value) if !value.is_empty() => value.parse::<usize>().map_err(|_| CliError::usage(tr!("option-not-number", name=name))),
+        _ => Ok(default),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::cli::error::{BAD_ARGUMENTS, INTEGRITY};
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn share_streams_join_back() {
+        let root=std::env::temp_dir().join(format!("shamir-cli-stream-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let input:Vec<u8>=(0..10_000u32).map(|i| (i*7) as u8).collect();
+        fs::write(path("input"), &input).unwrap();
+
+        split_stream(&args(&[&path("input"), "--threshold", "2", "--shares", "3", "--out", &path("out"),
+            "--chunk-size", "1000", "--high-watermark", "4000", "--low-watermark", "1000", "--quiet"])).unwrap();
+        join_stream(&args(&[&path("out/share-3-of-3.stream"), &path("out/share-1-of-3.stream"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read(path("joined")).unwrap(), input);
+
+        let chunk=split_stream(&args(&[&path("input"), "--threshold", "2", "--shares", "3", "--out", &path("out"), "--chunk-size", "big"]));
+        assert_eq!(chunk.unwrap_err().code, BAD_ARGUMENTS);
+        assert!(split_stream(&args(&[&path("input"), "--threshold", "2", "--shares", "256", "--out", &path("out")])).is_err());
+        assert_eq!(join_stream(&args(&[])).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mapped_input_splits_like_a_read_one() {
+        let root=std::env::temp_dir().join(format!("shamir-cli-mmap-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let input:Vec<u8>=(0..5000u32).map(|i| (i*3) as u8).collect();
+        fs::write(path("input"), &input).unwrap();
+
+        split_stream(&args(&[&path("input"), "--mmap", "--threshold", "2", "--shares", "2", "--out", &path("out"), "--chunk-size", "512", "--quiet"])).unwrap();
+        join_stream(&args(&[&path("out/share-1-of-2.stream"), &path("out/share-2-of-2.stream"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read(path("joined")).unwrap(), input);
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn resumable_split_never_deals_over_a_journal() {
+        let root=std::env::temp_dir().join(format!("shamir-cli-resumable-{}", std::process::id()));
+        let _=fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let path=|name:&str| root.join(name).display().to_string();
+        let input:Vec<u8>=(0..3000u32).map(|i| (i*11) as u8).collect();
+        fs::write(path("input"), &input).unwrap();
+        let dealt=|words:&[&str]| split_stream(&args(&[&[path("input").as_str(), "--out", &path("out"), "--quiet"], words].concat()));
+
+        dealt(&["--resumable", "--threshold", "2", "--shares", "3", "--chunk-size", "1000"]).unwrap();
+        // The journal goes once the split completes.
+        let journal=root.join("out").join(JOURNAL_FILE);
+        assert!(!journal.exists());
+        join_stream(&args(&[&path("out/share-2-of-3.stream"), &path("out/share-3-of-3.stream"), "--out", &path("joined"), "--quiet"])).unwrap();
+        assert_eq!(fs::read(path("joined")).unwrap(), input);
+
+        // A journal left behind is resumed, never dealt over.
+        fs::write(&journal, "not a journal\n").unwrap();
+        assert_eq!(dealt(&["--resumable", "--threshold", "2", "--shares", "3"]).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(dealt(&["--resume"]).unwrap_err().code, INTEGRITY);
+        assert_eq!(split_stream(&args(&["-", "--resumable", "--out", &path("out")])).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(dealt(&["--resume", "--mmap"]).unwrap_err().code, BAD_ARGUMENTS);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
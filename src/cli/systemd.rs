@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use shamir_rust::crypto::{zeroize, ShamirSS};
+use shamir_rust::encoding::decode_any_share;
+use shamir_rust::join::{ExtraShares, JoinOptions};
+
+use super::{output, read_string, tr, Args, CliError};
+
+// Service unseal with systemd credentials. Each host keeps its share of a
+// service's secret as a credential encrypted by `systemd-creds` (bound to the
+// host's TPM2 by default), so a share copied off the disk is useless. At
+// service start, `unseal --systemd` decrypts the local shares, runs --fetch
+// commands for shares held by other hosts, reconstructs the secret into a
+// sealed memfd (memory only, read-only once written) and execs the service
+// with the descriptor inherited:
+//
+//   shamir seal --systemd share-1-of-3.shs --name db-key [--with-key tpm2]
+//   shamir unseal --systemd --threshold 2 db-key.cred
+//       --fetch "ssh host2 systemd-creds decrypt /etc/credstore/db-key.cred -" --exec '/usr/bin/db --key-file /proc/self/fd/$SHAMIR_SECRET_FD'
+//
+// Inside a unit with LoadCredentialEncrypted=db-key:/etc/credstore/db-key.cred,
+// `--credential db-key` reads the share systemd already decrypted into
+// $CREDENTIALS_DIRECTORY instead. A --fetch command prints one share per
+// line. The service finds the descriptor in $SHAMIR_SECRET_FD.
+
+pub fn seal(args:&Args)->Result<(),CliError>{
+    require_systemd(args)?;
+    let file=args.positional.first().ok_or_else(|| CliError::with_usage(tr!("missing-share-file")))?;
+    let name=args.required("name")?;
+    let text=read_string(Path::new(file))?;
+    let (index, _)=decode_any_share(&text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+    let key=args.value("with-key").filter(|v| !v.is_empty()).unwrap_or("tpm2");
+    let out=args.value("out").filter(|v| !v.is_empty()).map(str::to_string).unwrap_or(format!("{name}.cred"));
+    if Path::new(&out).exists() {
+        return Err(tr!("credential-exists", path=out).into());
+    }
+    let name_arg=format!("--name={name}");
+    let key_arg=format!("--with-key={key}");
+    systemd_creds(&["encrypt", &name_arg, &key_arg, "-", &out], text.trim().as_bytes())
+        .map_err(|e| tr!("credential-encrypt-failed", name=name, error=e))?;
+    output::result(args, &tr!("credential-sealed", index=index, name=name, path=out), &[&"sealed", &index, &name, &out]);
+    Ok(())
+}
+
+pub fn unseal(args:&Args)->Result<(),CliError>{
+    require_systemd(args)?;
+    let command=args.required("exec")?;
+    let mut parts:BTreeMap<i32,Vec<u8>>=BTreeMap::new();
+    for file in &args.positional {
+        let name=Path::new(file).file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let name_arg=format!("--name={name}");
+        let text=systemd_creds(&["decrypt", &name_arg, file, "-"], b"")
+            .map_err(|e| CliError::integrity(tr!("credential-decrypt-failed", name=name, error=e)))?;
+        add_share(&mut parts, file, &text)?;
+    }
+    let names=args.list("credential");
+    if !names.is_empty() {
+        let dir=std::env::var_os("CREDENTIALS_DIRECTORY").ok_or_else(|| CliError::usage(tr!("credentials-directory-missing")))?;
+        for name in names {
+            let path=Path::new(&dir).join(name);
+            add_share(&mut parts, name, &read_string(&path)?)?;
+        }
+    }
+    if let Some(fetch)=args.value("fetch").filter(|v| !v.is_empty()) {
+        let text=run(Command::new("sh").args(["-c", fetch]), b"").map_err(|e| tr!("fetch-failed", command=fetch, error=e))?;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            add_share(&mut parts, fetch, line)?;
+        }
+    }
+    if parts.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-shares")));
+    }
+    let shares=parts.len();
+    let mut secret=match args.value("threshold").filter(|v| !v.is_empty()) {
+        Some(_) => {
+            let options=JoinOptions { threshold: Some(args.number("threshold")?), extra_shares: ExtraShares::ErrorCorrect, ..JoinOptions::default() };
+            ShamirSS::join_with_options(parts, &options)?
+        }
+        None => ShamirSS::join(parts)?,
+    };
+    let sealed=memfd::sealed("shamir-secret", &secret);
+    zeroize(&mut secret);
+    let fd=sealed?;
+    output::info(args, &tr!("unsealed", shares=shares, fd=fd), &[&"unsealed", &shares, &fd]);
+    Err(memfd::exec(command, fd).into())
+}
+
+fn require_systemd(args:&Args)->Result<(),CliError>{
+    if !args.flag("systemd") {
+        return Err(CliError::with_usage(tr!("missing-systemd")));
+    }
+    Ok(())
+}
+
+fn add_share(parts:&mut BTreeMap<i32,Vec<u8>>,source:&str,text:&str)->Result<(),CliError>{
+    let (index, data)=decode_any_share(text).map_err(|e| CliError::integrity(format!("{source}: {e}")))?;
+    if parts.insert(index, data).is_some() {
+        return Err(CliError::integrity(tr!("share-given-twice", file=source, index=index)));
+    }
+    Ok(())
+}
+
+// Runs systemd-creds with `input` on stdin; the share never appears on a
+// command line.
+fn systemd_creds(args:&[&str],input:&[u8])->Result<String,String>{
+    run(Command::new("systemd-creds").args(args), input)
+}
+
+fn run(command:&mut Command,input:&[u8])->Result<String,String>{
+    let mut child=command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+    child.stdin.take().ok_or("stdin")?.write_all(input).map_err(|e| e.to_string())?;
+    let out=child.wait_with_output().map_err(|e| e.to_string())?;
+    if !out.status.success() {
+        return Err(String::from_utf8_lossy(&out.stderr).trim().to_string());
+    }
+    String::from_utf8(out.stdout).map_err(|e| e.to_string())
+}
+
+#[cfg(target_os = "linux")]
+mod memfd{
+    use std::ffi::{c_char, c_int, c_uint, CString};
+    use std::fs::File;
+    use std::io::{Seek, SeekFrom, Write};
+    use std::os::fd::{FromRawFd, IntoRawFd, RawFd};
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    use super::super::tr;
+
+    const MFD_ALLOW_SEALING:c_uint=2;
+    const F_ADD_SEALS:c_int=1033;
+    const F_SEAL_SEAL:c_int=1;
+    const F_SEAL_SHRINK:c_int=2;
+    const F_SEAL_GROW:c_int=4;
+    const F_SEAL_WRITE:c_int=8;
+
+    extern "C" {
+        fn memfd_create(name:*const c_char,flags:c_uint)->c_int;
+        fn fcntl(fd:c_int,cmd:c_int, ...)->c_int;
+    }
+
+    // An anonymous in-memory file holding `data`, sealed against any further
+    // change. It has no close-on-exec flag, so the service inherits it.
+    pub fn sealed(name:&str,data:&[u8])->Result<RawFd,String>{
+        let name=CString::new(name).map_err(|e| e.to_string())?;
+        // SAFETY: `name` is a valid NUL-terminated string for the call.
+        let fd=unsafe { memfd_create(name.as_ptr(), MFD_ALLOW_SEALING) };
+        if fd<0 {
+            return Err(tr!("memfd-failed", error=std::io::Error::last_os_error()));
+        }
+        // SAFETY: `fd` was just created and is owned by nothing else.
+        let mut file=unsafe { File::from_raw_fd(fd) };
+        file.write_all(data).map_err(|e| tr!("memfd-failed", error=e))?;
+        file.seek(SeekFrom::Start(0)).map_err(|e| tr!("memfd-failed", error=e))?;
+        // SAFETY: F_ADD_SEALS takes a single int argument.
+        if unsafe { fcntl(fd, F_ADD_SEALS, F_SEAL_SEAL|F_SEAL_SHRINK|F_SEAL_GROW|F_SEAL_WRITE) }!=0 {
+            return Err(tr!("memfd-failed", error=std::io::Error::last_os_error()));
+        }
+        Ok(file.into_raw_fd())
+    }
+
+    // Replaces this process with `command`; only returns on failure.
+    pub fn exec(command:&str,fd:RawFd)->String{
+        let error=Command::new("sh").args(["-c", command]).env("SHAMIR_SECRET_FD", fd.to_string()).exec();
+        tr!("exec-failed", command=command, error=error)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod memfd{
+    use super::super::tr;
+
+    pub fn sealed(_name:&str,_data:&[u8])->Result<i32,String>{
+        Err(tr!("memfd-unsupported"))
+    }
+
+    pub fn exec(_command:&str,_fd:i32)->String{
+        tr!("memfd-unsupported")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use shamir_rust::encoding::encode_share;
+
+    use crate::cli::error::{BAD_ARGUMENTS, INTEGRITY};
+
+    fn args(words:&[&str])->Args{
+        Args::parse(&words.iter().map(|w| w.to_string()).collect::<Vec<_>>())
+    }
+
+    // Only failures are exercised: a successful unseal execs and never
+    // returns to the test.
+    #[test]
+    fn unseal_refuses_before_joining() {
+        assert_eq!(unseal(&args(&["--exec", "true"])).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(seal(&args(&["share.shs", "--name", "db-key"])).unwrap_err().code, BAD_ARGUMENTS);
+        assert_eq!(unseal(&args(&["--systemd", "--exec", "true"])).unwrap_err().code, BAD_ARGUMENTS);
+
+        let line=encode_share(1, b"share").trim().to_string();
+        let twice=format!("printf '%s\\n%s\\n' '{line}' '{line}'");
+        assert_eq!(unseal(&args(&["--systemd", "--exec", "true", "--fetch", &twice])).unwrap_err().code, INTEGRITY);
+        assert!(unseal(&args(&["--systemd", "--exec", "true", "--fetch", "echo broken >&2; exit 3"])).is_err());
+    }
+
+    #[test]
+    fn tool_errors_carry_its_stderr() {
+        assert_eq!(run(Command::new("sh").args(["-c", "echo refused >&2; exit 1"]), b"").unwrap_err(), "refused");
+        assert_eq!(run(&mut Command::new("cat"), b"on stdin").unwrap(), "on stdin");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sealed_memfd_cannot_be_changed() {
+        use std::fs::File;
+        use std::io::Read;
+        use std::os::fd::FromRawFd;
+
+        let fd=memfd::sealed("shamir-test", b"secret").unwrap();
+        // SAFETY: the descriptor was just created for this test alone.
+        let mut file=unsafe { File::from_raw_fd(fd) };
+        let mut read=Vec::new();
+        file.read_to_end(&mut read).unwrap();
+        assert_eq!(read, b"secret");
+        assert!(file.write_all(b"more").is_err());
+        assert!(file.set_len(0).is_err());
+    }
+}
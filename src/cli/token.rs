@@ -0,0 +1,165 @@
+use std::path::Path;
+use std::time::SystemTime;
+
+use rand::RngCore;
+use shamir_rust::encoding::{decode_any_share, share_fingerprint};
+use shamir_rust::error::ShamirError;
+use shamir_rust::revocation::{read_signed, signed_text};
+use shamir_rust::sha256::to_hex;
+use shamir_rust::token::{OutputForm, RecoveryToken};
+use shamir_rust::transcript::Transcript;
+
+use super::{dates, keyring, output, read_string, tr, write, Args, CliError};
+
+// Recovery-context tokens (see `token.rs`): `token issue` has the dealer
+// sign one, `token show` prints one, and `join --token` checks one before
+// reconstructing. With --require-token (set it in a config profile to make
+// it the rule) join refuses to run without a valid token.
+
+const ID_BYTES:usize=8;
+const DEFAULT_OUTPUTS:&str="file";
+
+pub fn run(args:&Args)->Result<(),CliError>{
+    match args.positional.first().map(|s| s.as_str()) {
+        Some("issue") => issue(args),
+        Some("show") => show(args),
+        _ => Err(CliError::with_usage(tr!("unknown-token-command"))),
+    }
+}
+
+fn issue(args:&Args)->Result<(),CliError>{
+    let key=args.required("sign-with")?;
+    let out=args.required("out")?;
+    let purpose=args.required("purpose")?.trim().to_string();
+    if purpose.is_empty() {
+        return Err(CliError::usage(tr!("token-no-purpose")));
+    }
+    let issued=dates::now().ok_or_else(|| CliError::usage(tr!("logical-time-omitted", what="token issue")))?;
+    let expires=dates::parse_time(args.required("expires")?).ok_or_else(|| CliError::usage(tr!("token-expires-malformed", value=args.value("expires").unwrap_or(""))))?;
+    if expires<=issued {
+        return Err(CliError::usage(tr!("token-expires-past")));
+    }
+    let outputs=match args.list("outputs") {
+        names if names.is_empty() => vec![DEFAULT_OUTPUTS],
+        names => names,
+    };
+    let outputs=outputs.into_iter()
+        .map(|name| OutputForm::from_name(name).ok_or_else(|| CliError::usage(tr!("token-output-unknown", form=name))))
+        .collect::<Result<Vec<_>,_>>()?;
+
+    let mut shares=Vec::new();
+    for file in &args.positional[1..] {
+        let (index, data)=decode_any_share(&read_string(Path::new(file))?).map_err(|e| CliError::integrity(format!("{file}: {e}")))?;
+        shares.push(share_fingerprint(index, &data));
+    }
+    shares.extend(args.list("fingerprint").into_iter().map(|fingerprint| fingerprint.to_string()));
+    if let Some(path)=args.value("transcript").filter(|v| !v.is_empty()) {
+        let transcript=Transcript::parse(&read_string(Path::new(path))?).map_err(|e| CliError::integrity(format!("{path}: {e}")))?;
+        shares.extend(transcript.custodians.iter().map(|c| c.fingerprint.clone()));
+        shares.extend(transcript.spares.iter().map(|s| s.fingerprint.clone()));
+    }
+    shares.sort();
+    shares.dedup();
+    if shares.is_empty() {
+        return Err(CliError::with_usage(tr!("missing-token-shares")));
+    }
+
+    let mut id=[0u8;ID_BYTES];
+    rand::thread_rng().fill_bytes(&mut id);
+    let token=RecoveryToken { id: to_hex(&id), purpose, issued, expires, outputs, shares };
+    let text=token.to_text();
+    let signature=keyring::sign(key, args.value("passphrase-file").filter(|v| !v.is_empty()), text.as_bytes())?;
+    write(Path::new(out), signed_text(&text, &signature).as_bytes())?;
+    output::result(args, &tr!("token-issued", id=token.id, path=out, shares=token.shares.len()), &[&"token", &token.id, &out]);
+    Ok(())
+}
+
+fn show(args:&Args)->Result<(),CliError>{
+    let file=args.positional.get(1).ok_or_else(|| CliError::with_usage(tr!("missing-token-file")))?;
+    let (token, signer)=read(args, file)?;
+    let outputs:Vec<&str>=token.outputs.iter().map(|f| f.name()).collect();
+    output::result(args, &tr!("token-summary", id=token.id, purpose=token.purpose), &[&"token", &token.id, &token.purpose]);
+    output::result(args, &tr!("token-window", issued=dates::format_time(token.issued), expires=dates::format_time(token.expires)), &[&"window", &token.issued, &token.expires]);
+    output::result(args, &tr!("token-outputs", outputs=outputs.join(", ")), &[&"outputs", &outputs.join(",")]);
+    output::result(args, &tr!("token-shares", shares=token.shares.join(", ")), &[&"shares", &token.shares.join(",")]);
+    match signer {
+        Some(fingerprint) => output::result(args, &tr!("signature-good", fingerprint=fingerprint), &[&"signature", &"good", &fingerprint]),
+        None => output::result(args, &tr!("token-signature-unchecked"), &[&"signature", &"unchecked"]),
+    }
+    Ok(())
+}
+
+// The token in `file`, with the signer's fingerprint when --token-key was
+// given. A token is only enforced once its signature has been checked.
+fn read(args:&Args,file:&str)->Result<(RecoveryToken,Option<String>),CliError>{
+    let (text, signature)=read_signed(&read_string(Path::new(file))?).map_err(CliError::integrity)?;
+    let signer=match (args.value("token-key").filter(|v| !v.is_empty()), signature) {
+        (Some(keyring), Some(signature)) => Some(keyring::verify(keyring, text.as_bytes(), &signature).map_err(CliError::integrity)?),
+        (Some(_), None) => return Err(CliError::integrity(tr!("token-unsigned", path=file))),
+        (None, _) => None,
+    };
+    Ok((RecoveryToken::parse(&text).map_err(|e| CliError::integrity(format!("{file}: {e}")))?, signer))
+}
+
+// Whether `token` lets these shares be joined into `form` now. Always on
+// the system clock: --logical-time and SOURCE_DATE_EPOCH only pin the dates
+// the CLI writes, and must not let whoever runs join bring an expired token
+// back to life.
+fn authorize(token:&RecoveryToken,fingerprints:&[String],form:OutputForm)->Result<(),CliError>{
+    token.check_at(fingerprints, SystemTime::now()).map_err(ShamirError::JoinDenied)?;
+    if !token.permits(form) {
+        let allowed:Vec<&str>=token.outputs.iter().map(|f| f.name()).collect();
+        return Err(ShamirError::JoinDenied(tr!("token-output-denied", form=form.name(), allowed=allowed.join(", "))).into());
+    }
+    Ok(())
+}
+
+// For join: checks --token against the shares and the output form before
+// anything is reconstructed, and returns the token it accepted. Without
+// --token this passes unless --require-token is set.
+pub fn enforce(args:&Args,fingerprints:&[String],form:OutputForm)->Result<Option<RecoveryToken>,CliError>{
+    let Some(file)=args.value("token").filter(|v| !v.is_empty()) else {
+        if args.flag("require-token") {
+            return Err(ShamirError::JoinDenied(tr!("token-required")).into());
+        }
+        return Ok(None);
+    };
+    if args.value("token-key").is_none_or(|v| v.is_empty()) {
+        return Err(CliError::usage(tr!("token-without-key")));
+    }
+    let (token, signer)=read(args, file)?;
+    authorize(&token, fingerprints, form)?;
+    // A secret going to stdout must not share it with the notice.
+    let signer=signer.unwrap_or_default();
+    if form!=OutputForm::Stdout {
+        output::info(args, &tr!("token-accepted", id=token.id, purpose=token.purpose, signer=signer), &[&"authorized", &token.id, &token.purpose, &signer]);
+    }
+    Ok(Some(token))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::UNIX_EPOCH;
+
+    use super::*;
+
+    // A clock pinned before the expiry must not revive the token.
+    #[test]
+    fn expired_token_is_rejected_under_a_logical_clock() {
+        dates::select(&Args::parse(&["--logical-time".to_string(), "2001-01-01".to_string()])).unwrap();
+        assert_eq!(dates::today().as_deref(), Some("2001-01-01"));
+        let day=86_400;
+        let token=RecoveryToken {
+            id: "0123456789abcdef".into(),
+            purpose: "audit".into(),
+            issued: 30*365*day,
+            expires: 32*365*day,
+            outputs: vec![OutputForm::File],
+            shares: vec!["0badf00d".into()],
+        };
+        assert!(authorize(&token, &["0badf00d".to_string()], OutputForm::File).is_err());
+        let current=RecoveryToken { expires: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()+day, ..token };
+        assert!(authorize(&current, &["0badf00d".to_string()], OutputForm::File).is_ok());
+        assert!(authorize(&current, &["0badf00d".to_string()], OutputForm::Stdout).is_err());
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use crate::encoding::share_fingerprint;
+use crate::error::ShamirError;
+use crate::sha256::{to_hex, Sha256};
+
+// Set-level commitment. Two splits with the same k and n give shares that
+// look alike and join without complaint when mixed, into garbage. With
+// `split --set-commitment` every share file carries one line listing the
+// fingerprints of all shares of its split (spares included) and a digest of
+// that list:
+//
+//   set <digest> <index>:<fingerprint>,<index>:<fingerprint>,...
+//
+// digest = SHA-256("shamir-set v1\n" + "<index>:<fingerprint>\n"...) in index
+// order, truncated to 16 bytes. `join` takes the commitment most shares
+// agree on and names every share that is not in it. This catches mistakes,
+// not forgery: anyone can write a set line.
+//
+// The line is also a guessing oracle, like a published check value (see
+// check.rs): k-1 custodians plus a guess of the secret fix the polynomial,
+// so they can recompute the missing shares and compare their fingerprints
+// with the line, offline. Only commit to the set of a secret that cannot be
+// guessed; the CLI warns below GUESSABLE_SECRET_LEN bytes.
+
+const LINE_PREFIX:&str="set ";
+const DIGEST_HEADER:&str="shamir-set v1\n";
+const DIGEST_LEN:usize=16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetCommitment{
+    pub members:BTreeMap<i32,String>,
+}
+
+impl SetCommitment{
+
+    pub fn new<'a,I:IntoIterator<Item=(&'a i32,&'a Vec<u8>)>>(parts:I)->SetCommitment{
+        SetCommitment { members: parts.into_iter().map(|(index, data)| (*index, share_fingerprint(*index, data))).collect() }
+    }
+
+    pub fn digest(&self)->String{
+        let mut text=DIGEST_HEADER.to_string();
+        for (index, fingerprint) in &self.members {
+            text.push_str(&format!("{index}:{fingerprint}\n"));
+        }
+        to_hex(&Sha256::digest(text.as_bytes())[..DIGEST_LEN])
+    }
+
+    pub fn contains(&self,index:i32,data:&[u8])->bool{
+        self.members.get(&index).is_some_and(|fingerprint| *fingerprint==share_fingerprint(index, data))
+    }
+
+    pub fn line(&self)->String{
+        let members:Vec<String>=self.members.iter().map(|(index, fingerprint)| format!("{index}:{fingerprint}")).collect();
+        format!("{LINE_PREFIX}{} {}\n", self.digest(), members.join(","))
+    }
+
+    // Parses the value of a `set` line and checks its digest.
+    pub fn parse(value:&str)->Result<SetCommitment,String>{
+        let (digest, list)=value.trim().split_once(' ').ok_or("Malformed set line")?;
+        let mut members=BTreeMap::new();
+        for member in list.trim().split(',') {
+            let (index, fingerprint)=member.split_once(':').ok_or_else(|| format!("Malformed set member {member:?}"))?;
+            let index=index.parse::<i32>().map_err(|_| format!("Malformed set member {member:?}"))?;
+            if members.insert(index, fingerprint.to_string()).is_some() {
+                return Err(format!("Set lists share {index} twice"));
+            }
+        }
+        let commitment=SetCommitment { members };
+        if commitment.digest()!=digest {
+            return Err("Set line does not match its digest".to_string());
+        }
+        Ok(commitment)
+    }
+}
+
+// The commitment in a bundle's `set` line, if it has one.
+pub fn read_set(bundle:&str)->Option<Result<SetCommitment,String>>{
+    bundle.lines().find_map(|line| line.trim().strip_prefix(LINE_PREFIX)).map(SetCommitment::parse)
+}
+
+// Checks that the shares come from one split. The reference is the
+// commitment carried by the most shares; a share is foreign when it carries
+// another one or is not listed in it, which also catches shares without a
+// set line. Nothing is checked when no share carries a commitment, and a tie
+// between two commitments cannot say which side is foreign.
+pub fn check_membership(shares:&[(i32,&[u8],Option<&SetCommitment>)])->Result<(),ShamirError>{
+    let mut groups:BTreeMap<String,(usize,&SetCommitment)>=BTreeMap::new();
+    for (_, _, commitment) in shares {
+        if let Some(commitment)=commitment {
+            groups.entry(commitment.digest()).or_insert((0, commitment)).0+=1;
+        }
+    }
+    let Some(most)=groups.values().map(|(count, _)| *count).max() else { return Ok(()) };
+    let mut leaders=groups.iter().filter(|(_, (count, _))| *count==most);
+    let (digest, (_, reference))=leaders.next().expect("groups is not empty");
+    if leaders.next().is_some() {
+        return Err(ShamirError::InconsistentShares(shares.iter().map(|(index, _, _)| *index).collect()));
+    }
+    let foreign:Vec<i32>=shares.iter()
+        .filter(|(index, data, commitment)| commitment.is_some_and(|c| c.digest()!=*digest) || !reference.contains(*index, data))
+        .map(|(index, _, _)| *index)
+        .collect();
+    if foreign.is_empty() { Ok(()) } else { Err(ShamirError::ForeignShares(foreign)) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Parts, ShamirSS};
+
+    #[test]
+    fn shares_mixed_from_two_splits_are_foreign() {
+        let first=ShamirSS::split(5, 3, b"same secret".to_vec()).unwrap();
+        let second=ShamirSS::split(5, 3, b"same secret".to_vec()).unwrap();
+        let (ours, theirs)=(SetCommitment::new(&first), SetCommitment::new(&second));
+        assert_ne!(ours.digest(), theirs.digest());
+        assert_eq!(read_set(&ours.line()).unwrap().unwrap(), ours);
+        let edited=ours.line().replacen("1:", "6:", 1);
+        assert!(read_set(&edited).unwrap().is_err());
+
+        // Shares 1, 2 and 4 from the first split, 3 and 5 from the second.
+        let mixed:Vec<(i32,&[u8],Option<&SetCommitment>)>=[(1, &first, &ours), (2, &first, &ours), (3, &second, &theirs), (4, &first, &ours), (5, &second, &theirs)]
+            .into_iter()
+            .map(|(index, parts, commitment):(i32,&Parts,&SetCommitment)| (index, parts[&index].as_slice(), Some(commitment)))
+            .collect();
+        assert_eq!(check_membership(&mixed), Err(ShamirError::ForeignShares(vec![3, 5])));
+
+        // Without their set line, shares from the second split are still
+        // not listed in the first split's commitment.
+        let unlabelled:Vec<_>=mixed.iter().map(|&(index, data, commitment)| (index, data, commitment.filter(|_| index!=3 && index!=5))).collect();
+        assert_eq!(check_membership(&unlabelled), Err(ShamirError::ForeignShares(vec![3, 5])));
+
+        let honest:Vec<_>=mixed.iter().filter(|(index, _, _)| *index!=3 && *index!=5).copied().collect();
+        assert_eq!(check_membership(&honest), Ok(()));
+        assert_eq!(check_membership(&mixed[1..4]), Err(ShamirError::ForeignShares(vec![3])));
+        // One share of each: neither side can be called foreign.
+        assert_eq!(check_membership(&mixed[1..3]), Err(ShamirError::InconsistentShares(vec![2, 3])));
+    }
+}
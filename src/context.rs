@@ -0,0 +1,210 @@
+use std::sync::{Arc, Mutex, OnceLock, Weak};
+
+use rand::RngCore;
+
+use crate::crypto::{Parts, ShamirSS, SMALL_SECRET_MAX};
+use crate::error::ShamirError;
+use crate::join::JoinOptions;
+use crate::rng::{PolicyRng, RngPolicy};
+
+// Shared configuration for servers that split and join on many threads.
+// A context is built once (CPU feature detection runs at most once per
+// process), cloned cheaply into every worker, and may differ per tenant.
+// With an RNG policy, all clones draw from one PolicyRng behind a mutex;
+// without one, each call uses the thread-local `rand::thread_rng`.
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum FieldBackend{
+    // EXP/LOG table arithmetic over GF(256), on every architecture.
+    Table,
+    // Split-table bulk multiply with NEON `vqtbl1q_u8` on aarch64; see
+    // src/bulk.rs. Elsewhere, or without NEON, it falls back to `Table`.
+    Neon,
+}
+
+impl FieldBackend{
+
+    // The fastest backend this CPU supports.
+    pub fn detect()->FieldBackend{
+        if cfg!(target_arch = "aarch64") && CpuFeatures::detect().neon { FieldBackend::Neon } else { FieldBackend::Table }
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct Limits{
+    pub max_secret_len:usize,
+    pub max_shares:i32,
+    pub max_shares_considered:usize,
+    // Longest secret split and joined on the stack (at most
+    // SMALL_SECRET_MAX); 0 turns the fast path off for this context.
+    pub small_secret_limit:usize,
+}
+
+impl Default for Limits{
+    fn default()->Self{
+        Limits { max_secret_len: usize::MAX, max_shares: 255, max_shares_considered: 255, small_secret_limit: SMALL_SECRET_MAX }
+    }
+}
+
+// `neon` picks the field backend; the x86 flags are reported only, as
+// there is no x86 bulk multiply yet.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct CpuFeatures{
+    pub sse2:bool,
+    pub ssse3:bool,
+    pub avx2:bool,
+    pub neon:bool,
+}
+
+impl CpuFeatures{
+
+    // Detected once per process and cached.
+    pub fn detect()->CpuFeatures{
+        static DETECTED:OnceLock<CpuFeatures>=OnceLock::new();
+        *DETECTED.get_or_init(|| {
+            #[allow(unused_mut)]
+            let mut features=CpuFeatures::default();
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            {
+                features.sse2=std::is_x86_feature_detected!("sse2");
+                features.ssse3=std::is_x86_feature_detected!("ssse3");
+                features.avx2=std::is_x86_feature_detected!("avx2");
+            }
+            #[cfg(target_arch = "aarch64")]
+            {
+                features.neon=std::arch::is_aarch64_feature_detected!("neon");
+            }
+            features
+        })
+    }
+}
+
+// Every context created in this process, for `scrub`.
+static LIVE:Mutex<Vec<Weak<Inner>>>=Mutex::new(Vec::new());
+
+#[derive(Debug)]
+struct Inner{
+    backend:FieldBackend,
+    limits:Limits,
+    rng_policy:Option<RngPolicy>,
+    rng:Option<Mutex<PolicyRng>>,
+    cpu:CpuFeatures,
+}
+
+#[derive(Debug,Clone)]
+pub struct ShamirContext{
+    inner:Arc<Inner>,
+}
+
+impl Default for ShamirContext{
+    fn default()->Self{
+        ShamirContext::new(Limits::default(), None)
+    }
+}
+
+impl ShamirContext{
+
+    pub fn new(limits:Limits,rng_policy:Option<RngPolicy>)->ShamirContext{
+        let inner=Arc::new(Inner {
+            backend: FieldBackend::detect(),
+            limits,
+            rng_policy,
+            rng: rng_policy.map(|policy| Mutex::new(PolicyRng::new(policy))),
+            cpu: CpuFeatures::detect(),
+        });
+        if inner.rng.is_some() {
+            let mut live=LIVE.lock().unwrap_or_else(|e| e.into_inner());
+            live.retain(|context| context.strong_count()>0);
+            live.push(Arc::downgrade(&inner));
+        }
+        ShamirContext { inner }
+    }
+
+    // Reseeds this context's RNG in place; see `hardening::scrub`.
+    pub fn scrub(&self){
+        if let Some(rng)=&self.inner.rng {
+            rng.lock().unwrap_or_else(|e| e.into_inner()).scrub();
+        }
+    }
+
+    pub fn backend(&self)->FieldBackend{
+        self.inner.backend
+    }
+
+    pub fn limits(&self)->Limits{
+        self.inner.limits
+    }
+
+    pub fn rng_policy(&self)->Option<RngPolicy>{
+        self.inner.rng_policy
+    }
+
+    pub fn cpu_features(&self)->CpuFeatures{
+        self.inner.cpu
+    }
+
+    pub fn split(&self,n:i32,k:i32,secret:Vec<u8>)->Result<Parts,ShamirError>{
+        match &self.inner.rng {
+            // A poisoned lock only means another split panicked; the RNG
+            // state itself is still usable.
+            Some(rng) => {
+                let mut rng=rng.lock().unwrap_or_else(|e| e.into_inner());
+                rng.begin_operation();
+                self.split_with_rng(n, k, secret, &mut *rng)
+            }
+            None => self.split_with_rng(n, k, secret, &mut rand::thread_rng()),
+        }
+    }
+
+    // `split` under this context's limits, drawing from the given RNG.
+    pub fn split_with_rng<R:RngCore+?Sized>(&self,n:i32,k:i32,secret:Vec<u8>,rng:&mut R)->Result<Parts,ShamirError>{
+        let limits=self.inner.limits;
+        if n>limits.max_shares {
+            return Err(ShamirError::TooManyShares)
+        }
+        if secret.len()>limits.max_secret_len {
+            return Err(ShamirError::SecretTooLarge { len: secret.len(), max: limits.max_secret_len })
+        }
+        ShamirSS::split_tuned(n, k, secret, rng, limits.small_secret_limit)
+    }
+
+    pub fn join(&self,parts:Parts)->Result<Vec<u8>,ShamirError>{
+        self.join_with_options(parts, &JoinOptions::default())
+    }
+
+    // `options.max_shares_considered` and `options.small_secret_limit` are
+    // further capped by the context limits; the context's field backend
+    // replaces `options.backend`.
+    pub fn join_with_options(&self,parts:Parts,options:&JoinOptions)->Result<Vec<u8>,ShamirError>{
+        let mut options=options.clone();
+        options.backend=self.inner.backend;
+        options.max_shares_considered=options.max_shares_considered.min(self.inner.limits.max_shares_considered);
+        options.small_secret_limit=options.small_secret_limit.min(self.inner.limits.small_secret_limit);
+        ShamirSS::join_with_options(parts, &options)
+    }
+}
+
+pub(crate) fn scrub_all(){
+    let live=LIVE.lock().unwrap_or_else(|e| e.into_inner());
+    for inner in live.iter().filter_map(Weak::upgrade) {
+        ShamirContext { inner }.scrub();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detected_backend_joins_like_the_tables() {
+        let context=ShamirContext::default();
+        assert_eq!(context.backend(), FieldBackend::detect());
+        assert_eq!(context.backend()==FieldBackend::Neon, cfg!(target_arch = "aarch64") && context.cpu_features().neon);
+
+        let secret:Vec<u8>=(0..=255).cycle().take(1000).collect();
+        let parts=context.split(5, 3, secret.clone()).unwrap();
+        let three:Parts=parts.into_iter().skip(1).take(3).collect();
+        assert_eq!(context.join(three.clone()).unwrap(), secret);
+        assert_eq!(ShamirSS::join(three).unwrap(), secret);
+    }
+}
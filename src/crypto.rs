@@ -1,169 +1,503 @@
+// Split and join are panic-free as in `pure.rs`: the lints below reject
+// indexing, unwrap and panic! anywhere in this file.
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)]
 
 
-use std::{collections::{HashSet,BTreeMap}, fmt::Debug};
+
+use std::{collections::{HashSet,BTreeMap}, fmt::Debug, ops::Range};
+
+use rand::{Rng, RngCore};
+use subtle::ConstantTimeEq;
+
+use crate::bulk;
+use crate::context::FieldBackend;
+use crate::error::ShamirError;
+use crate::sha256::{Sha256, HKDF_MAX_OUTPUT};
+use crate::pure;
+
+// `join` switches from per-byte Lagrange interpolation (O(m^2) divisions
+// per byte) to barycentric weights computed once (O(m^2) multiplications
+// and m divisions in total, then O(m) per byte) when at least this many
+// shares are joined. Results are identical; only speed differs.
+// `examples/bench-interpolation.rs` measures barycentric as faster from
+// k = 2 up, hence the default; `JoinOptions::barycentric_cutoff` overrides
+// it per call.
+pub const BARYCENTRIC_CUTOFF:usize=2;
+
+// Secrets of at most this many bytes (keys, mostly) are split and joined
+// with fixed-size stack arrays instead of per-byte Vecs. Shares are the
+// same either way: the fast path draws from the RNG in the same order and
+// joins with barycentric weights whatever the cutoff. `Limits` and
+// `JoinOptions` can lower it per context or per call, and 0 turns the fast
+// path off; `examples/bench-small-secrets.rs` compares the two paths.
+pub const SMALL_SECRET_MAX:usize=32;
+
+// Shares keyed by their x-coordinate (1..=255).
+pub type Parts=BTreeMap<i32,Vec<u8>>;
+pub type Share=(i32,Vec<u8>);
 
 #[derive(Debug,Clone)]
 pub struct ShamirSS;
 
 impl ShamirSS{
     
-    pub fn split(n:i32,k:i32,secret:Vec<u8>)->Result<BTreeMap<i32,Vec<u8>>,String>{
-       
-        if !(k > 1) {
-            return Err("Not k > 1".to_string())
+    pub fn split(n:i32,k:i32,secret:Vec<u8>)->Result<BTreeMap<i32,Vec<u8>>,ShamirError>{
+        Self::split_with_rng(n, k, secret, &mut rand::thread_rng())
+    }
+
+    // Same as `split`, drawing coefficients from the given RNG (e.g. a seeded
+    // ChaCha20Rng for reproducible statistics runs).
+    pub fn split_with_rng<R:RngCore+?Sized>(n:i32,k:i32,secret:Vec<u8>,rng:&mut R)->Result<BTreeMap<i32,Vec<u8>>,ShamirError>{
+        Self::split_tuned(n, k, secret, rng, SMALL_SECRET_MAX)
+    }
+
+    pub(crate) fn split_tuned<R:RngCore+?Sized>(n:i32,k:i32,secret:Vec<u8>,rng:&mut R,small_secret_limit:usize)->Result<Parts,ShamirError>{
+
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
          }
-         if !(n >= k) {
-            return Err("Not n >= k".to_string())
+         if n < k {
+            return Err(ShamirError::ThresholdAboveShares)
          }
-         if !(n <= 255){
-            return Err("Not n <= 255".to_string())
+         if n > 255 {
+            return Err(ShamirError::TooManyShares)
          } 
 
-       let seclen=secret.len();
-       let mut values:Vec<Vec<u8>>=vec![vec![0u8;seclen];n as usize];
+       let xs:Vec<u8>=(1..=n).map(|x| x as u8).collect();
+       Self::split_at_tuned(&xs, k, secret, rng, small_secret_limit)
+    }
+
+    // Issues one share per given x-coordinate instead of 1..=n. Any of the
+    // 255 non-zero coordinates may be used, in any order; x = 0 would be the
+    // secret itself and is rejected.
+    pub fn split_at(xs:&[u8],k:i32,secret:Vec<u8>)->Result<BTreeMap<i32,Vec<u8>>,ShamirError>{
+        Self::split_at_with_rng(xs, k, secret, &mut rand::thread_rng())
+    }
+
+    pub fn split_at_with_rng<R:RngCore+?Sized>(xs:&[u8],k:i32,secret:Vec<u8>,rng:&mut R)->Result<BTreeMap<i32,Vec<u8>>,ShamirError>{
+        Self::split_at_tuned(xs, k, secret, rng, SMALL_SECRET_MAX)
+    }
+
+    fn split_at_tuned<R:RngCore+?Sized>(xs:&[u8],k:i32,secret:Vec<u8>,rng:&mut R,small_secret_limit:usize)->Result<Parts,ShamirError>{
+
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        if (xs.len() as i32) < k {
+            return Err(ShamirError::ThresholdAboveShares)
+        }
+        let mut seen = HashSet::new();
+        for &x in xs {
+            if x == 0 {
+                return Err(ShamirError::InvalidIndex(0))
+            }
+            if !seen.insert(x) {
+                return Err(ShamirError::DuplicateIndex(x as i32))
+            }
+        }
+        if secret.is_empty() {
+            return Err(ShamirError::EmptySecret)
+        }
+        if secret.len() <= small_secret_limit.min(SMALL_SECRET_MAX) {
+            return Self::split_small(xs, k as usize, &secret, rng)
+        }
+
+       let mut values:Vec<Vec<u8>>=vec![Vec::with_capacity(secret.len());xs.len()];
        let degree=k-1;
-       for i in 0..=secret.len()-1{
-        let p = GFC256::generate(degree, secret[i]);
-        for x in  1..=n
+       for &byte in &secret {
+        let p = GFC256::generate(degree, byte, rng);
+        for (row, &x) in values.iter_mut().zip(xs)
            {
-              let index=x-1;              
-              let element =GFC256::eval(p.clone(), x as u8);
-              values[index as usize][i]=element;
-              
+              row.push(GFC256::eval(p.clone(), x));
            }
 
        }
-       let mut parts:BTreeMap<i32,Vec<u8>>=BTreeMap::new();
-       for i in 0..=n-1
-            {
-                let mut tmp = vec![0u8;secret.len()];
-
-                for ii in 0..=secret.len()-1
-                {
-                    tmp[ii] = values[i as usize][ii as usize];
+       Ok(xs.iter().zip(values).map(|(&x, row)| (x as i32, row)).collect())
+    }
+
+    pub fn join(parts:BTreeMap<i32,Vec<u8>>)->Result<Vec<u8>,ShamirError>{
+        
+        if parts.is_empty() { return Err(ShamirError::NoParts);}
+        if let Some(index) = parts.keys().find(|&&x| !(1..=255).contains(&x)) {
+            return Err(ShamirError::InvalidIndex(*index));
+        }
+        let pairs:Vec<(u8,&[u8])>=parts.iter().map(|(x, y)| (*x as u8, y.as_slice())).collect();
+        Self::join_pairs(&pairs)
+    }
+
+    // Map-free variants for FFI/WASM/embedded callers: shares as plain
+    // (x-coordinate, payload) pairs, in any order.
+    pub fn split_to_vec(n:i32,k:i32,secret:&[u8])->Result<Vec<(u8,Vec<u8>)>,ShamirError>{
+        let parts=Self::split(n, k, secret.to_vec())?;
+        Ok(parts.into_iter().map(|(x, y)| (x as u8, y)).collect())
+    }
+
+    pub fn join_pairs(pairs:&[(u8,&[u8])])->Result<Vec<u8>,ShamirError>{
+        Self::join_pairs_tuned(pairs, BARYCENTRIC_CUTOFF, SMALL_SECRET_MAX, FieldBackend::Table)
+    }
+
+    pub(crate) fn join_pairs_tuned(pairs:&[(u8,&[u8])],barycentric_cutoff:usize,small_secret_limit:usize,backend:FieldBackend)->Result<Vec<u8>,ShamirError>{
+
+        if pairs.is_empty() { return Err(ShamirError::NoParts);}
+        let mut seen = HashSet::new();
+        for (x, _) in pairs {
+            if *x == 0 {
+                return Err(ShamirError::InvalidIndex(0));
+            }
+            if !seen.insert(*x) {
+                return Err(ShamirError::DuplicateIndex(*x as i32));
+            }
+        }
+        let mut h = HashSet::new();
+        for (_, value) in pairs{
+            h.insert(value.len());
+        }
+        if h.len()!=1 {return Err(ShamirError::InconsistentLengths);}
+        if h.contains(&0) {return Err(ShamirError::EmptySecret);}
+        let Some(&len) = h.iter().next() else { return Err(ShamirError::NoParts) };
+        if len <= small_secret_limit.min(SMALL_SECRET_MAX) {
+            return Self::join_small(pairs, len)
+        }
+        let mut secret = vec![0u8;len];
+
+        if pairs.len() >= barycentric_cutoff {
+            let xs:Vec<u8>=pairs.iter().map(|(x, _)| *x).collect();
+            let weights=GFC256::barycentric_weights(&xs);
+            for ((_, y), &w) in pairs.iter().zip(&weights) {
+                bulk::mul_acc(backend, &mut secret, y, w);
+            }
+            return Ok(secret);
+        }
+
+
+        for (i, byte) in secret.iter_mut().enumerate() {
+            let points:Vec<(u8,u8)>=pairs.iter().map(|&(x, y)| (x, at(y, i))).collect();
+            *byte = GFC256::interpolate(points);
+        }
+
+       Ok(secret)
+    }
+
+    // Stack-only split for already validated arguments. At most 255 shares
+    // and coefficients, as the x-coordinates are distinct non-zero bytes;
+    // the parts of both arrays that were used are wiped before returning.
+    fn split_small<R:RngCore+?Sized>(xs:&[u8],k:usize,secret:&[u8],rng:&mut R)->Result<Parts,ShamirError>{
+        let mut values=[[0u8;SMALL_SECRET_MAX];255];
+        let mut coefficients=[0u8;255];
+        let p=coefficients.get_mut(..k).ok_or(ShamirError::TooManyShares)?;
+        for (i, &byte) in secret.iter().enumerate() {
+            GFC256::generate_into(p, byte, rng);
+            for (row, &x) in values.iter_mut().zip(xs) {
+                if let Some(slot)=row.get_mut(i) {
+                    *slot=GFC256::eval_slice(p, x);
                 }
-                parts.insert(i + 1, tmp);
             }
+        }
+        let parts=xs.iter().zip(&values).map(|(&x, row)| (x as i32, row.iter().take(secret.len()).copied().collect())).collect();
+        zeroize(p);
+        for row in values.iter_mut().take(xs.len()) {
+            zeroize(row);
+        }
+        Ok(parts)
+    }
 
+    // Stack-only join for already validated pairs of `len` bytes: barycentric
+    // weights at x = 0 (see `GFC256::barycentric_weights`), then one pass per
+    // share over the fixed-size secret.
+    fn join_small(pairs:&[(u8,&[u8])],len:usize)->Result<Vec<u8>,ShamirError>{
+        let mut xs=[0u8;255];
+        for (slot, &(x, _)) in xs.iter_mut().zip(pairs) {
+            *slot=x;
+        }
+        let xs=xs.get(..pairs.len()).ok_or(ShamirError::TooManyShares)?;
+        let l=xs.iter().fold(1u8, |acc, &x| GFC256::mul(acc, x));
+        let mut secret=[0u8;SMALL_SECRET_MAX];
+        for (j, &(xj, y)) in pairs.iter().enumerate() {
+            let d=xs.iter().enumerate().filter(|(m, _)| *m!=j).fold(xj, |acc, (_, &xm)| GFC256::mul(acc, GFC256::sub(xj, xm)));
+            let w=GFC256::div(l, d);
+            for (byte, &yb) in secret.iter_mut().zip(y) {
+                *byte=GFC256::add(*byte, GFC256::mul(w, yb));
+            }
+        }
+        let out=secret.iter().take(len).copied().collect();
+        zeroize(&mut secret);
+        Ok(out)
+    }
 
-       Ok(parts)
+    // Value of the polynomial through `parts` at any x, byte by byte. At x = 0
+    // this is the secret; at an unused x it is a fresh, valid share.
+    pub fn evaluate_at(parts:&BTreeMap<i32,Vec<u8>>,x:u8)->Result<Vec<u8>,ShamirError>{
+
+        if parts.is_empty() { return Err(ShamirError::NoParts);}
+        if let Some(index) = parts.keys().find(|&&x| !(1..=255).contains(&x)) {
+            return Err(ShamirError::InvalidIndex(*index));
+        }
+        let len=parts.values().next().map(|v| v.len()).unwrap_or(0);
+        if parts.values().any(|v| v.len()!=len) {
+            return Err(ShamirError::InconsistentLengths);
+        }
+        let xs:Vec<u8>=parts.keys().map(|&i| i as u8).collect();
+        let weights=GFC256::lagrange_coefficients(&xs, x);
+        Ok((0..len).map(|pos| {
+            parts.values().zip(&weights).fold(0u8, |acc, (y, &w)| GFC256::add(acc, GFC256::mul(w, at(y, pos))))
+        }).collect())
     }
 
-    pub fn join(parts:BTreeMap<i32,Vec<u8>>)->Result<Vec<u8>,String>{
-        let p=parts.clone();
-        
-        if !(parts.len() > 0) { return Err("No parts provided".to_string());}
-        let mut h = HashSet::new();
-        for value in p.values(){
-            let l=value.clone().len();
-            if l!=0 {
-                h.insert(l);
-            }        
-    
+    // HAZMAT: returns the dealer's whole polynomial, not just the secret.
+    // For each byte position, the coefficients c_0..c_d of the polynomial
+    // through `parts`, lowest degree first (c_0 is the secret byte), all
+    // vectors as long as the highest degree found plus one. With at least k
+    // consistent shares this is exactly what the dealer drew, so a
+    // deterministic split can be audited against its claimed seed; it also
+    // lets the holder mint shares at any x, which the secret alone does not.
+    // With fewer than k shares the result is some polynomial through them,
+    // not the dealer's, and nothing here can tell the difference.
+    pub fn recover_polynomial(parts:&BTreeMap<i32,Vec<u8>>)->Result<Vec<Vec<u8>>,ShamirError>{
+
+        if parts.is_empty() { return Err(ShamirError::NoParts);}
+        if let Some(index) = parts.keys().find(|&&x| !(1..=255).contains(&x)) {
+            return Err(ShamirError::InvalidIndex(*index));
         }
-        if h.len()!=1 {return Err("Varying lengths of part values".to_string());}
-        let len = h.iter().next().unwrap();   
-        let partslen=parts.len();
-        let mut secret = vec![0u8;len.clone()];
+        let len=parts.values().next().map(|v| v.len()).unwrap_or(0);
+        if parts.values().any(|v| v.len()!=len) {
+            return Err(ShamirError::InconsistentLengths);
+        }
+        let xs:Vec<u8>=parts.keys().map(|&i| i as u8).collect();
+        let basis=GFC256::lagrange_basis(&xs);
+        let mut coefficients:Vec<Vec<u8>>=(0..len).map(|pos| {
+            (0..xs.len()).map(|power| {
+                parts.values().zip(&basis).fold(0u8, |acc, (y, l)| GFC256::add(acc, GFC256::mul(at(l, power), at(y, pos))))
+            }).collect()
+        }).collect();
+        let degree=coefficients.iter().map(|p| GFC256::degree(p.clone())).max().unwrap_or(0) as usize;
+        for p in coefficients.iter_mut() {
+            p.truncate(degree+1);
+        }
+        Ok(coefficients)
+    }
 
+    // HAZMAT, expert mode: split with caller-provided coefficients instead of
+    // the RNG, e.g. coefficients a committee generated jointly, or to use this
+    // crate as the evaluation engine of a research protocol. `coefficients`
+    // has one row per secret byte, each with the k-1 coefficients c_1..c_{k-1}
+    // (lowest degree first, as `recover_polynomial` returns them minus c_0).
+    // Only the dimensions are checked: the shares are exactly as secret as
+    // the coefficients are unpredictable, and a zero c_{k-1} silently lowers
+    // the threshold for that byte.
+    pub fn split_with_coefficients(xs:&[u8],k:i32,secret:&[u8],coefficients:&[Vec<u8>])->Result<Parts,ShamirError>{
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        if coefficients.len()!=secret.len() {
+            return Err(ShamirError::BufferSize { expected: secret.len(), actual: coefficients.len() })
+        }
+        let degree=(k-1) as usize;
+        if let Some(row)=coefficients.iter().find(|row| row.len()!=degree) {
+            return Err(ShamirError::BufferSize { expected: degree, actual: row.len() })
+        }
+        let mut flat:Vec<u8>=coefficients.concat();
+        let parts=Self::split_flat(xs, k, secret, &flat);
+        zeroize(&mut flat);
+        parts
+    }
 
-        for i in 0..=secret.len()-1 {
-            let mut points = vec![vec![0u8;2];partslen as usize];
-            let mut j=0;
+    // Same, with the coefficients read from a byte stream such as an XOF:
+    // `fill` is asked once for secret.len() * (k-1) bytes, consumed row by
+    // row in the order `split_with_coefficients` takes them. No byte is
+    // rejected or redrawn, so a given stream always gives the same shares.
+    pub fn split_with_coefficient_stream<F:FnMut(&mut [u8])>(xs:&[u8],k:i32,secret:&[u8],mut fill:F)->Result<Parts,ShamirError>{
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        // Checked before the coefficient buffer is sized from k, so a huge k
+        // fails here instead of in the allocator. Distinct non-zero xs cap k
+        // at 255.
+        if k as usize>xs.len() {
+            return Err(ShamirError::ThresholdAboveShares)
+        }
+        if secret.is_empty() {
+            return Err(ShamirError::EmptySecret)
+        }
+        pure::check_xs(xs)?;
+        let len=secret.len().checked_mul((k-1) as usize).ok_or(ShamirError::BufferSize { expected: usize::MAX, actual: 0 })?;
+        let mut flat=vec![0u8;len];
+        fill(&mut flat);
+        let parts=Self::split_flat(xs, k, secret, &flat);
+        zeroize(&mut flat);
+        parts
+    }
 
+    // `split_with_coefficients` over one flat buffer of secret.len() * (k-1)
+    // coefficients, for callers that keep it in memory of their own, such as
+    // hsm.rs in a LockedBuffer.
+    pub(crate) fn split_flat(xs:&[u8],k:i32,secret:&[u8],coefficients:&[u8])->Result<Parts,ShamirError>{
+        let mut shares=vec![0u8;secret.len().saturating_mul(xs.len())];
+        pure::split_with_coefficients(secret, k as usize, coefficients, xs, &mut shares)?;
+        let parts=xs.iter().zip(shares.chunks_exact(secret.len().max(1))).map(|(&x, row)| (x as i32, row.to_vec())).collect();
+        zeroize(&mut shares);
+        Ok(parts)
+    }
 
-            let mut iter = parts.iter();
+    // `count` extra shares of the same polynomial at the highest unused
+    // x-coordinates (254 down; 255 stays free for check values), for a dealer
+    // to keep sealed and later issue in place of a lost share without a
+    // refresh. Any k of `parts` are enough to compute them.
+    pub fn spare_shares(parts:&BTreeMap<i32,Vec<u8>>,count:usize)->Result<BTreeMap<i32,Vec<u8>>,ShamirError>{
+        let free:Vec<u8>=(1..=254u8).rev().filter(|x| !parts.contains_key(&(*x as i32))).take(count).collect();
+        if free.len()<count {
+            return Err(ShamirError::TooManyShares)
+        }
+        free.into_iter().map(|x| Ok((x as i32, Self::evaluate_at(parts, x)?))).collect()
+    }
+
+    // Reconstructs only secret[range]; interpolation is per byte, so the rest
+    // of the secret is never materialised.
+    pub fn join_range(parts:&BTreeMap<i32,Vec<u8>>,range:Range<usize>)->Result<Vec<u8>,ShamirError>{
 
-            while let Some(item) = iter.next() {
-                //println!("{}", item.0);
-                points[j][0]=*item.0 as u8;
-                points[j][1]=item.1[i];
+        if parts.is_empty() { return Err(ShamirError::NoParts);}
+        if let Some(index) = parts.keys().find(|&&x| !(1..=255).contains(&x)) {
+            return Err(ShamirError::InvalidIndex(*index));
+        }
+        let len=parts.values().next().map(|v| v.len()).unwrap_or(0);
+        if parts.values().any(|v| v.len()!=len) {
+            return Err(ShamirError::InconsistentLengths);
+        }
+        if range.start>=range.end || range.end>len {
+            return Err(ShamirError::RangeOutOfBounds { start: range.start, end: range.end, len });
+        }
+        let pairs:Vec<(u8,&[u8])>=parts.iter().map(|(x, y)| (*x as u8, y.get(range.clone()).unwrap_or_default())).collect();
+        Self::join_pairs(&pairs)
+    }
 
-                j=j+1;
+    // Splits as usual and derives, for each custodian, a 32-byte wrapping key
+    // from their own share and the ceremony `context`. The dealer can use it to
+    // encrypt per-custodian material; the custodian re-derives it with
+    // `derived_key` from the share alone. It reveals nothing about the secret
+    // beyond what the share itself does.
+    pub fn split_with_derived_keys(n:i32,k:i32,secret:Vec<u8>,context:&[u8])->Result<Vec<(Share,[u8;32])>,ShamirError>{
+        let parts=Self::split(n, k, secret)?;
+        Ok(parts.into_iter().map(|(index, data)| {
+            let key=Self::derived_key(index, &data, context);
+            ((index, data), key)
+        }).collect())
+    }
 
-            }
+    pub fn derived_key(index:i32,share:&[u8],context:&[u8])->[u8;32]{
+        let mut info=b"shamir-custodian-key".to_vec();
+        info.extend_from_slice(&index.to_be_bytes());
+        Sha256::hkdf_key(context, share, &info)
+    }
+
+    // Reconstructs and compares against `expected` without branching on the
+    // secret bytes, for unseal-style checks that only need a yes/no.
+    pub fn join_and_verify(parts:BTreeMap<i32,Vec<u8>>,expected:&[u8])->Result<bool,ShamirError>{
+        let secret=Self::join(parts)?;
+        Ok(constant_time_eq(&secret, expected))
+    }
 
-            
-            secret[i] = GFC256::interpolate(points);
+    // For callers that only ever need derived keys: reconstructs internally,
+    // returns HKDF(salt = "", ikm = secret, info) of `out_len` bytes and wipes
+    // the secret before returning, so it never leaves this function.
+    pub fn join_then_hkdf(parts:BTreeMap<i32,Vec<u8>>,info:&[u8],out_len:usize)->Result<Vec<u8>,ShamirError>{
+        if out_len>HKDF_MAX_OUTPUT {
+            return Err(ShamirError::KdfOutputTooLong { requested: out_len, max: HKDF_MAX_OUTPUT })
         }
+        let mut secret=Self::join(parts)?;
+        let key=Sha256::hkdf(&[], &secret, info, out_len);
+        zeroize(&mut secret);
+        key
+    }
+}
 
-       Ok(secret.clone())
+// Byte `i` of a share, 0 past its end. Lengths are checked before any
+// interpolation, so the fallback is never taken; it keeps the core free of
+// indexing.
+fn at(y:&[u8],i:usize)->u8{
+    y.get(i).copied().unwrap_or(0)
+}
+
+// Overwrites a buffer with zeros in a way the optimiser will not elide.
+pub fn zeroize(buf:&mut [u8]){
+    for byte in buf.iter_mut() {
+        // SAFETY: `byte` is a valid, aligned, exclusive reference.
+        unsafe { std::ptr::write_volatile(byte, 0) };
     }
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+}
+
+// Length is treated as public; contents are compared with `subtle`, without
+// early exit or a data-dependent branch.
+pub fn constant_time_eq(a:&[u8],b:&[u8])->bool{
+    a.ct_eq(b).into()
 }
-struct GFC256;
+pub(crate) struct GFC256;
 
 impl GFC256 {
-    fn add(a:u8, b:u8)-> u8{
+    pub(crate) fn add(a:u8, b:u8)-> u8{
         a ^ b
     }
     fn sub(a:u8, b:u8)-> u8{
         Self::add(a,b)
     }
-    fn mul(a:u8, b:u8)-> u8{
-        
-        if a==0 || b==0 { return 0 }
-
-        let exp=LOG[a as usize] as i32 + LOG[b as usize] as i32;
-        
-        EXP[exp as usize]
+    pub(crate) fn mul(a:u8, b:u8)-> u8{
+        pure::gf_mul(a, b)
     }
-    fn div(a:u8, b:u8)-> u8{
-   
-        Self::mul(a, EXP[255 - LOG[b as usize] as usize])
-
+    pub(crate) fn div(a:u8, b:u8)-> u8{
+        pure::gf_div(a, b)
     }
 
-    fn eval(p:Vec<u8>, x:u8)-> u8{
-        let mut result:u8=0u8;
-
+    pub(crate) fn eval(p:Vec<u8>, x:u8)-> u8{
         // Horner's method
-        for i in (0..=p.len()-1).rev(){
-            let val=p[i];
-            result=Self::add(Self::mul(result, x), val);
-        }
-
-        result
+        Self::eval_slice(&p, x)
+    }
+    // Allocation-free counterparts of `eval` and `generate`; `generate_into`
+    // draws from the RNG in exactly the same order as `generate`.
+    pub(crate) fn eval_slice(p:&[u8], x:u8)-> u8{
+        p.iter().rev().fold(0u8, |result, &val| Self::add(Self::mul(result, x), val))
     }
-    fn degree(p:Vec<u8>)->i32{
-        let to=p.len()-1;
 
-        for i in (1..=to).rev(){
-            if p[i] != 0 {
-               return i as i32
+    pub(crate) fn generate_into<R:RngCore+?Sized>(p:&mut [u8], x:u8, rng:&mut R){
+        loop {
+            for c in p.iter_mut() {
+                *c = rng.gen::<u8>();
             }
-
+            if p.last().is_none_or(|&c| c != 0) {
+                break;
+            }
+        }
+        if let Some(c)=p.first_mut() {
+            *c = x;
         }
-        return 0;
+    }
 
-    
+    fn degree(p:Vec<u8>)->i32{
+        p.iter().enumerate().skip(1).rev().find(|(_, &c)| c != 0).map_or(0, |(i, _)| i as i32)
     }
-    fn generate(degree:i32, x:u8)->Vec<u8>{
+    fn generate<R:RngCore+?Sized>(degree:i32, x:u8, rng:&mut R)->Vec<u8>{
 
         let d:i32=degree+1;
         let mut p:Vec<u8>;
 
         loop {
-           p = (0..d).map(|_| rand::random::<u8>()).collect();
+           p = (0..d).map(|_| rng.gen::<u8>()).collect();
            let dg=Self::degree(p.clone());
            if dg == degree {
               break;
            }
 
         }
-        p[0] = x;
+        if let Some(c)=p.first_mut() {
+            *c = x;
+        }
 
         p
     }
-    fn interpolate(points:Vec<Vec<u8>>)-> u8 {
+    fn interpolate(points:Vec<(u8,u8)>)-> u8 {
         let x:u8=0;
         let mut y:u8=0;
-        let len=points.len()-1;
-        for i in 0..=len{
-            let ax:u8=points[i][0];
-            let ay:u8=points[i][1];
+        for (i, &(ax, ay)) in points.iter().enumerate(){
             let mut li:u8=1;
-            for j in 0..=len{
-                let bx = points[j][0];
+            for (j, &(bx, _)) in points.iter().enumerate(){
                 if i!=j{
                      li = Self::mul(li, Self::div(Self::sub(x, bx),Self::sub(ax, bx) ));
 
@@ -177,122 +511,131 @@ impl GFC256 {
         y
     }
 
+    // Lagrange basis values l_i(x) for the given x-coordinates, so the
+    // interpolating polynomial can be evaluated at x as sum(l_i * y_i) for any
+    // number of byte positions.
+    pub(crate) fn lagrange_coefficients(xs:&[u8], x:u8)->Vec<u8>{
+        xs.iter().enumerate().map(|(i, &ax)| {
+            let mut li:u8=1;
+            for (j, &bx) in xs.iter().enumerate() {
+                if i!=j {
+                    li = Self::mul(li, Self::div(Self::sub(x, bx), Self::sub(ax, bx)));
+                }
+            }
+            li
+        }).collect()
+    }
+
+    // The Lagrange basis polynomials for the given x-coordinates in
+    // coefficient form, lowest degree first: l_i = M(x) / (x - x_i) scaled by
+    // 1 / prod_{j != i}(x_i - x_j), with M(x) = prod(x - x_j).
+    pub(crate) fn lagrange_basis(xs:&[u8])->Vec<Vec<u8>>{
+        let mut master=vec![1u8];
+        for &x in xs {
+            // (x - x_j) * M: M shifted up one degree, plus x_j * M.
+            let mut next:Vec<u8>=std::iter::once(0).chain(master.iter().copied()).collect();
+            for (slot, &c) in next.iter_mut().zip(&master) {
+                *slot=Self::add(*slot, Self::mul(c, x));
+            }
+            master=next;
+        }
+        xs.iter().enumerate().map(|(i, &xi)| {
+            // Synthetic division of M by (x - x_i), highest degree first.
+            let mut quotient=vec![0u8;xs.len()];
+            let mut carry=0u8;
+            for (slot, &c) in quotient.iter_mut().rev().zip(master.iter().skip(1).rev()) {
+                carry=Self::add(c, Self::mul(carry, xi));
+                *slot=carry;
+            }
+            let scale=xs.iter().enumerate().filter(|(j, _)| *j!=i).fold(1u8, |acc, (_, &xj)| Self::mul(acc, Self::sub(xi, xj)));
+            quotient.iter().map(|&c| Self::div(c, scale)).collect()
+        }).collect()
+    }
+
+    // Barycentric form of the weights at x = 0: with L = prod(x_m), each
+    // weight is L / (x_j * prod_{m != j}(x_j - x_m)), one division per share.
+    pub(crate) fn barycentric_weights(xs:&[u8])->Vec<u8>{
+        let l=xs.iter().fold(1u8, |acc, &x| Self::mul(acc, x));
+        xs.iter().enumerate().map(|(j, &xj)| {
+            let d=xs.iter().enumerate().filter(|(m, _)| *m!=j).fold(xj, |acc, (_, &xm)| Self::mul(acc, Self::sub(xj, xm)));
+            Self::div(l, d)
+        }).collect()
+    }
+
 
 }
 
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::indexing_slicing)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn constant_time_eq_compares_contents_and_length() {
+        assert!(constant_time_eq(b"", b""));
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret!"));
+        assert!(!constant_time_eq(b"", b"\0"));
+    }
 
+    #[test]
+    fn join_and_verify_matches_only_the_secret() {
+        let parts=ShamirSS::split(3, 2, b"unseal".to_vec()).unwrap();
+        assert!(ShamirSS::join_and_verify(parts.clone(), b"unseal").unwrap());
+        assert!(!ShamirSS::join_and_verify(parts, b"unsea1").unwrap());
+    }
 
-static  LOG:[u8;256]=[
- 0xff,  0x00,  0x19,  0x01,  0x32,  0x02,  0x1a,
- 0xc6,  0x4b,  0xc7,  0x1b,  0x68,  0x33,  0xee,
- 0xdf,  0x03,  0x64,  0x04,  0xe0,  0x0e,  0x34,
- 0x8d,  0x81,  0xef,  0x4c,  0x71,  0x08,  0xc8,
- 0xf8,  0x69,  0x1c,  0xc1,  0x7d,  0xc2,  0x1d,
- 0xb5,  0xf9,  0xb9,  0x27,  0x6a,  0x4d,  0xe4,
- 0xa6,  0x72,  0x9a,  0xc9,  0x09,  0x78,  0x65,
- 0x2f,  0x8a,  0x05,  0x21,  0x0f,  0xe1,  0x24,
- 0x12,  0xf0,  0x82,  0x45,  0x35,  0x93,  0xda,
- 0x8e,  0x96,  0x8f,  0xdb,  0xbd,  0x36,  0xd0,
- 0xce,  0x94,  0x13,  0x5c,  0xd2,  0xf1,  0x40,
- 0x46,  0x83,  0x38,  0x66,  0xdd,  0xfd,  0x30,
- 0xbf,  0x06,  0x8b,  0x62,  0xb3,  0x25,  0xe2,
- 0x98,  0x22,  0x88,  0x91,  0x10,  0x7e,  0x6e,
- 0x48,  0xc3,  0xa3,  0xb6,  0x1e,  0x42,  0x3a,
- 0x6b,  0x28,  0x54,  0xfa,  0x85,  0x3d,  0xba,
- 0x2b,  0x79,  0x0a,  0x15,  0x9b,  0x9f,  0x5e,
- 0xca,  0x4e,  0xd4,  0xac,  0xe5,  0xf3,  0x73,
- 0xa7,  0x57,  0xaf,  0x58,  0xa8,  0x50,  0xf4,
- 0xea,  0xd6,  0x74,  0x4f,  0xae,  0xe9,  0xd5,
- 0xe7,  0xe6,  0xad,  0xe8,  0x2c,  0xd7,  0x75,
- 0x7a,  0xeb,  0x16,  0x0b,  0xf5,  0x59,  0xcb,
- 0x5f,  0xb0,  0x9c,  0xa9,  0x51,  0xa0,  0x7f,
- 0x0c,  0xf6,  0x6f,  0x17,  0xc4,  0x49,  0xec,
- 0xd8,  0x43,  0x1f,  0x2d,  0xa4,  0x76,  0x7b,
- 0xb7,  0xcc,  0xbb,  0x3e,  0x5a,  0xfb,  0x60,
- 0xb1,  0x86,  0x3b,  0x52,  0xa1,  0x6c,  0xaa,
- 0x55,  0x29,  0x9d,  0x97,  0xb2,  0x87,  0x90,
- 0x61,  0xbe,  0xdc,  0xfc,  0xbc,  0x95,  0xcf,
- 0xcd,  0x37,  0x3f,  0x5b,  0xd1,  0x53,  0x39,
- 0x84,  0x3c,  0x41,  0xa2,  0x6d,  0x47,  0x14,
- 0x2a,  0x9e,  0x5d,  0x56,  0xf2,  0xd3,  0xab,
- 0x44,  0x11,  0x92,  0xd9,  0x23,  0x20,  0x2e,
- 0x89,  0xb4,  0x7c,  0xb8,  0x26,  0x77,  0x99,
- 0xe3,  0xa5,  0x67,  0x4a,  0xed,  0xde,  0xc5,
- 0x31,  0xfe,  0x18,  0x0d,  0x63,  0x8c,  0x80,
- 0xc0,  0xf7,  0x70,  0x07];
-
- static EXP:[u8;510]=[
-     0x01,  0x03,  0x05,  0x0f,  0x11,  0x33,  0x55,
-     0xff,  0x1a,  0x2e,  0x72,  0x96,  0xa1,  0xf8,
-     0x13,  0x35,  0x5f,  0xe1,  0x38,  0x48,  0xd8,
-     0x73,  0x95,  0xa4,  0xf7,  0x02,  0x06,  0x0a,
-     0x1e,  0x22,  0x66,  0xaa,  0xe5,  0x34,  0x5c,
-     0xe4,  0x37,  0x59,  0xeb,  0x26,  0x6a,  0xbe,
-     0xd9,  0x70,  0x90,  0xab,  0xe6,  0x31,  0x53,
-     0xf5,  0x04,  0x0c,  0x14,  0x3c,  0x44,  0xcc,
-     0x4f,  0xd1,  0x68,  0xb8,  0xd3,  0x6e,  0xb2,
-     0xcd,  0x4c,  0xd4,  0x67,  0xa9,  0xe0,  0x3b,
-     0x4d,  0xd7,  0x62,  0xa6,  0xf1,  0x08,  0x18,
-     0x28,  0x78,  0x88,  0x83,  0x9e,  0xb9,  0xd0,
-     0x6b,  0xbd,  0xdc,  0x7f,  0x81,  0x98,  0xb3,
-     0xce,  0x49,  0xdb,  0x76,  0x9a,  0xb5,  0xc4,
-     0x57,  0xf9,  0x10,  0x30,  0x50,  0xf0,  0x0b,
-     0x1d,  0x27,  0x69,  0xbb,  0xd6,  0x61,  0xa3,
-     0xfe,  0x19,  0x2b,  0x7d,  0x87,  0x92,  0xad,
-     0xec,  0x2f,  0x71,  0x93,  0xae,  0xe9,  0x20,
-     0x60,  0xa0,  0xfb,  0x16,  0x3a,  0x4e,  0xd2,
-     0x6d,  0xb7,  0xc2,  0x5d,  0xe7,  0x32,  0x56,
-     0xfa,  0x15,  0x3f,  0x41,  0xc3,  0x5e,  0xe2,
-     0x3d,  0x47,  0xc9,  0x40,  0xc0,  0x5b,  0xed,
-     0x2c,  0x74,  0x9c,  0xbf,  0xda,  0x75,  0x9f,
-     0xba,  0xd5,  0x64,  0xac,  0xef,  0x2a,  0x7e,
-     0x82,  0x9d,  0xbc,  0xdf,  0x7a,  0x8e,  0x89,
-     0x80,  0x9b,  0xb6,  0xc1,  0x58,  0xe8,  0x23,
-     0x65,  0xaf,  0xea,  0x25,  0x6f,  0xb1,  0xc8,
-     0x43,  0xc5,  0x54,  0xfc,  0x1f,  0x21,  0x63,
-     0xa5,  0xf4,  0x07,  0x09,  0x1b,  0x2d,  0x77,
-     0x99,  0xb0,  0xcb,  0x46,  0xca,  0x45,  0xcf,
-     0x4a,  0xde,  0x79,  0x8b,  0x86,  0x91,  0xa8,
-     0xe3,  0x3e,  0x42,  0xc6,  0x51,  0xf3,  0x0e,
-     0x12,  0x36,  0x5a,  0xee,  0x29,  0x7b,  0x8d,
-     0x8c,  0x8f,  0x8a,  0x85,  0x94,  0xa7,  0xf2,
-     0x0d,  0x17,  0x39,  0x4b,  0xdd,  0x7c,  0x84,
-     0x97,  0xa2,  0xfd,  0x1c,  0x24,  0x6c,  0xb4,
-     0xc7,  0x52,  0xf6,  0x01,  0x03,  0x05,  0x0f,
-     0x11,  0x33,  0x55,  0xff,  0x1a,  0x2e,  0x72,
-     0x96,  0xa1,  0xf8,  0x13,  0x35,  0x5f,  0xe1,
-     0x38,  0x48,  0xd8,  0x73,  0x95,  0xa4,  0xf7,
-     0x02,  0x06,  0x0a,  0x1e,  0x22,  0x66,  0xaa,
-     0xe5,  0x34,  0x5c,  0xe4,  0x37,  0x59,  0xeb,
-     0x26,  0x6a,  0xbe,  0xd9,  0x70,  0x90,  0xab,
-     0xe6,  0x31,  0x53,  0xf5,  0x04,  0x0c,  0x14,
-     0x3c,  0x44,  0xcc,  0x4f,  0xd1,  0x68,  0xb8,
-     0xd3,  0x6e,  0xb2,  0xcd,  0x4c,  0xd4,  0x67,
-     0xa9,  0xe0,  0x3b,  0x4d,  0xd7,  0x62,  0xa6,
-     0xf1,  0x08,  0x18,  0x28,  0x78,  0x88,  0x83,
-     0x9e,  0xb9,  0xd0,  0x6b,  0xbd,  0xdc,  0x7f,
-     0x81,  0x98,  0xb3,  0xce,  0x49,  0xdb,  0x76,
-     0x9a,  0xb5,  0xc4,  0x57,  0xf9,  0x10,  0x30,
-     0x50,  0xf0,  0x0b,  0x1d,  0x27,  0x69,  0xbb,
-     0xd6,  0x61,  0xa3,  0xfe,  0x19,  0x2b,  0x7d,
-     0x87,  0x92,  0xad,  0xec,  0x2f,  0x71,  0x93,
-     0xae,  0xe9,  0x20,  0x60,  0xa0,  0xfb,  0x16,
-     0x3a,  0x4e,  0xd2,  0x6d,  0xb7,  0xc2,  0x5d,
-     0xe7,  0x32,  0x56,  0xfa,  0x15,  0x3f,  0x41,
-     0xc3,  0x5e,  0xe2,  0x3d,  0x47,  0xc9,  0x40,
-     0xc0,  0x5b,  0xed,  0x2c,  0x74,  0x9c,  0xbf,
-     0xda,  0x75,  0x9f,  0xba,  0xd5,  0x64,  0xac,
-     0xef,  0x2a,  0x7e,  0x82,  0x9d,  0xbc,  0xdf,
-     0x7a,  0x8e,  0x89,  0x80,  0x9b,  0xb6,  0xc1,
-     0x58,  0xe8,  0x23,  0x65,  0xaf,  0xea,  0x25,
-     0x6f,  0xb1,  0xc8,  0x43,  0xc5,  0x54,  0xfc,
-     0x1f,  0x21,  0x63,  0xa5,  0xf4,  0x07,  0x09,
-     0x1b,  0x2d,  0x77,  0x99,  0xb0,  0xcb,  0x46,
-     0xca,  0x45,  0xcf,  0x4a,  0xde,  0x79,  0x8b,
-     0x86,  0x91,  0xa8,  0xe3,  0x3e,  0x42,  0xc6,
-     0x51,  0xf3,  0x0e,  0x12,  0x36,  0x5a,  0xee,
-     0x29,  0x7b,  0x8d,  0x8c,  0x8f,  0x8a,  0x85,
-     0x94,  0xa7,  0xf2,  0x0d,  0x17,  0x39,  0x4b,
-     0xdd,  0x7c,  0x84,  0x97,  0xa2,  0xfd,  0x1c,
-     0x24,  0x6c,  0xb4,  0xc7,  0x52,  0xf6
- ];
\ No newline at end of file
+    #[test]
+    fn every_index_from_1_to_255_recovers() {
+        // Short and long secrets take different split paths.
+        for secret in [b"k".to_vec(), vec![0xa5;300]] {
+            let parts=ShamirSS::split(255, 3, secret.clone()).unwrap();
+            assert_eq!(parts.keys().copied().collect::<Vec<_>>(), (1..=255).collect::<Vec<_>>());
+            for x in 1..=253 {
+                let subset:BTreeMap<i32,Vec<u8>>=(x..x+3).map(|i| (i, parts[&i].clone())).collect();
+                assert_eq!(ShamirSS::join(subset).unwrap(), secret, "indices {x}..{}", x+3);
+            }
+            let ends:BTreeMap<i32,Vec<u8>>=[1, 128, 255].iter().map(|i| (*i, parts[i].clone())).collect();
+            assert_eq!(ShamirSS::join(ends).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn split_at_takes_any_nonzero_coordinates_in_any_order() {
+        let xs:Vec<u8>=(1..=255u8).rev().collect();
+        let parts=ShamirSS::split_at(&xs, 2, b"reversed".to_vec()).unwrap();
+        assert_eq!(parts.len(), 255);
+        let pair:BTreeMap<i32,Vec<u8>>=[255, 7].iter().map(|i| (*i, parts[i].clone())).collect();
+        assert_eq!(ShamirSS::join(pair).unwrap(), b"reversed");
+
+        assert!(matches!(ShamirSS::split_at(&[3, 0, 5], 2, b"s".to_vec()), Err(ShamirError::InvalidIndex(0))));
+        assert!(matches!(ShamirSS::split_at(&[3, 9, 3], 2, b"s".to_vec()), Err(ShamirError::DuplicateIndex(3))));
+        assert!(matches!(ShamirSS::split(256, 2, b"s".to_vec()), Err(ShamirError::TooManyShares)));
+    }
+
+    #[test]
+    fn join_rejects_indices_outside_1_to_255() {
+        let parts=ShamirSS::split(3, 2, b"s".to_vec()).unwrap();
+        for bad in [0, 256, -1] {
+            let mut shares=parts.clone();
+            let data=shares.remove(&1).unwrap();
+            shares.insert(bad, data);
+            assert!(matches!(ShamirSS::join(shares), Err(ShamirError::InvalidIndex(x)) if x==bad));
+        }
+        let pairs=[(0u8, &parts[&1][..]), (2, &parts[&2][..])];
+        assert!(matches!(ShamirSS::join_pairs(&pairs), Err(ShamirError::InvalidIndex(0))));
+    }
+
+    #[test]
+    fn coefficient_stream_validates_before_filling() {
+        let filled=std::cell::Cell::new(false);
+        let fill=|_:&mut [u8]| filled.set(true);
+        assert!(matches!(ShamirSS::split_with_coefficient_stream(&[1, 2, 3], i32::MAX, b"s", fill), Err(ShamirError::ThresholdAboveShares)));
+        assert!(matches!(ShamirSS::split_with_coefficient_stream(&[1, 2, 3], 2, b"", fill), Err(ShamirError::EmptySecret)));
+        assert!(matches!(ShamirSS::split_with_coefficient_stream(&[1, 2, 2], 2, b"s", fill), Err(ShamirError::DuplicateIndex(2))));
+        assert!(!filled.get(), "fill called for an invalid split");
+        let parts=ShamirSS::split_with_coefficient_stream(&[1, 2, 3], 2, b"s", |flat| flat.fill(7)).unwrap();
+        assert_eq!(ShamirSS::join(parts).unwrap(), b"s");
+    }
+}
@@ -0,0 +1,368 @@
+use std::fs;
+use std::path::Path;
+
+use base64::{engine::general_purpose, Engine as _};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::crypto::zeroize;
+use crate::hardening::write_private;
+use crate::hybrid::HybridSS;
+use crate::sha256::{from_hex, to_hex, Sha256};
+
+// The dealer's Ed25519 signing identity for transcripts, hints, receipts,
+// recovery instructions and revocation lists. `rotate` makes a new key
+// certified by the current one, so the public keys form a chain that a
+// custodian who pinned an earlier key can follow to the current one.
+//
+// The identity file is text, and only the current secret key is encrypted:
+//
+//   shamir-dealer v2
+//   key <public key hex> <certificate hex> <uid>     one per key, oldest first
+//   retired <fingerprint> <date>                     one per rotation
+//   secret <salt hex> <PBKDF2 iterations> <base64>
+//
+// The secret is the 32-byte signing key under HybridSS::encrypt_with_aad,
+// keyed by PBKDF2-HMAC-SHA256(passphrase, salt), with every line before it
+// as associated data: the chain and the retirements cannot be edited without
+// the passphrase. A key's certificate is a signature by the key before it
+// (the first key signs its own) of CERTIFY_CONTEXT | public key | uid.
+//
+// `DealerKey` is the public half custodians pin: the key lines under
+// `shamir-dealer-key v1`, every certificate checked on parse. A signature is
+// SIGNATURE_MAGIC | public key | Ed25519 signature of SIGN_CONTEXT | data,
+// and verifies against the current key of the pinned export only; older
+// signatures verify against the export pinned before the rotation.
+
+const IDENTITY_HEADER:&str="shamir-dealer v2";
+const KEY_HEADER:&str="shamir-dealer-key v1";
+const SIGNATURE_MAGIC:&[u8;4]=b"SHD1";
+const SIGN_CONTEXT:&[u8]=b"shamir-rust dealer signature\0";
+const CERTIFY_CONTEXT:&[u8]=b"shamir-rust dealer key\0";
+const PUBLIC_LEN:usize=32;
+const SIGNATURE_LEN:usize=64;
+const SALT_LEN:usize=16;
+pub const DEALER_ITERATIONS:u32=200_000;
+pub const MAX_DEALER_ITERATIONS:u32=10*DEALER_ITERATIONS;
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ChainKey{
+    pub public:[u8;PUBLIC_LEN],
+    pub uid:String,
+    certificate:[u8;SIGNATURE_LEN],
+}
+
+impl ChainKey{
+
+    // The first 20 bytes of SHA-256 of the public key, in hex.
+    pub fn fingerprint(&self)->String{
+        fingerprint(&self.public)
+    }
+
+    fn certify(signer:&SigningKey,public:[u8;PUBLIC_LEN],uid:&str)->ChainKey{
+        let certificate=signer.sign(&certified(&public, uid)).to_bytes();
+        ChainKey { public, uid: uid.to_string(), certificate }
+    }
+
+    fn line(&self)->String{
+        format!("key {} {} {}", to_hex(&self.public), to_hex(&self.certificate), self.uid)
+    }
+
+    fn parse(line:&str)->Option<ChainKey>{
+        let mut fields=line.strip_prefix("key ")?.splitn(3, ' ');
+        let public=from_hex(fields.next()?)?.try_into().ok()?;
+        let certificate=from_hex(fields.next()?)?.try_into().ok()?;
+        let uid=fields.next()?.to_string();
+        Some(ChainKey { public, uid, certificate })
+    }
+}
+
+// The public keys of a dealer, oldest first, each certified by the one
+// before it.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct DealerKey{
+    chain:Vec<ChainKey>,
+}
+
+impl DealerKey{
+
+    pub fn parse(text:&str)->Result<DealerKey,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(KEY_HEADER) {
+            return Err("Not a dealer public key".to_string());
+        }
+        Self::from_lines(lines)
+    }
+
+    // Key lines, then a check of every certificate along the chain.
+    fn from_lines<'a>(lines:impl Iterator<Item=&'a str>)->Result<DealerKey,String>{
+        let chain=lines.map(|line| ChainKey::parse(line).ok_or_else(|| format!("Malformed dealer key line: {line}")))
+            .collect::<Result<Vec<_>,_>>()?;
+        let Some(first)=chain.first() else {
+            return Err("Dealer key has no keys".to_string());
+        };
+        let mut signer=first.public;
+        for key in &chain {
+            let verifying=VerifyingKey::from_bytes(&signer).map_err(|_| format!("Dealer key {} is not an Ed25519 key", fingerprint(&signer)))?;
+            verifying.verify(&certified(&key.public, &key.uid), &Signature::from_bytes(&key.certificate))
+                .map_err(|_| format!("Dealer key {} is not certified by {}", key.fingerprint(), fingerprint(&signer)))?;
+            signer=key.public;
+        }
+        Ok(DealerKey { chain })
+    }
+
+    pub fn to_text(&self)->String{
+        let mut text=format!("{KEY_HEADER}\n");
+        for key in &self.chain {
+            text.push_str(&key.line());
+            text.push('\n');
+        }
+        text
+    }
+
+    pub fn chain(&self)->&[ChainKey]{
+        &self.chain
+    }
+
+    pub fn current(&self)->&ChainKey{
+        // The chain is never empty: `from_lines` and `generate` see to it.
+        &self.chain[self.chain.len()-1]
+    }
+
+    // True when `pinned`'s current key is in this chain, so this key was
+    // certified, through rotations, by the key a custodian pinned.
+    pub fn follows(&self,pinned:&DealerKey)->bool{
+        self.chain.iter().any(|key| key.public==pinned.current().public)
+    }
+
+    // Checks a signature by the current key and returns its fingerprint.
+    pub fn verify(&self,data:&[u8],signature:&[u8])->Result<String,String>{
+        let rest=signature.strip_prefix(SIGNATURE_MAGIC.as_slice()).filter(|rest| rest.len()==PUBLIC_LEN+SIGNATURE_LEN)
+            .ok_or("Not a dealer signature")?;
+        let (public, signature)=rest.split_at(PUBLIC_LEN);
+        let current=self.current();
+        if public!=current.public {
+            return Err(format!("Signed by dealer key {}, not the current key {}", fingerprint(public), current.fingerprint()));
+        }
+        let signature=Signature::from_slice(signature).map_err(|_| "Not a dealer signature".to_string())?;
+        VerifyingKey::from_bytes(&current.public)
+            .and_then(|verifying| verifying.verify(&signed(data), &signature))
+            .map_err(|_| format!("Bad signature by dealer key {}", current.fingerprint()))?;
+        Ok(current.fingerprint())
+    }
+}
+
+// True for signatures made by a `DealerIdentity`, as opposed to OpenPGP ones.
+pub fn is_dealer_signature(signature:&[u8])->bool{
+    signature.starts_with(SIGNATURE_MAGIC)
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct DealerIdentity{
+    key:DealerKey,
+    // (fingerprint, date) of every key rotated out.
+    pub retired:Vec<(String,String)>,
+    secret:String,
+}
+
+impl DealerIdentity{
+
+    pub fn generate(uid:&str,passphrase:&str)->Result<DealerIdentity,String>{
+        check_uid(uid)?;
+        let signing=SigningKey::generate(&mut OsRng);
+        let key=DealerKey { chain: vec![ChainKey::certify(&signing, signing.verifying_key().to_bytes(), uid)] };
+        let mut identity=DealerIdentity { key, retired: Vec::new(), secret: String::new() };
+        identity.secret=seal(&signing, &identity.header(), passphrase)?;
+        Ok(identity)
+    }
+
+    pub fn load(path:&Path)->Result<DealerIdentity,String>{
+        let text=fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        Self::parse(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    // The file holds the secret key, encrypted, but is still owner-only.
+    pub fn save(&self,path:&Path)->Result<(),String>{
+        write_private(path, self.to_text().as_bytes()).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn parse(text:&str)->Result<DealerIdentity,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(IDENTITY_HEADER) {
+            return Err("Not a dealer identity".to_string());
+        }
+        let mut keys=Vec::new();
+        let mut retired=Vec::new();
+        let mut secret=None;
+        for line in lines {
+            match line.split_once(' ') {
+                Some(("key", _)) if retired.is_empty() && secret.is_none() => keys.push(line),
+                Some(("retired", rest)) if secret.is_none() => {
+                    let (fingerprint, date)=rest.split_once(' ').unwrap_or((rest, ""));
+                    retired.push((fingerprint.to_string(), date.to_string()));
+                }
+                Some(("secret", rest)) if secret.is_none() => secret=Some(rest.to_string()),
+                _ => return Err(format!("Unexpected line in dealer identity: {line}")),
+            }
+        }
+        let key=DealerKey::from_lines(keys.into_iter())?;
+        let secret=secret.ok_or("Dealer identity has no secret key")?;
+        Ok(DealerIdentity { key, retired, secret })
+    }
+
+    pub fn to_text(&self)->String{
+        format!("{}secret {}\n", self.header(), self.secret)
+    }
+
+    // Every line before the secret: its associated data.
+    fn header(&self)->String{
+        let mut text=format!("{IDENTITY_HEADER}\n");
+        for key in &self.key.chain {
+            text.push_str(&key.line());
+            text.push('\n');
+        }
+        for (fingerprint, date) in &self.retired {
+            text.push_str(format!("retired {fingerprint} {date}").trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    // The public keys to give custodians, who pin them.
+    pub fn public_key(&self)->&DealerKey{
+        &self.key
+    }
+
+    pub fn fingerprint(&self)->String{
+        self.key.current().fingerprint()
+    }
+
+    // Detached signature of `data` by the current key, for `DealerKey::verify`.
+    pub fn sign(&self,data:&[u8],passphrase:&str)->Result<Vec<u8>,String>{
+        let signing=self.unlock(passphrase)?;
+        let mut signature=SIGNATURE_MAGIC.to_vec();
+        signature.extend_from_slice(&self.key.current().public);
+        signature.extend_from_slice(&signing.sign(&signed(data)).to_bytes());
+        Ok(signature)
+    }
+
+    // A new current key, certified by the old one and stored under the same
+    // passphrase. The old key is recorded as retired on `date`; its secret
+    // half is not kept.
+    pub fn rotate(&mut self,uid:Option<&str>,passphrase:&str,date:&str)->Result<(),String>{
+        let uid=uid.unwrap_or(&self.key.current().uid).to_string();
+        check_uid(&uid)?;
+        let previous=self.unlock(passphrase)?;
+        let signing=SigningKey::generate(&mut OsRng);
+        let mut rotated=self.clone();
+        rotated.key.chain.push(ChainKey::certify(&previous, signing.verifying_key().to_bytes(), &uid));
+        rotated.retired.push((self.fingerprint(), date.to_string()));
+        rotated.secret=seal(&signing, &rotated.header(), passphrase)?;
+        *self=rotated;
+        Ok(())
+    }
+
+    fn unlock(&self,passphrase:&str)->Result<SigningKey,String>{
+        let malformed=|| "Malformed dealer secret key".to_string();
+        let fields:Vec<&str>=self.secret.split(' ').collect();
+        let [salt, iterations, sealed]=fields.as_slice() else { return Err(malformed()) };
+        let salt=from_hex(salt).filter(|salt| salt.len()==SALT_LEN).ok_or_else(malformed)?;
+        let iterations:u32=iterations.parse().map_err(|_| malformed())?;
+        if !(1..=MAX_DEALER_ITERATIONS).contains(&iterations) {
+            return Err(format!("{iterations} PBKDF2 iterations is outside 1..={MAX_DEALER_ITERATIONS}"));
+        }
+        let sealed=general_purpose::STANDARD.decode(sealed).map_err(|_| malformed())?;
+        let mut key=Sha256::pbkdf2(passphrase.as_bytes(), &salt, iterations);
+        let plain=HybridSS::decrypt_with_aad(&key, &sealed, self.header().as_bytes());
+        zeroize(&mut key);
+        let mut plain=plain.map_err(|_| "Wrong passphrase or edited dealer identity".to_string())?;
+        let secret:Result<[u8;32],_>=plain.as_slice().try_into();
+        zeroize(&mut plain);
+        let mut secret=secret.map_err(|_| malformed())?;
+        let signing=SigningKey::from_bytes(&secret);
+        zeroize(&mut secret);
+        if signing.verifying_key().to_bytes()!=self.key.current().public {
+            return Err(malformed());
+        }
+        Ok(signing)
+    }
+}
+
+fn seal(signing:&SigningKey,header:&str,passphrase:&str)->Result<String,String>{
+    if passphrase.is_empty() {
+        return Err("The dealer key must be encrypted under a non-empty passphrase".to_string());
+    }
+    let mut salt=[0u8;SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut key=Sha256::pbkdf2(passphrase.as_bytes(), &salt, DEALER_ITERATIONS);
+    let sealed=HybridSS::encrypt_with_aad(&key, 1, signing.as_bytes(), header.as_bytes());
+    zeroize(&mut key);
+    Ok(format!("{} {DEALER_ITERATIONS} {}", to_hex(&salt), general_purpose::STANDARD.encode(sealed?)))
+}
+
+fn check_uid(uid:&str)->Result<(),String>{
+    if uid.trim().is_empty() || uid.trim()!=uid || uid.contains(['\n', '\r']) {
+        return Err(format!("Dealer user ID {uid:?} must be one non-empty line without surrounding spaces"));
+    }
+    Ok(())
+}
+
+fn fingerprint(public:&[u8])->String{
+    to_hex(&Sha256::digest(public)[..20])
+}
+
+fn certified(public:&[u8],uid:&str)->Vec<u8>{
+    [CERTIFY_CONTEXT, public, uid.as_bytes()].concat()
+}
+
+fn signed(data:&[u8])->Vec<u8>{
+    [SIGN_CONTEXT, data].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_sign_rotate_verify() {
+        let mut identity=DealerIdentity::generate("Dealer <dealer@example.org>", "passphrase").unwrap();
+        let pinned=DealerKey::parse(&identity.public_key().to_text()).unwrap();
+        let before=identity.sign(b"transcript", "passphrase").unwrap();
+        assert!(is_dealer_signature(&before));
+        assert_eq!(pinned.verify(b"transcript", &before).unwrap(), identity.fingerprint());
+        assert!(pinned.verify(b"transcript!", &before).is_err());
+        assert!(identity.sign(b"transcript", "wrong").is_err());
+
+        let stored=DealerIdentity::parse(&identity.to_text()).unwrap();
+        assert_eq!(stored, identity);
+        identity.rotate(None, "passphrase", "2026-10-16").unwrap();
+        assert_eq!(identity.retired, vec![(pinned.current().fingerprint(), "2026-10-16".to_string())]);
+        let after=identity.sign(b"revocations", "passphrase").unwrap();
+        let current=DealerKey::parse(&identity.public_key().to_text()).unwrap();
+        assert_eq!(current.verify(b"revocations", &after).unwrap(), identity.fingerprint());
+        assert!(pinned.verify(b"revocations", &after).is_err());
+        assert!(current.verify(b"transcript", &before).is_err());
+        assert!(current.follows(&pinned));
+        assert!(!pinned.follows(&current));
+        assert_eq!(current.current().uid, "Dealer <dealer@example.org>");
+
+        // A chain with a key that was not certified by its predecessor.
+        let stranger=DealerIdentity::generate("Stranger <s@example.org>", "passphrase").unwrap();
+        let forged=format!("{}{}\n", pinned.to_text(), stranger.public_key().current().line());
+        assert!(DealerKey::parse(&forged).unwrap_err().contains("not certified"));
+    }
+
+    #[test]
+    fn identity_file_is_bound_to_its_secret() {
+        let identity=DealerIdentity::generate("Dealer", "passphrase").unwrap();
+        assert!(DealerIdentity::generate("Dealer", "").is_err());
+        assert!(DealerIdentity::generate("Dealer\nkey", "passphrase").is_err());
+
+        let mut text=identity.to_text();
+        text=text.replace("secret ", "retired 00 2026-10-16\nsecret ");
+        let edited=DealerIdentity::parse(&text).unwrap();
+        assert!(edited.sign(b"data", "passphrase").unwrap_err().contains("edited"));
+        assert!(DealerIdentity::parse(&identity.public_key().to_text()).is_err());
+    }
+}
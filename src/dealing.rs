@@ -0,0 +1,418 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use rand::RngCore;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{zeroize, GFC256};
+use crate::hardening::{create_new_private, create_private};
+use crate::hybrid::HybridSS;
+use crate::sha256::{from_hex, to_hex, Sha256};
+use crate::stream::{fill, stream_header, StreamOptions, StreamSplitter};
+
+// Resumable dealing: a file split into share streams (the `stream.rs`
+// format, so `join_streams` reads the result) with a journal of the chunks
+// every share file already holds, so a split interrupted half way through a
+// large input on slow destinations continues where it stopped instead of
+// starting over.
+//
+// A resumed chunk must come out exactly as it would have the first time, so
+// coefficients are not drawn from the thread RNG: chunk c uses a ChaCha20
+// stream c under a random 32-byte seed. The seed plus any one share would give
+// the secret away, so the journal only holds it sealed (`HybridSS::encrypt`)
+// under a key derived from the SHA-256 of the input. Whoever can unseal it
+// already holds the secret, and resuming against a changed input fails.
+//
+//   shamir-dealing-journal v1
+//   threshold 3
+//   shares 1,2,3,4,5
+//   chunk-size 65536
+//   input 10485760
+//   seed <sealed seed, hex>
+//   chunk 0 <per share: first 8 bytes of SHA-256 of its chunk, hex, comma-separated>
+//
+// Chunk lines are appended once every share file holds that chunk on disk
+// (flushed and synced, share by share, without the writer threads of
+// `StreamSplitter`). On resume, share files are cut back to the journaled
+// length, the last journaled chunk of each is checked against its digest,
+// and the journal is rewritten without any torn last line. The journal is
+// removed once the split completes.
+
+// Where the CLI keeps the journal, next to the share streams.
+pub const JOURNAL_FILE:&str="dealing.journal";
+
+const HEADER:&str="shamir-dealing-journal v1";
+const SEED_LEN:usize=32;
+const DIGEST_LEN:usize=8;
+const KEY_INFO:&[u8]=b"shamir-dealing-journal";
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct DealingJournal{
+    pub threshold:usize,
+    pub xs:Vec<u8>,
+    pub chunk_size:usize,
+    pub input_len:u64,
+    pub sealed_seed:Vec<u8>,
+    // Per completed chunk, in order: one digest per share, in `xs` order.
+    pub chunks:Vec<Vec<String>>,
+}
+
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct DealingStats{
+    // Input bytes split by this run.
+    pub bytes:u64,
+    // Chunks already in the share files when this run started.
+    pub resumed_chunks:u64,
+    pub total_chunks:u64,
+}
+
+impl DealingJournal{
+
+    pub fn to_text(&self)->String{
+        let xs:Vec<String>=self.xs.iter().map(|x| x.to_string()).collect();
+        let mut out=format!("{HEADER}\nthreshold {}\nshares {}\nchunk-size {}\ninput {}\nseed {}\n",
+            self.threshold, xs.join(","), self.chunk_size, self.input_len, to_hex(&self.sealed_seed));
+        for (number, digests) in self.chunks.iter().enumerate() {
+            out.push_str(&chunk_line(number, digests));
+        }
+        out
+    }
+
+    // A last line without its newline was torn by the interruption and is
+    // dropped, as is anything after a malformed chunk line.
+    pub fn parse(text:&str)->Result<DealingJournal,String>{
+        let complete=match text.rfind('\n') {
+            Some(end) => &text[..end],
+            None => "",
+        };
+        let mut lines=complete.lines();
+        if lines.next()!=Some(HEADER) {
+            return Err("Not a dealing journal".to_string());
+        }
+        let mut journal=DealingJournal::default();
+        for line in lines {
+            let (key, value)=line.split_once(' ').unwrap_or((line, ""));
+            let malformed=|| format!("Malformed dealing journal line {line}");
+            match key {
+                "threshold" => journal.threshold=value.parse().map_err(|_| malformed())?,
+                "shares" => journal.xs=value.split(',').map(|x| x.parse::<u8>().map_err(|_| malformed())).collect::<Result<_,_>>()?,
+                "chunk-size" => journal.chunk_size=value.parse().map_err(|_| malformed())?,
+                "input" => journal.input_len=value.parse().map_err(|_| malformed())?,
+                "seed" => journal.sealed_seed=from_hex(value).ok_or_else(malformed)?,
+                "chunk" => {
+                    let (number, digests)=value.split_once(' ').unwrap_or((value, ""));
+                    let digests:Vec<String>=digests.split(',').map(str::to_string).collect();
+                    if number.parse::<usize>().ok()!=Some(journal.chunks.len()) || digests.len()!=journal.xs.len() {
+                        break;
+                    }
+                    journal.chunks.push(digests);
+                }
+                _ => {}
+            }
+        }
+        if journal.xs.is_empty() || journal.chunk_size==0 || journal.sealed_seed.is_empty() {
+            return Err("Dealing journal is missing its parameters".to_string());
+        }
+        Ok(journal)
+    }
+
+    pub fn total_chunks(&self)->u64{
+        self.input_len.div_ceil(self.chunk_size as u64)
+    }
+
+    // Bytes of input (and of each share stream after its header) the
+    // journaled chunks cover.
+    fn done_bytes(&self)->u64{
+        (self.chunks.len() as u64*self.chunk_size as u64).min(self.input_len)
+    }
+}
+
+fn chunk_line(number:usize,digests:&[String])->String{
+    format!("chunk {number} {}\n", digests.join(","))
+}
+
+fn chunk_digest(chunk:&[u8])->String{
+    to_hex(&Sha256::digest(chunk)[..DIGEST_LEN])
+}
+
+// SHA-256 of the whole input, read in chunks, turned into the sealing key.
+fn input_key(input:&Path,chunk_size:usize)->Result<(u64,Vec<u8>),String>{
+    let mut file=File::open(input).map_err(|e| format!("{}: {e}", input.display()))?;
+    let mut hasher=Sha256::new();
+    let mut buffer=vec![0u8;chunk_size];
+    let mut len=0u64;
+    loop {
+        let got=fill(&mut file, &mut buffer).map_err(|e| format!("{}: {e}", input.display()))?;
+        if got==0 {
+            break;
+        }
+        hasher.update(&buffer[..got]);
+        len+=got as u64;
+    }
+    zeroize(&mut buffer);
+    Ok((len, Sha256::hkdf_key::<SEED_LEN>(&[], &hasher.finalize(), KEY_INFO).to_vec()))
+}
+
+fn sync(file:&File,path:&Path)->Result<(),String>{
+    file.sync_data().map_err(|e| format!("{}: {e}", path.display()))
+}
+
+// Starts a resumable split of `input` into the share files at `paths`
+// (`paths[i]` for `xs[i]`), journaled at `journal`, which must not exist yet.
+pub fn split_file(k:i32,xs:&[u8],chunk_size:usize,input:&Path,paths:&[PathBuf],journal:&Path)->Result<DealingStats,String>{
+    StreamSplitter::new(k, xs, StreamOptions { chunk_size, ..Default::default() }).map_err(|e| e.to_string())?;
+    if paths.len()!=xs.len() {
+        return Err(format!("{} share files for {} shares", paths.len(), xs.len()));
+    }
+    let (input_len, key)=input_key(input, chunk_size)?;
+    let mut seed=[0u8;SEED_LEN];
+    rand::thread_rng().fill_bytes(&mut seed);
+    let state=DealingJournal {
+        threshold: k as usize,
+        xs: xs.to_vec(),
+        chunk_size,
+        input_len,
+        sealed_seed: HybridSS::encrypt(&key, 1, &seed)?,
+        chunks: Vec::new(),
+    };
+    let mut file=create_new_private(journal).map_err(|e| format!("{}: {e}", journal.display()))?;
+    file.write_all(state.to_text().as_bytes()).map_err(|e| format!("{}: {e}", journal.display()))?;
+    sync(&file, journal)?;
+    for (path, &x) in paths.iter().zip(xs) {
+        let mut share=create_private(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        share.write_all(stream_header(x, state.threshold).as_bytes()).map_err(|e| format!("{}: {e}", path.display()))?;
+        sync(&share, path)?;
+    }
+    let result=deal(&state, &seed, input, paths, file, journal);
+    zeroize(&mut seed);
+    result
+}
+
+// Continues the split journaled at `journal` over the same input and share
+// files.
+pub fn resume(input:&Path,paths:&[PathBuf],journal:&Path)->Result<DealingStats,String>{
+    let text=fs::read_to_string(journal).map_err(|e| format!("{}: {e}", journal.display()))?;
+    let state=DealingJournal::parse(&text)?;
+    if paths.len()!=state.xs.len() {
+        return Err(format!("{} share files for {} shares", paths.len(), state.xs.len()));
+    }
+    let (input_len, key)=input_key(input, state.chunk_size)?;
+    let seed=HybridSS::decrypt(&key, &state.sealed_seed).ok().filter(|seed| input_len==state.input_len && seed.len()==SEED_LEN)
+        .ok_or(format!("{} is not the input this split started from", input.display()))?;
+    let mut seed:[u8;SEED_LEN]=seed.try_into().map_err(|_| "Malformed seed".to_string())?;
+
+    let result:Result<DealingStats,String>=(|| {
+        for ((path, &x), share) in paths.iter().zip(&state.xs).zip(0..) {
+            check_share(&state, path, x, share)?;
+        }
+        // Rewritten whole, so a torn last line does not stay in front of the
+        // lines appended from here on.
+        let staged=journal.with_extension("journal.tmp");
+        fs::write(&staged, state.to_text()).map_err(|e| format!("{}: {e}", staged.display()))?;
+        fs::rename(&staged, journal).map_err(|e| format!("{}: {e}", journal.display()))?;
+        let file=OpenOptions::new().append(true).open(journal).map_err(|e| format!("{}: {e}", journal.display()))?;
+        deal(&state, &seed, input, paths, file, journal)
+    })();
+    zeroize(&mut seed);
+    result
+}
+
+// Cuts a share file back to the journaled chunks and checks the last of them.
+fn check_share(state:&DealingJournal,path:&Path,x:u8,share:usize)->Result<(),String>{
+    let header=stream_header(x, state.threshold);
+    let expected=header.len() as u64+state.done_bytes();
+    let file=OpenOptions::new().read(true).write(true).open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let len=file.metadata().map_err(|e| format!("{}: {e}", path.display()))?.len();
+    if len<expected {
+        return Err(format!("{}: {len} bytes on disk, the journal records {expected}", path.display()));
+    }
+    if len>expected {
+        file.set_len(expected).map_err(|e| format!("{}: {e}", path.display()))?;
+        sync(&file, path)?;
+    }
+    let mut reader=&file;
+    let mut start=vec![0u8;header.len()];
+    reader.read_exact(&mut start).map_err(|e| format!("{}: {e}", path.display()))?;
+    if start!=header.as_bytes() {
+        return Err(format!("{}: not share stream {x} of this split", path.display()));
+    }
+    if let Some(digests)=state.chunks.last() {
+        let last=state.chunks.len() as u64-1;
+        let chunk_start=last*state.chunk_size as u64;
+        let mut chunk=vec![0u8;(state.input_len-chunk_start).min(state.chunk_size as u64) as usize];
+        reader.seek(SeekFrom::Start(header.len() as u64+chunk_start)).map_err(|e| format!("{}: {e}", path.display()))?;
+        reader.read_exact(&mut chunk).map_err(|e| format!("{}: {e}", path.display()))?;
+        if chunk_digest(&chunk)!=digests[share] {
+            return Err(format!("{}: chunk {last} does not match the journal", path.display()));
+        }
+    }
+    Ok(())
+}
+
+// Splits every chunk after the journaled ones, appending it to each share
+// file and then a line to the journal.
+fn deal(state:&DealingJournal,seed:&[u8;SEED_LEN],input:&Path,paths:&[PathBuf],mut journal_file:File,journal:&Path)->Result<DealingStats,String>{
+    let resumed=state.chunks.len() as u64;
+    let mut reader=File::open(input).map_err(|e| format!("{}: {e}", input.display()))?;
+    reader.seek(SeekFrom::Start(state.done_bytes())).map_err(|e| format!("{}: {e}", input.display()))?;
+    let mut shares=paths.iter()
+        .map(|path| OpenOptions::new().append(true).open(path).map_err(|e| format!("{}: {e}", path.display())))
+        .collect::<Result<Vec<_>,_>>()?;
+    let mut input_chunk=vec![0u8;state.chunk_size];
+    let mut coefficients=vec![0u8;state.threshold];
+    let mut stats=DealingStats { bytes: 0, resumed_chunks: resumed, total_chunks: state.total_chunks() };
+    let result:Result<(),String>=(|| {
+        for number in resumed.. {
+            let len=fill(&mut reader, &mut input_chunk).map_err(|e| format!("{}: {e}", input.display()))?;
+            if len==0 {
+                break;
+            }
+            let mut rng=ChaCha20Rng::from_seed(*seed);
+            rng.set_stream(number);
+            let mut chunks=vec![vec![0u8;len];state.xs.len()];
+            for (i, &byte) in input_chunk[..len].iter().enumerate() {
+                GFC256::generate_into(&mut coefficients, byte, &mut rng);
+                for (chunk, &x) in chunks.iter_mut().zip(&state.xs) {
+                    chunk[i]=GFC256::eval_slice(&coefficients, x);
+                }
+            }
+            for ((share, chunk), path) in shares.iter_mut().zip(&chunks).zip(paths) {
+                share.write_all(chunk).map_err(|e| format!("{}: {e}", path.display()))?;
+                sync(share, path)?;
+            }
+            let digests:Vec<String>=chunks.iter().map(|chunk| chunk_digest(chunk)).collect();
+            journal_file.write_all(chunk_line(number as usize, &digests).as_bytes()).map_err(|e| format!("{}: {e}", journal.display()))?;
+            sync(&journal_file, journal)?;
+            stats.bytes+=len as u64;
+        }
+        Ok(())
+    })();
+    zeroize(&mut input_chunk);
+    zeroize(&mut coefficients);
+    result?;
+    fs::remove_file(journal).map_err(|e| format!("{}: {e}", journal.display()))?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::join_streams;
+
+    struct Dir(PathBuf);
+
+    impl Dir{
+        fn new(name:&str)->Dir{
+            let root=std::env::temp_dir().join(format!("shamir-dealing-{name}-{}", std::process::id()));
+            let _=fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Dir(root)
+        }
+
+        fn shares(&self,count:usize)->Vec<PathBuf>{
+            (1..=count).map(|x| self.0.join(format!("share.{x}"))).collect()
+        }
+    }
+
+    impl Drop for Dir{
+        fn drop(&mut self){
+            let _=fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn join(paths:&[PathBuf])->Vec<u8>{
+        let readers=paths.iter().map(|path| File::open(path).unwrap()).collect();
+        let mut out=Vec::new();
+        join_streams(readers, &mut out, 7).unwrap();
+        out
+    }
+
+    // What `split_file` journals before the first chunk, with a known seed.
+    fn start(dir:&Dir,input:&Path,seed:&[u8;SEED_LEN])->(DealingJournal,PathBuf){
+        let (input_len, key)=input_key(input, 10).unwrap();
+        let state=DealingJournal { threshold: 2, xs: vec![1, 2, 3], chunk_size: 10, input_len, sealed_seed: HybridSS::encrypt(&key, 1, seed).unwrap(), chunks: Vec::new() };
+        let journal=dir.0.join(JOURNAL_FILE);
+        fs::write(&journal, state.to_text()).unwrap();
+        for (path, x) in dir.shares(3).iter().zip(1..) {
+            fs::write(path, stream_header(x, 2)).unwrap();
+        }
+        (state, journal)
+    }
+
+    #[test]
+    fn split_file_joins_as_share_streams() {
+        let dir=Dir::new("split");
+        let input=dir.0.join("input");
+        let secret:Vec<u8>=(0..95u8).collect();
+        fs::write(&input, &secret).unwrap();
+        let paths=dir.shares(3);
+        let journal=dir.0.join(JOURNAL_FILE);
+        let stats=split_file(2, &[1, 2, 3], 10, &input, &paths, &journal).unwrap();
+        assert_eq!(stats, DealingStats { bytes: 95, resumed_chunks: 0, total_chunks: 10 });
+        assert!(!journal.exists());
+        assert_eq!(join(&paths[1..]), secret);
+        assert!(split_file(2, &[1, 2, 3], 10, &input, &paths[1..], &journal).is_err());
+    }
+
+    #[test]
+    fn resume_continues_where_the_split_stopped() {
+        let dir=Dir::new("resume");
+        let input=dir.0.join("input");
+        let secret:Vec<u8>=(0..25u8).collect();
+        fs::write(&input, &secret).unwrap();
+        let paths=dir.shares(3);
+        let seed=[7u8;SEED_LEN];
+
+        let (_, journal)=start(&dir, &input, &seed);
+        assert_eq!(resume(&input, &paths, &journal).unwrap().resumed_chunks, 0);
+        let whole:Vec<Vec<u8>>=paths.iter().map(|path| fs::read(path).unwrap()).collect();
+        assert_eq!(join(&paths[..2]), secret);
+
+        // Interrupted after chunk 0, with chunk 1 half written and its
+        // journal line torn.
+        let (mut state, journal)=start(&dir, &input, &seed);
+        let header=stream_header(1, 2).len();
+        state.chunks.push(whole.iter().map(|share| chunk_digest(&share[header..header+10])).collect());
+        fs::write(&journal, state.to_text()+"chunk 1 0011").unwrap();
+        for (path, share) in paths.iter().zip(&whole) {
+            fs::write(path, &share[..header+14]).unwrap();
+        }
+        let stats=resume(&input, &paths, &journal).unwrap();
+        assert_eq!(stats, DealingStats { bytes: 15, resumed_chunks: 1, total_chunks: 3 });
+        for (path, share) in paths.iter().zip(&whole) {
+            assert_eq!(&fs::read(path).unwrap(), share);
+        }
+    }
+
+    #[test]
+    fn resume_refuses_a_changed_input_or_share() {
+        let dir=Dir::new("refuse");
+        let input=dir.0.join("input");
+        fs::write(&input, [1u8;25]).unwrap();
+        let paths=dir.shares(3);
+        let (mut state, journal)=start(&dir, &input, &[7u8;SEED_LEN]);
+        state.chunks.push(vec!["00".to_string();3]);
+        fs::write(&journal, state.to_text()).unwrap();
+        for path in &paths {
+            let mut share=fs::read(path).unwrap();
+            share.extend_from_slice(&[0u8;10]);
+            fs::write(path, share).unwrap();
+        }
+        assert!(resume(&input, &paths, &journal).unwrap_err().contains("does not match the journal"));
+
+        fs::write(&input, [2u8;25]).unwrap();
+        assert!(resume(&input, &paths, &journal).unwrap_err().contains("not the input"));
+    }
+
+    #[test]
+    fn journal_text_drops_a_torn_last_line() {
+        let state=DealingJournal { threshold: 2, xs: vec![1, 4], chunk_size: 8, input_len: 20, sealed_seed: vec![1, 2, 3], chunks: vec![vec!["aa".to_string(), "bb".to_string()]] };
+        assert_eq!(DealingJournal::parse(&state.to_text()).unwrap(), state);
+        assert_eq!(DealingJournal::parse(&(state.to_text()+"chunk 1 cc,dd")).unwrap(), state);
+        assert_eq!(DealingJournal::parse(&(state.to_text()+"chunk 5 cc,dd\n")).unwrap(), state);
+        assert_eq!(state.total_chunks(), 3);
+        assert!(DealingJournal::parse("shamir-dealing-journal v1\nthreshold 2\n").is_err());
+        assert!(DealingJournal::parse("not a journal\n").is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use rand::RngCore;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::error::ShamirError;
+use crate::sha256::{from_hex, to_hex, Sha256};
+
+// Coupled splitting: a second factor held by a device. The secret is XORed
+// with a ChaCha20 keystream keyed by a random 32-byte device factor before it
+// is split, so the shares encode the masked secret. A full quorum without the
+// factor recovers only noise, and the factor alone is just a random key.
+//
+// The factor is stored apart from the shares (OS keychain, a file on the
+// device). Losing it loses the secret, so back it up just as separately:
+// `to_text` for an offline copy, or `escrow` to split it among a different
+// group of custodians than the one holding the shares.
+//
+// A wrong factor cannot be detected from the shares; it yields a wrong
+// secret. Pair coupled splits with a check value when that matters.
+//
+//   shamir-device-factor v1
+//   fingerprint <8 hex digits>
+//   factor <64 hex digits>
+
+pub const DEVICE_FACTOR_LEN:usize=32;
+const HEADER:&str="shamir-device-factor v1";
+
+pub struct DeviceFactor{
+    bytes:[u8;DEVICE_FACTOR_LEN],
+}
+
+impl DeviceFactor{
+
+    pub fn generate()->DeviceFactor{
+        let mut bytes=[0u8;DEVICE_FACTOR_LEN];
+        rand::thread_rng().fill_bytes(&mut bytes);
+        DeviceFactor { bytes }
+    }
+
+    pub fn from_bytes(bytes:&[u8])->Result<DeviceFactor,String>{
+        let bytes=bytes.try_into().map_err(|_| format!("A device factor is {DEVICE_FACTOR_LEN} bytes, not {}", bytes.len()))?;
+        Ok(DeviceFactor { bytes })
+    }
+
+    pub fn as_bytes(&self)->&[u8]{
+        &self.bytes
+    }
+
+    // Identifies the factor without revealing it, for transcripts and prompts.
+    pub fn fingerprint(&self)->String{
+        let mut input=b"shamir-device-factor-id".to_vec();
+        input.extend_from_slice(&self.bytes);
+        to_hex(&Sha256::digest(&input)[..4])
+    }
+
+    pub fn to_text(&self)->String{
+        format!("{HEADER}\nfingerprint {}\nfactor {}\n", self.fingerprint(), to_hex(&self.bytes))
+    }
+
+    pub fn parse(text:&str)->Result<DeviceFactor,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(HEADER) {
+            return Err("Not a device factor".to_string());
+        }
+        let mut fingerprint=None;
+        let mut factor=None;
+        for line in lines {
+            match line.split_once(' ') {
+                Some(("fingerprint", value)) => fingerprint=Some(value.trim()),
+                Some(("factor", value)) => factor=Some(DeviceFactor::from_bytes(&from_hex(value.trim()).ok_or("Malformed device factor")?)?),
+                _ => {}
+            }
+        }
+        let factor=factor.ok_or("Device factor file holds no factor")?;
+        if fingerprint.is_some_and(|f| f!=factor.fingerprint()) {
+            return Err("Device factor does not match its fingerprint".to_string());
+        }
+        Ok(factor)
+    }
+
+    // Splits the factor itself, k of n, for an escrow group.
+    pub fn escrow(&self,n:i32,k:i32)->Result<Parts,ShamirError>{
+        ShamirSS::split(n, k, self.bytes.to_vec())
+    }
+
+    pub fn from_escrow(parts:Parts)->Result<DeviceFactor,String>{
+        let mut joined=ShamirSS::join(parts)?;
+        let factor=DeviceFactor::from_bytes(&joined);
+        zeroize(&mut joined);
+        factor
+    }
+
+    // XORs the keystream into `data`, which starts `offset` bytes into the
+    // secret. Applying it twice restores the input.
+    pub fn apply_at(&self,offset:usize,data:&mut [u8]){
+        let mut key=Sha256::hmac(&self.bytes, b"shamir-device-factor-mask");
+        let mut rng=ChaCha20Rng::from_seed(key);
+        zeroize(&mut key);
+        // The generator hands out whole 32-bit words, so start at the word
+        // holding `offset` and drop the bytes before it.
+        rng.set_word_pos((offset/4) as u128);
+        let mut skip=offset%4;
+        let mut done=0;
+        let mut block=[0u8;64];
+        while done<data.len() {
+            let len=block.len().min(skip+data.len()-done);
+            rng.fill_bytes(&mut block[..len]);
+            for (byte, mask) in data[done..].iter_mut().zip(&block[skip..len]) {
+                *byte^=mask;
+            }
+            done+=len-skip;
+            skip=0;
+        }
+        zeroize(&mut block);
+    }
+}
+
+impl Drop for DeviceFactor{
+    fn drop(&mut self){
+        zeroize(&mut self.bytes);
+    }
+}
+
+impl ShamirSS{
+
+    pub fn split_coupled(n:i32,k:i32,mut secret:Vec<u8>,factor:&DeviceFactor)->Result<Parts,ShamirError>{
+        factor.apply_at(0, &mut secret);
+        Self::split(n, k, secret)
+    }
+
+    pub fn join_coupled(parts:Parts,factor:&DeviceFactor)->Result<Vec<u8>,ShamirError>{
+        let mut secret=Self::join(parts)?;
+        factor.apply_at(0, &mut secret);
+        Ok(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_need_the_factor() {
+        let factor=DeviceFactor::generate();
+        let parts=ShamirSS::split_coupled(3, 2, b"coupled secret".to_vec(), &factor).unwrap();
+        assert_ne!(ShamirSS::join(parts.clone()).unwrap(), b"coupled secret");
+        assert_eq!(ShamirSS::join_coupled(parts.clone(), &factor).unwrap(), b"coupled secret");
+        assert_ne!(ShamirSS::join_coupled(parts, &DeviceFactor::generate()).unwrap(), b"coupled secret");
+    }
+
+    #[test]
+    fn keystream_can_start_at_any_offset() {
+        let factor=DeviceFactor::from_bytes(&[7; DEVICE_FACTOR_LEN]).unwrap();
+        let mut whole=vec![0u8;200];
+        factor.apply_at(0, &mut whole);
+        for (offset, len) in [(1, 10), (3, 64), (63, 70), (130, 70)] {
+            let mut piece=vec![0u8;len];
+            factor.apply_at(offset, &mut piece);
+            assert_eq!(piece, whole[offset..offset+len], "offset {offset}");
+        }
+    }
+
+    #[test]
+    fn factor_survives_text_and_escrow() {
+        let factor=DeviceFactor::generate();
+        assert_eq!(DeviceFactor::parse(&factor.to_text()).unwrap().as_bytes(), factor.as_bytes());
+        let other=DeviceFactor::generate();
+        let mismatched=factor.to_text().replace(&factor.fingerprint(), &other.fingerprint());
+        assert!(DeviceFactor::parse(&mismatched).is_err());
+        assert!(DeviceFactor::from_bytes(&[1; 16]).is_err());
+
+        let escrow:Parts=factor.escrow(5, 3).unwrap().into_iter().take(3).collect();
+        assert_eq!(DeviceFactor::from_escrow(escrow).unwrap().as_bytes(), factor.as_bytes());
+    }
+}
@@ -0,0 +1,522 @@
+use base64::alphabet;
+use base64::engine::{general_purpose, DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig};
+use base64::Engine as _;
+use bech32::primitives::decode::UncheckedHrpstring;
+use bech32::{Bech32m, Checksum, Hrp};
+
+use crate::plate::{decode_plate, PLATE_HEADER};
+use crate::scheme::SHAMIR_SCHEME_ID;
+use crate::transform::{Pipeline, MAX_TRANSFORMS};
+use crate::sha256::{from_hex, to_hex, Sha256};
+
+// Share text form used on disk and over the wire: "<index>:<base64 payload>".
+pub fn encode_share(index:i32,data:&[u8])->String{
+    format!("{}:{}\n", index, general_purpose::STANDARD.encode(data))
+}
+
+// How decoders treat non-canonical input. `Strict` accepts only the exact
+// form the encoder writes, so a stored share has a single valid spelling.
+// `Lenient` (the default) normalizes what it safely can (missing padding,
+// non-zero trailing bits, URL-safe alphabet, upper case, grouping spaces,
+// leading zeros) and lists each normalization in `Decoded::warnings`.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub enum ParseMode{
+    #[default]
+    Lenient,
+    Strict,
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Decoded{
+    pub index:i32,
+    pub data:Vec<u8>,
+    pub warnings:Vec<String>,
+    // Only compact shares record one; see `transform.rs`.
+    pub transform:Pipeline,
+}
+
+const LENIENT_BASE64:GeneralPurpose=GeneralPurpose::new(&alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_allow_trailing_bits(true).with_decode_padding_mode(DecodePaddingMode::Indifferent));
+
+pub fn decode_share(text:&str)->Result<(i32,Vec<u8>),String>{
+    decode_share_with(text, ParseMode::Lenient).map(|d| (d.index, d.data))
+}
+
+pub fn decode_share_with(text:&str,mode:ParseMode)->Result<Decoded,String>{
+    let (index_text, payload)=text.trim().split_once(':').ok_or("Malformed share")?;
+    let index=index_text.parse::<i32>().map_err(|_| "Malformed share index".to_string())?;
+    if mode==ParseMode::Strict {
+        let data=general_purpose::STANDARD.decode(payload).map_err(|e| format!("Malformed share payload: {e}"))?;
+        if index_text!=index.to_string() || text.strip_suffix('\n').unwrap_or(text)!=encode_share(index, &data).trim_end() {
+            return Err("Share text is not in canonical form".to_string());
+        }
+        return Ok(Decoded { index, data, warnings: Vec::new(), transform: Pipeline::default() });
+    }
+
+    let mut warnings=Vec::new();
+    if index_text!=index.to_string() {
+        warnings.push(format!("share index {index_text:?} normalized to {index}"));
+    }
+    let mut cleaned:String=payload.split_whitespace().collect();
+    if cleaned.len()!=payload.len() {
+        warnings.push("whitespace removed from share payload".to_string());
+    }
+    if cleaned.contains(['-', '_']) {
+        cleaned=cleaned.replace('-', "+").replace('_', "/");
+        warnings.push("URL-safe base64 alphabet converted".to_string());
+    }
+    let data=LENIENT_BASE64.decode(&cleaned).map_err(|e| format!("Malformed share payload: {e}"))?;
+    if general_purpose::STANDARD.encode(&data)!=cleaned {
+        warnings.push("non-canonical base64 padding or trailing bits normalized".to_string());
+    }
+    Ok(Decoded { index, data, warnings, transform: Pipeline::default() })
+}
+
+// Short identifier for a share: first 4 bytes of SHA-256 over its text form.
+pub fn share_fingerprint(index:i32,data:&[u8])->String{
+    to_hex(&Sha256::digest(encode_share(index, data).as_bytes())[..4])
+}
+
+// Compact profile: a binary frame whose header has a fixed layout, so every
+// share of a secret has exactly the same length whatever its index, k or n.
+// The text form's decimal index ("7:" vs "142:") leaks through file sizes;
+// this one does not, which matters for steganographic storage. Fields that
+// could vary are reserved and must be zero instead of being optional:
+//
+//   0..4    magic "SHC1"
+//   4       share index (1..=255)
+//   5       scheme id (`SHAMIR_SCHEME_ID`)
+//   6..12   pre-split transform ids in the order applied, zero after the
+//           last (`transform::Pipeline`); all zero for none
+//   12..16  share length, big-endian u32
+//   16..    share bytes
+//   last 4  first 4 bytes of SHA-256 over everything before them
+//
+// `encode_compact_text` is the frame in base64, whose length is likewise
+// fixed; it always starts with COMPACT_TEXT_PREFIX.
+const COMPACT_MAGIC:&[u8;4]=b"SHC1";
+pub const COMPACT_HEADER_LEN:usize=16;
+const COMPACT_CHECKSUM_LEN:usize=4;
+const COMPACT_TEXT_PREFIX:&str="U0hD";
+
+// Length of a compact frame for a share of `share_len` bytes.
+pub fn compact_len(share_len:usize)->usize{
+    COMPACT_HEADER_LEN+share_len+COMPACT_CHECKSUM_LEN
+}
+
+pub fn encode_compact(index:i32,data:&[u8],transform:Pipeline)->Vec<u8>{
+    let mut out=Vec::with_capacity(compact_len(data.len()));
+    out.extend_from_slice(COMPACT_MAGIC);
+    out.push(index as u8);
+    out.push(SHAMIR_SCHEME_ID);
+    out.extend_from_slice(&transform.to_header());
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(data);
+    let checksum=Sha256::digest(&out);
+    out.extend_from_slice(&checksum[..COMPACT_CHECKSUM_LEN]);
+    out
+}
+
+pub fn decode_compact(frame:&[u8])->Result<(i32,Vec<u8>,Pipeline),String>{
+    if frame.len()<compact_len(0) || &frame[..4]!=COMPACT_MAGIC {
+        return Err("Not a compact share".to_string());
+    }
+    let (body, checksum)=frame.split_at(frame.len()-COMPACT_CHECKSUM_LEN);
+    if Sha256::digest(body)[..COMPACT_CHECKSUM_LEN]!=*checksum {
+        return Err("Compact share checksum mismatch".to_string());
+    }
+    if body[5]!=SHAMIR_SCHEME_ID {
+        return Err(format!("Unsupported compact share scheme {}", body[5]));
+    }
+    let transform=Pipeline::from_header(&body[6..6+MAX_TRANSFORMS])?;
+    let len=u32::from_be_bytes([body[12], body[13], body[14], body[15]]) as usize;
+    if body.len()!=COMPACT_HEADER_LEN+len {
+        return Err("Compact share length does not match its header".to_string());
+    }
+    if body[4]==0 {
+        return Err("Malformed share index".to_string());
+    }
+    Ok((body[4] as i32, body[COMPACT_HEADER_LEN..].to_vec(), transform))
+}
+
+// Length of `encode_compact_text` for a share of `share_len` bytes.
+pub fn compact_text_len(share_len:usize)->usize{
+    compact_len(share_len).div_ceil(3)*4+1
+}
+
+pub fn encode_compact_text(index:i32,data:&[u8],transform:Pipeline)->String{
+    format!("{}\n", general_purpose::STANDARD.encode(encode_compact(index, data, transform)))
+}
+
+// Strict mode takes only the unwrapped base64 the encoder writes; lenient
+// mode also drops line breaks and spaces.
+pub fn decode_compact_text_with(text:&str,mode:ParseMode)->Result<Decoded,String>{
+    let trimmed=text.trim();
+    let cleaned:String=trimmed.split_whitespace().collect();
+    let mut warnings=Vec::new();
+    if cleaned.len()!=trimmed.len() {
+        if mode==ParseMode::Strict {
+            return Err("Compact share is not in canonical form".to_string());
+        }
+        warnings.push("whitespace removed from compact share".to_string());
+    }
+    let frame=general_purpose::STANDARD.decode(&cleaned).map_err(|e| format!("Malformed compact share: {e}"))?;
+    let (index, data, transform)=decode_compact(&frame)?;
+    Ok(Decoded { index, data, warnings, transform })
+}
+
+// Bech32m (BIP-350) form of a share for media where base64 is awkward
+// (engraving, reading aloud): hrp "shamir", payload = index byte || share.
+// Shares longer than about 50 bytes exceed BIP-173's 90-character limit;
+// the checksum still detects errors but general bech32 tools may refuse them.
+const BECH32_HRP:Hrp=Hrp::parse_unchecked("shamir");
+
+// Bech32m with no length limit: shares are as long as their secret, and
+// longer than the 1023 characters the bech32 crate's Bech32m allows.
+enum ShareBech32m{}
+
+impl Checksum for ShareBech32m{
+    type MidstateRepr=u32;
+    const CODE_LENGTH:usize=usize::MAX;
+    const CHECKSUM_LENGTH:usize=<Bech32m as Checksum>::CHECKSUM_LENGTH;
+    const GENERATOR_SH:[u32;5]=<Bech32m as Checksum>::GENERATOR_SH;
+    const TARGET_RESIDUE:u32=<Bech32m as Checksum>::TARGET_RESIDUE;
+}
+
+fn convert_bits(data:&[u8],from:u32,to:u32,pad:bool)->Option<Vec<u8>>{
+    let mut acc:u32=0;
+    let mut bits:u32=0;
+    let mut out=Vec::new();
+    let maxv=(1<<to)-1;
+    for &value in data {
+        acc=(acc<<from)|value as u32;
+        bits+=from;
+        while bits>=to {
+            bits-=to;
+            out.push(((acc>>bits)&maxv) as u8);
+        }
+    }
+    if pad {
+        if bits>0 {
+            out.push(((acc<<(to-bits))&maxv) as u8);
+        }
+    } else if bits>=from || ((acc<<(to-bits))&maxv)!=0 {
+        return None;
+    }
+    Some(out)
+}
+
+pub fn encode_bech32(index:i32,data:&[u8])->String{
+    let mut payload=vec![index as u8];
+    payload.extend_from_slice(data);
+    bech32::encode::<ShareBech32m>(BECH32_HRP, &payload).unwrap_or_default()
+}
+
+pub fn decode_bech32(text:&str)->Result<(i32,Vec<u8>),String>{
+    decode_bech32_with(text, ParseMode::Lenient).map(|d| (d.index, d.data))
+}
+
+// Strict mode takes only the lower-case, ungrouped form `encode_bech32`
+// writes; lenient mode also accepts all upper case and spaces or dashes
+// between groups. Mixed case is always rejected, as BIP-173 requires.
+pub fn decode_bech32_with(text:&str,mode:ParseMode)->Result<Decoded,String>{
+    let trimmed=text.trim();
+    if trimmed.bytes().any(|b| b.is_ascii_uppercase()) && trimmed.bytes().any(|b| b.is_ascii_lowercase()) {
+        return Err("Mixed-case bech32 share".to_string());
+    }
+    let mut warnings=Vec::new();
+    let text:String=trimmed.chars().filter(|c| !c.is_whitespace() && *c!='-').collect();
+    if text.len()!=trimmed.len() {
+        warnings.push("grouping removed from bech32 share".to_string());
+    }
+    if text.bytes().any(|b| b.is_ascii_uppercase()) {
+        warnings.push("upper-case bech32 share normalized to lower case".to_string());
+    }
+    if mode==ParseMode::Strict && !warnings.is_empty() {
+        return Err("Bech32 share is not in canonical form".to_string());
+    }
+    let text=text.to_ascii_lowercase();
+    let unchecked=UncheckedHrpstring::new(&text).map_err(|e| format!("Malformed bech32 share: {e}"))?;
+    if unchecked.hrp()!=BECH32_HRP {
+        return Err("Not a bech32 share".to_string());
+    }
+    let checked=unchecked.validate_and_remove_checksum::<ShareBech32m>().map_err(|_| "Bech32 share checksum mismatch".to_string())?;
+    checked.validate_segwit_padding().map_err(|_| "Malformed bech32 share padding".to_string())?;
+    let payload:Vec<u8>=checked.byte_iter().collect();
+    let (index, share)=payload.split_first().ok_or("Empty bech32 share")?;
+    if *index==0 {
+        return Err("Malformed share index".to_string());
+    }
+    Ok(Decoded { index: *index as i32, data: share.to_vec(), warnings, transform: Pipeline::default() })
+}
+
+// Hex and Crockford Base32 forms, for stores that reject base64's `+/=`
+// (identifiers, some key-value stores and form fields). Like bech32 they
+// carry index byte || share, behind a prefix that tells them apart:
+//
+//   shx07a3f09c...      lower-case hex
+//   SH3270T8W...Q       Crockford Base32, then the check symbol
+//
+// Crockford's alphabet has no I, L, O or U, so a misread letter is either
+// invalid or the digit it resembles. The check symbol is the payload, read
+// as one number, modulo 37; values 32..36 are written `*~$=U`, so one share
+// in 37 ends in `=`. Where no `=` may appear at all, use hex.
+const HEX_PREFIX:&str="shx";
+const BASE32_PREFIX:&str="SH32";
+const CROCKFORD_CHARSET:&[u8;32]=b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CROCKFORD_CHECK_EXTRA:&[u8;5]=b"*~$=U";
+
+fn index_payload(index:i32,data:&[u8])->Vec<u8>{
+    let mut payload=vec![index as u8];
+    payload.extend_from_slice(data);
+    payload
+}
+
+fn split_payload(payload:&[u8])->Result<(i32,Vec<u8>),String>{
+    match payload.split_first() {
+        Some((&index, share)) if index!=0 => Ok((index as i32, share.to_vec())),
+        Some(_) => Err("Malformed share index".to_string()),
+        None => Err("Empty share".to_string()),
+    }
+}
+
+fn has_prefix(text:&str,prefix:&str)->bool{
+    text.get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+pub fn encode_hex(index:i32,data:&[u8])->String{
+    format!("{HEX_PREFIX}{}", to_hex(&index_payload(index, data)))
+}
+
+pub fn decode_hex(text:&str)->Result<(i32,Vec<u8>),String>{
+    decode_hex_with(text, ParseMode::Lenient).map(|d| (d.index, d.data))
+}
+
+// Strict mode takes only the lower-case, unbroken form `encode_hex` writes;
+// lenient mode also accepts upper case and whitespace.
+pub fn decode_hex_with(text:&str,mode:ParseMode)->Result<Decoded,String>{
+    let trimmed=text.trim();
+    let mut warnings=Vec::new();
+    let text:String=trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    if text.len()!=trimmed.len() {
+        warnings.push("whitespace removed from hex share".to_string());
+    }
+    if text.bytes().any(|b| b.is_ascii_uppercase()) {
+        warnings.push("upper-case hex share normalized to lower case".to_string());
+    }
+    if mode==ParseMode::Strict && !warnings.is_empty() {
+        return Err("Hex share is not in canonical form".to_string());
+    }
+    let text=text.to_ascii_lowercase();
+    let digits=text.strip_prefix(HEX_PREFIX).ok_or("Not a hex share")?;
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err("Invalid hex digit in share".to_string());
+    }
+    let payload=from_hex(digits).ok_or("Hex share has an odd number of digits")?;
+    let (index, data)=split_payload(&payload)?;
+    Ok(Decoded { index, data, warnings, transform: Pipeline::default() })
+}
+
+fn crockford_check(values:&[u8])->u8{
+    let check=values.iter().fold(0, |acc, &v| (acc*32+v as usize)%37);
+    if check<32 { CROCKFORD_CHARSET[check] } else { CROCKFORD_CHECK_EXTRA[check-32] }
+}
+
+pub fn encode_base32(index:i32,data:&[u8])->String{
+    let values=convert_bits(&index_payload(index, data), 8, 5, true).unwrap();
+    let mut out=BASE32_PREFIX.to_string();
+    out.extend(values.iter().map(|&v| CROCKFORD_CHARSET[v as usize] as char));
+    out.push(crockford_check(&values) as char);
+    out
+}
+
+pub fn decode_base32(text:&str)->Result<(i32,Vec<u8>),String>{
+    decode_base32_with(text, ParseMode::Lenient).map(|d| (d.index, d.data))
+}
+
+// Strict mode takes only the upper-case, ungrouped form `encode_base32`
+// writes; lenient mode also accepts lower case and spaces or hyphens
+// between groups, and reads O as 0 and I or L as 1, as Crockford intends.
+pub fn decode_base32_with(text:&str,mode:ParseMode)->Result<Decoded,String>{
+    let trimmed=text.trim();
+    let mut warnings=Vec::new();
+    let text:String=trimmed.chars().filter(|c| !c.is_whitespace() && *c!='-').collect();
+    if text.len()!=trimmed.len() {
+        warnings.push("grouping removed from base32 share".to_string());
+    }
+    if text.bytes().any(|b| b.is_ascii_lowercase()) {
+        warnings.push("lower-case base32 share normalized to upper case".to_string());
+    }
+    let text=text.to_ascii_uppercase();
+    let body=text.strip_prefix(BASE32_PREFIX).ok_or("Not a base32 share")?;
+    let normalized:Vec<u8>=body.bytes().map(|c| match c { b'O' => b'0', b'I' | b'L' => b'1', c => c }).collect();
+    if normalized!=body.as_bytes() {
+        warnings.push("letters O, I and L in base32 share read as 0 and 1".to_string());
+    }
+    if mode==ParseMode::Strict && !warnings.is_empty() {
+        return Err("Base32 share is not in canonical form".to_string());
+    }
+    let (check, symbols)=normalized.split_last().ok_or("Empty base32 share")?;
+    let values:Vec<u8>=symbols.iter()
+        .map(|c| CROCKFORD_CHARSET.iter().position(|x| x==c).map(|p| p as u8))
+        .collect::<Option<_>>().ok_or("Invalid base32 character")?;
+    if crockford_check(&values)!=*check {
+        return Err("Base32 share check symbol mismatch".to_string());
+    }
+    let payload=convert_bits(&values, 5, 8, false).ok_or("Malformed base32 share padding")?;
+    let (index, data)=split_payload(&payload)?;
+    Ok(Decoded { index, data, warnings, transform: Pipeline::default() })
+}
+
+// One logical share written in every physical encoding at once, so a
+// custodian can keep it on paper, metal and USB. The fingerprint line lets
+// whichever copy survives be checked on its own:
+//
+//   shamir-share-bundle v1
+//   fingerprint <share_fingerprint>
+//   text <index>:<base64>
+//   bech32 shamir1...
+pub fn encode_bundle(index:i32,data:&[u8])->String{
+    format!("shamir-share-bundle v1\nfingerprint {}\ntext {}bech32 {}\n",
+        share_fingerprint(index, data), encode_share(index, data), encode_bech32(index, data))
+}
+
+// Accepts any single physical encoding of a share: the text form, a compact
+// share in base64, a bech32, hex or Crockford Base32 string, a plate layout,
+// BIP-39 words (feature `mnemonic`), or a bundle. Every encoding present in a bundle must decode to the same
+// share and match the bundle's fingerprint.
+pub fn decode_any_share(text:&str)->Result<(i32,Vec<u8>),String>{
+    decode_any_share_with(text, ParseMode::Lenient).map(|d| (d.index, d.data))
+}
+
+pub fn decode_any_share_with(text:&str,mode:ParseMode)->Result<Decoded,String>{
+    let trimmed=text.trim();
+    if trimmed.get(..PLATE_HEADER.len()).is_some_and(|header| header.eq_ignore_ascii_case(PLATE_HEADER)) {
+        let (index, data)=decode_plate(trimmed)?;
+        return Ok(Decoded { index, data, warnings: Vec::new(), transform: Pipeline::default() });
+    }
+    if trimmed.starts_with(COMPACT_TEXT_PREFIX) {
+        return decode_compact_text_with(trimmed, mode);
+    }
+    if has_prefix(trimmed, HEX_PREFIX) {
+        return decode_hex_with(trimmed, mode);
+    }
+    if has_prefix(trimmed, BASE32_PREFIX) {
+        return decode_base32_with(trimmed, mode);
+    }
+    #[cfg(feature = "mnemonic")]
+    if crate::mnemonic::looks_like_mnemonic(trimmed) {
+        let (index, data, _)=crate::mnemonic::decode_mnemonic(trimmed)?;
+        return Ok(Decoded { index, data, warnings: Vec::new(), transform: Pipeline::default() });
+    }
+    if !trimmed.starts_with("shamir-share-bundle") {
+        return if trimmed.contains(':') { decode_share_with(text, mode) } else { decode_bech32_with(trimmed, mode) };
+    }
+
+    let mut lines=trimmed.lines();
+    if lines.next()!=Some("shamir-share-bundle v1") {
+        return Err("Unsupported share bundle version".to_string());
+    }
+    let mut fingerprint=None;
+    let mut share:Option<(i32,Vec<u8>)>=None;
+    let mut warnings=Vec::new();
+    for line in lines.map(str::trim).filter(|line| !line.is_empty()) {
+        let (kind, value)=line.split_once(' ').ok_or(format!("Malformed bundle line {line}"))?;
+        let decoded=match kind {
+            "fingerprint" => { fingerprint=Some(value.to_string()); continue; }
+            "text" => decode_share_with(value, mode)?,
+            "bech32" => decode_bech32_with(value, mode)?,
+            _ => continue,
+        };
+        warnings.extend(decoded.warnings.into_iter().map(|w| format!("{kind}: {w}")));
+        let decoded=(decoded.index, decoded.data);
+        match &share {
+            Some(existing) if *existing!=decoded => return Err(format!("Bundle {kind} encoding disagrees with the others")),
+            Some(_) => {}
+            None => share=Some(decoded),
+        }
+    }
+    let (index, data)=share.ok_or("Bundle holds no share encoding")?;
+    if let Some(expected)=fingerprint {
+        if share_fingerprint(index, &data)!=expected {
+            return Err("Bundle fingerprint does not match its share".to_string());
+        }
+    }
+    Ok(Decoded { index, data, warnings, transform: Pipeline::default() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHARE:&[u8]=b"\x00\x01share bytes\xff";
+
+    #[test]
+    fn every_encoding_round_trips() {
+        let pipeline=Pipeline::new(&[1]).unwrap();
+        let encoded=[encode_share(7, SHARE), encode_bech32(7, SHARE), encode_hex(7, SHARE), encode_base32(7, SHARE), encode_compact_text(7, SHARE, pipeline), encode_bundle(7, SHARE)];
+        for text in &encoded {
+            let decoded=decode_any_share_with(text, ParseMode::Strict).unwrap();
+            assert_eq!((decoded.index, decoded.data.as_slice()), (7, SHARE), "{text}");
+            assert!(decoded.warnings.is_empty(), "{text}");
+        }
+        assert_eq!(decode_compact_text_with(&encoded[4], ParseMode::Strict).unwrap().transform, pipeline);
+    }
+
+    #[test]
+    fn compact_frames_have_a_fixed_length() {
+        let frames:Vec<Vec<u8>>=[1, 7, 142, 255].iter().map(|&index| encode_compact(index, SHARE, Pipeline::default())).collect();
+        assert!(frames.iter().all(|frame| frame.len()==compact_len(SHARE.len())));
+        assert_eq!(encode_compact_text(142, SHARE, Pipeline::default()).len(), compact_text_len(SHARE.len()));
+
+        let mut flipped=frames[0].clone();
+        flipped[COMPACT_HEADER_LEN]^=1;
+        assert_eq!(decode_compact(&flipped), Err("Compact share checksum mismatch".to_string()));
+        assert!(decode_compact(&frames[0][..10]).is_err());
+        assert!(decode_compact(&encode_compact(0, SHARE, Pipeline::default())).is_err());
+    }
+
+    #[test]
+    fn lenient_mode_normalizes_and_warns() {
+        let decoded=decode_share_with(" 007:AAFzaGFyZSBieXRlc_8 ", ParseMode::Lenient).unwrap();
+        assert_eq!((decoded.index, decoded.data.as_slice()), (7, SHARE));
+        assert_eq!(decoded.warnings.len(), 3);
+        assert!(decode_share_with("007:AAFzaGFyZSBieXRlc/8=", ParseMode::Strict).is_err());
+
+        let grouped=encode_bech32(7, SHARE).to_ascii_uppercase();
+        let grouped=format!("{}-{}", &grouped[..10], &grouped[10..]);
+        assert_eq!(decode_bech32(&grouped).unwrap(), (7, SHARE.to_vec()));
+        assert!(decode_bech32_with(&grouped, ParseMode::Strict).is_err());
+        assert!(decode_bech32(&encode_bech32(7, SHARE).replacen('s', "S", 1)).is_err());
+
+        let misread=encode_base32(7, SHARE).replacen('0', "O", 1).to_ascii_lowercase();
+        assert_eq!(decode_base32(&misread).unwrap(), (7, SHARE.to_vec()));
+        assert_eq!(decode_hex(&encode_hex(7, SHARE).to_ascii_uppercase()).unwrap(), (7, SHARE.to_vec()));
+    }
+
+    #[test]
+    fn checksums_catch_typos() {
+        let bech32=encode_bech32(7, SHARE);
+        let typo=|text:&str, at:usize| {
+            let mut bytes=text.as_bytes().to_vec();
+            bytes[at]=if bytes[at]==b'q' { b'p' } else { b'q' };
+            String::from_utf8(bytes).unwrap()
+        };
+        assert!(decode_bech32(&typo(&bech32, 12)).is_err());
+        assert!(decode_base32(&typo(&encode_base32(7, SHARE), 6)).is_err());
+        assert!(decode_hex("shx07a").is_err());
+        assert!(decode_hex("shx00aa").is_err());
+    }
+
+    #[test]
+    fn bundles_must_agree_with_their_fingerprint() {
+        let bundle=encode_bundle(7, SHARE);
+        let other=encode_bundle(8, SHARE);
+        let text_line=|bundle:&str| bundle.lines().find(|line| line.starts_with("text ")).unwrap().to_string();
+        assert!(decode_any_share(&bundle.replace(&text_line(&bundle), &text_line(&other))).is_err());
+        let fingerprint=share_fingerprint(8, SHARE);
+        assert!(decode_any_share(&bundle.replace(&share_fingerprint(7, SHARE), &fingerprint)).is_err());
+        assert!(decode_any_share("shamir-share-bundle v2\n").is_err());
+        assert!(decode_any_share("shamir-share-bundle v1\nfingerprint 00\n").is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use rand::RngCore;
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::hybrid::HybridSS;
+use crate::sha256::Sha256;
+
+// Envelope encryption: data is encrypted under per-object data keys (DEKs)
+// and only the DEKs are wrapped by a top-level key protector, in the style of
+// Tink's KmsAead or an AWS KMS key. `KeyWrapper` is that protector interface;
+// `ShamirKeyWrapper` implements it with a key-encryption key (KEK) that only
+// exists once a quorum of custodians has supplied their shares.
+//
+// Wrapped DEKs use the hybrid sealed format under a key derived from the KEK
+// and the associated data, so a DEK unwraps only with the same associated
+// data it was wrapped with.
+
+pub trait KeyWrapper{
+    fn wrap_key(&self,dek:&[u8],associated_data:&[u8])->Result<Vec<u8>,String>;
+    fn unwrap_key(&self,wrapped:&[u8],associated_data:&[u8])->Result<Vec<u8>,String>;
+}
+
+const KEK_LEN:usize=32;
+
+pub struct ShamirKeyWrapper{
+    kek:[u8;KEK_LEN],
+}
+
+impl ShamirKeyWrapper{
+
+    // Draws a fresh KEK and splits it among n custodians. The returned
+    // wrapper is ready to use; drop it once the shares are handed out.
+    pub fn generate(n:i32,k:i32)->Result<(ShamirKeyWrapper,Parts),String>{
+        let mut kek=[0u8;KEK_LEN];
+        rand::thread_rng().fill_bytes(&mut kek);
+        let parts=ShamirSS::split(n, k, kek.to_vec())?;
+        Ok((ShamirKeyWrapper { kek }, parts))
+    }
+
+    pub fn from_shares(parts:Parts)->Result<ShamirKeyWrapper,String>{
+        let mut joined=ShamirSS::join(parts)?;
+        let kek:Result<[u8;KEK_LEN],_>=joined.as_slice().try_into();
+        zeroize(&mut joined);
+        Ok(ShamirKeyWrapper { kek: kek.map_err(|_| "Shares do not hold a key-encryption key".to_string())? })
+    }
+
+    fn wrapping_key(&self,associated_data:&[u8])->[u8;32]{
+        let mut input=b"shamir-envelope".to_vec();
+        input.extend_from_slice(associated_data);
+        Sha256::hmac(&self.kek, &input)
+    }
+}
+
+impl KeyWrapper for ShamirKeyWrapper{
+
+    fn wrap_key(&self,dek:&[u8],associated_data:&[u8])->Result<Vec<u8>,String>{
+        let mut key=self.wrapping_key(associated_data);
+        let wrapped=HybridSS::encrypt(&key, 1, dek);
+        zeroize(&mut key);
+        wrapped
+    }
+
+    fn unwrap_key(&self,wrapped:&[u8],associated_data:&[u8])->Result<Vec<u8>,String>{
+        let mut key=self.wrapping_key(associated_data);
+        let dek=HybridSS::decrypt(&key, wrapped);
+        zeroize(&mut key);
+        dek
+    }
+}
+
+impl Drop for ShamirKeyWrapper{
+    fn drop(&mut self){
+        zeroize(&mut self.kek);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quorum_unwraps_keys_bound_to_their_associated_data() {
+        let (dealer, parts)=ShamirKeyWrapper::generate(3, 2).unwrap();
+        let wrapped=dealer.wrap_key(b"data encryption key", b"bucket/object-1").unwrap();
+        drop(dealer);
+
+        let two:Parts=parts.clone().into_iter().take(2).collect();
+        let wrapper=ShamirKeyWrapper::from_shares(two).unwrap();
+        assert_eq!(wrapper.unwrap_key(&wrapped, b"bucket/object-1").unwrap(), b"data encryption key");
+        assert!(wrapper.unwrap_key(&wrapped, b"bucket/object-2").is_err());
+
+        let (other, _)=ShamirKeyWrapper::generate(3, 2).unwrap();
+        assert!(other.unwrap_key(&wrapped, b"bucket/object-1").is_err());
+        let short=ShamirSS::split(3, 2, b"not a kek".to_vec()).unwrap();
+        assert!(ShamirKeyWrapper::from_shares(short).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum ShamirError{
+    ThresholdTooSmall,
+    ThresholdAboveShares,
+    TooManyShares,
+    NoParts,
+    InconsistentLengths,
+    InvalidIndex(i32),
+    DuplicateIndex(i32),
+    InsufficientParts { collected:usize, threshold:usize },
+    InconsistentShares(Vec<i32>),
+    RangeOutOfBounds { start:usize, end:usize, len:usize },
+    CheckFailed,
+    BufferSize { expected:usize, actual:usize },
+    UnknownScheme(u8),
+    SecretTooLarge { len:usize, max:usize },
+    InvalidChunkSize,
+    JoinDenied(String),
+    KdfOutputTooLong { requested:usize, max:usize },
+    EmptySecret,
+    MislabeledShares(Vec<(i32,i32)>),
+    NotInvertible,
+    ForeignShares(Vec<i32>),
+    InvalidShares(Vec<i32>),
+}
+
+impl fmt::Display for ShamirError{
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        match self {
+            ShamirError::ThresholdTooSmall => f.write_str("Not k > 1"),
+            ShamirError::ThresholdAboveShares => f.write_str("Not n >= k"),
+            ShamirError::TooManyShares => f.write_str("Not n <= 255"),
+            ShamirError::NoParts => f.write_str("No parts provided"),
+            ShamirError::InconsistentLengths => f.write_str("Varying lengths of part values"),
+            ShamirError::InvalidIndex(index) => write!(f, "Invalid share index {index} (must be 1..=255)"),
+            ShamirError::DuplicateIndex(index) => write!(f, "Duplicate share index {index}"),
+            ShamirError::InsufficientParts { collected, threshold } => write!(f, "Only {collected} of {threshold} required parts provided"),
+            ShamirError::InconsistentShares(indices) => write!(f, "Shares {indices:?} are inconsistent with the others"),
+            ShamirError::RangeOutOfBounds { start, end, len } => write!(f, "Byte range {start}..{end} is outside the {len}-byte secret"),
+            ShamirError::CheckFailed => f.write_str("Shares do not match the published check value"),
+            ShamirError::BufferSize { expected, actual } => write!(f, "Buffer holds {actual} entries, {expected} required"),
+            ShamirError::UnknownScheme(id) => write!(f, "Unknown secret sharing scheme id {id}"),
+            ShamirError::SecretTooLarge { len, max } => write!(f, "Secret of {len} bytes exceeds the {max}-byte limit"),
+            ShamirError::InvalidChunkSize => f.write_str("Chunk size must be at least 1 byte"),
+            ShamirError::JoinDenied(reason) => write!(f, "Join not authorized: {reason}"),
+            ShamirError::KdfOutputTooLong { requested, max } => write!(f, "Requested {requested} bytes of key material, HKDF gives at most {max}"),
+            ShamirError::EmptySecret => f.write_str("Secret and shares must be at least 1 byte"),
+            ShamirError::MislabeledShares(fixes) => {
+                f.write_str("Shares appear to carry the wrong index:")?;
+                for (i, (labeled, likely)) in fixes.iter().enumerate() {
+                    write!(f, "{} {labeled} looks like share {likely}", if i==0 { "" } else { "," })?;
+                }
+                Ok(())
+            }
+            ShamirError::NotInvertible => f.write_str("Non-zero field element has no inverse; the field fails conformance"),
+            ShamirError::ForeignShares(indices) => write!(f, "Shares {indices:?} belong to a different split"),
+            ShamirError::InvalidShares(indices) => write!(f, "Shares {indices:?} fail their integrity check"),
+        }
+    }
+}
+
+impl ShamirError{
+
+    // Stable numeric codes for FFI callers. Codes are never reused or
+    // renumbered; new variants take the next number. 0 means success. The
+    // match has no wildcard arm, so a new variant does not compile until it
+    // is given a code.
+    pub fn code(&self)->i32{
+        match self {
+            ShamirError::ThresholdTooSmall => 1,
+            ShamirError::ThresholdAboveShares => 2,
+            ShamirError::TooManyShares => 3,
+            ShamirError::NoParts => 4,
+            ShamirError::InconsistentLengths => 5,
+            ShamirError::InvalidIndex(_) => 6,
+            ShamirError::DuplicateIndex(_) => 7,
+            ShamirError::InsufficientParts { .. } => 8,
+            ShamirError::InconsistentShares(_) => 9,
+            ShamirError::RangeOutOfBounds { .. } => 10,
+            ShamirError::CheckFailed => 11,
+            ShamirError::BufferSize { .. } => 12,
+            ShamirError::UnknownScheme(_) => 13,
+            ShamirError::SecretTooLarge { .. } => 14,
+            ShamirError::InvalidChunkSize => 15,
+            ShamirError::JoinDenied(_) => 16,
+            ShamirError::KdfOutputTooLong { .. } => 17,
+            ShamirError::EmptySecret => 18,
+            ShamirError::MislabeledShares(_) => 19,
+            ShamirError::NotInvertible => 20,
+            ShamirError::ForeignShares(_) => 21,
+            ShamirError::InvalidShares(_) => 22,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ShamirError {}
+
+impl From<ShamirError> for String{
+    fn from(err:ShamirError)->String{
+        err.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    // The published codes. The match has no wildcard arm either, so a new
+    // variant fails to compile here until its code is added to this table.
+    fn published(err:&ShamirError)->i32{
+        match err {
+            ShamirError::ThresholdTooSmall => 1,
+            ShamirError::ThresholdAboveShares => 2,
+            ShamirError::TooManyShares => 3,
+            ShamirError::NoParts => 4,
+            ShamirError::InconsistentLengths => 5,
+            ShamirError::InvalidIndex(_) => 6,
+            ShamirError::DuplicateIndex(_) => 7,
+            ShamirError::InsufficientParts { .. } => 8,
+            ShamirError::InconsistentShares(_) => 9,
+            ShamirError::RangeOutOfBounds { .. } => 10,
+            ShamirError::CheckFailed => 11,
+            ShamirError::BufferSize { .. } => 12,
+            ShamirError::UnknownScheme(_) => 13,
+            ShamirError::SecretTooLarge { .. } => 14,
+            ShamirError::InvalidChunkSize => 15,
+            ShamirError::JoinDenied(_) => 16,
+            ShamirError::KdfOutputTooLong { .. } => 17,
+            ShamirError::EmptySecret => 18,
+            ShamirError::MislabeledShares(_) => 19,
+            ShamirError::NotInvertible => 20,
+            ShamirError::ForeignShares(_) => 21,
+            ShamirError::InvalidShares(_) => 22,
+        }
+    }
+
+    // One sample per variant. The list and the match in `sample_of` come
+    // from the same arms, and the match has no wildcard: a new variant does
+    // not compile until it has an arm here, and so a place in `every`.
+    macro_rules! samples {
+        ($($variant:pat => $sample:expr,)*) => {
+            fn sample_of(err:&ShamirError)->ShamirError{
+                match err {
+                    $($variant => $sample,)*
+                }
+            }
+
+            fn every()->Vec<ShamirError>{
+                vec![$($sample,)*]
+            }
+        };
+    }
+
+    samples! {
+        ShamirError::ThresholdTooSmall => ShamirError::ThresholdTooSmall,
+        ShamirError::ThresholdAboveShares => ShamirError::ThresholdAboveShares,
+        ShamirError::TooManyShares => ShamirError::TooManyShares,
+        ShamirError::NoParts => ShamirError::NoParts,
+        ShamirError::InconsistentLengths => ShamirError::InconsistentLengths,
+        ShamirError::InvalidIndex(_) => ShamirError::InvalidIndex(0),
+        ShamirError::DuplicateIndex(_) => ShamirError::DuplicateIndex(1),
+        ShamirError::InsufficientParts { .. } => ShamirError::InsufficientParts { collected: 1, threshold: 2 },
+        ShamirError::InconsistentShares(_) => ShamirError::InconsistentShares(vec![1]),
+        ShamirError::RangeOutOfBounds { .. } => ShamirError::RangeOutOfBounds { start: 0, end: 2, len: 1 },
+        ShamirError::CheckFailed => ShamirError::CheckFailed,
+        ShamirError::BufferSize { .. } => ShamirError::BufferSize { expected: 2, actual: 1 },
+        ShamirError::UnknownScheme(_) => ShamirError::UnknownScheme(9),
+        ShamirError::SecretTooLarge { .. } => ShamirError::SecretTooLarge { len: 2, max: 1 },
+        ShamirError::InvalidChunkSize => ShamirError::InvalidChunkSize,
+        ShamirError::JoinDenied(_) => ShamirError::JoinDenied(String::new()),
+        ShamirError::KdfOutputTooLong { .. } => ShamirError::KdfOutputTooLong { requested: 9000, max: 8160 },
+        ShamirError::EmptySecret => ShamirError::EmptySecret,
+        ShamirError::MislabeledShares(_) => ShamirError::MislabeledShares(vec![(1, 2)]),
+        ShamirError::NotInvertible => ShamirError::NotInvertible,
+        ShamirError::ForeignShares(_) => ShamirError::ForeignShares(vec![1]),
+        ShamirError::InvalidShares(_) => ShamirError::InvalidShares(vec![1]),
+    }
+
+    #[test]
+    fn codes_are_unique_and_stable() {
+        let every=every();
+        // Each sample is listed under its own variant's arm.
+        for err in &every {
+            assert_eq!(core::mem::discriminant(&sample_of(err)), core::mem::discriminant(err));
+        }
+        let codes:Vec<i32>=every.iter().map(ShamirError::code).collect();
+        assert_eq!(codes, every.iter().map(published).collect::<Vec<_>>());
+        // Numbered from 1 without gaps: no code is shared and 0 stays free
+        // for success.
+        assert_eq!(codes, (1..=every.len() as i32).collect::<Vec<_>>());
+    }
+}
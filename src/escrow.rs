@@ -0,0 +1,321 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::guard::{JoinContext, JoinGuard};
+
+// Escrow across organizations: `required` of the listed organizations must
+// each bring their own internal quorum, e.g. "2 orgs out of 3, each with 2
+// of its 3 custodians". Written as
+//
+//   2 of acme=2/3, globex=2/3, initech=1/2
+//
+// `split` enforces this cryptographically with two levels of Shamir: the
+// secret is split `required` of n into one organization share per org (x =
+// the org's position in the policy, so the order is part of the policy),
+// and each organization share is split again, threshold of shares, among
+// that org's custodians. A quorum of 1 hands every custodian the whole
+// organization share. Shares are named by namespaced indices `org:index`;
+// an index only means something within its org, so acme:1 and globex:1 are
+// different shares.
+//
+// The policy is also a `JoinGuard` for flat splits where each org was given
+// its own indices of one polynomial (org per index in `ShareInfo::org`):
+// there it is governance only, as any k shares still reconstruct.
+
+#[derive(Debug,Clone,PartialEq,Eq,PartialOrd,Ord,Hash)]
+pub struct ShareId{
+    pub org:String,
+    pub index:i32,
+}
+
+impl ShareId{
+
+    pub fn parse(text:&str)->Result<ShareId,String>{
+        let (org, index)=text.trim().rsplit_once(':').ok_or(format!("Share id {text} is not org:index"))?;
+        let index=index.parse::<i32>().ok().filter(|i| (1..=255).contains(i)).ok_or(format!("Share id {text} has no index in 1..=255"))?;
+        if !valid_org(org) {
+            return Err(format!("Organization id {org} must be letters, digits, '.', '_' or '-'"));
+        }
+        Ok(ShareId { org: org.to_string(), index })
+    }
+}
+
+impl fmt::Display for ShareId{
+    fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+        write!(f, "{}:{}", self.org, self.index)
+    }
+}
+
+pub fn valid_org(org:&str)->bool{
+    !org.is_empty() && org.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'))
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct OrgQuorum{
+    pub org:String,
+    pub threshold:i32,
+    pub shares:i32,
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct EscrowPolicy{
+    pub required:i32,
+    pub orgs:Vec<OrgQuorum>,
+}
+
+// How the shares at hand measure up against a policy, org by org.
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct OrgStatus{
+    pub org:String,
+    pub present:i32,
+    pub threshold:i32,
+}
+
+impl OrgStatus{
+    pub fn met(&self)->bool{
+        self.present>=self.threshold
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct EscrowReport{
+    pub required:i32,
+    pub orgs:Vec<OrgStatus>,
+    // Shares of organizations the policy does not name, or past an org's count.
+    pub unknown:Vec<ShareId>,
+}
+
+impl EscrowReport{
+
+    pub fn met(&self)->Vec<&str>{
+        self.orgs.iter().filter(|o| o.met()).map(|o| o.org.as_str()).collect()
+    }
+
+    pub fn satisfied(&self)->bool{
+        self.met().len()>=self.required as usize
+    }
+
+    // Ok when satisfied, otherwise which orgs are short and by how much.
+    pub fn check(&self)->Result<(),String>{
+        if self.satisfied() {
+            return Ok(());
+        }
+        let short:Vec<String>=self.orgs.iter().filter(|o| !o.met())
+            .map(|o| format!("{} has {} of {}", o.org, o.present, o.threshold))
+            .collect();
+        Err(format!("{} of {} organizations reached their quorum, {} required ({})",
+            self.met().len(), self.orgs.len(), self.required, short.join(", ")))
+    }
+}
+
+impl EscrowPolicy{
+
+    pub fn parse(text:&str)->Result<EscrowPolicy,String>{
+        let malformed=|| format!("Escrow policy {text} is not \"M of org=K/N, ...\"");
+        let (required, orgs)=text.trim().split_once(" of ").ok_or_else(malformed)?;
+        let required=required.trim().parse::<i32>().map_err(|_| malformed())?;
+        let mut quorums=Vec::new();
+        for entry in orgs.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (org, quorum)=entry.split_once('=').ok_or_else(malformed)?;
+            let (threshold, shares)=quorum.split_once('/').ok_or_else(malformed)?;
+            quorums.push(OrgQuorum {
+                org: org.trim().to_string(),
+                threshold: threshold.trim().parse().map_err(|_| malformed())?,
+                shares: shares.trim().parse().map_err(|_| malformed())?,
+            });
+        }
+        let policy=EscrowPolicy { required, orgs: quorums };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn to_text(&self)->String{
+        let orgs:Vec<String>=self.orgs.iter().map(|o| format!("{}={}/{}", o.org, o.threshold, o.shares)).collect();
+        format!("{} of {}", self.required, orgs.join(", "))
+    }
+
+    pub fn validate(&self)->Result<(),String>{
+        if self.orgs.is_empty() || self.orgs.len()>255 {
+            return Err("An escrow policy names 1 to 255 organizations".to_string());
+        }
+        if self.required<1 || self.required as usize>self.orgs.len() {
+            return Err(format!("{} of {} organizations cannot be met", self.required, self.orgs.len()));
+        }
+        let mut seen=BTreeSet::new();
+        for o in &self.orgs {
+            if !valid_org(&o.org) {
+                return Err(format!("Organization id {} must be letters, digits, '.', '_' or '-'", o.org));
+            }
+            if !seen.insert(o.org.as_str()) {
+                return Err(format!("Organization {} is listed twice", o.org));
+            }
+            if o.threshold<1 || o.shares<o.threshold || o.shares>255 {
+                return Err(format!("Organization {} needs 1 <= quorum <= shares <= 255, not {}/{}", o.org, o.threshold, o.shares));
+            }
+        }
+        Ok(())
+    }
+
+    fn org(&self,org:&str)->Option<&OrgQuorum>{
+        self.orgs.iter().find(|o| o.org==org)
+    }
+
+    // Counts the distinct shares each org brings; indices beyond an org's
+    // share count, and orgs the policy does not name, count for nothing.
+    pub fn evaluate(&self,ids:&[ShareId])->EscrowReport{
+        let mut present:BTreeMap<&str,BTreeSet<i32>>=BTreeMap::new();
+        let mut unknown=Vec::new();
+        for id in ids {
+            match self.org(&id.org) {
+                Some(o) if (1..=o.shares).contains(&id.index) => {
+                    present.entry(o.org.as_str()).or_default().insert(id.index);
+                }
+                _ => unknown.push(id.clone()),
+            }
+        }
+        let counts=present.into_iter().map(|(org, indices)| (org, indices.len() as i32)).collect();
+        self.report(&counts, unknown)
+    }
+
+    fn report(&self,counts:&BTreeMap<&str,i32>,unknown:Vec<ShareId>)->EscrowReport{
+        EscrowReport {
+            required: self.required,
+            orgs: self.orgs.iter().map(|o| OrgStatus {
+                org: o.org.clone(),
+                present: counts.get(o.org.as_str()).copied().unwrap_or(0),
+                threshold: o.threshold,
+            }).collect(),
+            unknown,
+        }
+    }
+
+    // Two-level split; shares come out org by org in policy order.
+    pub fn split(&self,secret:&[u8])->Result<Vec<(ShareId,Vec<u8>)>,String>{
+        self.validate()?;
+        let mut org_shares=split_level(self.orgs.len() as i32, self.required, secret)?;
+        let mut out=Vec::new();
+        for (x, o) in (1..).zip(&self.orgs) {
+            let org_share=org_shares.get_mut(&x).ok_or("Organization share missing")?;
+            let parts=split_level(o.shares, o.threshold, org_share);
+            zeroize(org_share);
+            out.extend(parts?.into_iter().map(|(index, data)| (ShareId { org: o.org.clone(), index }, data)));
+        }
+        Ok(out)
+    }
+
+    // Rebuilds each org share from the orgs that met their quorum, then the
+    // secret from those. Fails with the evaluation's reasons before any
+    // interpolation when the policy is not met.
+    pub fn join(&self,shares:&[(ShareId,Vec<u8>)])->Result<Vec<u8>,String>{
+        self.validate()?;
+        let ids:Vec<ShareId>=shares.iter().map(|(id, _)| id.clone()).collect();
+        let report=self.evaluate(&ids);
+        if let Some(id)=report.unknown.first() {
+            return Err(format!("Share {id} is not part of this escrow"));
+        }
+        report.check()?;
+        let mut org_shares=Parts::new();
+        for (x, o) in (1..).zip(&self.orgs) {
+            let parts:Parts=shares.iter().filter(|(id, _)| id.org==o.org).map(|(id, data)| (id.index, data.clone())).collect();
+            if (parts.len() as i32)<o.threshold {
+                continue;
+            }
+            org_shares.insert(x, ShamirSS::join(parts).map_err(|e| format!("{}: {e}", o.org))?);
+        }
+        let secret=ShamirSS::join(org_shares.clone()).map_err(|e| e.to_string());
+        for share in org_shares.values_mut() {
+            zeroize(share);
+        }
+        secret
+    }
+}
+
+// k of n at one level. Shamir needs k >= 2; a quorum of 1 is every holder
+// getting the value itself, which joins like a degree-0 polynomial.
+fn split_level(n:i32,k:i32,secret:&[u8])->Result<Parts,String>{
+    if k==1 {
+        if secret.is_empty() {
+            return Err("Secret and shares must be at least 1 byte".to_string());
+        }
+        return Ok((1..=n).map(|x| (x, secret.to_vec())).collect());
+    }
+    ShamirSS::split(n, k, secret.to_vec()).map_err(|e| e.to_string())
+}
+
+// Grouped check over a flat split: `ShareInfo::org` says whose each share is.
+impl JoinGuard for EscrowPolicy{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        // Indices are global in a flat split, so shares are counted per org
+        // without the per-org index range `evaluate` applies.
+        let mut counts:BTreeMap<&str,i32>=BTreeMap::new();
+        for org in ctx.shares.iter().filter_map(|s| s.org.as_deref()) {
+            *counts.entry(org).or_default()+=1;
+        }
+        self.report(&counts, Vec::new()).check()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy()->EscrowPolicy{
+        EscrowPolicy::parse("2 of acme=2/3, globex=2/3, initech=1/2").unwrap()
+    }
+
+    fn take(shares:&[(ShareId,Vec<u8>)],ids:&[&str])->Vec<(ShareId,Vec<u8>)>{
+        ids.iter().map(|id| {
+            let id=ShareId::parse(id).unwrap();
+            shares.iter().find(|(share, _)| *share==id).unwrap().clone()
+        }).collect()
+    }
+
+    #[test]
+    fn policy_text_round_trips_and_validates() {
+        let policy=policy();
+        assert_eq!(policy.to_text(), "2 of acme=2/3, globex=2/3, initech=1/2");
+        assert_eq!(EscrowPolicy::parse(&policy.to_text()).unwrap(), policy);
+        for bad in ["2 acme=2/3", "4 of a=1/1, b=1/1, c=1/1", "1 of a=1/1, a=1/1", "1 of a=3/2", "1 of a b=1/1", "1 of a=1"] {
+            assert!(EscrowPolicy::parse(bad).is_err(), "{bad}");
+        }
+        assert_eq!(ShareId::parse("dept.eu:7").unwrap().to_string(), "dept.eu:7");
+        assert!(ShareId::parse("acme:0").is_err());
+        assert!(ShareId::parse("acme").is_err());
+    }
+
+    #[test]
+    fn two_orgs_each_with_their_quorum_recover() {
+        let policy=policy();
+        let shares=policy.split(b"escrowed").unwrap();
+        assert_eq!(shares.len(), 8);
+        assert_eq!(policy.join(&take(&shares, &["acme:1", "acme:3", "initech:2"])).unwrap(), b"escrowed");
+        assert_eq!(policy.join(&take(&shares, &["globex:2", "globex:1", "acme:2", "acme:3"])).unwrap(), b"escrowed");
+
+        let short=policy.join(&take(&shares, &["acme:1", "globex:1", "initech:1"])).unwrap_err();
+        assert!(short.contains("1 of 3 organizations reached their quorum, 2 required"), "{short}");
+        let mut stray=take(&shares, &["acme:1", "acme:2", "initech:1"]);
+        stray.push((ShareId::parse("umbrella:1").unwrap(), vec![0;8]));
+        assert!(policy.join(&stray).is_err());
+    }
+
+    #[test]
+    fn evaluate_counts_distinct_shares_in_range() {
+        let ids:Vec<ShareId>=["acme:1", "acme:1", "acme:4", "globex:1", "globex:2"].iter().map(|id| ShareId::parse(id).unwrap()).collect();
+        let report=policy().evaluate(&ids);
+        assert_eq!(report.met(), vec!["globex"]);
+        assert_eq!(report.orgs[0].present, 1);
+        assert_eq!(report.unknown, vec![ShareId::parse("acme:4").unwrap()]);
+        assert!(!report.satisfied());
+    }
+
+    #[test]
+    fn policy_guards_a_flat_split() {
+        let parts=ShamirSS::split(6, 2, b"secret".to_vec()).unwrap();
+        let orgs:BTreeMap<i32,String>=[(1, "acme"), (2, "acme"), (3, "globex"), (4, "initech")].iter().map(|(i, o)| (*i, o.to_string())).collect();
+        let subset=|indices:&[i32]| -> Parts { indices.iter().map(|i| (*i, parts[i].clone())).collect() };
+        let policy=policy();
+        assert!(policy.check(&JoinContext::new(&subset(&[1, 2, 4])).with_orgs(&orgs)).is_ok());
+        assert!(policy.check(&JoinContext::new(&subset(&[1, 3])).with_orgs(&orgs)).is_err());
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeMap;
+
+use crate::crypto::zeroize;
+use crate::error::ShamirError;
+use crate::session::{Progress, QuorumSession};
+use crate::sha256::Sha256;
+
+// Share exchange between phones during an in-person recovery: a custodian's
+// app sends its share to the coordinator's over whatever the platform offers
+// (a sequence of QR codes, NFC taps, Bluetooth LE writes). Those transports
+// carry small messages, lose some and may drop mid-transfer, so the share is
+// cut into frames that each carry their own checksum and position:
+//
+//   version (1) | transfer id (4) | sequence (u16 BE) | total (u16 BE)
+//   | chunk | first 4 bytes of SHA-256 over everything before
+//
+// The transfer id is the first 4 bytes of SHA-256 over the payload (the
+// share index byte followed by the share), so it both groups frames and
+// verifies the reassembled share. Frames arrive in any order and repeats are
+// ignored. To resume a broken transfer the receiver reports `missing` and the
+// sender re-sends just those frames. A receiver handles several senders at
+// once and hands completed shares to a `QuorumSession`.
+//
+// A receiver keeps at most MAX_PENDING_TRANSFERS unfinished transfers and
+// MAX_PENDING_BYTES of their chunks (`with_limits` changes both): frames
+// that claim a huge total, or from many made-up transfer ids, are refused
+// instead of growing its memory without bound.
+//
+// Frames are not encrypted: the transports are short-range and the meeting
+// is in person, but an application that needs confidentiality on the link
+// should wrap frames itself.
+
+const FRAME_VERSION:u8=1;
+const FRAME_HEADER_LEN:usize=9;
+const FRAME_CHECKSUM_LEN:usize=4;
+// Fits a version 10 QR code in binary mode, and a few BLE writes.
+pub const DEFAULT_CHUNK_LEN:usize=160;
+// A quorum meeting has a handful of custodians, each sending one share.
+pub const MAX_PENDING_TRANSFERS:usize=16;
+pub const MAX_PENDING_BYTES:usize=256*1024;
+
+pub type TransferId=[u8;4];
+
+fn transfer_id(payload:&[u8])->TransferId{
+    Sha256::digest(payload)[..4].try_into().unwrap()
+}
+
+pub struct ShareSender{
+    id:TransferId,
+    payload:Vec<u8>,
+    chunk_len:usize,
+}
+
+impl ShareSender{
+
+    pub fn new(index:i32,data:&[u8],chunk_len:usize)->Result<ShareSender,String>{
+        if !(1..=255).contains(&index) {
+            return Err(ShamirError::InvalidIndex(index).to_string());
+        }
+        if chunk_len==0 {
+            return Err("Chunk length must be at least 1 byte".to_string());
+        }
+        let mut payload=vec![index as u8];
+        payload.extend_from_slice(data);
+        if payload.len().div_ceil(chunk_len)>u16::MAX as usize {
+            zeroize(&mut payload);
+            return Err(format!("Share needs more than {} frames of {chunk_len} bytes", u16::MAX));
+        }
+        Ok(ShareSender { id: transfer_id(&payload), payload, chunk_len })
+    }
+
+    pub fn id(&self)->TransferId{
+        self.id
+    }
+
+    pub fn frame_count(&self)->u16{
+        self.payload.len().div_ceil(self.chunk_len) as u16
+    }
+
+    pub fn frame(&self,sequence:u16)->Option<Vec<u8>>{
+        let total=self.frame_count();
+        if sequence>=total {
+            return None;
+        }
+        let start=sequence as usize*self.chunk_len;
+        let chunk=&self.payload[start..(start+self.chunk_len).min(self.payload.len())];
+        let mut frame=Vec::with_capacity(FRAME_HEADER_LEN+chunk.len()+FRAME_CHECKSUM_LEN);
+        frame.push(FRAME_VERSION);
+        frame.extend_from_slice(&self.id);
+        frame.extend_from_slice(&sequence.to_be_bytes());
+        frame.extend_from_slice(&total.to_be_bytes());
+        frame.extend_from_slice(chunk);
+        let checksum=Sha256::digest(&frame);
+        frame.extend_from_slice(&checksum[..FRAME_CHECKSUM_LEN]);
+        Some(frame)
+    }
+
+    pub fn frames(&self)->Vec<Vec<u8>>{
+        (0..self.frame_count()).filter_map(|sequence| self.frame(sequence)).collect()
+    }
+}
+
+impl Drop for ShareSender{
+    fn drop(&mut self){
+        zeroize(&mut self.payload);
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum FrameStatus{
+    // Frame stored; `received` of `total` frames of this transfer so far.
+    Partial{transfer:TransferId,received:u16,total:u16},
+    // The last frame of a transfer; the share has been added to the session.
+    Complete{transfer:TransferId,index:i32,progress:Progress},
+    // Already had this frame, or the transfer is already complete.
+    Duplicate{transfer:TransferId},
+}
+
+struct Transfer{
+    total:u16,
+    chunks:BTreeMap<u16,Vec<u8>>,
+}
+
+pub struct ShareReceiver{
+    session:QuorumSession,
+    transfers:BTreeMap<TransferId,Transfer>,
+    completed:Vec<TransferId>,
+    max_transfers:usize,
+    max_bytes:usize,
+    pending_bytes:usize,
+}
+
+impl ShareReceiver{
+
+    pub fn new(session:QuorumSession)->ShareReceiver{
+        Self::with_limits(session, MAX_PENDING_TRANSFERS, MAX_PENDING_BYTES)
+    }
+
+    // A receiver holding at most `max_transfers` unfinished transfers and
+    // `max_bytes` of their chunks.
+    pub fn with_limits(session:QuorumSession,max_transfers:usize,max_bytes:usize)->ShareReceiver{
+        ShareReceiver { session, transfers: BTreeMap::new(), completed: Vec::new(), max_transfers, max_bytes, pending_bytes: 0 }
+    }
+
+    // Checks and stores one frame. A damaged frame is an error and leaves
+    // the transfer as it was, so the sender can simply send it again.
+    pub fn accept(&mut self,frame:&[u8])->Result<FrameStatus,String>{
+        if frame.len()<FRAME_HEADER_LEN+1+FRAME_CHECKSUM_LEN {
+            return Err("Frame is too short".to_string());
+        }
+        let (body, checksum)=frame.split_at(frame.len()-FRAME_CHECKSUM_LEN);
+        if Sha256::digest(body)[..FRAME_CHECKSUM_LEN]!=*checksum {
+            return Err("Frame checksum mismatch".to_string());
+        }
+        if body[0]!=FRAME_VERSION {
+            return Err(format!("Unsupported frame version {}", body[0]));
+        }
+        let id:TransferId=body[1..5].try_into().unwrap();
+        let sequence=u16::from_be_bytes([body[5], body[6]]);
+        let total=u16::from_be_bytes([body[7], body[8]]);
+        if sequence>=total {
+            return Err(format!("Frame {sequence} of a {total}-frame transfer"));
+        }
+        if self.completed.contains(&id) {
+            return Ok(FrameStatus::Duplicate { transfer: id });
+        }
+        if !self.transfers.contains_key(&id) && self.transfers.len()>=self.max_transfers {
+            return Err(format!("Already receiving {} transfers; finish or abandon one first", self.transfers.len()));
+        }
+        let chunk=&body[FRAME_HEADER_LEN..];
+        if let Some(transfer)=self.transfers.get(&id) {
+            if transfer.total!=total {
+                return Err("Frame disagrees with earlier frames on the transfer length".to_string());
+            }
+            if transfer.chunks.contains_key(&sequence) {
+                return Ok(FrameStatus::Duplicate { transfer: id });
+            }
+        }
+        if self.pending_bytes+chunk.len()>self.max_bytes {
+            return Err(format!("Pending transfers would exceed {} bytes", self.max_bytes));
+        }
+        let transfer=self.transfers.entry(id).or_insert_with(|| Transfer { total, chunks: BTreeMap::new() });
+        transfer.chunks.insert(sequence, chunk.to_vec());
+        self.pending_bytes+=chunk.len();
+        if transfer.chunks.len()<total as usize {
+            return Ok(FrameStatus::Partial { transfer: id, received: transfer.chunks.len() as u16, total });
+        }
+        self.complete(id)
+    }
+
+    fn complete(&mut self,id:TransferId)->Result<FrameStatus,String>{
+        let mut transfer=self.transfers.remove(&id).unwrap();
+        let mut payload:Vec<u8>=transfer.chunks.values().flatten().copied().collect();
+        self.pending_bytes-=payload.len();
+        for chunk in transfer.chunks.values_mut() {
+            zeroize(chunk);
+        }
+        if transfer_id(&payload)!=id {
+            zeroize(&mut payload);
+            return Err("Reassembled share does not match its transfer id; resend all frames".to_string());
+        }
+        let index=payload[0] as i32;
+        let result=self.session.add(index, payload[1..].to_vec());
+        zeroize(&mut payload);
+        let progress=result.map_err(|e| e.to_string())?;
+        self.completed.push(id);
+        Ok(FrameStatus::Complete { transfer: id, index, progress })
+    }
+
+    // Sequence numbers still to come for a transfer, for resuming it.
+    pub fn missing(&self,id:&TransferId)->Vec<u16>{
+        match self.transfers.get(id) {
+            Some(transfer) => (0..transfer.total).filter(|sequence| !transfer.chunks.contains_key(sequence)).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    // Drops an unfinished transfer and wipes its chunks, making room for
+    // another; its sender has to start over.
+    pub fn abandon(&mut self,id:&TransferId){
+        if let Some(mut transfer)=self.transfers.remove(id) {
+            for chunk in transfer.chunks.values_mut() {
+                self.pending_bytes-=chunk.len();
+                zeroize(chunk);
+            }
+        }
+    }
+
+    pub fn pending(&self)->Vec<TransferId>{
+        self.transfers.keys().copied().collect()
+    }
+
+    pub fn progress(&self)->Progress{
+        self.session.progress()
+    }
+
+    // Reconstructs once the session has its threshold; partial transfers
+    // are wiped.
+    pub fn finalize(&mut self)->Result<Vec<u8>,ShamirError>{
+        let secret=self.session.finalize()?;
+        self.wipe_partial();
+        Ok(secret)
+    }
+
+    fn wipe_partial(&mut self){
+        for transfer in self.transfers.values_mut() {
+            for chunk in transfer.chunks.values_mut() {
+                zeroize(chunk);
+            }
+        }
+        self.transfers.clear();
+        self.pending_bytes=0;
+    }
+}
+
+impl Drop for ShareReceiver{
+    fn drop(&mut self){
+        self.wipe_partial();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::ShamirSS;
+
+    fn receiver()->ShareReceiver{
+        ShareReceiver::new(QuorumSession::new(2).unwrap())
+    }
+
+    // A frame with a correct checksum over whatever header it is given.
+    fn framed(id:TransferId,sequence:u16,total:u16,chunk:&[u8])->Vec<u8>{
+        let mut frame=vec![FRAME_VERSION];
+        frame.extend_from_slice(&id);
+        frame.extend_from_slice(&sequence.to_be_bytes());
+        frame.extend_from_slice(&total.to_be_bytes());
+        frame.extend_from_slice(chunk);
+        let checksum=Sha256::digest(&frame);
+        frame.extend_from_slice(&checksum[..FRAME_CHECKSUM_LEN]);
+        frame
+    }
+
+    #[test]
+    fn shares_round_trip_in_any_order() {
+        let parts=ShamirSS::split(3, 2, b"exchange secret".to_vec()).unwrap();
+        let mut receiver=receiver();
+        for (index, data) in parts.iter().take(2) {
+            let sender=ShareSender::new(*index, data, 4).unwrap();
+            let mut frames=sender.frames();
+            frames.reverse();
+            let last=frames.pop().unwrap();
+            for frame in &frames {
+                assert!(matches!(receiver.accept(frame).unwrap(), FrameStatus::Partial { .. }));
+            }
+            assert!(matches!(receiver.accept(&last).unwrap(), FrameStatus::Complete { index: i, .. } if i==*index));
+            assert_eq!(receiver.accept(&last).unwrap(), FrameStatus::Duplicate { transfer: sender.id() });
+        }
+        assert_eq!(receiver.finalize().unwrap(), b"exchange secret");
+    }
+
+    #[test]
+    fn dropped_frame_is_resent_from_missing() {
+        let sender=ShareSender::new(1, b"a share long enough for frames", 5).unwrap();
+        let mut receiver=receiver();
+        for frame in sender.frames().iter().enumerate().filter(|(i, _)| *i!=2).map(|(_, f)| f) {
+            receiver.accept(frame).unwrap();
+        }
+        assert_eq!(receiver.missing(&sender.id()), vec![2]);
+        assert_eq!(receiver.pending(), vec![sender.id()]);
+        assert!(matches!(receiver.accept(&sender.frame(2).unwrap()).unwrap(), FrameStatus::Complete { index: 1, .. }));
+        assert!(receiver.missing(&sender.id()).is_empty());
+        assert!(receiver.pending().is_empty());
+    }
+
+    #[test]
+    fn corrupted_frame_is_refused_and_changes_nothing() {
+        let sender=ShareSender::new(1, b"share", 2).unwrap();
+        let mut receiver=receiver();
+        let mut frame=sender.frame(0).unwrap();
+        frame[FRAME_HEADER_LEN]^=1;
+        assert_eq!(receiver.accept(&frame).unwrap_err(), "Frame checksum mismatch");
+        assert!(receiver.pending().is_empty());
+        assert!(receiver.accept(&sender.frame(0).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn out_of_range_sequence_is_refused() {
+        let sender=ShareSender::new(1, b"share", 2).unwrap();
+        assert!(sender.frame(sender.frame_count()).is_none());
+        let mut receiver=receiver();
+        assert!(receiver.accept(&framed(sender.id(), 3, 3, b"xx")).is_err());
+        assert!(receiver.accept(&framed(sender.id(), 0, 0, b"xx")).is_err());
+        assert!(receiver.pending().is_empty());
+    }
+
+    #[test]
+    fn pending_transfers_are_capped() {
+        let mut receiver=ShareReceiver::with_limits(QuorumSession::new(2).unwrap(), 2, 8);
+        receiver.accept(&framed([1;4], 0, 9, b"abc")).unwrap();
+        receiver.accept(&framed([2;4], 0, 9, b"abc")).unwrap();
+        assert!(receiver.accept(&framed([3;4], 0, 9, b"abc")).is_err());
+        assert!(receiver.accept(&framed([1;4], 1, 9, b"abc")).is_err());
+        assert_eq!(receiver.pending(), vec![[1;4], [2;4]]);
+
+        receiver.abandon(&[1;4]);
+        receiver.accept(&framed([3;4], 0, 9, b"abc")).unwrap();
+        receiver.accept(&framed([3;4], 1, 9, b"ab")).unwrap();
+        assert_eq!(receiver.missing(&[3;4]), (2..9).collect::<Vec<u16>>());
+    }
+}
@@ -0,0 +1,182 @@
+// The safety contract of each extern function is in the comment above it.
+#![allow(clippy::missing_safety_doc)]
+
+use std::cell::RefCell;
+use std::ffi::{c_char, CString};
+
+use crate::crypto::{zeroize, ShamirSS};
+use crate::error::ShamirError;
+
+// C ABI, built as libshamir.so/.a by the `ffi/` crate and declared in
+// include/shamir.h. Functions return `ShamirError::code()` (0 on success)
+// and keep the message of the last failure on the calling thread,
+// retrievable with `shamir_last_error_message`. The pointer stays valid
+// until the next failing call on the same thread or
+// `shamir_clear_last_error`.
+//
+// Shares cross the boundary as records of 1 + L bytes for an L-byte secret:
+// the x-coordinate, then the payload. A NULL buffer with a non-zero length
+// fails as a buffer of 0 bytes.
+
+thread_local! {
+    static LAST_ERROR:RefCell<Option<(i32,CString)>>=const { RefCell::new(None) };
+}
+
+// Stores `err` as this thread's last error and returns its code, for use
+// as the return value of an extern "C" function.
+pub fn set_last_error(err:&ShamirError)->i32{
+    let code=err.code();
+    // Display output never contains NUL bytes; fall back to an empty message
+    // rather than panicking across the FFI boundary if it ever did.
+    let message=CString::new(err.to_string()).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut()=Some((code, message)));
+    code
+}
+
+// Code of this thread's last error, 0 if none.
+#[no_mangle]
+pub extern "C" fn shamir_last_error_code()->i32{
+    LAST_ERROR.with(|last| last.borrow().as_ref().map(|(code, _)| *code).unwrap_or(0))
+}
+
+// NUL-terminated UTF-8 message of this thread's last error, or NULL if none.
+#[no_mangle]
+pub extern "C" fn shamir_last_error_message()->*const c_char{
+    LAST_ERROR.with(|last| last.borrow().as_ref().map(|(_, message)| message.as_ptr()).unwrap_or(std::ptr::null()))
+}
+
+// Splits the `secret_len` bytes at `secret` into `n` share records with
+// threshold `k`, written to `shares`, which must hold exactly
+// n * (1 + secret_len) bytes. Nothing is written on failure.
+//
+// Safety: `secret` must be readable for `secret_len` bytes and `shares`
+// writable for `shares_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn shamir_split(n:i32,k:i32,secret:*const u8,secret_len:usize,shares:*mut u8,shares_len:usize)->i32{
+    let Some(secret)=input(secret, secret_len) else {
+        return set_last_error(&ShamirError::BufferSize { expected: secret_len, actual: 0 });
+    };
+    let Some(shares)=output(shares, shares_len) else {
+        return set_last_error(&ShamirError::BufferSize { expected: shares_len, actual: 0 });
+    };
+    let expected=usize::try_from(n).unwrap_or(0).saturating_mul(secret_len.saturating_add(1));
+    if (1..=255).contains(&n) && shares.len()!=expected {
+        return set_last_error(&ShamirError::BufferSize { expected, actual: shares.len() });
+    }
+    match ShamirSS::split_to_vec(n, k, secret) {
+        Ok(mut parts) => {
+            for (record, (x, y)) in shares.chunks_exact_mut(secret_len+1).zip(parts.iter_mut().map(|(x, y)| (*x, y))) {
+                let (index, payload)=record.split_at_mut(1);
+                index[0]=x;
+                payload.copy_from_slice(y);
+                zeroize(y);
+            }
+            0
+        }
+        Err(err) => set_last_error(&err),
+    }
+}
+
+// Joins `count` share records of `share_len` bytes each, read from
+// `shares`, into `secret`, which must hold share_len - 1 bytes.
+//
+// Safety: `shares` must be readable for count * share_len bytes and
+// `secret` writable for `secret_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn shamir_join(shares:*const u8,count:usize,share_len:usize,secret:*mut u8,secret_len:usize)->i32{
+    let total=count.saturating_mul(share_len);
+    let Some(shares)=input(shares, total) else {
+        return set_last_error(&ShamirError::BufferSize { expected: total, actual: 0 });
+    };
+    let Some(secret)=output(secret, secret_len) else {
+        return set_last_error(&ShamirError::BufferSize { expected: secret_len, actual: 0 });
+    };
+    if share_len<2 {
+        return set_last_error(&ShamirError::EmptySecret);
+    }
+    if secret.len()!=share_len-1 {
+        return set_last_error(&ShamirError::BufferSize { expected: share_len-1, actual: secret.len() });
+    }
+    let pairs:Vec<(u8,&[u8])>=shares.chunks_exact(share_len).map(|record| (record[0], &record[1..])).collect();
+    match ShamirSS::join_pairs(&pairs) {
+        Ok(mut joined) => {
+            secret.copy_from_slice(&joined);
+            zeroize(&mut joined);
+            0
+        }
+        Err(err) => set_last_error(&err),
+    }
+}
+
+// Safety: as for the extern functions above; NULL only with a length of 0.
+unsafe fn input<'a>(ptr:*const u8,len:usize)->Option<&'a [u8]>{
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, len) => Some(std::slice::from_raw_parts(ptr, len)),
+    }
+}
+
+unsafe fn output<'a>(ptr:*mut u8,len:usize)->Option<&'a mut [u8]>{
+    match (ptr.is_null(), len) {
+        (_, 0) => Some(&mut []),
+        (true, _) => None,
+        (false, len) => Some(std::slice::from_raw_parts_mut(ptr, len)),
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn shamir_clear_last_error(){
+    LAST_ERROR.with(|last| *last.borrow_mut()=None);
+}
+
+// Clears this thread's last error, zeroizing the message first.
+pub(crate) fn scrub_last_error(){
+    if let Some((_, message))=LAST_ERROR.with(|last| last.borrow_mut().take()) {
+        zeroize(&mut message.into_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn split_and_join_through_the_c_abi() {
+        let secret=b"hello secret";
+        let record=secret.len()+1;
+        let mut shares=vec![0u8;5*record];
+        assert_eq!(unsafe { shamir_split(5, 3, secret.as_ptr(), secret.len(), shares.as_mut_ptr(), shares.len()) }, 0);
+        assert_eq!(shares.chunks(record).map(|r| r[0]).collect::<Vec<_>>(), [1, 2, 3, 4, 5]);
+
+        // Any three records, in any order.
+        let picked:Vec<u8>=[4, 1, 2].iter().flat_map(|&i| shares[i*record..(i+1)*record].to_vec()).collect();
+        let mut out=vec![0u8;secret.len()];
+        assert_eq!(unsafe { shamir_join(picked.as_ptr(), 3, record, out.as_mut_ptr(), out.len()) }, 0);
+        assert_eq!(out, secret);
+    }
+
+    #[test]
+    fn failures_set_the_last_error() {
+        let secret=b"key";
+        let mut shares=vec![0u8;7];
+        let code=unsafe { shamir_split(2, 2, secret.as_ptr(), secret.len(), shares.as_mut_ptr(), shares.len()) };
+        assert_eq!(code, ShamirError::BufferSize { expected: 8, actual: 7 }.code());
+        assert_eq!(shamir_last_error_code(), code);
+        let message=unsafe { CStr::from_ptr(shamir_last_error_message()) };
+        assert_eq!(message.to_str().unwrap(), "Buffer holds 7 entries, 8 required");
+
+        let mut out=[0u8;3];
+        let code=unsafe { shamir_join(std::ptr::null(), 2, 4, out.as_mut_ptr(), out.len()) };
+        assert_eq!(code, ShamirError::BufferSize { expected: 8, actual: 0 }.code());
+
+        let twice=[1u8, 9, 9, 9, 1, 8, 8, 8];
+        let code=unsafe { shamir_join(twice.as_ptr(), 2, 4, out.as_mut_ptr(), out.len()) };
+        assert_eq!(code, ShamirError::DuplicateIndex(1).code());
+
+        shamir_clear_last_error();
+        assert_eq!(shamir_last_error_code(), 0);
+        assert!(shamir_last_error_message().is_null());
+    }
+}
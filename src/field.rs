@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+use rand::{Rng, RngCore};
+
+use crate::error::ShamirError;
+use crate::pure;
+use crate::tables::{mul_slow, POLYNOMIAL};
+
+// Checked field arithmetic for experimenting with other fields (GF(2^8)
+// under another reduction polynomial, GF(p), ...). `split` and `join` below
+// are Shamir's scheme over any `Field`; they are correct for an
+// implementation that passes `testutil::field_conformance`, which checks the
+// field axioms the scheme relies on. `ShamirSS` keeps its own GF(256) path;
+// `Gf256` here is the same field.
+//
+// Division is checked: `inv` and `checked_div` return None for zero instead
+// of panicking or returning garbage, so a field that is not one (a reducible
+// polynomial, a composite modulus) shows up as a missing inverse.
+
+pub trait Field:Copy+Eq+Debug{
+    // Number of elements.
+    const ORDER:u64;
+    const ZERO:Self;
+    const ONE:Self;
+
+    fn add(self,other:Self)->Self;
+    fn neg(self)->Self;
+    fn mul(self,other:Self)->Self;
+    // None for zero.
+    fn inv(self)->Option<Self>;
+    // Enumerates the field: `element(0)` is ZERO, `element(1)` is ONE and
+    // indices below ORDER give distinct elements. Share indices are mapped
+    // to x-coordinates with it.
+    fn element(index:u64)->Self;
+
+    fn sub(self,other:Self)->Self{
+        self.add(other.neg())
+    }
+
+    fn checked_div(self,other:Self)->Option<Self>{
+        other.inv().map(|inv| self.mul(inv))
+    }
+
+    fn random<R:RngCore+?Sized>(rng:&mut R)->Self{
+        Self::element(rng.gen_range(0..Self::ORDER))
+    }
+}
+
+// GF(2^8) modulo 0x11B, the field `ShamirSS` uses.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Gf256(pub u8);
+
+impl Field for Gf256{
+    const ORDER:u64=256;
+    const ZERO:Gf256=Gf256(0);
+    const ONE:Gf256=Gf256(1);
+
+    fn add(self,other:Gf256)->Gf256{
+        Gf256(pure::gf_add(self.0, other.0))
+    }
+
+    fn neg(self)->Gf256{
+        self
+    }
+
+    fn mul(self,other:Gf256)->Gf256{
+        Gf256(pure::gf_mul(self.0, other.0))
+    }
+
+    fn inv(self)->Option<Gf256>{
+        (self.0!=0).then(|| Gf256(pure::gf_div(1, self.0)))
+    }
+
+    fn element(index:u64)->Gf256{
+        Gf256(index as u8)
+    }
+}
+
+// GF(2^8) modulo any degree-8 polynomial (bit 8 set), multiplied bit by bit
+// and inverted by search: slow, for experiments only. Only an irreducible
+// polynomial gives a field.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Gf256Poly<const POLY:u16>(pub u8);
+
+// The default polynomial through the generic path, for comparing against
+// `Gf256`.
+pub type Gf256Aes=Gf256Poly<POLYNOMIAL>;
+
+impl<const POLY:u16> Field for Gf256Poly<POLY>{
+    const ORDER:u64=256;
+    const ZERO:Self=Gf256Poly(0);
+    const ONE:Self=Gf256Poly(1);
+
+    fn add(self,other:Self)->Self{
+        Gf256Poly(self.0^other.0)
+    }
+
+    fn neg(self)->Self{
+        self
+    }
+
+    fn mul(self,other:Self)->Self{
+        Gf256Poly(mul_slow(self.0, other.0, POLY))
+    }
+
+    fn inv(self)->Option<Self>{
+        (1..=255u8).find(|&b| mul_slow(self.0, b, POLY)==1).map(Gf256Poly)
+    }
+
+    fn element(index:u64)->Self{
+        Gf256Poly(index as u8)
+    }
+}
+
+// Integers modulo P, for P below 2^32 so products fit in a u64. Only a prime
+// P gives a field.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+pub struct Fp<const P:u64>(u64);
+
+impl<const P:u64> Fp<P>{
+    const MODULUS_FITS:()=assert!(P>=2 && P<=u32::MAX as u64, "Fp needs 2 <= P < 2^32");
+
+    pub fn new(value:u64)->Fp<P>{
+        #[allow(clippy::let_unit_value)]
+        let _=Self::MODULUS_FITS;
+        Fp(value%P)
+    }
+
+    pub fn value(self)->u64{
+        self.0
+    }
+}
+
+impl<const P:u64> Field for Fp<P>{
+    const ORDER:u64=P;
+    const ZERO:Self=Fp(0);
+    const ONE:Self=Fp(1);
+
+    fn add(self,other:Self)->Self{
+        Fp::new(self.0+other.0)
+    }
+
+    fn neg(self)->Self{
+        Fp::new(P-self.0)
+    }
+
+    fn mul(self,other:Self)->Self{
+        Fp::new(self.0*other.0)
+    }
+
+    // Extended Euclid; None when gcd(value, P) is not 1.
+    fn inv(self)->Option<Self>{
+        let (mut r0, mut r1)=(P as i128, self.0 as i128);
+        let (mut t0, mut t1)=(0i128, 1i128);
+        while r1!=0 {
+            let q=r0/r1;
+            (r0, r1)=(r1, r0-q*r1);
+            (t0, t1)=(t1, t0-q*t1);
+        }
+        (r0==1).then(|| Fp::new(t0.rem_euclid(P as i128) as u64))
+    }
+
+    fn element(index:u64)->Self{
+        Fp::new(index)
+    }
+}
+
+// Shamir's scheme over `F`: for each secret element a random polynomial of
+// degree k-1 (non-zero leading coefficient) with the secret as constant
+// term, evaluated at `F::element(index)` for each share index. Returns one
+// row per index.
+pub fn split<F:Field,R:RngCore+?Sized>(secret:&[F],k:usize,indices:&[u64],rng:&mut R)->Result<Vec<(u64,Vec<F>)>,ShamirError>{
+    if k<=1 {
+        return Err(ShamirError::ThresholdTooSmall);
+    }
+    if indices.len()<k {
+        return Err(ShamirError::ThresholdAboveShares);
+    }
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    let xs=check_indices::<F>(indices)?;
+    let mut rows:Vec<(u64,Vec<F>)>=indices.iter().map(|&index| (index, Vec::with_capacity(secret.len()))).collect();
+    let mut coefficients=vec![F::ZERO; k];
+    for &s in secret {
+        loop {
+            for c in coefficients.iter_mut() {
+                *c=F::random(rng);
+            }
+            if coefficients[k-1]!=F::ZERO {
+                break;
+            }
+        }
+        coefficients[0]=s;
+        for ((_, row), &x) in rows.iter_mut().zip(&xs) {
+            row.push(coefficients.iter().rev().fold(F::ZERO, |acc, &c| acc.mul(x).add(c)));
+        }
+    }
+    Ok(rows)
+}
+
+// Lagrange interpolation at zero through the given shares.
+pub fn join<F:Field>(shares:&[(u64,Vec<F>)])->Result<Vec<F>,ShamirError>{
+    if shares.is_empty() {
+        return Err(ShamirError::NoParts);
+    }
+    let indices:Vec<u64>=shares.iter().map(|(index, _)| *index).collect();
+    let xs=check_indices::<F>(&indices)?;
+    let len=shares[0].1.len();
+    if shares.iter().any(|(_, row)| row.len()!=len) {
+        return Err(ShamirError::InconsistentLengths);
+    }
+    if len==0 {
+        return Err(ShamirError::EmptySecret);
+    }
+    let mut weights=Vec::with_capacity(xs.len());
+    for (i, &xi) in xs.iter().enumerate() {
+        // l_i(0) = prod_{j != i} x_j / (x_j - x_i)
+        let mut li=F::ONE;
+        for (j, &xj) in xs.iter().enumerate() {
+            if i!=j {
+                li=li.mul(xj.checked_div(xj.sub(xi)).ok_or(ShamirError::NotInvertible)?);
+            }
+        }
+        weights.push(li);
+    }
+    Ok((0..len).map(|pos| shares.iter().zip(&weights).fold(F::ZERO, |acc, ((_, row), &w)| acc.add(w.mul(row[pos])))).collect())
+}
+
+fn check_indices<F:Field>(indices:&[u64])->Result<Vec<F>,ShamirError>{
+    let mut seen=HashSet::new();
+    for &index in indices {
+        if index==0 || index>=F::ORDER {
+            return Err(ShamirError::InvalidIndex(i32::try_from(index).unwrap_or(i32::MAX)));
+        }
+        if !seen.insert(index) {
+            return Err(ShamirError::DuplicateIndex(index as i32));
+        }
+    }
+    Ok(indices.iter().map(|&index| F::element(index)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{Parts, ShamirSS};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn split_and_join_over_other_fields() {
+        let rng=&mut StdRng::seed_from_u64(473);
+        let secret:Vec<Fp<65521>>=[12345, 0, 65520].into_iter().map(Fp::new).collect();
+        let rows=split(&secret, 3, &[1, 2, 3, 4, 5], rng).unwrap();
+        assert_eq!(join(&rows[1..4]).unwrap(), secret);
+        assert_ne!(join(&rows[..2]).unwrap(), secret);
+
+        let secret:Vec<Gf256Poly<0x11d>>=b"other polynomial".iter().map(|&b| Gf256Poly(b)).collect();
+        let rows=split(&secret, 2, &[7, 200], rng).unwrap();
+        assert_eq!(join(&rows).unwrap(), secret);
+    }
+
+    #[test]
+    fn gf256_shares_join_with_shamirss() {
+        let rng=&mut StdRng::seed_from_u64(1);
+        let secret:Vec<Gf256>=b"same field".iter().map(|&b| Gf256(b)).collect();
+        let rows=split(&secret, 2, &[1, 2, 3], rng).unwrap();
+        let parts:Parts=rows.iter().map(|(x, row)| (*x as i32, row.iter().map(|e| e.0).collect())).collect();
+        assert_eq!(ShamirSS::join(parts).unwrap(), b"same field");
+    }
+
+    #[test]
+    fn non_fields_fail_with_missing_inverses() {
+        // 2 and 4 differ by 2, which has no inverse modulo 6.
+        let shares=[(2, vec![Fp::<6>::new(1)]), (4, vec![Fp::<6>::new(3)])];
+        assert_eq!(join(&shares), Err(ShamirError::NotInvertible));
+        assert_eq!(Fp::<7>::new(3).inv(), Some(Fp::new(5)));
+        assert_eq!(Gf256::ZERO.inv(), None);
+        assert_eq!(Gf256Poly::<0x100>(2).inv(), None);
+
+        let rng=&mut StdRng::seed_from_u64(2);
+        assert_eq!(split(&[Fp::<7>::new(1)], 2, &[1, 7], rng), Err(ShamirError::InvalidIndex(7)));
+        assert_eq!(split(&[Fp::<7>::new(1)], 2, &[3, 3], rng), Err(ShamirError::DuplicateIndex(3)));
+        assert_eq!(split::<Fp<7>,_>(&[], 2, &[1, 2], rng), Err(ShamirError::EmptySecret));
+    }
+}
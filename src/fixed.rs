@@ -0,0 +1,158 @@
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)]
+
+use heapless::Vec;
+
+use crate::error::ShamirError;
+use crate::pure::{gf_add, gf_div, gf_mul};
+
+// Join without a heap, for bootloaders and secure elements that have no
+// allocator at all (feature `fixed-capacity`, which also builds with
+// `core-pure`). The maximum share count N and secret length L are const
+// generics, so the collections are `heapless` vectors sized at compile time:
+// `FixedShares<N, L>` takes N * (L + 1) bytes plus the vectors' lengths, on
+// the stack or in a static. Nothing here allocates, and only the error variants
+// without payload are ever returned.
+//
+//   let mut shares=FixedShares::<3, 32>::new();
+//   shares.push(1, &share_1)?;
+//   shares.push(3, &share_3)?;
+//   let secret=shares.join()?;   // FixedSecret<32>; secret.as_bytes()
+//
+// Both types zeroize their storage when dropped or cleared. Like `pure.rs`,
+// the module is linted free of indexing, unwrap and panic!.
+
+pub struct FixedShares<const N:usize,const L:usize>{
+    shares:Vec<(u8,Vec<u8,L>),N>,
+}
+
+impl<const N:usize,const L:usize> Default for FixedShares<N,L>{
+    fn default()->Self{
+        Self::new()
+    }
+}
+
+impl<const N:usize,const L:usize> FixedShares<N,L>{
+
+    pub const fn new()->Self{
+        const { assert!(N>0 && N<=255 && L>0, "FixedShares needs 1 <= N <= 255 and L >= 1") };
+        FixedShares { shares: Vec::new() }
+    }
+
+    // Adds the share at x. The first share fixes the secret length.
+    pub fn push(&mut self,x:u8,share:&[u8])->Result<(),ShamirError>{
+        if x==0 {
+            return Err(ShamirError::InvalidIndex(0));
+        }
+        if self.shares.iter().any(|&(seen, _)| seen==x) {
+            return Err(ShamirError::DuplicateIndex(x as i32));
+        }
+        if share.is_empty() {
+            return Err(ShamirError::EmptySecret);
+        }
+        let mut row=Vec::new();
+        if row.extend_from_slice(share).is_err() {
+            return Err(ShamirError::SecretTooLarge { len: share.len(), max: L });
+        }
+        if !self.is_empty() && share.len()!=self.secret_len() {
+            wipe(&mut row);
+            return Err(ShamirError::InconsistentLengths);
+        }
+        if let Err((_, mut row))=self.shares.push((x, row)) {
+            wipe(&mut row);
+            return Err(ShamirError::BufferSize { expected: N, actual: N+1 });
+        }
+        Ok(())
+    }
+
+    pub fn len(&self)->usize{
+        self.shares.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.shares.is_empty()
+    }
+
+    pub fn secret_len(&self)->usize{
+        self.shares.first().map_or(0, |(_, row)| row.len())
+    }
+
+    // Interpolates at 0 over every share pushed; as with `join_into`, the
+    // caller must have at least k of them.
+    pub fn join(&self)->Result<FixedSecret<L>,ShamirError>{
+        if self.shares.is_empty() {
+            return Err(ShamirError::NoParts);
+        }
+        // l_i(0) = prod_{j != i} x_j / (x_i - x_j), once for all bytes.
+        let mut weights:Vec<u8,N>=self.shares.iter().enumerate().map(|(i, &(xi, _))| {
+            self.shares.iter().enumerate().filter(|&(j, _)| j!=i).fold(1, |li, (_, &(xj, _))| gf_mul(li, gf_div(xj, gf_add(xi, xj))))
+        }).collect();
+        let bytes=(0..self.secret_len()).map(|pos| {
+            self.shares.iter().zip(&weights).fold(0, |acc, ((_, row), &w)| gf_add(acc, gf_mul(w, row.get(pos).copied().unwrap_or(0))))
+        }).collect();
+        wipe(&mut weights);
+        Ok(FixedSecret { bytes })
+    }
+
+    pub fn clear(&mut self){
+        for (x, row) in self.shares.iter_mut() {
+            wipe(core::slice::from_mut(x));
+            wipe(row);
+        }
+        self.shares.clear();
+    }
+}
+
+impl<const N:usize,const L:usize> Drop for FixedShares<N,L>{
+    fn drop(&mut self){
+        self.clear();
+    }
+}
+
+pub struct FixedSecret<const L:usize>{
+    bytes:Vec<u8,L>,
+}
+
+impl<const L:usize> FixedSecret<L>{
+
+    pub fn as_bytes(&self)->&[u8]{
+        &self.bytes
+    }
+}
+
+impl<const L:usize> Drop for FixedSecret<L>{
+    fn drop(&mut self){
+        wipe(&mut self.bytes);
+    }
+}
+
+// `crypto::zeroize` needs std; this is the same volatile loop.
+fn wipe(bytes:&mut [u8]){
+    for byte in bytes.iter_mut() {
+        // SAFETY: `byte` is a valid, aligned, exclusive reference.
+        unsafe { core::ptr::write_volatile(byte, 0) };
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    // 0x42 split 2-of-3 with a_1 = 0x17: shares f(x) = 0x42 ^ 0x17*x.
+    #[test]
+    fn joins_like_the_heap_path_and_rejects_overflows() {
+        let share=|x:u8| [gf_add(0x42, gf_mul(0x17, x)), gf_add(0x24, gf_mul(0x99, x))];
+        let mut shares=FixedShares::<2, 2>::new();
+        shares.push(3, &share(3)).unwrap();
+        assert_eq!(shares.push(3, &share(3)), Err(ShamirError::DuplicateIndex(3)));
+        assert_eq!(shares.push(1, &[1, 2, 3]), Err(ShamirError::SecretTooLarge { len: 3, max: 2 }));
+        assert_eq!(shares.push(1, &[1]), Err(ShamirError::InconsistentLengths));
+        shares.push(1, &share(1)).unwrap();
+        assert_eq!(shares.push(2, &share(2)), Err(ShamirError::BufferSize { expected: 2, actual: 3 }));
+        assert_eq!(shares.join().unwrap().as_bytes(), &[0x42, 0x24]);
+        shares.clear();
+        assert!(shares.is_empty() && shares.secret_len()==0);
+        assert_eq!(shares.join().err(), Some(ShamirError::NoParts));
+    }
+}
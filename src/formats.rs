@@ -0,0 +1,318 @@
+use base64::{engine::general_purpose, Engine as _};
+use ciborium::Value;
+
+use crate::encoding::{decode_any_share, decode_base32, decode_bech32, decode_compact_text_with, decode_hex, decode_share, encode_base32,
+    encode_bech32, encode_bundle, encode_compact_text, encode_hex, encode_share, ParseMode};
+use crate::json::{Json, JsonExt};
+use crate::plate::{decode_plate, encode_plate, DEFAULT_PLATE_WIDTH, PLATE_HEADER};
+use crate::transform::Pipeline;
+
+// Share encodings behind one trait, so applications can pick a format by
+// name and third parties can add their own (a QR payload, CBOR, a mnemonic)
+// without touching the core. `Formats` holds the built-in encodings plus any
+// registered ones and decodes by asking each, in order, whether it
+// recognizes the text.
+//
+// The split and join arithmetic never depends on this module; for a build
+// with no encodings or dependencies at all, use the `core-pure` feature.
+//
+// `convert` re-encodes a share from one form to another, for applications
+// that normalize user-supplied shares into one storage form. Besides the
+// text encodings it knows two binary forms by name, which the text-based
+// trait cannot hold: RAW (index byte || share bytes) and CBOR (a map
+// {"index": n, "share": h'...'}, the CBOR twin of the JSON encoding).
+
+pub trait ShareEncoding{
+    // Short lower-case identifier, e.g. "bech32".
+    fn name(&self)->&str;
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>;
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>;
+    // Whether `text` looks like this encoding; decoding may still fail.
+    fn detect(&self,text:&str)->bool;
+}
+
+// "<index>:<base64>", the form share files use.
+pub struct TextEncoding;
+
+impl ShareEncoding for TextEncoding{
+    fn name(&self)->&str{ "text" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ Ok(encode_share(index, data)) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{ decode_share(text) }
+    fn detect(&self,text:&str)->bool{
+        text.trim().split_once(':').is_some_and(|(index, _)| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+    }
+}
+
+pub struct Bech32Encoding;
+
+impl ShareEncoding for Bech32Encoding{
+    fn name(&self)->&str{ "bech32" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ Ok(encode_bech32(index, data)) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{ decode_bech32(text) }
+    fn detect(&self,text:&str)->bool{ text.trim().to_ascii_lowercase().starts_with("shamir1") }
+}
+
+// "shx" and lower-case hex of index || share; see `encoding.rs`.
+pub struct HexEncoding;
+
+impl ShareEncoding for HexEncoding{
+    fn name(&self)->&str{ "hex" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ Ok(encode_hex(index, data)) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{ decode_hex(text) }
+    fn detect(&self,text:&str)->bool{ text.trim().get(..3).is_some_and(|prefix| prefix.eq_ignore_ascii_case("shx")) }
+}
+
+// "SH32" and Crockford Base32 of index || share with a check symbol.
+pub struct Base32Encoding;
+
+impl ShareEncoding for Base32Encoding{
+    fn name(&self)->&str{ "base32" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ Ok(encode_base32(index, data)) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{ decode_base32(text) }
+    fn detect(&self,text:&str)->bool{ text.trim().get(..4).is_some_and(|prefix| prefix.eq_ignore_ascii_case("SH32")) }
+}
+
+// Fixed-header compact frame in base64; see `encoding.rs`.
+pub struct CompactEncoding;
+
+impl ShareEncoding for CompactEncoding{
+    fn name(&self)->&str{ "compact" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ Ok(encode_compact_text(index, data, Pipeline::default())) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{
+        let decoded=decode_compact_text_with(text, ParseMode::Lenient)?;
+        if !decoded.transform.is_empty() {
+            return Err("Compact share was transformed before splitting; decode it with decode_any_share".to_string());
+        }
+        Ok((decoded.index, decoded.data))
+    }
+    fn detect(&self,text:&str)->bool{ text.trim().starts_with("U0hD") }
+}
+
+pub struct BundleEncoding;
+
+impl ShareEncoding for BundleEncoding{
+    fn name(&self)->&str{ "bundle" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ Ok(encode_bundle(index, data)) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{ decode_any_share(text) }
+    fn detect(&self,text:&str)->bool{ text.trim().starts_with("shamir-share-bundle") }
+}
+
+pub struct PlateEncoding{
+    pub width:usize,
+}
+
+impl Default for PlateEncoding{
+    fn default()->Self{
+        PlateEncoding { width: DEFAULT_PLATE_WIDTH }
+    }
+}
+
+impl ShareEncoding for PlateEncoding{
+    fn name(&self)->&str{ "plate" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ encode_plate(index, data, self.width) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{ decode_plate(text) }
+    fn detect(&self,text:&str)->bool{
+        text.trim().get(..PLATE_HEADER.len()).is_some_and(|header| header.eq_ignore_ascii_case(PLATE_HEADER))
+    }
+}
+
+// {"index": 1, "share": "<base64>"}, for APIs that exchange JSON.
+pub struct JsonEncoding;
+
+impl ShareEncoding for JsonEncoding{
+    fn name(&self)->&str{ "json" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{
+        Ok(Json::object([("index", (index as i64).into()), ("share", general_purpose::STANDARD.encode(data).into())]).to_pretty())
+    }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{
+        let json=Json::parse(text)?;
+        let index=json.get("index").and_then(Json::as_i64).filter(|index| (1..=255).contains(index)).ok_or("JSON share has no valid index")?;
+        let share=json.get("share").and_then(Json::as_str).ok_or("JSON share has no share field")?;
+        let data=general_purpose::STANDARD.decode(share).map_err(|e| format!("Malformed share payload: {e}"))?;
+        Ok((index as i32, data))
+    }
+    fn detect(&self,text:&str)->bool{ text.trim_start().starts_with('{') }
+}
+
+// BIP-39 words in `language`, e.g. for a custodian who will read the share
+// aloud; see `mnemonic.rs`. Decoding recognizes any language.
+#[cfg(feature = "mnemonic")]
+pub struct MnemonicEncoding{
+    pub language:bip39::Language,
+}
+
+#[cfg(feature = "mnemonic")]
+impl Default for MnemonicEncoding{
+    fn default()->Self{
+        MnemonicEncoding { language: bip39::Language::English }
+    }
+}
+
+#[cfg(feature = "mnemonic")]
+impl ShareEncoding for MnemonicEncoding{
+    fn name(&self)->&str{ "mnemonic" }
+    fn encode(&self,index:i32,data:&[u8])->Result<String,String>{ crate::mnemonic::encode_mnemonic(index, data, self.language) }
+    fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{
+        crate::mnemonic::decode_mnemonic(text).map(|(index, data, _)| (index, data))
+    }
+    fn detect(&self,text:&str)->bool{ crate::mnemonic::looks_like_mnemonic(text) }
+}
+
+pub const RAW:&str="raw";
+pub const CBOR:&str="cbor";
+// For `convert`: detect the text encoding, as `decode` does.
+pub const AUTO:&str="auto";
+
+pub struct Formats{
+    encodings:Vec<Box<dyn ShareEncoding+Send+Sync>>,
+}
+
+impl Default for Formats{
+    fn default()->Self{
+        Formats::builtin()
+    }
+}
+
+impl Formats{
+
+    // Most specific first: a bundle also contains text and bech32 lines.
+    pub fn builtin()->Formats{
+        Formats { encodings: vec![
+            Box::new(BundleEncoding),
+            Box::new(PlateEncoding::default()),
+            Box::new(CompactEncoding),
+            Box::new(JsonEncoding),
+            Box::new(Bech32Encoding),
+            Box::new(HexEncoding),
+            Box::new(Base32Encoding),
+            Box::new(TextEncoding),
+            #[cfg(feature = "mnemonic")]
+            Box::new(MnemonicEncoding::default()),
+        ] }
+    }
+
+    // Adds an encoding, consulted before the ones already present. Replaces
+    // any encoding with the same name.
+    pub fn register(&mut self,encoding:Box<dyn ShareEncoding+Send+Sync>){
+        self.encodings.retain(|existing| existing.name()!=encoding.name());
+        self.encodings.insert(0, encoding);
+    }
+
+    pub fn names(&self)->Vec<&str>{
+        self.encodings.iter().map(|encoding| encoding.name()).collect()
+    }
+
+    pub fn get(&self,name:&str)->Option<&(dyn ShareEncoding+Send+Sync)>{
+        self.encodings.iter().find(|encoding| encoding.name()==name).map(|encoding| encoding.as_ref())
+    }
+
+    pub fn encode(&self,name:&str,index:i32,data:&[u8])->Result<String,String>{
+        self.get(name).ok_or(format!("Unknown share encoding {name}"))?.encode(index, data)
+    }
+
+    // Decodes with the first encoding that recognizes the text.
+    pub fn decode(&self,text:&str)->Result<(i32,Vec<u8>),String>{
+        self.encodings.iter().find(|encoding| encoding.detect(text))
+            .ok_or("Unrecognized share encoding".to_string())?
+            .decode(text)
+    }
+
+    // Re-encodes `share`, in the form named `from` (or AUTO), into the form
+    // named `to`. The index and share bytes pass through unchanged. Text
+    // encodings are read and written as UTF-8.
+    pub fn convert(&self,share:&[u8],from:&str,to:&str)->Result<Vec<u8>,String>{
+        let (index, data)=match from {
+            RAW => decode_raw(share)?,
+            CBOR => decode_cbor(share)?,
+            _ => {
+                let text=core::str::from_utf8(share).map_err(|_| format!("Share is not text; name its binary form ({RAW} or {CBOR})"))?;
+                match from {
+                    AUTO => self.decode(text)?,
+                    name => self.get(name).ok_or(format!("Unknown share encoding {name}"))?.decode(text)?,
+                }
+            }
+        };
+        match to {
+            RAW => Ok(encode_raw(index, &data)),
+            CBOR => encode_cbor(index, &data),
+            name => self.encode(name, index, &data).map(String::into_bytes),
+        }
+    }
+}
+
+// `Formats::builtin().convert`.
+pub fn convert(share:&[u8],from:&str,to:&str)->Result<Vec<u8>,String>{
+    Formats::builtin().convert(share, from, to)
+}
+
+fn encode_raw(index:i32,data:&[u8])->Vec<u8>{
+    let mut out=vec![index as u8];
+    out.extend_from_slice(data);
+    out
+}
+
+fn decode_raw(bytes:&[u8])->Result<(i32,Vec<u8>),String>{
+    match bytes.split_first() {
+        Some((&index, data)) if index!=0 && !data.is_empty() => Ok((index as i32, data.to_vec())),
+        _ => Err("Malformed raw share".to_string()),
+    }
+}
+
+fn encode_cbor(index:i32,data:&[u8])->Result<Vec<u8>,String>{
+    let share=Value::Map(vec![
+        (Value::Text("index".to_string()), Value::Integer(index.into())),
+        (Value::Text("share".to_string()), Value::Bytes(data.to_vec())),
+    ]);
+    let mut out=Vec::with_capacity(data.len()+20);
+    ciborium::into_writer(&share, &mut out).map_err(|e| format!("CBOR share: {e}"))?;
+    Ok(out)
+}
+
+fn decode_cbor(mut bytes:&[u8])->Result<(i32,Vec<u8>),String>{
+    let value:Value=ciborium::from_reader(&mut bytes).map_err(|e| format!("Malformed CBOR share: {e}"))?;
+    if !bytes.is_empty() {
+        return Err("Trailing bytes after CBOR share".to_string());
+    }
+    let Value::Map(fields)=value else {
+        return Err("CBOR share is not a map of index and share".to_string());
+    };
+    if fields.len()!=2 {
+        return Err("CBOR share is not a map of index and share".to_string());
+    }
+    let (mut index, mut share)=(None, None);
+    for (key, value) in fields {
+        match (key.as_text(), value) {
+            (Some("index"), Value::Integer(n)) if index.is_none() => index=u8::try_from(n).ok().filter(|&n| n!=0).map(i32::from),
+            (Some("share"), Value::Bytes(data)) if share.is_none() => share=Some(data),
+            _ => return Err("CBOR share has an unexpected or invalid field".to_string()),
+        }
+    }
+    match (index, share) {
+        (Some(index), Some(share)) if !share.is_empty() => Ok((index, share)),
+        _ => Err("CBOR share has no valid index or share".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cbor_round_trips_and_rejects_malformed_shares() {
+        let cbor=convert(b"\x03\xde\xad", RAW, CBOR).unwrap();
+        assert_eq!(cbor, b"\xa2\x65index\x03\x65share\x42\xde\xad");
+        assert_eq!(convert(&cbor, CBOR, RAW).unwrap(), b"\x03\xde\xad");
+        for bad in [
+            &b"\xa2\x65index\x03\x65share\x42\xde"[..],
+            b"\xa2\x65index\x03\x65share\x42\xde\xad\x00",
+            b"\xa2\x65index\x00\x65share\x42\xde\xad",
+            b"\xa2\x65index\x19\x01\x00\x65share\x42\xde\xad",
+            b"\xa2\x65index\x03\x65share\x40",
+            b"\xa2\x65index\x03\x65index\x03",
+            b"\xa1\x65index\x03",
+            b"\x82\x03\x42\xde\xad",
+        ] {
+            assert!(convert(bad, CBOR, RAW).is_err(), "{bad:x?}");
+        }
+    }
+}
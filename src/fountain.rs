@@ -0,0 +1,443 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::crypto::zeroize;
+use crate::error::ShamirError;
+use crate::sha256::Sha256;
+
+// Air-gap transfer of a share too large for one QR code, in the style of
+// Blockchain Commons' UR: the share is cut into equal fragments and sent as
+// an endless sequence of parts, shown as an animated QR code. Parts 1..=N
+// carry fragment 1..=N; every later part is the XOR of a pseudo-random
+// subset of fragments chosen from its sequence number (a Luby transform
+// fountain code), so a receiver that starts late or misses frames still
+// completes after scanning slightly more than N parts, in any order. Each
+// part is one line of QR alphanumeric text:
+//
+//   UR:SHAMIR-SHARE/<sequence>-<fragments>/<base32 body>
+//
+// where the body is
+//
+//   sequence (u32 BE) | fragments (u32 BE) | message length (u32 BE)
+//   | message CRC-32 (u32 BE) | fragment | CRC-32 of everything before
+//
+// and the message is the share index byte followed by the share, as in
+// `exchange.rs`. The message CRC-32 both seeds the fragment selection and
+// checks the reassembled share. Like exchange frames, parts are not
+// encrypted. The framing follows UR, but the body is not CBOR and the text is
+// not bytewords, so these parts are read by `FountainDecoder`, not by
+// generic UR wallets.
+
+pub const UR_TYPE:&str="SHAMIR-SHARE";
+// About 220 characters a part: a version 8 QR code at medium error correction.
+pub const DEFAULT_FRAGMENT_LEN:usize=100;
+// Keeps a forged part from making the decoder allocate without bound.
+pub const MAX_FRAGMENTS:usize=65_535;
+const BODY_HEADER_LEN:usize=16;
+const BODY_CHECKSUM_LEN:usize=4;
+const BASE32_CHARSET:&[u8;32]=b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+pub struct FountainEncoder{
+    message:Vec<u8>,
+    fragment_len:usize,
+    fragments:usize,
+    checksum:u32,
+}
+
+impl FountainEncoder{
+
+    pub fn new(index:i32,data:&[u8],max_fragment_len:usize)->Result<FountainEncoder,String>{
+        if !(1..=255).contains(&index) {
+            return Err(ShamirError::InvalidIndex(index).to_string());
+        }
+        if max_fragment_len==0 {
+            return Err("Fragment length must be at least 1 byte".to_string());
+        }
+        let mut message=vec![index as u8];
+        message.extend_from_slice(data);
+        // Equal fragments no longer than the maximum, with as little padding
+        // in the last one as possible.
+        let fragments=message.len().div_ceil(max_fragment_len);
+        if fragments>MAX_FRAGMENTS {
+            zeroize(&mut message);
+            return Err(format!("Share needs more than {MAX_FRAGMENTS} fragments of {max_fragment_len} bytes"));
+        }
+        let fragment_len=message.len().div_ceil(fragments);
+        let checksum=crc32(&message);
+        Ok(FountainEncoder { message, fragment_len, fragments, checksum })
+    }
+
+    pub fn fragment_count(&self)->usize{
+        self.fragments
+    }
+
+    // Part `sequence` (from 1) as a line of text; any sequence number is
+    // valid, the ones past the fragment count being mixed parts.
+    pub fn part(&self,sequence:u32)->String{
+        let mut fragment=vec![0u8; self.fragment_len];
+        for index in part_indexes(sequence, self.fragments, self.checksum) {
+            let start=index*self.fragment_len;
+            let end=(start+self.fragment_len).min(self.message.len());
+            for (f, b) in fragment.iter_mut().zip(&self.message[start.min(end)..end]) {
+                *f^=b;
+            }
+        }
+        let mut body=Vec::with_capacity(BODY_HEADER_LEN+self.fragment_len+BODY_CHECKSUM_LEN);
+        body.extend_from_slice(&sequence.to_be_bytes());
+        body.extend_from_slice(&(self.fragments as u32).to_be_bytes());
+        body.extend_from_slice(&(self.message.len() as u32).to_be_bytes());
+        body.extend_from_slice(&self.checksum.to_be_bytes());
+        body.extend_from_slice(&fragment);
+        zeroize(&mut fragment);
+        let checksum=crc32(&body);
+        body.extend_from_slice(&checksum.to_be_bytes());
+        let part=format!("UR:{UR_TYPE}/{sequence}-{}/{}", self.fragments, base32_encode(&body));
+        zeroize(&mut body);
+        part
+    }
+
+    // The first `count` parts: every fragment once, then mixed parts.
+    pub fn parts(&self,count:usize)->Vec<String>{
+        (1..=count.max(1) as u32).map(|sequence| self.part(sequence)).collect()
+    }
+}
+
+impl Drop for FountainEncoder{
+    fn drop(&mut self){
+        zeroize(&mut self.message);
+    }
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum PartStatus{
+    // Part used; `solved` of `fragments` fragments are known so far.
+    Progress{solved:usize,fragments:usize},
+    // Already had this part, or the share is already complete.
+    Duplicate,
+    // Every fragment is known and the share checks out; see `share`.
+    Complete{index:i32},
+}
+
+#[derive(Clone,Copy,PartialEq,Eq)]
+struct Header{
+    fragments:usize,
+    message_len:usize,
+    checksum:u32,
+    fragment_len:usize,
+}
+
+pub struct FountainDecoder{
+    header:Option<Header>,
+    solved:BTreeMap<usize,Vec<u8>>,
+    // Mixed parts not yet reduced to a single fragment.
+    pending:Vec<(BTreeSet<usize>,Vec<u8>)>,
+    seen:BTreeSet<u32>,
+    message:Option<Vec<u8>>,
+}
+
+impl Default for FountainDecoder{
+    fn default()->FountainDecoder{
+        FountainDecoder::new()
+    }
+}
+
+impl FountainDecoder{
+
+    pub fn new()->FountainDecoder{
+        FountainDecoder { header: None, solved: BTreeMap::new(), pending: Vec::new(), seen: BTreeSet::new(), message: None }
+    }
+
+    // Checks and uses one part. A damaged part, or one from another share,
+    // is an error and leaves the decoder as it was.
+    pub fn receive(&mut self,part:&str)->Result<PartStatus,String>{
+        let (sequence, header, fragment)=parse_part(part)?;
+        if let Some(expected)=self.header {
+            if header!=expected {
+                return Err("Part belongs to a different share or fragment size".to_string());
+            }
+        }
+        if self.message.is_some() || !self.seen.insert(sequence) {
+            return Ok(PartStatus::Duplicate);
+        }
+        self.header=Some(header);
+        let indexes=part_indexes(sequence, header.fragments, header.checksum).into_iter().collect();
+        self.add(indexes, fragment);
+        if self.solved.len()==header.fragments {
+            return self.complete(header);
+        }
+        Ok(PartStatus::Progress { solved: self.solved.len(), fragments: header.fragments })
+    }
+
+    // Reduces a part by the known fragments and solves whatever it and the
+    // pending parts then determine.
+    fn add(&mut self,indexes:BTreeSet<usize>,fragment:Vec<u8>){
+        let mut queue=vec![(indexes, fragment)];
+        while let Some((mut indexes, mut fragment))=queue.pop() {
+            self.reduce(&mut indexes, &mut fragment);
+            match indexes.len() {
+                0 => zeroize(&mut fragment),
+                1 => {
+                    let index=*indexes.first().unwrap();
+                    self.solved.insert(index, fragment);
+                    // Pending parts that contain the new fragment may now
+                    // come down to one.
+                    let (ready, waiting):(Vec<_>, Vec<_>)=std::mem::take(&mut self.pending).into_iter().partition(|(pending, _)| pending.contains(&index));
+                    self.pending=waiting;
+                    queue.extend(ready);
+                }
+                _ => self.pending.push((indexes, fragment)),
+            }
+        }
+    }
+
+    fn reduce(&self,indexes:&mut BTreeSet<usize>,fragment:&mut [u8]){
+        indexes.retain(|index| match self.solved.get(index) {
+            Some(known) => {
+                for (f, k) in fragment.iter_mut().zip(known) {
+                    *f^=k;
+                }
+                false
+            }
+            None => true,
+        });
+    }
+
+    fn complete(&mut self,header:Header)->Result<PartStatus,String>{
+        let mut message:Vec<u8>=self.solved.values().flatten().copied().collect();
+        message.truncate(header.message_len);
+        if crc32(&message)!=header.checksum || message.is_empty() || message[0]==0 {
+            zeroize(&mut message);
+            self.reset();
+            return Err("Reassembled share does not match its checksum; scan the frames again".to_string());
+        }
+        let index=message[0] as i32;
+        self.message=Some(message);
+        self.wipe_fragments();
+        Ok(PartStatus::Complete { index })
+    }
+
+    // Fragments known and in total; (0, 0) before the first part.
+    pub fn progress(&self)->(usize,usize){
+        (self.solved.len(), self.header.map(|header| header.fragments).unwrap_or(0))
+    }
+
+    pub fn is_complete(&self)->bool{
+        self.message.is_some()
+    }
+
+    // The share index and data once complete.
+    pub fn share(&self)->Option<(i32,Vec<u8>)>{
+        self.message.as_ref().map(|message| (message[0] as i32, message[1..].to_vec()))
+    }
+
+    fn wipe_fragments(&mut self){
+        for fragment in self.solved.values_mut().chain(self.pending.iter_mut().map(|(_, fragment)| fragment)) {
+            zeroize(fragment);
+        }
+        self.solved.clear();
+        self.pending.clear();
+    }
+
+    fn reset(&mut self){
+        self.wipe_fragments();
+        self.header=None;
+        self.seen.clear();
+    }
+}
+
+impl Drop for FountainDecoder{
+    fn drop(&mut self){
+        self.wipe_fragments();
+        if let Some(message)=&mut self.message {
+            zeroize(message);
+        }
+    }
+}
+
+fn parse_part(part:&str)->Result<(u32,Header,Vec<u8>),String>{
+    let part=part.trim().to_ascii_uppercase();
+    let rest=part.strip_prefix("UR:").and_then(|rest| rest.strip_prefix(UR_TYPE)).and_then(|rest| rest.strip_prefix('/'))
+        .ok_or(format!("Not a UR:{UR_TYPE} part"))?;
+    let (position, encoded)=rest.split_once('/').ok_or("Part has no sequence number")?;
+    let mut body=base32_decode(encoded).ok_or("Part body is not base32")?;
+    if body.len()<BODY_HEADER_LEN+1+BODY_CHECKSUM_LEN {
+        zeroize(&mut body);
+        return Err("Part is too short".to_string());
+    }
+    let (content, checksum)=body.split_at(body.len()-BODY_CHECKSUM_LEN);
+    if crc32(content).to_be_bytes()!=*checksum {
+        zeroize(&mut body);
+        return Err("Part checksum mismatch".to_string());
+    }
+    let word=|i:usize| u32::from_be_bytes(content[i*4..i*4+4].try_into().unwrap());
+    let (sequence, fragments, message_len, message_checksum)=(word(0), word(1), word(2), word(3));
+    let fragment=content[BODY_HEADER_LEN..].to_vec();
+    zeroize(&mut body);
+    if position!=format!("{sequence}-{fragments}") {
+        return Err(format!("Part is labelled {position} but carries {sequence}-{fragments}"));
+    }
+    let header=Header { fragments: fragments as usize, message_len: message_len as usize, checksum: message_checksum, fragment_len: fragment.len() };
+    if sequence==0 || fragments==0 || header.fragments>MAX_FRAGMENTS || message_len as usize>header.fragments*header.fragment_len || message_len as usize<=(header.fragments-1)*header.fragment_len {
+        return Err(format!("Part {sequence}-{fragments} has an inconsistent header"));
+    }
+    Ok((sequence, header, fragment))
+}
+
+// The fragments (from 0) XORed into part `sequence`: fragment sequence-1 for
+// the first `fragments` parts, then a random degree and subset from a
+// SHA-256 stream seeded with the sequence number and message checksum. The
+// degree follows the ideal soliton distribution, weights 1/d.
+fn part_indexes(sequence:u32,fragments:usize,checksum:u32)->Vec<usize>{
+    if sequence as usize<=fragments {
+        return vec![sequence as usize-1];
+    }
+    let mut seed=[0u8;8];
+    seed[..4].copy_from_slice(&sequence.to_be_bytes());
+    seed[4..].copy_from_slice(&checksum.to_be_bytes());
+    let mut stream=SeedStream { seed: Sha256::digest(&seed), counter: 0 };
+    let weights:Vec<u64>=(1..=fragments as u64).map(|degree| (1<<32)/degree).collect();
+    let mut pick=stream.below(weights.iter().sum());
+    let mut degree=1;
+    for (i, &weight) in weights.iter().enumerate() {
+        if pick<weight {
+            degree=i+1;
+            break;
+        }
+        pick-=weight;
+    }
+    let mut indexes:Vec<usize>=(0..fragments).collect();
+    for i in 0..degree {
+        let j=i+stream.below((fragments-i) as u64) as usize;
+        indexes.swap(i, j);
+    }
+    indexes.truncate(degree);
+    indexes
+}
+
+struct SeedStream{
+    seed:[u8;32],
+    counter:u32,
+}
+
+impl SeedStream{
+    // A value in 0..bound; the modulo bias is irrelevant here, only that
+    // sender and receiver agree.
+    fn below(&mut self,bound:u64)->u64{
+        let mut input=self.seed.to_vec();
+        input.extend_from_slice(&self.counter.to_be_bytes());
+        self.counter+=1;
+        let block=Sha256::digest(&input);
+        u64::from_be_bytes(block[..8].try_into().unwrap())%bound
+    }
+}
+
+// CRC-32 (IEEE 802.3, reflected, polynomial 0xEDB88320).
+pub fn crc32(data:&[u8])->u32{
+    let mut crc=!0u32;
+    for &b in data {
+        crc^=b as u32;
+        for _ in 0..8 {
+            crc=(crc>>1)^(0xEDB8_8320&(crc&1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+// RFC 4648 base32 without padding: upper case and digits only, so a part is
+// QR alphanumeric text.
+fn base32_encode(data:&[u8])->String{
+    let mut out=String::with_capacity(data.len().div_ceil(5)*8);
+    let (mut buffer, mut bits)=(0u32, 0);
+    for &b in data {
+        buffer=(buffer<<8|b as u32)&0xFFFF;
+        bits+=8;
+        while bits>=5 {
+            bits-=5;
+            out.push(BASE32_CHARSET[(buffer>>bits&31) as usize] as char);
+        }
+    }
+    if bits>0 {
+        out.push(BASE32_CHARSET[(buffer<<(5-bits)&31) as usize] as char);
+    }
+    out
+}
+
+fn base32_decode(text:&str)->Option<Vec<u8>>{
+    let mut out=Vec::with_capacity(text.len()*5/8);
+    let (mut buffer, mut bits)=(0u32, 0);
+    for c in text.bytes() {
+        let value=BASE32_CHARSET.iter().position(|&x| x==c)? as u32;
+        buffer=(buffer<<5|value)&0xFFFF;
+        bits+=5;
+        if bits>=8 {
+            bits-=8;
+            out.push((buffer>>bits) as u8);
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn share()->Vec<u8>{
+        (0..250u32).map(|i| (i*31+7) as u8).collect()
+    }
+
+    #[test]
+    fn crc32_and_base32_match_their_standards() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(base32_encode(b"foobar"), "MZXW6YTBOI");
+        assert_eq!(base32_decode("MZXW6YTBOI").unwrap(), b"foobar");
+        assert!(base32_decode("MZXW6YTBO1").is_none());
+    }
+
+    #[test]
+    fn late_receiver_completes_from_mixed_parts() {
+        let encoder=FountainEncoder::new(9, &share(), 40).unwrap();
+        assert_eq!(encoder.fragment_count(), 7);
+        let mut decoder=FountainDecoder::new();
+        let mut status=PartStatus::Duplicate;
+        // Starts after the plain fragments and sees only every other part.
+        for sequence in (5..2000).step_by(2) {
+            status=decoder.receive(&encoder.part(sequence).to_ascii_lowercase()).unwrap();
+            if decoder.is_complete() {
+                break;
+            }
+        }
+        assert_eq!(status, PartStatus::Complete { index: 9 });
+        assert_eq!(decoder.share(), Some((9, share())));
+        assert_eq!(decoder.receive(&encoder.part(1)).unwrap(), PartStatus::Duplicate);
+    }
+
+    #[test]
+    fn parts_in_order_complete_after_one_pass() {
+        let encoder=FountainEncoder::new(1, &share(), DEFAULT_FRAGMENT_LEN).unwrap();
+        let mut decoder=FountainDecoder::default();
+        let parts=encoder.parts(3);
+        assert_eq!(decoder.receive(&parts[0]).unwrap(), PartStatus::Progress { solved: 1, fragments: 3 });
+        assert_eq!(decoder.receive(&parts[0]).unwrap(), PartStatus::Duplicate);
+        assert_eq!(decoder.progress(), (1, 3));
+        decoder.receive(&parts[2]).unwrap();
+        assert_eq!(decoder.receive(&parts[1]).unwrap(), PartStatus::Complete { index: 1 });
+    }
+
+    #[test]
+    fn damaged_and_foreign_parts_are_refused() {
+        let encoder=FountainEncoder::new(2, &share(), 40).unwrap();
+        let other=FountainEncoder::new(3, &share(), 40).unwrap();
+        let mut decoder=FountainDecoder::new();
+        decoder.receive(&encoder.part(1)).unwrap();
+        assert!(decoder.receive(&other.part(2)).is_err());
+
+        let part=encoder.part(2);
+        let last=if part.ends_with('A') { "B" } else { "A" };
+        assert!(decoder.receive(&(part[..part.len()-1].to_string()+last)).is_err());
+        assert!(decoder.receive(&part.replacen("/2-7/", "/3-7/", 1)).is_err());
+        assert!(decoder.receive("UR:OTHER/1-1/AAAA").is_err());
+        assert_eq!(decoder.progress(), (1, 7));
+
+        assert!(FountainEncoder::new(0, &share(), 40).is_err());
+        assert!(FountainEncoder::new(1, &share(), 0).is_err());
+    }
+}
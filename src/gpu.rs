@@ -0,0 +1,375 @@
+use std::sync::mpsc;
+
+use rand::{Rng, RngCore};
+use wgpu::util::DeviceExt;
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::error::ShamirError;
+use crate::tables::{EXP, LOG};
+
+// Batch splitting on the GPU, for escrow migrations of millions of small
+// secrets. Coefficients are drawn on the CPU; the GPU only evaluates every
+// polynomial at x = 1..=n, one invocation per 4 secret bytes and share
+// (WGSL below, log/exp tables in a storage buffer). The secrets of a batch
+// are laid end to end and cut into chunks that fit the device's dispatch and
+// buffer limits.
+//
+// Every CROSS_CHECK_EVERY-th secret of a batch, and the last, is split again
+// on the CPU from the same coefficients and compared, so a miscompiled shader
+// or a faulty device fails the batch instead of handing out bad shares.
+// Without an adapter, `split_batch` takes the CPU path for the whole batch.
+//
+// GPU table lookups are not constant-time and device memory is not locked:
+// the buffers are cleared before they are dropped, but a secret that must not
+// leave the CPU should not use this module.
+
+pub const CROSS_CHECK_EVERY:usize=64;
+const WORKGROUP:u32=64;
+
+const SHADER:&str=r#"
+struct Params { positions: u32, k: u32, stride_words: u32, pad: u32 }
+@group(0) @binding(0) var<uniform> params: Params;
+// LOG (256 entries) then EXP (510 entries).
+@group(0) @binding(1) var<storage, read> tables: array<u32>;
+// Per secret byte: the byte, then its k-1 coefficients, lowest degree first.
+@group(0) @binding(2) var<storage, read> rows: array<u32>;
+// Share x-1 of every byte, 4 bytes per word, stride_words words per share.
+@group(0) @binding(3) var<storage, read_write> shares: array<u32>;
+
+fn byte_at(i: u32) -> u32 {
+    return (rows[i >> 2u] >> ((i & 3u) * 8u)) & 0xffu;
+}
+
+fn mul(a: u32, b: u32) -> u32 {
+    if (a == 0u || b == 0u) { return 0u; }
+    return tables[256u + tables[a] + tables[b]];
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let word = id.x;
+    let x = id.y + 1u;
+    if (word >= params.stride_words) { return; }
+    var out = 0u;
+    for (var b = 0u; b < 4u; b++) {
+        let p = word * 4u + b;
+        if (p >= params.positions) { break; }
+        var y = 0u;
+        var c = params.k;
+        loop {
+            if (c == 0u) { break; }
+            c -= 1u;
+            y = mul(y, x) ^ byte_at(p * params.k + c);
+        }
+        out |= y << (b * 8u);
+    }
+    shares[id.y * params.stride_words + word] = out;
+}
+"#;
+
+pub struct GpuSplitter{
+    device:wgpu::Device,
+    queue:wgpu::Queue,
+    pipeline:wgpu::ComputePipeline,
+    tables:wgpu::Buffer,
+    adapter:String,
+}
+
+impl GpuSplitter{
+
+    // The default adapter, high-performance preferred. Errs when there is no
+    // usable GPU (or software adapter).
+    pub fn new()->Result<GpuSplitter,String>{
+        let instance=wgpu::Instance::default();
+        let adapter=pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        })).map_err(|e| format!("No GPU adapter: {e}"))?;
+        let (device, queue)=pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default()))
+            .map_err(|e| format!("GPU device unavailable: {e}"))?;
+        let module=device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("shamir-split"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline=device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("shamir-split"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        let words:Vec<u8>=LOG.iter().chain(EXP.iter()).flat_map(|&v| (v as u32).to_le_bytes()).collect();
+        let tables=device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("shamir-tables"),
+            contents: &words,
+            usage: wgpu::BufferUsages::STORAGE,
+        });
+        Ok(GpuSplitter { device, queue, pipeline, tables, adapter: adapter.get_info().name })
+    }
+
+    pub fn adapter_name(&self)->&str{
+        &self.adapter
+    }
+
+    pub fn split_batch(&self,n:i32,k:i32,secrets:&[Vec<u8>])->Result<Vec<Parts>,String>{
+        check_dimensions(n, k).map_err(|e| e.to_string())?;
+        let mut coefficients=random_coefficients(k, secrets, &mut rand::thread_rng());
+        let parts=self.split_batch_with_coefficients(n, k, secrets, &coefficients);
+        zeroize(&mut coefficients);
+        parts
+    }
+
+    // As `split_batch_cpu`: `coefficients` holds, secret after secret, the
+    // rows `ShamirSS::split_with_coefficient_stream` reads.
+    pub fn split_batch_with_coefficients(&self,n:i32,k:i32,secrets:&[Vec<u8>],coefficients:&[u8])->Result<Vec<Parts>,String>{
+        check_dimensions(n, k).map_err(|e| e.to_string())?;
+        let total:usize=secrets.iter().map(Vec::len).sum();
+        let degree=(k-1) as usize;
+        if coefficients.len()!=total*degree {
+            return Err(ShamirError::BufferSize { expected: total*degree, actual: coefficients.len() }.to_string());
+        }
+        let (n, k)=(n as usize, k as usize);
+
+        // One row of k bytes per secret byte.
+        let mut rows=Vec::with_capacity(total*k);
+        for (byte, row) in secrets.iter().flatten().zip(coefficients.chunks_exact(degree.max(1))) {
+            rows.push(*byte);
+            rows.extend_from_slice(row);
+        }
+        let mut evaluated=vec![0u8;n*total];
+        let chunk=self.chunk_positions(n, k);
+        let mut start=0;
+        while start<total {
+            let end=(start+chunk).min(total);
+            let result=self.evaluate(&rows[start*k..end*k], k, n);
+            let mut values=match result {
+                Ok(values) => values,
+                Err(e) => {
+                    zeroize(&mut rows);
+                    zeroize(&mut evaluated);
+                    return Err(e);
+                }
+            };
+            let stride=(end-start).next_multiple_of(4);
+            for share in 0..n {
+                evaluated[share*total+start..share*total+end].copy_from_slice(&values[share*stride..share*stride+end-start]);
+            }
+            zeroize(&mut values);
+            start=end;
+        }
+        zeroize(&mut rows);
+
+        let mut batch=Vec::with_capacity(secrets.len());
+        let mut offset=0;
+        for secret in secrets {
+            let parts:Parts=(0..n).map(|share| {
+                let from=share*total+offset;
+                ((share+1) as i32, evaluated[from..from+secret.len()].to_vec())
+            }).collect();
+            batch.push(parts);
+            offset+=secret.len();
+        }
+        zeroize(&mut evaluated);
+        cross_check(n as i32, k as i32, secrets, coefficients, &batch)?;
+        Ok(batch)
+    }
+
+    // Positions per dispatch: within the workgroup count limit, and with the
+    // rows and shares within the storage binding limit.
+    fn chunk_positions(&self,n:usize,k:usize)->usize{
+        let limits=self.device.limits();
+        let binding=limits.max_storage_buffer_binding_size.min(limits.max_buffer_size) as usize;
+        let dispatch=limits.max_compute_workgroups_per_dimension as usize*WORKGROUP as usize*4;
+        (dispatch.min(binding/k.max(n)) & !3).max(4)
+    }
+
+    // The shares of `rows` (k bytes per position) at x = 1..=n, n rows of
+    // the position count rounded up to 4.
+    fn evaluate(&self,rows:&[u8],k:usize,n:usize)->Result<Vec<u8>,String>{
+        let positions=rows.len()/k;
+        let stride_words=positions.div_ceil(4);
+        let mut padded=rows.to_vec();
+        padded.resize(rows.len().next_multiple_of(4), 0);
+        let params:Vec<u8>=[positions as u32, k as u32, stride_words as u32, 0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        let init=|label, contents:&[u8], usage| self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor { label: Some(label), contents, usage });
+        let params=init("shamir-params", &params, wgpu::BufferUsages::UNIFORM);
+        let input=init("shamir-rows", &padded, wgpu::BufferUsages::STORAGE|wgpu::BufferUsages::COPY_DST);
+        zeroize(&mut padded);
+        let size=(n*stride_words*4) as u64;
+        let output=self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("shamir-shares"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE|wgpu::BufferUsages::COPY_SRC|wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let staging=self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("shamir-readback"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ|wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group=self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shamir-split"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry { binding: 0, resource: params.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 1, resource: self.tables.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 2, resource: input.as_entire_binding() },
+                wgpu::BindGroupEntry { binding: 3, resource: output.as_entire_binding() },
+            ],
+        });
+
+        let mut encoder=self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: Some("shamir-split") });
+        {
+            let mut pass=encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: Some("shamir-split"), timestamp_writes: None });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups((stride_words as u32).div_ceil(WORKGROUP), n as u32, 1);
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, size);
+        // Nothing secret stays in device memory once the shares are copied.
+        encoder.clear_buffer(&input, 0, None);
+        encoder.clear_buffer(&output, 0, None);
+        self.queue.submit([encoder.finish()]);
+
+        let slice=staging.slice(..);
+        let (sender, receiver)=mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| { let _=sender.send(result); });
+        self.device.poll(wgpu::PollType::wait_indefinitely()).map_err(|e| format!("GPU poll failed: {e}"))?;
+        receiver.recv().map_err(|e| e.to_string())?.map_err(|e| format!("GPU readback failed: {e}"))?;
+        let values=slice.get_mapped_range().map_err(|e| format!("GPU readback failed: {e:?}"))?.to_vec();
+        staging.unmap();
+        Ok(values)
+    }
+}
+
+fn check_dimensions(n:i32,k:i32)->Result<(),ShamirError>{
+    if k<=1 {
+        return Err(ShamirError::ThresholdTooSmall);
+    }
+    if n<k {
+        return Err(ShamirError::ThresholdAboveShares);
+    }
+    if n>255 {
+        return Err(ShamirError::TooManyShares);
+    }
+    Ok(())
+}
+
+// Uniform coefficients, with the top one of every byte nonzero as in
+// `ShamirSS::split`, so no byte has a lower threshold.
+pub fn random_coefficients<R:RngCore+?Sized>(k:i32,secrets:&[Vec<u8>],rng:&mut R)->Vec<u8>{
+    let degree=(k.max(2)-1) as usize;
+    let total:usize=secrets.iter().map(Vec::len).sum();
+    let mut coefficients=vec![0u8;total*degree];
+    rng.fill_bytes(&mut coefficients);
+    for row in coefficients.chunks_exact_mut(degree) {
+        if let Some(top)=row.last_mut() {
+            while *top==0 {
+                *top=rng.gen();
+            }
+        }
+    }
+    coefficients
+}
+
+// The CPU path, from the same coefficient layout as the GPU.
+pub fn split_batch_cpu(n:i32,k:i32,secrets:&[Vec<u8>],coefficients:&[u8])->Result<Vec<Parts>,String>{
+    check_dimensions(n, k).map_err(|e| e.to_string())?;
+    let degree=(k-1) as usize;
+    let total:usize=secrets.iter().map(Vec::len).sum();
+    if coefficients.len()!=total*degree {
+        return Err(ShamirError::BufferSize { expected: total*degree, actual: coefficients.len() }.to_string());
+    }
+    let xs:Vec<u8>=(1..=n as u8).collect();
+    let mut offset=0;
+    secrets.iter().map(|secret| {
+        let rows=&coefficients[offset..offset+secret.len()*degree];
+        offset+=secret.len()*degree;
+        ShamirSS::split_with_coefficient_stream(&xs, k, secret, |buf| buf.copy_from_slice(rows)).map_err(|e| e.to_string())
+    }).collect()
+}
+
+fn cross_check(n:i32,k:i32,secrets:&[Vec<u8>],coefficients:&[u8],batch:&[Parts])->Result<(),String>{
+    let degree=(k-1) as usize;
+    let mut offset=0;
+    for (position, secret) in secrets.iter().enumerate() {
+        let rows=&coefficients[offset..offset+secret.len()*degree];
+        offset+=secret.len()*degree;
+        if position%CROSS_CHECK_EVERY!=0 && position+1!=secrets.len() {
+            continue;
+        }
+        let expected=split_batch_cpu(n, k, std::slice::from_ref(secret), rows)?;
+        if expected.first()!=batch.get(position) {
+            return Err(format!("GPU shares of secret {position} disagree with the CPU; split this batch with split_batch_cpu"));
+        }
+    }
+    Ok(())
+}
+
+// Splits on the GPU when there is one, on the CPU otherwise.
+pub fn split_batch(n:i32,k:i32,secrets:&[Vec<u8>])->Result<Vec<Parts>,String>{
+    match GpuSplitter::new() {
+        Ok(gpu) => gpu.split_batch(n, k, secrets),
+        Err(_) => {
+            check_dimensions(n, k).map_err(|e| e.to_string())?;
+            let mut coefficients=random_coefficients(k, secrets, &mut rand::thread_rng());
+            let batch=split_batch_cpu(n, k, secrets, &coefficients);
+            zeroize(&mut coefficients);
+            batch
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn batch(count:usize)->Vec<Vec<u8>>{
+        (0..count).map(|i| (0..(i%40)+1).map(|j| (i*31+j*7) as u8).collect()).collect()
+    }
+
+    #[test]
+    fn cpu_path_joins_and_matches_per_secret_splits() {
+        let secrets=batch(100);
+        let coefficients=random_coefficients(3, &secrets, &mut ChaCha20Rng::seed_from_u64(427));
+        let shares=split_batch_cpu(5, 3, &secrets, &coefficients).unwrap();
+        for (secret, parts) in secrets.iter().zip(&shares) {
+            let subset:Parts=parts.iter().skip(2).map(|(i, d)| (*i, d.clone())).collect();
+            assert_eq!(&ShamirSS::join(subset).unwrap(), secret);
+        }
+        assert!(cross_check(5, 3, &secrets, &coefficients, &shares).is_ok());
+        assert!(split_batch_cpu(5, 3, &secrets, &coefficients[1..]).is_err());
+        assert!(split_batch_cpu(2, 3, &secrets, &coefficients).is_err());
+    }
+
+    #[test]
+    fn cross_check_catches_a_wrong_share() {
+        let secrets=batch(CROSS_CHECK_EVERY+1);
+        let coefficients=random_coefficients(2, &secrets, &mut ChaCha20Rng::seed_from_u64(1));
+        let mut shares=split_batch_cpu(3, 2, &secrets, &coefficients).unwrap();
+        if let Some(byte)=shares[CROSS_CHECK_EVERY].get_mut(&2).and_then(|d| d.first_mut()) {
+            *byte^=1;
+        }
+        assert!(cross_check(3, 2, &secrets, &coefficients, &shares).is_err());
+    }
+
+    #[test]
+    fn gpu_matches_cpu_when_an_adapter_exists() {
+        let Ok(gpu)=GpuSplitter::new() else {
+            eprintln!("no GPU adapter; only the CPU path was tested");
+            return;
+        };
+        let secrets=batch(1000);
+        for k in [2, 3, 7] {
+            let coefficients=random_coefficients(k, &secrets, &mut ChaCha20Rng::seed_from_u64(k as u64));
+            let cpu=split_batch_cpu(9, k, &secrets, &coefficients).unwrap();
+            assert_eq!(gpu.split_batch_with_coefficients(9, k, &secrets, &coefficients).unwrap(), cpu, "{}", gpu.adapter_name());
+        }
+        let shares=split_batch(9, 5, &secrets).unwrap();
+        assert_eq!(ShamirSS::join(shares[999].clone()).unwrap(), secrets[999]);
+    }
+}
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::crypto::{constant_time_eq, Parts, ShamirSS};
+use crate::encoding::share_fingerprint;
+use crate::error::ShamirError;
+use crate::sha256::{to_hex, Sha256};
+
+// Join authorization. Integrators express recovery governance as a
+// `JoinGuard` evaluated against metadata about the assembled shares before
+// anything is reconstructed; `AllOf` and `AnyOf` compose guards. A closure
+// `Fn(&JoinContext)->Result<(),String>` is itself a guard.
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ShareInfo{
+    pub index:i32,
+    pub fingerprint:String,
+    pub label:Option<String>,
+    // Owning organization, for escrow policies (see `escrow.rs`).
+    pub org:Option<String>,
+}
+
+#[derive(Debug,Clone)]
+pub struct JoinContext{
+    pub shares:Vec<ShareInfo>,
+    pub approvals:Vec<String>,
+    pub now:SystemTime,
+}
+
+impl JoinContext{
+
+    pub fn new(parts:&Parts)->JoinContext{
+        JoinContext {
+            shares: parts.iter().map(|(index, data)| ShareInfo { index: *index, fingerprint: share_fingerprint(*index, data), label: None, org: None }).collect(),
+            approvals: Vec::new(),
+            now: SystemTime::now(),
+        }
+    }
+
+    // Custodian labels by share index, e.g. from the ceremony transcript.
+    pub fn with_labels(mut self,labels:&BTreeMap<i32,String>)->JoinContext{
+        for share in &mut self.shares {
+            share.label=labels.get(&share.index).cloned();
+        }
+        self
+    }
+
+    // Owning organization by share index, e.g. from the transcript's `org`.
+    pub fn with_orgs(mut self,orgs:&BTreeMap<i32,String>)->JoinContext{
+        for share in &mut self.shares {
+            share.org=orgs.get(&share.index).cloned();
+        }
+        self
+    }
+
+    pub fn with_approval(mut self,token:&str)->JoinContext{
+        self.approvals.push(token.to_string());
+        self
+    }
+}
+
+pub trait JoinGuard{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>;
+}
+
+impl<F:Fn(&JoinContext)->Result<(),String>> JoinGuard for F{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        self(ctx)
+    }
+}
+
+pub struct AllOf(pub Vec<Box<dyn JoinGuard>>);
+
+impl JoinGuard for AllOf{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        self.0.iter().try_for_each(|guard| guard.check(ctx))
+    }
+}
+
+// Passes when any member passes; otherwise reports every member's reason.
+pub struct AnyOf(pub Vec<Box<dyn JoinGuard>>);
+
+impl JoinGuard for AnyOf{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        let mut reasons=Vec::new();
+        for guard in &self.0 {
+            match guard.check(ctx) {
+                Ok(()) => return Ok(()),
+                Err(reason) => reasons.push(reason),
+            }
+        }
+        Err(format!("none of: {}", reasons.join("; ")))
+    }
+}
+
+// Allowed hours of the day in UTC, [from_hour, to_hour); wraps past midnight
+// when from_hour > to_hour.
+pub struct TimeWindow{
+    pub from_hour:u8,
+    pub to_hour:u8,
+}
+
+impl JoinGuard for TimeWindow{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        let secs=ctx.now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let hour=((secs/3600)%24) as u8;
+        let inside=if self.from_hour<=self.to_hour {
+            (self.from_hour..self.to_hour).contains(&hour)
+        } else {
+            hour>=self.from_hour || hour<self.to_hour
+        };
+        if inside { Ok(()) } else { Err(format!("{hour:02}:00 UTC is outside {:02}:00-{:02}:00", self.from_hour, self.to_hour)) }
+    }
+}
+
+// Every listed custodian label must be among the assembled shares.
+pub struct RequiredLabels(pub Vec<String>);
+
+impl JoinGuard for RequiredLabels{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        let missing:Vec<&str>=self.0.iter()
+            .filter(|label| !ctx.shares.iter().any(|s| s.label.as_deref()==Some(label.as_str())))
+            .map(|label| label.as_str())
+            .collect();
+        if missing.is_empty() { Ok(()) } else { Err(format!("missing custodians {}", missing.join(", "))) }
+    }
+}
+
+// An approver holding `key` authorizes one specific set of shares by
+// issuing `approval_token(key, fingerprints)`; the token is useless for any
+// other combination of shares.
+pub struct ApprovalToken{
+    pub key:Vec<u8>,
+}
+
+impl ApprovalToken{
+    pub fn issue(&self,fingerprints:&[String])->String{
+        let mut sorted=fingerprints.to_vec();
+        sorted.sort();
+        to_hex(&Sha256::hmac(&self.key, format!("shamir-join-approval\n{}", sorted.join("\n")).as_bytes()))
+    }
+}
+
+impl JoinGuard for ApprovalToken{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        let fingerprints:Vec<String>=ctx.shares.iter().map(|s| s.fingerprint.clone()).collect();
+        let expected=self.issue(&fingerprints);
+        if ctx.approvals.iter().any(|token| constant_time_eq(token.as_bytes(), expected.as_bytes())) {
+            Ok(())
+        } else {
+            Err("no valid approval token for these shares".to_string())
+        }
+    }
+}
+
+impl ShamirSS{
+
+    // Runs `guard` on `ctx` and reconstructs only if it passes. `ctx` must
+    // describe exactly the shares in `parts`.
+    pub fn join_guarded(parts:Parts,ctx:&JoinContext,guard:&dyn JoinGuard)->Result<Vec<u8>,ShamirError>{
+        let submitted=parts.iter().map(|(index, data)| (*index, share_fingerprint(*index, data)));
+        if !submitted.eq(ctx.shares.iter().map(|s| (s.index, s.fingerprint.clone()))) {
+            return Err(ShamirError::JoinDenied("context does not describe the submitted shares".to_string()));
+        }
+        guard.check(ctx).map_err(ShamirError::JoinDenied)?;
+        Self::join(parts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at_hour(hour:u64)->SystemTime{
+        UNIX_EPOCH+Duration::from_secs(86400*1000+hour*3600)
+    }
+
+    fn labelled(parts:&Parts)->JoinContext{
+        let labels:BTreeMap<i32,String>=parts.keys().map(|index| (*index, format!("custodian-{index}"))).collect();
+        JoinContext::new(parts).with_labels(&labels)
+    }
+
+    #[test]
+    fn time_window_wraps_past_midnight() {
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let mut ctx=JoinContext::new(&parts);
+        let office=TimeWindow { from_hour: 9, to_hour: 17 };
+        let night=TimeWindow { from_hour: 22, to_hour: 6 };
+        for (hour, in_office, at_night) in [(8, false, false), (9, true, false), (16, true, false), (17, false, false), (23, false, true), (0, false, true), (6, false, false)] {
+            ctx.now=at_hour(hour);
+            assert_eq!(office.check(&ctx).is_ok(), in_office, "{hour}:00");
+            assert_eq!(night.check(&ctx).is_ok(), at_night, "{hour}:00");
+        }
+    }
+
+    #[test]
+    fn composed_guards_report_why_they_refuse() {
+        let parts:Parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap().into_iter().take(2).collect();
+        let ctx=labelled(&parts);
+        let present=RequiredLabels(vec!["custodian-1".to_string()]);
+        let absent=RequiredLabels(vec!["custodian-3".to_string()]);
+        assert!(present.check(&ctx).is_ok());
+        assert_eq!(absent.check(&ctx), Err("missing custodians custodian-3".to_string()));
+
+        let deny=|_:&JoinContext| Err::<(),String>("closed".to_string());
+        assert!(AllOf(vec![Box::new(present), Box::new(deny)]).check(&ctx).is_err());
+        assert!(AnyOf(vec![Box::new(deny), Box::new(RequiredLabels(Vec::new()))]).check(&ctx).is_ok());
+        assert_eq!(AnyOf(vec![Box::new(deny), Box::new(absent)]).check(&ctx), Err("none of: closed; missing custodians custodian-3".to_string()));
+    }
+
+    #[test]
+    fn approval_token_is_bound_to_the_share_set() {
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let approver=ApprovalToken { key: b"approver key".to_vec() };
+        let first:Parts=parts.iter().take(2).map(|(i, d)| (*i, d.clone())).collect();
+        let last:Parts=parts.iter().skip(1).map(|(i, d)| (*i, d.clone())).collect();
+        let fingerprints:Vec<String>=JoinContext::new(&first).shares.into_iter().map(|s| s.fingerprint).rev().collect();
+        let token=approver.issue(&fingerprints);
+
+        let approved=JoinContext::new(&first).with_approval(&token);
+        assert_eq!(ShamirSS::join_guarded(first.clone(), &approved, &approver).unwrap(), b"secret");
+        assert!(ShamirSS::join_guarded(first.clone(), &JoinContext::new(&first), &approver).is_err());
+        let elsewhere=JoinContext::new(&last).with_approval(&token);
+        assert!(matches!(ShamirSS::join_guarded(last, &elsewhere, &approver), Err(ShamirError::JoinDenied(_))));
+    }
+
+    #[test]
+    fn context_must_describe_the_submitted_shares() {
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let allow=|_:&JoinContext| Ok::<(),String>(());
+        let ctx=JoinContext::new(&parts);
+        let two:Parts=parts.iter().take(2).map(|(i, d)| (*i, d.clone())).collect();
+        assert!(matches!(ShamirSS::join_guarded(two, &ctx, &allow), Err(ShamirError::JoinDenied(_))));
+        assert_eq!(ShamirSS::join_guarded(parts, &ctx, &allow).unwrap(), b"secret");
+    }
+}
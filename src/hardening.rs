@@ -0,0 +1,159 @@
+// Process-level hygiene for hosts that handle secrets: `scrub` before a
+// snapshot, fork into an untrusted child or deliberate crash,
+// `disable_core_dumps` early in `main` so a crash never writes share or
+// secret bytes to disk, and `create_private` for the files that do hold them.
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+// Wipes the state this crate keeps between calls:
+//   - every live `ShamirContext` RNG is reseeded in place, overwriting the
+//     ChaCha state that produced earlier coefficients;
+//   - the calling thread's FFI error message is zeroized and cleared.
+// It does not reach `rand::thread_rng`, which `ShamirSS::split` and a
+// context without an RNG policy draw from: its ChaCha key and counter stay
+// in memory until `rand` reseeds them, and with them every coefficient
+// block it produced can be regenerated. Split through a `ShamirContext`
+// with an `RngPolicy` when that matters. There is no Lagrange coefficient
+// cache to clear: weights are computed per join on the stack. Buffers owned
+// by the caller (secrets, `Parts`) are the caller's to zeroize; other
+// threads' thread-local state is untouched.
+pub fn scrub(){
+    crate::context::scrub_all();
+    crate::ffi::scrub_last_error();
+}
+
+// Stops the process from writing core dumps: RLIMIT_CORE 0 on Unix and, on
+// Linux, PR_SET_DUMPABLE 0 (which also blocks ptrace attach by non-root
+// users). On Windows, turns off the Windows Error Reporting dialog and dump.
+pub fn disable_core_dumps()->Result<(),String>{
+    sys::disable_core_dumps()
+}
+
+// Whether this process may still write a core dump: RLIMIT_CORE is not 0.
+// None where that cannot be told (Windows, other platforms).
+pub fn core_dumps_enabled()->Option<bool>{
+    sys::core_dumps_enabled()
+}
+
+// Files holding shares or secrets are owner-only (0600 on Unix) from the
+// moment they exist, whatever the umask. An existing regular file is
+// replaced and tightened to 0600 before anything is written to it; other
+// files (a FIFO, /dev/stdout) keep their mode.
+pub fn create_private(path:&Path)->io::Result<File>{
+    let file=private_options().create(true).truncate(true).open(path)?;
+    #[cfg(unix)]
+    if file.metadata()?.is_file() {
+        file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    }
+    Ok(file)
+}
+
+// `create_private` for a file that must not exist yet.
+pub fn create_new_private(path:&Path)->io::Result<File>{
+    private_options().create_new(true).open(path)
+}
+
+pub fn write_private(path:&Path,data:&[u8])->io::Result<()>{
+    create_private(path)?.write_all(data)
+}
+
+pub fn write_new_private(path:&Path,data:&[u8])->io::Result<()>{
+    create_new_private(path)?.write_all(data)
+}
+
+fn private_options()->OpenOptions{
+    let mut options=OpenOptions::new();
+    options.write(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    options
+}
+
+#[cfg(unix)]
+mod sys{
+    pub fn disable_core_dumps()->Result<(),String>{
+        let limit=libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        // SAFETY: `limit` is a valid rlimit for the duration of the call.
+        if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) }!=0 {
+            return Err(format!("setrlimit(RLIMIT_CORE) failed: {}", std::io::Error::last_os_error()));
+        }
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            // SAFETY: PR_SET_DUMPABLE takes a single unsigned long argument.
+            if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0 as libc::c_ulong) }!=0 {
+                return Err(format!("prctl(PR_SET_DUMPABLE) failed: {}", std::io::Error::last_os_error()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn core_dumps_enabled()->Option<bool>{
+        let mut limit=libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        // SAFETY: `limit` is a valid, writable rlimit for the duration of the call.
+        (unsafe { libc::getrlimit(libc::RLIMIT_CORE, &mut limit) }==0).then_some(limit.rlim_cur!=0)
+    }
+}
+
+#[cfg(windows)]
+mod sys{
+    const SEM_FAILCRITICALERRORS:u32=0x0001;
+    const SEM_NOGPFAULTERRORBOX:u32=0x0002;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn SetErrorMode(mode:u32)->u32;
+    }
+
+    pub fn disable_core_dumps()->Result<(),String>{
+        // SAFETY: SetErrorMode only updates the process error mode flags.
+        unsafe { SetErrorMode(SEM_FAILCRITICALERRORS|SEM_NOGPFAULTERRORBOX) };
+        Ok(())
+    }
+
+    pub fn core_dumps_enabled()->Option<bool>{
+        None
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys{
+    pub fn disable_core_dumps()->Result<(),String>{
+        Err("Disabling core dumps is not supported on this platform".to_string())
+    }
+
+    pub fn core_dumps_enabled()->Option<bool>{
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn mode(path:&Path)->u32{
+        std::os::unix::fs::PermissionsExt::mode(&std::fs::metadata(path).unwrap().permissions()) & 0o777
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn private_files_are_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path=std::env::temp_dir().join(format!("shamir-private-{}", std::process::id()));
+        std::fs::write(&path, b"old contents").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        write_private(&path, b"share").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(std::fs::read(&path).unwrap(), b"share");
+
+        assert_eq!(write_new_private(&path, b"other").unwrap_err().kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(std::fs::read(&path).unwrap(), b"share");
+        std::fs::remove_file(&path).unwrap();
+        write_new_private(&path, b"fresh").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,75 @@
+use base64::{engine::general_purpose, Engine as _};
+
+// A short plaintext note per share ("stored in safe deposit box #42"), read
+// without any reconstruction, so heirs and auditors can locate the other
+// shares. The hint line is plain text in the share bundle; the dealer signs
+// a statement binding it to that share's index and fingerprint, so a hint
+// cannot be edited or moved to another share without breaking the signature:
+//
+//   shamir-share-hint v1
+//   share <index> <fingerprint>
+//   hint <text>
+//
+// Hints are one line of at most MAX_HINT_LEN bytes with no control
+// characters: room for a location, not for smuggling share material or a
+// second secret into every copy.
+
+pub const MAX_HINT_LEN:usize=140;
+const STATEMENT_HEADER:&str="shamir-share-hint v1";
+const HINT_PREFIX:&str="hint ";
+const SIGNATURE_PREFIX:&str="hint-signature ";
+
+pub fn check_hint(hint:&str)->Result<(),String>{
+    if hint.trim().is_empty() {
+        return Err("Hint is empty".to_string());
+    }
+    if hint.len()>MAX_HINT_LEN {
+        return Err(format!("Hint of {} bytes exceeds the {MAX_HINT_LEN}-byte limit", hint.len()));
+    }
+    if hint.chars().any(char::is_control) {
+        return Err("Hint must be a single line without control characters".to_string());
+    }
+    Ok(())
+}
+
+// The text the dealer signs for one share's hint.
+pub fn hint_statement(index:i32,fingerprint:&str,hint:&str)->String{
+    format!("{STATEMENT_HEADER}\nshare {index} {fingerprint}\nhint {}\n", hint.trim())
+}
+
+pub fn hint_lines(hint:&str,signature:&[u8])->String{
+    format!("{HINT_PREFIX}{}\n{SIGNATURE_PREFIX}{}\n", hint.trim(), general_purpose::STANDARD.encode(signature))
+}
+
+// The hint and its signature from a share bundle, if it carries them.
+pub fn read_hint(bundle:&str)->Result<Option<(String,Vec<u8>)>,String>{
+    let find=|prefix:&str| bundle.lines().find_map(|line| line.trim().strip_prefix(prefix));
+    let (Some(hint), Some(signature))=(find(HINT_PREFIX), find(SIGNATURE_PREFIX)) else { return Ok(None) };
+    check_hint(hint)?;
+    let signature=general_purpose::STANDARD.decode(signature.trim()).map_err(|e| format!("Malformed hint signature: {e}"))?;
+    Ok(Some((hint.trim().to_string(), signature)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hint_lines_round_trip() {
+        let bundle=format!("shamir-share v1\n{}", hint_lines(" safe deposit box #42 ", b"signature"));
+        assert_eq!(read_hint(&bundle).unwrap(), Some(("safe deposit box #42".to_string(), b"signature".to_vec())));
+        assert_eq!(hint_statement(2, "1a2b3c4d", "box #42 "), "shamir-share-hint v1\nshare 2 1a2b3c4d\nhint box #42\n");
+        assert_eq!(read_hint("shamir-share v1\nhint unsigned\n").unwrap(), None);
+        assert!(read_hint("hint box\nhint-signature !!!\n").is_err());
+    }
+
+    #[test]
+    fn hints_are_one_short_line() {
+        assert!(check_hint("safe deposit box #42").is_ok());
+        assert!(check_hint(&"x".repeat(MAX_HINT_LEN)).is_ok());
+        assert!(check_hint(&"x".repeat(MAX_HINT_LEN+1)).is_err());
+        assert!(check_hint("  ").is_err());
+        assert!(check_hint("box\t42").is_err());
+        assert!(read_hint(&hint_lines(&"x".repeat(MAX_HINT_LEN+1), b"s")).is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use rand::{Rng, RngCore};
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::transcript::HsmRecord;
+
+// Dealer-side splitting of a secret that lives in an HSM. The secret is
+// unwrapped or derived inside the HSM and comes into host memory only in a
+// `LockedBuffer`, for as long as the split takes, as do the polynomial
+// coefficients (with any one share they give it back); the shares
+// themselves are ordinary memory, like any split's. The transcript records
+// which HSM and mechanism supplied it (an `hsm` line, see `transcript.rs`).
+//
+// The crate does not bind PKCS#11 itself; an integrator implements
+// `HsmSecret` over their binding. The usual shape is:
+//   - C_UnwrapKey (e.g. CKM_AES_KEY_WRAP_KWP) or C_DeriveKey (e.g.
+//     CKM_SP800_108_COUNTER_KDF) into a session object with CKA_TOKEN false,
+//     CKA_SENSITIVE false and CKA_EXTRACTABLE true;
+//   - `secret_len` from CKA_VALUE_LEN;
+//   - `read_into`: C_GetAttributeValue(CKA_VALUE) straight into the buffer
+//     given, then C_DestroyObject;
+//   - `record` from C_GetTokenInfo, the mechanism used and the key's
+//     CKA_LABEL, with the key check value (CKA_CHECK_VALUE) and any vendor
+//     key attestation the HSM can produce.
+//
+// Call `hardening::disable_core_dumps` first: locking keeps the secret out
+// of swap, not out of a core file.
+
+// Secret bytes from an HSM.
+pub trait HsmSecret{
+    // Which HSM, key and mechanism supply the secret, for the transcript.
+    fn record(&self)->Result<HsmRecord,String>;
+    fn secret_len(&mut self)->Result<usize,String>;
+    // Fills `buf`, exactly `secret_len` bytes, with the secret.
+    fn read_into(&mut self,buf:&mut [u8])->Result<(),String>;
+}
+
+// A fixed-size heap buffer that is locked into RAM (mlock) and, on Linux,
+// left out of core dumps, and wiped before it is unlocked and freed. It
+// never grows, so the bytes are never copied to an unlocked block.
+pub struct LockedBuffer{
+    data:Box<[u8]>,
+}
+
+impl LockedBuffer{
+
+    // Fails when the memory cannot be locked, e.g. RLIMIT_MEMLOCK is too low.
+    pub fn new(len:usize)->Result<LockedBuffer,String>{
+        let mut data=vec![0u8;len].into_boxed_slice();
+        sys::lock(&mut data).map_err(|e| format!("Cannot lock {len} bytes of memory for the secret: {e}"))?;
+        Ok(LockedBuffer { data })
+    }
+
+    pub fn as_slice(&self)->&[u8]{
+        &self.data
+    }
+
+    pub fn as_mut_slice(&mut self)->&mut [u8]{
+        &mut self.data
+    }
+}
+
+impl Drop for LockedBuffer{
+    fn drop(&mut self){
+        zeroize(&mut self.data);
+        sys::unlock(&mut self.data);
+    }
+}
+
+impl ShamirSS{
+
+    // Splits the HSM's secret into shares 1..=n. Returns the shares and the
+    // record for `Transcript::hsm`. The secret is read after every argument
+    // is checked and is wiped before this returns, on success or failure.
+    pub fn split_from_hsm<S:HsmSecret+?Sized>(n:i32,k:i32,hsm:&mut S)->Result<(Parts,HsmRecord),String>{
+        if !(2..=255).contains(&n) || !(2..=n).contains(&k) {
+            return Err(format!("Cannot split {k} of {n}: need 2 <= k <= n <= 255"));
+        }
+        let record=hsm.record()?;
+        let len=hsm.secret_len()?;
+        if len==0 {
+            return Err("The HSM returned an empty secret".to_string());
+        }
+        let mut secret=LockedBuffer::new(len)?;
+        hsm.read_into(secret.as_mut_slice())?;
+        let xs:Vec<u8>=(1..=n).map(|x| x as u8).collect();
+        let degree=(k-1) as usize;
+        // The coefficients and any one share give the secret back, so they
+        // are locked and wiped like it.
+        let mut coefficients=LockedBuffer::new(len*degree)?;
+        let mut rng=rand::thread_rng();
+        // Coefficients straight from the RNG, with the leading one of each
+        // byte's polynomial redrawn until non-zero, as `split` does, so no
+        // byte falls below threshold k.
+        rng.fill_bytes(coefficients.as_mut_slice());
+        for row in coefficients.as_mut_slice().chunks_exact_mut(degree) {
+            if let Some(leading)=row.last_mut() {
+                while *leading==0 {
+                    *leading=rng.gen();
+                }
+            }
+        }
+        let parts=ShamirSS::split_flat(&xs, k, secret.as_slice(), coefficients.as_slice())?;
+        Ok((parts, record))
+    }
+}
+
+#[cfg(unix)]
+mod sys{
+    use std::ffi::c_void;
+
+    pub fn lock(data:&mut [u8])->Result<(),String>{
+        if data.is_empty() {
+            return Ok(());
+        }
+        // SAFETY: locks pages of a live allocation owned by the caller.
+        if unsafe { libc::mlock(data.as_ptr() as *const c_void, data.len()) }!=0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        #[cfg(target_os = "linux")]
+        {
+            // madvise wants a page-aligned start: advise the whole pages
+            // inside the buffer; the partial ones at the edges are locked
+            // but may still be dumped.
+            // SAFETY: sysconf only reads a system constant.
+            let page=usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(0);
+            if page==0 {
+                return Ok(());
+            }
+            let start=(data.as_ptr() as usize).next_multiple_of(page);
+            let end=(data.as_ptr() as usize+data.len())/page*page;
+            if end>start {
+                // SAFETY: advice on whole pages within the caller's buffer;
+                // failure only means the pages may be dumped.
+                unsafe { libc::madvise(start as *mut c_void, end-start, libc::MADV_DONTDUMP) };
+            }
+        }
+        Ok(())
+    }
+
+    pub fn unlock(data:&mut [u8]){
+        if !data.is_empty() {
+            // SAFETY: unlocks the range locked in `lock`.
+            unsafe { libc::munlock(data.as_ptr() as *const c_void, data.len()) };
+        }
+    }
+}
+
+#[cfg(windows)]
+mod sys{
+    use std::ffi::c_void;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn VirtualLock(addr:*mut c_void,len:usize)->i32;
+        fn VirtualUnlock(addr:*mut c_void,len:usize)->i32;
+    }
+
+    pub fn lock(data:&mut [u8])->Result<(),String>{
+        if data.is_empty() {
+            return Ok(());
+        }
+        // SAFETY: locks pages of a live allocation owned by the caller.
+        if unsafe { VirtualLock(data.as_mut_ptr() as *mut c_void, data.len()) }==0 {
+            return Err(std::io::Error::last_os_error().to_string());
+        }
+        Ok(())
+    }
+
+    pub fn unlock(data:&mut [u8]){
+        if !data.is_empty() {
+            // SAFETY: unlocks the range locked in `lock`.
+            unsafe { VirtualUnlock(data.as_mut_ptr() as *mut c_void, data.len()) };
+        }
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+mod sys{
+    pub fn lock(_data:&mut [u8])->Result<(),String>{
+        Err("Locking memory is not supported on this platform".to_string())
+    }
+
+    pub fn unlock(_data:&mut [u8]){}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeHsm(Vec<u8>);
+
+    impl HsmSecret for FakeHsm{
+        fn record(&self)->Result<HsmRecord,String>{
+            Ok(HsmRecord { token: "fake".into(), mechanism: "CKM_VENDOR_DEFINED".into(), key: "test".into(), ..Default::default() })
+        }
+
+        fn secret_len(&mut self)->Result<usize,String>{
+            Ok(self.0.len())
+        }
+
+        fn read_into(&mut self,buf:&mut [u8])->Result<(),String>{
+            buf.copy_from_slice(&self.0);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn splits_the_hsm_secret_from_locked_memory() {
+        let (parts, record)=ShamirSS::split_from_hsm(5, 3, &mut FakeHsm(b"hsm secret".to_vec())).unwrap();
+        assert_eq!(record.token, "fake");
+        let subset:Parts=parts.into_iter().skip(2).collect();
+        assert_eq!(ShamirSS::join(subset).unwrap(), b"hsm secret");
+        assert!(ShamirSS::split_from_hsm(2, 3, &mut FakeHsm(b"s".to_vec())).is_err());
+        assert!(ShamirSS::split_from_hsm(3, 2, &mut FakeHsm(Vec::new())).is_err());
+    }
+}
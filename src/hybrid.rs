@@ -0,0 +1,270 @@
+use chacha20poly1305::aead::stream::{DecryptorBE32, EncryptorBE32};
+use chacha20poly1305::aead::{KeyInit, Payload};
+use chacha20poly1305::XChaCha20Poly1305;
+use rand::RngCore;
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::progress::{Phase, ProgressObserver, Tracker, PROGRESS_CHUNK};
+
+// Hybrid mode: the payload is encrypted under a fresh random data key and
+// only the 32-byte key is split, so share size stays constant no matter how
+// large the payload is.
+//
+// Encryption is XChaCha20-Poly1305 in the STREAM construction (big-endian
+// 32-bit segment counter, as `aead::stream::EncryptorBE32`): the payload is
+// cut into PROGRESS_CHUNK-byte segments, each sealed with its own tag and the
+// last one marked as such, so truncation and reordering are detected and
+// progress can be reported per segment.
+//
+// Sealed layout: MAGIC | generation (u32 BE) | nonce prefix (19) | segments,
+// each the ciphertext followed by a 16-byte tag. The header is the
+// associated data of every segment, followed by any context the caller
+// binds with `encrypt_with_aad`. The generation starts at 1 and is bumped
+// by every rotation, so callers can refuse payloads older than the one they
+// expect. Nonce prefixes are random: the same key may seal many payloads.
+
+const MAGIC:&[u8;4]=b"SHX2";
+const NONCE_PREFIX_LEN:usize=19;
+const HEADER_LEN:usize=8+NONCE_PREFIX_LEN;
+const KEY_LEN:usize=32;
+const TAG_LEN:usize=16;
+const SEGMENT_LEN:usize=PROGRESS_CHUNK+TAG_LEN;
+
+#[derive(Debug,Clone)]
+pub struct HybridSS;
+
+impl HybridSS{
+
+    pub fn seal(n:i32,k:i32,payload:&[u8])->Result<(Vec<u8>,Parts),String>{
+        Self::seal_with_aad(n, k, payload, &[])
+    }
+
+    // `seal` also authenticating `aad`, which is not stored: `open_with_aad`
+    // needs the same bytes, so metadata kept next to the sealed payload (a
+    // manifest, say) cannot be swapped without the open failing.
+    pub fn seal_with_aad(n:i32,k:i32,payload:&[u8],aad:&[u8])->Result<(Vec<u8>,Parts),String>{
+        Self::seal_generation_tracked(n, k, payload, 1, aad, None)
+    }
+
+    // Opens the payload with the current shares and re-seals it under a fresh
+    // data key and the next generation. Old shares cannot open the result.
+    pub fn rotate(parts:Parts,sealed:&[u8],n:i32,k:i32)->Result<(Vec<u8>,Parts),String>{
+        Self::rotate_with_aad(parts, sealed, n, k, &[])
+    }
+
+    // `rotate` for a payload sealed with `seal_with_aad`; the result is bound
+    // to the same `aad`.
+    pub fn rotate_with_aad(parts:Parts,sealed:&[u8],n:i32,k:i32,aad:&[u8])->Result<(Vec<u8>,Parts),String>{
+        let generation=Self::generation(sealed)?;
+        let next=generation.checked_add(1).ok_or("Generation counter exhausted")?;
+        let mut payload=Self::open_with_aad(parts, sealed, aad)?;
+        let rotated=Self::seal_generation_tracked(n, k, &payload, next, aad, None);
+        zeroize(&mut payload);
+        rotated
+    }
+
+    pub fn generation(sealed:&[u8])->Result<u32,String>{
+        match sealed {
+            [m0, m1, m2, m3, g0, g1, g2, g3, ..] if sealed.len()>=HEADER_LEN+TAG_LEN && [*m0, *m1, *m2, *m3]==*MAGIC => {
+                Ok(u32::from_be_bytes([*g0, *g1, *g2, *g3]))
+            }
+            _ => Err("Not a sealed payload".to_string()),
+        }
+    }
+
+    // `seal` reporting encryption progress, for large payloads.
+    pub fn seal_with_progress(n:i32,k:i32,payload:&[u8],observer:&mut dyn ProgressObserver)->Result<(Vec<u8>,Parts),String>{
+        let mut tracker=Tracker::new(observer, Phase::Encrypting, payload.len());
+        Self::seal_generation_tracked(n, k, payload, 1, &[], Some(&mut tracker))
+    }
+
+    fn seal_generation_tracked(n:i32,k:i32,payload:&[u8],generation:u32,aad:&[u8],tracker:Option<&mut Tracker>)->Result<(Vec<u8>,Parts),String>{
+        let mut key=[0u8;KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut key);
+
+        let parts=ShamirSS::split(n, k, key.to_vec());
+        let sealed=Self::encrypt_tracked(&key, generation, payload, aad, tracker);
+        zeroize(&mut key);
+
+        Ok((sealed?,parts?))
+    }
+
+    pub fn open(parts:Parts,sealed:&[u8])->Result<Vec<u8>,String>{
+        Self::open_tracked(parts, sealed, &[], None)
+    }
+
+    pub fn open_with_aad(parts:Parts,sealed:&[u8],aad:&[u8])->Result<Vec<u8>,String>{
+        Self::open_tracked(parts, sealed, aad, None)
+    }
+
+    // `open` reporting decryption progress, for large payloads.
+    pub fn open_with_progress(parts:Parts,sealed:&[u8],observer:&mut dyn ProgressObserver)->Result<Vec<u8>,String>{
+        let mut tracker=Tracker::new(observer, Phase::Decrypting, Self::payload_len(sealed));
+        Self::open_tracked(parts, sealed, &[], Some(&mut tracker))
+    }
+
+    fn open_tracked(parts:Parts,sealed:&[u8],aad:&[u8],tracker:Option<&mut Tracker>)->Result<Vec<u8>,String>{
+        let mut key=ShamirSS::join(parts)?;
+        let payload=Self::decrypt_tracked(&key, sealed, aad, tracker);
+        zeroize(&mut key);
+        payload
+    }
+
+    pub fn encrypt(key:&[u8],generation:u32,payload:&[u8])->Result<Vec<u8>,String>{
+        Self::encrypt_tracked(key, generation, payload, &[], None)
+    }
+
+    // `encrypt` also authenticating `aad`, which is not stored: `decrypt_with_aad`
+    // must be given the same bytes. For headers kept next to the sealed payload.
+    pub fn encrypt_with_aad(key:&[u8],generation:u32,payload:&[u8],aad:&[u8])->Result<Vec<u8>,String>{
+        Self::encrypt_tracked(key, generation, payload, aad, None)
+    }
+
+    fn encrypt_tracked(key:&[u8],generation:u32,payload:&[u8],aad:&[u8],mut tracker:Option<&mut Tracker>)->Result<Vec<u8>,String>{
+        let mut nonce=[0u8;NONCE_PREFIX_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let mut sealed=Vec::with_capacity(HEADER_LEN+payload.len()+payload.len().div_ceil(PROGRESS_CHUNK).max(1)*TAG_LEN);
+        sealed.extend_from_slice(MAGIC);
+        sealed.extend_from_slice(&generation.to_be_bytes());
+        sealed.extend_from_slice(&nonce);
+        let header=[sealed.as_slice(), aad].concat();
+
+        let mut encryptor=EncryptorBE32::from_aead(Self::cipher(key)?, nonce.as_slice().into());
+        let overflow=|_| "Payload too large to seal".to_string();
+        let mut rest=payload;
+        while rest.len()>PROGRESS_CHUNK {
+            let (segment, tail)=rest.split_at(PROGRESS_CHUNK);
+            sealed.extend_from_slice(&encryptor.encrypt_next(Payload { msg: segment, aad: &header }).map_err(overflow)?);
+            if let Some(tracker)=tracker.as_deref_mut() {
+                tracker.advance(segment.len());
+            }
+            rest=tail;
+        }
+        sealed.extend_from_slice(&encryptor.encrypt_last(Payload { msg: rest, aad: &header }).map_err(overflow)?);
+        if let Some(tracker)=tracker {
+            tracker.advance(rest.len());
+        }
+        Ok(sealed)
+    }
+
+    pub fn decrypt(key:&[u8],sealed:&[u8])->Result<Vec<u8>,String>{
+        Self::decrypt_tracked(key, sealed, &[], None)
+    }
+
+    pub fn decrypt_with_aad(key:&[u8],sealed:&[u8],aad:&[u8])->Result<Vec<u8>,String>{
+        Self::decrypt_tracked(key, sealed, aad, None)
+    }
+
+    fn decrypt_tracked(key:&[u8],sealed:&[u8],aad:&[u8],mut tracker:Option<&mut Tracker>)->Result<Vec<u8>,String>{
+        let generation=Self::generation(sealed)?;
+        let (stored, body)=sealed.split_at(HEADER_LEN);
+        let header=&[stored, aad].concat();
+        let mismatch=|_| format!("Sealed payload failed integrity check (shares do not match generation {generation})");
+
+        let mut decryptor=DecryptorBE32::from_aead(Self::cipher(key)?, stored[8..].into());
+        let mut payload=Vec::with_capacity(body.len());
+        let mut rest=body;
+        while rest.len()>SEGMENT_LEN {
+            let (segment, tail)=rest.split_at(SEGMENT_LEN);
+            payload.extend_from_slice(&decryptor.decrypt_next(Payload { msg: segment, aad: header }).map_err(mismatch)?);
+            if let Some(tracker)=tracker.as_deref_mut() {
+                tracker.advance(PROGRESS_CHUNK);
+            }
+            rest=tail;
+        }
+        payload.extend_from_slice(&decryptor.decrypt_last(Payload { msg: rest, aad: header }).map_err(mismatch)?);
+        if let Some(tracker)=tracker {
+            tracker.advance(rest.len()-TAG_LEN);
+        }
+        Ok(payload)
+    }
+
+    // Plaintext length of a sealed payload: every segment carries a tag.
+    fn payload_len(sealed:&[u8])->usize{
+        let body=sealed.len().saturating_sub(HEADER_LEN);
+        body.saturating_sub(body.div_ceil(SEGMENT_LEN).max(1)*TAG_LEN)
+    }
+
+    fn cipher(key:&[u8])->Result<XChaCha20Poly1305,String>{
+        XChaCha20Poly1305::new_from_slice(key).map_err(|_| format!("Data key must be {KEY_LEN} bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_across_segment_boundaries() {
+        let key=[7u8;KEY_LEN];
+        for len in [0, 1, PROGRESS_CHUNK-1, PROGRESS_CHUNK, PROGRESS_CHUNK+1, 2*PROGRESS_CHUNK] {
+            let payload:Vec<u8>=(0..len).map(|i| i as u8).collect();
+            let sealed=HybridSS::encrypt(&key, 3, &payload).unwrap();
+            assert_eq!(HybridSS::generation(&sealed).unwrap(), 3);
+            assert_eq!(HybridSS::payload_len(&sealed), len);
+            assert_eq!(HybridSS::decrypt(&key, &sealed).unwrap(), payload, "{len} bytes");
+        }
+    }
+
+    #[test]
+    fn rejects_tampering_truncation_and_wrong_keys() {
+        let key=[7u8;KEY_LEN];
+        let payload=vec![42u8;PROGRESS_CHUNK+10];
+        let sealed=HybridSS::encrypt(&key, 1, &payload).unwrap();
+
+        let mut flipped=sealed.clone();
+        flipped[HEADER_LEN+5]^=1;
+        assert!(HybridSS::decrypt(&key, &flipped).is_err());
+
+        let mut relabeled=sealed.clone();
+        relabeled[7]=2;
+        assert!(HybridSS::decrypt(&key, &relabeled).is_err());
+
+        // Dropping the last segment leaves a non-final segment at the end.
+        assert!(HybridSS::decrypt(&key, &sealed[..HEADER_LEN+SEGMENT_LEN]).is_err());
+        assert!(HybridSS::decrypt(&[8u8;KEY_LEN], &sealed).is_err());
+
+        let bound=HybridSS::encrypt_with_aad(&key, 1, &payload, b"header").unwrap();
+        assert_eq!(HybridSS::decrypt_with_aad(&key, &bound, b"header").unwrap(), payload);
+        assert!(HybridSS::decrypt_with_aad(&key, &bound, b"Header").is_err());
+        assert!(HybridSS::decrypt(&key, &bound).is_err());
+        assert!(HybridSS::decrypt(&key[..16], &sealed).is_err());
+    }
+
+    #[test]
+    fn generation_needs_a_whole_header() {
+        let sealed=HybridSS::encrypt(&[7u8;KEY_LEN], 9, b"x").unwrap();
+        for len in 0..HEADER_LEN+TAG_LEN {
+            assert!(HybridSS::generation(&sealed[..len]).is_err());
+        }
+        assert!(HybridSS::generation(b"SHX1\0\0\0\x01").is_err());
+    }
+
+    #[test]
+    fn rotate_bumps_the_generation() {
+        let (sealed, parts)=HybridSS::seal(3, 2, b"payload").unwrap();
+        let (rotated, fresh)=HybridSS::rotate(parts.clone(), &sealed, 3, 2).unwrap();
+        assert_eq!(HybridSS::generation(&rotated).unwrap(), 2);
+        assert_eq!(HybridSS::open(fresh, &rotated).unwrap(), b"payload");
+        assert!(HybridSS::open(parts, &rotated).is_err());
+    }
+
+    #[test]
+    fn sealed_payload_is_bound_to_its_aad() {
+        let (sealed, parts)=HybridSS::seal_with_aad(3, 2, b"payload", b"manifest").unwrap();
+        assert_eq!(HybridSS::open_with_aad(parts.clone(), &sealed, b"manifest").unwrap(), b"payload");
+        assert!(HybridSS::open_with_aad(parts.clone(), &sealed, b"forged").is_err());
+        assert!(HybridSS::open(parts.clone(), &sealed).is_err());
+
+        let (rotated, fresh)=HybridSS::rotate_with_aad(parts, &sealed, 3, 2, b"manifest").unwrap();
+        assert_eq!(HybridSS::open_with_aad(fresh.clone(), &rotated, b"manifest").unwrap(), b"payload");
+        assert!(HybridSS::open(fresh, &rotated).is_err());
+    }
+
+    #[test]
+    fn seal_open_with_shares() {
+        let (sealed, parts)=HybridSS::seal(3, 2, b"payload").unwrap();
+        let two:Parts=parts.into_iter().take(2).collect();
+        assert_eq!(HybridSS::open(two, &sealed).unwrap(), b"payload");
+    }
+}
@@ -0,0 +1,121 @@
+use rand::RngCore;
+
+use crate::crypto::{zeroize, ShamirSS, GFC256};
+use crate::error::ShamirError;
+
+// Heap-free split/join for callers that cannot allocate (microcontrollers,
+// kernel modules). Shares live in caller-provided fixed-size buffers; share
+// i (0-based) in `out` is the point x = i + 1. Polynomials and Lagrange
+// weights use stack arrays of the maximum size (255 bytes). With the
+// caller's RNG neither function allocates (see
+// `testutil::assert_no_allocations`); `split_in_place` draws from
+// thread_rng, whose state is allocated on first use in each thread.
+
+impl ShamirSS{
+
+    // Splits `secret` into `out[..n]` and zeroizes `secret` afterwards.
+    pub fn split_in_place<const L:usize>(n:usize,k:usize,secret:&mut [u8;L],out:&mut [[u8;L]])->Result<(),ShamirError>{
+        Self::split_in_place_with_rng(n, k, secret, out, &mut rand::thread_rng())
+    }
+
+    pub fn split_in_place_with_rng<const L:usize,R:RngCore+?Sized>(n:usize,k:usize,secret:&mut [u8;L],out:&mut [[u8;L]],rng:&mut R)->Result<(),ShamirError>{
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        if n < k {
+            return Err(ShamirError::ThresholdAboveShares)
+        }
+        if n > 255 {
+            return Err(ShamirError::TooManyShares)
+        }
+        if out.len() < n {
+            return Err(ShamirError::BufferSize { expected: n, actual: out.len() })
+        }
+
+        let mut p=[0u8;255];
+        for (i, &byte) in secret.iter().enumerate() {
+            GFC256::generate_into(&mut p[..k], byte, rng);
+            for (j, share) in out[..n].iter_mut().enumerate() {
+                share[i]=GFC256::eval_slice(&p[..k], j as u8+1);
+            }
+        }
+        zeroize(&mut p);
+        zeroize(secret);
+        Ok(())
+    }
+
+    // Joins shares[i] taken at xs[i] into `out`.
+    pub fn join_in_place<const L:usize>(xs:&[u8],shares:&[[u8;L]],out:&mut [u8;L])->Result<(),ShamirError>{
+        if xs.is_empty() {
+            return Err(ShamirError::NoParts)
+        }
+        if xs.len()!=shares.len() {
+            return Err(ShamirError::BufferSize { expected: xs.len(), actual: shares.len() })
+        }
+        if xs.len() > 255 {
+            return Err(ShamirError::TooManyShares)
+        }
+        for (i, &x) in xs.iter().enumerate() {
+            if x == 0 {
+                return Err(ShamirError::InvalidIndex(0))
+            }
+            if xs[..i].contains(&x) {
+                return Err(ShamirError::DuplicateIndex(x as i32))
+            }
+        }
+
+        // l_i(0) = prod_{j != i} x_j / (x_i - x_j)
+        let mut weights=[0u8;255];
+        for (i, &xi) in xs.iter().enumerate() {
+            let mut li=1u8;
+            for (j, &xj) in xs.iter().enumerate() {
+                if i!=j {
+                    li=GFC256::mul(li, GFC256::div(xj, GFC256::add(xi, xj)));
+                }
+            }
+            weights[i]=li;
+        }
+        for (pos, byte) in out.iter_mut().enumerate() {
+            *byte=shares.iter().zip(&weights).fold(0u8, |acc, (share, &w)| GFC256::add(acc, GFC256::mul(w, share[pos])));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn split_and_join_in_caller_buffers() {
+        let mut secret=*b"sixteen byte key";
+        let mut shares=[[0u8;16];5];
+        ShamirSS::split_in_place_with_rng(5, 3, &mut secret, &mut shares, &mut StdRng::seed_from_u64(7)).unwrap();
+        assert_eq!(secret, [0u8;16]);
+
+        let mut out=[0u8;16];
+        ShamirSS::join_in_place(&[2, 4, 5], &[shares[1], shares[3], shares[4]], &mut out).unwrap();
+        assert_eq!(&out, b"sixteen byte key");
+
+        // The same points as the allocating join.
+        let parts=shares.iter().enumerate().map(|(i, share)| (i as i32+1, share.to_vec())).take(3).collect();
+        assert_eq!(ShamirSS::join(parts).unwrap(), b"sixteen byte key");
+    }
+
+    #[test]
+    fn bad_arguments_are_rejected() {
+        let mut secret=[1u8;4];
+        let mut shares=[[0u8;4];2];
+        assert_eq!(ShamirSS::split_in_place(3, 2, &mut secret, &mut shares), Err(ShamirError::BufferSize { expected: 3, actual: 2 }));
+        assert_eq!(ShamirSS::split_in_place(2, 1, &mut secret, &mut shares), Err(ShamirError::ThresholdTooSmall));
+        assert_eq!(secret, [1u8;4]);
+
+        let mut out=[0u8;4];
+        assert_eq!(ShamirSS::join_in_place(&[], &[], &mut out), Err(ShamirError::NoParts));
+        assert_eq!(ShamirSS::join_in_place(&[1, 0], &shares, &mut out), Err(ShamirError::InvalidIndex(0)));
+        assert_eq!(ShamirSS::join_in_place(&[1, 1], &shares, &mut out), Err(ShamirError::DuplicateIndex(1)));
+        assert_eq!(ShamirSS::join_in_place(&[1], &shares, &mut out), Err(ShamirError::BufferSize { expected: 1, actual: 2 }));
+    }
+}
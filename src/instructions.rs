@@ -0,0 +1,123 @@
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::sha256::{from_hex, to_hex, Sha256};
+
+// Recovery instructions travel with each share so a custodian who finds it
+// years later knows how recovery works. The dealer signs the text once
+// (detached OpenPGP signature) and every share bundle carries both, base64
+// encoded on one line each; bundle readers skip lines they do not know.
+//
+//   shamir-recovery-instructions v1
+//   url https://example.org/recovery
+//   contact Alice <alice@example.org>
+//   procedure-sha256 <hex SHA-256 of the written procedure>
+//   note Bring ID; two custodians must be present
+
+const HEADER:&str="shamir-recovery-instructions v1";
+const TEXT_PREFIX:&str="instructions ";
+const SIGNATURE_PREFIX:&str="instructions-signature ";
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct RecoveryInstructions{
+    pub url:Option<String>,
+    pub contacts:Vec<String>,
+    pub procedure_sha256:Option<String>,
+    pub notes:Vec<String>,
+}
+
+impl RecoveryInstructions{
+
+    pub fn to_text(&self)->String{
+        let mut out=format!("{HEADER}\n");
+        if let Some(url)=&self.url {
+            out.push_str(&format!("url {url}\n"));
+        }
+        for contact in &self.contacts {
+            out.push_str(&format!("contact {contact}\n"));
+        }
+        if let Some(hash)=&self.procedure_sha256 {
+            out.push_str(&format!("procedure-sha256 {hash}\n"));
+        }
+        for note in &self.notes {
+            out.push_str(&format!("note {note}\n"));
+        }
+        out
+    }
+
+    pub fn parse(text:&str)->Result<RecoveryInstructions,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(HEADER) {
+            return Err("Not a recovery instructions file".to_string());
+        }
+        let mut instructions=RecoveryInstructions::default();
+        for line in lines {
+            let (key, value)=line.split_once(' ').map(|(k, v)| (k, v.trim())).unwrap_or((line, ""));
+            match key {
+                "url" => instructions.url=Some(value.to_string()),
+                "contact" => instructions.contacts.push(value.to_string()),
+                "procedure-sha256" => {
+                    if from_hex(value).map(|h| h.len())!=Some(32) {
+                        return Err(format!("procedure-sha256 {value} is not a SHA-256 digest"));
+                    }
+                    instructions.procedure_sha256=Some(value.to_ascii_lowercase());
+                }
+                "note" => instructions.notes.push(value.to_string()),
+                _ => return Err(format!("Unknown recovery instructions field {key}")),
+            }
+        }
+        Ok(instructions)
+    }
+
+    // Whether `procedure` is the document the instructions refer to.
+    pub fn matches_procedure(&self,procedure:&[u8])->bool{
+        self.procedure_sha256.as_deref()==Some(to_hex(&Sha256::digest(procedure)).as_str())
+    }
+}
+
+pub fn instructions_lines(text:&str,signature:&[u8])->String{
+    format!("{TEXT_PREFIX}{}\n{SIGNATURE_PREFIX}{}\n", general_purpose::STANDARD.encode(text), general_purpose::STANDARD.encode(signature))
+}
+
+// The signed instructions text and its signature from a share bundle, if it
+// carries them.
+pub fn read_instructions(bundle:&str)->Result<Option<(String,Vec<u8>)>,String>{
+    let find=|prefix:&str| bundle.lines().find_map(|line| line.trim().strip_prefix(prefix));
+    let (Some(text), Some(signature))=(find(TEXT_PREFIX), find(SIGNATURE_PREFIX)) else { return Ok(None) };
+    let text=general_purpose::STANDARD.decode(text.trim()).map_err(|e| format!("Malformed instructions: {e}"))?;
+    let signature=general_purpose::STANDARD.decode(signature.trim()).map_err(|e| format!("Malformed instructions signature: {e}"))?;
+    Ok(Some((String::from_utf8(text).map_err(|_| "Instructions are not UTF-8".to_string())?, signature)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instructions()->RecoveryInstructions{
+        RecoveryInstructions {
+            url: Some("https://example.org/recovery".to_string()),
+            contacts: vec!["Alice <alice@example.org>".to_string()],
+            procedure_sha256: Some(to_hex(&Sha256::digest(b"procedure"))),
+            notes: vec!["Bring ID".to_string(), "Two custodians must be present".to_string()],
+        }
+    }
+
+    #[test]
+    fn instructions_round_trip_through_a_bundle() {
+        let text=instructions().to_text();
+        assert_eq!(RecoveryInstructions::parse(&text).unwrap(), instructions());
+        let bundle=format!("shamir-share v1\n{}", instructions_lines(&text, b"signature"));
+        assert_eq!(read_instructions(&bundle).unwrap(), Some((text, b"signature".to_vec())));
+        assert_eq!(read_instructions("shamir-share v1\n").unwrap(), None);
+        assert!(read_instructions("instructions !!!\ninstructions-signature c2ln\n").is_err());
+    }
+
+    #[test]
+    fn procedure_digest_is_checked() {
+        assert!(instructions().matches_procedure(b"procedure"));
+        assert!(!instructions().matches_procedure(b"edited procedure"));
+        assert!(!RecoveryInstructions::default().matches_procedure(b"procedure"));
+        assert!(RecoveryInstructions::parse(&format!("{HEADER}\nprocedure-sha256 abcd\n")).is_err());
+        assert!(RecoveryInstructions::parse(&format!("{HEADER}\nphone 555\n")).is_err());
+        assert!(RecoveryInstructions::parse("note no header\n").is_err());
+    }
+}
@@ -0,0 +1,471 @@
+use std::collections::HashSet;
+
+use crate::check::CheckValue;
+use crate::context::FieldBackend;
+use crate::crypto::{Parts, ShamirSS, BARYCENTRIC_CUTOFF, GFC256, SMALL_SECRET_MAX};
+use crate::error::ShamirError;
+use crate::sha256::Sha256;
+
+// Bounded, adversary-aware join.
+//
+// Cost with m shares of L bytes:
+//   - plain `join`: O(m^2 * L) field operations;
+//   - `join_with_options` with a known threshold k: O(k^2 + m*k) for the
+//     Lagrange weights plus O(m*k*L) to reconstruct and check every extra
+//     share against the first k;
+//   - the correction fallback decodes every byte position with
+//     Berlekamp-Welch, a linear system of at most m unknowns, so the worst
+//     case is O(m^3*L).
+// `max_shares_considered` caps m, which bounds all of the above no matter how
+// many shares an adversary submits.
+//
+// With a threshold, `extra_shares` decides what happens to shares beyond the
+// first k: `UseFirstK` skips them (O(k^2*L)), `VerifyConsistency` checks them
+// and fails on any mismatch, `ErrorCorrect` runs the correction above.
+// When either fails, `find_mislabeled` checks whether relabeling one or two
+// shares would make them agree, and if so the error names the likely
+// correct indices instead of just the disagreeing shares.
+
+// Above this many shares, `find_mislabeled` does not look for swapped pairs.
+pub const MAX_PAIR_DIAGNOSIS:usize=32;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum ExtraShares{
+    UseFirstK,
+    VerifyConsistency,
+    ErrorCorrect,
+}
+
+#[derive(Debug,Clone)]
+pub struct JoinOptions{
+    pub threshold:Option<i32>,
+    pub max_shares_considered:usize,
+    pub extra_shares:ExtraShares,
+    // Shares from which a join without threshold interpolates with
+    // barycentric weights; see `crypto::BARYCENTRIC_CUTOFF`.
+    pub barycentric_cutoff:usize,
+    // Longest secret joined on the stack; see `crypto::SMALL_SECRET_MAX`.
+    pub small_secret_limit:usize,
+    // Bulk multiply for the barycentric join; `ShamirContext` sets the
+    // detected one.
+    pub backend:FieldBackend,
+}
+
+impl Default for JoinOptions{
+    fn default()->Self{
+        JoinOptions { threshold: None, max_shares_considered: 255, extra_shares: ExtraShares::ErrorCorrect, barycentric_cutoff: BARYCENTRIC_CUTOFF, small_secret_limit: SMALL_SECRET_MAX, backend: FieldBackend::Table }
+    }
+}
+
+// What a join did: the policy applied (None when every share was
+// interpolated because no threshold was given), the shares that
+// reconstructed the secret, the extra shares left unchecked, and the extra
+// shares that disagreed and were outvoted.
+#[derive(Debug,Clone)]
+pub struct JoinReport{
+    pub secret:Vec<u8>,
+    pub policy:Option<ExtraShares>,
+    pub used:Vec<i32>,
+    pub ignored:Vec<i32>,
+    pub rejected:Vec<i32>,
+}
+
+// Provenance of a recovery for logs and recovery screens: `JoinReport` plus
+// whether error correction outvoted any share, which integrity checks
+// passed, SHA-256 of the secret, which can be logged and compared with a
+// digest recorded at split time without revealing the secret, and the id of
+// the recovery token that authorized it (see `token.rs`), if any.
+#[derive(Debug,Clone)]
+pub struct Reconstruction{
+    pub secret:Vec<u8>,
+    pub used:Vec<i32>,
+    pub ignored:Vec<i32>,
+    pub rejected:Vec<i32>,
+    pub corrected:bool,
+    pub checks:Vec<IntegrityCheck>,
+    pub secret_digest:[u8;32],
+    pub token:Option<String>,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum IntegrityCheck{
+    // Every share beyond the threshold lies on the reconstructed polynomial.
+    ExtraSharesConsistent,
+    // Some shares disagreed and a majority of the others outvoted them.
+    MajorityAgreement,
+    // The polynomial matched the dealer's published check value.
+    CheckValue,
+}
+
+impl ShamirSS{
+
+    // `join_with_report`, then the check value if one is given. The check is
+    // evaluated through the shares that reconstructed the secret, so shares
+    // outvoted by error correction do not make it fail.
+    pub fn join_detailed(parts:Parts,options:&JoinOptions,check:Option<&CheckValue>)->Result<Reconstruction,ShamirError>{
+        let total=parts.len().min(options.max_shares_considered.max(1));
+        // Only needed to evaluate the check value.
+        let copy=if check.is_some() { parts.clone() } else { Parts::new() };
+        let report=Self::join_with_report(parts, options)?;
+        let extras=total-report.used.len()-report.ignored.len();
+        let mut checks=Vec::new();
+        match (report.policy, report.rejected.is_empty()) {
+            (Some(ExtraShares::UseFirstK), _) => {}
+            (_, false) => checks.push(IntegrityCheck::MajorityAgreement),
+            (_, true) if extras>0 => checks.push(IntegrityCheck::ExtraSharesConsistent),
+            (_, true) => {}
+        }
+        if let Some(check) = check {
+            let base:Parts=copy.into_iter().filter(|(index, _)| report.used.contains(index)).collect();
+            check.verify(&base)?;
+            checks.push(IntegrityCheck::CheckValue);
+        }
+        Ok(Reconstruction {
+            secret_digest: Sha256::digest(&report.secret),
+            corrected: !report.rejected.is_empty(),
+            secret: report.secret,
+            used: report.used,
+            ignored: report.ignored,
+            rejected: report.rejected,
+            checks,
+            token: None,
+        })
+    }
+
+    // Without a threshold every considered share is interpolated, like `join`.
+    // With one, the first k shares reconstruct and `extra_shares` decides how
+    // the rest are treated. Under `ErrorCorrect`, if some disagree, the
+    // shares are decoded as a Reed-Solomon codeword and the result consistent
+    // with a majority (at least (m+k)/2 shares) is returned, whichever shares
+    // are the bad ones. Otherwise the indices of the shares that disagree with
+    // the first base set are reported.
+    pub fn join_with_options(parts:Parts,options:&JoinOptions)->Result<Vec<u8>,ShamirError>{
+        Self::join_with_report(parts, options).map(|report| report.secret)
+    }
+
+    pub fn join_with_report(parts:Parts,options:&JoinOptions)->Result<JoinReport,ShamirError>{
+        if parts.is_empty() { return Err(ShamirError::NoParts);}
+        if let Some(index) = parts.keys().find(|&&x| !(1..=255).contains(&x)) {
+            return Err(ShamirError::InvalidIndex(*index));
+        }
+        let considered:Vec<(u8,&[u8])>=parts.iter()
+            .take(options.max_shares_considered.max(1))
+            .map(|(x, y)| (*x as u8, y.as_slice()))
+            .collect();
+        let indices=|positions:Vec<usize>| Self::indices(&considered, positions);
+
+        let k=match options.threshold {
+            None => {
+                let secret=Self::join_pairs_tuned(&considered, options.barycentric_cutoff, options.small_secret_limit, options.backend)?;
+                return Ok(JoinReport { secret, policy: None, used: indices((0..considered.len()).collect()), ignored: Vec::new(), rejected: Vec::new() });
+            }
+            Some(k) if k <= 1 => return Err(ShamirError::ThresholdTooSmall),
+            Some(k) => k as usize,
+        };
+        if considered.len() < k {
+            return Err(ShamirError::InsufficientParts { collected: considered.len(), threshold: k });
+        }
+        let len=considered[0].1.len();
+        if considered.iter().any(|(_, y)| y.len()!=len) {
+            return Err(ShamirError::InconsistentLengths);
+        }
+        if len==0 {
+            return Err(ShamirError::EmptySecret);
+        }
+
+        let m=considered.len();
+        let policy=options.extra_shares;
+        let report=|base:&[usize], ignored:Vec<i32>, rejected:Vec<i32>| JoinReport {
+            secret: Self::reconstruct(&considered, base, 0),
+            policy: Some(policy),
+            used: indices(base.to_vec()),
+            ignored,
+            rejected,
+        };
+        let first:Vec<usize>=(0..k).collect();
+        let majority=|disagreeing:usize| 2*(m-disagreeing) >= m+k;
+        // The first k shares may be the bad ones, so shares that disagree with
+        // them are not to blame: name the shares off the decoded polynomial,
+        // or every share when too many are bad to tell which.
+        let culprits=|| indices(Self::correct(&considered, k).filter(|bad| !bad.is_empty() && majority(bad.len())).unwrap_or_else(|| (0..m).collect()));
+        let disagreeing=Self::disagreeing(&considered, &first);
+        match policy {
+            ExtraShares::UseFirstK => return Ok(report(&first, indices((k..m).collect()), Vec::new())),
+            ExtraShares::VerifyConsistency => {
+                if !disagreeing.is_empty() {
+                    return Err(Self::mislabeled_or(&considered, k, culprits()));
+                }
+                return Ok(report(&first, Vec::new(), Vec::new()));
+            }
+            ExtraShares::ErrorCorrect => {}
+        }
+
+        if majority(disagreeing.len()) {
+            return Ok(report(&first, Vec::new(), indices(disagreeing)));
+        }
+        if let Some(rejected)=Self::correct(&considered, k).filter(|rejected| majority(rejected.len())) {
+            let base:Vec<usize>=(0..m).filter(|i| !rejected.contains(i)).take(k).collect();
+            return Ok(report(&base, Vec::new(), indices(rejected)));
+        }
+        Err(Self::mislabeled_or(&considered, k, indices((0..m).collect())))
+    }
+
+    // Positions of the shares off the polynomial that every byte position
+    // decodes to, or None when some position has more than (m-k)/2 errors.
+    fn correct(points:&[(u8,&[u8])],k:usize)->Option<Vec<usize>>{
+        let xs:Vec<u8>=points.iter().map(|(x, _)| *x).collect();
+        let errors=(points.len()-k)/2;
+        let mut bad=vec![false;points.len()];
+        for pos in 0..points[0].1.len() {
+            let ys:Vec<u8>=points.iter().map(|(_, y)| y[pos]).collect();
+            let p=Self::berlekamp_welch(&xs, &ys, k, errors)?;
+            for (i, (&x, &y)) in xs.iter().zip(&ys).enumerate() {
+                bad[i]|=GFC256::eval_slice(&p, x)!=y;
+            }
+        }
+        Some((0..points.len()).filter(|&i| bad[i]).collect())
+    }
+
+    // Berlekamp-Welch: the polynomial of degree < k through all but at most
+    // `errors` of the points (x_i, y_i), lowest degree first. Solves
+    // Q(x_i) = y_i * E(x_i) for a monic error locator E of degree `errors`
+    // and Q of degree < errors+k, then P = Q / E. None if the system has no
+    // solution or E does not divide Q, i.e. there are more errors than that.
+    fn berlekamp_welch(xs:&[u8],ys:&[u8],k:usize,errors:usize)->Option<Vec<u8>>{
+        let q_len=errors+k;
+        let unknowns=q_len+errors;
+        let mut rows:Vec<Vec<u8>>=xs.iter().zip(ys).map(|(&x, &y)| {
+            let powers:Vec<u8>=std::iter::successors(Some(1u8), |&p| Some(GFC256::mul(p, x))).take(q_len+1).collect();
+            let mut row=powers[..q_len].to_vec();
+            row.extend(powers[..errors].iter().map(|&p| GFC256::mul(p, y)));
+            row.push(GFC256::mul(powers[errors], y));
+            row
+        }).collect();
+
+        // Gauss-Jordan elimination; free unknowns are left at 0.
+        let mut pivots=Vec::new();
+        for col in 0..unknowns {
+            let Some(found)=(pivots.len()..rows.len()).find(|&r| rows[r][col]!=0) else { continue };
+            let r=pivots.len();
+            rows.swap(r, found);
+            let inverse=GFC256::div(1, rows[r][col]);
+            for c in rows[r].iter_mut() {
+                *c=GFC256::mul(*c, inverse);
+            }
+            let pivot=rows[r].clone();
+            for (other, row) in rows.iter_mut().enumerate() {
+                let factor=row[col];
+                if other!=r && factor!=0 {
+                    for (c, &p) in row.iter_mut().zip(&pivot) {
+                        *c=GFC256::add(*c, GFC256::mul(factor, p));
+                    }
+                }
+            }
+            pivots.push(col);
+        }
+        if rows[pivots.len()..].iter().any(|row| row[unknowns]!=0) {
+            return None;
+        }
+        let mut solution=vec![0u8;unknowns];
+        for (r, &col) in pivots.iter().enumerate() {
+            solution[col]=rows[r][unknowns];
+        }
+
+        // Long division of Q by E (monic), highest degree first.
+        let mut remainder=solution[..q_len].to_vec();
+        let mut locator=solution[q_len..].to_vec();
+        locator.push(1);
+        let mut quotient=vec![0u8;k];
+        for d in (errors..q_len).rev() {
+            let c=remainder[d];
+            quotient[d-errors]=c;
+            for (slot, &e) in remainder[d-errors..=d].iter_mut().zip(&locator) {
+                *slot=GFC256::add(*slot, GFC256::mul(c, e));
+            }
+        }
+        remainder.iter().all(|&c| c==0).then_some(quotient)
+    }
+
+    // Looks for shares stored under the wrong index: a swapped label, or a
+    // payload copied into another share's file. Such a share is a valid point
+    // of the polynomial at a different x, so it fails consistency checks like
+    // a corrupted share, but relabeling it makes every share agree.
+    //
+    // Each single share, and then each pair, is set aside in turn; if the
+    // remaining shares all agree and each set-aside payload equals the
+    // polynomial at exactly one other index, that is the likely correct
+    // assignment. Returns (labeled index, likely index) pairs, or nothing if
+    // no relabeling, or more than one, explains the shares. The remaining
+    // shares must include at least one beyond the threshold to be checked, so
+    // one mislabeled share needs k+2 shares and a swapped pair k+3.
+    //
+    // Single suspects cost O(m^2*k*L); pairs O(m^3*k*L) and are only tried
+    // for at most `MAX_PAIR_DIAGNOSIS` shares.
+    pub fn find_mislabeled(parts:&Parts,k:i32)->Vec<(i32,i32)>{
+        if k<=1 || parts.keys().any(|x| !(1..=255).contains(x)) {
+            return Vec::new();
+        }
+        let points:Vec<(u8,&[u8])>=parts.iter().map(|(x, y)| (*x as u8, y.as_slice())).collect();
+        let len=points.first().map_or(0, |(_, y)| y.len());
+        if len==0 || points.iter().any(|(_, y)| y.len()!=len) {
+            return Vec::new();
+        }
+        Self::mislabeled(&points, k as usize)
+    }
+
+    fn mislabeled_or(points:&[(u8,&[u8])],k:usize,inconsistent:Vec<i32>)->ShamirError{
+        let fixes=Self::mislabeled(points, k);
+        if fixes.is_empty() { ShamirError::InconsistentShares(inconsistent) } else { ShamirError::MislabeledShares(fixes) }
+    }
+
+    fn mislabeled(points:&[(u8,&[u8])],k:usize)->Vec<(i32,i32)>{
+        let m=points.len();
+        let singles:Vec<Vec<usize>>=(0..m).map(|i| vec![i]).collect();
+        if m>=k+2 {
+            if let Some(fixes) = Self::unique_relabeling(points, k, singles) {
+                return fixes;
+            }
+        }
+        if m>=k+3 && m<=MAX_PAIR_DIAGNOSIS {
+            let pairs=(0..m).flat_map(|i| (i+1..m).map(move |j| vec![i, j])).collect();
+            if let Some(fixes) = Self::unique_relabeling(points, k, pairs) {
+                return fixes;
+            }
+        }
+        Vec::new()
+    }
+
+    // The relabeling for the only suspect set that explains the shares, if
+    // exactly one does.
+    fn unique_relabeling(points:&[(u8,&[u8])],k:usize,suspects:Vec<Vec<usize>>)->Option<Vec<(i32,i32)>>{
+        let mut found=None;
+        for suspect in suspects {
+            let kept:Vec<usize>=(0..points.len()).filter(|i| !suspect.contains(i)).collect();
+            let base=&kept[..k];
+            let consistent=kept[k..].iter().all(|&i| Self::reconstruct(points, base, points[i].0)==points[i].1);
+            if !consistent {
+                continue;
+            }
+            let fixes:Option<Vec<(i32,i32)>>=suspect.iter().map(|&i| {
+                let mut matches=(1..=255u8)
+                    .filter(|&x| x!=points[i].0)
+                    .filter(|&x| Self::reconstruct(points, base, x)==points[i].1);
+                match (matches.next(), matches.next()) {
+                    (Some(x), None) => Some((points[i].0 as i32, x as i32)),
+                    _ => None,
+                }
+            }).collect();
+            if let Some(fixes) = fixes {
+                if found.is_some() {
+                    return None;
+                }
+                found=Some(fixes);
+            }
+        }
+        found
+    }
+
+    fn indices(points:&[(u8,&[u8])],positions:Vec<usize>)->Vec<i32>{
+        positions.into_iter().map(|i| points[i].0 as i32).collect()
+    }
+
+    // Positions of shares outside `base` whose payload differs from the
+    // polynomial through the base shares.
+    fn disagreeing(points:&[(u8,&[u8])],base:&[usize])->Vec<usize>{
+        let members:HashSet<usize>=base.iter().copied().collect();
+        (0..points.len())
+            .filter(|i| !members.contains(i))
+            .filter(|&i| Self::reconstruct(points, base, points[i].0)!=points[i].1)
+            .collect()
+    }
+
+    fn reconstruct(points:&[(u8,&[u8])],base:&[usize],x:u8)->Vec<u8>{
+        let xs:Vec<u8>=base.iter().map(|&i| points[i].0).collect();
+        let weights=GFC256::lagrange_coefficients(&xs, x);
+        (0..points[base[0]].1.len()).map(|pos| {
+            base.iter().zip(&weights).fold(0u8, |acc, (&i, &w)| GFC256::add(acc, GFC256::mul(w, points[i].1[pos])))
+        }).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+    use super::*;
+
+    fn corrupt(parts:&mut Parts,indices:&[i32]){
+        for index in indices {
+            let data=parts.get_mut(index).unwrap();
+            data[0]^=0x5a;
+            if let Some(last)=data.last_mut() {
+                *last^=0x01;
+            }
+        }
+    }
+
+    #[test]
+    fn error_correct_finds_non_adjacent_bad_shares() {
+        let secret=b"error correcting join".to_vec();
+        let mut parts=ShamirSS::split(9, 3, secret.clone()).unwrap();
+        corrupt(&mut parts, &[1, 4, 7]);
+        let options=JoinOptions { threshold: Some(3), ..JoinOptions::default() };
+        let report=ShamirSS::join_with_report(parts, &options).unwrap();
+        assert_eq!(report.secret, secret);
+        assert_eq!(report.rejected, vec![1, 4, 7]);
+        assert!(report.used.iter().all(|i| ![1, 4, 7].contains(i)));
+    }
+
+    #[test]
+    fn error_correct_handles_every_placement_up_to_the_bound() {
+        let secret=b"k".to_vec();
+        let parts=ShamirSS::split(7, 3, secret.clone()).unwrap();
+        let options=JoinOptions { threshold: Some(3), ..JoinOptions::default() };
+        // (7-3)/2 = 2 bad shares can be corrected wherever they are.
+        for a in 1..=7 {
+            for b in a+1..=7 {
+                let mut bad=parts.clone();
+                corrupt(&mut bad, &[a, b]);
+                let report=ShamirSS::join_with_report(bad, &options).unwrap();
+                assert_eq!(report.secret, secret, "bad shares {a} and {b}");
+                assert_eq!(report.rejected, vec![a, b]);
+            }
+        }
+    }
+
+    #[test]
+    fn error_correct_fails_beyond_the_bound() {
+        // Seeded: with random shares, three errors could by chance also be
+        // two errors away from another polynomial.
+        let mut rng=ChaCha20Rng::seed_from_u64(413);
+        let mut parts=ShamirSS::split_with_rng(7, 3, b"secret".to_vec(), &mut rng).unwrap();
+        corrupt(&mut parts, &[2, 4, 6]);
+        let options=JoinOptions { threshold: Some(3), ..JoinOptions::default() };
+        assert!(ShamirSS::join_with_report(parts, &options).is_err());
+    }
+
+    #[test]
+    fn consistent_extra_shares_are_used_as_is() {
+        let parts=ShamirSS::split(5, 3, b"secret".to_vec()).unwrap();
+        let options=JoinOptions { threshold: Some(3), ..JoinOptions::default() };
+        let report=ShamirSS::join_with_report(parts, &options).unwrap();
+        assert_eq!(report.used, vec![1, 2, 3]);
+        assert!(report.rejected.is_empty());
+    }
+
+    // Verification names the bad shares, not those that disagree with the
+    // first k, which may include them.
+    #[test]
+    fn verify_consistency_blames_the_bad_shares() {
+        let mut rng=ChaCha20Rng::seed_from_u64(428);
+        let mut parts=ShamirSS::split_with_rng(9, 3, b"consistency".to_vec(), &mut rng).unwrap();
+        corrupt(&mut parts, &[1, 4, 7]);
+        let options=JoinOptions { threshold: Some(3), extra_shares: ExtraShares::VerifyConsistency, ..JoinOptions::default() };
+        assert_eq!(ShamirSS::join_with_report(parts.clone(), &options).err(), Some(ShamirError::InconsistentShares(vec![1, 4, 7])));
+        corrupt(&mut parts, &[2, 5]);
+        let all=(1..=9).collect();
+        assert_eq!(ShamirSS::join_with_report(parts.clone(), &options).err(), Some(ShamirError::InconsistentShares(all)));
+        let correcting=JoinOptions { threshold: Some(3), ..JoinOptions::default() };
+        assert_eq!(ShamirSS::join_with_report(parts, &correcting).err(), Some(ShamirError::InconsistentShares((1..=9).collect())));
+    }
+}
@@ -0,0 +1,180 @@
+use std::fmt;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sha256::{to_hex, Sha256};
+
+// Append-only, hash-chained log of operations on a sharing. One line per
+// entry:
+//
+//   seq \t unix-time \t event \t fingerprints (comma list) \t note \t prev \t hash
+//
+// where hash = SHA-256 over the first six fields joined by tabs and prev is the
+// previous entry's hash (64 zeros for the first entry). Editing, dropping or
+// reordering any line breaks every hash after it.
+
+const GENESIS:&str="0000000000000000000000000000000000000000000000000000000000000000";
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum JournalEvent{
+    Split,
+    Refresh,
+    Reshare,
+    Rotate,
+    Recovery,
+}
+
+impl fmt::Display for JournalEvent{
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        f.write_str(match self {
+            JournalEvent::Split => "split",
+            JournalEvent::Refresh => "refresh",
+            JournalEvent::Reshare => "reshare",
+            JournalEvent::Rotate => "rotate",
+            JournalEvent::Recovery => "recovery",
+        })
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct JournalEntry{
+    pub seq:u64,
+    pub timestamp:u64,
+    pub event:String,
+    pub fingerprints:Vec<String>,
+    pub note:String,
+    pub prev:String,
+    pub hash:String,
+}
+
+impl JournalEntry{
+    fn digest(&self)->String{
+        to_hex(&Sha256::digest(self.body().as_bytes()))
+    }
+
+    fn body(&self)->String{
+        format!("{}\t{}\t{}\t{}\t{}\t{}", self.seq, self.timestamp, self.event, self.fingerprints.join(","), self.note, self.prev)
+    }
+}
+
+pub fn append_entry(path:&Path,event:JournalEvent,fingerprints:&[String],note:&str)->Result<JournalEntry,String>{
+    let now=SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    append_entry_at(path, event, fingerprints, note, now)
+}
+
+// `append_entry` with the caller's clock, in seconds since the epoch, for
+// reproducible journals; 0 records no time.
+pub fn append_entry_at(path:&Path,event:JournalEvent,fingerprints:&[String],note:&str,timestamp:u64)->Result<JournalEntry,String>{
+    let entries=if path.exists() { read_journal(path)? } else { Vec::new() };
+    verify_entries(&entries)?;
+
+    let clean=|v:&str| v.replace(['\t', '\n', '\r', ','], " ");
+    let mut entry=JournalEntry {
+        seq: entries.last().map(|e| e.seq+1).unwrap_or(1),
+        timestamp,
+        event: event.to_string(),
+        fingerprints: fingerprints.iter().map(|f| clean(f)).collect(),
+        note: note.replace(['\t', '\n', '\r'], " "),
+        prev: entries.last().map(|e| e.hash.clone()).unwrap_or(GENESIS.to_string()),
+        hash: String::new(),
+    };
+    entry.hash=entry.digest();
+
+    let mut file=OpenOptions::new().create(true).append(true).open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    writeln!(file, "{}\t{}", entry.body(), entry.hash).map_err(|e| format!("{}: {e}", path.display()))?;
+    file.sync_all().map_err(|e| format!("{}: {e}", path.display()))?;
+    Ok(entry)
+}
+
+pub fn read_journal(path:&Path)->Result<Vec<JournalEntry>,String>{
+    let text=fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    text.lines().filter(|l| !l.is_empty()).enumerate().map(|(i, line)| {
+        let fields:Vec<&str>=line.split('\t').collect();
+        if fields.len()!=7 {
+            return Err(format!("Journal line {} is malformed", i+1));
+        }
+        Ok(JournalEntry {
+            seq: fields[0].parse().map_err(|_| format!("Journal line {} has a bad sequence number", i+1))?,
+            timestamp: fields[1].parse().map_err(|_| format!("Journal line {} has a bad timestamp", i+1))?,
+            event: fields[2].to_string(),
+            fingerprints: fields[3].split(',').filter(|f| !f.is_empty()).map(|f| f.to_string()).collect(),
+            note: fields[4].to_string(),
+            prev: fields[5].to_string(),
+            hash: fields[6].to_string(),
+        })
+    }).collect()
+}
+
+// Returns the number of entries, or the first entry where the chain breaks.
+pub fn verify_journal(path:&Path)->Result<usize,String>{
+    let entries=read_journal(path)?;
+    verify_entries(&entries)?;
+    Ok(entries.len())
+}
+
+fn verify_entries(entries:&[JournalEntry])->Result<(),String>{
+    let mut prev=GENESIS.to_string();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.seq!=i as u64+1 {
+            return Err(format!("Journal entry {} has sequence number {}", i+1, entry.seq));
+        }
+        if entry.prev!=prev {
+            return Err(format!("Journal entry {} does not chain to the previous entry", entry.seq));
+        }
+        if entry.hash!=entry.digest() {
+            return Err(format!("Journal entry {} has been modified", entry.seq));
+        }
+        prev=entry.hash.clone();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn journal(name:&str)->std::path::PathBuf{
+        let path=std::env::temp_dir().join(format!("shamir-journal-{name}-{}", std::process::id()));
+        let _=fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn entries_chain_and_clean_their_fields() {
+        let path=journal("chain");
+        let first=append_entry_at(&path, JournalEvent::Split, &["aa".to_string(), "b\tb".to_string()], "first\nline", 100).unwrap();
+        let second=append_entry_at(&path, JournalEvent::Recovery, &[], "", 200).unwrap();
+        assert_eq!((first.seq, first.prev.as_str()), (1, GENESIS));
+        assert_eq!((second.seq, second.prev.as_str()), (2, first.hash.as_str()));
+        assert_eq!(verify_journal(&path), Ok(2));
+
+        let entries=read_journal(&path).unwrap();
+        assert_eq!(entries, vec![first, second]);
+        assert_eq!(entries[0].fingerprints, vec!["aa", "b b"]);
+        assert_eq!((entries[0].note.as_str(), entries[1].event.as_str()), ("first line", "recovery"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn edits_break_the_chain() {
+        let path=journal("edit");
+        for event in [JournalEvent::Split, JournalEvent::Refresh, JournalEvent::Rotate] {
+            append_entry_at(&path, event, &["aa".to_string()], "", 0).unwrap();
+        }
+        let text=fs::read_to_string(&path).unwrap();
+
+        fs::write(&path, text.replacen("refresh", "reshare", 1)).unwrap();
+        assert_eq!(verify_journal(&path), Err("Journal entry 2 has been modified".to_string()));
+        assert!(append_entry_at(&path, JournalEvent::Recovery, &[], "", 0).is_err());
+
+        let lines:Vec<&str>=text.lines().collect();
+        fs::write(&path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(verify_journal(&path), Err("Journal entry 2 has sequence number 3".to_string()));
+
+        fs::write(&path, format!("{}\nnot an entry\n", lines[0])).unwrap();
+        assert_eq!(verify_journal(&path), Err("Journal line 2 is malformed".to_string()));
+        fs::remove_file(&path).unwrap();
+    }
+}
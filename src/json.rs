@@ -0,0 +1,45 @@
+// JSON documents (plans, reports, share documents, the JSON Schema) are
+// serde_json values; `JsonExt` keeps the crate's builders short. Keys come
+// out sorted and indented by two spaces, as `serde_json::to_string_pretty`
+// writes them.
+
+pub use serde_json::Value as Json;
+
+pub trait JsonExt{
+    fn object<const N:usize>(fields:[(&str,Json);N])->Json;
+    fn str(value:&str)->Json;
+    fn to_pretty(&self)->String;
+    fn parse(text:&str)->Result<Json,String>;
+}
+
+impl JsonExt for Json{
+
+    fn object<const N:usize>(fields:[(&str,Json);N])->Json{
+        Json::Object(fields.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+    }
+
+    fn str(value:&str)->Json{
+        Json::String(value.to_string())
+    }
+
+    fn to_pretty(&self)->String{
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    fn parse(text:&str)->Result<Json,String>{
+        serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {e}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builders_write_sorted_pretty_json() {
+        let doc=Json::object([("threshold", Json::from(2)), ("scheme", Json::str("shamir"))]);
+        assert_eq!(doc.to_pretty(), "{\n  \"scheme\": \"shamir\",\n  \"threshold\": 2\n}");
+        assert_eq!(Json::parse(&doc.to_pretty()).unwrap(), doc);
+        assert!(Json::parse("{\"threshold\": }").unwrap_err().starts_with("Invalid JSON"));
+    }
+}
@@ -0,0 +1,247 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::crypto::{zeroize, Parts};
+use crate::encoding::decode_any_share;
+
+// Shares for a Kubernetes init container. Each operator keeps its share in a
+// Secret in its own namespace; the shares reach the pod as Secret volumes
+// (mounted at e.g. /shares/ops, /shares/sec) or as environment variables
+// from `secretKeyRef`, and the init container joins them into an in-memory
+// emptyDir for the main container. No single namespace's Secret, and no
+// single operator's RBAC, is enough to read the credential.
+//
+// Because a share file that other users or containers can read or swap is
+// as good as leaked, reading is strict:
+//   - a key file must resolve (through the kubelet's `..data` symlinks) to a
+//     regular file inside its mount, no larger than MAX_SHARE_FILE_BYTES;
+//   - the file must be owned by root or the current user and give no access
+//     to others; group read is accepted only with `allow_group_read`, for
+//     `defaultMode: 0440` with fsGroup when the pod does not run as root;
+//   - the mount directory must not be writable by group or others, unless
+//     it is root-owned and sticky like the kubelet's own Secret mounts;
+//   - two sources giving the same share index is an error.
+// Environment variables cannot be permission-checked; each one is removed
+// from the environment once read, so processes started later do not inherit
+// it. Prefer volumes. Permission checks need Unix; elsewhere reading fails.
+
+pub const MAX_SHARE_FILE_BYTES:u64=64*1024;
+// Entries the kubelet's atomic writer keeps beside the key files.
+const ATOMIC_WRITER_PREFIX:&str="..";
+
+#[derive(Debug,Clone,Default)]
+pub struct ShareMounts{
+    // Secret volume mount points; every key in each is one share.
+    pub dirs:Vec<PathBuf>,
+    // Single share files (a `subPath` or one key of a volume).
+    pub files:Vec<PathBuf>,
+    // Environment variables holding one share each.
+    pub env:Vec<String>,
+    pub allow_group_read:bool,
+}
+
+// Reads every share the pod was given. The error names the offending file or
+// variable; share bytes never appear in it.
+pub fn read_shares(mounts:&ShareMounts)->Result<Parts,String>{
+    let mut parts=Parts::new();
+    let mut sources:Vec<(i32,String)>=Vec::new();
+    let mut add=|source:String, text:&mut String| -> Result<(), String> {
+        let decoded=decode_any_share(text);
+        // SAFETY: zero bytes are valid UTF-8.
+        zeroize(unsafe { text.as_bytes_mut() });
+        let (index, data)=decoded.map_err(|e| format!("{source}: {e}"))?;
+        if let Some((_, first))=sources.iter().find(|(i, _)| *i==index) {
+            return Err(format!("Share {index} given twice: {first} and {source}"));
+        }
+        sources.push((index, source));
+        parts.insert(index, data);
+        Ok(())
+    };
+
+    for dir in &mounts.dirs {
+        check_mount(dir)?;
+        let mut keys=Vec::new();
+        for entry in fs::read_dir(dir).map_err(|e| format!("{}: {e}", dir.display()))? {
+            let entry=entry.map_err(|e| format!("{}: {e}", dir.display()))?;
+            if !entry.file_name().to_string_lossy().starts_with(ATOMIC_WRITER_PREFIX) {
+                keys.push(entry.path());
+            }
+        }
+        keys.sort();
+        for key in keys {
+            let mut text=read_share_file(dir, &key, mounts.allow_group_read)?;
+            add(key.display().to_string(), &mut text)?;
+        }
+    }
+    for file in &mounts.files {
+        let dir=file.parent().filter(|p| !p.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        check_mount(dir)?;
+        let mut text=read_share_file(dir, file, mounts.allow_group_read)?;
+        add(file.display().to_string(), &mut text)?;
+    }
+    for name in &mounts.env {
+        let mut text=std::env::var(name).map_err(|e| format!("${name}: {e}"))?;
+        std::env::remove_var(name);
+        add(format!("${name}"), &mut text)?;
+    }
+    Ok(parts)
+}
+
+// Reads one key file of the volume mounted at `mount`, after the checks above.
+pub fn read_share_file(mount:&Path,path:&Path,allow_group_read:bool)->Result<String,String>{
+    let mount=fs::canonicalize(mount).map_err(|e| format!("{}: {e}", mount.display()))?;
+    let target=fs::canonicalize(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    if !target.starts_with(&mount) {
+        return Err(format!("{}: resolves to {}, outside {}", path.display(), target.display(), mount.display()));
+    }
+    let metadata=fs::metadata(&target).map_err(|e| format!("{}: {e}", path.display()))?;
+    if !metadata.is_file() {
+        return Err(format!("{}: not a regular file", path.display()));
+    }
+    if metadata.len()>MAX_SHARE_FILE_BYTES {
+        return Err(format!("{}: {} bytes, more than the {MAX_SHARE_FILE_BYTES}-byte limit for a share", path.display(), metadata.len()));
+    }
+    sys::check_file(path, &metadata, allow_group_read)?;
+    fs::read_to_string(&target).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+fn check_mount(dir:&Path)->Result<(),String>{
+    let metadata=fs::metadata(dir).map_err(|e| format!("{}: {e}", dir.display()))?;
+    if !metadata.is_dir() {
+        return Err(format!("{}: not a directory", dir.display()));
+    }
+    sys::check_dir(dir, &metadata)
+}
+
+#[cfg(unix)]
+mod sys{
+    use std::fs::Metadata;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::Path;
+
+    extern "C" {
+        fn geteuid()->u32;
+    }
+
+    pub fn check_file(path:&Path,metadata:&Metadata,allow_group_read:bool)->Result<(),String>{
+        // SAFETY: geteuid has no arguments and cannot fail.
+        let euid=unsafe { geteuid() };
+        if metadata.uid()!=0 && metadata.uid()!=euid {
+            return Err(format!("{}: owned by uid {}, not root or this user ({euid})", path.display(), metadata.uid()));
+        }
+        let allowed=if allow_group_read { 0o740 } else { 0o700 };
+        let mode=metadata.mode()&0o777;
+        if mode&!allowed!=0 {
+            return Err(format!("{}: mode {mode:04o} gives access beyond {allowed:04o}; set defaultMode on the Secret volume", path.display()));
+        }
+        Ok(())
+    }
+
+    // The kubelet mounts Secret volumes as root-owned sticky tmpfs (1777):
+    // others cannot replace root's key links there, and a file they add
+    // fails the owner check.
+    pub fn check_dir(dir:&Path,metadata:&Metadata)->Result<(),String>{
+        let mode=metadata.mode()&0o7777;
+        if mode&0o022!=0 && !(mode&0o1000!=0 && metadata.uid()==0) {
+            return Err(format!("{}: mode {mode:04o} lets others replace shares", dir.display()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod sys{
+    use std::fs::Metadata;
+    use std::path::Path;
+
+    pub fn check_file(path:&Path,_metadata:&Metadata,_allow_group_read:bool)->Result<(),String>{
+        Err(format!("{}: share file permissions can only be checked on Unix", path.display()))
+    }
+
+    pub fn check_dir(dir:&Path,_metadata:&Metadata)->Result<(),String>{
+        Err(format!("{}: share file permissions can only be checked on Unix", dir.display()))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    use crate::crypto::ShamirSS;
+    use crate::encoding::encode_share;
+
+    fn mount(name:&str)->PathBuf{
+        let dir=std::env::temp_dir().join(format!("shamir-k8s-{name}-{}", std::process::id()));
+        let _=fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        chmod(&dir, 0o700);
+        dir
+    }
+
+    fn chmod(path:&Path,mode:u32){
+        fs::set_permissions(path, fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    // Lays out a key the way the kubelet's atomic writer does:
+    // key -> ..data/key, ..data -> ..<timestamp>.
+    fn secret_key(dir:&Path,key:&str,text:&str,mode:u32){
+        let data=dir.join("..2026_01_01");
+        fs::create_dir_all(&data).unwrap();
+        fs::write(data.join(key), text).unwrap();
+        chmod(&data.join(key), mode);
+        if !dir.join("..data").exists() {
+            symlink("..2026_01_01", dir.join("..data")).unwrap();
+        }
+        symlink(Path::new("..data").join(key), dir.join(key)).unwrap();
+    }
+
+    #[test]
+    fn reads_volumes_files_and_environment() {
+        let parts=ShamirSS::split(3, 3, b"credential".to_vec()).unwrap();
+        let (ops, sec)=(mount("ops"), mount("sec"));
+        secret_key(&ops, "share", &encode_share(1, &parts[&1]), 0o400);
+        fs::write(sec.join("share"), encode_share(2, &parts[&2])).unwrap();
+        chmod(&sec.join("share"), 0o440);
+        let variable=format!("SHAMIR_K8S_TEST_SHARE_{}", std::process::id());
+        std::env::set_var(&variable, encode_share(3, &parts[&3]));
+
+        let mut mounts=ShareMounts { dirs: vec![ops.clone()], files: vec![sec.join("share")], env: vec![variable.clone()], allow_group_read: false };
+        assert!(read_shares(&mounts).unwrap_err().contains("mode 0440"));
+        assert!(std::env::var(&variable).is_ok());
+        mounts.allow_group_read=true;
+        assert_eq!(ShamirSS::join(read_shares(&mounts).unwrap()).unwrap(), b"credential");
+        assert!(std::env::var(&variable).is_err());
+
+        mounts.env.clear();
+        mounts.files.push(ops.join("share"));
+        assert!(read_shares(&mounts).unwrap_err().contains("given twice"));
+        fs::remove_dir_all(&ops).unwrap();
+        fs::remove_dir_all(&sec).unwrap();
+    }
+
+    #[test]
+    fn refuses_files_others_could_read_or_swap() {
+        let (dir, outside)=(mount("strict"), mount("outside"));
+        fs::write(outside.join("share"), encode_share(1, b"share")).unwrap();
+        chmod(&outside.join("share"), 0o600);
+        symlink(outside.join("share"), dir.join("escape")).unwrap();
+        assert!(read_share_file(&dir, &dir.join("escape"), false).unwrap_err().contains("outside"));
+
+        fs::write(dir.join("open"), encode_share(1, b"share")).unwrap();
+        chmod(&dir.join("open"), 0o644);
+        assert!(read_share_file(&dir, &dir.join("open"), true).is_err());
+        chmod(&dir.join("open"), 0o600);
+        assert!(read_share_file(&dir, &dir.join("open"), false).is_ok());
+
+        fs::write(dir.join("large"), vec![b'A';MAX_SHARE_FILE_BYTES as usize+1]).unwrap();
+        chmod(&dir.join("large"), 0o600);
+        assert!(read_share_file(&dir, &dir.join("large"), false).unwrap_err().contains("limit"));
+
+        chmod(&dir, 0o777);
+        let mounts=ShareMounts { files: vec![dir.join("open")], ..ShareMounts::default() };
+        assert!(read_shares(&mounts).unwrap_err().contains("lets others replace shares"));
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_dir_all(&outside).unwrap();
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt;
+
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::encoding::{decode_any_share, encode_bundle};
+use crate::sha256::{to_hex, Sha256};
+
+// Splitting a keypair: only the private half is secret, so only it goes
+// through Shamir. The public half rides along in plain sight, as a line in
+// every share bundle:
+//
+//   public-key ed25519 <base64 of the raw 32-byte key>
+//
+// so custodians and auditors can tell which identity (SSH host key, signing
+// key, age recipient) a share protects by reading the file, without gathering
+// a quorum. Like the other bundle lines it is not authenticated on its own: a
+// dealer who wants it tamper-evident signs it, as with hints.
+//
+// Keys are raw bytes, not parsed: Ed25519 and X25519 keys are the 32-byte
+// forms (an Ed25519 private key may also be the 64-byte seed||public form,
+// whose public half must then match), RSA keys are DER (PKCS#1 or PKCS#8
+// private, PKCS#1 or SubjectPublicKeyInfo public). Nothing here derives a
+// public key from a private one.
+
+const LINE_PREFIX:&str="public-key ";
+const ED25519_EXPANDED_LEN:usize=64;
+const RAW_KEY_LEN:usize=32;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum KeyAlgorithm{
+    Ed25519,
+    X25519,
+    Rsa,
+}
+
+impl KeyAlgorithm{
+
+    pub fn name(&self)->&'static str{
+        match self {
+            KeyAlgorithm::Ed25519 => "ed25519",
+            KeyAlgorithm::X25519 => "x25519",
+            KeyAlgorithm::Rsa => "rsa",
+        }
+    }
+
+    pub fn parse(name:&str)->Result<KeyAlgorithm,String>{
+        match name.trim().to_ascii_lowercase().as_str() {
+            "ed25519" => Ok(KeyAlgorithm::Ed25519),
+            "x25519" => Ok(KeyAlgorithm::X25519),
+            "rsa" => Ok(KeyAlgorithm::Rsa),
+            _ => Err(format!("Unknown key algorithm {name}; expected ed25519, x25519 or rsa")),
+        }
+    }
+
+    fn check_public(&self,key:&[u8])->Result<(),String>{
+        let ok=match self {
+            KeyAlgorithm::Ed25519 | KeyAlgorithm::X25519 => key.len()==RAW_KEY_LEN,
+            KeyAlgorithm::Rsa => is_der_sequence(key),
+        };
+        if ok { Ok(()) } else { Err(format!("Not a {} public key ({} bytes)", self.name(), key.len())) }
+    }
+
+    fn check_private(&self,key:&[u8],public:&[u8])->Result<(),String>{
+        match self {
+            KeyAlgorithm::Ed25519 if key.len()==ED25519_EXPANDED_LEN => {
+                if key.get(RAW_KEY_LEN..)!=Some(public) {
+                    return Err("Ed25519 private key carries a different public key".to_string());
+                }
+                Ok(())
+            }
+            KeyAlgorithm::Ed25519 | KeyAlgorithm::X25519 if key.len()==RAW_KEY_LEN => Ok(()),
+            KeyAlgorithm::Rsa if is_der_sequence(key) => Ok(()),
+            _ => Err(format!("Not a {} private key ({} bytes)", self.name(), key.len())),
+        }
+    }
+}
+
+// DER starts with a SEQUENCE tag; enough to catch a PEM file or a raw key
+// passed where DER was meant.
+fn is_der_sequence(key:&[u8])->bool{
+    key.len()>2 && key.first()==Some(&0x30)
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct PublicKey{
+    pub algorithm:KeyAlgorithm,
+    pub key:Vec<u8>,
+}
+
+impl PublicKey{
+
+    pub fn new(algorithm:KeyAlgorithm,key:&[u8])->Result<PublicKey,String>{
+        algorithm.check_public(key)?;
+        Ok(PublicKey { algorithm, key: key.to_vec() })
+    }
+
+    // First 8 bytes of SHA-256 of the key bytes, hex: short enough to read
+    // out over the phone when comparing against a known identity.
+    pub fn fingerprint(&self)->String{
+        to_hex(&Sha256::digest(&self.key)[..8])
+    }
+
+    pub fn line(&self)->String{
+        format!("{LINE_PREFIX}{} {}\n", self.algorithm.name(), general_purpose::STANDARD.encode(&self.key))
+    }
+}
+
+impl fmt::Display for PublicKey{
+    fn fmt(&self,f:&mut fmt::Formatter<'_>)->fmt::Result{
+        write!(f, "{} {}", self.algorithm.name(), self.fingerprint())
+    }
+}
+
+// The public key a share bundle carries, if any.
+pub fn read_public_key(bundle:&str)->Result<Option<PublicKey>,String>{
+    let Some(value)=bundle.lines().find_map(|line| line.trim().strip_prefix(LINE_PREFIX)) else { return Ok(None) };
+    let (algorithm, key)=value.trim().split_once(' ').ok_or(format!("Malformed public key line {value}"))?;
+    let key=general_purpose::STANDARD.decode(key.trim()).map_err(|e| format!("Malformed public key: {e}"))?;
+    PublicKey::new(KeyAlgorithm::parse(algorithm)?, &key).map(Some)
+}
+
+// Splits `private` k of n and returns one share bundle per index, each with
+// the public key line appended.
+pub fn split_keypair(n:i32,k:i32,public:&PublicKey,private:&[u8])->Result<Vec<(i32,String)>,String>{
+    public.algorithm.check_private(private, &public.key)?;
+    let parts=ShamirSS::split(n, k, private.to_vec()).map_err(|e| e.to_string())?;
+    Ok(parts.into_iter().map(|(index, mut data)| {
+        let bundle=encode_bundle(index, &data)+&public.line();
+        zeroize(&mut data);
+        (index, bundle)
+    }).collect())
+}
+
+// Joins share bundles from `split_keypair`. Every share must carry the same
+// public key; an expanded Ed25519 private key is checked against it.
+pub fn join_keypair(bundles:&[String])->Result<(PublicKey,Vec<u8>),String>{
+    let mut public:Option<PublicKey>=None;
+    let mut parts=Parts::new();
+    for bundle in bundles {
+        let key=read_public_key(bundle)?.ok_or("Share carries no public key")?;
+        if public.as_ref().is_some_and(|p| *p!=key) {
+            return Err(format!("Shares protect different public keys ({} and {key})", public.as_ref().map(|p| p.to_string()).unwrap_or_default()));
+        }
+        public=Some(key);
+        let (index, data)=decode_any_share(bundle)?;
+        parts.insert(index, data);
+    }
+    let public=public.ok_or("No shares")?;
+    let mut private=ShamirSS::join(parts).map_err(|e| e.to_string())?;
+    if let Err(e)=public.algorithm.check_private(&private, &public.key) {
+        zeroize(&mut private);
+        return Err(e);
+    }
+    Ok((public, private))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_keypair_carries_the_public_key() {
+        let public=PublicKey::new(KeyAlgorithm::parse(" Ed25519 ").unwrap(), &[7u8;32]).unwrap();
+        let mut private=[9u8;64];
+        private[32..].copy_from_slice(&public.key);
+        let bundles:Vec<String>=split_keypair(3, 2, &public, &private).unwrap().into_iter().map(|(_, b)| b).collect();
+        assert_eq!(read_public_key(&bundles[0]).unwrap(), Some(public.clone()));
+        assert_eq!(public.to_string(), format!("ed25519 {}", public.fingerprint()));
+
+        let (joined, key)=join_keypair(&bundles[1..]).unwrap();
+        assert_eq!((joined, key), (public, private.to_vec()));
+    }
+
+    #[test]
+    fn mismatched_keys_are_refused() {
+        let public=PublicKey::new(KeyAlgorithm::Ed25519, &[7u8;32]).unwrap();
+        let other=PublicKey::new(KeyAlgorithm::X25519, &[8u8;32]).unwrap();
+        assert!(KeyAlgorithm::parse("dsa").is_err());
+        assert!(PublicKey::new(KeyAlgorithm::Ed25519, &[7u8;31]).is_err());
+        assert!(PublicKey::new(KeyAlgorithm::Rsa, &[7u8;32]).is_err());
+        assert!(split_keypair(3, 2, &public, &[9u8;64]).is_err());
+        assert!(split_keypair(3, 2, &public, &[9u8;33]).is_err());
+
+        let mine=split_keypair(3, 2, &public, &[1u8;32]).unwrap();
+        let theirs=split_keypair(3, 2, &other, &[2u8;32]).unwrap();
+        assert!(join_keypair(&[mine[0].1.clone(), theirs[1].1.clone()]).is_err());
+        assert!(join_keypair(&[]).is_err());
+        assert_eq!(read_public_key(&encode_bundle(1, b"share")).unwrap(), None);
+    }
+}
@@ -0,0 +1,142 @@
+// Without the default `std` feature only the side-effect-free math is
+// built (`pure`, `tables`, `error`, and `fixed` with `fixed-capacity`); see
+// `pure.rs`. The pure-only build follows from `not(feature = "std")` alone:
+// `core-pure` names it and adds nothing, so enabling it next to `std` (as
+// `--all-features` does) builds the full crate.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod ack;
+#[cfg(feature = "age-plugin")]
+pub mod age_plugin;
+#[cfg(feature = "test-util")]
+pub mod adversary;
+#[cfg(feature = "std")]
+pub mod allocator;
+#[cfg(feature = "std")]
+pub mod archive;
+#[cfg(feature = "std")]
+pub mod bulk;
+#[cfg(feature = "std")]
+pub mod check;
+#[cfg(feature = "std")]
+pub mod chunked;
+#[cfg(feature = "std")]
+pub mod commitment;
+#[cfg(feature = "std")]
+pub mod context;
+#[cfg(feature = "std")]
+pub mod crypto;
+#[cfg(feature = "std")]
+pub mod dealer;
+#[cfg(feature = "std")]
+pub mod dealing;
+#[cfg(feature = "std")]
+pub mod device;
+#[cfg(feature = "std")]
+pub mod encoding;
+#[cfg(feature = "std")]
+pub mod envelope;
+#[cfg(feature = "std")]
+pub mod ffi;
+#[cfg(feature = "std")]
+pub mod field;
+#[cfg(feature = "fixed-capacity")]
+pub mod fixed;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod escrow;
+#[cfg(feature = "std")]
+pub mod exchange;
+#[cfg(feature = "formats")]
+pub mod formats;
+#[cfg(feature = "std")]
+pub mod fountain;
+#[cfg(feature = "gpu")]
+pub mod gpu;
+#[cfg(feature = "std")]
+pub mod guard;
+#[cfg(feature = "std")]
+pub mod hardening;
+#[cfg(feature = "std")]
+pub mod hint;
+#[cfg(feature = "hsm")]
+pub mod hsm;
+#[cfg(feature = "std")]
+pub mod hybrid;
+#[cfg(feature = "std")]
+pub mod inplace;
+#[cfg(feature = "std")]
+pub mod instructions;
+#[cfg(feature = "std")]
+pub mod join;
+#[cfg(feature = "std")]
+pub mod json;
+#[cfg(feature = "std")]
+pub mod keypair;
+#[cfg(feature = "k8s")]
+pub mod k8s;
+#[cfg(feature = "std")]
+pub mod lint;
+#[cfg(feature = "std")]
+pub mod journal;
+#[cfg(feature = "mmap")]
+pub mod mmap;
+#[cfg(feature = "mnemonic")]
+pub mod mnemonic;
+#[cfg(feature = "std")]
+pub mod plate;
+#[cfg(feature = "pq-wrap")]
+pub mod pq_wrap;
+#[cfg(feature = "std")]
+pub mod prepared;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(feature = "proto")]
+pub mod proto;
+pub mod pure;
+#[cfg(feature = "std")]
+pub mod qr;
+#[cfg(feature = "std")]
+pub mod receipt;
+#[cfg(feature = "std")]
+pub mod revocation;
+#[cfg(feature = "std")]
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod schema;
+#[cfg(feature = "std")]
+pub mod scheme;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod sparse;
+#[cfg(feature = "spec")]
+pub mod spec;
+#[cfg(feature = "std")]
+pub mod sha256;
+#[cfg(feature = "std")]
+pub mod shareset;
+#[cfg(feature = "std")]
+pub mod stats;
+#[cfg(feature = "std")]
+pub mod stream;
+pub mod tables;
+#[cfg(feature = "test-util")]
+pub mod testutil;
+#[cfg(feature = "std")]
+pub mod token;
+#[cfg(feature = "std")]
+pub mod transcript;
+#[cfg(feature = "std")]
+pub mod transform;
+#[cfg(feature = "std")]
+pub mod verifiable;
+#[cfg(feature = "std")]
+pub mod watermark;
+
+pub use error::ShamirError;
+#[cfg(feature = "std")]
+pub use hardening::scrub;
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::crypto::{Parts, Share};
+
+// Cheap sanity checks for freshly issued shares, aimed at catastrophic RNG
+// or copy-paste failures rather than subtle bias (see `stats` for that).
+// Payload checks only apply from MIN_LINT_LEN bytes up: a short honest share
+// is all zeros or repeats another often enough to make them noise.
+
+pub const MIN_LINT_LEN:usize=4;
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum LintWarning{
+    AllZeros { index:i32 },
+    RepeatedByte { index:i32, byte:u8 },
+    SameAsShare { index:i32, other:i32 },
+    IndexOutOfRange { index:i32, expected:RangeInclusive<i32> },
+    EmptyPayload { index:i32 },
+}
+
+impl fmt::Display for LintWarning{
+    fn fmt(&self, f:&mut fmt::Formatter<'_>)->fmt::Result{
+        match self {
+            LintWarning::AllZeros { index } => write!(f, "share {index}: payload is all zeros"),
+            LintWarning::RepeatedByte { index, byte } => write!(f, "share {index}: payload is the byte {byte:#04x} repeated"),
+            LintWarning::SameAsShare { index, other } => write!(f, "share {index}: payload equals share {other}"),
+            LintWarning::IndexOutOfRange { index, expected } => write!(f, "share {index}: index outside expected range {}..={}", expected.start(), expected.end()),
+            LintWarning::EmptyPayload { index } => write!(f, "share {index}: payload is empty"),
+        }
+    }
+}
+
+pub trait ShareLint{
+    fn lint(&self)->Vec<LintWarning>;
+}
+
+impl ShareLint for Share{
+    fn lint(&self)->Vec<LintWarning>{
+        let (index, data)=(self.0, &self.1);
+        let mut warnings=Vec::new();
+        if !(1..=255).contains(&index) {
+            warnings.push(LintWarning::IndexOutOfRange { index, expected: 1..=255 });
+        }
+        match data.first() {
+            None => warnings.push(LintWarning::EmptyPayload { index }),
+            Some(_) if data.len()<MIN_LINT_LEN => {}
+            Some(0) if data.iter().all(|&b| b==0) => warnings.push(LintWarning::AllZeros { index }),
+            Some(&byte) if data.iter().all(|&b| b==byte) => warnings.push(LintWarning::RepeatedByte { index, byte }),
+            Some(_) => {}
+        }
+        warnings
+    }
+}
+
+// Lints every share, then the set: identical payloads and, when given, indices
+// outside the range the dealer issued (e.g. 1..=n).
+pub fn lint_shares(parts:&Parts,expected:Option<RangeInclusive<i32>>)->Vec<LintWarning>{
+    let mut warnings=Vec::new();
+    let mut first_with:HashMap<&[u8],i32>=HashMap::new();
+    for (index, data) in parts {
+        warnings.extend((*index, data.clone()).lint());
+        if let Some(range)=&expected {
+            if (1..=255).contains(index) && !range.contains(index) {
+                warnings.push(LintWarning::IndexOutOfRange { index: *index, expected: range.clone() });
+            }
+        }
+        if data.len()>=MIN_LINT_LEN {
+            match first_with.get(data.as_slice()) {
+                Some(&other) => warnings.push(LintWarning::SameAsShare { index: *index, other }),
+                None => { first_with.insert(data, *index); }
+            }
+        }
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn degenerate_shares_are_flagged() {
+        assert_eq!((1, vec![0; 8]).lint(), vec![LintWarning::AllZeros { index: 1 }]);
+        assert_eq!((2, vec![7; 8]).lint(), vec![LintWarning::RepeatedByte { index: 2, byte: 7 }]);
+        assert_eq!((3, Vec::new()).lint(), vec![LintWarning::EmptyPayload { index: 3 }]);
+        assert_eq!((0, vec![1, 2, 3, 4]).lint(), vec![LintWarning::IndexOutOfRange { index: 0, expected: 1..=255 }]);
+        // Below MIN_LINT_LEN a zero payload is plausible.
+        assert!((4, vec![0; MIN_LINT_LEN-1]).lint().is_empty());
+        assert!((5, vec![1, 2, 3, 4]).lint().is_empty());
+    }
+
+    #[test]
+    fn set_checks_copies_and_issued_range() {
+        let parts:Parts=[(1, vec![1, 2, 3, 4]), (2, vec![5, 6, 7, 8]), (3, vec![1, 2, 3, 4]), (9, vec![9, 8, 7, 6])].into_iter().collect();
+        assert_eq!(lint_shares(&parts, Some(1..=5)), vec![
+            LintWarning::SameAsShare { index: 3, other: 1 },
+            LintWarning::IndexOutOfRange { index: 9, expected: 1..=5 },
+        ]);
+        assert_eq!(lint_shares(&parts, None), vec![LintWarning::SameAsShare { index: 3, other: 1 }]);
+        assert_eq!(LintWarning::SameAsShare { index: 3, other: 1 }.to_string(), "share 3: payload equals share 1");
+    }
+}
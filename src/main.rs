@@ -1,21 +1,36 @@
-mod crypto;
+mod cli;
 
 use base64::{engine::general_purpose, Engine as _};
 use std::collections::BTreeMap;
-use crypto::ShamirSS;
+use std::process;
+use shamir_rust::crypto::ShamirSS;
 
 fn main() {
 
+    let raw:Vec<String>=std::env::args().skip(1).collect();
+    if raw.is_empty() {
+        demo();
+        return;
+    }
+
+    let mut args=cli::Args::parse(&raw[1..]);
+    if let Err(err)=cli::apply_profile(&raw[0], &mut args).and_then(|()| cli::run(&raw[0], &args)) {
+        cli::output::error(&args, &err);
+        process::exit(err.code);
+    }
+}
+
+fn demo() {
+
     let secret= b"Hello Shamir Shared Secret!!!!!";
     let numparts = 5;
     let miniumparts = 3;
-    
+
     println!("Origin Shared Secret: {} ", String::from_utf8_lossy(secret));
     println!("Origin Shared Secret Bytes: {:?} ", secret);
-    
-    let keys=ShamirSS::split(numparts, miniumparts, secret.to_vec());
-    if keys.is_ok(){
-        let keys = keys.unwrap();
+
+    match ShamirSS::split(numparts, miniumparts, secret.to_vec()) {
+        Ok(keys) => {
         let keysiter = keys.clone();
         for key in keysiter{
 
@@ -28,7 +43,7 @@ fn main() {
                 print!("{:02X} ", byte);
             }
             print!("]");
-            println!(); 
+            println!();
         }
         let mut parts:BTreeMap<i32,Vec<u8>>=BTreeMap::new();
         for (key, value) in &keys {
@@ -38,22 +53,17 @@ fn main() {
             }
         }
 
-      
-        let nshared=ShamirSS::join(parts);
-        if nshared.is_ok(){
-            
-            let shared = nshared.unwrap();
+
+        match ShamirSS::join(parts) {
+            Ok(shared) => {
             println!("Restaured Shared Secret Bytes: {:?} ", shared);
             let shared_string_value = String::from_utf8_lossy(secret);
             println!("Restaured Shared Secret: {}",shared_string_value);
+            }
+            Err(msg) => println!("{msg}"),
         }
-        else{
-            let msg=nshared.unwrap_err();
-            println!("{msg}");
         }
-    }else {
-        let msg=keys.unwrap_err();
-        println!("{msg}");
+        Err(msg) => println!("{msg}"),
     }
 
 }
@@ -0,0 +1,150 @@
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::stream::{StreamSplitter, StreamStats};
+
+// Splitting huge read-only files through a memory map (feature `mmap`, via
+// memmap2). The file is mapped read-only and fed to `StreamSplitter`, so a
+// multi-GB input is never copied into process memory: only the current
+// chunk and the share queues are, as with `split`.
+//
+// Where the input's bytes end up:
+//   - Mapped pages are the kernel's page cache, shared with every process
+//     reading the file and kept after this one exits until evicted. Reading
+//     the file with `read` puts it in the page cache too; mapping adds no
+//     exposure there, and unlike a heap copy the pages are clean, so they
+//     are dropped rather than written to swap under memory pressure.
+//   - Pages already split are released from this process's mapping as the
+//     split goes on (MADV_DONTNEED), so its resident size stays at a few
+//     chunks whatever the file size.
+//   - At the end the kernel is asked to drop the file's cached pages
+//     (POSIX_FADV_DONTNEED, Linux only). This is advice: pages another
+//     process is using, or that are dirty, stay.
+//   - File-backed mappings are left out of core dumps by the default
+//     coredump_filter; heap buffers are not.
+// The page advice is Unix only; elsewhere the map is plain. The file must not
+// be truncated while mapped: touching a page past the new end kills the
+// process with SIGBUS. Split a copy if the file may change.
+
+pub struct MappedFile{
+    map:Mmap,
+    file:File,
+}
+
+impl MappedFile{
+
+    pub fn open(path:&Path)->io::Result<MappedFile>{
+        let file=File::open(path)?;
+        // SAFETY: the map is read-only and private to this process; a file
+        // truncated underneath it is the SIGBUS case documented above.
+        let map=unsafe { Mmap::map(&file)? };
+        #[cfg(unix)]
+        let _=map.advise(memmap2::Advice::Sequential);
+        Ok(MappedFile { map, file })
+    }
+
+    pub fn len(&self)->usize{
+        self.map.len()
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.map.is_empty()
+    }
+
+    pub fn as_slice(&self)->&[u8]{
+        &self.map
+    }
+
+    // Reads the mapping front to back, releasing pages once read.
+    pub fn reader(&self)->MappedReader<'_>{
+        MappedReader { map: &self.map, position: 0, released: 0 }
+    }
+
+    // Asks the kernel to drop the file's pages from the page cache.
+    pub fn drop_cache(&self){
+        drop_cache(&self.file, self.map.len());
+    }
+}
+
+pub struct MappedReader<'a>{
+    map:&'a Mmap,
+    position:usize,
+    released:usize,
+}
+
+// Released in steps of 1 MiB, a multiple of every page size in use.
+const PAGE_RELEASE:usize=1<<20;
+
+impl Read for MappedReader<'_>{
+    fn read(&mut self,buffer:&mut [u8])->io::Result<usize>{
+        let data:&[u8]=self.map;
+        let len=buffer.len().min(data.len()-self.position);
+        buffer[..len].copy_from_slice(&data[self.position..self.position+len]);
+        self.position+=len;
+        // Whole pages behind the read position are done with.
+        let done=self.position/PAGE_RELEASE*PAGE_RELEASE;
+        if done>self.released {
+            release(self.map, self.released, done-self.released);
+            self.released=done;
+        }
+        Ok(len)
+    }
+}
+
+impl StreamSplitter{
+
+    // `split` over a memory-mapped file; see above for what stays in memory.
+    pub fn split_mmap<W:Write+Send>(&self,path:&Path,writers:Vec<W>)->Result<StreamStats,String>{
+        let mapped=MappedFile::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let result=self.split(mapped.reader(), writers);
+        mapped.drop_cache();
+        result
+    }
+}
+
+// Drops this process's pages for the range; reading it again faults them
+// back in from the file, so the slice stays valid.
+#[cfg(unix)]
+fn release(map:&Mmap,offset:usize,len:usize){
+    // SAFETY: the map is private and read-only, so MADV_DONTNEED loses no
+    // data; failure is harmless.
+    let _=unsafe { map.unchecked_advise_range(memmap2::UncheckedAdvice::DontNeed, offset, len) };
+}
+
+#[cfg(not(unix))]
+fn release(_map:&Mmap,_offset:usize,_len:usize){}
+
+#[cfg(target_os = "linux")]
+fn drop_cache(file:&File,len:usize){
+    use std::os::unix::io::AsRawFd;
+    // SAFETY: advice on an open descriptor; failure is harmless.
+    unsafe { libc::posix_fadvise(file.as_raw_fd(), 0, len as libc::off_t, libc::POSIX_FADV_DONTNEED) };
+}
+
+#[cfg(not(target_os = "linux"))]
+fn drop_cache(_file:&File,_len:usize){}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_returns_the_file_across_released_pages() {
+        let path=std::env::temp_dir().join(format!("shamir-mmap-{}", std::process::id()));
+        let data:Vec<u8>=(0..PAGE_RELEASE*2+12345).map(|i| (i*31%251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let mapped=MappedFile::open(&path).unwrap();
+        let mut read=Vec::new();
+        mapped.reader().read_to_end(&mut read).unwrap();
+        assert_eq!(read, data);
+        assert_eq!(mapped.as_slice(), &data[..]);
+        mapped.drop_cache();
+        std::fs::write(&path, b"").unwrap();
+        let empty=MappedFile::open(&path).unwrap();
+        assert!(empty.is_empty() && empty.as_slice().is_empty());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
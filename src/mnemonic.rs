@@ -0,0 +1,163 @@
+use bip39::{Language, Mnemonic};
+use unicode_normalization::UnicodeNormalization;
+
+use crate::sha256::Sha256;
+
+// Mnemonic form of a share: words from one of the BIP-39 wordlists, so a
+// share can be written down or read aloud in the custodian's language. The
+// language is picked when encoding and recognized from the words when
+// decoding.
+//
+// Payload: index byte | share length byte | share | first 4 bytes of SHA-256
+// over what precedes them, read as 11-bit numbers (big-endian, zero bits
+// appended to fill the last word), one word each. Shares are at most 255
+// bytes; a 32-byte share is 28 words.
+//
+// The wordlists are stored in Unicode NFKD, so input is normalized the same
+// way first: "ábaco" typed with a precomposed á, or pasted decomposed,
+// decodes alike. Japanese words are joined with the ideographic space, as
+// BIP-39 does; any whitespace separates words when decoding.
+
+pub const MAX_MNEMONIC_SHARE:usize=255;
+const CHECKSUM_LEN:usize=4;
+
+pub const LANGUAGES:&[(&str,Language)]=&[
+    ("en", Language::English),
+    ("es", Language::Spanish),
+    ("fr", Language::French),
+    ("it", Language::Italian),
+    ("pt", Language::Portuguese),
+    ("cs", Language::Czech),
+    ("ja", Language::Japanese),
+    ("ko", Language::Korean),
+    ("zh-hans", Language::SimplifiedChinese),
+    ("zh-hant", Language::TraditionalChinese),
+];
+
+// "es" -> Spanish, for command lines and configuration files.
+pub fn language(code:&str)->Option<Language>{
+    LANGUAGES.iter().find(|(name, _)| name.eq_ignore_ascii_case(code)).map(|(_, language)| *language)
+}
+
+pub fn encode_mnemonic(index:i32,data:&[u8],language:Language)->Result<String,String>{
+    if !(1..=255).contains(&index) {
+        return Err(format!("Share index {index} is out of range"));
+    }
+    if data.is_empty() || data.len()>MAX_MNEMONIC_SHARE {
+        return Err(format!("Mnemonic shares hold 1 to {MAX_MNEMONIC_SHARE} bytes, not {}", data.len()));
+    }
+    let mut payload=vec![index as u8, data.len() as u8];
+    payload.extend_from_slice(data);
+    let checksum=Sha256::digest(&payload);
+    payload.extend_from_slice(&checksum[..CHECKSUM_LEN]);
+
+    let words=language.word_list();
+    let separator=if language==Language::Japanese { "\u{3000}" } else { " " };
+    let mut out=Vec::new();
+    let (mut acc, mut bits)=(0u32, 0u32);
+    for byte in payload {
+        acc=(acc<<8)|byte as u32;
+        bits+=8;
+        while bits>=11 {
+            bits-=11;
+            out.push(words[((acc>>bits)&0x7ff) as usize]);
+        }
+    }
+    if bits>0 {
+        out.push(words[((acc<<(11-bits))&0x7ff) as usize]);
+    }
+    Ok(out.join(separator))
+}
+
+// Decodes a mnemonic share in whichever language its words are from.
+pub fn decode_mnemonic(text:&str)->Result<(i32,Vec<u8>,Language),String>{
+    let normalized:String=text.nfkd().collect();
+    let language=Mnemonic::language_of(&normalized).map_err(|e| format!("Not a mnemonic share: {e}"))?;
+    let mut bytes=Vec::new();
+    let (mut acc, mut bits)=(0u32, 0u32);
+    for (position, word) in normalized.split_whitespace().enumerate() {
+        let value=language.find_word(word).ok_or(format!("Word {} ({word}) is not in the {language} wordlist", position+1))?;
+        acc=(acc<<11)|value as u32;
+        bits+=11;
+        while bits>=8 {
+            bits-=8;
+            bytes.push((acc>>bits) as u8);
+        }
+        acc&=(1<<bits)-1;
+    }
+
+    let [index, len, rest @ ..]=bytes.as_slice() else { return Err("Mnemonic share is too short".to_string()) };
+    let len=*len as usize;
+    let body=2+len;
+    let (Some(checksum), Some(padding))=(rest.get(len..len+CHECKSUM_LEN), rest.get(len+CHECKSUM_LEN..)) else {
+        return Err("Mnemonic share is too short for its length byte".to_string());
+    };
+    // Fill bits only: fewer than 11, so at most one whole byte, all zero.
+    if padding.len()>1 || acc!=0 || padding.iter().any(|&b| b!=0) {
+        return Err("Mnemonic share has extra words".to_string());
+    }
+    if Sha256::digest(&bytes[..body])[..CHECKSUM_LEN]!=*checksum {
+        return Err("Mnemonic share checksum mismatch (a word is wrong or missing)".to_string());
+    }
+    if *index==0 || len==0 {
+        return Err("Malformed mnemonic share".to_string());
+    }
+    Ok((*index as i32, rest[..len].to_vec(), language))
+}
+
+// Whether the first word of `text` is in some wordlist, i.e. it is worth
+// trying `decode_mnemonic`.
+pub fn looks_like_mnemonic(text:&str)->bool{
+    let normalized:String=text.trim().nfkd().collect();
+    let mut words=normalized.split_whitespace();
+    match (words.next(), words.next()) {
+        (Some(first), Some(_)) => Language::ALL.iter().any(|language| language.find_word(first).is_some()),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_in_every_language() {
+        let data:Vec<u8>=(0..32).map(|i| i*7+3).collect();
+        for (code, language) in LANGUAGES {
+            let words=encode_mnemonic(200, &data, *language).unwrap();
+            assert_eq!(decode_mnemonic(&words).unwrap(), (200, data.clone(), *language), "{code}");
+        }
+    }
+
+    #[test]
+    fn every_length_round_trips() {
+        for len in 1..=MAX_MNEMONIC_SHARE {
+            let data=vec![0xffu8;len];
+            let words=encode_mnemonic(1, &data, Language::English).unwrap();
+            assert_eq!(words.split(' ').count(), ((len+6)*8).div_ceil(11));
+            assert_eq!(decode_mnemonic(&words).unwrap().1, data);
+        }
+        assert!(encode_mnemonic(1, &[0;256], Language::English).is_err());
+    }
+
+    #[test]
+    fn composed_and_decomposed_input_decode_alike() {
+        let words=encode_mnemonic(3, b"secret share", Language::Spanish).unwrap();
+        let composed:String=words.nfc().collect();
+        assert_ne!(composed, words);
+        assert_eq!(decode_mnemonic(&composed).unwrap(), decode_mnemonic(&words).unwrap());
+        assert_eq!(language("ES"), Some(Language::Spanish));
+    }
+
+    #[test]
+    fn wrong_or_missing_word_is_detected() {
+        let words=encode_mnemonic(3, b"secret share", Language::English).unwrap();
+        let mut list:Vec<&str>=words.split(' ').collect();
+        list[4]=if list[4]=="zoo" { "abandon" } else { "zoo" };
+        assert!(decode_mnemonic(&list.join(" ")).is_err());
+        list.remove(4);
+        assert!(decode_mnemonic(&list.join(" ")).is_err());
+        assert!(looks_like_mnemonic(&words));
+        assert!(!looks_like_mnemonic("1:c2VjcmV0"));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::encoding::{decode_bech32, encode_bech32};
+
+// Layout for stamping a share onto a steel backup plate. The share is its
+// bech32m string without the "shamir1" prefix, upper case: 32 characters
+// that exclude the easily confused 1, B, I and O. It is laid out in a grid
+// with lettered rows and numbered columns so a character can be named by
+// coordinate ("C5"), and every row ends in a check character (weighted sum
+// of the row's values mod 32) so a mis-stamped or misread character is
+// located to its row; the bech32 checksum still covers the whole share.
+//
+//   SHAMIR PLATE V1
+//      1 2 3 4 5 6 7 8  C
+//   A  Q Y P Z R 9 X 8  K
+//   B  G F 2 T V D W 0  M
+//   C  S 3 J N 5        4
+//
+// `decode_plate` reads the grid back: rows may come in any order, the
+// column header and blank lines are ignored, and lower case is accepted.
+
+pub const PLATE_HEADER:&str="SHAMIR PLATE V1";
+pub const DEFAULT_PLATE_WIDTH:usize=8;
+const PLATE_CHARSET:&[u8;32]=b"QPZRY9X8GF2TVDW0S3JN54KHCE6MUA7L";
+const BECH32_PREFIX:&str="shamir1";
+
+pub fn encode_plate(index:i32,data:&[u8],width:usize)->Result<String,String>{
+    if !(1..=26).contains(&width) {
+        return Err(format!("Plate width {width} is not in 1..=26"));
+    }
+    let encoded=encode_bech32(index, data).to_ascii_uppercase();
+    let values:Vec<u8>=encoded[BECH32_PREFIX.len()..].bytes().map(value).collect::<Option<_>>().ok_or("Share is not plate-encodable")?;
+    let label_width=row_label(values.len().div_ceil(width)-1).len();
+    let cell_width=width.to_string().len();
+    let mut out=format!("{PLATE_HEADER}\n{:label_width$} ", "");
+    for column in 1..=width {
+        out.push_str(&format!(" {column:>cell_width$}"));
+    }
+    out.push_str("  C\n");
+    for (row, chunk) in values.chunks(width).enumerate() {
+        out.push_str(&format!("{:<label_width$} ", row_label(row)));
+        for column in 0..width {
+            let c=chunk.get(column).map(|&v| PLATE_CHARSET[v as usize] as char).unwrap_or(' ');
+            out.push_str(&format!(" {c:>cell_width$}"));
+        }
+        out.push_str(&format!("  {}\n", PLATE_CHARSET[row_check(chunk) as usize] as char));
+    }
+    Ok(out)
+}
+
+pub fn decode_plate(text:&str)->Result<(i32,Vec<u8>),String>{
+    let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+    if !lines.next().is_some_and(|line| line.eq_ignore_ascii_case(PLATE_HEADER)) {
+        return Err("Not a plate layout".to_string());
+    }
+    let mut rows:Vec<(usize,Vec<u8>)>=Vec::new();
+    for line in lines {
+        let mut fields=line.split_whitespace();
+        let Some(label)=fields.next() else { continue };
+        let Some(row)=parse_row_label(label) else { continue };
+        let cells:Vec<&str>=fields.collect();
+        let (check, cells)=cells.split_last().ok_or(format!("Row {label} is empty"))?;
+        let mut values=Vec::new();
+        for (column, cell) in cells.iter().enumerate() {
+            let c=cell.to_ascii_uppercase();
+            let v=(c.len()==1).then(|| value(c.as_bytes()[0])).flatten()
+                .ok_or(format!("{}{}: '{cell}' is not a plate character", label.to_ascii_uppercase(), column+1))?;
+            values.push(v);
+        }
+        let check=check.to_ascii_uppercase();
+        if check.len()!=1 || value(check.as_bytes()[0])!=Some(row_check(&values)) {
+            return Err(format!("Row {} fails its check character", label.to_ascii_uppercase()));
+        }
+        if rows.iter().any(|(r, _)| *r==row) {
+            return Err(format!("Row {} appears twice", label.to_ascii_uppercase()));
+        }
+        rows.push((row, values));
+    }
+    rows.sort_by_key(|(row, _)| *row);
+    if rows.iter().enumerate().any(|(i, (row, _))| i!=*row) {
+        return Err("Plate is missing a row".to_string());
+    }
+    let encoded:String=rows.iter().flat_map(|(_, values)| values.iter().map(|&v| PLATE_CHARSET[v as usize] as char)).collect();
+    decode_bech32(&format!("{BECH32_PREFIX}{}", encoded.to_ascii_lowercase()))
+}
+
+fn value(c:u8)->Option<u8>{
+    PLATE_CHARSET.iter().position(|&x| x==c).map(|p| p as u8)
+}
+
+// Weights 1, 2, 3, ... so swapping two neighbours changes the check.
+fn row_check(values:&[u8])->u8{
+    (values.iter().enumerate().map(|(i, &v)| (i+1)*v as usize).sum::<usize>()%32) as u8
+}
+
+// A..Z, then AA, AB, ... as in spreadsheets.
+fn row_label(mut row:usize)->String{
+    let mut label=Vec::new();
+    loop {
+        label.push(b'A'+(row%26) as u8);
+        if row<26 {
+            break;
+        }
+        row=row/26-1;
+    }
+    label.reverse();
+    String::from_utf8(label).unwrap()
+}
+
+fn parse_row_label(label:&str)->Option<usize>{
+    if label.is_empty() || !label.bytes().all(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    label.bytes().try_fold(0usize, |acc, b| acc.checked_mul(26)?.checked_add((b.to_ascii_uppercase()-b'A') as usize+1)).map(|n| n-1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plate_round_trips_in_any_row_order() {
+        let data=b"plate share bytes".to_vec();
+        let plate=encode_plate(3, &data, DEFAULT_PLATE_WIDTH).unwrap();
+        assert!(plate.starts_with(PLATE_HEADER));
+        assert_eq!(decode_plate(&plate).unwrap(), (3, data.clone()));
+        assert_eq!(decode_plate(&plate.to_ascii_lowercase()).unwrap(), (3, data.clone()));
+
+        let mut lines:Vec<&str>=plate.lines().collect();
+        lines[2..].reverse();
+        assert_eq!(decode_plate(&lines.join("\n")).unwrap(), (3, data.clone()));
+        let narrow=encode_plate(3, &data, 1).unwrap();
+        assert!(narrow.lines().any(|line| line.starts_with("AA ")));
+        assert_eq!(decode_plate(&narrow).unwrap(), (3, data));
+        assert!(encode_plate(3, b"x", 27).is_err());
+    }
+
+    #[test]
+    fn a_misstamped_cell_names_its_row() {
+        let plate=encode_plate(3, b"plate share bytes", DEFAULT_PLATE_WIDTH).unwrap();
+        let mut lines:Vec<String>=plate.lines().map(str::to_string).collect();
+        let row=&mut lines[3];
+        let cell=row.find(|c:char| c!='B' && c!=' ').unwrap();
+        let stamped=if row.as_bytes()[cell]==b'Q' { "P" } else { "Q" };
+        row.replace_range(cell..cell+1, stamped);
+        assert_eq!(decode_plate(&lines.join("\n")).unwrap_err(), "Row B fails its check character");
+
+        let missing:Vec<&str>=plate.lines().filter(|line| !line.starts_with('B')).collect();
+        assert!(decode_plate(&missing.join("\n")).unwrap_err().contains("missing a row"));
+        assert_eq!(row_label(0), "A");
+        assert_eq!(row_label(27), "AB");
+        assert_eq!(parse_row_label("ab"), Some(27));
+    }
+}
@@ -0,0 +1,185 @@
+use ml_kem::kem::{Decapsulate, DecapsulationKey, Encapsulate, EncapsulationKey};
+use ml_kem::{Ciphertext, Encoded, EncodedSizeUser, KemCore, MlKem768, MlKem768Params};
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::hybrid::HybridSS;
+use crate::sha256::Sha256;
+
+// Hybrid mode with more than one way to the data key: the payload is sealed
+// as in `HybridSS`, its data key is split into Shamir shares as usual, and
+// optionally also wrapped with ML-KEM-768 (FIPS 203) to a recovery
+// authority, so either a quorum of shares or the authority's decapsulation
+// key opens it.
+//
+//   MAGIC | flags (1) | [ML-KEM ciphertext (1088) | wrapped data key] | sealed
+//
+// `flags` says which wrapping paths the file carries: FLAG_SHARES is always
+// set, FLAG_ML_KEM when the ciphertext and wrapped key follow; other bits
+// are refused, so a later path cannot be mistaken for this layout. The
+// wrapped data key is `HybridSS::encrypt` of the key under HKDF-SHA-256(salt
+// = ciphertext, ikm = ML-KEM shared secret, WRAP_INFO). `sealed` is the
+// SHX2 payload of `HybridSS` whose associated data is everything before it,
+// so the flags and the authority path cannot be stripped or swapped.
+
+const MAGIC:&[u8;4]=b"SHW1";
+pub const FLAG_SHARES:u8=0x01;
+pub const FLAG_ML_KEM:u8=0x02;
+const KNOWN_FLAGS:u8=FLAG_SHARES|FLAG_ML_KEM;
+const WRAP_INFO:&[u8]=b"shamir-rust ml-kem-768 data key";
+const KEY_LEN:usize=32;
+const CIPHERTEXT_LEN:usize=1088;
+// An SHX2 header (27 bytes), the key and its tag.
+const WRAPPED_KEY_LEN:usize=27+KEY_LEN+16;
+
+type Decapsulation=DecapsulationKey<MlKem768Params>;
+type Encapsulation=EncapsulationKey<MlKem768Params>;
+
+// The parts of a wrapped payload, borrowed from it.
+struct Layout<'a>{
+    header:&'a [u8],
+    kem:Option<(&'a [u8],&'a [u8])>,
+    sealed:&'a [u8],
+}
+
+// A fresh recovery authority key pair: (decapsulation key, encapsulation
+// key). The decapsulation key is secret; the encapsulation key goes to
+// whoever seals.
+pub fn generate_authority()->(Vec<u8>,Vec<u8>){
+    let (decapsulation, encapsulation)=MlKem768::generate(&mut OsRng);
+    (decapsulation.as_bytes().to_vec(), encapsulation.as_bytes().to_vec())
+}
+
+// Seals `payload` with a fresh data key split k of n and, when `authority`
+// is an ML-KEM-768 encapsulation key, also wrapped to it.
+pub fn seal(n:i32,k:i32,payload:&[u8],authority:Option<&[u8]>)->Result<(Vec<u8>,Parts),String>{
+    let mut key=[0u8;KEY_LEN];
+    OsRng.fill_bytes(&mut key);
+    let sealed=seal_with_key(&key, n, k, payload, authority);
+    zeroize(&mut key);
+    sealed
+}
+
+fn seal_with_key(key:&[u8;KEY_LEN],n:i32,k:i32,payload:&[u8],authority:Option<&[u8]>)->Result<(Vec<u8>,Parts),String>{
+    let parts=ShamirSS::split(n, k, key.to_vec())?;
+    let mut header=MAGIC.to_vec();
+    match authority {
+        Some(authority) => {
+            let encapsulation=Encapsulation::from_bytes(&Encoded::<Encapsulation>::try_from(authority)
+                .map_err(|_| "Recovery authority key is not an ML-KEM-768 encapsulation key".to_string())?);
+            let (ciphertext, mut shared)=encapsulation.encapsulate(&mut OsRng).map_err(|_| "ML-KEM encapsulation failed".to_string())?;
+            let mut wrap_key:[u8;KEY_LEN]=Sha256::hkdf_key(&ciphertext, &shared, WRAP_INFO);
+            let wrapped=HybridSS::encrypt(&wrap_key, 1, key);
+            zeroize(&mut shared);
+            zeroize(&mut wrap_key);
+            header.push(FLAG_SHARES|FLAG_ML_KEM);
+            header.extend_from_slice(&ciphertext);
+            header.extend_from_slice(&wrapped?);
+        }
+        None => header.push(FLAG_SHARES),
+    }
+    let sealed=HybridSS::encrypt_with_aad(key, 1, payload, &header)?;
+    header.extend_from_slice(&sealed);
+    Ok((header, parts))
+}
+
+// The wrapping paths a payload carries.
+pub fn flags(wrapped:&[u8])->Result<u8,String>{
+    let layout=parse(wrapped)?;
+    Ok(if layout.kem.is_some() { FLAG_SHARES|FLAG_ML_KEM } else { FLAG_SHARES })
+}
+
+pub fn open_with_shares(parts:Parts,wrapped:&[u8])->Result<Vec<u8>,String>{
+    let layout=parse(wrapped)?;
+    let mut key=ShamirSS::join(parts)?;
+    let payload=HybridSS::decrypt_with_aad(&key, layout.sealed, layout.header);
+    zeroize(&mut key);
+    payload
+}
+
+// Opens the payload with the recovery authority's decapsulation key, for
+// payloads sealed with FLAG_ML_KEM.
+pub fn open_with_authority(decapsulation:&[u8],wrapped:&[u8])->Result<Vec<u8>,String>{
+    let layout=parse(wrapped)?;
+    let (ciphertext, wrapped_key)=layout.kem.ok_or("Payload has no recovery authority path")?;
+    let decapsulation=Decapsulation::from_bytes(&Encoded::<Decapsulation>::try_from(decapsulation)
+        .map_err(|_| "Not an ML-KEM-768 decapsulation key".to_string())?);
+    let ciphertext=Ciphertext::<MlKem768>::try_from(ciphertext).map_err(|_| "Malformed ML-KEM ciphertext".to_string())?;
+    let mut shared=decapsulation.decapsulate(&ciphertext).map_err(|_| "ML-KEM decapsulation failed".to_string())?;
+    let mut wrap_key:[u8;KEY_LEN]=Sha256::hkdf_key(&ciphertext, &shared, WRAP_INFO);
+    zeroize(&mut shared);
+    let key=HybridSS::decrypt(&wrap_key, wrapped_key).map_err(|_| "Data key does not unwrap with this authority key".to_string());
+    zeroize(&mut wrap_key);
+    let mut key=key?;
+    let payload=HybridSS::decrypt_with_aad(&key, layout.sealed, layout.header);
+    zeroize(&mut key);
+    payload
+}
+
+fn parse(wrapped:&[u8])->Result<Layout<'_>,String>{
+    let Some((&flags, rest))=wrapped.strip_prefix(MAGIC.as_slice()).and_then(|rest| rest.split_first()) else {
+        return Err("Not a wrapped payload".to_string());
+    };
+    if flags & !KNOWN_FLAGS!=0 || flags & FLAG_SHARES==0 {
+        return Err(format!("Unsupported wrapping paths {flags:#04x}"));
+    }
+    if flags & FLAG_ML_KEM==0 {
+        return Ok(Layout { header: &wrapped[..MAGIC.len()+1], kem: None, sealed: rest });
+    }
+    if rest.len()<CIPHERTEXT_LEN+WRAPPED_KEY_LEN {
+        return Err("Truncated recovery authority path".to_string());
+    }
+    let (ciphertext, rest)=rest.split_at(CIPHERTEXT_LEN);
+    let (wrapped_key, sealed)=rest.split_at(WRAPPED_KEY_LEN);
+    let header=&wrapped[..wrapped.len()-sealed.len()];
+    Ok(Layout { header, kem: Some((ciphertext, wrapped_key)), sealed })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn either_path_opens_the_payload() {
+        let (decapsulation, encapsulation)=generate_authority();
+        let (wrapped, parts)=seal(3, 2, b"escrowed payload", Some(&encapsulation)).unwrap();
+        assert_eq!(flags(&wrapped).unwrap(), FLAG_SHARES|FLAG_ML_KEM);
+        let two:Parts=parts.into_iter().take(2).collect();
+        assert_eq!(open_with_shares(two, &wrapped).unwrap(), b"escrowed payload");
+        assert_eq!(open_with_authority(&decapsulation, &wrapped).unwrap(), b"escrowed payload");
+
+        let (other, _)=generate_authority();
+        assert!(open_with_authority(&other, &wrapped).is_err());
+    }
+
+    #[test]
+    fn shares_only_payload_has_no_authority_path() {
+        let (decapsulation, _)=generate_authority();
+        let (wrapped, parts)=seal(3, 2, b"payload", None).unwrap();
+        assert_eq!(flags(&wrapped).unwrap(), FLAG_SHARES);
+        assert!(open_with_authority(&decapsulation, &wrapped).is_err());
+        assert_eq!(open_with_shares(parts, &wrapped).unwrap(), b"payload");
+    }
+
+    #[test]
+    fn flags_are_authenticated_and_unknown_ones_refused() {
+        let (_, encapsulation)=generate_authority();
+        let (wrapped, parts)=seal(3, 2, b"payload", Some(&encapsulation)).unwrap();
+
+        let mut unknown=wrapped.clone();
+        unknown[4]|=0x80;
+        assert!(flags(&unknown).is_err());
+
+        // Dropping the authority path leaves a shares-only header that the
+        // sealed payload was not bound to.
+        let mut stripped=wrapped[..4].to_vec();
+        stripped.push(FLAG_SHARES);
+        stripped.extend_from_slice(&wrapped[5+CIPHERTEXT_LEN+WRAPPED_KEY_LEN..]);
+        assert_eq!(flags(&stripped).unwrap(), FLAG_SHARES);
+        assert!(open_with_shares(parts.clone(), &stripped).is_err());
+        assert!(open_with_shares(parts, &wrapped).is_ok());
+        assert!(flags(&wrapped[..100]).is_err());
+        assert!(seal(3, 2, b"payload", Some(&encapsulation[1..])).is_err());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::crypto::{zeroize, GFC256};
+use crate::error::ShamirError;
+use crate::pure::gf_mul;
+use crate::session::Progress;
+
+// Joins with the interpolation done ahead of time, for services with an
+// unseal-latency budget. When it is known which custodians will answer, the
+// Lagrange weights at x = 0 depend only on their x-coordinates, so
+// `PreparedJoin::new` computes them up front, together with a 256-entry
+// multiplication table per share (table[y] = weight * y). What remains is
+// one table lookup and one XOR per share byte.
+//
+// `PendingJoin` goes further and folds each share into the result as it
+// arrives, so the cost left when the last share comes in is a single pass
+// over that share. The running sum is not the secret until every share is
+// in, but it is wiped on drop like one.
+//
+// The result is the same as `ShamirSS::join` over the same shares. Shares
+// from x-coordinates the join was not prepared for are rejected with
+// `InvalidIndex`; a different set of responders needs a new `PreparedJoin`.
+
+#[derive(Debug,Clone)]
+pub struct PreparedJoin{
+    xs:Vec<u8>,
+    tables:Vec<[u8;256]>,
+}
+
+impl PreparedJoin{
+
+    pub fn new(xs:&[u8])->Result<PreparedJoin,ShamirError>{
+        if xs.is_empty() {
+            return Err(ShamirError::NoParts);
+        }
+        for (i, &x) in xs.iter().enumerate() {
+            if x==0 {
+                return Err(ShamirError::InvalidIndex(0));
+            }
+            if xs.iter().take(i).any(|&seen| seen==x) {
+                return Err(ShamirError::DuplicateIndex(x as i32));
+            }
+        }
+        let tables=GFC256::barycentric_weights(xs).into_iter().map(|w| {
+            let mut table=[0u8;256];
+            for (y, slot) in (0..=255u8).zip(table.iter_mut()) {
+                *slot=gf_mul(w, y);
+            }
+            table
+        }).collect();
+        Ok(PreparedJoin { xs: xs.to_vec(), tables })
+    }
+
+    pub fn xs(&self)->&[u8]{
+        &self.xs
+    }
+
+    pub fn start(&self)->PendingJoin<'_>{
+        PendingJoin { prepared: self, sum: Vec::new(), seen: vec![false;self.xs.len()] }
+    }
+
+    // All the prepared shares at once, in any order.
+    pub fn join(&self,pairs:&[(u8,&[u8])])->Result<Vec<u8>,ShamirError>{
+        let mut pending=self.start();
+        for &(x, y) in pairs {
+            pending.add(x, y)?;
+        }
+        pending.finish()
+    }
+
+    fn slot(&self,x:u8)->Result<usize,ShamirError>{
+        self.xs.iter().position(|&p| p==x).ok_or(ShamirError::InvalidIndex(x as i32))
+    }
+}
+
+#[derive(Debug)]
+pub struct PendingJoin<'a>{
+    prepared:&'a PreparedJoin,
+    sum:Vec<u8>,
+    seen:Vec<bool>,
+}
+
+impl PendingJoin<'_>{
+
+    // Folds one share into the running sum: one lookup and XOR per byte.
+    pub fn add(&mut self,x:u8,share:&[u8])->Result<Progress,ShamirError>{
+        let slot=self.prepared.slot(x)?;
+        if self.seen.get(slot)==Some(&true) {
+            return Err(ShamirError::DuplicateIndex(x as i32));
+        }
+        if share.is_empty() {
+            return Err(ShamirError::EmptySecret);
+        }
+        if self.sum.is_empty() {
+            self.sum=vec![0u8;share.len()];
+        } else if self.sum.len()!=share.len() {
+            return Err(ShamirError::InconsistentLengths);
+        }
+        let table=self.prepared.tables.get(slot).ok_or(ShamirError::InvalidIndex(x as i32))?;
+        for (acc, &y) in self.sum.iter_mut().zip(share) {
+            *acc^=table[y as usize];
+        }
+        if let Some(seen)=self.seen.get_mut(slot) {
+            *seen=true;
+        }
+        Ok(self.progress())
+    }
+
+    pub fn progress(&self)->Progress{
+        Progress { collected: self.seen.iter().filter(|&&s| s).count(), threshold: self.seen.len() }
+    }
+
+    // The secret, once every prepared share has been added.
+    pub fn finish(mut self)->Result<Vec<u8>,ShamirError>{
+        let progress=self.progress();
+        if !progress.is_complete() {
+            return Err(ShamirError::InsufficientParts { collected: progress.collected, threshold: progress.threshold });
+        }
+        Ok(std::mem::take(&mut self.sum))
+    }
+}
+
+impl Drop for PendingJoin<'_>{
+    fn drop(&mut self){
+        zeroize(&mut self.sum);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::ShamirSS;
+
+    #[test]
+    fn prepared_join_matches_the_plain_join() {
+        let parts=ShamirSS::split(5, 3, b"unseal key".to_vec()).unwrap();
+        let prepared=PreparedJoin::new(&[5, 2, 4]).unwrap();
+        let pairs:Vec<(u8,&[u8])>=[4, 2, 5].iter().map(|&x| (x as u8, parts[&x].as_slice())).collect();
+        assert_eq!(prepared.join(&pairs).unwrap(), b"unseal key");
+
+        let mut pending=prepared.start();
+        assert_eq!(pending.add(2, &parts[&2]).unwrap(), Progress { collected: 1, threshold: 3 });
+        assert_eq!(pending.add(2, &parts[&2]), Err(ShamirError::DuplicateIndex(2)));
+        assert_eq!(pending.add(1, &parts[&1]), Err(ShamirError::InvalidIndex(1)));
+        assert_eq!(pending.add(5, &parts[&5][..4]), Err(ShamirError::InconsistentLengths));
+        pending.add(5, &parts[&5]).unwrap();
+        assert_eq!(prepared.start().finish(), Err(ShamirError::InsufficientParts { collected: 0, threshold: 3 }));
+        pending.add(4, &parts[&4]).unwrap();
+        assert_eq!(pending.finish().unwrap(), b"unseal key");
+    }
+
+    #[test]
+    fn responders_must_be_distinct_nonzero_points() {
+        assert_eq!(PreparedJoin::new(&[]).unwrap_err(), ShamirError::NoParts);
+        assert_eq!(PreparedJoin::new(&[1, 0]).unwrap_err(), ShamirError::InvalidIndex(0));
+        assert_eq!(PreparedJoin::new(&[3, 1, 3]).unwrap_err(), ShamirError::DuplicateIndex(3));
+        assert_eq!(PreparedJoin::new(&[3, 1]).unwrap().xs(), [3, 1]);
+    }
+}
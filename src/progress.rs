@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::error::ShamirError;
+
+// Progress reporting for large operations. Work is processed in
+// PROGRESS_CHUNK-byte chunks and the observer is called after each one, so a
+// GUI can drive a progress bar without wrapping readers itself. Results are
+// identical to the non-reporting calls.
+
+pub const PROGRESS_CHUNK:usize=64*1024;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum Phase{
+    Splitting,
+    Joining,
+    Encrypting,
+    Decrypting,
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ProgressUpdate{
+    pub phase:Phase,
+    pub processed:u64,
+    pub total:u64,
+    // Extrapolated from the rate so far; None until something was processed.
+    pub eta:Option<Duration>,
+}
+
+pub trait ProgressObserver{
+    fn on_progress(&mut self,update:&ProgressUpdate);
+}
+
+impl<F:FnMut(&ProgressUpdate)> ProgressObserver for F{
+    fn on_progress(&mut self,update:&ProgressUpdate){
+        self(update)
+    }
+}
+
+pub(crate) struct Tracker<'a>{
+    observer:&'a mut dyn ProgressObserver,
+    phase:Phase,
+    processed:u64,
+    total:u64,
+    started:Instant,
+}
+
+impl<'a> Tracker<'a>{
+
+    pub(crate) fn new(observer:&'a mut dyn ProgressObserver,phase:Phase,total:usize)->Tracker<'a>{
+        Tracker { observer, phase, processed: 0, total: total as u64, started: Instant::now() }
+    }
+
+    pub(crate) fn advance(&mut self,bytes:usize){
+        self.processed+=bytes as u64;
+        let eta=match self.processed {
+            0 => None,
+            done => Some(self.started.elapsed().mul_f64((self.total-done) as f64/done as f64)),
+        };
+        self.observer.on_progress(&ProgressUpdate { phase: self.phase, processed: self.processed, total: self.total, eta });
+    }
+}
+
+impl ShamirSS{
+
+    pub fn split_with_progress(n:i32,k:i32,secret:&[u8],observer:&mut dyn ProgressObserver)->Result<Parts,ShamirError>{
+        if secret.is_empty() {
+            return Self::split(n, k, Vec::new());
+        }
+        let mut tracker=Tracker::new(observer, Phase::Splitting, secret.len());
+        let mut rng=rand::thread_rng();
+        let mut parts:Parts=BTreeMap::new();
+        for chunk in secret.chunks(PROGRESS_CHUNK) {
+            for (x, y) in Self::split_with_rng(n, k, chunk.to_vec(), &mut rng)? {
+                parts.entry(x).or_insert_with(|| Vec::with_capacity(secret.len())).extend_from_slice(&y);
+            }
+            tracker.advance(chunk.len());
+        }
+        Ok(parts)
+    }
+
+    pub fn join_with_progress(parts:&Parts,observer:&mut dyn ProgressObserver)->Result<Vec<u8>,ShamirError>{
+        let len=parts.values().next().map(|v| v.len()).ok_or(ShamirError::NoParts)?;
+        if len==0 {
+            return Self::join(parts.clone());
+        }
+        let mut tracker=Tracker::new(observer, Phase::Joining, len);
+        let mut secret=Vec::with_capacity(len);
+        for start in (0..len).step_by(PROGRESS_CHUNK) {
+            let end=(start+PROGRESS_CHUNK).min(len);
+            secret.extend_from_slice(&Self::join_range(parts, start..end)?);
+            tracker.advance(end-start);
+        }
+        Ok(secret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn observer_sees_every_chunk() {
+        let secret:Vec<u8>=(0..PROGRESS_CHUNK*2+100).map(|i| i as u8).collect();
+        let mut updates=Vec::new();
+        let parts=ShamirSS::split_with_progress(3, 2, &secret, &mut |update:&ProgressUpdate| updates.push(*update)).unwrap();
+        let processed:Vec<u64>=updates.iter().map(|u| u.processed).collect();
+        assert_eq!(processed, [PROGRESS_CHUNK as u64, 2*PROGRESS_CHUNK as u64, secret.len() as u64]);
+        assert!(updates.iter().all(|u| u.phase==Phase::Splitting && u.total==secret.len() as u64 && u.eta.is_some()));
+        assert_eq!(updates.last().unwrap().eta, Some(Duration::ZERO));
+
+        let mut joins=0;
+        let two:Parts=parts.into_iter().skip(1).collect();
+        assert_eq!(ShamirSS::join_with_progress(&two, &mut |update:&ProgressUpdate| {
+            assert_eq!(update.phase, Phase::Joining);
+            joins+=1;
+        }).unwrap(), secret);
+        assert_eq!(joins, 3);
+    }
+
+    #[test]
+    fn empty_inputs_report_nothing() {
+        let mut calls=0;
+        let parts=ShamirSS::split_with_progress(3, 2, &[], &mut |_:&ProgressUpdate| calls+=1);
+        assert_eq!(parts, ShamirSS::split(3, 2, Vec::new()));
+        assert_eq!(ShamirSS::join_with_progress(&Parts::new(), &mut |_:&ProgressUpdate| calls+=1), Err(ShamirError::NoParts));
+        assert_eq!(calls, 0);
+    }
+}
@@ -0,0 +1,397 @@
+use std::collections::BTreeSet;
+
+use crate::crypto::Parts;
+use crate::join::{self, Reconstruction};
+use crate::shareset;
+use crate::transcript::{self, CustodianRecord, EntropyRecord, HsmRecord, SpareRecord};
+
+// The messages of proto/shamir.proto as prost types, with converters to and
+// from the native types, so share metadata can travel through existing
+// protobuf tooling. The structs are written out with prost's derive rather
+// than generated by prost-build, so building needs no protoc; the tests
+// below pin their wire format. Decoding skips unknown fields, so messages
+// from a newer schema still parse.
+//
+// Native to protobuf never fails; protobuf to native checks what the native
+// type assumes (indices in 1..=255, no repeated index, values that fit).
+
+pub use prost::Message;
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct Share{
+    #[prost(uint32, tag = "1")]
+    pub index:u32,
+    #[prost(bytes = "vec", tag = "2")]
+    pub data:Vec<u8>,
+}
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct ShareSet{
+    #[prost(uint32, tag = "1")]
+    pub threshold:u32,
+    #[prost(message, repeated, tag = "2")]
+    pub shares:Vec<Share>,
+}
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct Custodian{
+    #[prost(uint32, tag = "1")]
+    pub index:u32,
+    #[prost(string, tag = "2")]
+    pub label:String,
+    #[prost(string, tag = "3")]
+    pub fingerprint:String,
+    #[prost(string, optional, tag = "4")]
+    pub contact:Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub next_drill_due:Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub watermark:Option<String>,
+    #[prost(string, optional, tag = "7")]
+    pub org:Option<String>,
+    #[prost(string, optional, tag = "8")]
+    pub ack_challenge:Option<String>,
+    #[prost(string, optional, tag = "9")]
+    pub ack_digest:Option<String>,
+    #[prost(string, optional, tag = "10")]
+    pub acknowledged:Option<String>,
+}
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct Spare{
+    #[prost(uint32, tag = "1")]
+    pub index:u32,
+    #[prost(string, tag = "2")]
+    pub fingerprint:String,
+    #[prost(string, optional, tag = "3")]
+    pub activated:Option<String>,
+}
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct EntropyReport{
+    #[prost(uint64, tag = "1")]
+    pub bytes:u64,
+    #[prost(string, tag = "2")]
+    pub chi_squared:String,
+    #[prost(string, tag = "3")]
+    pub serial_correlation:String,
+    #[prost(string, tag = "4")]
+    pub serial_limit:String,
+    #[prost(bool, tag = "5")]
+    pub os_random:bool,
+    #[prost(bool, tag = "6")]
+    pub passed:bool,
+    #[prost(string, tag = "7")]
+    pub source:String,
+}
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct HsmSource{
+    #[prost(string, tag = "1")]
+    pub token:String,
+    #[prost(string, tag = "2")]
+    pub manufacturer:String,
+    #[prost(string, tag = "3")]
+    pub model:String,
+    #[prost(string, tag = "4")]
+    pub serial:String,
+    #[prost(string, tag = "5")]
+    pub mechanism:String,
+    #[prost(string, tag = "6")]
+    pub key:String,
+    #[prost(string, optional, tag = "7")]
+    pub check_value:Option<String>,
+    #[prost(string, optional, tag = "8")]
+    pub attestation:Option<String>,
+}
+
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct Transcript{
+    #[prost(uint32, tag = "1")]
+    pub threshold:u32,
+    #[prost(uint32, tag = "2")]
+    pub shares:u32,
+    #[prost(string, tag = "3")]
+    pub created:String,
+    #[prost(message, repeated, tag = "4")]
+    pub custodians:Vec<Custodian>,
+    #[prost(message, repeated, tag = "5")]
+    pub spares:Vec<Spare>,
+    #[prost(message, optional, tag = "6")]
+    pub entropy:Option<EntropyReport>,
+    #[prost(string, tag = "7")]
+    pub ceremony:String,
+    #[prost(string, tag = "8")]
+    pub escrow:String,
+    #[prost(message, optional, tag = "9")]
+    pub hsm:Option<HsmSource>,
+}
+
+#[derive(Clone,Copy,Debug,PartialEq,Eq,Hash,PartialOrd,Ord,prost::Enumeration)]
+#[repr(i32)]
+pub enum IntegrityCheck{
+    Unspecified=0,
+    ExtraSharesConsistent=1,
+    MajorityAgreement=2,
+    CheckValue=3,
+}
+
+// `checks` holds `IntegrityCheck` values as i32, as prost does for enums, so
+// values added by a newer schema survive a round trip.
+#[derive(Clone,PartialEq,Eq,prost::Message)]
+pub struct AuditReport{
+    #[prost(uint32, repeated, tag = "1")]
+    pub used:Vec<u32>,
+    #[prost(uint32, repeated, tag = "2")]
+    pub ignored:Vec<u32>,
+    #[prost(uint32, repeated, tag = "3")]
+    pub rejected:Vec<u32>,
+    #[prost(bool, tag = "4")]
+    pub corrected:bool,
+    #[prost(enumeration = "IntegrityCheck", repeated, tag = "5")]
+    pub checks:Vec<i32>,
+    #[prost(bytes = "vec", tag = "6")]
+    pub secret_digest:Vec<u8>,
+}
+
+impl From<(i32,&[u8])> for Share{
+    fn from((index, data):(i32,&[u8]))->Share{
+        Share { index: index as u32, data: data.to_vec() }
+    }
+}
+
+impl TryFrom<Share> for (i32,Vec<u8>){
+    type Error=String;
+    fn try_from(share:Share)->Result<(i32,Vec<u8>),String>{
+        Ok((share_index(share.index)?, share.data))
+    }
+}
+
+impl From<&shareset::ShareSet> for ShareSet{
+    fn from(set:&shareset::ShareSet)->ShareSet{
+        ShareSet {
+            threshold: set.threshold as u32,
+            shares: set.parts.iter().map(|(index, data)| Share::from((*index, data.as_slice()))).collect(),
+        }
+    }
+}
+
+impl TryFrom<ShareSet> for shareset::ShareSet{
+    type Error=String;
+    fn try_from(set:ShareSet)->Result<shareset::ShareSet,String>{
+        let mut parts=Parts::new();
+        for share in set.shares {
+            let (index, data)=share.try_into()?;
+            if parts.insert(index, data).is_some() {
+                return Err(format!("Share index {index} appears twice"));
+            }
+        }
+        Ok(shareset::ShareSet { threshold: number(set.threshold, "threshold")?, parts })
+    }
+}
+
+impl From<&transcript::Transcript> for Transcript{
+    fn from(t:&transcript::Transcript)->Transcript{
+        Transcript {
+            threshold: t.threshold as u32,
+            shares: t.shares as u32,
+            created: t.created.clone(),
+            ceremony: t.ceremony.clone(),
+            escrow: t.escrow.clone(),
+            custodians: t.custodians.iter().map(|c| Custodian {
+                index: c.index as u32,
+                label: c.label.clone(),
+                fingerprint: c.fingerprint.clone(),
+                contact: c.contact.clone(),
+                next_drill_due: c.next_drill_due.clone(),
+                watermark: c.watermark.clone(),
+                org: c.org.clone(),
+                ack_challenge: c.ack_challenge.clone(),
+                ack_digest: c.ack_digest.clone(),
+                acknowledged: c.acknowledged.clone(),
+            }).collect(),
+            spares: t.spares.iter().map(|s| Spare {
+                index: s.index as u32,
+                fingerprint: s.fingerprint.clone(),
+                activated: s.activated.clone(),
+            }).collect(),
+            entropy: t.entropy.as_ref().map(|e| EntropyReport {
+                bytes: e.bytes as u64,
+                chi_squared: e.chi_squared.clone(),
+                serial_correlation: e.serial_correlation.clone(),
+                serial_limit: e.serial_limit.clone(),
+                os_random: e.os_random,
+                passed: e.passed,
+                source: e.source.clone(),
+            }),
+            hsm: t.hsm.as_ref().map(|h| HsmSource {
+                token: h.token.clone(),
+                manufacturer: h.manufacturer.clone(),
+                model: h.model.clone(),
+                serial: h.serial.clone(),
+                mechanism: h.mechanism.clone(),
+                key: h.key.clone(),
+                check_value: h.check_value.clone(),
+                attestation: h.attestation.clone(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Transcript> for transcript::Transcript{
+    type Error=String;
+    fn try_from(t:Transcript)->Result<transcript::Transcript,String>{
+        let mut seen=BTreeSet::new();
+        // Escrow indices are per organization.
+        let mut check=|index:u32,org:Option<&str>| {
+            let index=share_index(index)?;
+            if !seen.insert((org.map(str::to_string), index)) {
+                return Err(format!("Share index {index} appears twice in the transcript"));
+            }
+            Ok(index)
+        };
+        let mut custodians=Vec::with_capacity(t.custodians.len());
+        for c in t.custodians {
+            custodians.push(CustodianRecord {
+                index: check(c.index, c.org.as_deref())?,
+                label: c.label,
+                fingerprint: c.fingerprint,
+                contact: c.contact,
+                next_drill_due: c.next_drill_due,
+                watermark: c.watermark,
+                org: c.org,
+                ack_challenge: c.ack_challenge,
+                ack_digest: c.ack_digest,
+                acknowledged: c.acknowledged,
+            });
+        }
+        let mut spares=Vec::with_capacity(t.spares.len());
+        for s in t.spares {
+            spares.push(SpareRecord { index: check(s.index, None)?, fingerprint: s.fingerprint, activated: s.activated });
+        }
+        let entropy=match t.entropy {
+            Some(e) => Some(EntropyRecord {
+                bytes: usize::try_from(e.bytes).map_err(|_| format!("bytes {} is out of range", e.bytes))?,
+                chi_squared: e.chi_squared,
+                serial_correlation: e.serial_correlation,
+                serial_limit: e.serial_limit,
+                os_random: e.os_random,
+                passed: e.passed,
+                source: e.source,
+            }),
+            None => None,
+        };
+        Ok(transcript::Transcript {
+            threshold: number(t.threshold, "threshold")?,
+            shares: number(t.shares, "shares")?,
+            created: t.created,
+            ceremony: t.ceremony,
+            escrow: t.escrow,
+            custodians,
+            spares,
+            entropy,
+            hsm: t.hsm.map(|h| HsmRecord {
+                token: h.token,
+                manufacturer: h.manufacturer,
+                model: h.model,
+                serial: h.serial,
+                mechanism: h.mechanism,
+                key: h.key,
+                check_value: h.check_value,
+                attestation: h.attestation,
+            }),
+        })
+    }
+}
+
+// The report leaves the secret out; only its digest is kept.
+impl From<&Reconstruction> for AuditReport{
+    fn from(r:&Reconstruction)->AuditReport{
+        let indices=|list:&[i32]| list.iter().map(|&x| x as u32).collect();
+        AuditReport {
+            used: indices(&r.used),
+            ignored: indices(&r.ignored),
+            rejected: indices(&r.rejected),
+            corrected: r.corrected,
+            checks: r.checks.iter().map(|&check| check_value(check)).collect(),
+            secret_digest: r.secret_digest.to_vec(),
+        }
+    }
+}
+
+impl AuditReport{
+
+    // The checks this version knows; newer values are skipped.
+    pub fn integrity_checks(&self)->Vec<join::IntegrityCheck>{
+        self.checks.iter().filter_map(|&value| match IntegrityCheck::try_from(value) {
+            Ok(IntegrityCheck::ExtraSharesConsistent) => Some(join::IntegrityCheck::ExtraSharesConsistent),
+            Ok(IntegrityCheck::MajorityAgreement) => Some(join::IntegrityCheck::MajorityAgreement),
+            Ok(IntegrityCheck::CheckValue) => Some(join::IntegrityCheck::CheckValue),
+            Ok(IntegrityCheck::Unspecified) | Err(_) => None,
+        }).collect()
+    }
+}
+
+fn check_value(check:join::IntegrityCheck)->i32{
+    let value=match check {
+        join::IntegrityCheck::ExtraSharesConsistent => IntegrityCheck::ExtraSharesConsistent,
+        join::IntegrityCheck::MajorityAgreement => IntegrityCheck::MajorityAgreement,
+        join::IntegrityCheck::CheckValue => IntegrityCheck::CheckValue,
+    };
+    value as i32
+}
+
+fn share_index(index:u32)->Result<i32,String>{
+    if !(1..=255).contains(&index) {
+        return Err(format!("Invalid share index {index} (must be 1..=255)"));
+    }
+    Ok(index as i32)
+}
+
+fn number(value:u32,name:&str)->Result<i32,String>{
+    i32::try_from(value).map_err(|_| format!("{name} {value} is out of range"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bytes as protoc writes them for the schema, so the derived structs
+    // cannot drift from proto/shamir.proto unnoticed.
+    #[test]
+    fn wire_format_matches_the_schema() {
+        let share=Share { index: 3, data: vec![0xaa, 0xbb] };
+        assert_eq!(share.encode_to_vec(), [0x08, 0x03, 0x12, 0x02, 0xaa, 0xbb]);
+        let set=ShareSet { threshold: 2, shares: vec![share.clone()] };
+        assert_eq!(set.encode_to_vec(), [0x08, 0x02, 0x12, 0x06, 0x08, 0x03, 0x12, 0x02, 0xaa, 0xbb]);
+        let report=AuditReport { used: vec![1, 2], checks: vec![IntegrityCheck::CheckValue as i32], ..Default::default() };
+        assert_eq!(report.encode_to_vec(), [0x0a, 0x02, 0x01, 0x02, 0x2a, 0x01, 0x03]);
+
+        // Unknown fields (here 15, a varint) are skipped.
+        let newer=[0x08, 0x03, 0x78, 0x01, 0x12, 0x02, 0xaa, 0xbb];
+        assert_eq!(Share::decode(&newer[..]).unwrap(), share);
+        assert!(Share::decode(&[0x12, 0x05, 0xaa][..]).is_err());
+    }
+
+    #[test]
+    fn converts_share_sets_and_rejects_bad_indices() {
+        let native=shareset::ShareSet::split(3, 2, b"proto".to_vec()).unwrap();
+        let bytes=ShareSet::from(&native).encode_to_vec();
+        let back:shareset::ShareSet=ShareSet::decode(bytes.as_slice()).unwrap().try_into().unwrap();
+        assert_eq!(back.parts, native.parts);
+        assert_eq!(back.join().unwrap(), b"proto");
+
+        let twice=ShareSet { threshold: 2, shares: vec![Share { index: 1, data: vec![1] }, Share { index: 1, data: vec![2] }] };
+        assert!(shareset::ShareSet::try_from(twice).is_err());
+        let zero=ShareSet { threshold: 2, shares: vec![Share { index: 0, data: vec![1] }] };
+        assert!(shareset::ShareSet::try_from(zero).is_err());
+    }
+
+    #[test]
+    fn audit_reports_keep_unknown_checks() {
+        let report=AuditReport { checks: vec![2, 9], ..Default::default() };
+        let decoded=AuditReport::decode(report.encode_to_vec().as_slice()).unwrap();
+        assert_eq!(decoded.checks, [2, 9]);
+        assert_eq!(decoded.integrity_checks(), [join::IntegrityCheck::MajorityAgreement]);
+    }
+}
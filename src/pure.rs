@@ -0,0 +1,214 @@
+#![deny(clippy::indexing_slicing, clippy::unwrap_used, clippy::expect_used, clippy::panic, clippy::unreachable, clippy::todo, clippy::unimplemented)]
+
+use crate::error::ShamirError;
+use crate::tables::{EXP, LOG};
+
+// Side-effect-free core: field arithmetic, split and join over caller-owned
+// buffers. No RNG, clock, I/O or allocation; the random polynomial
+// coefficients come in as an argument. This is the only code built with
+// `--no-default-features --features core-pure`, so verification tools (Kani,
+// Creusot) see exactly the math `ShamirSS` runs on.
+//
+// Buffers are row-major: `coefficients` holds k-1 bytes per secret byte
+// (degree 1 first), `shares` holds one row of secret.len() bytes per x.
+//
+// Panic-free by construction: no indexing, slicing, unwrap or panic! (the
+// lints at the top make any of them a clippy error), so split and join
+// return an error or a value for every input. The Kani harness
+// `never_panics` checks the same for arbitrary buffers.
+
+pub fn gf_add(a:u8,b:u8)->u8{
+    a^b
+}
+
+pub fn gf_mul(a:u8,b:u8)->u8{
+    if a==0 || b==0 {
+        return 0;
+    }
+    // LOG values are at most 254 and EXP holds 510 entries, so the lookups
+    // always succeed.
+    EXP.get(log(a) as usize+log(b) as usize).copied().unwrap_or(0)
+}
+
+// Callers reject duplicate x-coordinates first, so b is never 0 there; b = 0
+// gives 0 rather than a panic.
+pub fn gf_div(a:u8,b:u8)->u8{
+    if b==0 {
+        return 0;
+    }
+    gf_mul(a, EXP.get(255-log(b) as usize).copied().unwrap_or(0))
+}
+
+fn log(a:u8)->u8{
+    LOG.get(a as usize).copied().unwrap_or(0)
+}
+
+pub(crate) fn check_xs(xs:&[u8])->Result<(),ShamirError>{
+    for (i, &x) in xs.iter().enumerate() {
+        if x==0 {
+            return Err(ShamirError::InvalidIndex(0));
+        }
+        if xs.iter().take(i).any(|&seen| seen==x) {
+            return Err(ShamirError::DuplicateIndex(x as i32));
+        }
+    }
+    Ok(())
+}
+
+pub fn split_with_coefficients(secret:&[u8],k:usize,coefficients:&[u8],xs:&[u8],shares:&mut [u8])->Result<(),ShamirError>{
+    if k<=1 {
+        return Err(ShamirError::ThresholdTooSmall);
+    }
+    if xs.len()<k {
+        return Err(ShamirError::ThresholdAboveShares);
+    }
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    check_xs(xs)?;
+    let degree=k-1;
+    let expected=secret.len().checked_mul(degree).ok_or(ShamirError::BufferSize { expected: usize::MAX, actual: coefficients.len() })?;
+    if coefficients.len()!=expected {
+        return Err(ShamirError::BufferSize { expected, actual: coefficients.len() });
+    }
+    let expected=secret.len().checked_mul(xs.len()).ok_or(ShamirError::BufferSize { expected: usize::MAX, actual: shares.len() })?;
+    if shares.len()!=expected {
+        return Err(ShamirError::BufferSize { expected, actual: shares.len() });
+    }
+    for (row, &x) in shares.chunks_exact_mut(secret.len()).zip(xs) {
+        for ((out, &byte), p) in row.iter_mut().zip(secret).zip(coefficients.chunks_exact(degree)) {
+            // Horner's method over secret + p[0]*x + ... + p[k-2]*x^(k-1).
+            let high=p.iter().rev().fold(0u8, |acc, &c| gf_add(gf_mul(acc, x), c));
+            *out=gf_add(gf_mul(high, x), byte);
+        }
+    }
+    Ok(())
+}
+
+pub fn join_into(xs:&[u8],shares:&[u8],secret:&mut [u8])->Result<(),ShamirError>{
+    if xs.is_empty() {
+        return Err(ShamirError::NoParts);
+    }
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    check_xs(xs)?;
+    let expected=secret.len().checked_mul(xs.len()).ok_or(ShamirError::BufferSize { expected: usize::MAX, actual: shares.len() })?;
+    if shares.len()!=expected {
+        return Err(ShamirError::BufferSize { expected, actual: shares.len() });
+    }
+    let len=secret.len();
+    for (pos, byte) in secret.iter_mut().enumerate() {
+        *byte=0;
+        for (i, (&xi, row)) in xs.iter().zip(shares.chunks_exact(len)).enumerate() {
+            // l_i(0) = prod_{j != i} x_j / (x_i - x_j)
+            let mut li=1u8;
+            for (j, &xj) in xs.iter().enumerate() {
+                if i!=j {
+                    li=gf_mul(li, gf_div(xj, gf_add(xi, xj)));
+                }
+            }
+            *byte=gf_add(*byte, gf_mul(li, row.get(pos).copied().unwrap_or(0)));
+        }
+    }
+    Ok(())
+}
+
+// Kani proof harness: `cargo kani --no-default-features --features core-pure`.
+#[cfg(kani)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod verification {
+    use super::*;
+
+    // Any buffers, any lengths up to the bound: Kani fails the proof on any
+    // reachable panic, overflow or out-of-bounds access.
+    #[kani::proof]
+    #[kani::unwind(5)]
+    fn never_panics(){
+        let secret:[u8;2]=kani::any();
+        let coefficients:[u8;3]=kani::any();
+        let xs:[u8;3]=kani::any();
+        let shares:[u8;4]=kani::any();
+        let (secret_len, coefficients_len, xs_len, shares_len, k):(usize,usize,usize,usize,usize)=kani::any();
+        kani::assume(secret_len<=2 && coefficients_len<=3 && xs_len<=3 && shares_len<=4);
+        let mut out=shares;
+        let _=split_with_coefficients(&secret[..secret_len], k, &coefficients[..coefficients_len], &xs[..xs_len], &mut out[..shares_len]);
+        let mut joined=secret;
+        let _=join_into(&xs[..xs_len], &shares[..shares_len], &mut joined[..secret_len]);
+        let _=gf_div(secret[0], coefficients[0]);
+    }
+
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn split_join_round_trip(){
+        let secret:[u8;2]=kani::any();
+        let coefficients:[u8;2]=kani::any();
+        let xs:[u8;2]=kani::any();
+        kani::assume(xs[0]!=0 && xs[1]!=0 && xs[0]!=xs[1]);
+
+        let mut shares=[0u8;4];
+        split_with_coefficients(&secret, 2, &coefficients, &xs, &mut shares).unwrap();
+        let mut joined=[0u8;2];
+        join_into(&xs, &shares, &mut joined).unwrap();
+        assert_eq!(joined, secret);
+    }
+
+    #[kani::proof]
+    fn mul_div_inverse(){
+        let a:u8=kani::any();
+        let b:u8=kani::any();
+        kani::assume(b!=0);
+        assert_eq!(gf_div(gf_mul(a, b), b), a);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::indexing_slicing, clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_operations() {
+        assert_eq!(gf_mul(0x57, 0x83), 0xc1);
+        assert_eq!(gf_mul(0x57, 0x13), 0xfe);
+        for a in 0..=255u8 {
+            assert_eq!(gf_mul(a, 1), a);
+            assert_eq!(gf_mul(a, 0), 0);
+            for b in 1..=255u8 {
+                assert_eq!(gf_div(gf_mul(a, b), b), a);
+            }
+        }
+        assert_eq!(gf_div(7, 0), 0);
+    }
+
+    #[test]
+    fn split_evaluates_the_given_polynomial() {
+        // k = 2: share(x) = secret + c * x.
+        let secret=[0x10, 0x20];
+        let coefficients=[0x57, 0x01];
+        let xs=[1, 0x83, 3];
+        let mut shares=[0u8;6];
+        split_with_coefficients(&secret, 2, &coefficients, &xs, &mut shares).unwrap();
+        assert_eq!(shares[..2], [0x10^0x57, 0x20^0x01]);
+        assert_eq!(shares[2], 0x10^0xc1);
+
+        let mut joined=[0u8;2];
+        join_into(&xs[1..], &shares[2..], &mut joined).unwrap();
+        assert_eq!(joined, secret);
+    }
+
+    #[test]
+    fn bad_buffers_are_errors() {
+        let mut shares=[0u8;4];
+        assert_eq!(split_with_coefficients(&[1, 2], 2, &[3], &[1, 2], &mut shares), Err(ShamirError::BufferSize { expected: 2, actual: 1 }));
+        assert_eq!(split_with_coefficients(&[1, 2], 2, &[3, 4], &[1, 2], &mut shares[..3]), Err(ShamirError::BufferSize { expected: 4, actual: 3 }));
+        assert_eq!(split_with_coefficients(&[1, 2], 3, &[3, 4, 5, 6], &[1, 2], &mut shares), Err(ShamirError::ThresholdAboveShares));
+        assert_eq!(split_with_coefficients(&[], 2, &[], &[1, 2], &mut []), Err(ShamirError::EmptySecret));
+        assert_eq!(split_with_coefficients(&[1, 2], 2, &[3, 4], &[2, 2], &mut shares), Err(ShamirError::DuplicateIndex(2)));
+
+        let mut secret=[0u8;2];
+        assert_eq!(join_into(&[1, 0], &shares, &mut secret), Err(ShamirError::InvalidIndex(0)));
+        assert_eq!(join_into(&[1, 2], &shares[..3], &mut secret), Err(ShamirError::BufferSize { expected: 4, actual: 3 }));
+        assert_eq!(join_into(&[], &[], &mut secret), Err(ShamirError::NoParts));
+    }
+}
@@ -0,0 +1,68 @@
+// QR code symbols for the air-gap transfer formats (see `fountain.rs`), so
+// frames can be shown on one machine and scanned on another. Encoding is the
+// `qrcode` crate's: the smallest version that fits the text at the chosen
+// error correction level, with the segment modes and mask it picks.
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum EccLevel{
+    // Recovers about 7% of the symbol.
+    Low,
+    // About 15%; the default for frames shown on a screen.
+    Medium,
+    // About 25%.
+    Quartile,
+    // About 30%.
+    High,
+}
+
+impl From<EccLevel> for qrcode::EcLevel{
+    fn from(level:EccLevel)->qrcode::EcLevel{
+        match level {
+            EccLevel::Low => qrcode::EcLevel::L,
+            EccLevel::Medium => qrcode::EcLevel::M,
+            EccLevel::Quartile => qrcode::EcLevel::Q,
+            EccLevel::High => qrcode::EcLevel::H,
+        }
+    }
+}
+
+pub struct QrCode{
+    inner:qrcode::QrCode,
+}
+
+impl QrCode{
+
+    pub fn encode_text(text:&str,ecc:EccLevel)->Result<QrCode,String>{
+        qrcode::QrCode::with_error_correction_level(text, ecc.into())
+            .map(|inner| QrCode { inner })
+            .map_err(|e| format!("{} characters do not fit in a QR code: {e}", text.len()))
+    }
+
+    // Modules per side, without the quiet zone.
+    pub fn size(&self)->usize{
+        self.inner.width()
+    }
+
+    // Whether the module at column x, row y is dark.
+    pub fn module(&self,x:usize,y:usize)->bool{
+        self.inner[(x, y)]==qrcode::Color::Dark
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_grow_with_the_text_and_the_level() {
+        let small=QrCode::encode_text("UR:SHAMIR/1-2/ABC", EccLevel::Medium).unwrap();
+        assert_eq!(small.size(), 21);
+        // Finder pattern corners are dark, the separator next to them light.
+        assert!(small.module(0, 0) && small.module(small.size()-1, 0) && small.module(0, small.size()-1));
+        assert!(!small.module(7, 0));
+        let long="x".repeat(500);
+        let low=QrCode::encode_text(&long, EccLevel::Low).unwrap();
+        assert!(QrCode::encode_text(&long, EccLevel::High).unwrap().size()>low.size());
+        assert!(QrCode::encode_text(&"x".repeat(3000), EccLevel::High).is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use rand::RngCore;
+
+use crate::encoding::encode_share;
+use crate::sha256::{to_hex, Sha256};
+use crate::transcript::{CustodianRecord, Transcript};
+
+// Split receipts: what a custodian keeps apart from the share to show, years
+// later, which share they were given and by whom. A receipt holds no share
+// material, only its SHA-256, so it can be stored anywhere (email, a desk
+// drawer, a ticket). The dealer signs it (last line, detached OpenPGP
+// signature as for revocation lists), so a receipt cannot be forged to fit
+// a substituted share:
+//
+//   shamir-split-receipt v1
+//   ceremony 4be1a0c29d7730f5
+//   created 2026-10-16
+//   threshold 3
+//   shares 5
+//   share 2 1a2b3c4d
+//   custodian bob
+//   digest <SHA-256 of the share's text form, hex>
+//   note <verification instructions, one line each>
+//   signature <base64>
+//
+// `created` and `custodian` are left out when unknown. Note lines are signed
+// with the rest but carry no data.
+//
+// The digest is a guessing oracle, like a published check value (see
+// check.rs): k-1 custodians plus a guess of the secret fix the polynomial,
+// so with anyone else's receipt they can recompute that share and compare
+// digests, offline. A receipt is only harmless to leave lying around when
+// the secret cannot be guessed; the CLI warns below GUESSABLE_SECRET_LEN
+// bytes.
+
+const HEADER:&str="shamir-split-receipt v1";
+const SIGNATURE_PREFIX:&str="signature ";
+const CEREMONY_ID_BYTES:usize=8;
+const NOTES:&[&str]=&[
+    "This receipt records which share you were given; it does not contain the share and need not be kept secret.",
+    "To check a share against it: shamir receipt verify SHARE-FILE --receipt RECEIPT-FILE --dealer-key DEALER-KEYRING",
+    "The dealer keyring is the public key the dealer exported (shamir dealer export).",
+];
+
+// A random id for a new split, written to the transcript and every receipt.
+pub fn new_ceremony_id()->String{
+    let mut id=[0u8;CEREMONY_ID_BYTES];
+    rand::thread_rng().fill_bytes(&mut id);
+    to_hex(&id)
+}
+
+fn share_digest(index:i32,data:&[u8])->String{
+    to_hex(&Sha256::digest(encode_share(index, data).as_bytes()))
+}
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct Receipt{
+    pub ceremony:String,
+    pub created:String,
+    pub threshold:i32,
+    pub shares:i32,
+    pub index:i32,
+    pub fingerprint:String,
+    pub custodian:String,
+    pub digest:String,
+}
+
+impl Receipt{
+
+    // The receipt for `custodian`'s share `data` in `transcript`'s split.
+    pub fn new(transcript:&Transcript,custodian:&CustodianRecord,data:&[u8])->Receipt{
+        Receipt {
+            ceremony: transcript.ceremony.clone(),
+            created: transcript.created.clone(),
+            threshold: transcript.threshold,
+            shares: transcript.shares,
+            index: custodian.index,
+            fingerprint: custodian.fingerprint.clone(),
+            custodian: custodian.label.clone(),
+            digest: share_digest(custodian.index, data),
+        }
+    }
+
+    // Whether the share (index, data) is the one this receipt was issued for.
+    pub fn check(&self,index:i32,data:&[u8])->Result<(),String>{
+        if index!=self.index {
+            return Err(format!("Share has index {index}, the receipt is for share {}", self.index));
+        }
+        if share_digest(index, data)!=self.digest {
+            return Err(format!("Share {index} is not the share the receipt was issued for"));
+        }
+        Ok(())
+    }
+
+    // The text that is signed.
+    pub fn to_text(&self)->String{
+        let clean=|v:&str| v.replace(['\n', '\r'], " ").trim().to_string();
+        let mut out=format!("{HEADER}\nceremony {}\n", clean(&self.ceremony));
+        if !self.created.is_empty() {
+            out.push_str(&format!("created {}\n", clean(&self.created)));
+        }
+        out.push_str(&format!("threshold {}\nshares {}\nshare {} {}\n", self.threshold, self.shares, self.index, clean(&self.fingerprint)));
+        if !self.custodian.is_empty() {
+            out.push_str(&format!("custodian {}\n", clean(&self.custodian)));
+        }
+        out.push_str(&format!("digest {}\n", self.digest));
+        for note in NOTES {
+            out.push_str(&format!("note {note}\n"));
+        }
+        out
+    }
+
+    pub fn parse(text:&str)->Result<Receipt,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(HEADER) {
+            return Err("Not a split receipt".to_string());
+        }
+        let mut receipt=Receipt::default();
+        for line in lines {
+            if line.starts_with(SIGNATURE_PREFIX) {
+                break;
+            }
+            let (key, value)=line.split_once(' ').unwrap_or((line, ""));
+            let number=|value:&str| value.parse::<i32>().map_err(|_| format!("Malformed receipt line {line}"));
+            match key {
+                "ceremony" => receipt.ceremony=value.to_string(),
+                "created" => receipt.created=value.to_string(),
+                "threshold" => receipt.threshold=number(value)?,
+                "shares" => receipt.shares=number(value)?,
+                "share" => {
+                    let (index, fingerprint)=value.split_once(' ').ok_or(format!("Malformed receipt line {line}"))?;
+                    receipt.index=number(index)?;
+                    if !(1..=255).contains(&receipt.index) {
+                        return Err(format!("Receipt share index {} is outside 1..=255", receipt.index));
+                    }
+                    receipt.fingerprint=fingerprint.trim().to_string();
+                }
+                "custodian" => receipt.custodian=value.to_string(),
+                "digest" => receipt.digest=value.to_ascii_lowercase(),
+                // Notes, and fields from newer versions.
+                _ => {}
+            }
+        }
+        if receipt.ceremony.is_empty() || receipt.index==0 || receipt.fingerprint.is_empty() || receipt.digest.len()!=64 {
+            return Err("Receipt has no ceremony, share or digest".to_string());
+        }
+        if !receipt.digest.starts_with(&receipt.fingerprint) {
+            return Err("Receipt fingerprint does not match its digest".to_string());
+        }
+        Ok(receipt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt(index:i32,data:&[u8])->Receipt{
+        let digest=share_digest(index, data);
+        Receipt {
+            ceremony: new_ceremony_id(),
+            created: "2026-10-16".to_string(),
+            threshold: 2,
+            shares: 3,
+            index,
+            fingerprint: digest[..8].to_string(),
+            custodian: "bob".to_string(),
+            digest,
+        }
+    }
+
+    #[test]
+    fn receipt_round_trips_and_checks_its_share() {
+        let issued=receipt(2, b"share bytes");
+        let signed=format!("{}signature c2lnbmF0dXJl\n", issued.to_text());
+        assert_eq!(Receipt::parse(&signed).unwrap(), issued);
+        let upper=issued.to_text().replace(&issued.digest, &issued.digest.to_ascii_uppercase());
+        assert_eq!(Receipt::parse(&upper).unwrap(), issued);
+
+        assert!(issued.check(2, b"share bytes").is_ok());
+        assert!(issued.check(3, b"share bytes").unwrap_err().contains("receipt is for share 2"));
+        assert!(issued.check(2, b"share bytez").is_err());
+    }
+
+    #[test]
+    fn share_index_must_be_in_range() {
+        let issued=receipt(2, b"share bytes");
+        for index in ["0", "-1", "256", "x"] {
+            let text=issued.to_text().replace("share 2 ", &format!("share {index} "));
+            assert!(Receipt::parse(&text).is_err(), "index {index}");
+        }
+        let text=issued.to_text().replace("share 2 ", "share 255 ");
+        assert_eq!(Receipt::parse(&text).unwrap().index, 255);
+
+        let other=receipt(2, b"other share");
+        let swapped=issued.to_text().replace(&issued.digest, &other.digest);
+        assert!(Receipt::parse(&swapped).unwrap_err().contains("does not match"));
+        assert!(Receipt::parse(&issued.to_text().replace("digest ", "note ")).is_err());
+    }
+}
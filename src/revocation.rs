@@ -0,0 +1,213 @@
+use base64::{engine::general_purpose, Engine as _};
+
+use crate::crypto::{Parts, Share};
+use crate::encoding::share_fingerprint;
+use crate::guard::{JoinContext, JoinGuard};
+
+// Administrative revocation, the analogue of an X.509 CRL. Interpolation
+// accepts any share that is on the polynomial, so a share reported lost or
+// stolen stays mathematically valid until the sharing is refreshed; a
+// revocation list lets the tools refuse it in the meantime.
+//
+// The list only grows: every change appends entries and increments the
+// serial. A reader that kept an earlier list checks `extends` before
+// trusting a newer one, so dropping an entry is detected. The dealer signs
+// the text (detached OpenPGP signature) and the signature travels as the
+// last line, base64 encoded:
+//
+//   shamir-revocation-list v1
+//   serial 2
+//   revoke fingerprint 0ba20012 2026-10-16 custodian reported the card stolen
+//   revoke generation 3 2026-10-16 superseded by rotation
+//   signature <base64>
+//
+// A `generation` entry revokes every share of a payload generation (see
+// `HybridSS::generation`).
+
+const HEADER:&str="shamir-revocation-list v1";
+const SIGNATURE_PREFIX:&str="signature ";
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum Revoked{
+    Fingerprint(String),
+    Generation(u32),
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Revocation{
+    pub target:Revoked,
+    pub date:String,
+    pub reason:String,
+}
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct RevocationList{
+    pub serial:u64,
+    pub entries:Vec<Revocation>,
+}
+
+impl RevocationList{
+
+    // Appends an entry and bumps the serial. Revoking something already
+    // revoked is a no-op. The date must be YYYY-MM-DD: it is one
+    // space-separated field of the line.
+    pub fn revoke(&mut self,target:Revoked,date:&str,reason:&str)->Result<(),String>{
+        check_date(date)?;
+        if self.entries.iter().any(|entry| entry.target==target) {
+            return Ok(());
+        }
+        let reason=reason.replace(['\n', '\r'], " ").trim().to_string();
+        self.entries.push(Revocation { target, date: date.to_string(), reason });
+        self.serial+=1;
+        Ok(())
+    }
+
+    pub fn is_revoked(&self,share:&Share)->bool{
+        self.revocation_for(share).is_some()
+    }
+
+    pub fn revocation_for(&self,share:&Share)->Option<&Revocation>{
+        self.fingerprint_revocation(&share_fingerprint(share.0, &share.1))
+    }
+
+    pub fn fingerprint_revocation(&self,fingerprint:&str)->Option<&Revocation>{
+        self.entries.iter().find(|entry| matches!(&entry.target, Revoked::Fingerprint(f) if f==fingerprint))
+    }
+
+    pub fn generation_revocation(&self,generation:u32)->Option<&Revocation>{
+        self.entries.iter().find(|entry| matches!(entry.target, Revoked::Generation(g) if g==generation))
+    }
+
+    // Indices of the revoked shares in `parts`.
+    pub fn revoked_in(&self,parts:&Parts)->Vec<i32>{
+        parts.iter().filter(|(index, data)| self.fingerprint_revocation(&share_fingerprint(**index, data)).is_some()).map(|(index, _)| *index).collect()
+    }
+
+    // Whether this list is `previous` with entries appended.
+    pub fn extends(&self,previous:&RevocationList)->Result<(),String>{
+        if self.serial<previous.serial {
+            return Err(format!("Revocation list serial {} is older than {}", self.serial, previous.serial));
+        }
+        if !self.entries.starts_with(&previous.entries) {
+            return Err("Revocation list drops or changes earlier entries".to_string());
+        }
+        Ok(())
+    }
+
+    // The text that is signed.
+    pub fn to_text(&self)->String{
+        let mut out=format!("{HEADER}\nserial {}\n", self.serial);
+        for entry in &self.entries {
+            let target=match &entry.target {
+                Revoked::Fingerprint(fingerprint) => format!("fingerprint {fingerprint}"),
+                Revoked::Generation(generation) => format!("generation {generation}"),
+            };
+            out.push_str(format!("revoke {target} {} {}", entry.date, entry.reason).trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn parse(text:&str)->Result<RevocationList,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(HEADER) {
+            return Err("Not a revocation list".to_string());
+        }
+        let serial=lines.next().and_then(|line| line.strip_prefix("serial "))
+            .and_then(|serial| serial.trim().parse::<u64>().ok())
+            .ok_or("Revocation list has no serial")?;
+        let mut list=RevocationList { serial, entries: Vec::new() };
+        for line in lines {
+            if line.starts_with(SIGNATURE_PREFIX) {
+                break;
+            }
+            let mut fields=line.splitn(5, ' ');
+            let (Some("revoke"), Some(kind), Some(value), Some(date))=(fields.next(), fields.next(), fields.next(), fields.next()) else {
+                return Err(format!("Malformed revocation line {line}"));
+            };
+            let target=match kind {
+                "fingerprint" => Revoked::Fingerprint(value.to_string()),
+                "generation" => Revoked::Generation(value.parse::<u32>().map_err(|_| format!("Malformed generation in {line}"))?),
+                _ => return Err(format!("Unknown revocation kind {kind}")),
+            };
+            check_date(date)?;
+            list.entries.push(Revocation { target, date: date.to_string(), reason: fields.next().unwrap_or("").to_string() });
+        }
+        if list.entries.len() as u64>list.serial {
+            return Err(format!("Revocation list serial {} is below its {} entries", list.serial, list.entries.len()));
+        }
+        Ok(list)
+    }
+}
+
+// Refuses a join that includes a revoked share.
+impl JoinGuard for RevocationList{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        let revoked:Vec<String>=ctx.shares.iter()
+            .filter_map(|share| {
+                let entry=self.fingerprint_revocation(&share.fingerprint)?;
+                Some(format!("share {} revoked on {}", share.index, entry.date))
+            })
+            .collect();
+        if revoked.is_empty() { Ok(()) } else { Err(revoked.join("; ")) }
+    }
+}
+
+fn check_date(date:&str)->Result<(),String>{
+    let bytes=date.as_bytes();
+    let valid=bytes.len()==10 && bytes.iter().enumerate().all(|(i, b)| if i==4 || i==7 { *b==b'-' } else { b.is_ascii_digit() });
+    if valid { Ok(()) } else { Err(format!("Revocation date {date:?} is not YYYY-MM-DD")) }
+}
+
+pub fn signed_text(text:&str,signature:&[u8])->String{
+    format!("{text}{SIGNATURE_PREFIX}{}\n", general_purpose::STANDARD.encode(signature))
+}
+
+// Splits a signed list (or recovery token) into the signed text and its
+// signature, if it has one.
+pub fn read_signed(text:&str)->Result<(String,Option<Vec<u8>>),String>{
+    let Some(start)=text.find(&format!("\n{SIGNATURE_PREFIX}")) else { return Ok((text.to_string(), None)) };
+    let signature=text[start+1+SIGNATURE_PREFIX.len()..].trim();
+    let signature=general_purpose::STANDARD.decode(signature).map_err(|e| format!("Malformed signature line: {e}"))?;
+    Ok((text[..start+1].to_string(), Some(signature)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_round_trips_and_only_grows() {
+        let mut first=RevocationList::default();
+        first.revoke(Revoked::Fingerprint("0ba20012".into()), "2026-10-16", "card\nstolen").unwrap();
+        first.revoke(Revoked::Fingerprint("0ba20012".into()), "2026-10-17", "again").unwrap();
+        assert_eq!(first.serial, 1);
+        assert_eq!(RevocationList::parse(&first.to_text()).unwrap(), first);
+
+        let mut second=first.clone();
+        second.revoke(Revoked::Generation(3), "2026-10-17", "").unwrap();
+        assert!(second.extends(&first).is_ok());
+        assert!(first.extends(&second).is_err());
+        let mut dropped=second.clone();
+        dropped.entries.remove(0);
+        assert!(dropped.extends(&first).is_err());
+    }
+
+    #[test]
+    fn rejects_dates_that_break_the_line_format() {
+        let mut list=RevocationList::default();
+        for date in ["2026-10-16 extra", "16/10/2026", "", "2026-1-16"] {
+            assert!(list.revoke(Revoked::Generation(1), date, "").is_err(), "{date:?}");
+        }
+        assert!(list.entries.is_empty());
+        assert!(RevocationList::parse("shamir-revocation-list v1\nserial 1\nrevoke generation 1 yesterday\n").is_err());
+    }
+
+    #[test]
+    fn signature_line_splits_off() {
+        let text=RevocationList::default().to_text();
+        let (signed, signature)=read_signed(&signed_text(&text, b"sig")).unwrap();
+        assert_eq!((signed, signature), (text.clone(), Some(b"sig".to_vec())));
+        assert_eq!(read_signed(&text).unwrap(), (text, None));
+    }
+}
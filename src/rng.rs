@@ -0,0 +1,188 @@
+use rand::rngs::OsRng;
+use rand::{Error, RngCore};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::error::ShamirError;
+
+// RNG for long-running dealers: a ChaCha20 stream seeded from the OS that is
+// reseeded after a number of output bytes or split operations, and whenever
+// the process id changes (a fork() child would otherwise replay the parent's
+// coefficient stream).
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct RngPolicy{
+    pub reseed_after_bytes:u64,
+    pub reseed_after_operations:u64,
+    pub fork_detection:bool,
+}
+
+impl Default for RngPolicy{
+    fn default()->Self{
+        RngPolicy { reseed_after_bytes: 1<<20, reseed_after_operations: 1024, fork_detection: true }
+    }
+}
+
+#[derive(Debug)]
+pub struct PolicyRng{
+    policy:RngPolicy,
+    inner:ChaCha20Rng,
+    bytes:u64,
+    operations:u64,
+    pid:u32,
+    reseeds:u64,
+}
+
+impl PolicyRng{
+
+    pub fn new(policy:RngPolicy)->PolicyRng{
+        PolicyRng {
+            policy,
+            inner: Self::fresh(),
+            bytes: 0,
+            operations: 0,
+            pid: std::process::id(),
+            reseeds: 0,
+        }
+    }
+
+    // Call once per split; counts towards `reseed_after_operations` and
+    // re-checks the pid before any coefficients are drawn.
+    pub fn begin_operation(&mut self){
+        self.operations+=1;
+        if self.operations>self.policy.reseed_after_operations {
+            self.reseed();
+        }
+        self.check_fork();
+    }
+
+    pub fn reseed(&mut self){
+        self.inner=Self::fresh();
+        self.bytes=0;
+        self.operations=0;
+        self.reseeds+=1;
+    }
+
+    // Overwrites the generator state in place with a fresh seed.
+    pub fn scrub(&mut self){
+        self.reseed();
+    }
+
+    pub fn reseeds(&self)->u64{
+        self.reseeds
+    }
+
+    fn fresh()->ChaCha20Rng{
+        ChaCha20Rng::from_rng(OsRng).expect("OS random source unavailable")
+    }
+
+    fn check_fork(&mut self){
+        if self.policy.fork_detection {
+            let pid=std::process::id();
+            if pid!=self.pid {
+                self.pid=pid;
+                self.reseed();
+            }
+        }
+    }
+
+    fn account(&mut self,len:usize){
+        self.check_fork();
+        self.bytes+=len as u64;
+        if self.bytes>self.policy.reseed_after_bytes {
+            self.reseed();
+            self.bytes=len as u64;
+        }
+    }
+}
+
+impl RngCore for PolicyRng{
+    fn next_u32(&mut self)->u32{
+        self.account(4);
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self)->u64{
+        self.account(8);
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self,dest:&mut [u8]){
+        self.account(dest.len());
+        self.inner.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self,dest:&mut [u8])->Result<(),Error>{
+        self.account(dest.len());
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+impl ShamirSS{
+
+    // `split` drawing from a long-lived PolicyRng, counting one operation.
+    pub fn split_with_policy(n:i32,k:i32,secret:Vec<u8>,rng:&mut PolicyRng)->Result<Parts,ShamirError>{
+        rng.begin_operation();
+        Self::split_with_rng(n, k, secret, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(bytes:u64,operations:u64)->RngPolicy{
+        RngPolicy { reseed_after_bytes: bytes, reseed_after_operations: operations, fork_detection: true }
+    }
+
+    #[test]
+    fn reseeds_after_the_byte_budget() {
+        let mut rng=PolicyRng::new(policy(64, u64::MAX));
+        let mut block=[0u8;32];
+        rng.fill_bytes(&mut block);
+        rng.fill_bytes(&mut block);
+        assert_eq!(rng.reseeds(), 0);
+
+        // The stream that would have continued without a reseed.
+        let mut stale=rng.inner.clone();
+        rng.fill_bytes(&mut block);
+        assert_eq!(rng.reseeds(), 1);
+        let mut expected=[0u8;32];
+        stale.fill_bytes(&mut expected);
+        assert_ne!(block, expected);
+        assert_eq!(rng.bytes, 32);
+    }
+
+    #[test]
+    fn reseeds_after_the_operation_budget() {
+        let mut rng=PolicyRng::new(policy(u64::MAX, 3));
+        for _ in 0..3 {
+            ShamirSS::split_with_policy(3, 2, b"secret".to_vec(), &mut rng).unwrap();
+        }
+        assert_eq!(rng.reseeds(), 0);
+        ShamirSS::split_with_policy(3, 2, b"secret".to_vec(), &mut rng).unwrap();
+        assert_eq!(rng.reseeds(), 1);
+        assert_eq!(rng.operations, 0);
+    }
+
+    #[test]
+    fn pid_change_forces_a_reseed() {
+        // As a fork() child sees it: the pid recorded at seeding is the parent's.
+        let mut rng=PolicyRng::new(RngPolicy::default());
+        rng.pid=rng.pid.wrapping_add(1);
+        let mut stale=rng.inner.clone();
+        let value=rng.next_u64();
+        assert_eq!(rng.reseeds(), 1);
+        assert_eq!(rng.pid, std::process::id());
+        assert_ne!(value, stale.next_u64());
+
+        rng.pid=rng.pid.wrapping_add(1);
+        rng.begin_operation();
+        assert_eq!(rng.reseeds(), 2);
+
+        let mut unchecked=PolicyRng::new(RngPolicy { fork_detection: false, ..RngPolicy::default() });
+        unchecked.pid=unchecked.pid.wrapping_add(1);
+        unchecked.next_u64();
+        assert_eq!(unchecked.reseeds(), 0);
+    }
+}
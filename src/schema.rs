@@ -0,0 +1,493 @@
+use std::collections::BTreeMap;
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::json::{Json, JsonExt};
+use crate::sha256::Sha256;
+use crate::transcript::JSON_VERSION as TRANSCRIPT_JSON_VERSION;
+
+// Structured secrets: a small schema names the fields and their types, the
+// record is serialized canonically (schema order, big-endian, length-prefixed
+// variable fields) before splitting and parsed back after joining.
+//
+// Encoding: MAGIC | first 8 bytes of SHA-256(schema text) | fields. The
+// schema digest makes a join under the wrong schema fail instead of
+// returning garbage fields. Variable-length fields show up in the share
+// length; give them a fixed size when that matters.
+//
+// Schema text: comma-separated `name:type`, with types `text`, `bytes`,
+// `bytes(N)`, `u64`, `bool` and `fixed(D)` (decimal with D fractional digits,
+// stored as an i64 count of 10^-D units).
+//
+// Typed structs go through serde: `split_typed` serializes any `Serialize`
+// struct whose fields match the schema (`String`, `Vec<u8>` or `[u8; N]`,
+// `u64`, `bool`, and `i64` for fixed), and `join_typed` deserializes it back.
+// The intermediate serde_json value is not zeroized; the encoded secret is.
+
+const MAGIC:&[u8;4]=b"SCH1";
+const DIGEST_LEN:usize=8;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum FieldType{
+    Text,
+    Bytes(Option<usize>),
+    U64,
+    Bool,
+    Fixed(u8),
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Field{
+    pub name:String,
+    pub kind:FieldType,
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct Schema{
+    pub fields:Vec<Field>,
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub enum Value{
+    Text(String),
+    Bytes(Vec<u8>),
+    U64(u64),
+    Bool(bool),
+    Fixed(i64),
+}
+
+pub type Record=BTreeMap<String,Value>;
+
+impl Schema{
+
+    pub fn parse(text:&str)->Result<Schema,String>{
+        let mut fields:Vec<Field>=Vec::new();
+        for item in text.split(',').map(str::trim).filter(|item| !item.is_empty()) {
+            let (name, kind)=item.split_once(':').ok_or(format!("Schema field {item} has no type"))?;
+            let name=name.trim();
+            if name.is_empty() || fields.iter().any(|f| f.name==name) {
+                return Err(format!("Schema field name {name:?} is empty or repeated"));
+            }
+            let kind=match kind.trim() {
+                "text" => FieldType::Text,
+                "bytes" => FieldType::Bytes(None),
+                "u64" => FieldType::U64,
+                "bool" => FieldType::Bool,
+                other => {
+                    let arg=|prefix:&str| other.strip_prefix(prefix).and_then(|rest| rest.strip_suffix(')'));
+                    if let Some(len)=arg("bytes(") {
+                        FieldType::Bytes(Some(len.parse().map_err(|_| format!("Bad length in {other}"))?))
+                    } else if let Some(digits)=arg("fixed(") {
+                        let digits:u8=digits.parse().map_err(|_| format!("Bad digits in {other}"))?;
+                        if digits>18 {
+                            return Err(format!("{other} has more than 18 fractional digits"));
+                        }
+                        FieldType::Fixed(digits)
+                    } else {
+                        return Err(format!("Unknown schema type {other}"));
+                    }
+                }
+            };
+            fields.push(Field { name: name.to_string(), kind });
+        }
+        if fields.is_empty() {
+            return Err("Schema has no fields".to_string());
+        }
+        Ok(Schema { fields })
+    }
+
+    // Canonical text form; `parse(to_text())` gives the same schema.
+    pub fn to_text(&self)->String{
+        self.fields.iter().map(|field| {
+            let kind=match field.kind {
+                FieldType::Text => "text".to_string(),
+                FieldType::Bytes(None) => "bytes".to_string(),
+                FieldType::Bytes(Some(len)) => format!("bytes({len})"),
+                FieldType::U64 => "u64".to_string(),
+                FieldType::Bool => "bool".to_string(),
+                FieldType::Fixed(digits) => format!("fixed({digits})"),
+            };
+            format!("{}:{kind}", field.name)
+        }).collect::<Vec<_>>().join(",")
+    }
+
+    fn digest(&self)->[u8;DIGEST_LEN]{
+        Sha256::digest(self.to_text().as_bytes())[..DIGEST_LEN].try_into().unwrap()
+    }
+
+    pub fn encode(&self,record:&Record)->Result<Vec<u8>,String>{
+        if let Some(extra)=record.keys().find(|key| !self.fields.iter().any(|f| &f.name==*key)) {
+            return Err(format!("Record field {extra} is not in the schema"));
+        }
+        let mut out=Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&self.digest());
+        for field in &self.fields {
+            let value=record.get(&field.name).ok_or(format!("Record is missing field {}", field.name))?;
+            match (field.kind, value) {
+                (FieldType::Text, Value::Text(text)) => put_variable(&mut out, text.as_bytes())?,
+                (FieldType::Bytes(None), Value::Bytes(data)) => put_variable(&mut out, data)?,
+                (FieldType::Bytes(Some(len)), Value::Bytes(data)) if data.len()==len => out.extend_from_slice(data),
+                (FieldType::Bytes(Some(len)), Value::Bytes(data)) => {
+                    return Err(format!("Field {} holds {} bytes, schema requires {len}", field.name, data.len()));
+                }
+                (FieldType::U64, Value::U64(v)) => out.extend_from_slice(&v.to_be_bytes()),
+                (FieldType::Bool, Value::Bool(v)) => out.push(*v as u8),
+                (FieldType::Fixed(_), Value::Fixed(v)) => out.extend_from_slice(&v.to_be_bytes()),
+                _ => return Err(format!("Field {} does not match its schema type", field.name)),
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn decode(&self,data:&[u8])->Result<Record,String>{
+        if data.len()<MAGIC.len()+DIGEST_LEN || &data[..MAGIC.len()]!=MAGIC {
+            return Err("Not a schema-encoded secret".to_string());
+        }
+        if data[MAGIC.len()..MAGIC.len()+DIGEST_LEN]!=self.digest() {
+            return Err("Secret was encoded with a different schema".to_string());
+        }
+        let mut rest=&data[MAGIC.len()+DIGEST_LEN..];
+        let mut record=Record::new();
+        for field in &self.fields {
+            let value=match field.kind {
+                FieldType::Text => {
+                    let bytes=take_variable(&mut rest)?;
+                    Value::Text(String::from_utf8(bytes.to_vec()).map_err(|_| format!("Field {} is not UTF-8", field.name))?)
+                }
+                FieldType::Bytes(None) => Value::Bytes(take_variable(&mut rest)?.to_vec()),
+                FieldType::Bytes(Some(len)) => Value::Bytes(take(&mut rest, len)?.to_vec()),
+                FieldType::U64 => Value::U64(u64::from_be_bytes(take(&mut rest, 8)?.try_into().unwrap())),
+                FieldType::Bool => match take(&mut rest, 1)?[0] {
+                    0 => Value::Bool(false),
+                    1 => Value::Bool(true),
+                    _ => return Err(format!("Field {} is not a boolean", field.name)),
+                },
+                FieldType::Fixed(_) => Value::Fixed(i64::from_be_bytes(take(&mut rest, 8)?.try_into().unwrap())),
+            };
+            record.insert(field.name.clone(), value);
+        }
+        if !rest.is_empty() {
+            return Err("Trailing bytes after the last schema field".to_string());
+        }
+        Ok(record)
+    }
+}
+
+impl Schema{
+
+    // The record of a JSON object with the schema's fields, as serde_json
+    // writes a struct: text as strings, bytes as arrays of 0..=255.
+    pub fn record_from_json(&self,value:&Json)->Result<Record,String>{
+        let object=value.as_object().ok_or("Typed secret is not a struct")?;
+        if let Some(extra)=object.keys().find(|key| !self.fields.iter().any(|f| &f.name==*key)) {
+            return Err(format!("Struct field {extra} is not in the schema"));
+        }
+        let mut record=Record::new();
+        for field in &self.fields {
+            let item=object.get(&field.name).ok_or(format!("Struct is missing field {}", field.name))?;
+            let mismatch=|| format!("Struct field {} does not match its schema type", field.name);
+            let value=match field.kind {
+                FieldType::Text => Value::Text(item.as_str().ok_or_else(mismatch)?.to_string()),
+                FieldType::Bytes(_) => Value::Bytes(item.as_array().ok_or_else(mismatch)?.iter()
+                    .map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()).ok_or_else(mismatch))
+                    .collect::<Result<_,_>>()?),
+                FieldType::U64 => Value::U64(item.as_u64().ok_or_else(mismatch)?),
+                FieldType::Bool => Value::Bool(item.as_bool().ok_or_else(mismatch)?),
+                FieldType::Fixed(_) => Value::Fixed(item.as_i64().ok_or_else(mismatch)?),
+            };
+            record.insert(field.name.clone(), value);
+        }
+        Ok(record)
+    }
+
+    pub fn record_to_json(&self,record:&Record)->Json{
+        Json::Object(record.iter().map(|(name, value)| {
+            let item=match value {
+                Value::Text(text) => Json::str(text),
+                Value::Bytes(data) => Json::Array(data.iter().map(|&b| Json::from(b)).collect()),
+                Value::U64(v) => Json::from(*v),
+                Value::Bool(v) => Json::from(*v),
+                Value::Fixed(v) => Json::from(*v),
+            };
+            (name.clone(), item)
+        }).collect())
+    }
+}
+
+fn put_variable(out:&mut Vec<u8>,data:&[u8])->Result<(),String>{
+    let len=u32::try_from(data.len()).map_err(|_| "Field longer than 4 GiB".to_string())?;
+    out.extend_from_slice(&len.to_be_bytes());
+    out.extend_from_slice(data);
+    Ok(())
+}
+
+fn take<'a>(rest:&mut &'a [u8],len:usize)->Result<&'a [u8],String>{
+    if rest.len()<len {
+        return Err("Schema-encoded secret is truncated".to_string());
+    }
+    let (head, tail)=rest.split_at(len);
+    *rest=tail;
+    Ok(head)
+}
+
+fn take_variable<'a>(rest:&mut &'a [u8])->Result<&'a [u8],String>{
+    let len=u32::from_be_bytes(take(rest, 4)?.try_into().unwrap()) as usize;
+    take(rest, len)
+}
+
+impl ShamirSS{
+
+    pub fn split_record(n:i32,k:i32,schema:&Schema,record:&Record)->Result<Parts,String>{
+        Ok(Self::split(n, k, schema.encode(record)?)?)
+    }
+
+    pub fn join_record(parts:Parts,schema:&Schema)->Result<Record,String>{
+        let mut encoded=Self::join(parts)?;
+        let record=schema.decode(&encoded);
+        encoded.fill(0);
+        record
+    }
+
+    pub fn split_typed<T:Serialize>(n:i32,k:i32,schema:&Schema,value:&T)->Result<Parts,String>{
+        let json=serde_json::to_value(value).map_err(|e| format!("Cannot serialize the typed secret: {e}"))?;
+        Self::split_record(n, k, schema, &schema.record_from_json(&json)?)
+    }
+
+    pub fn join_typed<T:DeserializeOwned>(parts:Parts,schema:&Schema)->Result<T,String>{
+        let record=Self::join_record(parts, schema)?;
+        serde_json::from_value(schema.record_to_json(&record)).map_err(|e| format!("Joined record does not fit the type: {e}"))
+    }
+}
+
+// JSON Schema (draft 2020-12) for the JSON documents the crate writes, so
+// integrators can validate them in their own pipelines and languages:
+//   share       `formats::JsonEncoding`: {"index": 1, "share": "<base64>"}
+//   share_sets  `split --stdin-multi` on stdout: one entry per record
+//   transcript  `Transcript::to_json`
+// The root accepts any of the three; refer to `#/$defs/<name>` for one.
+// Derived with schemars from the document shapes below, which only exist
+// for the schema: a field a writer adds to a document is added here too.
+// Optional fields are `Option` (written as null) or `#[serde(default)]`
+// (may be absent); unknown properties are allowed, since readers skip
+// fields from newer versions.
+pub const JSON_SCHEMA_ID:&str="urn:shamir-rust:json-schema:v1";
+
+pub fn json_schema()->Json{
+    let mut schema=schemars::schema_for!(documents::Document);
+    schema.insert("$id".to_string(), JSON_SCHEMA_ID.into());
+    schema.to_value()
+}
+
+const TEXT_SHARE_PATTERN:&str="^[0-9]{1,3}:[A-Za-z0-9+/]*={0,2}$";
+const DATE_PATTERN:&str="^[0-9]{4}-[0-9]{2}-[0-9]{2}$";
+const HEX_PATTERN:&str="^[0-9a-f]+$";
+const FINGERPRINT_PATTERN:&str="^[0-9a-f]{8}$";
+
+// The shapes are only described, never built or read.
+#[allow(dead_code)]
+mod documents{
+    use schemars::JsonSchema;
+
+    use super::{DATE_PATTERN, FINGERPRINT_PATTERN, HEX_PATTERN, TEXT_SHARE_PATTERN, TRANSCRIPT_JSON_VERSION};
+
+    #[derive(JsonSchema)]
+    #[serde(untagged)]
+    #[schemars(title = "shamir-rust JSON documents")]
+    pub(super) enum Document{
+        Share(ShareDocument),
+        ShareSets(ShareSets),
+        Transcript(Box<TranscriptDocument>),
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "share")]
+    pub(super) struct ShareDocument{
+        #[schemars(range(min = 1, max = 255))]
+        index:u8,
+        #[schemars(extend("contentEncoding" = "base64"))]
+        share:String,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "share_sets")]
+    pub(super) struct ShareSets(Vec<ShareSetEntry>);
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "share_set")]
+    pub(super) struct ShareSetEntry{
+        #[schemars(range(min = 1))]
+        record:u64,
+        #[schemars(range(min = 1))]
+        bytes:u64,
+        shares:Vec<SetShare>,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "set_share")]
+    pub(super) struct SetShare{
+        #[schemars(range(min = 1, max = 255))]
+        index:u8,
+        #[schemars(regex(pattern = FINGERPRINT_PATTERN))]
+        fingerprint:String,
+        #[schemars(regex(pattern = TEXT_SHARE_PATTERN))]
+        share:String,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "transcript")]
+    pub(super) struct TranscriptDocument{
+        #[schemars(extend("const" = TRANSCRIPT_JSON_VERSION))]
+        version:i64,
+        #[schemars(range(min = 1))]
+        threshold:u32,
+        #[schemars(range(min = 1))]
+        shares:u32,
+        #[schemars(regex(pattern = DATE_PATTERN))]
+        created:Option<String>,
+        #[schemars(regex(pattern = HEX_PATTERN))]
+        id:Option<String>,
+        escrow:Option<String>,
+        custodians:Vec<Custodian>,
+        #[serde(default)]
+        spares:Vec<Spare>,
+        entropy:Option<Entropy>,
+        hsm:Option<Hsm>,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "custodian")]
+    pub(super) struct Custodian{
+        #[schemars(range(min = 1, max = 255))]
+        index:u8,
+        label:String,
+        #[schemars(regex(pattern = FINGERPRINT_PATTERN))]
+        fingerprint:String,
+        contact:Option<String>,
+        #[schemars(regex(pattern = DATE_PATTERN))]
+        next_drill_due:Option<String>,
+        #[schemars(regex(pattern = HEX_PATTERN))]
+        watermark:Option<String>,
+        org:Option<String>,
+        #[schemars(regex(pattern = HEX_PATTERN))]
+        ack_challenge:Option<String>,
+        #[schemars(regex(pattern = HEX_PATTERN))]
+        ack_digest:Option<String>,
+        #[schemars(regex(pattern = DATE_PATTERN))]
+        acknowledged:Option<String>,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "spare")]
+    pub(super) struct Spare{
+        #[schemars(range(min = 1, max = 255))]
+        index:u8,
+        #[schemars(regex(pattern = FINGERPRINT_PATTERN))]
+        fingerprint:String,
+        #[schemars(regex(pattern = DATE_PATTERN))]
+        activated:Option<String>,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "entropy")]
+    pub(super) struct Entropy{
+        bytes:u64,
+        #[serde(default)]
+        chi_squared:String,
+        #[serde(default)]
+        serial_correlation:String,
+        #[serde(default)]
+        serial_limit:String,
+        os_random:bool,
+        passed:bool,
+        #[serde(default)]
+        source:String,
+    }
+
+    #[derive(JsonSchema)]
+    #[schemars(rename = "hsm")]
+    pub(super) struct Hsm{
+        token:String,
+        #[serde(default)]
+        manufacturer:String,
+        #[serde(default)]
+        model:String,
+        #[serde(default)]
+        serial:String,
+        mechanism:String,
+        key:String,
+        #[schemars(regex(pattern = HEX_PATTERN))]
+        check_value:Option<String>,
+        #[schemars(extend("contentEncoding" = "base64"))]
+        attestation:Option<String>,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug,PartialEq,Serialize,Deserialize)]
+    pub(super) struct Login{
+        username:String,
+        password:String,
+        totp_seed:[u8;20],
+        counter:u64,
+        admin:bool,
+        limit:i64,
+    }
+
+    const LOGIN:&str="username:text,password:text,totp_seed:bytes(20),counter:u64,admin:bool,limit:fixed(2)";
+
+    #[test]
+    fn typed_struct_round_trips_through_serde() {
+        let schema=Schema::parse(LOGIN).unwrap();
+        let login=Login { username: "ops".into(), password: "hunter2".into(), totp_seed: [7;20], counter: 42, admin: true, limit: -150 };
+        let parts=ShamirSS::split_typed(5, 3, &schema, &login).unwrap();
+        let subset:Parts=parts.into_iter().skip(2).collect();
+        assert_eq!(ShamirSS::join_typed::<Login>(subset.clone(), &schema).unwrap(), login);
+
+        let other=Schema::parse("username:text,password:text").unwrap();
+        assert!(ShamirSS::join_typed::<Login>(subset, &other).is_err());
+        assert!(ShamirSS::split_typed(5, 3, &other, &login).is_err());
+    }
+
+    // Every field `Transcript::to_json` writes is described, and every
+    // required one is written, so the schema stays in step with the writer.
+    #[test]
+    fn json_schema_describes_the_transcript_writer() {
+        use crate::transcript::{CustodianRecord, EntropyRecord, HsmRecord, SpareRecord, Transcript};
+        let transcript=Transcript {
+            threshold: 2,
+            shares: 3,
+            custodians: vec![CustodianRecord { index: 1, label: "alice".into(), fingerprint: "0badf00d".into(), ..Default::default() }],
+            spares: vec![SpareRecord { index: 254, fingerprint: "deadbeef".into(), activated: None }],
+            entropy: Some(EntropyRecord::default()),
+            hsm: Some(HsmRecord::default()),
+            ..Default::default()
+        };
+        let document=transcript.to_json();
+        let schema=json_schema();
+        assert_eq!(schema["$id"], JSON_SCHEMA_ID);
+        assert_eq!(schema["anyOf"].as_array().map(Vec::len), Some(3));
+        let check=|def:&str, value:&Json| {
+            let shape=&schema["$defs"][def];
+            let written=value.as_object().unwrap();
+            for key in written.keys() {
+                assert!(shape["properties"].get(key).is_some(), "{def}.{key} is written but not in the schema");
+            }
+            for key in shape["required"].as_array().unwrap() {
+                assert!(written.contains_key(key.as_str().unwrap()), "{def}.{key} is required but not written");
+            }
+        };
+        check("transcript", &document);
+        check("custodian", &document["custodians"][0]);
+        check("spare", &document["spares"][0]);
+        check("entropy", &document["entropy"]);
+        check("hsm", &document["hsm"]);
+        assert_eq!(schema["$defs"]["transcript"]["properties"]["version"]["const"], TRANSCRIPT_JSON_VERSION);
+        assert_eq!(schema["$defs"]["share_sets"]["items"]["$ref"], "#/$defs/share_set");
+    }
+}
@@ -0,0 +1,173 @@
+use rand::RngCore;
+
+use crate::crypto::{Parts, ShamirSS, Share};
+use crate::error::ShamirError;
+
+// Common interface over secret sharing schemes, so applications can be
+// generic over the scheme and a stored scheme id can pick the joiner.
+// Each scheme owns its parameter type; joining needs only the shares.
+
+pub const SHAMIR_SCHEME_ID:u8=1;
+pub const XOR_SCHEME_ID:u8=2;
+
+pub trait SecretSharingScheme{
+    type Params;
+
+    fn scheme_id(&self)->u8;
+    fn split(&self,params:&Self::Params,secret:&[u8])->Result<Vec<Share>,ShamirError>;
+    fn join(&self,shares:&[Share])->Result<Vec<u8>,ShamirError>;
+}
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct ThresholdParams{
+    pub threshold:i32,
+    pub shares:i32,
+}
+
+// k-of-n Shamir over GF(256), as implemented by `ShamirSS`.
+#[derive(Debug,Clone,Copy,Default)]
+pub struct Shamir;
+
+impl SecretSharingScheme for Shamir{
+    type Params=ThresholdParams;
+
+    fn scheme_id(&self)->u8{
+        SHAMIR_SCHEME_ID
+    }
+
+    fn split(&self,params:&ThresholdParams,secret:&[u8])->Result<Vec<Share>,ShamirError>{
+        Ok(ShamirSS::split(params.shares, params.threshold, secret.to_vec())?.into_iter().collect())
+    }
+
+    fn join(&self,shares:&[Share])->Result<Vec<u8>,ShamirError>{
+        ShamirSS::join(distinct(shares)?)
+    }
+}
+
+// The shares keyed by index; a repeated index is an error rather than one
+// copy silently replacing the other.
+fn distinct(shares:&[Share])->Result<Parts,ShamirError>{
+    let mut parts=Parts::new();
+    for (index, data) in shares {
+        if parts.insert(*index, data.clone()).is_some() {
+            return Err(ShamirError::DuplicateIndex(*index))
+        }
+    }
+    Ok(parts)
+}
+
+// n-of-n XOR splitting: n-1 random shares and one share holding the secret
+// XORed with all of them. Every share is needed; there is no threshold.
+// Shares are indexed 1..=n and their data starts with n, so a join can tell
+// a missing share from a complete set instead of returning a wrong secret.
+#[derive(Debug,Clone,Copy,Default)]
+pub struct Xor;
+
+impl SecretSharingScheme for Xor{
+    type Params=i32;
+
+    fn scheme_id(&self)->u8{
+        XOR_SCHEME_ID
+    }
+
+    fn split(&self,shares:&i32,secret:&[u8])->Result<Vec<Share>,ShamirError>{
+        if *shares<=1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        if *shares>255 {
+            return Err(ShamirError::TooManyShares)
+        }
+        let mut rng=rand::thread_rng();
+        let mut last=secret.to_vec();
+        let mut out=Vec::with_capacity(*shares as usize);
+        for index in 1..*shares {
+            let mut data=vec![0u8;secret.len()];
+            rng.fill_bytes(&mut data);
+            for (l, d) in last.iter_mut().zip(&data) {
+                *l^=d;
+            }
+            out.push((index, data));
+        }
+        out.push((*shares, last));
+        Ok(out.into_iter().map(|(index, data)| (index, [&[*shares as u8][..], &data].concat())).collect())
+    }
+
+    fn join(&self,shares:&[Share])->Result<Vec<u8>,ShamirError>{
+        let parts=distinct(shares)?;
+        let (_, first)=parts.first_key_value().ok_or(ShamirError::NoParts)?;
+        let (&n, len)=first.split_first().map(|(n, rest)| (n, rest.len())).ok_or(ShamirError::EmptySecret)?;
+        let foreign:Vec<i32>=parts.iter().filter(|(_, data)| data.first()!=Some(&n)).map(|(index, _)| *index).collect();
+        if !foreign.is_empty() {
+            return Err(ShamirError::ForeignShares(foreign))
+        }
+        if let Some(index)=parts.keys().find(|index| !(1..=n as i32).contains(*index)) {
+            return Err(ShamirError::InvalidIndex(*index))
+        }
+        if parts.len()!=n as usize {
+            return Err(ShamirError::InsufficientParts { collected: parts.len(), threshold: n as usize })
+        }
+        let mut secret=vec![0u8;len];
+        for data in parts.values() {
+            if data.len()!=len+1 {
+                return Err(ShamirError::InconsistentLengths)
+            }
+            for (s, d) in secret.iter_mut().zip(&data[1..]) {
+                *s^=d;
+            }
+        }
+        Ok(secret)
+    }
+}
+
+// Joins with whichever scheme the id names, e.g. one read from a share header.
+pub fn join_with_scheme(scheme_id:u8,shares:&[Share])->Result<Vec<u8>,ShamirError>{
+    match scheme_id {
+        SHAMIR_SCHEME_ID => Shamir.join(shares),
+        XOR_SCHEME_ID => Xor.join(shares),
+        other => Err(ShamirError::UnknownScheme(other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<S:SecretSharingScheme>(scheme:&S,params:&S::Params,take:usize){
+        let shares=scheme.split(params, b"scheme secret").unwrap();
+        assert_eq!(scheme.join(&shares[..take]).unwrap(), b"scheme secret");
+        assert_eq!(join_with_scheme(scheme.scheme_id(), &shares[..take]).unwrap(), b"scheme secret");
+    }
+
+    fn rejects_duplicates<S:SecretSharingScheme>(scheme:&S,params:&S::Params){
+        let mut shares=scheme.split(params, b"scheme secret").unwrap();
+        let first=shares[0].clone();
+        shares.push(first);
+        assert_eq!(scheme.join(&shares), Err(ShamirError::DuplicateIndex(1)));
+    }
+
+    #[test]
+    fn both_schemes_round_trip_through_the_trait() {
+        round_trip(&Shamir, &ThresholdParams { threshold: 2, shares: 3 }, 2);
+        round_trip(&Xor, &3, 3);
+        assert_eq!(join_with_scheme(9, &[]), Err(ShamirError::UnknownScheme(9)));
+    }
+
+    #[test]
+    fn both_schemes_reject_duplicate_indices() {
+        rejects_duplicates(&Shamir, &ThresholdParams { threshold: 2, shares: 3 });
+        rejects_duplicates(&Xor, &3);
+    }
+
+    #[test]
+    fn xor_needs_every_share() {
+        let shares=Xor.split(&3, b"scheme secret").unwrap();
+        for missing in 0..3 {
+            let rest:Vec<Share>=shares.iter().enumerate().filter(|(i, _)| *i!=missing).map(|(_, s)| s.clone()).collect();
+            assert_eq!(Xor.join(&rest), Err(ShamirError::InsufficientParts { collected: 2, threshold: 3 }));
+        }
+        let other=Xor.split(&4, b"scheme secret").unwrap();
+        let mixed=[shares[0].clone(), shares[1].clone(), other[2].clone()];
+        assert_eq!(Xor.join(&mixed), Err(ShamirError::ForeignShares(vec![3])));
+        assert_eq!(Xor.join(&[]), Err(ShamirError::NoParts));
+    }
+}
@@ -0,0 +1,237 @@
+use std::fs;
+use std::path::Path;
+
+use rand::RngCore;
+
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::encoding::{decode_share, encode_share};
+use crate::error::ShamirError;
+use crate::hardening::write_private;
+use crate::hybrid::HybridSS;
+use crate::sha256::Sha256;
+
+// Collects shares one at a time (e.g. one per custodian request) until the
+// threshold is reached. The session's copies of the shares are overwritten
+// once `finalize` has enough of them, on `wipe`, and when the session is dropped.
+//
+// For recoveries spanning days, `save` writes the collected shares to disk
+// encrypted under a passphrase and `resume` restores them. File layout:
+// MAGIC | salt (16) | PBKDF2 iterations (u32 BE) | HybridSS::encrypt_with_aad
+// of the session text ("threshold=K", "expected=x,y,...", then one share per
+// line), keyed by PBKDF2-HMAC-SHA256(passphrase, salt), with the header
+// before it as associated data. The iteration count is read before anything
+// is authenticated, so `resume` refuses counts above MAX_SESSION_ITERATIONS
+// instead of spending hours on an edited file.
+
+const SESSION_MAGIC:&[u8;4]=b"SHJ2";
+const SALT_LEN:usize=16;
+const HEADER_LEN:usize=4+SALT_LEN+4;
+pub const SESSION_ITERATIONS:u32=200_000;
+pub const MAX_SESSION_ITERATIONS:u32=10*SESSION_ITERATIONS;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct Progress{
+    pub collected:usize,
+    pub threshold:usize,
+}
+
+impl Progress{
+    pub fn is_complete(&self)->bool{
+        self.collected>=self.threshold
+    }
+}
+
+#[derive(Debug)]
+pub struct QuorumSession{
+    threshold:usize,
+    parts:Parts,
+    expected:Vec<i32>,
+}
+
+impl QuorumSession{
+
+    pub fn new(threshold:i32)->Result<QuorumSession,ShamirError>{
+        if threshold <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        Ok(QuorumSession { threshold: threshold as usize, parts: Parts::new(), expected: Vec::new() })
+    }
+
+    pub fn add(&mut self,index:i32,data:Vec<u8>)->Result<Progress,ShamirError>{
+        if !(1..=255).contains(&index) {
+            return Err(ShamirError::InvalidIndex(index));
+        }
+        if self.parts.contains_key(&index) {
+            return Err(ShamirError::DuplicateIndex(index));
+        }
+        if let Some(first)=self.parts.values().next() {
+            if first.len()!=data.len() {
+                return Err(ShamirError::InconsistentLengths);
+            }
+        }
+        self.parts.insert(index, data);
+        Ok(self.progress())
+    }
+
+    pub fn progress(&self)->Progress{
+        Progress { collected: self.parts.len(), threshold: self.threshold }
+    }
+
+    pub fn indices(&self)->Vec<i32>{
+        self.parts.keys().copied().collect()
+    }
+
+    // Indices of the custodians that may still bring a share, so a long
+    // recovery can show who is outstanding.
+    pub fn set_expected(&mut self,indices:&[i32]){
+        self.expected=indices.to_vec();
+    }
+
+    pub fn outstanding(&self)->Vec<i32>{
+        self.expected.iter().copied().filter(|index| !self.parts.contains_key(index)).collect()
+    }
+
+    pub fn save(&self,path:&Path,passphrase:&str)->Result<(),String>{
+        let mut salt=[0u8;SALT_LEN];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut key=Sha256::pbkdf2(passphrase.as_bytes(), &salt, SESSION_ITERATIONS);
+
+        let expected:Vec<String>=self.expected.iter().map(|i| i.to_string()).collect();
+        let mut text=format!("threshold={}\nexpected={}\n", self.threshold, expected.join(","));
+        for (index, data) in &self.parts {
+            text.push_str(&encode_share(*index, data));
+        }
+        let mut plain=text.into_bytes();
+
+        let mut file=SESSION_MAGIC.to_vec();
+        file.extend_from_slice(&salt);
+        file.extend_from_slice(&SESSION_ITERATIONS.to_be_bytes());
+        let sealed=HybridSS::encrypt_with_aad(&key, 1, &plain, &file);
+        zeroize(&mut plain);
+        zeroize(&mut key);
+        file.extend_from_slice(&sealed?);
+        // The file holds the collected shares.
+        write_private(path, &file).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    pub fn resume(path:&Path,passphrase:&str)->Result<QuorumSession,String>{
+        let file=fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        if file.len()<HEADER_LEN || !file.starts_with(SESSION_MAGIC) {
+            return Err(format!("{} is not a saved join session", path.display()));
+        }
+        let (header, sealed)=file.split_at(HEADER_LEN);
+        let (salt, count)=header[SESSION_MAGIC.len()..].split_at(SALT_LEN);
+        let iterations=u32::from_be_bytes([count[0], count[1], count[2], count[3]]);
+        if !(1..=MAX_SESSION_ITERATIONS).contains(&iterations) {
+            return Err(format!("{}: {iterations} PBKDF2 iterations is outside 1..={MAX_SESSION_ITERATIONS}", path.display()));
+        }
+        let mut key=Sha256::pbkdf2(passphrase.as_bytes(), salt, iterations);
+        let plain=HybridSS::decrypt_with_aad(&key, sealed, header);
+        zeroize(&mut key);
+        let mut plain=plain.map_err(|_| "Wrong passphrase or damaged session file".to_string())?;
+
+        let result=Self::parse_saved(&plain);
+        zeroize(&mut plain);
+        result
+    }
+
+    fn parse_saved(plain:&[u8])->Result<QuorumSession,String>{
+        let text=std::str::from_utf8(plain).map_err(|_| "Session file is not text".to_string())?;
+        let mut lines=text.lines();
+        let threshold=lines.next().and_then(|l| l.strip_prefix("threshold=")).and_then(|v| v.parse::<i32>().ok())
+            .ok_or("Session file is missing its threshold")?;
+        let expected=lines.next().and_then(|l| l.strip_prefix("expected=")).ok_or("Session file is missing expected indices")?;
+        let mut session=QuorumSession::new(threshold)?;
+        session.expected=expected.split(',').filter(|v| !v.is_empty())
+            .map(|v| v.parse::<i32>().map_err(|_| "Malformed expected index".to_string()))
+            .collect::<Result<_,_>>()?;
+        for line in lines.filter(|l| !l.is_empty()) {
+            let (index, data)=decode_share(line)?;
+            session.add(index, data)?;
+        }
+        Ok(session)
+    }
+
+    // Reconstructs once the threshold is met. Calling it early keeps the
+    // collected shares so collection can continue.
+    pub fn finalize(&mut self)->Result<Vec<u8>,ShamirError>{
+        let progress=self.progress();
+        if !progress.is_complete() {
+            return Err(ShamirError::InsufficientParts { collected: progress.collected, threshold: progress.threshold });
+        }
+        // Joined straight from the session's own copies (indices were
+        // checked by `add`), so no unwiped clone of the shares is left behind.
+        let pairs:Vec<(u8,&[u8])>=self.parts.iter().map(|(x, y)| (*x as u8, y.as_slice())).collect();
+        let secret=ShamirSS::join_pairs(&pairs);
+        self.wipe();
+        secret
+    }
+
+    pub fn wipe(&mut self){
+        for data in self.parts.values_mut() {
+            zeroize(data);
+        }
+        self.parts.clear();
+    }
+}
+
+impl Drop for QuorumSession{
+    fn drop(&mut self){
+        self.wipe();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(secret:&[u8])->QuorumSession{
+        let mut session=QuorumSession::new(2).unwrap();
+        for (index, data) in ShamirSS::split(3, 2, secret.to_vec()).unwrap().into_iter().take(2) {
+            session.add(index, data).unwrap();
+        }
+        session
+    }
+
+    #[test]
+    fn finalize_recovers_and_empties_the_session() {
+        let mut session=session(b"quorum");
+        assert_eq!(session.finalize().unwrap(), b"quorum");
+        assert!(session.indices().is_empty());
+    }
+
+    #[test]
+    fn saved_session_is_owner_only_and_resumes() {
+        let path=std::env::temp_dir().join(format!("shamir-session-{}", std::process::id()));
+        session(b"quorum").save(&path, "passphrase").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+        }
+        let mut resumed=QuorumSession::resume(&path, "passphrase").unwrap();
+        assert!(QuorumSession::resume(&path, "wrong").is_err());
+        fs::remove_file(&path).unwrap();
+        assert_eq!(resumed.finalize().unwrap(), b"quorum");
+    }
+
+    #[test]
+    fn resume_rejects_edited_headers() {
+        let path=std::env::temp_dir().join(format!("shamir-session-header-{}", std::process::id()));
+        session(b"quorum").save(&path, "passphrase").unwrap();
+        let saved=fs::read(&path).unwrap();
+        let edit=|offset:usize, bytes:&[u8]| {
+            let mut file=saved.clone();
+            file[offset..offset+bytes.len()].copy_from_slice(bytes);
+            fs::write(&path, file).unwrap();
+            QuorumSession::resume(&path, "passphrase").unwrap_err()
+        };
+        // A huge count is refused before any key derivation.
+        assert!(edit(4+SALT_LEN, &u32::MAX.to_be_bytes()).contains("PBKDF2 iterations"));
+        assert!(edit(4+SALT_LEN, &0u32.to_be_bytes()).contains("PBKDF2 iterations"));
+        // A different count within bounds, or the old magic, fails.
+        assert!(edit(4+SALT_LEN, &(SESSION_ITERATIONS+1).to_be_bytes()).contains("Wrong passphrase"));
+        assert!(edit(0, b"SHJ1").contains("not a saved join session"));
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,147 @@
+// SHA-256 (FIPS 180-4), HMAC-SHA256 (RFC 2104), HKDF (RFC 5869) and PBKDF2
+// (RFC 8018) behind one small type, used for digests, integrity tags and key
+// derivation. The primitives are the RustCrypto sha2/hmac/hkdf/pbkdf2 crates.
+
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use sha2::Digest;
+
+use crate::error::ShamirError;
+
+// The most HKDF-SHA256 can produce: 255 blocks of 32 bytes.
+pub const HKDF_MAX_OUTPUT:usize=255*32;
+
+#[derive(Debug,Clone,Default)]
+pub struct Sha256{
+    inner:sha2::Sha256,
+}
+
+impl Sha256{
+
+    pub fn new()->Sha256{
+        Sha256::default()
+    }
+
+    pub fn update(&mut self, data:&[u8]){
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self)->[u8;32]{
+        self.inner.finalize().into()
+    }
+
+    pub fn digest(data:&[u8])->[u8;32]{
+        sha2::Sha256::digest(data).into()
+    }
+
+    pub fn hmac(key:&[u8], data:&[u8])->[u8;32]{
+        let mut mac=<Hmac<sha2::Sha256> as Mac>::new_from_slice(key).expect("HMAC takes keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    }
+
+    // HKDF (RFC 5869) extract-and-expand; more than HKDF_MAX_OUTPUT bytes
+    // is KdfOutputTooLong.
+    pub fn hkdf(salt:&[u8], ikm:&[u8], info:&[u8], out_len:usize)->Result<Vec<u8>,ShamirError>{
+        let mut out=vec![0u8;out_len];
+        Hkdf::<sha2::Sha256>::new(Some(salt), ikm).expand(info, &mut out)
+            .map_err(|_| ShamirError::KdfOutputTooLong { requested: out_len, max: HKDF_MAX_OUTPUT })?;
+        Ok(out)
+    }
+
+    // HKDF into a fixed-size key, whose length is checked at compile time.
+    pub fn hkdf_key<const N:usize>(salt:&[u8], ikm:&[u8], info:&[u8])->[u8;N]{
+        const { assert!(N<=HKDF_MAX_OUTPUT, "HKDF output too long") };
+        let mut out=[0u8;N];
+        Hkdf::<sha2::Sha256>::new(Some(salt), ikm).expand(info, &mut out).expect("length checked at compile time");
+        out
+    }
+
+    // PBKDF2-HMAC-SHA256 (RFC 8018) producing a single 32-byte block.
+    pub fn pbkdf2(password:&[u8], salt:&[u8], iterations:u32)->[u8;32]{
+        pbkdf2::pbkdf2_hmac_array::<sha2::Sha256, 32>(password, salt, iterations)
+    }
+}
+
+pub fn to_hex(bytes:&[u8])->String{
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn from_hex(text:&str)->Option<Vec<u8>>{
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i+2)?, 16).ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text:&str)->Vec<u8>{
+        from_hex(text).unwrap()
+    }
+
+    // FIPS 180-4 examples (NIST CSRC "SHA256.pdf" and "SHA2_Additional").
+    #[test]
+    fn sha256_matches_fips_180_4(){
+        assert_eq!(to_hex(&Sha256::digest(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(to_hex(&Sha256::digest(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(to_hex(&Sha256::digest(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1");
+
+        let mut h=Sha256::new();
+        for _ in 0..1000 {
+            h.update(&[b'a';1000]);
+        }
+        assert_eq!(to_hex(&h.finalize()), "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0");
+    }
+
+    // RFC 4231 test cases 1, 2 and 6 (key longer than a block).
+    #[test]
+    fn hmac_matches_rfc_4231(){
+        assert_eq!(to_hex(&Sha256::hmac(&[0x0b;20], b"Hi There")), "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(to_hex(&Sha256::hmac(b"Jefe", b"what do ya want for nothing?")), "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        assert_eq!(to_hex(&Sha256::hmac(&[0xaa;131], b"Test Using Larger Than Block-Size Key - Hash Key First")),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+    }
+
+    // RFC 5869 test cases 1 and 3 (no salt, no info).
+    #[test]
+    fn hkdf_matches_rfc_5869(){
+        let salt:Vec<u8>=(0x00..=0x0c).collect();
+        let info:Vec<u8>=(0xf0..=0xf9).collect();
+        assert_eq!(Sha256::hkdf(&salt, &[0x0b;22], &info, 42).unwrap(),
+            hex("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"));
+        assert_eq!(Sha256::hkdf(&[], &[0x0b;22], &[], 42).unwrap(),
+            hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"));
+        let key:[u8;32]=Sha256::hkdf_key(&[], &[0x0b;22], &[]);
+        assert_eq!(key[..], hex("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d")[..]);
+    }
+
+    #[test]
+    fn hkdf_refuses_output_past_the_limit(){
+        assert_eq!(Sha256::hkdf(&[], b"ikm", &[], HKDF_MAX_OUTPUT).unwrap().len(), HKDF_MAX_OUTPUT);
+        assert_eq!(Sha256::hkdf(&[], b"ikm", &[], HKDF_MAX_OUTPUT+1),
+            Err(ShamirError::KdfOutputTooLong { requested: HKDF_MAX_OUTPUT+1, max: HKDF_MAX_OUTPUT }));
+    }
+
+    // The RFC 6070 inputs, which the RFC only gives for HMAC-SHA1, with the
+    // widely published HMAC-SHA256 outputs, and RFC 7914 section 11.
+    #[test]
+    fn pbkdf2_matches_published_vectors(){
+        assert_eq!(to_hex(&Sha256::pbkdf2(b"password", b"salt", 1)), "120fb6cffcf8b32c43e7225256c4f837a86548c92ccc35480805987cb70be17b");
+        assert_eq!(to_hex(&Sha256::pbkdf2(b"password", b"salt", 2)), "ae4d0c95af6b46d32d0adff928f06dd02a303f8ef3c251dfd6e2d85a95474c43");
+        assert_eq!(to_hex(&Sha256::pbkdf2(b"password", b"salt", 4096)), "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
+        assert_eq!(to_hex(&Sha256::pbkdf2(b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096)),
+            "348c89dbcbd32b2f32d814b8116e84cf2b17347ebc1800181c4e2a1fb8dd53e1");
+        assert_eq!(to_hex(&Sha256::pbkdf2(b"passwd", b"salt", 1)), "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc");
+    }
+
+    #[test]
+    fn hex_round_trips_and_rejects_odd_or_non_hex(){
+        assert_eq!(from_hex(&to_hex(&[0x00, 0xab, 0xff])), Some(vec![0x00, 0xab, 0xff]));
+        assert_eq!(from_hex("abc"), None);
+        assert_eq!(from_hex("zz"), None);
+    }
+}
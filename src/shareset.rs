@@ -0,0 +1,81 @@
+use crate::crypto::{Parts, ShamirSS, GFC256};
+use crate::error::ShamirError;
+
+// A full sharing (one share per custodian) with its threshold. Sharings are
+// additively homomorphic: adding two of them share by share gives a sharing
+// of the sum of their secrets, which in GF(256) is the XOR. Two dealers can
+// each split a random contribution and the custodians combine their shares
+// locally, so no single dealer ever knows the final secret.
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct ShareSet{
+    pub threshold:i32,
+    pub parts:Parts,
+}
+
+impl ShareSet{
+
+    pub fn split(n:i32,k:i32,secret:Vec<u8>)->Result<ShareSet,ShamirError>{
+        Ok(ShareSet { threshold: k, parts: ShamirSS::split(n, k, secret)? })
+    }
+
+    // Both sets must cover the same x-coordinates with equally long shares.
+    // The sum polynomial has the larger of the two degrees, so the result
+    // keeps the larger threshold.
+    pub fn add(&self,other:&ShareSet)->Result<ShareSet,ShamirError>{
+        let unmatched:Vec<i32>=self.parts.keys().filter(|x| !other.parts.contains_key(x))
+            .chain(other.parts.keys().filter(|x| !self.parts.contains_key(x)))
+            .copied().collect();
+        if !unmatched.is_empty() {
+            return Err(ShamirError::InconsistentShares(unmatched))
+        }
+        let mut parts=Parts::new();
+        for (x, a) in &self.parts {
+            let b=&other.parts[x];
+            if a.len()!=b.len() {
+                return Err(ShamirError::InconsistentLengths)
+            }
+            parts.insert(*x, a.iter().zip(b).map(|(&a, &b)| GFC256::add(a, b)).collect());
+        }
+        Ok(ShareSet { threshold: self.threshold.max(other.threshold), parts })
+    }
+
+    pub fn join(&self)->Result<Vec<u8>,ShamirError>{
+        ShamirSS::join(self.parts.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sum_of_two_dealers_joins_to_the_xor() {
+        let alice=ShareSet::split(5, 3, b"alice's part".to_vec()).unwrap();
+        let bob=ShareSet::split(5, 2, b"bob's secret".to_vec()).unwrap();
+        let sum=alice.add(&bob).unwrap();
+        assert_eq!(sum.threshold, 3);
+        let xor:Vec<u8>=b"alice's part".iter().zip(b"bob's secret").map(|(a, b)| a^b).collect();
+        assert_eq!(sum.join().unwrap(), xor);
+
+        let three:Parts=sum.parts.iter().skip(2).map(|(x, data)| (*x, data.clone())).collect();
+        assert_eq!(ShamirSS::join(three).unwrap(), xor);
+        assert_eq!(sum.add(&bob).unwrap().join().unwrap(), b"alice's part");
+    }
+
+    #[test]
+    fn mismatched_sharings_do_not_add() {
+        let five=ShareSet::split(5, 3, b"secret".to_vec()).unwrap();
+        let four=ShareSet::split(4, 3, b"secret".to_vec()).unwrap();
+        assert_eq!(five.add(&four), Err(ShamirError::InconsistentShares(vec![5])));
+        assert_eq!(four.add(&five), Err(ShamirError::InconsistentShares(vec![5])));
+
+        let mut shifted=four.clone();
+        let data=shifted.parts.remove(&4).unwrap();
+        shifted.parts.insert(6, data);
+        assert_eq!(four.add(&shifted), Err(ShamirError::InconsistentShares(vec![4, 6])));
+
+        let longer=ShareSet::split(5, 3, b"longer secret".to_vec()).unwrap();
+        assert_eq!(five.add(&longer), Err(ShamirError::InconsistentLengths));
+    }
+}
@@ -0,0 +1,87 @@
+use crate::crypto::{zeroize, Parts, ShamirSS};
+use crate::error::ShamirError;
+use crate::transform::Transform;
+
+// Trimming trailing zeros before a split, as a `transform` step (id 1) so
+// the compact share header records it and a join undoes it. Shares look
+// random whatever the secret, so a padded key blob costs its full padded
+// length in every share; `TrimZeros` splits a 4-byte big-endian length
+// followed by the secret with its trailing zero bytes removed, and the join
+// pads it back to the exact original length. The length prefix is shared
+// with the secret, so shares still reveal only the trimmed size.
+
+pub struct TrimZeros;
+
+const LENGTH_PREFIX:usize=4;
+
+impl Transform for TrimZeros{
+    fn id(&self)->u8{ 1 }
+    fn name(&self)->&str{ "trim-zeros" }
+
+    fn apply(&self,secret:&[u8])->Result<Vec<u8>,String>{
+        let len=u32::try_from(secret.len()).map_err(|_| format!("Secret of {} bytes is too large to trim", secret.len()))?;
+        let kept=secret.iter().rposition(|&b| b!=0).map_or(0, |last| last+1);
+        let mut out=Vec::with_capacity(LENGTH_PREFIX+kept);
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(&secret[..kept]);
+        Ok(out)
+    }
+
+    fn restore(&self,mut data:Vec<u8>)->Result<Vec<u8>,String>{
+        let len=data.get(..LENGTH_PREFIX).map(|prefix| u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize);
+        let len=match len {
+            Some(len) if data.len()-LENGTH_PREFIX<=len => len,
+            _ => {
+                zeroize(&mut data);
+                return Err("Trimmed secret has a malformed length prefix".to_string());
+            }
+        };
+        data.drain(..LENGTH_PREFIX);
+        if data.try_reserve_exact(len-data.len()).is_err() {
+            zeroize(&mut data);
+            return Err(format!("Cannot restore a secret of {len} bytes"));
+        }
+        data.resize(len, 0);
+        Ok(data)
+    }
+}
+
+impl ShamirSS{
+
+    // `split` of the secret with its trailing zeros trimmed. Keep the
+    // transform with the shares (compact encoding) or call `join_sparse`.
+    pub fn split_sparse(n:i32,k:i32,secret:&[u8])->Result<Parts,ShamirError>{
+        let trimmed=TrimZeros.apply(secret).map_err(|_| ShamirError::SecretTooLarge { len: secret.len(), max: u32::MAX as usize })?;
+        Self::split(n, k, trimmed)
+    }
+
+    pub fn join_sparse(parts:Parts)->Result<Vec<u8>,String>{
+        TrimZeros.restore(Self::join(parts)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trailing_zeros_are_trimmed_and_restored() {
+        let mut secret=b"key".to_vec();
+        secret.resize(64, 0);
+        assert_eq!(TrimZeros.apply(&secret).unwrap(), [&[0, 0, 0, 64][..], b"key"].concat());
+        let parts=ShamirSS::split_sparse(3, 2, &secret).unwrap();
+        assert!(parts.values().all(|data| data.len()==7));
+        assert_eq!(ShamirSS::join_sparse(parts).unwrap(), secret);
+
+        for secret in [Vec::new(), vec![0; 5], b"no zeros".to_vec()] {
+            assert_eq!(TrimZeros.restore(TrimZeros.apply(&secret).unwrap()).unwrap(), secret);
+        }
+    }
+
+    #[test]
+    fn malformed_length_prefix_is_rejected() {
+        assert!(TrimZeros.restore(vec![0, 0]).is_err());
+        // Claims 2 bytes but carries 3.
+        assert!(TrimZeros.restore(vec![0, 0, 0, 2, 1, 2, 3]).is_err());
+    }
+}
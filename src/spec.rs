@@ -0,0 +1,148 @@
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::encoding::encode_share;
+use crate::error::ShamirError;
+
+// Bit-exact behaviour of `split_deterministic`, written down so other
+// implementations can reproduce it from the constants below:
+//
+// - Field: GF(2^8) with reduction polynomial FIELD_POLYNOMIAL, exp/log tables
+//   built from GENERATOR.
+// - RNG: ChaCha20 (20 rounds) keyed with the 32-byte seed, all-zero nonce,
+//   block counter starting at 0; 32-bit words are read little-endian from the
+//   keystream in order.
+// - Coefficients: for each secret byte, in order of position, draw k words and
+//   keep the low byte of each as c0..c(k-1). While c(k-1) == 0 redraw all k.
+//   Then replace c0 with the secret byte.
+// - Indices: share i (1-based) is the polynomial evaluated at x = i.
+// - Text form: "<index>:<base64 standard, padded>\n".
+
+pub const SPEC_VERSION:&str="shamir-rust-spec-1";
+pub const FIELD_POLYNOMIAL:u16=0x11b;
+pub const GENERATOR:u8=0x03;
+pub const RNG:&str="chacha20-keystream-nonce0-le-u32";
+pub const COEFFICIENT_BYTE:&str="low-byte-of-u32";
+pub const REJECT_ZERO_LEADING_COEFFICIENT:bool=true;
+pub const FIRST_INDEX:i32=1;
+
+pub struct Vector{
+    pub seed:[u8;32],
+    pub n:i32,
+    pub k:i32,
+    pub secret:&'static [u8],
+    pub shares:&'static [&'static str],
+}
+
+pub fn split_deterministic(n:i32,k:i32,secret:Vec<u8>,seed:[u8;32])->Result<Parts,ShamirError>{
+    ShamirSS::split_with_rng(n, k, secret, &mut ChaCha20Rng::from_seed(seed))
+}
+
+// Published vectors; any conforming implementation must reproduce them.
+pub const VECTORS:&[Vector]=&[
+    Vector {
+        seed: [0u8;32],
+        n: 3,
+        k: 2,
+        secret: b"spec",
+        shares: &["1:0yPF6A==\n", "2:KNY+bg==\n", "3:iIWe5Q==\n"],
+    },
+    Vector {
+        seed: [0x5a;32],
+        n: 5,
+        k: 3,
+        secret: b"Hello Shamir",
+        shares: &[
+            "1:J7sOnL01XOaPxNhD\n",
+            "2:rmoNIIn0pADc/5Od\n",
+            "3:wbRv0Fvhq44yViKs\n",
+            "4:f+0MHsdGLx/zE7y1\n",
+            "5:EDNu7hVTIJEdug2E\n",
+        ],
+    },
+];
+
+pub fn check_conformance()->Result<usize,String>{
+    for (number, vector) in VECTORS.iter().enumerate() {
+        let parts=split_deterministic(vector.n, vector.k, vector.secret.to_vec(), vector.seed)?;
+        let got:Vec<String>=parts.iter().map(|(index, data)| encode_share(*index, data)).collect();
+        if got!=vector.shares {
+            return Err(format!("Vector {number} differs: expected {:?}, got {got:?}", vector.shares));
+        }
+        if ShamirSS::join(parts)?!=vector.secret {
+            return Err(format!("Vector {number} does not join back to its secret"));
+        }
+    }
+    Ok(VECTORS.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::GFC256;
+    use crate::encoding::decode_share;
+
+    // Carry-less multiplication reduced by FIELD_POLYNOMIAL, bit by bit.
+    fn reference_mul(mut a:u8,mut b:u8)->u8{
+        let mut product=0u8;
+        while b!=0 {
+            if b & 1==1 {
+                product^=a;
+            }
+            let carry=a & 0x80!=0;
+            a<<=1;
+            if carry {
+                a^=(FIELD_POLYNOMIAL & 0xff) as u8;
+            }
+            b>>=1;
+        }
+        product
+    }
+
+    #[test]
+    fn published_vectors_conform() {
+        assert_eq!(check_conformance().unwrap(), VECTORS.len());
+    }
+
+    #[test]
+    fn field_matches_the_pinned_polynomial_and_generator() {
+        for a in 0..=255u8 {
+            for b in 0..=255u8 {
+                assert_eq!(GFC256::mul(a, b), reference_mul(a, b), "{a} * {b}");
+            }
+        }
+        let mut seen=std::collections::HashSet::new();
+        let mut power=1u8;
+        for _ in 0..255 {
+            assert!(seen.insert(power), "GENERATOR has order below 255");
+            power=GFC256::mul(power, GENERATOR);
+        }
+        assert_eq!(power, 1);
+    }
+
+    #[test]
+    fn every_threshold_subset_of_a_vector_joins() {
+        for vector in VECTORS {
+            let shares:Vec<(i32,Vec<u8>)>=vector.shares.iter().map(|text| decode_share(text).unwrap()).collect();
+            assert_eq!(shares.first().unwrap().0, FIRST_INDEX);
+            let n=shares.len();
+            for mask in 0u32..1<<n {
+                if mask.count_ones() as i32!=vector.k {
+                    continue;
+                }
+                let subset:Parts=(0..n).filter(|i| mask & 1<<i!=0).map(|i| shares[i].clone()).collect();
+                assert_eq!(ShamirSS::join(subset).unwrap(), vector.secret);
+            }
+        }
+    }
+
+    #[test]
+    fn output_depends_only_on_the_seed() {
+        let vector=&VECTORS[1];
+        let again=split_deterministic(vector.n, vector.k, vector.secret.to_vec(), vector.seed).unwrap();
+        assert_eq!(again, split_deterministic(vector.n, vector.k, vector.secret.to_vec(), vector.seed).unwrap());
+        let mut seed=vector.seed;
+        seed[0]^=1;
+        assert_ne!(again, split_deterministic(vector.n, vector.k, vector.secret.to_vec(), seed).unwrap());
+    }
+}
@@ -0,0 +1,187 @@
+use rand::rngs::OsRng;
+use rand::RngCore;
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::error::ShamirError;
+
+// Statistical self-test: for a fixed secret, every share byte should be
+// uniform over GF(256). Splits run with a seeded ChaCha20Rng so failures are
+// reproducible.
+
+// Chi-squared critical value for 255 degrees of freedom at p = 0.001.
+pub const CHI_SQUARED_CRITICAL:f64=330.52;
+
+#[derive(Debug,Clone)]
+pub struct ShareStatistics{
+    pub index:i32,
+    pub chi_squared:f64,
+    pub entropy_bits:f64,
+}
+
+#[derive(Debug,Clone)]
+pub struct StatisticsReport{
+    pub samples_per_share:usize,
+    pub shares:Vec<ShareStatistics>,
+    // Fraction of neighbouring byte positions holding equal values; ~1/256
+    // unless coefficients leak across positions.
+    pub repeat_rate:f64,
+    pub repeat_limit:f64,
+}
+
+impl StatisticsReport{
+    pub fn passed(&self)->bool{
+        self.shares.iter().all(|s| s.chi_squared<CHI_SQUARED_CRITICAL) && self.repeat_rate<=self.repeat_limit
+    }
+}
+
+pub fn share_statistics(n:i32,k:i32,secret:&[u8],trials:usize,seed:u64)->Result<StatisticsReport,ShamirError>{
+    // Checked before the per-share histograms are allocated, which for an
+    // unchecked n would be up to 2^31 of them.
+    if k <= 1 {
+        return Err(ShamirError::ThresholdTooSmall)
+    }
+    if n < k {
+        return Err(ShamirError::ThresholdAboveShares)
+    }
+    if n > 255 {
+        return Err(ShamirError::TooManyShares)
+    }
+    let mut rng=ChaCha20Rng::seed_from_u64(seed);
+    let mut histograms=vec![[0u64;256];n.max(0) as usize];
+    let mut repeats=0u64;
+    let mut neighbours=0u64;
+
+    for _ in 0..trials {
+        let parts=ShamirSS::split_with_rng(n, k, secret.to_vec(), &mut rng)?;
+        for (index, data) in &parts {
+            let histogram=&mut histograms[*index as usize-1];
+            for byte in data {
+                histogram[*byte as usize]+=1;
+            }
+            for pair in data.windows(2) {
+                neighbours+=1;
+                if pair[0]==pair[1] {
+                    repeats+=1;
+                }
+            }
+        }
+    }
+
+    let samples=trials*secret.len();
+    let shares=histograms.iter().enumerate().map(|(i, histogram)| ShareStatistics {
+        index: i as i32+1,
+        chi_squared: chi_squared(histogram, samples),
+        entropy_bits: entropy(histogram, samples),
+    }).collect();
+
+    let p=1.0/256.0;
+    let repeat_rate=if neighbours==0 { 0.0 } else { repeats as f64/neighbours as f64 };
+    let repeat_limit=p+6.0*(p*(1.0-p)/neighbours.max(1) as f64).sqrt();
+
+    Ok(StatisticsReport { samples_per_share: samples, shares, repeat_rate, repeat_limit })
+}
+
+// Post-split check of the shares a ceremony actually produced, for auditors
+// who want evidence of healthy entropy on the day. One split is a small
+// sample, so this catches a broken generator (stuck, repeating, correlated
+// output), not subtle bias; `share_statistics` is the thorough test.
+#[derive(Debug,Clone)]
+pub struct EntropyReport{
+    pub bytes:usize,
+    // None when there are fewer than 5 bytes per histogram bin, too few for
+    // the test to mean anything.
+    pub chi_squared:Option<f64>,
+    pub entropy_bits:f64,
+    // Correlation of each share byte with the next byte of the same share;
+    // ~0 for independent bytes.
+    pub serial_correlation:f64,
+    pub serial_limit:f64,
+    // The OS random source answered a fresh request just now.
+    pub os_random:bool,
+}
+
+impl EntropyReport{
+    pub fn passed(&self)->bool{
+        self.chi_squared.is_none_or(|chi| chi<CHI_SQUARED_CRITICAL) && self.serial_correlation.abs()<=self.serial_limit && self.os_random
+    }
+}
+
+pub fn assess_shares(parts:&Parts)->EntropyReport{
+    let mut histogram=[0u64;256];
+    let mut pairs=Vec::new();
+    for data in parts.values() {
+        for byte in data {
+            histogram[*byte as usize]+=1;
+        }
+        pairs.extend(data.windows(2).map(|pair| (pair[0] as f64, pair[1] as f64)));
+    }
+    let bytes=histogram.iter().sum::<u64>() as usize;
+    let mut probe=[0u8;32];
+    let os_random=OsRng.try_fill_bytes(&mut probe).is_ok() && probe.iter().any(|&b| b!=0);
+    EntropyReport {
+        bytes,
+        chi_squared: (bytes>=5*256).then(|| chi_squared(&histogram, bytes)),
+        entropy_bits: entropy(&histogram, bytes),
+        serial_correlation: correlation(&pairs),
+        // About four standard deviations of the coefficient for independent bytes.
+        serial_limit: if pairs.is_empty() { 1.0 } else { 4.0/(pairs.len() as f64).sqrt() },
+        os_random,
+    }
+}
+
+fn correlation(pairs:&[(f64,f64)])->f64{
+    let n=pairs.len() as f64;
+    if pairs.len()<2 {
+        return 0.0;
+    }
+    let (mean_a, mean_b)=(pairs.iter().map(|p| p.0).sum::<f64>()/n, pairs.iter().map(|p| p.1).sum::<f64>()/n);
+    let covariance:f64=pairs.iter().map(|(a, b)| (a-mean_a)*(b-mean_b)).sum();
+    let var_a:f64=pairs.iter().map(|(a, _)| (a-mean_a)*(a-mean_a)).sum();
+    let var_b:f64=pairs.iter().map(|(_, b)| (b-mean_b)*(b-mean_b)).sum();
+    if var_a==0.0 || var_b==0.0 {
+        // Constant bytes: as correlated as it gets.
+        return 1.0;
+    }
+    covariance/(var_a*var_b).sqrt()
+}
+
+fn chi_squared(histogram:&[u64;256],samples:usize)->f64{
+    let expected=samples as f64/256.0;
+    if expected==0.0 {
+        return 0.0;
+    }
+    histogram.iter().map(|&count| {
+        let d=count as f64-expected;
+        d*d/expected
+    }).sum()
+}
+
+fn entropy(histogram:&[u64;256],samples:usize)->f64{
+    if samples==0 {
+        return 0.0;
+    }
+    histogram.iter().filter(|&&c| c>0).map(|&count| {
+        let p=count as f64/samples as f64;
+        -p*p.log2()
+    }).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameters_are_checked_before_allocating() {
+        assert!(matches!(share_statistics(2_000_000_000, 3, b"x", 1, 0), Err(ShamirError::TooManyShares)));
+        assert!(matches!(share_statistics(3, 1, b"x", 1, 0), Err(ShamirError::ThresholdTooSmall)));
+        assert!(matches!(share_statistics(2, 3, b"x", 1, 0), Err(ShamirError::ThresholdAboveShares)));
+    }
+
+    #[test]
+    fn small_run_reports_every_share() {
+        let report=share_statistics(5, 3, b"fixed secret", 4, 7).unwrap();
+        assert_eq!(report.shares.len(), 5);
+        assert_eq!(report.samples_per_share, 48);
+    }
+}
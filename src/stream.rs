@@ -0,0 +1,420 @@
+use std::collections::{HashSet, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::{Condvar, Mutex};
+use std::thread;
+
+use rand::RngCore;
+
+use crate::crypto::{zeroize, GFC256};
+use crate::error::ShamirError;
+
+// Streaming split and join for inputs too large to hold in memory, such as
+// `tar c dir | shamir split-stream`. Every byte is split on its own as in
+// `ShamirSS`, so share streams are as long as the input plus a header line:
+//   shamir-stream v1 index=<x> threshold=<k>
+//
+// Each share writer runs on its own thread behind a queue of chunks. Memory
+// stays bounded by backpressure: once any queue holds more than the high
+// watermark, reading stops until that queue drains to the low watermark, so
+// a slow writer (a share on a network mount) slows the whole split down
+// instead of letting the other queues grow. Peak buffering is about
+// high watermark + chunk size per share.
+
+const MAGIC:&str="shamir-stream v1";
+const MAX_HEADER_LEN:usize=128;
+
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub struct StreamOptions{
+    pub chunk_size:usize,
+    pub high_watermark:usize,
+    pub low_watermark:usize,
+}
+
+impl Default for StreamOptions{
+    fn default()->StreamOptions{
+        StreamOptions { chunk_size: 64*1024, high_watermark: 4*1024*1024, low_watermark: 1024*1024 }
+    }
+}
+
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct StreamStats{
+    // Input bytes split.
+    pub bytes:u64,
+    // Times reading paused for a writer to catch up.
+    pub stalls:u64,
+    // Most bytes queued for any one writer at a time.
+    pub peak_buffered:usize,
+}
+
+pub struct StreamSplitter{
+    k:usize,
+    xs:Vec<u8>,
+    options:StreamOptions,
+}
+
+impl StreamSplitter{
+
+    // One share stream per x-coordinate. A zero chunk size is
+    // InvalidChunkSize; the high watermark is raised to at least one chunk
+    // and the low one lowered to at most the high one.
+    pub fn new(k:i32,xs:&[u8],options:StreamOptions)->Result<StreamSplitter,ShamirError>{
+        if k <= 1 {
+            return Err(ShamirError::ThresholdTooSmall)
+        }
+        if (xs.len() as i32) < k {
+            return Err(ShamirError::ThresholdAboveShares)
+        }
+        let mut seen=HashSet::new();
+        for &x in xs {
+            if x == 0 {
+                return Err(ShamirError::InvalidIndex(0))
+            }
+            if !seen.insert(x) {
+                return Err(ShamirError::DuplicateIndex(x as i32))
+            }
+        }
+        if options.chunk_size==0 {
+            return Err(ShamirError::InvalidChunkSize)
+        }
+        let high_watermark=options.high_watermark.max(options.chunk_size);
+        let options=StreamOptions { high_watermark, low_watermark: options.low_watermark.min(high_watermark), ..options };
+        Ok(StreamSplitter { k: k as usize, xs: xs.to_vec(), options })
+    }
+
+    pub fn split<R:Read,W:Write+Send>(&self,reader:R,writers:Vec<W>)->Result<StreamStats,String>{
+        self.split_with_rng(reader, writers, &mut rand::thread_rng())
+    }
+
+    // `writers[i]` receives the share stream at `xs[i]`. An empty input gives
+    // header-only streams.
+    pub fn split_with_rng<R:Read,W:Write+Send,G:RngCore+?Sized>(&self,mut reader:R,writers:Vec<W>,rng:&mut G)->Result<StreamStats,String>{
+        if writers.len()!=self.xs.len() {
+            return Err(format!("{} share writers for {} shares", writers.len(), self.xs.len()));
+        }
+        let queues:Vec<Queue>=self.xs.iter().map(|_| Queue::default()).collect();
+        thread::scope(|scope| {
+            let handles:Vec<_>=queues.iter().zip(writers).map(|(queue, writer)| scope.spawn(move || queue.drain(writer))).collect();
+            let produced=self.produce(&mut reader, &queues, rng);
+            for queue in &queues {
+                queue.close();
+            }
+            let mut failure=None;
+            for (handle, &x) in handles.into_iter().zip(&self.xs) {
+                if let Err(e)=handle.join().unwrap_or_else(|_| Err("writer thread panicked".to_string())) {
+                    failure.get_or_insert(format!("share {x}: {e}"));
+                }
+            }
+            // A writer's own error says more than the reader's "a share
+            // writer failed".
+            match (produced, failure) {
+                (_, Some(e)) | (Err(e), None) => Err(e),
+                (Ok(stats), None) => Ok(stats),
+            }
+        })
+    }
+
+    fn produce<R:Read,G:RngCore+?Sized>(&self,reader:&mut R,queues:&[Queue],rng:&mut G)->Result<StreamStats,String>{
+        let mut stats=StreamStats::default();
+        for (queue, &x) in queues.iter().zip(&self.xs) {
+            queue.push(stream_header(x, self.k).into_bytes(), &self.options, &mut stats)?;
+        }
+        let mut input=vec![0u8;self.options.chunk_size];
+        let mut coefficients=vec![0u8;self.k];
+        let result=loop {
+            let len=match fill(reader, &mut input) {
+                Ok(0) => break Ok(stats),
+                Ok(len) => len,
+                Err(e) => break Err(e.to_string()),
+            };
+            let mut chunks=vec![vec![0u8;len];self.xs.len()];
+            for (i, &byte) in input[..len].iter().enumerate() {
+                GFC256::generate_into(&mut coefficients, byte, rng);
+                for (chunk, &x) in chunks.iter_mut().zip(&self.xs) {
+                    chunk[i]=GFC256::eval_slice(&coefficients, x);
+                }
+            }
+            stats.bytes+=len as u64;
+            if let Err(e)=chunks.into_iter().zip(queues).try_for_each(|(chunk, queue)| queue.push(chunk, &self.options, &mut stats)) {
+                break Err(e);
+            }
+        };
+        zeroize(&mut input);
+        zeroize(&mut coefficients);
+        result
+    }
+}
+
+// Chunks waiting for one share writer.
+#[derive(Default)]
+struct Queue{
+    state:Mutex<QueueState>,
+    changed:Condvar,
+}
+
+#[derive(Default)]
+struct QueueState{
+    chunks:VecDeque<Vec<u8>>,
+    buffered:usize,
+    // No more chunks will come.
+    closed:bool,
+    // The writer failed and has stopped.
+    failed:bool,
+}
+
+impl Queue{
+
+    // Queues a chunk, then waits for the writer if the queue is above the
+    // high watermark. Fails once the writer has.
+    fn push(&self,chunk:Vec<u8>,options:&StreamOptions,stats:&mut StreamStats)->Result<(),String>{
+        let mut state=self.state.lock().map_err(|_| "share queue poisoned".to_string())?;
+        if state.failed {
+            return Err("a share writer failed".to_string());
+        }
+        state.buffered+=chunk.len();
+        state.chunks.push_back(chunk);
+        stats.peak_buffered=stats.peak_buffered.max(state.buffered);
+        self.changed.notify_all();
+        if state.buffered>options.high_watermark {
+            stats.stalls+=1;
+            state=self.changed.wait_while(state, |s| s.buffered>options.low_watermark && !s.failed).map_err(|_| "share queue poisoned".to_string())?;
+            if state.failed {
+                return Err("a share writer failed".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    fn close(&self){
+        if let Ok(mut state)=self.state.lock() {
+            state.closed=true;
+        }
+        self.changed.notify_all();
+    }
+
+    // Writer thread: writes chunks in order until the queue is closed and
+    // empty, then flushes.
+    fn drain<W:Write>(&self,mut writer:W)->Result<(),String>{
+        let result=self.write_all(&mut writer).and_then(|()| writer.flush().map_err(|e| e.to_string()));
+        if result.is_err() {
+            if let Ok(mut state)=self.state.lock() {
+                state.failed=true;
+                state.chunks.clear();
+                state.buffered=0;
+            }
+            self.changed.notify_all();
+        }
+        result
+    }
+
+    fn write_all<W:Write>(&self,writer:&mut W)->Result<(),String>{
+        loop {
+            let chunk={
+                let state=self.state.lock().map_err(|_| "share queue poisoned".to_string())?;
+                let mut state=self.changed.wait_while(state, |s| s.chunks.is_empty() && !s.closed).map_err(|_| "share queue poisoned".to_string())?;
+                match state.chunks.pop_front() {
+                    Some(chunk) => chunk,
+                    None => return Ok(()),
+                }
+            };
+            writer.write_all(&chunk).map_err(|e| e.to_string())?;
+            if let Ok(mut state)=self.state.lock() {
+                state.buffered-=chunk.len();
+            }
+            self.changed.notify_all();
+        }
+    }
+}
+
+// Joins share streams read in lockstep, a chunk at a time, into `writer`.
+// Needs at least as many streams as the threshold in their headers; returns
+// the number of bytes written.
+pub fn join_streams<R:Read,W:Write>(readers:Vec<R>,mut writer:W,chunk_size:usize)->Result<u64,String>{
+    if readers.is_empty() {
+        return Err(ShamirError::NoParts.to_string());
+    }
+    if chunk_size==0 {
+        return Err(ShamirError::InvalidChunkSize.to_string());
+    }
+    let mut readers=readers;
+    let mut xs=Vec::with_capacity(readers.len());
+    let mut threshold=None;
+    for reader in readers.iter_mut() {
+        let (x, k)=read_header(reader)?;
+        if xs.contains(&x) {
+            return Err(ShamirError::DuplicateIndex(x as i32).to_string());
+        }
+        if threshold.is_some_and(|t| t!=k) {
+            return Err("Share streams disagree on the threshold".to_string());
+        }
+        threshold=Some(k);
+        xs.push(x);
+    }
+    let k=threshold.unwrap_or(0);
+    if xs.len()<k {
+        return Err(ShamirError::InsufficientParts { collected: xs.len(), threshold: k }.to_string());
+    }
+    let weights=GFC256::barycentric_weights(&xs);
+    let mut buffers=vec![vec![0u8;chunk_size];readers.len()];
+    let mut secret=vec![0u8;chunk_size];
+    let mut total=0u64;
+    loop {
+        let mut len=None;
+        for (reader, buffer) in readers.iter_mut().zip(buffers.iter_mut()) {
+            let got=fill(reader, buffer).map_err(|e| e.to_string())?;
+            if len.is_some_and(|l| l!=got) {
+                return Err(ShamirError::InconsistentLengths.to_string());
+            }
+            len=Some(got);
+        }
+        let len=len.unwrap_or(0);
+        if len==0 {
+            break;
+        }
+        for (pos, byte) in secret[..len].iter_mut().enumerate() {
+            *byte=buffers.iter().zip(&weights).fold(0u8, |acc, (y, &w)| GFC256::add(acc, GFC256::mul(w, y[pos])));
+        }
+        if let Err(e)=writer.write_all(&secret[..len]) {
+            zeroize(&mut secret);
+            return Err(e.to_string());
+        }
+        total+=len as u64;
+    }
+    zeroize(&mut secret);
+    writer.flush().map_err(|e| e.to_string())?;
+    Ok(total)
+}
+
+pub(crate) fn stream_header(x:u8,k:usize)->String{
+    format!("{MAGIC} index={x} threshold={k}\n")
+}
+
+// Reads the header line a byte at a time, so nothing after it is consumed.
+fn read_header<R:Read>(reader:&mut R)->Result<(u8,usize),String>{
+    let mut line=Vec::new();
+    let mut byte=[0u8;1];
+    loop {
+        if reader.read(&mut byte).map_err(|e| e.to_string())?==0 || line.len()>MAX_HEADER_LEN {
+            return Err("Not a share stream".to_string());
+        }
+        if byte[0]==b'\n' {
+            break;
+        }
+        line.push(byte[0]);
+    }
+    let line=String::from_utf8(line).map_err(|_| "Not a share stream".to_string())?;
+    let fields=line.strip_prefix(MAGIC).ok_or("Not a share stream")?;
+    let mut index=None;
+    let mut threshold=None;
+    for field in fields.split_whitespace() {
+        match field.split_once('=') {
+            Some(("index", v)) => index=v.parse::<u8>().ok().filter(|&x| x!=0),
+            Some(("threshold", v)) => threshold=v.parse::<usize>().ok().filter(|&k| k>1),
+            _ => {}
+        }
+    }
+    match (index, threshold) {
+        (Some(x), Some(k)) => Ok((x, k)),
+        _ => Err("Malformed share stream header".to_string()),
+    }
+}
+
+// Reads until `buffer` is full or the input ends; the byte count read.
+pub(crate) fn fill<R:Read>(reader:&mut R,buffer:&mut [u8])->io::Result<usize>{
+    let mut len=0;
+    while len<buffer.len() {
+        match reader.read(&mut buffer[len..]) {
+            Ok(0) => break,
+            Ok(got) => len+=got,
+            Err(e) if e.kind()==io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    struct Slow(Vec<u8>);
+
+    impl Write for Slow{
+        fn write(&mut self,buf:&[u8])->io::Result<usize>{
+            thread::sleep(Duration::from_millis(1));
+            self.0.write(buf)
+        }
+
+        fn flush(&mut self)->io::Result<()>{
+            Ok(())
+        }
+    }
+
+    struct Broken;
+
+    impl Write for Broken{
+        fn write(&mut self,_buf:&[u8])->io::Result<usize>{
+            Err(io::Error::other("disk full"))
+        }
+
+        fn flush(&mut self)->io::Result<()>{
+            Ok(())
+        }
+    }
+
+    fn input()->Vec<u8>{
+        (0..10_000u32).map(|i| (i*7+3) as u8).collect()
+    }
+
+    fn split(k:i32,xs:&[u8],options:StreamOptions)->Vec<Vec<u8>>{
+        let mut streams=vec![Vec::new();xs.len()];
+        StreamSplitter::new(k, xs, options).unwrap().split(input().as_slice(), streams.iter_mut().collect()).unwrap();
+        streams
+    }
+
+    fn join(streams:&[Vec<u8>])->Result<Vec<u8>,String>{
+        let mut out=Vec::new();
+        join_streams(streams.iter().map(|s| s.as_slice()).collect(), &mut out, 1000)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn any_k_streams_join() {
+        let streams=split(3, &[1, 2, 5, 9], StreamOptions { chunk_size: 777, ..StreamOptions::default() });
+        assert!(streams[2].starts_with(b"shamir-stream v1 index=5 threshold=3\n"));
+        assert_eq!(join(&streams[1..]).unwrap(), input());
+        assert_eq!(join(&[streams[3].clone(), streams[0].clone(), streams[2].clone()]).unwrap(), input());
+        assert!(join(&streams[..2]).is_err());
+        assert!(join(&[streams[0].clone(), streams[0].clone(), streams[1].clone()]).is_err());
+
+        let mut short=streams[1].clone();
+        short.pop();
+        assert!(join(&[streams[0].clone(), short, streams[2].clone()]).is_err());
+        assert!(join(&[b"not a stream\n".to_vec()]).is_err());
+    }
+
+    #[test]
+    fn slow_writer_bounds_buffering() {
+        let options=StreamOptions { chunk_size: 500, high_watermark: 1000, low_watermark: 500 };
+        let splitter=StreamSplitter::new(2, &[1, 2], options).unwrap();
+        let mut fast=Vec::new();
+        let mut slow=Slow(Vec::new());
+        let writers:Vec<Box<dyn Write+Send>>=vec![Box::new(&mut fast), Box::new(&mut slow)];
+        let stats=splitter.split(input().as_slice(), writers).unwrap();
+        assert_eq!(stats.bytes, 10_000);
+        assert!(stats.stalls>0);
+        assert!(stats.peak_buffered<=options.high_watermark+options.chunk_size, "{stats:?}");
+        assert_eq!(join(&[fast, slow.0]).unwrap(), input());
+    }
+
+    #[test]
+    fn failing_writer_stops_the_split() {
+        let splitter=StreamSplitter::new(2, &[1, 2], StreamOptions { chunk_size: 100, high_watermark: 100, low_watermark: 0 }).unwrap();
+        let writers:Vec<Box<dyn Write+Send>>=vec![Box::new(Vec::new()), Box::new(Broken)];
+        assert_eq!(splitter.split(input().as_slice(), writers).unwrap_err(), "share 2: disk full");
+        assert!(StreamSplitter::new(2, &[1, 1], StreamOptions::default()).is_err());
+        assert!(StreamSplitter::new(3, &[1, 2], StreamOptions::default()).is_err());
+        assert!(StreamSplitter::new(2, &[0, 1], StreamOptions::default()).is_err());
+        assert!(StreamSplitter::new(2, &[1, 2], StreamOptions { chunk_size: 0, ..StreamOptions::default() }).is_err());
+    }
+}
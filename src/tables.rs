@@ -0,0 +1,109 @@
+// GF(256) EXP/LOG tables, generated at compile time from the field's
+// reduction polynomial and generator instead of being transcribed by hand.
+//
+// EXP[i] = GENERATOR^i for i in 0..510 (the period is 255; the doubled length
+// lets `mul` index EXP[LOG[a] + LOG[b]] without a modulo). LOG is its inverse
+// on non-zero elements; LOG[0] = 0xff is a sentinel, since 0 has no
+// logarithm and callers check for zero first.
+//
+// `exp_log_tables` accepts any reduction polynomial and generator, for
+// interoperating with implementations over a different representation of
+// GF(256). The compile-time check below compares every product from the
+// default tables with a slow shift-and-reduce multiply, so a bad table fails
+// the build.
+
+pub const POLYNOMIAL:u16=0x11b;
+pub const GENERATOR:u8=3;
+
+pub static EXP:[u8;510]=TABLES.0;
+pub static LOG:[u8;256]=TABLES.1;
+
+const TABLES:([u8;510],[u8;256])=exp_log_tables(POLYNOMIAL, GENERATOR);
+
+// Carry-less multiply reduced by `polynomial`, one bit at a time.
+pub const fn mul_slow(a:u8,b:u8,polynomial:u16)->u8{
+    let mut a=a as u16;
+    let mut b=b;
+    let mut product:u16=0;
+    while b!=0 {
+        if b&1==1 {
+            product^=a;
+        }
+        a<<=1;
+        if a&0x100!=0 {
+            a^=polynomial;
+        }
+        b>>=1;
+    }
+    product as u8
+}
+
+// Panics (at compile time when used in a const) if `generator` does not
+// generate the multiplicative group for `polynomial`.
+pub const fn exp_log_tables(polynomial:u16,generator:u8)->([u8;510],[u8;256]){
+    let mut exp=[0u8;510];
+    let mut log=[0u8;256];
+    log[0]=0xff;
+    let mut value:u8=1;
+    let mut i=0;
+    while i<510 {
+        exp[i]=value;
+        if i<255 {
+            assert!(i==0 || value!=1, "generator order is less than 255");
+            log[value as usize]=i as u8;
+        }
+        value=mul_slow(value, generator, polynomial);
+        i+=1;
+    }
+    (exp, log)
+}
+
+const fn tables_match_reference()->bool{
+    let mut a=1;
+    while a<256 {
+        let mut b=1;
+        while b<256 {
+            let fast=TABLES.0[TABLES.1[a] as usize+TABLES.1[b] as usize];
+            if fast!=mul_slow(a as u8, b as u8, POLYNOMIAL) {
+                return false;
+            }
+            b+=1;
+        }
+        a+=1;
+    }
+    true
+}
+
+const _:()=assert!(tables_match_reference(), "GF(256) tables disagree with the reference multiply");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_invert_each_other() {
+        for a in 1..=255u8 {
+            assert_eq!(EXP[LOG[a as usize] as usize], a);
+        }
+        assert_eq!(EXP[..255], EXP[255..]);
+        assert_eq!(LOG[0], 0xff);
+        assert_eq!(mul_slow(0x57, 0x83, POLYNOMIAL), 0xc1);
+    }
+
+    #[test]
+    fn other_representations_build_consistent_tables() {
+        // The Reed-Solomon field of QR codes and many other Shamir tools.
+        let (exp, log)=exp_log_tables(0x11d, 2);
+        for a in 1..=255u8 {
+            for b in [1u8, 2, 0x53, 0xca, 0xff] {
+                assert_eq!(exp[log[a as usize] as usize+log[b as usize] as usize], mul_slow(a, b, 0x11d));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "generator order")]
+    fn a_non_generator_is_refused() {
+        exp_log_tables(POLYNOMIAL, 1);
+    }
+}
@@ -0,0 +1,340 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rand::seq::SliceRandom;
+use rand::{Rng, RngCore};
+use rand_chacha::{rand_core::SeedableRng, ChaCha20Rng};
+
+use crate::crypto::{Parts, ShamirSS, GFC256};
+use crate::field::{self, Field};
+
+// Test helpers for integrators (feature `test-util`).
+//
+// `assert_hiding` checks empirically that k-1 shares say nothing about the
+// secret. For each trial it splits, picks k-1 shares at random and, for
+// every candidate secret byte s, solves for the unique degree k-1 polynomial
+// through (0, s) and those shares, then evaluates it at a held-out share's x.
+// Hiding holds when the 256 candidates give 256 distinct completions: every
+// secret byte stays possible, each with exactly one consistent k-th share.
+// Splits that repeat across trials are also reported, since they mean the
+// coefficients are not random (e.g. a constant or reused RNG seed).
+
+pub fn assert_hiding(n:i32,k:i32,secret:&[u8],trials:usize){
+    assert_hiding_with(n, k, secret, trials, |secret| ShamirSS::split(n, k, secret.to_vec()).expect("split failed"));
+}
+
+// Same check for the caller's own split path, e.g. a wrapper that supplies
+// its RNG or chooses indices.
+pub fn assert_hiding_with<F:FnMut(&[u8])->Parts>(n:i32,k:i32,secret:&[u8],trials:usize,mut split:F){
+    assert!(k>1 && n>=k, "assert_hiding needs 1 < k <= n");
+    let mut rng=rand::thread_rng();
+    let mut seen=HashSet::new();
+    for trial in 0..trials {
+        let parts:Vec<(u8,Vec<u8>)>=split(secret).into_iter().map(|(x, y)| (x as u8, y)).collect();
+        assert_eq!(parts.len(), n as usize, "trial {trial}: split returned {} shares, expected {n}", parts.len());
+        let xs:HashSet<u8>=parts.iter().map(|(x, _)| *x).collect();
+        assert!(xs.len()==parts.len() && !xs.contains(&0), "trial {trial}: share indices must be distinct and non-zero");
+
+        let mut order:Vec<usize>=(0..parts.len()).collect();
+        order.shuffle(&mut rng);
+        let known:Vec<&(u8,Vec<u8>)>=order[..k as usize-1].iter().map(|&i| &parts[i]).collect();
+        let held_out=&parts[order[k as usize-1]];
+
+        let mut xs:Vec<u8>=vec![0];
+        xs.extend(known.iter().map(|(x, _)| *x));
+        let weights=GFC256::lagrange_coefficients(&xs, held_out.0);
+        for pos in 0..secret.len() {
+            let mut completions=[false;256];
+            for s in 0..=255u8 {
+                let y=known.iter().zip(&weights[1..]).fold(GFC256::mul(weights[0], s), |acc, ((_, y), &w)| GFC256::add(acc, GFC256::mul(w, y[pos])));
+                assert!(!completions[y as usize], "trial {trial}, byte {pos}: two candidate secrets share a completion, so {} shares leak the secret", k-1);
+                completions[y as usize]=true;
+            }
+        }
+
+        // Only meaningful with at least 64 bits of coefficients per split;
+        // below that, honest repeats are expected by the birthday bound.
+        if (k as usize-1)*secret.len()>=8 {
+            assert!(seen.insert(parts), "trial {trial}: split repeated an earlier trial's shares; coefficients are not random");
+        }
+    }
+}
+
+// Exhaustive check of the scheme's two core properties for small n (at most
+// 16, as every subset is visited). Splits once and asserts that every subset
+// of k or more shares joins to `secret`.
+pub fn check_all_subsets(n:i32,k:i32,secret:&[u8]){
+    check_subsets(n, k, secret, &mut rand::thread_rng(), 0);
+}
+
+// Also checks, with a ChaCha20Rng seeded from `seed` driving both the split
+// and the solver, that every subset of fewer than k shares is consistent
+// with `candidates` other secrets. For each candidate c the solver picks
+// random shares at unused x coordinates until there are k-1 points, solves
+// the last one so the degree k-1 polynomial passes through (0, c), and
+// asserts that joining the subset with those k - |subset| completions gives
+// c. A subset that pinned the secret down could not be completed this way.
+pub fn check_all_subsets_seeded(n:i32,k:i32,secret:&[u8],seed:u64,candidates:usize){
+    check_subsets(n, k, secret, &mut ChaCha20Rng::seed_from_u64(seed), candidates);
+}
+
+fn check_subsets<R:RngCore>(n:i32,k:i32,secret:&[u8],rng:&mut R,candidates:usize){
+    assert!(k>1 && n>=k && n<=16, "check_all_subsets needs 1 < k <= n <= 16");
+    let parts:Vec<(i32,Vec<u8>)>=ShamirSS::split_with_rng(n, k, secret.to_vec(), rng).expect("split failed").into_iter().collect();
+    for mask in 0u32..1<<n {
+        let subset:Parts=parts.iter().enumerate().filter(|(i, _)| mask>>i&1==1).map(|(_, (x, y))| (*x, y.clone())).collect();
+        if subset.len()>=k as usize {
+            let joined=ShamirSS::join(subset.clone()).unwrap_or_else(|e| panic!("subset {:?}: join failed: {e}", subset.keys()));
+            assert!(joined==secret, "subset {:?} of {} shares joined to the wrong secret", subset.keys(), subset.len());
+            continue;
+        }
+        for candidate in 0..candidates {
+            let mut other=secret.to_vec();
+            while other==secret {
+                rng.fill_bytes(&mut other);
+            }
+            let completed=complete(&subset, k, &other, rng);
+            let joined=ShamirSS::join(completed).unwrap_or_else(|e| panic!("subset {:?}, candidate {candidate}: join failed: {e}", subset.keys()));
+            assert!(joined==other, "subset {:?} of {} shares cannot be completed to candidate secret {candidate}", subset.keys(), subset.len());
+        }
+    }
+}
+
+// Adds k - |subset| shares at fresh x coordinates so the k shares lie on a
+// degree k-1 polynomial through (0, secret).
+fn complete<R:RngCore>(subset:&Parts,k:i32,secret:&[u8],rng:&mut R)->Parts{
+    let mut free:Vec<u8>=(1..=255u8).filter(|x| !subset.contains_key(&(*x as i32))).collect();
+    free.shuffle(rng);
+    let extra=&free[..k as usize-subset.len()];
+    let (random, solved)=extra.split_at(extra.len()-1);
+    let mut completed=subset.clone();
+    for &x in random {
+        completed.insert(x as i32, (0..secret.len()).map(|_| rng.gen()).collect());
+    }
+    let mut xs:Vec<u8>=vec![0];
+    xs.extend(completed.keys().map(|&x| x as u8));
+    let weights=GFC256::lagrange_coefficients(&xs, solved[0]);
+    let y=(0..secret.len())
+        .map(|pos| completed.values().zip(&weights[1..]).fold(GFC256::mul(weights[0], secret[pos]), |acc, (y, &w)| GFC256::add(acc, GFC256::mul(w, y[pos]))))
+        .collect();
+    completed.insert(solved[0] as i32, y);
+    completed
+}
+
+// Conformance suite for `Field` implementations: passing it means
+// `field::split` and `field::join` are correct over the field. Checks, with a
+// ChaCha20Rng seeded from `seed`:
+// - ZERO and ONE are distinct identities, `element` enumerates distinct
+//   elements from ZERO and ONE, and `random` is not constant;
+// - every element has a negation and every non-zero one an inverse (zero has
+//   none), over the spanning set;
+// - commutativity, no zero divisors and checked division over every pair of
+//   the spanning set;
+// - associativity and distributivity over every pair of the spanning set
+//   with each of FIELD_TRIPLE_SAMPLES third operands;
+// - split/join round trips for every subset of at least k shares.
+// The spanning set is the whole field up to FIELD_EXHAUSTIVE_ORDER elements,
+// otherwise the first and last 16 elements plus random ones, as many in
+// total. Panics with the counterexample on the first failure.
+pub const FIELD_EXHAUSTIVE_ORDER:u64=256;
+pub const FIELD_TRIPLE_SAMPLES:usize=32;
+
+pub fn field_conformance<F:Field>(){
+    field_conformance_seeded::<F>(0);
+}
+
+pub fn field_conformance_seeded<F:Field>(seed:u64){
+    let rng=&mut ChaCha20Rng::seed_from_u64(seed);
+    assert!(F::ORDER>=2, "a field has at least two elements, ORDER is {}", F::ORDER);
+    assert!(F::ZERO!=F::ONE, "ZERO and ONE are equal");
+    assert!(F::element(0)==F::ZERO && F::element(1)==F::ONE, "element(0) and element(1) must be ZERO and ONE");
+    let enumerated=F::ORDER.min(4096);
+    let mut distinct=Vec::with_capacity(enumerated as usize);
+    for index in 0..enumerated {
+        let e=F::element(index);
+        assert!(!distinct.contains(&e), "element({index}) = {e:?} repeats an earlier element");
+        distinct.push(e);
+    }
+    let first=F::random(rng);
+    assert!((0..64).any(|_| F::random(rng)!=first), "random() returned {first:?} 65 times in a row");
+
+    let span=spanning_set::<F>(rng);
+    let triples:Vec<F>=(0..FIELD_TRIPLE_SAMPLES).map(|i| if i<2 { F::element(i as u64) } else { F::random(rng) }).collect();
+    for &a in &span {
+        assert!(a.add(F::ZERO)==a && a.mul(F::ONE)==a, "{a:?}: ZERO or ONE is not an identity");
+        assert!(a.mul(F::ZERO)==F::ZERO, "{a:?} * ZERO is not ZERO");
+        assert!(a.add(a.neg())==F::ZERO, "{a:?} + neg({a:?}) is not ZERO");
+        match (a==F::ZERO, a.inv()) {
+            (true, Some(inv)) => panic!("ZERO has inverse {inv:?}"),
+            (false, None) => panic!("{a:?} has no inverse"),
+            (false, Some(inv)) => assert!(a.mul(inv)==F::ONE, "{a:?} * inv {inv:?} is not ONE"),
+            (true, None) => {}
+        }
+    }
+    for &a in &span {
+        for &b in &span {
+            assert!(a.add(b)==b.add(a), "{a:?} + {b:?} is not commutative");
+            assert!(a.mul(b)==b.mul(a), "{a:?} * {b:?} is not commutative");
+            assert!(a.sub(b).add(b)==a, "({a:?} - {b:?}) + {b:?} is not {a:?}");
+            assert!(a.mul(b)!=F::ZERO || a==F::ZERO || b==F::ZERO, "{a:?} * {b:?} is ZERO: zero divisors");
+            match a.checked_div(b) {
+                None => assert!(b==F::ZERO, "{a:?} / {b:?} failed for non-zero {b:?}"),
+                Some(q) => assert!(b!=F::ZERO && q.mul(b)==a, "{a:?} / {b:?} = {q:?} does not multiply back"),
+            }
+            for &c in &triples {
+                assert!(a.add(b).add(c)==a.add(b.add(c)), "addition is not associative for {a:?}, {b:?}, {c:?}");
+                assert!(a.mul(b).mul(c)==a.mul(b.mul(c)), "multiplication is not associative for {a:?}, {b:?}, {c:?}");
+                assert!(a.mul(b.add(c))==a.mul(b).add(a.mul(c)), "multiplication does not distribute over addition for {a:?}, {b:?}, {c:?}");
+            }
+        }
+    }
+
+    let max_shares=(F::ORDER-1).min(7) as usize;
+    for k in [2, 3, 5].into_iter().filter(|&k| k<=max_shares) {
+        let n=(k+2).min(max_shares);
+        let secret:Vec<F>=(0..8).map(|_| F::random(rng)).collect();
+        let indices:Vec<u64>=(1..=n as u64).collect();
+        let shares=field::split(&secret, k, &indices, rng).unwrap_or_else(|e| panic!("split {k} of {n} failed: {e}"));
+        for mask in 0u32..1<<n {
+            let subset:Vec<(u64,Vec<F>)>=shares.iter().enumerate().filter(|(i, _)| mask>>i&1==1).map(|(_, share)| share.clone()).collect();
+            if subset.len()<k {
+                continue;
+            }
+            let joined=field::join(&subset).unwrap_or_else(|e| panic!("join of shares {:?} failed: {e}", subset.iter().map(|(x, _)| x).collect::<Vec<_>>()));
+            assert!(joined==secret, "shares {:?} of a {k}-of-{n} split joined to the wrong secret", subset.iter().map(|(x, _)| x).collect::<Vec<_>>());
+        }
+    }
+}
+
+fn spanning_set<F:Field>(rng:&mut ChaCha20Rng)->Vec<F>{
+    if F::ORDER<=FIELD_EXHAUSTIVE_ORDER {
+        return (0..F::ORDER).map(F::element).collect();
+    }
+    let mut span:Vec<F>=(0..16).chain(F::ORDER-16..F::ORDER).map(F::element).collect();
+    while span.len()<FIELD_EXHAUSTIVE_ORDER as usize {
+        span.push(F::random(rng));
+    }
+    span
+}
+
+// Allocation accounting, to check in tests that a path allocates nothing
+// (no hidden copies of secret data on the heap). Install the counter as the
+// test binary's global allocator, wrapping the allocator the application
+// really uses:
+//
+//     #[global_allocator]
+//     static ALLOCATOR:CountingAllocator=CountingAllocator::new(System);
+//
+// `count_allocations` then reports the allocations made on the calling
+// thread while its closure runs (other threads are not counted), and
+// `assert_no_allocations` panics unless there were none. Frees are not
+// counted; a realloc counts as an allocation of the new size.
+pub struct CountingAllocator<A=System>{
+    inner:A,
+}
+
+#[derive(Debug,Clone,Copy,Default,PartialEq,Eq)]
+pub struct Allocations{
+    pub count:usize,
+    pub bytes:usize,
+}
+
+static COUNTER_INSTALLED:AtomicBool=AtomicBool::new(false);
+
+thread_local! {
+    // Const-initialised and without a destructor, so the allocator can use it
+    // without allocating.
+    static COUNTING:Cell<Option<Allocations>>=const { Cell::new(None) };
+}
+
+impl<A> CountingAllocator<A>{
+    pub const fn new(inner:A)->CountingAllocator<A>{
+        CountingAllocator { inner }
+    }
+
+    fn record(bytes:usize){
+        COUNTER_INSTALLED.store(true, Ordering::Relaxed);
+        let _=COUNTING.try_with(|counting| {
+            if let Some(seen)=counting.get() {
+                counting.set(Some(Allocations { count: seen.count+1, bytes: seen.bytes+bytes }));
+            }
+        });
+    }
+}
+
+// SAFETY: every call is delegated unchanged to `inner`; counting touches only
+// a thread-local Cell.
+unsafe impl<A:GlobalAlloc> GlobalAlloc for CountingAllocator<A>{
+    unsafe fn alloc(&self,layout:Layout)->*mut u8{
+        Self::record(layout.size());
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self,layout:Layout)->*mut u8{
+        Self::record(layout.size());
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self,ptr:*mut u8,layout:Layout,new_size:usize)->*mut u8{
+        Self::record(new_size);
+        self.inner.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self,ptr:*mut u8,layout:Layout){
+        self.inner.dealloc(ptr, layout);
+    }
+}
+
+// Panics if CountingAllocator is not the global allocator, rather than
+// reporting zero allocations.
+pub fn count_allocations<T,F:FnOnce()->T>(f:F)->(T,Allocations){
+    let outer=COUNTING.with(|counting| counting.replace(Some(Allocations::default())));
+    let result=f();
+    let inner=COUNTING.with(|counting| counting.replace(outer)).unwrap_or_default();
+    assert!(COUNTER_INSTALLED.load(Ordering::Relaxed), "count_allocations needs testutil::CountingAllocator as the #[global_allocator]");
+    if let Some(outer)=outer {
+        COUNTING.with(|counting| counting.set(Some(Allocations { count: outer.count+inner.count, bytes: outer.bytes+inner.bytes })));
+    }
+    (result, inner)
+}
+
+pub fn assert_no_allocations<T,F:FnOnce()->T>(what:&str,f:F)->T{
+    let (result, allocations)=count_allocations(f);
+    assert!(allocations.count==0, "{what} made {} allocations ({} bytes)", allocations.count, allocations.bytes);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_quorum_joins_and_every_smaller_subset_hides() {
+        check_all_subsets(5, 3, b"subsets");
+        check_all_subsets(4, 4, &[0]);
+        check_all_subsets_seeded(6, 3, b"subsets", 458, 2);
+        check_all_subsets_seeded(5, 5, &[0xff, 0], 459, 2);
+    }
+
+    // 2^n subsets are visited, so n is capped.
+    #[test]
+    #[should_panic(expected = "1 < k <= n <= 16")]
+    fn rejects_sizes_it_cannot_enumerate() {
+        check_all_subsets(17, 2, b"too many subsets");
+    }
+
+    // Every field the crate ships, with the default and another irreducible
+    // polynomial, and primes on both sides of FIELD_EXHAUSTIVE_ORDER.
+    #[test]
+    fn shipped_fields_conform() {
+        field_conformance::<field::Gf256>();
+        field_conformance::<field::Gf256Aes>();
+        field_conformance::<field::Gf256Poly<0x11d>>();
+        field_conformance::<field::Fp<7>>();
+        field_conformance::<field::Fp<257>>();
+        field_conformance_seeded::<field::Fp<65521>>(473);
+        field_conformance::<field::Fp<4294967291>>();
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::check::CheckValue;
+use crate::crypto::{Parts, ShamirSS};
+use crate::error::ShamirError;
+use crate::guard::{JoinContext, JoinGuard};
+use crate::join::{JoinOptions, Reconstruction};
+
+// Recovery-context tokens: a capability the dealer issues to say when, why
+// and into what a sharing may be reconstructed. The token names the shares
+// it covers by fingerprint, the purpose of the recovery, a validity window
+// and the output forms the recovered secret may take. The dealer signs the
+// text (detached OpenPGP signature, last line, as for revocation lists):
+//
+//   shamir-recovery-token v1
+//   id 5c0f3e9a21b7d446
+//   purpose restore the payments database after incident 4711
+//   issued 1760572800
+//   expires 1761177600
+//   outputs file
+//   share 0ba20012
+//   share 77c1e5f0
+//   signature <base64>
+//
+// Times are Unix seconds. A token is not single use: it authorizes any
+// number of joins of those shares until it expires, and a journal records
+// each one with the token id.
+
+const HEADER:&str="shamir-recovery-token v1";
+const SIGNATURE_PREFIX:&str="signature ";
+
+// Where the reconstructed secret goes.
+#[derive(Debug,Clone,Copy,PartialEq,Eq)]
+pub enum OutputForm{
+    File,
+    Stdout,
+    Clipboard,
+}
+
+impl OutputForm{
+
+    pub fn name(self)->&'static str{
+        match self {
+            OutputForm::File => "file",
+            OutputForm::Stdout => "stdout",
+            OutputForm::Clipboard => "clipboard",
+        }
+    }
+
+    pub fn from_name(name:&str)->Option<OutputForm>{
+        match name {
+            "file" => Some(OutputForm::File),
+            "stdout" => Some(OutputForm::Stdout),
+            "clipboard" => Some(OutputForm::Clipboard),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct RecoveryToken{
+    pub id:String,
+    pub purpose:String,
+    pub issued:u64,
+    pub expires:u64,
+    pub outputs:Vec<OutputForm>,
+    // Fingerprints of the shares the token covers.
+    pub shares:Vec<String>,
+}
+
+impl RecoveryToken{
+
+    pub fn permits(&self,form:OutputForm)->bool{
+        self.outputs.contains(&form)
+    }
+
+    // Whether the token is valid at `now` for shares with these fingerprints.
+    pub fn check_at(&self,fingerprints:&[String],now:SystemTime)->Result<(),String>{
+        let secs=now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        if secs<self.issued {
+            return Err(format!("recovery token {} is not valid before {}", self.id, self.issued));
+        }
+        if secs>=self.expires {
+            return Err(format!("recovery token {} expired at {}", self.id, self.expires));
+        }
+        let foreign:Vec<&str>=fingerprints.iter().filter(|f| !self.shares.contains(f)).map(|f| f.as_str()).collect();
+        if !foreign.is_empty() {
+            return Err(format!("recovery token {} does not cover shares {}", self.id, foreign.join(", ")));
+        }
+        Ok(())
+    }
+
+    // The text that is signed.
+    pub fn to_text(&self)->String{
+        let clean=|v:&str| v.replace(['\n', '\r'], " ").trim().to_string();
+        let outputs:Vec<&str>=self.outputs.iter().map(|form| form.name()).collect();
+        let mut out=format!("{HEADER}\nid {}\npurpose {}\nissued {}\nexpires {}\noutputs {}\n",
+            clean(&self.id), clean(&self.purpose), self.issued, self.expires, outputs.join(","));
+        for share in &self.shares {
+            out.push_str(&format!("share {}\n", clean(share)));
+        }
+        out
+    }
+
+    pub fn parse(text:&str)->Result<RecoveryToken,String>{
+        let mut lines=text.lines().map(str::trim).filter(|line| !line.is_empty());
+        if lines.next()!=Some(HEADER) {
+            return Err("Not a recovery token".to_string());
+        }
+        let mut field=|name:&str| -> Result<String,String> {
+            lines.next().map(|line| line.split_once(' ').unwrap_or((line, ""))).filter(|(key, _)| *key==name)
+                .map(|(_, value)| value.trim().to_string())
+                .ok_or(format!("Recovery token has no {name} line"))
+        };
+        let id=field("id")?;
+        let purpose=field("purpose")?;
+        let issued=field("issued")?.parse::<u64>().map_err(|_| "Malformed recovery token issue time".to_string())?;
+        let expires=field("expires")?.parse::<u64>().map_err(|_| "Malformed recovery token expiry".to_string())?;
+        let outputs=field("outputs")?.split(',').map(str::trim).filter(|name| !name.is_empty())
+            .map(|name| OutputForm::from_name(name).ok_or(format!("Unknown output form {name} in recovery token")))
+            .collect::<Result<Vec<_>,_>>()?;
+        let mut shares=Vec::new();
+        for line in lines {
+            if line.starts_with(SIGNATURE_PREFIX) {
+                break;
+            }
+            let share=line.strip_prefix("share ").ok_or(format!("Malformed recovery token line {line}"))?;
+            shares.push(share.trim().to_string());
+        }
+        if id.is_empty() || purpose.is_empty() {
+            return Err("Recovery token has no id or purpose".to_string());
+        }
+        if expires<=issued {
+            return Err("Recovery token expires before it is issued".to_string());
+        }
+        if shares.is_empty() || outputs.is_empty() {
+            return Err("Recovery token covers no shares or output forms".to_string());
+        }
+        Ok(RecoveryToken { id, purpose, issued, expires, outputs, shares })
+    }
+}
+
+// Refuses a join the token does not cover or no longer covers.
+impl JoinGuard for RecoveryToken{
+    fn check(&self,ctx:&JoinContext)->Result<(),String>{
+        let fingerprints:Vec<String>=ctx.shares.iter().map(|share| share.fingerprint.clone()).collect();
+        self.check_at(&fingerprints, ctx.now)
+    }
+}
+
+impl ShamirSS{
+
+    // `join_detailed` under a recovery token: the token must cover every
+    // share in `parts` at `now`, and its id is recorded in the result.
+    pub fn join_authorized(parts:Parts,options:&JoinOptions,check:Option<&CheckValue>,token:&RecoveryToken,now:SystemTime)->Result<Reconstruction,ShamirError>{
+        let ctx=JoinContext { now, ..JoinContext::new(&parts) };
+        token.check(&ctx).map_err(ShamirError::JoinDenied)?;
+        let mut reconstruction=Self::join_detailed(parts, options, check)?;
+        reconstruction.token=Some(token.id.clone());
+        Ok(reconstruction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::encoding::share_fingerprint;
+
+    fn token(parts:&Parts)->RecoveryToken{
+        RecoveryToken {
+            id: "5c0f3e9a21b7d446".to_string(),
+            purpose: "restore the payments database".to_string(),
+            issued: 1_760_572_800,
+            expires: 1_761_177_600,
+            outputs: vec![OutputForm::File],
+            shares: parts.iter().take(2).map(|(index, data)| share_fingerprint(*index, data)).collect(),
+        }
+    }
+
+    fn at(secs:u64)->SystemTime{
+        UNIX_EPOCH+Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn token_round_trips_and_scopes_outputs() {
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let token=token(&parts);
+        let signed=format!("{}signature c2lnbmF0dXJl\n", token.to_text());
+        assert_eq!(RecoveryToken::parse(&signed).unwrap(), token);
+        assert!(token.permits(OutputForm::File));
+        assert!(!token.permits(OutputForm::Clipboard));
+        assert!(RecoveryToken::parse(&token.to_text().replace("outputs file", "outputs printer")).is_err());
+        assert!(RecoveryToken::parse(&token.to_text().replace("expires 1761177600", "expires 1760572800")).is_err());
+    }
+
+    #[test]
+    fn join_is_limited_to_covered_shares_and_the_window() {
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let token=token(&parts);
+        let covered:Parts=parts.clone().into_iter().take(2).collect();
+        let options=JoinOptions::default();
+        let joined=ShamirSS::join_authorized(covered.clone(), &options, None, &token, at(1_760_600_000)).unwrap();
+        assert_eq!((joined.secret.as_slice(), joined.token.as_deref()), (&b"secret"[..], Some(token.id.as_str())));
+
+        for now in [1_760_572_799, 1_761_177_600] {
+            assert!(matches!(ShamirSS::join_authorized(covered.clone(), &options, None, &token, at(now)), Err(ShamirError::JoinDenied(_))));
+        }
+        let uncovered:Parts=parts.into_iter().skip(1).collect();
+        match ShamirSS::join_authorized(uncovered, &options, None, &token, at(1_760_600_000)) {
+            Err(ShamirError::JoinDenied(message)) => assert!(message.contains("does not cover")),
+            other => panic!("{other:?}"),
+        }
+    }
+}
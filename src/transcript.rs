@@ -0,0 +1,375 @@
+use std::fmt::Write as _;
+
+use crate::json::{Json, JsonExt};
+use crate::stats::EntropyReport;
+
+// Dealer-side record of a ceremony: parameters plus one line per custodian.
+// Plain text so it can be reviewed, diffed and archived alongside paper
+// records. Each line is a record kind followed by tab-separated key=value
+// fields; values may contain spaces but not tabs or newlines.
+//
+//   shamir-transcript v1
+//   ceremony	threshold=3	shares=5	created=2026-10-16	id=4be1a0c29d7730f5
+//   custodian	index=1	label=alice	fingerprint=1a2b3c4d	contact=alice@example.com	next_drill_due=2027-04-16
+//
+// `created` is left out when the split recorded no date
+// (`--logical-time omit`). `id` names the ceremony on custodian receipts
+// (see `receipt.rs`); transcripts from before receipts have none.
+//
+// An escrow across organizations (see `escrow.rs`) adds `escrow=<policy>`
+// to the ceremony line and `org=<id>` to each custodian; `threshold` and
+// `shares` then count organizations, and indices are per org, so the same
+// index appears once for each organization.
+//
+// `watermark` (optional) is the custodian's leak-tracing nonce, see
+// `watermark.rs`; the transcript is the only place it is kept.
+//
+// `split --ack` adds `ack_challenge=<hex>` and `ack_digest=<hex>` to each
+// custodian, and `acknowledged=<date>` once their response came back; see
+// `ack.rs`.
+//
+// Spare shares kept sealed by the dealer are listed as
+//   spare	index=254	fingerprint=5e6f7a8b	activated=2027-01-02
+// where `activated` appears once the spare has been issued to a custodian.
+//
+// A split with an entropy report (see `stats::assess_shares`) adds
+//   entropy	bytes=160	chi_squared=n/a	serial_correlation=-0.0312	serial_limit=0.3289	os_random=ok	result=pass	source=...
+// with the statistics as written, and the RNG the split drew from.
+//
+// A secret that came out of an HSM (see `hsm.rs`) adds
+//   hsm	token=ceremony-01	manufacturer=...	model=...	serial=...	mechanism=CKM_AES_KEY_WRAP_KWP	key=root-kek	check_value=1a2b3c	attestation=...
+// naming the token, the key and how it was unwrapped or derived; the key
+// check value and the vendor's attestation (base64) appear when the HSM
+// gave them.
+
+const HEADER:&str="shamir-transcript v1";
+// `version` in `to_json`; bumped only when a field changes meaning.
+pub const JSON_VERSION:i64=1;
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct CustodianRecord{
+    pub index:i32,
+    pub label:String,
+    pub fingerprint:String,
+    pub contact:Option<String>,
+    pub next_drill_due:Option<String>,
+    pub watermark:Option<String>,
+    pub org:Option<String>,
+    pub ack_challenge:Option<String>,
+    pub ack_digest:Option<String>,
+    pub acknowledged:Option<String>,
+}
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct SpareRecord{
+    pub index:i32,
+    pub fingerprint:String,
+    pub activated:Option<String>,
+}
+
+// Numbers are kept as written so a transcript reads back unchanged.
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct EntropyRecord{
+    pub bytes:usize,
+    pub chi_squared:String,
+    pub serial_correlation:String,
+    pub serial_limit:String,
+    pub os_random:bool,
+    pub passed:bool,
+    pub source:String,
+}
+
+impl EntropyRecord{
+    pub fn new(report:&EntropyReport,source:&str)->EntropyRecord{
+        EntropyRecord {
+            bytes: report.bytes,
+            chi_squared: report.chi_squared.map_or("n/a".to_string(), |chi| format!("{chi:.2}")),
+            serial_correlation: format!("{:.4}", report.serial_correlation),
+            serial_limit: format!("{:.4}", report.serial_limit),
+            os_random: report.os_random,
+            passed: report.passed(),
+            source: source.to_string(),
+        }
+    }
+}
+
+// As reported by the HSM, e.g. from PKCS#11 C_GetTokenInfo.
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct HsmRecord{
+    pub token:String,
+    pub manufacturer:String,
+    pub model:String,
+    pub serial:String,
+    pub mechanism:String,
+    pub key:String,
+    pub check_value:Option<String>,
+    pub attestation:Option<String>,
+}
+
+#[derive(Debug,Clone,Default,PartialEq,Eq)]
+pub struct Transcript{
+    pub threshold:i32,
+    pub shares:i32,
+    pub created:String,
+    pub ceremony:String,
+    pub escrow:String,
+    pub custodians:Vec<CustodianRecord>,
+    pub spares:Vec<SpareRecord>,
+    pub entropy:Option<EntropyRecord>,
+    pub hsm:Option<HsmRecord>,
+}
+
+impl Transcript{
+
+    pub fn to_text(&self)->String{
+        let mut out=format!("{HEADER}\n");
+        let _=write!(out, "ceremony\tthreshold={}\tshares={}", self.threshold, self.shares);
+        if !self.created.is_empty() {
+            let _=write!(out, "\tcreated={}", clean(&self.created));
+        }
+        if !self.ceremony.is_empty() {
+            let _=write!(out, "\tid={}", clean(&self.ceremony));
+        }
+        if !self.escrow.is_empty() {
+            let _=write!(out, "\tescrow={}", clean(&self.escrow));
+        }
+        out.push('\n');
+        for c in &self.custodians {
+            let _=write!(out, "custodian\tindex={}\tlabel={}\tfingerprint={}", c.index, clean(&c.label), clean(&c.fingerprint));
+            if let Some(contact)=&c.contact {
+                let _=write!(out, "\tcontact={}", clean(contact));
+            }
+            if let Some(due)=&c.next_drill_due {
+                let _=write!(out, "\tnext_drill_due={}", clean(due));
+            }
+            if let Some(nonce)=&c.watermark {
+                let _=write!(out, "\twatermark={}", clean(nonce));
+            }
+            if let Some(org)=&c.org {
+                let _=write!(out, "\torg={}", clean(org));
+            }
+            for (key, value) in [("ack_challenge", &c.ack_challenge), ("ack_digest", &c.ack_digest), ("acknowledged", &c.acknowledged)] {
+                if let Some(value)=value {
+                    let _=write!(out, "\t{key}={}", clean(value));
+                }
+            }
+            out.push('\n');
+        }
+        for spare in &self.spares {
+            let _=write!(out, "spare\tindex={}\tfingerprint={}", spare.index, clean(&spare.fingerprint));
+            if let Some(date)=&spare.activated {
+                let _=write!(out, "\tactivated={}", clean(date));
+            }
+            out.push('\n');
+        }
+        if let Some(e)=&self.entropy {
+            let _=writeln!(out, "entropy\tbytes={}\tchi_squared={}\tserial_correlation={}\tserial_limit={}\tos_random={}\tresult={}\tsource={}",
+                e.bytes, clean(&e.chi_squared), clean(&e.serial_correlation), clean(&e.serial_limit),
+                if e.os_random { "ok" } else { "failed" }, if e.passed { "pass" } else { "fail" }, clean(&e.source));
+        }
+        if let Some(h)=&self.hsm {
+            let _=write!(out, "hsm\ttoken={}\tmanufacturer={}\tmodel={}\tserial={}\tmechanism={}\tkey={}",
+                clean(&h.token), clean(&h.manufacturer), clean(&h.model), clean(&h.serial), clean(&h.mechanism), clean(&h.key));
+            for (key, value) in [("check_value", &h.check_value), ("attestation", &h.attestation)] {
+                if let Some(value)=value {
+                    let _=write!(out, "\t{key}={}", clean(value));
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // The same record as JSON, for pipelines that validate it against
+    // `schema::json_schema()`. Fields that the text form leaves out are null.
+    pub fn to_json(&self)->Json{
+        let optional=|value:&str| (!value.is_empty()).then(|| value.to_string());
+        Json::object([
+            ("version", JSON_VERSION.into()),
+            ("threshold", (self.threshold as i64).into()),
+            ("shares", (self.shares as i64).into()),
+            ("created", optional(&self.created).into()),
+            ("id", optional(&self.ceremony).into()),
+            ("escrow", optional(&self.escrow).into()),
+            ("custodians", Json::Array(self.custodians.iter().map(|c| Json::object([
+                ("index", (c.index as i64).into()),
+                ("label", c.label.as_str().into()),
+                ("fingerprint", c.fingerprint.as_str().into()),
+                ("contact", c.contact.clone().into()),
+                ("next_drill_due", c.next_drill_due.clone().into()),
+                ("watermark", c.watermark.clone().into()),
+                ("org", c.org.clone().into()),
+                ("ack_challenge", c.ack_challenge.clone().into()),
+                ("ack_digest", c.ack_digest.clone().into()),
+                ("acknowledged", c.acknowledged.clone().into()),
+            ])).collect())),
+            ("spares", Json::Array(self.spares.iter().map(|s| Json::object([
+                ("index", (s.index as i64).into()),
+                ("fingerprint", s.fingerprint.as_str().into()),
+                ("activated", s.activated.clone().into()),
+            ])).collect())),
+            ("entropy", self.entropy.as_ref().map_or(Json::Null, |e| Json::object([
+                ("bytes", (e.bytes as i64).into()),
+                ("chi_squared", e.chi_squared.as_str().into()),
+                ("serial_correlation", e.serial_correlation.as_str().into()),
+                ("serial_limit", e.serial_limit.as_str().into()),
+                ("os_random", e.os_random.into()),
+                ("passed", e.passed.into()),
+                ("source", e.source.as_str().into()),
+            ]))),
+            ("hsm", self.hsm.as_ref().map_or(Json::Null, |h| Json::object([
+                ("token", h.token.as_str().into()),
+                ("manufacturer", h.manufacturer.as_str().into()),
+                ("model", h.model.as_str().into()),
+                ("serial", h.serial.as_str().into()),
+                ("mechanism", h.mechanism.as_str().into()),
+                ("key", h.key.as_str().into()),
+                ("check_value", h.check_value.clone().into()),
+                ("attestation", h.attestation.clone().into()),
+            ]))),
+        ])
+    }
+
+    pub fn parse(text:&str)->Result<Transcript,String>{
+        let mut lines=text.lines();
+        if lines.next().map(|l| l.trim())!=Some(HEADER) {
+            return Err("Not a shamir transcript".to_string());
+        }
+        let mut transcript=Transcript::default();
+        for (number, line) in lines.enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let mut fields=line.split('\t');
+            let kind=fields.next().unwrap_or_default();
+            let pairs:Vec<(&str,&str)>=fields.map(|f| f.split_once('=').unwrap_or((f, ""))).collect();
+            let get=|key:&str| pairs.iter().find(|(k, _)| *k==key).map(|(_, v)| v.to_string());
+            let number_of=|key:&str| get(key).and_then(|v| v.parse::<i32>().ok())
+                .ok_or(format!("Transcript line {}: missing or invalid {key}", number+2));
+            match kind {
+                "ceremony" => {
+                    transcript.threshold=number_of("threshold")?;
+                    transcript.shares=number_of("shares")?;
+                    transcript.created=get("created").unwrap_or_default();
+                    transcript.ceremony=get("id").unwrap_or_default();
+                    transcript.escrow=get("escrow").unwrap_or_default();
+                }
+                "custodian" => transcript.custodians.push(CustodianRecord {
+                    index: number_of("index")?,
+                    label: get("label").unwrap_or_default(),
+                    fingerprint: get("fingerprint").unwrap_or_default(),
+                    contact: get("contact"),
+                    next_drill_due: get("next_drill_due"),
+                    watermark: get("watermark"),
+                    org: get("org"),
+                    ack_challenge: get("ack_challenge"),
+                    ack_digest: get("ack_digest"),
+                    acknowledged: get("acknowledged"),
+                }),
+                "spare" => transcript.spares.push(SpareRecord {
+                    index: number_of("index")?,
+                    fingerprint: get("fingerprint").unwrap_or_default(),
+                    activated: get("activated"),
+                }),
+                "entropy" => transcript.entropy=Some(EntropyRecord {
+                    bytes: get("bytes").and_then(|v| v.parse().ok()).ok_or(format!("Transcript line {}: missing or invalid bytes", number+2))?,
+                    chi_squared: get("chi_squared").unwrap_or_default(),
+                    serial_correlation: get("serial_correlation").unwrap_or_default(),
+                    serial_limit: get("serial_limit").unwrap_or_default(),
+                    os_random: get("os_random").as_deref()==Some("ok"),
+                    passed: get("result").as_deref()==Some("pass"),
+                    source: get("source").unwrap_or_default(),
+                }),
+                "hsm" => transcript.hsm=Some(HsmRecord {
+                    token: get("token").unwrap_or_default(),
+                    manufacturer: get("manufacturer").unwrap_or_default(),
+                    model: get("model").unwrap_or_default(),
+                    serial: get("serial").unwrap_or_default(),
+                    mechanism: get("mechanism").unwrap_or_default(),
+                    key: get("key").unwrap_or_default(),
+                    check_value: get("check_value"),
+                    attestation: get("attestation"),
+                }),
+                // Unknown record kinds are kept forward-compatible.
+                _ => {}
+            }
+        }
+        Ok(transcript)
+    }
+
+    // Custodians given an acknowledgment challenge who have not answered it.
+    pub fn unacknowledged(&self)->Vec<&CustodianRecord>{
+        self.custodians.iter().filter(|c| c.ack_challenge.is_some() && c.acknowledged.is_none()).collect()
+    }
+
+    // Custodians whose next drill date (YYYY-MM-DD) is before `today`.
+    pub fn overdue(&self,today:&str)->Vec<&CustodianRecord>{
+        self.custodians.iter()
+            .filter(|c| c.next_drill_due.as_deref().is_some_and(|due| due<today))
+            .collect()
+    }
+}
+
+fn clean(value:&str)->String{
+    value.replace(['\t', '\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcript()->Transcript{
+        Transcript {
+            threshold: 2,
+            shares: 3,
+            created: "2026-10-16".to_string(),
+            ceremony: "4be1a0c29d7730f5".to_string(),
+            custodians: vec![
+                CustodianRecord { index: 1, label: "alice".to_string(), fingerprint: "1a2b3c4d".to_string(), contact: Some("alice@example.com".to_string()), next_drill_due: Some("2027-04-16".to_string()), ..Default::default() },
+                CustodianRecord { index: 2, label: "bob\tsmith".to_string(), fingerprint: "2b3c4d5e".to_string(), next_drill_due: Some("2026-01-01".to_string()), ack_challenge: Some("00ff".to_string()), ..Default::default() },
+                CustodianRecord { index: 3, label: "carol".to_string(), fingerprint: "3c4d5e6f".to_string(), ack_challenge: Some("ff00".to_string()), acknowledged: Some("2026-10-17".to_string()), ..Default::default() },
+            ],
+            spares: vec![SpareRecord { index: 254, fingerprint: "5e6f7a8b".to_string(), activated: None }],
+            entropy: Some(EntropyRecord { bytes: 160, chi_squared: "n/a".to_string(), serial_correlation: "-0.0312".to_string(), serial_limit: "0.3289".to_string(), os_random: true, passed: true, source: "os".to_string() }),
+            hsm: Some(HsmRecord { token: "ceremony-01".to_string(), mechanism: "CKM_AES_KEY_WRAP_KWP".to_string(), check_value: Some("1a2b3c".to_string()), ..Default::default() }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn text_form_round_trips() {
+        let text=transcript().to_text();
+        assert!(text.starts_with("shamir-transcript v1\nceremony\tthreshold=2\tshares=3\tcreated=2026-10-16\tid=4be1a0c29d7730f5\n"));
+        assert!(text.contains("\tlabel=bob smith\t"));
+        let mut expected=transcript();
+        expected.custodians[1].label="bob smith".to_string();
+        assert_eq!(Transcript::parse(&text).unwrap(), expected);
+        assert_eq!(Transcript::parse(&(text+"future\tfield=1\n")).unwrap(), expected);
+    }
+
+    #[test]
+    fn parse_reports_the_bad_line() {
+        assert!(Transcript::parse("shamir-transcript v2\n").is_err());
+        let bad=Transcript::parse("shamir-transcript v1\nceremony\tthreshold=2\tshares=3\ncustodian\tindex=x\n").unwrap_err();
+        assert_eq!(bad, "Transcript line 3: missing or invalid index");
+    }
+
+    #[test]
+    fn lists_overdue_and_unacknowledged_custodians() {
+        let transcript=transcript();
+        let indices=|records:Vec<&CustodianRecord>| records.iter().map(|c| c.index).collect::<Vec<_>>();
+        assert_eq!(indices(transcript.overdue("2026-10-16")), vec![2]);
+        assert_eq!(indices(transcript.overdue("2027-05-01")), vec![1, 2]);
+        assert_eq!(indices(transcript.unacknowledged()), vec![2]);
+    }
+
+    #[test]
+    fn json_leaves_missing_fields_null() {
+        let mut transcript=transcript();
+        transcript.created.clear();
+        let json=transcript.to_json();
+        assert_eq!(json["version"], JSON_VERSION);
+        assert_eq!(json["created"], Json::Null);
+        assert_eq!(json["custodians"][0]["contact"], "alice@example.com");
+        assert_eq!(json["custodians"][2]["contact"], Json::Null);
+        assert_eq!(json["hsm"]["check_value"], "1a2b3c");
+        assert_eq!(Transcript::default().to_json()["entropy"], Json::Null);
+    }
+}
@@ -0,0 +1,259 @@
+use crate::crypto::zeroize;
+use crate::sparse::TrimZeros;
+
+// Pre-split transforms as an ordered pipeline. A split applies each step in
+// turn to the secret and records the step ids in the compact share header
+// (bytes 6..12, see `encoding.rs`); a join looks each id up in a
+// `Transforms` registry and undoes the steps in reverse order. The registry
+// holds the built-in steps plus any a downstream crate registers (its own
+// compression or encryption, say), so adding one needs no change here.
+//
+// Ids 1..=127 are kept for built-in steps, 128..=255 for registered ones; 0
+// ends the list. A step's id is part of the share format: never reuse one
+// for a different transform.
+
+pub trait Transform{
+    fn id(&self)->u8;
+    // Short lower-case identifier, e.g. "trim-zeros".
+    fn name(&self)->&str;
+    fn apply(&self,data:&[u8])->Result<Vec<u8>,String>;
+    // Undoes `apply`; `data` is consumed and should be zeroized if rejected.
+    fn restore(&self,data:Vec<u8>)->Result<Vec<u8>,String>;
+}
+
+// Steps that fit in the header.
+pub const MAX_TRANSFORMS:usize=6;
+pub const FIRST_CUSTOM_ID:u8=128;
+
+// Step ids in the order they were applied; the header bytes as they are.
+#[derive(Debug,Clone,Copy,PartialEq,Eq,Default)]
+pub struct Pipeline{
+    ids:[u8;MAX_TRANSFORMS],
+}
+
+impl Pipeline{
+
+    pub fn new(ids:&[u8])->Result<Pipeline,String>{
+        if ids.len()>MAX_TRANSFORMS {
+            return Err(format!("At most {MAX_TRANSFORMS} transforms fit in a share header, not {}", ids.len()));
+        }
+        if ids.contains(&0) {
+            return Err("Transform id 0 is reserved".to_string());
+        }
+        let mut pipeline=Pipeline::default();
+        pipeline.ids[..ids.len()].copy_from_slice(ids);
+        Ok(pipeline)
+    }
+
+    pub fn ids(&self)->&[u8]{
+        let len=self.ids.iter().position(|&id| id==0).unwrap_or(MAX_TRANSFORMS);
+        &self.ids[..len]
+    }
+
+    pub fn is_empty(&self)->bool{
+        self.ids[0]==0
+    }
+
+    pub fn to_header(self)->[u8;MAX_TRANSFORMS]{
+        self.ids
+    }
+
+    // A zero ends the list; anything after it must be zero too.
+    pub fn from_header(bytes:&[u8])->Result<Pipeline,String>{
+        let bytes:[u8;MAX_TRANSFORMS]=bytes.try_into().map_err(|_| "Malformed transform list".to_string())?;
+        let pipeline=Pipeline { ids: bytes };
+        if bytes[pipeline.ids().len()..].iter().any(|&id| id!=0) {
+            return Err("Malformed transform list".to_string());
+        }
+        Ok(pipeline)
+    }
+}
+
+// Hides the secret's exact length: a 4-byte big-endian length, the
+// data, then zeros up to the next power of two (at least PAD_MIN_BYTES).
+// Shares then reveal only which size class the secret falls in.
+pub struct Pad;
+
+const PAD_LENGTH_PREFIX:usize=4;
+const PAD_MIN_BYTES:usize=32;
+
+impl Transform for Pad{
+    fn id(&self)->u8{ 2 }
+    fn name(&self)->&str{ "pad" }
+
+    fn apply(&self,data:&[u8])->Result<Vec<u8>,String>{
+        let len=u32::try_from(data.len()).map_err(|_| format!("Secret of {} bytes is too large to pad", data.len()))?;
+        let padded=(PAD_LENGTH_PREFIX+data.len()).next_power_of_two().max(PAD_MIN_BYTES);
+        let mut out=Vec::with_capacity(padded);
+        out.extend_from_slice(&len.to_be_bytes());
+        out.extend_from_slice(data);
+        out.resize(padded, 0);
+        Ok(out)
+    }
+
+    fn restore(&self,mut data:Vec<u8>)->Result<Vec<u8>,String>{
+        let len=data.get(..PAD_LENGTH_PREFIX).map(|prefix| u32::from_be_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]) as usize);
+        match len {
+            Some(len) if len<=data.len()-PAD_LENGTH_PREFIX => {
+                data.drain(..PAD_LENGTH_PREFIX);
+                zeroize(&mut data[len..]);
+                data.truncate(len);
+                Ok(data)
+            }
+            _ => {
+                zeroize(&mut data);
+                Err("Padded secret has a malformed length prefix".to_string())
+            }
+        }
+    }
+}
+
+pub struct Transforms{
+    transforms:Vec<Box<dyn Transform+Send+Sync>>,
+}
+
+impl Default for Transforms{
+    fn default()->Self{
+        Transforms::builtin()
+    }
+}
+
+impl Transforms{
+
+    pub fn builtin()->Transforms{
+        Transforms { transforms: vec![Box::new(TrimZeros), Box::new(Pad)] }
+    }
+
+    // Adds a transform, replacing any registered one with the same id or
+    // name. Built-in ids cannot be taken over.
+    pub fn register(&mut self,transform:Box<dyn Transform+Send+Sync>)->Result<(),String>{
+        if transform.id()<FIRST_CUSTOM_ID {
+            return Err(format!("Transform {} must use an id from {FIRST_CUSTOM_ID} up, not {}", transform.name(), transform.id()));
+        }
+        if Transforms::builtin().by_name(transform.name()).is_some() {
+            return Err(format!("Transform name {} is taken by a built-in transform", transform.name()));
+        }
+        self.transforms.retain(|existing| existing.id()!=transform.id() && existing.name()!=transform.name());
+        self.transforms.push(transform);
+        Ok(())
+    }
+
+    pub fn names(&self)->Vec<&str>{
+        self.transforms.iter().map(|transform| transform.name()).collect()
+    }
+
+    pub fn get(&self,id:u8)->Option<&(dyn Transform+Send+Sync)>{
+        self.transforms.iter().find(|transform| transform.id()==id).map(|transform| transform.as_ref())
+    }
+
+    pub fn by_name(&self,name:&str)->Option<&(dyn Transform+Send+Sync)>{
+        self.transforms.iter().find(|transform| transform.name()==name).map(|transform| transform.as_ref())
+    }
+
+    // The pipeline for steps given by name, in application order.
+    pub fn pipeline(&self,names:&[&str])->Result<Pipeline,String>{
+        let ids:Vec<u8>=names.iter()
+            .map(|name| self.by_name(name).map(|transform| transform.id()).ok_or(format!("Unknown transform {name}; known: {}", self.names().join(", "))))
+            .collect::<Result<_,_>>()?;
+        Pipeline::new(&ids)
+    }
+
+    pub fn describe(&self,pipeline:&Pipeline)->Vec<String>{
+        pipeline.ids().iter().map(|&id| self.get(id).map_or(format!("#{id}"), |transform| transform.name().to_string())).collect()
+    }
+
+    // Runs the steps in order; intermediate results are zeroized.
+    pub fn apply(&self,pipeline:&Pipeline,secret:&[u8])->Result<Vec<u8>,String>{
+        let steps=self.steps(pipeline)?;
+        let mut data=secret.to_vec();
+        for step in steps {
+            let next=step.apply(&data);
+            zeroize(&mut data);
+            data=next?;
+        }
+        Ok(data)
+    }
+
+    // Undoes `apply`. Every id must be known before anything is restored, so
+    // an unregistered step fails up front rather than half way.
+    pub fn restore(&self,pipeline:&Pipeline,data:Vec<u8>)->Result<Vec<u8>,String>{
+        let steps=match self.steps(pipeline) {
+            Ok(steps) => steps,
+            Err(e) => {
+                let mut data=data;
+                zeroize(&mut data);
+                return Err(e);
+            }
+        };
+        steps.into_iter().rev().try_fold(data, |data, step| step.restore(data))
+    }
+
+    fn steps(&self,pipeline:&Pipeline)->Result<Vec<&(dyn Transform+Send+Sync)>,String>{
+        pipeline.ids().iter()
+            .map(|&id| self.get(id).ok_or(format!("Shares were transformed with unknown transform {id}; register it before joining")))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Reverse(u8, &'static str);
+
+    impl Transform for Reverse{
+        fn id(&self)->u8{ self.0 }
+        fn name(&self)->&str{ self.1 }
+
+        fn apply(&self,data:&[u8])->Result<Vec<u8>,String>{
+            Ok(data.iter().rev().copied().collect())
+        }
+
+        fn restore(&self,data:Vec<u8>)->Result<Vec<u8>,String>{
+            Ok(data.into_iter().rev().collect())
+        }
+    }
+
+    #[test]
+    fn pipeline_header_round_trips() {
+        let pipeline=Pipeline::new(&[1, 2, 200]).unwrap();
+        assert_eq!(pipeline.to_header(), [1, 2, 200, 0, 0, 0]);
+        assert_eq!(Pipeline::from_header(&pipeline.to_header()).unwrap().ids(), &[1, 2, 200]);
+        assert!(Pipeline::default().is_empty());
+        assert!(Pipeline::new(&[1; 7]).is_err());
+        assert!(Pipeline::new(&[1, 0]).is_err());
+        assert!(Pipeline::from_header(&[1, 0, 2, 0, 0, 0]).is_err());
+        assert!(Pipeline::from_header(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn pad_hides_the_length() {
+        for len in [0, 1, 27, 28, 29, 100] {
+            let secret=vec![0xa5u8;len];
+            let padded=Pad.apply(&secret).unwrap();
+            assert_eq!(padded.len(), (len+4).next_power_of_two().max(32));
+            assert_eq!(Pad.restore(padded).unwrap(), secret);
+        }
+        assert!(Pad.restore(vec![0, 0, 0, 9, 1, 2]).is_err());
+        assert!(Pad.restore(vec![0, 0]).is_err());
+    }
+
+    #[test]
+    fn registered_steps_run_in_order_and_undo_in_reverse() {
+        let mut transforms=Transforms::builtin();
+        assert!(transforms.register(Box::new(Reverse(3, "reverse"))).is_err());
+        assert!(transforms.register(Box::new(Reverse(200, "pad"))).is_err());
+        transforms.register(Box::new(Reverse(200, "reverse"))).unwrap();
+
+        let pipeline=transforms.pipeline(&["trim-zeros", "reverse", "pad"]).unwrap();
+        assert_eq!(transforms.describe(&pipeline), vec!["trim-zeros", "reverse", "pad"]);
+        assert!(transforms.pipeline(&["zip"]).is_err());
+        let applied=transforms.apply(&pipeline, b"secret\0\0\0").unwrap();
+        assert_eq!(applied.len(), 32);
+        assert_eq!(transforms.restore(&pipeline, applied.clone()).unwrap(), b"secret\0\0\0");
+
+        let builtin=Transforms::default();
+        assert_eq!(builtin.describe(&pipeline), vec!["trim-zeros", "#200", "pad"]);
+        assert!(builtin.restore(&pipeline, applied).is_err());
+    }
+}
@@ -0,0 +1,285 @@
+use base64::engine::general_purpose;
+use base64::Engine as _;
+
+use crate::crypto::{Parts, ShamirSS};
+use crate::error::ShamirError;
+use crate::sha256::Sha256;
+
+// Verifiable shares: each share carries its index, the threshold, the share
+// count and a Merkle proof that its bytes are one of the n shares of one
+// split. The Merkle root names the split. `join_verifiable` checks every
+// share against the root and threshold the caller expects before
+// interpolating, and says which ones are corrupted or come from another
+// split, instead of returning garbage.
+//
+// Binary layout, version 1:
+//
+//   "SSV" | version | k | n | index | root (32) | path length | path (32 each) | data length (u32 BE) | data
+//
+// leaf = SHA-256(0x00 | "shamir-verifiable v1" | k | n | index | data), and
+// node = SHA-256(0x01 | left | right). Leaves are in index order; an odd node
+// out is carried up unchanged. Hashes, not Feldman commitments: GF(256) has
+// no group in which discrete logarithms are hard.
+//
+// A proof only shows that a share belongs to the tree its root describes.
+// Whoever rewrites a share's data, or its threshold, can rebuild its root
+// and proof too, and so passes it off as a share of some other split. The
+// root and threshold in a share are therefore never the reference: the
+// dealer publishes both at split time (`VerifiableShare::root` of any
+// share) and whoever joins passes them in.
+//
+// Text form: `sv1:<base64 of the binary form>`.
+
+const MAGIC:&[u8;3]=b"SSV";
+pub const VERIFIABLE_VERSION:u8=1;
+const LEAF_TAG:&[u8]=b"shamir-verifiable v1";
+const TEXT_PREFIX:&str="sv1:";
+const HASH_LEN:usize=32;
+
+#[derive(Debug,Clone,PartialEq,Eq)]
+pub struct VerifiableShare{
+    pub threshold:u8,
+    pub shares:u8,
+    pub index:u8,
+    pub root:[u8;32],
+    pub path:Vec<[u8;32]>,
+    pub data:Vec<u8>,
+}
+
+impl VerifiableShare{
+
+    pub fn to_bytes(&self)->Vec<u8>{
+        let mut out=Vec::with_capacity(9+HASH_LEN*(1+self.path.len())+4+self.data.len());
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&[VERIFIABLE_VERSION, self.threshold, self.shares, self.index]);
+        out.extend_from_slice(&self.root);
+        out.push(self.path.len() as u8);
+        for node in &self.path {
+            out.extend_from_slice(node);
+        }
+        out.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
+        out.extend_from_slice(&self.data);
+        out
+    }
+
+    pub fn from_bytes(bytes:&[u8])->Result<VerifiableShare,String>{
+        let rest=bytes.strip_prefix(MAGIC.as_slice()).ok_or("Not a verifiable share")?;
+        let (header, rest)=take(rest, 4)?;
+        if header[0]!=VERIFIABLE_VERSION {
+            return Err(format!("Verifiable share version {} is not supported", header[0]));
+        }
+        let (root, rest)=take(rest, HASH_LEN)?;
+        let (count, mut rest)=take(rest, 1)?;
+        let mut path=Vec::with_capacity(count[0] as usize);
+        for _ in 0..count[0] {
+            let (node, tail)=take(rest, HASH_LEN)?;
+            path.push(hash(node));
+            rest=tail;
+        }
+        let (len, rest)=take(rest, 4)?;
+        let len=u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+        let (data, rest)=take(rest, len)?;
+        if !rest.is_empty() {
+            return Err("Trailing bytes after the verifiable share".to_string());
+        }
+        Ok(VerifiableShare { threshold: header[1], shares: header[2], index: header[3], root: hash(root), path, data: data.to_vec() })
+    }
+
+    pub fn to_text(&self)->String{
+        format!("{TEXT_PREFIX}{}\n", general_purpose::STANDARD.encode(self.to_bytes()))
+    }
+
+    pub fn parse(text:&str)->Result<VerifiableShare,String>{
+        let payload=text.trim().strip_prefix(TEXT_PREFIX).ok_or("Not a verifiable share")?;
+        let bytes=general_purpose::STANDARD.decode(payload).map_err(|e| format!("Malformed verifiable share: {e}"))?;
+        Self::from_bytes(&bytes)
+    }
+
+    // Whether the header is sane and the proof leads to the share's root.
+    pub fn is_intact(&self)->bool{
+        let (index, n)=(self.index as usize, self.shares as usize);
+        if self.threshold<2 || self.threshold>self.shares || index==0 || index>n || self.path.len()!=path_len(index-1, n) {
+            return false;
+        }
+        let mut node=self.leaf();
+        let (mut position, mut width, mut path)=(index-1, n, self.path.iter());
+        while width>1 {
+            let has_sibling=position%2==1 || position+1<width;
+            if has_sibling {
+                let Some(sibling)=path.next() else { return false };
+                node=if position%2==0 { parent(&node, sibling) } else { parent(sibling, &node) };
+            }
+            position/=2;
+            width=width.div_ceil(2);
+        }
+        node==self.root
+    }
+
+    fn leaf(&self)->[u8;32]{
+        leaf(self.threshold, self.shares, self.index, &self.data)
+    }
+}
+
+impl ShamirSS{
+
+    // Like `split`, with every share carrying its metadata and a proof of
+    // membership in this split.
+    pub fn split_verifiable(n:i32,k:i32,secret:Vec<u8>)->Result<Vec<VerifiableShare>,ShamirError>{
+        let parts=Self::split(n, k, secret)?;
+        let (k, n)=(k as u8, n as u8);
+        let levels=tree(parts.iter().map(|(index, data)| leaf(k, n, *index as u8, data)).collect());
+        let root=levels.last().and_then(|level| level.first()).copied().unwrap_or_default();
+        Ok(parts.into_iter().map(|(index, data)| VerifiableShare {
+            threshold: k,
+            shares: n,
+            index: index as u8,
+            root,
+            path: proof(&levels, index as usize-1),
+            data,
+        }).collect())
+    }
+
+    // Checks every share against the split's published `root` and
+    // `threshold`, then joins them. Corrupted shares, and shares recording
+    // another threshold, fail with `InvalidShares`, shares of another split
+    // with `ForeignShares`, and fewer than `threshold` shares with
+    // `InsufficientParts`; nothing is interpolated until all checks pass.
+    pub fn join_verifiable(shares:&[VerifiableShare],root:&[u8;32],threshold:u8)->Result<Vec<u8>,ShamirError>{
+        if shares.is_empty() {
+            return Err(ShamirError::NoParts);
+        }
+        let invalid:Vec<i32>=shares.iter().filter(|s| !s.is_intact() || s.threshold!=threshold).map(|s| s.index as i32).collect();
+        if !invalid.is_empty() {
+            return Err(ShamirError::InvalidShares(invalid));
+        }
+        let foreign:Vec<i32>=shares.iter().filter(|s| s.root!=*root).map(|s| s.index as i32).collect();
+        if !foreign.is_empty() {
+            return Err(ShamirError::ForeignShares(foreign));
+        }
+        let mut parts=Parts::new();
+        for share in shares {
+            if parts.insert(share.index as i32, share.data.clone()).is_some() {
+                return Err(ShamirError::DuplicateIndex(share.index as i32));
+            }
+        }
+        let threshold=threshold as usize;
+        if parts.len()<threshold {
+            return Err(ShamirError::InsufficientParts { collected: parts.len(), threshold });
+        }
+        Self::join(parts)
+    }
+}
+
+fn leaf(k:u8,n:u8,index:u8,data:&[u8])->[u8;32]{
+    let mut h=Sha256::new();
+    h.update(&[0]);
+    h.update(LEAF_TAG);
+    h.update(&[k, n, index]);
+    h.update(data);
+    h.finalize()
+}
+
+fn parent(left:&[u8;32],right:&[u8;32])->[u8;32]{
+    let mut h=Sha256::new();
+    h.update(&[1]);
+    h.update(left);
+    h.update(right);
+    h.finalize()
+}
+
+// Every level of the tree, leaves first, root last.
+fn tree(leaves:Vec<[u8;32]>)->Vec<Vec<[u8;32]>>{
+    let mut levels=vec![leaves];
+    while let Some(level)=levels.last().filter(|level| level.len()>1) {
+        let next=level.chunks(2).map(|pair| if let [left, right]=pair { parent(left, right) } else { pair[0] }).collect();
+        levels.push(next);
+    }
+    levels
+}
+
+fn proof(levels:&[Vec<[u8;32]>],mut position:usize)->Vec<[u8;32]>{
+    let mut path=Vec::new();
+    for level in &levels[..levels.len()-1] {
+        if let Some(sibling)=level.get(position^1) {
+            path.push(*sibling);
+        }
+        position/=2;
+    }
+    path
+}
+
+// Siblings on the way up from `position` in a tree of `width` leaves.
+fn path_len(mut position:usize,mut width:usize)->usize{
+    let mut len=0;
+    while width>1 {
+        if position%2==1 || position+1<width {
+            len+=1;
+        }
+        position/=2;
+        width=width.div_ceil(2);
+    }
+    len
+}
+
+fn hash(bytes:&[u8])->[u8;32]{
+    let mut out=[0u8;32];
+    out.copy_from_slice(bytes);
+    out
+}
+
+fn take(bytes:&[u8],len:usize)->Result<(&[u8],&[u8]),String>{
+    if bytes.len()<len {
+        return Err("Verifiable share is truncated".to_string());
+    }
+    Ok(bytes.split_at(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn joins_against_the_published_root_and_threshold() {
+        let shares=ShamirSS::split_verifiable(5, 3, b"verifiable".to_vec()).unwrap();
+        let root=shares[0].root;
+        assert_eq!(ShamirSS::join_verifiable(&shares[1..4], &root, 3).unwrap(), b"verifiable");
+        let parsed:Vec<VerifiableShare>=shares.iter().map(|s| VerifiableShare::parse(&s.to_text()).unwrap()).collect();
+        assert_eq!(parsed, shares);
+        assert_eq!(ShamirSS::join_verifiable(&shares[..2], &root, 3), Err(ShamirError::InsufficientParts { collected: 2, threshold: 3 }));
+    }
+
+    #[test]
+    fn rejects_a_tampered_proof() {
+        let mut shares=ShamirSS::split_verifiable(5, 3, b"verifiable".to_vec()).unwrap();
+        let root=shares[0].root;
+        shares[1].path[0][0]^=1;
+        shares[2].data[0]^=1;
+        assert_eq!(ShamirSS::join_verifiable(&shares[..3], &root, 3), Err(ShamirError::InvalidShares(vec![2, 3])));
+    }
+
+    // The reference is the caller's root, not the root most shares carry.
+    #[test]
+    fn rejects_shares_of_another_split() {
+        let ours=ShamirSS::split_verifiable(5, 3, b"verifiable".to_vec()).unwrap();
+        let theirs=ShamirSS::split_verifiable(5, 3, b"impostor!!".to_vec()).unwrap();
+        let mixed=[ours[0].clone(), theirs[1].clone(), theirs[2].clone()];
+        assert_eq!(ShamirSS::join_verifiable(&mixed, &ours[0].root, 3), Err(ShamirError::ForeignShares(vec![2, 3])));
+        assert_eq!(ShamirSS::join_verifiable(&theirs[..3], &ours[0].root, 3), Err(ShamirError::ForeignShares(vec![1, 2, 3])));
+    }
+
+    // Shares cannot lower the threshold the caller expects: editing it breaks
+    // the proof, and rebuilding the proof changes the root.
+    #[test]
+    fn rejects_a_lowered_threshold() {
+        let shares=ShamirSS::split_verifiable(5, 3, b"verifiable".to_vec()).unwrap();
+        let root=shares[0].root;
+        let mut edited=shares[..2].to_vec();
+        for share in &mut edited {
+            share.threshold=2;
+        }
+        assert_eq!(ShamirSS::join_verifiable(&edited, &root, 3), Err(ShamirError::InvalidShares(vec![1, 2])));
+        let rebuilt=ShamirSS::split_verifiable(5, 2, b"verifiable".to_vec()).unwrap();
+        assert_eq!(ShamirSS::join_verifiable(&rebuilt[..2], &root, 3), Err(ShamirError::InvalidShares(vec![1, 2])));
+        assert_eq!(ShamirSS::join_verifiable(&rebuilt[..2], &rebuilt[0].root, 3), Err(ShamirError::InvalidShares(vec![1, 2])));
+    }
+}
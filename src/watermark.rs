@@ -0,0 +1,75 @@
+use rand::RngCore;
+
+use crate::encoding::encode_share;
+use crate::sha256::{from_hex, to_hex, Sha256};
+use crate::transcript::{CustodianRecord, Transcript};
+
+// Leak tracing. Each custodian gets a random nonce that only the dealer's
+// transcript and that custodian's share file know about; the file carries
+// `watermark <tag>`, where tag = HMAC-SHA256(nonce, share text) truncated to
+// 16 bytes. The tag is metadata beside the share, so reconstruction never
+// sees it. A custodian can strip their own tag but cannot produce another
+// custodian's, so a leaked share that still carries a tag names its holder.
+//
+// Shares with a watermark are written as bundles, whose readers skip lines
+// they do not know, so tools that predate watermarks still decode them.
+
+pub const WATERMARK_NONCE_LEN:usize=16;
+const TAG_LEN:usize=16;
+const LINE_PREFIX:&str="watermark ";
+
+pub fn new_watermark_nonce()->String{
+    let mut nonce=[0u8;WATERMARK_NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    to_hex(&nonce)
+}
+
+pub fn watermark_tag(nonce:&str,index:i32,data:&[u8])->Result<String,String>{
+    let key=from_hex(nonce).ok_or("Malformed watermark nonce")?;
+    Ok(to_hex(&Sha256::hmac(&key, encode_share(index, data).as_bytes())[..TAG_LEN]))
+}
+
+pub fn watermark_line(tag:&str)->String{
+    format!("{LINE_PREFIX}{tag}\n")
+}
+
+// The tag in a bundle's `watermark` line, if it has one.
+pub fn read_watermark(bundle:&str)->Option<&str>{
+    bundle.lines().find_map(|line| line.trim().strip_prefix(LINE_PREFIX)).map(str::trim)
+}
+
+// The custodian the share was issued to, if `tag` matches one of the
+// transcript's watermark nonces for this share.
+pub fn trace<'a>(transcript:&'a Transcript,index:i32,data:&[u8],tag:&str)->Option<&'a CustodianRecord>{
+    transcript.custodians.iter().find(|c| {
+        c.watermark.as_deref().is_some_and(|nonce| watermark_tag(nonce, index, data).is_ok_and(|expected| expected==tag))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::ShamirSS;
+
+    #[test]
+    fn leaked_share_names_its_custodian() {
+        let parts=ShamirSS::split(3, 2, b"secret".to_vec()).unwrap();
+        let mut transcript=Transcript { threshold: 2, shares: 3, ..Transcript::default() };
+        for index in parts.keys() {
+            transcript.custodians.push(CustodianRecord { index: *index, label: format!("custodian {index}"), watermark: Some(new_watermark_nonce()), ..CustodianRecord::default() });
+        }
+        let nonce=transcript.custodians[1].watermark.clone().unwrap();
+        let tag=watermark_tag(&nonce, 2, &parts[&2]).unwrap();
+        let bundle=format!("{}{}", encode_share(2, &parts[&2]), watermark_line(&tag));
+        assert_eq!(read_watermark(&bundle), Some(tag.as_str()));
+        assert_eq!(trace(&transcript, 2, &parts[&2], &tag).unwrap().label, "custodian 2");
+
+        // The tag is bound to the share it was issued with.
+        assert!(trace(&transcript, 3, &parts[&3], &tag).is_none());
+        assert!(trace(&transcript, 2, &parts[&1], &tag).is_none());
+        let forged=watermark_tag(&new_watermark_nonce(), 2, &parts[&2]).unwrap();
+        assert!(trace(&transcript, 2, &parts[&2], &forged).is_none());
+        assert!(read_watermark(&encode_share(2, &parts[&2])).is_none());
+        assert!(watermark_tag("not hex", 2, &parts[&2]).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+// Drives age-plugin-shamir through both age plugin state machines from the
+// client side, as age itself does.
+use std::fs;
+
+use age_core::format::Stanza;
+use age_core::plugin::Connection;
+use shamir_rust::age_plugin::STANZA_TAG;
+
+const PLUGIN:&str=env!("CARGO_BIN_EXE_age-plugin-shamir");
+const FILE_KEY:[u8;16]=[0x5a;16];
+
+fn wrap_to(recipient:&str)->Vec<Stanza>{
+    let mut conn=Connection::open(PLUGIN.as_ref(), "recipient-v1").unwrap();
+    conn.unidir_send(|mut phase| {
+        phase.send("add-recipient", &[recipient], &[])?;
+        phase.send("wrap-file-key", &[], &FILE_KEY)
+    }).unwrap();
+    let mut stanzas=Vec::new();
+    conn.bidir_receive(&["recipient-stanza", "error"], |mut command, reply| {
+        assert_eq!(command.tag, "recipient-stanza", "{}", String::from_utf8_lossy(&command.body));
+        assert_eq!(command.args.remove(0), "0");
+        command.tag=command.args.remove(0);
+        stanzas.push(command);
+        reply.ok(None)
+    }).unwrap();
+    stanzas
+}
+
+// The file key, and the prompts answered with `typed` one per request.
+fn unwrap_with(identity:&str,stanzas:&[Stanza],typed:&[String])->(Option<Vec<u8>>,Vec<String>){
+    let mut conn=Connection::open(PLUGIN.as_ref(), "identity-v1").unwrap();
+    conn.unidir_send(|mut phase| {
+        phase.send("add-identity", &[identity], &[])?;
+        for stanza in stanzas {
+            phase.send_stanza("recipient-stanza", &["0"], stanza)?;
+        }
+        Ok(())
+    }).unwrap();
+    let (mut file_key, mut errors)=(None, Vec::new());
+    let mut typed=typed.iter();
+    conn.bidir_receive(&["msg", "request-secret", "file-key", "error"], |command, reply| match command.tag.as_str() {
+        "request-secret" => match typed.next() {
+            Some(share) => reply.ok(Some(share.as_bytes())),
+            None => reply.fail(),
+        },
+        "file-key" => {
+            file_key=Some(command.body);
+            reply.ok(None)
+        }
+        _ => {
+            errors.push(String::from_utf8_lossy(&command.body).to_string());
+            reply.ok(None)
+        }
+    }).unwrap();
+    (file_key, errors)
+}
+
+#[test]
+fn encrypts_to_the_recipient_and_decrypts_with_k_shares() {
+    let dir=std::env::temp_dir().join(format!("shamir-age-plugin-{}", std::process::id()));
+    let _=fs::remove_dir_all(&dir);
+    let output=std::process::Command::new(PLUGIN)
+        .args(["--generate", "--threshold", "2", "--shares", "3", "--out"]).arg(&dir)
+        .output().unwrap();
+    assert!(output.status.success());
+    let stdout=String::from_utf8(output.stdout).unwrap();
+    let identity=stdout.lines().find(|l| l.starts_with("AGE-PLUGIN-SHAMIR-1")).unwrap();
+    let recipient=stdout.lines().find_map(|l| l.strip_prefix("# recipient: ")).unwrap();
+
+    let stanzas=wrap_to(recipient);
+    assert_eq!(stanzas.len(), 1);
+    assert_eq!(stanzas[0].tag, STANZA_TAG);
+
+    // Shares from the environment, then typed at the prompt.
+    std::env::set_var("AGE_PLUGIN_SHAMIR_SHARES", dir.join("share-1.txt"));
+    let share=|i:usize| fs::read_to_string(dir.join(format!("share-{i}.txt"))).unwrap();
+    let typed_line=share(3).lines().find_map(|l| l.strip_prefix("bech32 ")).unwrap().to_string();
+    let (file_key, errors)=unwrap_with(identity, &stanzas, &["not a share".to_string(), typed_line]);
+    assert_eq!(file_key.as_deref(), Some(&FILE_KEY[..]), "{errors:?}");
+
+    // Too few shares: the user cancels the prompt.
+    let (file_key, errors)=unwrap_with(identity, &stanzas, &[]);
+    assert!(file_key.is_none());
+    assert_eq!(errors.len(), 1);
+
+    // Stanzas for other recipients are left alone, without prompting.
+    std::env::remove_var("AGE_PLUGIN_SHAMIR_SHARES");
+    let foreign=Stanza { tag: "X25519".to_string(), args: vec!["AAAA".to_string()], body: vec![0;32] };
+    assert_eq!(unwrap_with(identity, &[foreign], &[]), (None, Vec::new()));
+    fs::remove_dir_all(&dir).unwrap();
+}
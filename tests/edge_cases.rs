@@ -0,0 +1,90 @@
+// Adversarial and boundary inputs that must fail with a typed error rather
+// than panic, plus the largest configurations the field allows.
+use std::collections::BTreeMap;
+
+use shamir_rust::crypto::ShamirSS;
+use shamir_rust::error::ShamirError;
+use shamir_rust::join::JoinOptions;
+
+fn parts(shares:&[(i32,&[u8])])->BTreeMap<i32,Vec<u8>>{
+    shares.iter().map(|(x, y)| (*x, y.to_vec())).collect()
+}
+
+#[test]
+fn empty_secrets_and_shares() {
+    assert_eq!(ShamirSS::split(3, 2, Vec::new()), Err(ShamirError::EmptySecret));
+    assert_eq!(ShamirSS::split_at(&[1, 2], 2, Vec::new()), Err(ShamirError::EmptySecret));
+    assert_eq!(ShamirSS::join(parts(&[(1, &[]), (2, &[])])), Err(ShamirError::EmptySecret));
+    let threshold=JoinOptions { threshold: Some(2), ..Default::default() };
+    assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[]), (2, &[]), (3, &[])]), &threshold), Err(ShamirError::EmptySecret));
+}
+
+// A share longer (or shorter) than the rest, including an empty one, on
+// both interpolation paths and on the stack-only path.
+#[test]
+fn shares_of_different_lengths() {
+    for cutoff in [2, usize::MAX] {
+        for small_secret_limit in [0, 32] {
+            let options=JoinOptions { barycentric_cutoff: cutoff, small_secret_limit, ..Default::default() };
+            assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[]), (2, &[1, 2])]), &options), Err(ShamirError::InconsistentLengths));
+            assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[1, 2, 3]), (2, &[1, 2])]), &options), Err(ShamirError::InconsistentLengths));
+        }
+    }
+    assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[]), (2, &[1]), (3, &[1])]), &JoinOptions::default()), Err(ShamirError::InconsistentLengths));
+    let threshold=JoinOptions { threshold: Some(2), ..Default::default() };
+    assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[1]), (2, &[1]), (3, &[1, 2])]), &threshold), Err(ShamirError::InconsistentLengths));
+}
+
+// Index 0 is the secret itself; indices beyond the field do not exist.
+#[test]
+fn indices_outside_the_field() {
+    assert_eq!(ShamirSS::split_at(&[0, 1], 2, vec![1]), Err(ShamirError::InvalidIndex(0)));
+    assert_eq!(ShamirSS::join(parts(&[(0, &[1]), (2, &[1])])), Err(ShamirError::InvalidIndex(0)));
+    assert_eq!(ShamirSS::join(parts(&[(256, &[1]), (2, &[1])])), Err(ShamirError::InvalidIndex(256)));
+    assert_eq!(ShamirSS::join(parts(&[(-1, &[1]), (2, &[1])])), Err(ShamirError::InvalidIndex(-1)));
+    assert_eq!(ShamirSS::join_pairs(&[(0, &[1][..]), (1, &[2][..])]), Err(ShamirError::InvalidIndex(0)));
+    let threshold=JoinOptions { threshold: Some(2), ..Default::default() };
+    assert_eq!(ShamirSS::join_with_options(parts(&[(0, &[1]), (2, &[1])]), &threshold), Err(ShamirError::InvalidIndex(0)));
+}
+
+// Duplicate x-coordinates would divide by zero in the Lagrange weights.
+#[test]
+fn duplicate_indices() {
+    assert_eq!(ShamirSS::split_at(&[3, 3], 2, vec![1]), Err(ShamirError::DuplicateIndex(3)));
+    assert_eq!(ShamirSS::join_pairs(&[(1, &[1][..]), (1, &[2][..])]), Err(ShamirError::DuplicateIndex(1)));
+    let (xs, shares)=([5u8, 5], [[1u8;4], [2u8;4]]);
+    assert_eq!(ShamirSS::join_in_place(&xs, &shares, &mut [0u8;4]), Err(ShamirError::DuplicateIndex(5)));
+}
+
+#[test]
+fn parameter_bounds() {
+    assert_eq!(ShamirSS::split(256, 2, vec![1]), Err(ShamirError::TooManyShares));
+    assert_eq!(ShamirSS::split(255, 256, vec![1]), Err(ShamirError::ThresholdAboveShares));
+    assert_eq!(ShamirSS::split(-1, -5, vec![1]), Err(ShamirError::ThresholdTooSmall));
+    assert_eq!(ShamirSS::split(3, 1, vec![1]), Err(ShamirError::ThresholdTooSmall));
+    assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[1]), (2, &[1])]), &JoinOptions { threshold: Some(300), ..Default::default() }),
+        Err(ShamirError::InsufficientParts { collected: 2, threshold: 300 }));
+    assert_eq!(ShamirSS::join_with_options(parts(&[(1, &[1]), (2, &[1])]), &JoinOptions { threshold: Some(1), ..Default::default() }),
+        Err(ShamirError::ThresholdTooSmall));
+    assert_eq!(ShamirSS::join(BTreeMap::new()), Err(ShamirError::NoParts));
+}
+
+// Maximal configurations: every x-coordinate in use, up to k == n == 255.
+#[test]
+fn maximal_configurations() {
+    let secret=b"edge".to_vec();
+    for k in [2, 128, 255] {
+        let all=ShamirSS::split(255, k, secret.clone()).unwrap();
+        assert_eq!(all.len(), 255);
+        let quorum:BTreeMap<i32,Vec<u8>>=all.iter().rev().take(k as usize).map(|(x, y)| (*x, y.clone())).collect();
+        assert_eq!(ShamirSS::join(quorum).unwrap(), secret);
+        assert_eq!(ShamirSS::join_with_options(all, &JoinOptions { threshold: Some(k), ..Default::default() }).unwrap(), secret);
+    }
+    let mut fixed=[7u8;8];
+    let mut out=[[0u8;8];255];
+    ShamirSS::split_in_place(255, 255, &mut fixed, &mut out).unwrap();
+    let xs:Vec<u8>=(1..=255).collect();
+    let mut joined=[0u8;8];
+    ShamirSS::join_in_place(&xs, &out, &mut joined).unwrap();
+    assert_eq!(joined, [7u8;8]);
+}
@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use shamir_rust::crypto::ShamirSS;
+use shamir_rust::encoding::{decode_compact, decode_share, encode_compact};
+use shamir_rust::formats::{convert, CBOR, RAW};
+use shamir_rust::transform::Transforms;
+
+// Format stability: `tests/golden/` holds shares written by each released
+// format version, one directory per format and version, all from the same
+// 2-of-3 split of `secret.bin`. The files are never regenerated. When a
+// format changes, its new version gets a new directory next to the old one,
+// and the old files must keep parsing and joining. Each file must also be
+// exactly what the current encoder writes for that share, so a writer change
+// is caught here too; relax that per directory only once a format has moved
+// on to a newer version. Run with `cargo test --test golden_formats`.
+
+const GOLDEN:&str="tests/golden";
+
+// Directory, encoding name for `formats::convert`.
+const FORMATS:&[(&str,&str)]=&[
+    ("text-v1", "text"),
+    ("bundle-v1", "bundle"),
+    ("plate-v1", "plate"),
+    ("compact-v1", "compact"),
+    ("json-v1", "json"),
+    ("bech32-v1", "bech32"),
+    ("hex-v1", "hex"),
+    ("base32-v1", "base32"),
+    ("raw-v1", RAW),
+    ("cbor-v1", CBOR),
+];
+
+fn read_dir(dir:&Path)->Vec<Vec<u8>>{
+    let mut files:Vec<_>=fs::read_dir(dir).unwrap_or_else(|e| panic!("{}: {e}", dir.display()))
+        .map(|entry| entry.unwrap().path())
+        .collect();
+    files.sort();
+    assert!(files.len()>=2, "{}: fewer shares than the threshold", dir.display());
+    files.iter().map(|path| fs::read(path).unwrap()).collect()
+}
+
+// Every pair joins back to the secret.
+fn check_join(dir:&str,parts:&BTreeMap<i32,Vec<u8>>,secret:&[u8],restore:&dyn Fn(Vec<u8>)->Vec<u8>){
+    for (a, b) in parts.keys().flat_map(|a| parts.keys().filter(move |b| a<*b).map(move |b| (*a, *b))) {
+        let pair:BTreeMap<i32,Vec<u8>>=[(a, parts[&a].clone()), (b, parts[&b].clone())].into();
+        assert_eq!(restore(ShamirSS::join(pair).unwrap()), secret, "{dir}: shares {a} and {b} do not join to the secret");
+    }
+}
+
+fn golden_root()->PathBuf{
+    Path::new(env!("CARGO_MANIFEST_DIR")).join(GOLDEN)
+}
+
+#[test]
+fn every_format_parses_re_encodes_and_joins() {
+    let root=golden_root();
+    let secret=fs::read(root.join("secret.bin")).unwrap();
+    for &(dir, name) in FORMATS {
+        let mut parts=BTreeMap::new();
+        for file in read_dir(&root.join(dir)) {
+            let text=convert(&file, name, "text").unwrap_or_else(|e| panic!("{dir}: {e}"));
+            let (index, data)=decode_share(&String::from_utf8(text.clone()).unwrap()).unwrap();
+            assert_eq!(convert(&text, "text", name).unwrap(), file, "{dir}: share {index} is no longer written the same way");
+            parts.insert(index, data);
+        }
+        check_join(dir, &parts, &secret, &|joined| joined);
+    }
+}
+
+// The binary compact frame, with a `pad` transform recorded in its header.
+#[test]
+fn compact_binary_frame_parses_re_encodes_and_joins() {
+    let root=golden_root();
+    let secret=fs::read(root.join("secret.bin")).unwrap();
+    let transforms=Transforms::builtin();
+    let mut parts=BTreeMap::new();
+    let mut pipeline=None;
+    for file in read_dir(&root.join("compact-binary-v1")) {
+        let (index, data, recorded)=decode_compact(&file).unwrap_or_else(|e| panic!("compact-binary-v1: {e}"));
+        assert_eq!(encode_compact(index, &data, recorded), file, "compact-binary-v1: share {index} is no longer written the same way");
+        assert!(pipeline.is_none_or(|p| p==recorded), "compact-binary-v1: shares disagree on their transforms");
+        pipeline=Some(recorded);
+        parts.insert(index, data);
+    }
+    let pipeline=pipeline.unwrap();
+    assert_eq!(transforms.describe(&pipeline), ["pad"]);
+    check_join("compact-binary-v1", &parts, &secret, &|joined| transforms.restore(&pipeline, joined).unwrap());
+}